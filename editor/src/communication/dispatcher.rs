@@ -11,17 +11,18 @@ use std::collections::VecDeque;
 pub struct Dispatcher {
 	message_queue: VecDeque<Message>,
 	pub responses: Vec<FrontendMessage>,
-	message_handlers: DispatcherMessageHandlers,
+	pub(crate) message_handlers: DispatcherMessageHandlers,
+	frame_ticks_requested: bool,
 }
 
 #[remain::sorted]
 #[derive(Debug, Default)]
-struct DispatcherMessageHandlers {
+pub(crate) struct DispatcherMessageHandlers {
 	global_message_handler: GlobalMessageHandler,
 	input_mapper_message_handler: InputMapperMessageHandler,
 	input_preprocessor_message_handler: InputPreprocessorMessageHandler,
 	layout_message_handler: LayoutMessageHandler,
-	portfolio_message_handler: PortfolioMessageHandler,
+	pub(crate) portfolio_message_handler: PortfolioMessageHandler,
 	tool_message_handler: ToolMessageHandler,
 }
 
@@ -97,6 +98,17 @@ impl Dispatcher {
 				}
 			}
 		}
+
+		self.update_frame_ticks_requested();
+	}
+
+	/// Lets the frontend know when it should start or stop sending frame ticks, based on whether any handler is currently animating.
+	fn update_frame_ticks_requested(&mut self) {
+		let frame_ticks_requested = self.message_handlers.input_mapper_message_handler.frame_ticks_requested(self.collect_actions());
+		if frame_ticks_requested != self.frame_ticks_requested {
+			self.frame_ticks_requested = frame_ticks_requested;
+			self.responses.push(FrontendMessage::UpdateFrameTicksRequested { requested: frame_ticks_requested });
+		}
 	}
 
 	pub fn collect_actions(&self) -> ActionList {
@@ -116,7 +128,7 @@ impl Dispatcher {
 		if log::max_level() == log::LevelFilter::Trace
 			&& !(matches!(
 				message,
-				InputPreprocessor(_) | Frontend(FrontendMessage::UpdateCanvasZoom { .. }) | Frontend(FrontendMessage::UpdateCanvasRotation { .. })
+				InputPreprocessor(_) | InputMapper(InputMapperMessage::FrameTick) | Frontend(FrontendMessage::UpdateCanvasZoom { .. }) | Frontend(FrontendMessage::UpdateCanvasRotation { .. })
 			) || MessageDiscriminant::from(message).local_name().ends_with("PointerMove"))
 		{
 			log::trace!("Message: {:?}", message);
@@ -129,14 +141,19 @@ impl Dispatcher {
 mod test {
 	use crate::communication::set_uuid_seed;
 	use crate::document::clipboards::Clipboard;
+	use crate::document::utility_types::{AlignAxis, TransformInputMode};
 	use crate::document::DocumentMessageHandler;
 	use crate::message_prelude::*;
 	use crate::misc::test_utils::EditorTestUtils;
+	use crate::viewport_tools::tool::ToolType;
 	use crate::Editor;
 
 	use graphene::color::Color;
+	use graphene::layers::style::{PathStyle, Stroke};
 	use graphene::Operation;
 
+	use glam::{DAffine2, DVec2};
+
 	fn init_logger() {
 		let _ = env_logger::builder().is_test(true).try_init();
 	}
@@ -168,14 +185,14 @@ mod test {
 		init_logger();
 		let mut editor = create_editor_with_three_layers();
 
-		let document_before_copy = editor.dispatcher.message_handlers.portfolio_message_handler.active_document().graphene_document.clone();
+		let document_before_copy = editor.active_document().graphene_document.clone();
 		editor.handle_message(PortfolioMessage::Copy { clipboard: Clipboard::User });
 		editor.handle_message(PortfolioMessage::PasteIntoFolder {
 			clipboard: Clipboard::User,
 			folder_path: vec![],
 			insert_index: -1,
 		});
-		let document_after_copy = editor.dispatcher.message_handlers.portfolio_message_handler.active_document().graphene_document.clone();
+		let document_after_copy = editor.active_document().graphene_document.clone();
 
 		let layers_before_copy = document_before_copy.root.as_folder().unwrap().layers();
 		let layers_after_copy = document_after_copy.root.as_folder().unwrap().layers();
@@ -202,7 +219,7 @@ mod test {
 		init_logger();
 		let mut editor = create_editor_with_three_layers();
 
-		let document_before_copy = editor.dispatcher.message_handlers.portfolio_message_handler.active_document().graphene_document.clone();
+		let document_before_copy = editor.active_document().graphene_document.clone();
 		let shape_id = document_before_copy.root.as_folder().unwrap().layer_ids[1];
 
 		editor.handle_message(DocumentMessage::SetSelectedLayers {
@@ -215,7 +232,7 @@ mod test {
 			insert_index: -1,
 		});
 
-		let document_after_copy = editor.dispatcher.message_handlers.portfolio_message_handler.active_document().graphene_document.clone();
+		let document_after_copy = editor.active_document().graphene_document.clone();
 
 		let layers_before_copy = document_before_copy.root.as_folder().unwrap().layers();
 		let layers_after_copy = document_after_copy.root.as_folder().unwrap().layers();
@@ -247,7 +264,7 @@ mod test {
 
 		editor.handle_message(DocumentMessage::CreateEmptyFolder { container_path: vec![] });
 
-		let document_before_added_shapes = editor.dispatcher.message_handlers.portfolio_message_handler.active_document().graphene_document.clone();
+		let document_before_added_shapes = editor.active_document().graphene_document.clone();
 		let folder_id = document_before_added_shapes.root.as_folder().unwrap().layer_ids[FOLDER_INDEX];
 
 		// TODO: This adding of a Line and Pen should be rewritten using the corresponding functions in EditorTestUtils.
@@ -265,13 +282,14 @@ mod test {
 			transform: [0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
 			style: Default::default(),
 			points: vec![(10.0, 20.0), (30.0, 40.0)],
+			closed: false,
 		});
 
 		editor.handle_message(DocumentMessage::SetSelectedLayers {
 			replacement_selected_layers: vec![vec![folder_id]],
 		});
 
-		let document_before_copy = editor.dispatcher.message_handlers.portfolio_message_handler.active_document().graphene_document.clone();
+		let document_before_copy = editor.active_document().graphene_document.clone();
 
 		editor.handle_message(PortfolioMessage::Copy { clipboard: Clipboard::User });
 		editor.handle_message(DocumentMessage::DeleteSelectedLayers);
@@ -286,7 +304,7 @@ mod test {
 			insert_index: -1,
 		});
 
-		let document_after_copy = editor.dispatcher.message_handlers.portfolio_message_handler.active_document().graphene_document.clone();
+		let document_after_copy = editor.active_document().graphene_document.clone();
 
 		let layers_before_copy = document_before_copy.root.as_folder().unwrap().layers();
 		let layers_after_copy = document_after_copy.root.as_folder().unwrap().layers();
@@ -323,96 +341,3857 @@ mod test {
 
 	#[test]
 	/// - create rect, shape and ellipse
-	/// - select ellipse and rect
-	/// - copy
-	/// - delete
-	/// - create another rect
-	/// - paste
-	/// - paste
-	fn copy_paste_deleted_layers() {
+	/// - create a folder and enter it
+	/// - select ellipse, copy and paste
+	/// - draw another rect
+	/// - assert that the pasted ellipse and the new rect were inserted into the entered folder
+	fn paste_and_draw_into_entered_folder() {
 		init_logger();
 		let mut editor = create_editor_with_three_layers();
 
-		const ELLIPSE_INDEX: usize = 2;
-		const SHAPE_INDEX: usize = 1;
-		const RECT_INDEX: usize = 0;
+		editor.handle_message(DocumentMessage::CreateEmptyFolder { container_path: vec![] });
 
-		let document_before_copy = editor.dispatcher.message_handlers.portfolio_message_handler.active_document().graphene_document.clone();
-		let rect_id = document_before_copy.root.as_folder().unwrap().layer_ids[RECT_INDEX];
-		let ellipse_id = document_before_copy.root.as_folder().unwrap().layer_ids[ELLIPSE_INDEX];
+		let document_before_paste = editor.active_document().graphene_document.clone();
+		let root_layer_ids = &document_before_paste.root.as_folder().unwrap().layer_ids;
+		let (ellipse_id, folder_id) = (root_layer_ids[2], root_layer_ids[3]);
 
+		editor.handle_message(DocumentMessage::EnterFolder { folder_path: vec![folder_id] });
 		editor.handle_message(DocumentMessage::SetSelectedLayers {
-			replacement_selected_layers: vec![vec![rect_id], vec![ellipse_id]],
+			replacement_selected_layers: vec![vec![ellipse_id]],
 		});
 		editor.handle_message(PortfolioMessage::Copy { clipboard: Clipboard::User });
-		editor.handle_message(DocumentMessage::DeleteSelectedLayers);
-		editor.draw_rect(0., 800., 12., 200.);
-		editor.handle_message(PortfolioMessage::PasteIntoFolder {
-			clipboard: Clipboard::User,
-			folder_path: vec![],
-			insert_index: -1,
+		editor.handle_message(PortfolioMessage::Paste { clipboard: Clipboard::User });
+		editor.draw_rect(0., 0., 100., 100.);
+
+		let document_after_paste = editor.active_document().graphene_document.clone();
+
+		// Nothing was added to the root
+		assert_eq!(document_after_paste.root.as_folder().unwrap().layer_ids.len(), 4);
+
+		let folder_layer_ids = &document_after_paste.folder(&[folder_id]).unwrap().layer_ids;
+		assert_eq!(folder_layer_ids.len(), 2);
+
+		let pasted_ellipse = document_after_paste.layer(&[folder_id, folder_layer_ids[0]]).unwrap();
+		assert_eq!(pasted_ellipse, document_before_paste.layer(&[ellipse_id]).unwrap());
+		assert!(document_after_paste.layer(&[folder_id, folder_layer_ids[1]]).is_ok());
+	}
+
+	#[test]
+	/// - start drawing a rect, then switch to the select tool in the middle of the drag
+	/// - assert that the rect is discarded and the select tool's cursor is shown rather than the rect tool's
+	/// - activate the select tool again and assert that the only response is a confirmation of the active tool
+	fn activating_a_tool_aborts_the_previous_one() {
+		use crate::frontend::utility_types::MouseCursorIcon;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		editor.select_tool(ToolType::Rectangle);
+		editor.move_mouse(0., 0.);
+		editor.lmb_mousedown(0., 0.);
+		editor.move_mouse(100., 100.);
+
+		let responses = editor.handle_message(ToolMessage::ActivateTool { tool_type: ToolType::Select });
+		let last_cursor = responses.iter().rev().find_map(|response| match response {
+			FrontendMessage::UpdateMouseCursor { cursor } => Some(*cursor),
+			_ => None,
 		});
-		editor.handle_message(PortfolioMessage::PasteIntoFolder {
-			clipboard: Clipboard::User,
-			folder_path: vec![],
-			insert_index: -1,
+		assert_eq!(last_cursor, Some(MouseCursorIcon::Default));
+		assert!(responses.contains(&FrontendMessage::UpdateActiveTool { tool_name: "Select".into() }));
+
+		let document = editor.active_document();
+		assert!(document.graphene_document.root.as_folder().unwrap().layers().is_empty());
+
+		let responses = editor.handle_message(ToolMessage::ActivateTool { tool_type: ToolType::Select });
+		assert_eq!(responses, vec![FrontendMessage::UpdateActiveTool { tool_name: "Select".into() }]);
+	}
+
+	#[test]
+	/// - for every tool, draw and select a rect, then start dragging from its top left corner and abort partway through the drag
+	/// - assert that the tool is ready again, the layers, artboards, and undo history are as they were before the drag, and no overlays or transactions are left behind
+	/// - do the same again but press Ctrl+Z instead of aborting, asserting that a drag that edits the document is rolled back without undoing the rect
+	fn aborting_any_tool_leaves_a_clean_state() {
+		use crate::input::input_preprocessor::ModifierKeys;
+		use crate::input::keyboard::Key;
+		use crate::input::mouse::MouseKeys;
+
+		/// Everything an aborted interaction must leave as it found it
+		#[derive(Debug, PartialEq)]
+		struct Snapshot {
+			layers: String,
+			artboards: Vec<LayerId>,
+			undo_steps: usize,
+			overlays: usize,
+			open_transactions: usize,
+		}
+
+		fn snapshot(editor: &mut Editor) -> Snapshot {
+			// Let the tool redraw the overlays it shows while idle, so they're counted the same way before and after
+			editor.handle_message(DocumentMessage::SelectionChanged);
+
+			let document = editor.active_document();
+			Snapshot {
+				layers: serde_json::to_string(&document.graphene_document.root.data).unwrap(),
+				artboards: document.artboard_message_handler.artboard_ids.clone(),
+				undo_steps: document.document_undo_history.len(),
+				overlays: document.overlays_message_handler.overlays_graphene_document.root.as_folder().unwrap().layer_ids.len(),
+				open_transactions: document.open_transactions,
+			}
+		}
+
+		init_logger();
+
+		// The Fill tool finishes its edit on the click itself, so it never has an interaction in progress to abort
+		let tools = [
+			ToolType::Select,
+			ToolType::Crop,
+			ToolType::Navigate,
+			ToolType::Eyedropper,
+			ToolType::Text,
+			ToolType::Path,
+			ToolType::Pen,
+			ToolType::Freehand,
+			ToolType::Spline,
+			ToolType::Line,
+			ToolType::Rectangle,
+			ToolType::Ellipse,
+			ToolType::Shape,
+		];
+		/// Draws a rect and then starts dragging over it with the tool, returning the state from before the drag
+		fn start_drag(editor: &mut Editor, tool_type: ToolType) -> Snapshot {
+			editor.draw_rect(0., 0., 100., 100.);
+			editor.select_tool(tool_type);
+			let before = snapshot(editor);
+			assert_eq!(before.open_transactions, 0, "Drawing the rect left a transaction open");
+
+			editor.move_mouse(0., 0.);
+			editor.lmb_mousedown(0., 0.);
+			editor.pointer_move((30., 40.), MouseKeys::LEFT, ModifierKeys::empty());
+			before
+		}
+
+		for tool_type in tools {
+			set_uuid_seed(0);
+			let mut editor = Editor::new();
+			let before = start_drag(&mut editor, tool_type);
+			editor.handle_message(ToolMessage::AbortCurrentTool);
+
+			let busy = editor.dispatcher.message_handlers.tool_message_handler.active_tool_is_busy();
+			assert!(!busy, "The {} tool isn't ready after aborting", tool_type);
+			assert_eq!(snapshot(&mut editor), before, "The {} tool didn't clean up after aborting", tool_type);
+
+			// Undoing an interaction that's editing the document should only roll it back, leaving the rect drawn before it
+			set_uuid_seed(0);
+			let mut editor = Editor::new();
+			let before = start_drag(&mut editor, tool_type);
+			if editor.active_document().open_transactions > 0 {
+				editor.press_key(Key::KeyZ, ModifierKeys::CONTROL);
+
+				let busy = editor.dispatcher.message_handlers.tool_message_handler.active_tool_is_busy();
+				assert!(!busy, "The {} tool isn't ready after undoing", tool_type);
+				assert_eq!(snapshot(&mut editor), before, "Undoing in the {} tool undid more than the interaction", tool_type);
+			}
+		}
+	}
+
+	#[test]
+	/// - start drawing a rect, then hold R and move the pointer a quarter turn around its center, and then 80° around it while also holding Ctrl
+	/// - assert that the rect keeps its size and turns by 90° about its center, and then by the 75° snap increment
+	fn rect_rotates_while_drawing() {
+		use crate::input::input_preprocessor::ModifierKeys;
+		use crate::input::mouse::EditorMouseState;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		editor.select_tool(ToolType::Rectangle);
+		editor.move_mouse(0., 0.);
+		editor.lmb_mousedown(0., 0.);
+		editor.move_mouse(100., 50.);
+		editor.input(InputPreprocessorMessage::KeyDown {
+			key: Key::KeyR,
+			modifier_keys: ModifierKeys::empty(),
 		});
 
-		let document_after_copy = editor.dispatcher.message_handlers.portfolio_message_handler.active_document().graphene_document.clone();
+		fn rect_transform(editor: &Editor) -> DAffine2 {
+			let document = &editor.active_document().graphene_document;
+			let rect_id = editor.last_layer_id();
+			document.layer(&[rect_id]).unwrap().transform
+		}
 
-		let layers_before_copy = document_before_copy.root.as_folder().unwrap().layers();
-		let layers_after_copy = document_after_copy.root.as_folder().unwrap().layers();
+		let center = DVec2::new(50., 25.);
+		editor.move_mouse(25., 75.);
+		let transform = rect_transform(&editor);
+		assert!(transform.matrix2.x_axis.abs_diff_eq(DVec2::new(0., 100.), 1e-10));
+		assert!(transform.matrix2.y_axis.abs_diff_eq(DVec2::new(-50., 0.), 1e-10));
+		assert!(transform.transform_point2(DVec2::splat(0.5)).abs_diff_eq(center, 1e-10));
 
-		assert_eq!(layers_before_copy.len(), 3);
-		assert_eq!(layers_after_copy.len(), 6);
+		let mut editor_mouse_state = EditorMouseState::new();
+		editor_mouse_state.editor_position = center + DAffine2::from_angle(80_f64.to_radians()).transform_vector2(DVec2::new(50., 25.));
+		editor.input(InputPreprocessorMessage::PointerMove {
+			editor_mouse_state,
+			modifier_keys: ModifierKeys::CONTROL,
+		});
+		let x_axis = rect_transform(&editor).matrix2.x_axis;
+		assert!((x_axis.y.atan2(x_axis.x).to_degrees() - 75.).abs() < 1e-10);
+		assert!((x_axis.length() - 100.).abs() < 1e-10);
+	}
 
-		let rect_before_copy = &layers_before_copy[RECT_INDEX];
-		let ellipse_before_copy = &layers_before_copy[ELLIPSE_INDEX];
+	#[test]
+	/// - select the rect tool, then swap the center and constrain bindings
+	/// - assert that the hints now name Alt for constraining, then draw a rect while holding Alt
+	/// - assert that Alt now constrains the rect to a square, and that conflicting bindings are rejected with an error
+	fn modifier_bindings_can_be_reassigned() {
+		use crate::input::input_mapper::ModifierBindings;
+		use crate::input::input_preprocessor::ModifierKeys;
+		use crate::input::mouse::MouseKeys;
+		use crate::misc::KeysGroup;
 
-		assert_eq!(layers_after_copy[0], layers_before_copy[SHAPE_INDEX]);
-		assert_eq!(&layers_after_copy[2], rect_before_copy);
-		assert_eq!(&layers_after_copy[3], ellipse_before_copy);
-		assert_eq!(&layers_after_copy[4], rect_before_copy);
-		assert_eq!(&layers_after_copy[5], ellipse_before_copy);
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+		editor.select_tool(ToolType::Rectangle);
+
+		let bindings = ModifierBindings {
+			center: Key::KeyShift,
+			constrain: Key::KeyAlt,
+		};
+		assert!(bindings.conflicts().is_empty());
+		let responses = editor.handle_message(GlobalMessage::SetModifierBindings { bindings });
+		let constrain_hint_keys = responses.iter().find_map(|response| match response {
+			FrontendMessage::UpdateInputHints { hint_data } => hint_data
+				.0
+				.iter()
+				.flat_map(|group| group.0.iter())
+				.find(|hint| hint.label == "Constrain Square")
+				.map(|hint| hint.key_groups.clone()),
+			_ => None,
+		});
+		assert_eq!(constrain_hint_keys, Some(vec![KeysGroup(vec![Key::KeyAlt])]));
+
+		let modifier_keys = ModifierKeys::ALT;
+		editor.pointer_move((0., 0.), MouseKeys::empty(), modifier_keys);
+		editor.pointer_down((0., 0.), MouseKeys::LEFT, modifier_keys);
+		editor.pointer_move((100., 40.), MouseKeys::LEFT, modifier_keys);
+		editor.pointer_up((100., 40.), MouseKeys::empty(), modifier_keys);
+
+		let document = &editor.active_document().graphene_document;
+		let rect_id = editor.last_layer_id();
+		let transform = document.layer(&[rect_id]).unwrap().transform;
+		assert!(transform.matrix2.x_axis.abs_diff_eq(DVec2::new(100., 0.), 1e-10));
+		assert!(transform.matrix2.y_axis.abs_diff_eq(DVec2::new(0., 100.), 1e-10));
+
+		let conflicting = ModifierBindings {
+			center: Key::KeyShift,
+			constrain: Key::KeyShift,
+		};
+		assert_eq!(conflicting.conflicts().len(), 1);
+		let responses = editor.handle_message(GlobalMessage::SetModifierBindings { bindings: conflicting });
+		assert!(responses.iter().any(|response| matches!(response, FrontendMessage::DisplayDialogError { .. })));
 	}
 
 	#[test]
-	#[ignore] // TODO: Re-enable test, see issue #444 (https://github.com/GraphiteEditor/Graphite/pull/444)
 	/// - create rect, shape and ellipse
-	/// - select ellipse and rect
-	/// - move them down and back up again
-	fn move_selection() {
+	/// - select rect and ellipse with the select tool
+	/// - switch to the path tool and back to the select tool
+	/// - assert that the same layers are still selected
+	fn switching_tools_preserves_selection() {
 		init_logger();
 		let mut editor = create_editor_with_three_layers();
 
-		fn map_to_vec(paths: Vec<&[LayerId]>) -> Vec<Vec<LayerId>> {
-			paths.iter().map(|layer| layer.to_vec()).collect::<Vec<_>>()
+		let layer_ids = editor.root_layer_ids();
+		let selection = vec![vec![layer_ids[0]], vec![layer_ids[2]]];
+
+		editor.select_tool(ToolType::Select);
+		editor.handle_message(DocumentMessage::SetSelectedLayers {
+			replacement_selected_layers: selection.clone(),
+		});
+
+		assert_eq!(editor.selected_layers(), selection);
+
+		editor.select_tool(ToolType::Path);
+		assert_eq!(editor.selected_layers(), selection);
+
+		editor.select_tool(ToolType::Select);
+		assert_eq!(editor.selected_layers(), selection);
+	}
+
+	#[test]
+	/// - create rect, shape and ellipse
+	/// - select rect, then nudge it, mark the document as dirty without changing it, and rotate the rect
+	/// - deselect everything
+	/// - assert that the selection bounds and rotation sent to the frontend follow along, and are only sent when they change
+	fn selection_bounds_follow_selection_and_nudges() {
+		init_logger();
+		let mut editor = create_editor_with_three_layers();
+
+		fn last_selection_bounds(responses: Vec<FrontendMessage>) -> Option<[DVec2; 2]> {
+			let bounds = responses.into_iter().rev().find_map(|message| match message {
+				FrontendMessage::UpdateSelectionBounds { bounds, .. } => Some(bounds),
+				_ => None,
+			});
+			bounds.expect("UpdateSelectionBounds should have been sent")
 		}
-		let sorted_layers = map_to_vec(editor.dispatcher.message_handlers.portfolio_message_handler.active_document().all_layers_sorted());
-		println!("Sorted layers: {:?}", sorted_layers);
 
-		let verify_order = |handler: &mut DocumentMessageHandler| {
-			(
-				map_to_vec(handler.all_layers_sorted()),
-				map_to_vec(handler.non_selected_layers_sorted()),
-				map_to_vec(handler.selected_layers_sorted()),
-			)
-		};
+		let rect_id = editor.root_layer_ids()[0];
 
-		editor.handle_message(DocumentMessage::SetSelectedLayers {
-			replacement_selected_layers: sorted_layers[..2].to_vec(),
+		let responses = editor.handle_message(DocumentMessage::SetSelectedLayers {
+			replacement_selected_layers: vec![vec![rect_id]],
 		});
+		let [min, max] = last_selection_bounds(responses).unwrap();
+		let cage_bounds = editor.active_document().selected_visible_layers_bounding_box();
+		assert_eq!(Some([min, max]), cage_bounds);
 
-		editor.handle_message(DocumentMessage::ReorderSelectedLayers { relative_index_offset: 1 });
-		let (all, non_selected, selected) = verify_order(editor.dispatcher.message_handlers.portfolio_message_handler.active_document_mut());
-		assert_eq!(all, non_selected.into_iter().chain(selected.into_iter()).collect::<Vec<_>>());
+		let responses = editor.handle_message(DocumentMessage::NudgeSelectedLayers { delta_x: 10., delta_y: -5. });
+		let offset = DVec2::new(10., -5.);
+		let [nudged_min, nudged_max] = last_selection_bounds(responses).unwrap();
+		assert!((nudged_min - (min + offset)).length() < 1e-10);
+		assert!((nudged_max - (max + offset)).length() < 1e-10);
 
-		editor.handle_message(DocumentMessage::ReorderSelectedLayers { relative_index_offset: -1 });
-		let (all, non_selected, selected) = verify_order(editor.dispatcher.message_handlers.portfolio_message_handler.active_document_mut());
-		assert_eq!(all, selected.into_iter().chain(non_selected.into_iter()).collect::<Vec<_>>());
+		let responses = editor.handle_message(ToolMessage::DocumentIsDirty);
+		let sent = responses.iter().any(|message| matches!(message, FrontendMessage::UpdateSelectionBounds { .. }));
+		assert!(!sent, "Unchanged selection bounds shouldn't be sent again");
 
-		editor.handle_message(DocumentMessage::ReorderSelectedLayers { relative_index_offset: isize::MAX });
-		let (all, non_selected, selected) = verify_order(editor.dispatcher.message_handlers.portfolio_message_handler.active_document_mut());
-		assert_eq!(all, non_selected.into_iter().chain(selected.into_iter()).collect::<Vec<_>>());
+		let responses = editor.handle_message(Operation::TransformLayer {
+			path: vec![rect_id],
+			transform: DAffine2::from_angle(0.5).to_cols_array(),
+		});
+		let rotation = responses.into_iter().rev().find_map(|message| match message {
+			FrontendMessage::UpdateSelectionBounds { rotation, .. } => Some(rotation),
+			_ => None,
+		});
+		let rotation = rotation.expect("Rotating the rect should send its rotation");
+		assert!((rotation - 0.5).abs() < 1e-10, "The rotation should be the rect's own");
+
+		let responses = editor.handle_message(DocumentMessage::DeselectAllLayers);
+		assert_eq!(last_selection_bounds(responses), None);
+	}
+
+	#[test]
+	/// - enable pixel snapping and zoom in to 200%
+	/// - draw a rect and drag it by a fraction of a document pixel
+	/// - assert that the top left corner of the rect lands on a whole pixel in document space
+	fn pixel_snapping_moves_to_whole_document_pixels() {
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		editor.handle_message(GlobalMessage::SetPixelSnapping { enabled: true });
+		editor.handle_message(MovementMessage::SetCanvasZoom { zoom_factor: 2. });
+		editor.draw_rect(0., 0., 100., 100.);
+
+		// At 200% zoom this is a move of (1.7, 2.4) in document space
+		editor.drag_tool(ToolType::Select, 50., 50., 53.4, 54.8);
+
+		let document = &editor.active_document().graphene_document;
+		let rect_id = editor.last_layer_id();
+		let [viewport_top_left, _] = document.viewport_bounding_box(&[rect_id]).unwrap().unwrap();
+		let top_left = document.root.transform.inverse().transform_point2(viewport_top_left);
+
+		assert!((top_left - DVec2::new(2., 2.)).length() < 1e-10, "Expected the rect at (2, 2) but it is at {}", top_left);
+	}
+
+	#[test]
+	/// - draw a line at about 35° while holding Shift to snap its angle, first with the default increment and then with a 45° one
+	/// - assert that the line snaps to 30° and then to 45°, and that setting a zero, negative or non-finite increment afterwards is ignored
+	fn line_angle_snaps_to_configured_increment() {
+		use crate::input::input_preprocessor::ModifierKeys;
+		use crate::input::mouse::MouseKeys;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		fn draw_snapped_line(editor: &mut Editor) -> f64 {
+			editor.select_tool(ToolType::Line);
+
+			let modifier_keys = ModifierKeys::SHIFT;
+			editor.pointer_move((0., 0.), MouseKeys::empty(), modifier_keys);
+			editor.pointer_down((0., 0.), MouseKeys::LEFT, modifier_keys);
+			editor.pointer_move((100., 70.), MouseKeys::LEFT, modifier_keys);
+			editor.pointer_up((100., 70.), MouseKeys::empty(), modifier_keys);
+
+			let document = &editor.active_document().graphene_document;
+			let line_id = editor.last_layer_id();
+			let direction = document.layer(&[line_id]).unwrap().transform.matrix2.x_axis;
+			direction.y.atan2(direction.x).to_degrees().abs()
+		}
+
+		assert!((draw_snapped_line(&mut editor) - 30.).abs() < 1e-10);
+
+		editor.handle_message(GlobalMessage::SetRotateSnapAngle { angle: 45. });
+		assert!((draw_snapped_line(&mut editor) - 45.).abs() < 1e-10);
+
+		for angle in [0., -15., f64::NAN, f64::INFINITY] {
+			editor.handle_message(GlobalMessage::SetRotateSnapAngle { angle });
+		}
+		assert!((draw_snapped_line(&mut editor) - 45.).abs() < 1e-10, "Invalid snap angles should be ignored");
+	}
+
+	#[test]
+	/// - draw a rect and rotate it by 0.3° with the Select tool's rotation handle
+	/// - assert that it snaps straight to 0°, but rotating it by 5° or while holding Alt leaves the angle alone
+	fn rotation_straightens_nearly_axis_aligned_layers() {
+		use crate::input::input_preprocessor::ModifierKeys;
+		use crate::input::mouse::MouseKeys;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+		editor.draw_rect(0., 0., 100., 100.);
+
+		fn rotate_rect(editor: &mut Editor, degrees: f64, modifier_keys: ModifierKeys) -> f64 {
+			editor.select_tool(ToolType::Select);
+
+			// Just outside the top left corner of the bounding box, where dragging rotates about the center
+			let pivot = DVec2::new(50., 50.);
+			let start = DVec2::new(-20., -20.);
+			let end = pivot + DAffine2::from_angle(degrees.to_radians()).transform_vector2(start - pivot);
+
+			editor.pointer_move(start, MouseKeys::empty(), modifier_keys);
+			editor.pointer_down(start, MouseKeys::LEFT, modifier_keys);
+			editor.pointer_move(end, MouseKeys::LEFT, modifier_keys);
+			editor.pointer_up(end, MouseKeys::empty(), modifier_keys);
+
+			let document = &editor.active_document().graphene_document;
+			let rect_id = editor.last_layer_id();
+			let x_axis = document.layer(&[rect_id]).unwrap().transform.matrix2.x_axis;
+			x_axis.y.atan2(x_axis.x).to_degrees()
+		}
+
+		let angle = rotate_rect(&mut editor, 0.3, ModifierKeys::empty());
+		assert!(angle.abs() < 1e-10, "Expected the rect to snap to 0° but it is at {}°", angle);
+
+		let angle = rotate_rect(&mut editor, 5., ModifierKeys::empty());
+		assert!((angle - 5.).abs() < 1e-10, "Expected the rect to rotate freely to 5° but it is at {}°", angle);
+
+		let angle = rotate_rect(&mut editor, -4.7, ModifierKeys::ALT);
+		assert!((angle - 0.3).abs() < 1e-10, "Expected Alt to keep the rect at 0.3° but it is at {}°", angle);
+	}
+
+	#[test]
+	/// - draw a rect and rotate it with the R key by dragging 0.3° around it, then again by 5°
+	/// - assert that it snaps straight to 0° like it does with the rotation handle, but rotating it by 5° leaves the angle alone
+	fn rotation_with_the_r_key_straightens_nearly_axis_aligned_layers() {
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+		editor.draw_rect(0., 0., 100., 100.);
+		editor.select_tool(ToolType::Select);
+
+		fn rotate_rect(editor: &mut Editor, degrees: f64) -> f64 {
+			// The rect is turned by the angle the pointer sweeps around its center
+			let pivot = DVec2::new(50., 50.);
+			let start = DVec2::new(150., 50.);
+			let end = pivot + DAffine2::from_angle(degrees.to_radians()).transform_vector2(start - pivot);
+
+			editor.move_mouse(start.x, start.y);
+			editor.handle_message(TransformLayerMessage::BeginRotate { transform_each: false });
+			editor.move_mouse(end.x, end.y);
+			editor.handle_message(TransformLayerMessage::ApplyTransformOperation);
+
+			let document = &editor.active_document().graphene_document;
+			let rect_id = editor.last_layer_id();
+			let x_axis = document.layer(&[rect_id]).unwrap().transform.matrix2.x_axis;
+			x_axis.y.atan2(x_axis.x).to_degrees()
+		}
+
+		let angle = rotate_rect(&mut editor, 0.3);
+		assert!(angle.abs() < 1e-10, "Expected the rect to snap to 0° but it is at {}°", angle);
+
+		let angle = rotate_rect(&mut editor, 5.);
+		assert!((angle - 5.).abs() < 1e-10, "Expected the rect to rotate freely to 5° but it is at {}°", angle);
+	}
+
+	#[test]
+	/// - draw two rects of different sizes, select both, and rotate them by 90° with the Select tool's rotation handle
+	/// - assert that they turn about the center of their combined bounds, where the pivot marker starts, rather than the average of their centers
+	fn rotating_several_layers_turns_about_their_combined_center() {
+		use crate::input::input_preprocessor::ModifierKeys;
+		use crate::input::mouse::MouseKeys;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+		editor.draw_rect(0., 0., 100., 100.);
+		editor.draw_rect(200., 0., 300., 300.);
+		editor.select_tool(ToolType::Select);
+		editor.handle_message(DocumentMessage::SelectAllLayers);
+
+		// The combined bounds span (0, 0) to (300, 300), while the average of the two centers is (150, 100)
+		let pivot = DVec2::new(150., 150.);
+		let start = DVec2::new(-20., -20.);
+		let end = pivot + DAffine2::from_angle(90_f64.to_radians()).transform_vector2(start - pivot);
+		let modifier_keys = ModifierKeys::empty();
+		editor.pointer_move(start, MouseKeys::empty(), modifier_keys);
+		editor.pointer_down(start, MouseKeys::LEFT, modifier_keys);
+		editor.pointer_move(end, MouseKeys::LEFT, modifier_keys);
+		editor.pointer_up(end, MouseKeys::empty(), modifier_keys);
+
+		let document = &editor.active_document().graphene_document;
+		let small_rect_id = document.root.as_folder().unwrap().layer_ids[0];
+		let [min, max] = document.viewport_bounding_box(&[small_rect_id]).unwrap().unwrap();
+		let center = (min + max) / 2.;
+		// The small rect's center at (50, 50) turns a quarter turn about (150, 150)
+		assert!(center.abs_diff_eq(DVec2::new(250., 50.), 1e-10), "Expected it centered on (250, 50) but it is on {}", center);
+	}
+
+	#[test]
+	/// - draw a path with the Pen tool, placing one point near the path's second point and the last one near its start
+	/// - assert that the point near the second one doesn't snap, while the last one closes the path at its start
+	fn pen_snaps_only_to_the_start_of_its_path() {
+		use crate::input::mouse::{EditorMouseState, MouseKeys, ScrollDelta};
+		use graphene::layers::layer_info::LayerDataType;
+		use kurbo::PathEl;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+		editor.select_tool(ToolType::Pen);
+
+		for (x, y) in [(10., 10.), (110., 10.), (110., 110.), (108., 12.), (14., 13.)] {
+			editor.move_mouse(x, y);
+			editor.lmb_mousedown(x, y);
+			editor.mouseup(EditorMouseState {
+				editor_position: (x, y).into(),
+				mouse_keys: MouseKeys::empty(),
+				scroll_delta: ScrollDelta::default(),
+				pressure: None,
+			});
+		}
+		editor.handle_message(PenMessage::Confirm);
+
+		let document = &editor.active_document().graphene_document;
+		let path_id = editor.last_layer_id();
+		let shape = match &document.layer(&[path_id]).unwrap().data {
+			LayerDataType::Shape(shape) => shape,
+			_ => panic!("The Pen tool should draw a shape"),
+		};
+		let viewport_points: Vec<DVec2> = shape
+			.path
+			.elements()
+			.iter()
+			.filter_map(|element| match element {
+				PathEl::MoveTo(point) | PathEl::LineTo(point) => Some(document.root.transform.transform_point2(DVec2::new(point.x, point.y))),
+				_ => None,
+			})
+			.collect();
+
+		let expected = [(10., 10.), (110., 10.), (110., 110.), (108., 12.)].map(|(x, y)| DVec2::new(x, y));
+		assert_eq!(shape.path.elements().last(), Some(&PathEl::ClosePath));
+		assert_eq!(viewport_points.len(), expected.len());
+		assert!(viewport_points.iter().zip(expected).all(|(point, expected)| point.distance(expected) < 1e-10));
+	}
+
+	#[test]
+	/// - click with the Crop tool without dragging, then drag out an artboard, then drag along only one axis
+	/// - assert that only the artboard with an area is kept
+	fn crop_tool_discards_empty_artboards() {
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		fn artboard_count(editor: &Editor) -> usize {
+			let document = editor.active_document();
+			document.artboard_message_handler.artboard_ids.len()
+		}
+
+		editor.drag_tool(ToolType::Crop, 50., 50., 50., 50.);
+		assert_eq!(artboard_count(&editor), 0);
+
+		editor.drag_tool(ToolType::Crop, 10., 10., 110., 60.);
+		assert_eq!(artboard_count(&editor), 1);
+
+		editor.drag_tool(ToolType::Crop, 10., 200., 110., 200.);
+		assert_eq!(artboard_count(&editor), 1);
+
+		let artboards = &editor.active_document().artboard_message_handler;
+		assert_eq!(artboards.artboards_graphene_document.root.as_folder().unwrap().layer_ids, artboards.artboard_ids);
+	}
+
+	#[test]
+	/// - draw an artboard with the Crop tool in each of the four diagonal directions
+	/// - assert that each artboard has a positive size and its origin at the top left corner of the dragged area
+	fn crop_tool_draws_artboards_with_positive_sizes() {
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		let drags = [((100., 100.), (150., 130.)), ((400., 100.), (350., 130.)), ((100., 400.), (150., 370.)), ((400., 400.), (350., 370.))];
+		for (index, ((start_x, start_y), (end_x, end_y))) in drags.into_iter().enumerate() {
+			editor.drag_tool(ToolType::Crop, start_x, start_y, end_x, end_y);
+
+			let document = editor.active_document();
+			let artboards = &document.artboard_message_handler;
+			assert_eq!(artboards.artboard_ids.len(), index + 1);
+			let artboard_id = *artboards.artboard_ids.last().unwrap();
+			let transform = artboards.artboards_graphene_document.layer(&[artboard_id]).unwrap().transform;
+
+			let viewport_to_document = document.graphene_document.root.transform.inverse();
+			let (start, end) = (DVec2::new(start_x, start_y), DVec2::new(end_x, end_y));
+			let expected_origin = viewport_to_document.transform_point2(start.min(end)).round();
+			let expected_size = viewport_to_document.transform_vector2(end - start).abs().round();
+
+			assert_eq!(transform.translation, expected_origin);
+			assert_eq!(transform.matrix2 * DVec2::ONE, expected_size);
+			assert!(expected_size.min_element() > 0.);
+		}
+	}
+
+	#[test]
+	/// - draw an artboard with the Crop tool, then drag its bottom right and top left corners while holding Shift
+	/// - assert that the artboard keeps its aspect ratio, and that the corner opposite the dragged one stays in place
+	fn crop_tool_constrains_artboard_aspect_ratio() {
+		use crate::input::input_preprocessor::ModifierKeys;
+		use crate::input::mouse::EditorMouseState;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		fn artboard_bounds(editor: &Editor) -> [DVec2; 2] {
+			let document = editor.active_document();
+			let artboards = &document.artboard_message_handler;
+			let transform = artboards.artboards_graphene_document.layer(&[artboards.artboard_ids[0]]).unwrap().transform;
+			[transform.translation, transform.translation + transform.matrix2 * DVec2::ONE]
+		}
+		fn drag_corner_constrained(editor: &mut Editor, from: DVec2, to: DVec2) {
+			let document = editor.active_document();
+			let (from, to) = (document.document_to_viewport(from), document.document_to_viewport(to));
+
+			editor.move_mouse(from.x, from.y);
+			editor.lmb_mousedown(from.x, from.y);
+			let mut editor_mouse_state = EditorMouseState::new();
+			editor_mouse_state.editor_position = to;
+			editor.input(InputPreprocessorMessage::PointerMove {
+				editor_mouse_state,
+				modifier_keys: ModifierKeys::SHIFT,
+			});
+			editor.mouseup(editor_mouse_state);
+		}
+
+		let document = editor.active_document();
+		let (start, end) = (document.document_to_viewport(DVec2::new(100., 100.)), document.document_to_viewport(DVec2::new(300., 200.)));
+		editor.drag_tool(ToolType::Crop, start.x, start.y, end.x, end.y);
+		assert_eq!(artboard_bounds(&editor), [DVec2::new(100., 100.), DVec2::new(300., 200.)]);
+
+		// The horizontal scale is the greater, so the height follows it
+		drag_corner_constrained(&mut editor, DVec2::new(300., 200.), DVec2::new(500., 250.));
+		assert_eq!(artboard_bounds(&editor), [DVec2::new(100., 100.), DVec2::new(500., 300.)]);
+
+		// The top left corner moves by the constrained size, rather than to the pointer
+		drag_corner_constrained(&mut editor, DVec2::new(100., 100.), DVec2::new(0., 90.));
+		assert_eq!(artboard_bounds(&editor), [DVec2::new(0., 50.), DVec2::new(500., 300.)]);
+	}
+
+	#[test]
+	/// - draw a rect, then drag across it with the select tool while holding space
+	/// - assert that the canvas pans by the dragged distance while the rect is neither selected nor moved
+	/// - drag across it again without space and assert that the select tool gets the input instead
+	fn space_drag_pans_through_a_ready_tool() {
+		use crate::input::input_preprocessor::ModifierKeys;
+		use crate::input::keyboard::Key;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		editor.draw_rect(0., 0., 100., 100.);
+		editor.handle_message(DocumentMessage::DeselectAllLayers);
+
+		fn root_and_rect_translation(editor: &Editor) -> (DVec2, DVec2) {
+			let document = &editor.active_document().graphene_document;
+			(document.root.transform.translation, document.root.as_folder().unwrap().layers()[0].transform.translation)
+		}
+		let (root_before, rect_before) = root_and_rect_translation(&editor);
+
+		let modifier_keys = ModifierKeys::empty();
+		editor.input(InputPreprocessorMessage::KeyDown { key: Key::KeySpace, modifier_keys });
+		editor.drag_tool(ToolType::Select, 50., 50., 80., 90.);
+		editor.input(InputPreprocessorMessage::KeyUp { key: Key::KeySpace, modifier_keys });
+
+		let (root_panned, rect_panned) = root_and_rect_translation(&editor);
+		assert!((root_panned - root_before).distance(DVec2::new(30., 40.)) < 1e-10);
+		assert_eq!(rect_panned, rect_before);
+		assert!(editor.active_document().selected_layers_sorted().is_empty());
+
+		editor.drag_tool(ToolType::Select, 50., 50., 50., 50.);
+
+		let (root_after, _) = root_and_rect_translation(&editor);
+		assert_eq!(root_after, root_panned);
+		assert_eq!(editor.active_document().selected_layers_sorted().len(), 1);
+	}
+
+	#[test]
+	/// - select the Navigate tool, whose own middle mouse button mapping comes before the canvas navigation's, and drag with the middle mouse button
+	/// - assert that the tool stays ready throughout, since the ready tool lets the input fall through to canvas navigation, which pans by the dragged distance
+	fn middle_drag_falls_through_a_ready_navigate_tool() {
+		use crate::input::mouse::{EditorMouseState, MouseKeys};
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+		editor.select_tool(ToolType::Navigate);
+
+		let root_translation = |editor: &Editor| {
+			editor
+				.dispatcher
+				.message_handlers
+				.portfolio_message_handler
+				.active_document()
+				.graphene_document
+				.root
+				.transform
+				.translation
+		};
+		let tool_is_busy = |editor: &Editor| editor.dispatcher.message_handlers.tool_message_handler.active_tool_is_busy();
+		let root_before = root_translation(&editor);
+
+		let mut mouse_state = EditorMouseState::from_editor_position(10., 10.);
+		mouse_state.mouse_keys = MouseKeys::MIDDLE;
+		editor.mousedown(mouse_state);
+		assert!(!tool_is_busy(&editor), "The Navigate tool shouldn't start its own pan while it's ready");
+
+		editor.move_mouse(40., 50.);
+		editor.mouseup(EditorMouseState::from_editor_position(40., 50.));
+		assert!(!tool_is_busy(&editor));
+		assert!((root_translation(&editor) - root_before).distance(DVec2::new(30., 40.)) < 1e-10);
+	}
+
+	#[test]
+	/// - place two snap reference points and draw a rect starting near their midpoint
+	/// - change the fraction and draw a rect starting near the target a quarter of the way back from the second point
+	/// - assert that each rect starts on its target
+	/// - start drawing another rect and press escape twice, asserting that the first press aborts the rect and the second clears the reference points
+	fn snap_reference_points_snap_between_them() {
+		use crate::input::input_preprocessor::ModifierKeys;
+		use crate::input::keyboard::Key;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		fn place_reference_point(editor: &mut Editor, x: f64, y: f64) {
+			editor.move_mouse(x, y);
+			editor.handle_message(DocumentMessage::PlaceSnapReferencePoint);
+		}
+		fn last_layer_viewport_origin(editor: &Editor) -> DVec2 {
+			let document = &editor.active_document().graphene_document;
+			let layer = editor.last_layer();
+			(document.root.transform * layer.transform).translation
+		}
+
+		place_reference_point(&mut editor, 100., 100.);
+		place_reference_point(&mut editor, 300., 200.);
+
+		editor.draw_rect(204., 147., 400., 400.);
+		assert!(last_layer_viewport_origin(&editor).distance(DVec2::new(200., 150.)) < 1e-10);
+
+		editor.handle_message(DocumentMessage::SetSnapReferenceFraction { fraction: 0.25 });
+		editor.draw_rect(247., 178., 500., 500.);
+		assert!(last_layer_viewport_origin(&editor).distance(DVec2::new(250., 175.)) < 1e-10);
+
+		let modifier_keys = ModifierKeys::empty();
+		let layer_count = |editor: &Editor| {
+			editor
+				.dispatcher
+				.message_handlers
+				.portfolio_message_handler
+				.active_document()
+				.graphene_document
+				.root
+				.as_folder()
+				.unwrap()
+				.layer_ids
+				.len()
+		};
+		let layers_before = layer_count(&editor);
+		editor.move_mouse(600., 600.);
+		editor.lmb_mousedown(600., 600.);
+		editor.move_mouse(700., 700.);
+		editor.input(InputPreprocessorMessage::KeyDown { key: Key::KeyEscape, modifier_keys });
+		assert!(
+			!editor.dispatcher.message_handlers.tool_message_handler.active_tool_is_busy(),
+			"Escape should abort the rect being drawn"
+		);
+		assert_eq!(layer_count(&editor), layers_before);
+		assert!(!editor.active_document().snap_reference_points.is_empty());
+
+		editor.input(InputPreprocessorMessage::KeyDown { key: Key::KeyEscape, modifier_keys });
+		assert!(editor.active_document().snap_reference_points.is_empty());
+	}
+
+	#[test]
+	/// - create rect, shape and ellipse and group them
+	/// - in both view modes, transform a layer and then the folder, and toggle a layer's visibility
+	/// - assert that each render matches a full render and that unchanged layers come from the cache
+	fn render_cache_reuses_unchanged_layers() {
+		use graphene::layers::layer_info::RenderCacheStats;
+		use graphene::layers::style::ViewMode;
+
+		init_logger();
+		let mut editor = create_editor_with_three_layers();
+		editor.handle_message(DocumentMessage::SelectAllLayers);
+		editor.handle_message(DocumentMessage::GroupSelectedLayers);
+
+		let document = &mut editor.dispatcher.message_handlers.portfolio_message_handler.active_document_mut().graphene_document;
+		let folder_path = vec![document.root.as_folder().unwrap().layer_ids[0]];
+		let layer_path = [folder_path.clone(), vec![document.folder(&folder_path).unwrap().layer_ids[0]]].concat();
+		let transform = DAffine2::from_translation(DVec2::new(10., 20.)).to_cols_array();
+		let show_layer = Operation::SetLayerVisibility {
+			path: layer_path.clone(),
+			visible: true,
+		};
+		let move_layer = Operation::TransformLayer { path: layer_path.clone(), transform };
+		let move_folder = Operation::TransformLayer { path: folder_path.clone(), transform };
+
+		// Renders the document and checks the result against rendering a copy without any cached output
+		let mut render = |operation: &Operation, view_mode: ViewMode| -> RenderCacheStats {
+			document.handle_operation(operation).unwrap();
+			let before = document.render_cache_stats;
+			let svg = document.render_root(view_mode);
+			let after = document.render_cache_stats;
+
+			assert_eq!(svg, document.clone().render_root(view_mode));
+			RenderCacheStats {
+				hits: after.hits - before.hits,
+				misses: after.misses - before.misses,
+			}
+		};
+
+		for view_mode in [ViewMode::Normal, ViewMode::Outline] {
+			render(&show_layer, view_mode);
+
+			// Only the edited layer and the folders above it are rendered again, while its two siblings are reused
+			let stats = render(&move_layer, view_mode);
+			assert_eq!(stats, RenderCacheStats { hits: 2, misses: 3 });
+
+			// In the outline view, every layer is drawn with the transforms of its parents, so moving the folder invalidates them all
+			let stats = render(&move_folder, view_mode);
+			let expected = match view_mode {
+				ViewMode::Outline => RenderCacheStats { hits: 0, misses: 5 },
+				_ => RenderCacheStats { hits: 3, misses: 2 },
+			};
+			assert_eq!(stats, expected);
+
+			render(&Operation::ToggleLayerVisibility { path: layer_path.clone() }, view_mode);
+			render(&Operation::ToggleLayerVisibility { path: folder_path.clone() }, view_mode);
+			render(&Operation::ToggleLayerVisibility { path: folder_path.clone() }, view_mode);
+		}
+	}
+
+	#[test]
+	/// - draw two rects side by side
+	/// - drag a marquee that encloses the first rect and only touches the second
+	/// - assert that both are selected, but only the first one while holding Alt
+	fn marquee_selects_touched_or_enclosed_layers() {
+		use crate::input::input_preprocessor::ModifierKeys;
+		use crate::input::mouse::MouseKeys;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		editor.draw_rect(0., 0., 100., 100.);
+		editor.draw_rect(200., 0., 300., 100.);
+
+		fn drag_marquee(editor: &mut Editor, modifier_keys: ModifierKeys) -> Vec<Vec<LayerId>> {
+			editor.handle_message(DocumentMessage::DeselectAllLayers);
+			editor.select_tool(ToolType::Select);
+
+			editor.pointer_move((-10., -10.), MouseKeys::empty(), modifier_keys);
+			editor.pointer_down((-10., -10.), MouseKeys::LEFT, modifier_keys);
+			editor.pointer_move((250., 110.), MouseKeys::LEFT, modifier_keys);
+			editor.pointer_up((250., 110.), MouseKeys::empty(), modifier_keys);
+
+			let document = editor.active_document();
+			document.selected_layers_sorted().iter().map(|path| path.to_vec()).collect()
+		}
+
+		let layer_ids = editor.root_layer_ids();
+
+		assert_eq!(drag_marquee(&mut editor, ModifierKeys::empty()), vec![vec![layer_ids[0]], vec![layer_ids[1]]]);
+		assert_eq!(drag_marquee(&mut editor, ModifierKeys::ALT), vec![vec![layer_ids[0]]]);
+	}
+
+	#[test]
+	/// - draw a rect and click just beside it with the select tool
+	/// - assert that nothing is selected with the default tolerance
+	/// - widen the selection tolerance, click at the same spot, and assert that the rect is selected
+	fn selection_tolerance_widens_clicks() {
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		editor.draw_rect(0., 0., 100., 100.);
+		editor.handle_message(DocumentMessage::DeselectAllLayers);
+
+		editor.drag_tool(ToolType::Select, 105., 50., 105., 50.);
+		assert!(editor.selected_layers().is_empty());
+
+		editor.handle_message(GlobalMessage::SetSelectionTolerance { tolerance: 10., point_threshold: 10. });
+		editor.drag_tool(ToolType::Select, 105., 50., 105., 50.);
+
+		let rect_id = editor.last_layer_id();
+		assert_eq!(editor.selected_layers(), vec![vec![rect_id]]);
+	}
+
+	#[test]
+	/// - turn off deselecting on empty canvas clicks, then click and drag a marquee on empty canvas with a rect selected
+	/// - assert that the selection is kept until the marquee selects another rect, which replaces it
+	/// - assert that escape still deselects everything
+	fn empty_canvas_click_can_keep_selection() {
+		use crate::input::input_preprocessor::ModifierKeys;
+		use crate::input::keyboard::Key;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		editor.draw_rect(0., 0., 100., 100.);
+		editor.draw_rect(200., 0., 300., 100.);
+
+		let layer_ids = editor.root_layer_ids();
+
+		editor.handle_message(GlobalMessage::SetDeselectOnEmptyClick { enabled: false });
+		editor.drag_tool(ToolType::Select, 50., 50., 50., 50.);
+		assert_eq!(editor.selected_layers(), vec![vec![layer_ids[0]]]);
+
+		editor.drag_tool(ToolType::Select, 150., 150., 150., 150.);
+		assert_eq!(editor.selected_layers(), vec![vec![layer_ids[0]]]);
+
+		editor.drag_tool(ToolType::Select, 150., 150., 160., 200.);
+		assert_eq!(editor.selected_layers(), vec![vec![layer_ids[0]]]);
+
+		editor.drag_tool(ToolType::Select, 180., 150., 250., 50.);
+		assert_eq!(editor.selected_layers(), vec![vec![layer_ids[1]]]);
+
+		let modifier_keys = ModifierKeys::empty();
+		editor.input(InputPreprocessorMessage::KeyDown { key: Key::KeyEscape, modifier_keys });
+		assert!(editor.selected_layers().is_empty());
+
+		editor.handle_message(GlobalMessage::SetDeselectOnEmptyClick { enabled: true });
+		editor.drag_tool(ToolType::Select, 50., 50., 50., 50.);
+		editor.drag_tool(ToolType::Select, 150., 150., 150., 150.);
+		assert!(editor.selected_layers().is_empty());
+	}
+
+	#[test]
+	/// - draw a rect, then start dragging it with the Select tool and press Escape partway through the drag before releasing
+	/// - assert that the rect goes back to where it was and stays selected, rather than the Escape clearing the selection
+	fn escape_cancels_select_drag() {
+		use crate::input::input_preprocessor::ModifierKeys;
+		use crate::input::keyboard::Key;
+		use crate::input::mouse::MouseKeys;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+		editor.draw_rect(0., 0., 100., 100.);
+		let rect = vec![editor.last_layer_id()];
+		let transform = editor.last_layer().transform;
+
+		editor.select_tool(ToolType::Select);
+		editor.move_mouse(50., 50.);
+		editor.lmb_mousedown(50., 50.);
+		editor.pointer_move((80., 90.), MouseKeys::LEFT, ModifierKeys::empty());
+		assert_ne!(editor.last_layer().transform, transform, "The drag should move the rect");
+
+		editor.press_key(Key::KeyEscape, ModifierKeys::empty());
+		editor.pointer_up((80., 90.), MouseKeys::empty(), ModifierKeys::empty());
+		assert!(!editor.dispatcher.message_handlers.tool_message_handler.active_tool_is_busy());
+		assert_eq!(editor.last_layer().transform, transform, "Escape should put the rect back");
+		assert_eq!(editor.selected_layers(), vec![rect], "Escape should cancel the drag without deselecting the rect");
+		assert_eq!(editor.active_document().open_transactions, 0);
+	}
+
+	#[test]
+	/// - import a palette into the swatch library, then rename and remove swatches
+	/// - apply swatches to the primary color and to the selected layers
+	/// - assert that each change to the library is saved and only the selected shape is filled
+	fn swatch_library_applies_named_colors() {
+		use crate::global::swatches::{SwatchLibrary, SwatchTarget};
+		use graphene::layers::layer_info::LayerDataType;
+
+		init_logger();
+		let mut editor = create_editor_with_three_layers();
+
+		fn saved_swatch_names(responses: Vec<FrontendMessage>) -> Option<Vec<String>> {
+			let saved = responses.into_iter().rev().find_map(|message| match message {
+				FrontendMessage::TriggerSaveSwatchLibrary { swatch_library } => Some(swatch_library),
+				_ => None,
+			})?;
+			let library: SwatchLibrary = serde_json::from_str(&saved).unwrap();
+			Some(library.swatches.into_iter().map(|swatch| swatch.name).collect())
+		}
+
+		fn layer_fill(editor: &Editor, layer_id: LayerId) -> Option<Color> {
+			let document = editor.active_document();
+			match &document.graphene_document.layer(&[layer_id]).unwrap().data {
+				LayerDataType::Shape(shape) => shape.style.fill().map(|fill| fill.color()),
+				_ => None,
+			}
+		}
+
+		let palette = "// Primaries\n#FF0000 Red\n\n00FF0080 Translucent Green\n0000ff\n";
+		let responses = editor.handle_message(GlobalMessage::ImportPalette { palette: palette.into() });
+		assert_eq!(saved_swatch_names(responses).unwrap(), vec!["Red", "Translucent Green", "0000FF"]);
+
+		// A malformed palette is reported without changing the library
+		let responses = editor.handle_message(GlobalMessage::ImportPalette {
+			palette: "#FF0000 Red\nnot a color".into(),
+		});
+		assert!(responses.iter().any(|message| matches!(message, FrontendMessage::DisplayDialogError { .. })));
+		assert_eq!(saved_swatch_names(responses), None);
+
+		let responses = editor.handle_message(GlobalMessage::ApplySwatch {
+			index: 2,
+			target: SwatchTarget::PrimaryColor,
+		});
+		let primary_color = responses.into_iter().find_map(|message| match message {
+			FrontendMessage::UpdateWorkingColors { primary, .. } => Some(primary),
+			_ => None,
+		});
+		assert_eq!(primary_color, Some(Color::from_rgb8(0, 0, 0xFF)));
+
+		let layer_ids = editor.root_layer_ids();
+		let (rect_id, ellipse_id) = (layer_ids[0], layer_ids[2]);
+		editor.handle_message(DocumentMessage::SetSelectedLayers {
+			replacement_selected_layers: vec![vec![rect_id]],
+		});
+		editor.handle_message(GlobalMessage::ApplySwatch {
+			index: 1,
+			target: SwatchTarget::SelectedLayers,
+		});
+		assert_eq!(layer_fill(&editor, rect_id), Some(Color::from_rgba8(0, 0xFF, 0, 0x80)));
+		assert_eq!(layer_fill(&editor, ellipse_id), Some(Color::GREEN));
+
+		editor.handle_message(GlobalMessage::RenameSwatch { index: 2, name: "Blue".into() });
+		let responses = editor.handle_message(GlobalMessage::RemoveSwatch { index: 0 });
+		assert_eq!(saved_swatch_names(responses).unwrap(), vec!["Translucent Green", "Blue"]);
+	}
+
+	#[test]
+	/// - draw a rect and drop red onto it as its fill, then undo, then drop blue onto it as its stroke
+	/// - drop green onto the empty canvas beside it, then undo
+	/// - widen the selection tolerance and drop yellow just beside the rect
+	/// - assert that the rect's fill changes and is restored by the undo, it gains a blue stroke, the canvas background turns green until undone, and the widened tolerance reaches the rect
+	fn color_dropped_on_canvas_applies_to_shape_under_it() {
+		use crate::consts::DROPPED_STROKE_WIDTH;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+		editor.select_primary_color(Color::BLACK);
+		editor.draw_rect(0., 0., 100., 100.);
+		editor.handle_message(DocumentMessage::DeselectAllLayers);
+
+		editor.handle_message(ToolMessage::ApplyColorAtPosition {
+			color: Color::RED,
+			position: (50., 50.),
+			apply_to_stroke: false,
+		});
+		assert_eq!(editor.last_shape_style().fill().map(|fill| fill.color()), Some(Color::RED));
+
+		editor.handle_message(DocumentMessage::Undo);
+		assert_eq!(editor.last_shape_style().fill().map(|fill| fill.color()), Some(Color::BLACK));
+
+		editor.handle_message(ToolMessage::ApplyColorAtPosition {
+			color: Color::BLUE,
+			position: (50., 50.),
+			apply_to_stroke: true,
+		});
+		assert_eq!(editor.last_shape_style().stroke(), Some(Stroke::new(Color::BLUE, DROPPED_STROKE_WIDTH)));
+		assert_eq!(editor.last_shape_style().fill().map(|fill| fill.color()), Some(Color::BLACK));
+
+		editor.handle_message(ToolMessage::ApplyColorAtPosition {
+			color: Color::GREEN,
+			position: (300., 300.),
+			apply_to_stroke: false,
+		});
+		let canvas_background = |editor: &Editor| {
+			editor
+				.dispatcher
+				.message_handlers
+				.portfolio_message_handler
+				.active_document()
+				.artboard_message_handler
+				.canvas_background
+		};
+		assert_eq!(canvas_background(&editor), Some(Color::GREEN));
+
+		editor.handle_message(DocumentMessage::Undo);
+		assert_eq!(canvas_background(&editor), None);
+
+		editor.handle_message(ToolMessage::SetSelectionTolerance { tolerance: 10., point_threshold: 10. });
+		editor.handle_message(ToolMessage::ApplyColorAtPosition {
+			color: Color::from_unsafe(1., 1., 0.),
+			position: (105., 50.),
+			apply_to_stroke: false,
+		});
+		assert_eq!(editor.last_shape_style().fill().map(|fill| fill.color()), Some(Color::from_unsafe(1., 1., 0.)));
+	}
+
+	#[test]
+	/// - draw a rect, then drag its opacity slider for three seconds, pause and set the opacity again, and then nudge it right away
+	/// - undo three times, then lower the undo history limit
+	/// - assert that the whole slider drag is one undo step, the pause and the different kind of edit each start a new one, and the limit drops the oldest steps
+	fn rapid_edits_of_the_same_kind_share_an_undo_step() {
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		editor.draw_rect(0., 0., 100., 100.);
+
+		let undo_steps = |editor: &Editor| editor.active_document().document_undo_history.len();
+		let opacity = |editor: &Editor| {
+			let document = &editor.active_document().graphene_document;
+			let rect_id = editor.last_layer_id();
+			document.layer(&[rect_id]).unwrap().opacity
+		};
+		let undo_steps_before = undo_steps(&editor);
+
+		// Drag the opacity slider for three seconds, changing it every frame
+		for frame in 0..=187 {
+			editor.handle_message(InputPreprocessorMessage::SetTime { timestamp: frame * 16 });
+			editor.handle_message(DocumentMessage::SetOpacityForSelectedLayers { opacity: 1. - frame as f64 / 200. });
+		}
+		assert_eq!(undo_steps(&editor), undo_steps_before + 1);
+
+		// A pause starts a new undo step
+		editor.handle_message(InputPreprocessorMessage::SetTime { timestamp: 10_000 });
+		editor.handle_message(DocumentMessage::SetOpacityForSelectedLayers { opacity: 0.5 });
+		assert_eq!(undo_steps(&editor), undo_steps_before + 2);
+
+		// So does a different kind of edit, even without a pause
+		editor.handle_message(InputPreprocessorMessage::SetTime { timestamp: 10_016 });
+		editor.handle_message(DocumentMessage::NudgeSelectedLayers { delta_x: 1., delta_y: 0. });
+		assert_eq!(undo_steps(&editor), undo_steps_before + 3);
+
+		// Undoing the whole slider drag at once restores the opacity from before it
+		editor.handle_message(DocumentMessage::Undo);
+		editor.handle_message(DocumentMessage::Undo);
+		assert_eq!(opacity(&editor), 1. - 187. / 200.);
+		editor.handle_message(DocumentMessage::Undo);
+		assert_eq!(opacity(&editor), 1.);
+
+		// Lowering the history size drops the oldest undo steps
+		editor.handle_message(PortfolioMessage::SetUndoHistoryLimits {
+			max_undo_steps: 1,
+			coalesce_window: 500,
+		});
+		assert_eq!(undo_steps(&editor), 1);
+	}
+
+	#[test]
+	/// - create rect, shape and ellipse, then draw another red rect and select only the first one
+	/// - apply a style to the layers with the same fill as the selection, and assert that only both red rects are restyled in a single undo step
+	fn style_applies_to_layers_matching_the_selection() {
+		use crate::document::utility_types::StyleMatchCriterion;
+		use graphene::layers::layer_info::LayerDataType;
+
+		init_logger();
+		let mut editor = create_editor_with_three_layers();
+		editor.select_primary_color(Color::RED);
+		editor.draw_rect(500., 500., 600., 600.);
+
+		fn layer_style(editor: &Editor, layer_id: LayerId) -> PathStyle {
+			let document = editor.active_document();
+			match &document.graphene_document.layer(&[layer_id]).unwrap().data {
+				LayerDataType::Shape(shape) => shape.style,
+				_ => panic!("Expected a shape"),
+			}
+		}
+		fn undo_steps(editor: &Editor) -> usize {
+			editor.active_document().document_undo_history.len()
+		}
+
+		let layer_ids = editor.root_layer_ids();
+		let original_styles: Vec<_> = layer_ids.iter().map(|&id| layer_style(&editor, id)).collect();
+		editor.handle_message(DocumentMessage::SetSelectedLayers {
+			replacement_selected_layers: vec![vec![layer_ids[0]]],
+		});
+		let undo_steps_before = undo_steps(&editor);
+
+		let style = PathStyle::new(Some(Stroke::new(Color::BLACK, 4.)), None);
+		editor.handle_message(DocumentMessage::SetStyleForMatchingLayers {
+			style,
+			criterion: StyleMatchCriterion::SameFill,
+		});
+		let styles: Vec<_> = layer_ids.iter().map(|&id| layer_style(&editor, id)).collect();
+		assert_eq!(styles, vec![style, original_styles[1], original_styles[2], style]);
+		assert_eq!(undo_steps(&editor), undo_steps_before + 1);
+
+		editor.handle_message(DocumentMessage::Undo);
+		let styles: Vec<_> = layer_ids.iter().map(|&id| layer_style(&editor, id)).collect();
+		assert_eq!(styles, original_styles);
+	}
+
+	#[test]
+	/// - create a new document from a preset, then one with a custom artboard, then one without an artboard
+	/// - assert that each document starts with a single named artboard of the given size and background centered on the origin, or none at all
+	fn new_documents_start_with_an_artboard() {
+		use crate::document::utility_types::{ArtboardPreset, NewDocumentArtboard};
+		use graphene::layers::layer_info::LayerDataType;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		fn artboards(editor: &Editor) -> Vec<(Option<String>, DAffine2, Option<Color>)> {
+			let artboards = &editor.active_document().artboard_message_handler;
+			let document = &artboards.artboards_graphene_document;
+			artboards
+				.artboard_ids
+				.iter()
+				.map(|&id| {
+					let layer = document.layer(&[id]).unwrap();
+					let fill = match &layer.data {
+						LayerDataType::Shape(shape) => shape.style.fill().map(|fill| fill.color()),
+						_ => None,
+					};
+					(layer.name.clone(), layer.transform, fill)
+				})
+				.collect()
+		}
+
+		editor.handle_message(PortfolioMessage::NewDocument {
+			artboard: Some(NewDocumentArtboard::from_preset(ArtboardPreset::FullHd)),
+		});
+		let expected_transform = DAffine2::from_scale_angle_translation(DVec2::new(1920., 1080.), 0., DVec2::new(-960., -540.));
+		assert_eq!(artboards(&editor), vec![(Some("1080p".into()), expected_transform, Some(Color::WHITE))]);
+
+		let artboard = NewDocumentArtboard {
+			name: "Poster".into(),
+			size: (600., 900.),
+			background: Color::BLACK,
+		};
+		editor.handle_message(PortfolioMessage::NewDocument { artboard: Some(artboard) });
+		let expected_transform = DAffine2::from_scale_angle_translation(DVec2::new(600., 900.), 0., DVec2::new(-300., -450.));
+		assert_eq!(artboards(&editor), vec![(Some("Poster".into()), expected_transform, Some(Color::BLACK))]);
+
+		editor.handle_message(PortfolioMessage::NewDocument { artboard: None });
+		assert!(artboards(&editor).is_empty());
+		assert_eq!(editor.dispatcher.message_handlers.portfolio_message_handler.ordered_document_iterator().count(), 4);
+	}
+
+	#[test]
+	/// - draw a rect, then undo every step and redo them again, then delete the rect and undo that, then open a new document
+	/// - assert that after each step the frontend is told whether there is anything left to undo and redo, and what it would undo or redo
+	fn undo_redo_state_is_sent_to_the_frontend() {
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		fn undo_redo_state(responses: &[FrontendMessage]) -> Option<(bool, bool)> {
+			responses.iter().rev().find_map(|message| match message {
+				FrontendMessage::UpdateUndoRedoState { can_undo, can_redo, .. } => Some((*can_undo, *can_redo)),
+				_ => None,
+			})
+		}
+		fn undo_redo_labels(responses: &[FrontendMessage]) -> Option<(Option<String>, Option<String>)> {
+			responses.iter().rev().find_map(|message| match message {
+				FrontendMessage::UpdateUndoRedoState { undo_label, redo_label, .. } => Some((undo_label.clone(), redo_label.clone())),
+				_ => None,
+			})
+		}
+		fn history_sizes(editor: &Editor) -> (usize, usize) {
+			let document = editor.active_document();
+			(document.document_undo_history.len(), document.document_redo_history.len())
+		}
+
+		editor.draw_rect(0., 0., 100., 100.);
+		let (undo_steps, _) = history_sizes(&editor);
+		assert!(undo_steps > 0);
+
+		for step in 1..=undo_steps {
+			let responses = editor.handle_message(DocumentMessage::Undo);
+			assert_eq!(history_sizes(&editor), (undo_steps - step, step));
+			assert_eq!(undo_redo_state(&responses), Some((step < undo_steps, true)));
+		}
+		for step in 1..=undo_steps {
+			let responses = editor.handle_message(DocumentMessage::Redo);
+			assert_eq!(undo_redo_state(&responses), Some((true, step < undo_steps)));
+		}
+
+		// Steps saved by a single message are named after it, and transactions after the first operation they apply
+		let responses = editor.handle_message(DocumentMessage::DeleteSelectedLayers);
+		assert_eq!(undo_redo_labels(&responses), Some((Some("Undo Delete Selected Layers".into()), None)));
+		let responses = editor.handle_message(DocumentMessage::Undo);
+		assert_eq!(undo_redo_labels(&responses), Some((Some("Undo Add Rect".into()), Some("Redo Delete Selected Layers".into()))));
+
+		let responses = editor.handle_message(PortfolioMessage::NewDocument { artboard: None });
+		assert_eq!(undo_redo_state(&responses), Some((false, false)));
+	}
+
+	#[test]
+	/// - draw a rect, which selects it, and type digits on their own and in quick succession
+	/// - assert that single digits set the opacity in steps of 10%, and two quick digits set the exact percentage in a single undo step
+	fn digits_set_opacity_of_selected_layers() {
+		use crate::input::input_preprocessor::ModifierKeys;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		editor.draw_rect(0., 0., 100., 100.);
+
+		let opacity = |editor: &Editor| {
+			let document = &editor.active_document().graphene_document;
+			let rect_id = editor.last_layer_id();
+			document.layer(&[rect_id]).unwrap().opacity
+		};
+		let type_digit = |editor: &mut Editor, key: Key, timestamp: u64| {
+			editor.handle_message(InputPreprocessorMessage::SetTime { timestamp });
+			editor.input(InputPreprocessorMessage::KeyDown {
+				key,
+				modifier_keys: ModifierKeys::empty(),
+			});
+			editor.input(InputPreprocessorMessage::KeyUp {
+				key,
+				modifier_keys: ModifierKeys::empty(),
+			});
+		};
+
+		type_digit(&mut editor, Key::Key5, 0);
+		assert_eq!(opacity(&editor), 0.5);
+
+		type_digit(&mut editor, Key::Key2, 10_000);
+		assert_eq!(opacity(&editor), 0.2);
+		type_digit(&mut editor, Key::Key5, 10_100);
+		assert_eq!(opacity(&editor), 0.25);
+
+		type_digit(&mut editor, Key::Key0, 20_000);
+		assert_eq!(opacity(&editor), 1.);
+
+		editor.handle_message(DocumentMessage::Undo);
+		assert_eq!(opacity(&editor), 0.25);
+		editor.handle_message(DocumentMessage::Undo);
+		assert_eq!(opacity(&editor), 0.5);
+	}
+
+	#[test]
+	/// - draw a rect, which selects it, and match the digit keys on their own and while holding Alt
+	/// - assert that plain digits set the opacity, while the debug actions on the digits still get through with Alt
+	fn digits_keep_debug_actions_with_layers_selected() {
+		use crate::input::input_mapper::Mapping;
+		use crate::input::keyboard::KeyStates;
+		use crate::input::InputMapperMessage;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+		editor.draw_rect(0., 0., 100., 100.);
+		assert!(!editor.selected_layers().is_empty());
+
+		let mapping = Mapping::default();
+		let actions = editor.dispatcher.collect_actions();
+		let match_key = |key: Key, alt: bool| {
+			let mut keys = KeyStates::new();
+			keys.set(key as usize);
+			if alt {
+				keys.set(Key::KeyAlt as usize);
+			}
+			mapping.match_message(InputMapperMessage::KeyDown(key), &keys, actions.clone())
+		};
+
+		assert_eq!(match_key(Key::Key5, false), Some(DocumentMessage::TypeOpacityDigit { digit: 5 }.into()));
+		assert_eq!(match_key(Key::Key1, true), Some(GlobalMessage::LogInfo.into()));
+		assert_eq!(match_key(Key::Key2, true), Some(GlobalMessage::LogDebug.into()));
+		assert_eq!(match_key(Key::Key3, true), Some(GlobalMessage::LogTrace.into()));
+		assert_eq!(match_key(Key::Key9, true), Some(DocumentMessage::DebugPrintDocument.into()));
+	}
+
+	#[test]
+	/// - zoom, rotate, and pan the canvas
+	/// - assert that converting points and vectors from the viewport to the document and back gives them back unchanged, and that vectors aren't moved by the pan
+	fn viewport_and_document_conversions_are_inverses() {
+		init_logger();
+		let mut editor = Editor::new();
+
+		editor.handle_message(MovementMessage::SetCanvasZoom { zoom_factor: 3. });
+		editor.handle_message(MovementMessage::SetCanvasRotationDegrees { angle_degrees: 30. });
+		editor.handle_message(MovementMessage::TranslateCanvas { delta: DVec2::new(-120., 45.) });
+
+		let document = editor.active_document();
+		for point in [DVec2::ZERO, DVec2::new(250., -80.), DVec2::new(-1e4, 3e3)] {
+			assert!(document.document_to_viewport(document.viewport_to_document(point)).abs_diff_eq(point, 1e-9));
+			assert!(document.viewport_to_document(document.document_to_viewport(point)).abs_diff_eq(point, 1e-9));
+			assert!(document.document_to_viewport_vector(document.viewport_to_document_vector(point)).abs_diff_eq(point, 1e-9));
+			assert!(document.viewport_to_document_vector(document.document_to_viewport_vector(point)).abs_diff_eq(point, 1e-9));
+		}
+
+		// Vectors are zoomed and rotated like positions, but aren't moved by the pan
+		let vector = DVec2::new(10., 0.);
+		let converted_vector = document.viewport_to_document(vector) - document.viewport_to_document(DVec2::ZERO);
+		assert!(document.viewport_to_document_vector(vector).abs_diff_eq(converted_vector, 1e-9));
+		assert!((document.viewport_to_document_vector(vector).length() - 10. / 3.).abs() < 1e-9);
+	}
+
+	#[test]
+	/// - zoom to 200% and draw a rect 100 pixels wide in the viewport, then give it a stroke 4 units wide
+	/// - assert that its bounding box is 50 units wide in the document, a missing layer has none, and only the box including the stroke grows by half the stroke width
+	fn layer_bounding_box_is_in_document_space() {
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		editor.handle_message(MovementMessage::SetCanvasZoom { zoom_factor: 2. });
+		editor.draw_rect(0., 0., 100., 100.);
+
+		let document = editor.active_document();
+		let rect_id = editor.last_layer_id();
+		let [min, max] = document.layer_bounding_box(&[rect_id], false).unwrap();
+		assert!((max - min - DVec2::splat(50.)).length() < 1e-10, "Expected a 50 by 50 box at 200% zoom but got {:?}", max - min);
+		assert_eq!(document.layer_bounding_box(&[rect_id], true), Some([min, max]));
+		assert_eq!(document.layer_bounding_box(&[rect_id + 1], false), None);
+
+		// A stroke 4 units wide extends 2 units beyond the path on every side
+		let style = PathStyle::new(Some(Stroke::new(Color::BLACK, 4.)), None);
+		editor.handle_message(Operation::SetLayerStyle { path: vec![rect_id], style });
+
+		let document = editor.active_document();
+		assert_eq!(document.layer_bounding_box(&[rect_id], false), Some([min, max]));
+		let [stroke_min, stroke_max] = document.layer_bounding_box(&[rect_id], true).unwrap();
+		assert!((min - stroke_min - DVec2::splat(2.)).length() < 1e-10 && (stroke_max - max - DVec2::splat(2.)).length() < 1e-10);
+	}
+
+	#[test]
+	/// - draw two rects and select both
+	/// - set the X position of the selection to an absolute value, then move it by a relative Y value
+	/// - assert that both rects move by the same amount each time, keeping their positions relative to each other
+	fn position_selected_layers_absolute_and_relative() {
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		editor.draw_rect(0., 0., 100., 100.);
+		editor.draw_rect(200., 50., 300., 150.);
+
+		let layer_ids = editor.root_layer_ids();
+		editor.handle_message(DocumentMessage::SetSelectedLayers {
+			replacement_selected_layers: layer_ids.iter().map(|id| vec![*id]).collect(),
+		});
+
+		let top_left = |editor: &Editor, id: LayerId| editor.active_document().layer_bounding_box(&[id], false).unwrap()[0];
+		let [first_before, second_before] = [top_left(&editor, layer_ids[0]), top_left(&editor, layer_ids[1])];
+
+		// An absolute value moves the left edge of the selection as a whole, keeping the layers' positions relative to each other
+		editor.handle_message(DocumentMessage::PositionSelectedLayers {
+			axis: AlignAxis::X,
+			value: 10.,
+			mode: TransformInputMode::Absolute,
+		});
+		let shift = DVec2::new(10. - first_before.x, 0.);
+		assert!((top_left(&editor, layer_ids[0]) - (first_before + shift)).length() < 1e-10);
+		assert!((top_left(&editor, layer_ids[1]) - (second_before + shift)).length() < 1e-10);
+
+		// A relative value moves each layer by that distance
+		editor.handle_message(DocumentMessage::PositionSelectedLayers {
+			axis: AlignAxis::Y,
+			value: 5.,
+			mode: TransformInputMode::Relative,
+		});
+		let shift = shift + DVec2::new(0., 5.);
+		assert!((top_left(&editor, layer_ids[0]) - (first_before + shift)).length() < 1e-10);
+		assert!((top_left(&editor, layer_ids[1]) - (second_before + shift)).length() < 1e-10);
+	}
+
+	#[test]
+	/// - draw two rects with a third smaller one between them, and drag the smaller one to just short of the middle
+	/// - assert that it snaps to the position with equal gaps on both sides
+	fn dragging_between_layers_snaps_to_equal_spacing() {
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		editor.draw_rect(0., 0., 100., 100.);
+		editor.draw_rect(300., 0., 400., 100.);
+		editor.draw_rect(140., 20., 200., 80.);
+
+		// Equal gaps of 70 on both sides put the left edge at 170, which is within the snap tolerance of 168.5
+		editor.drag_tool(ToolType::Select, 170., 50., 198.5, 50.);
+
+		let document = &editor.active_document().graphene_document;
+		let layer_ids = editor.root_layer_ids();
+		let [min, max] = document.viewport_bounding_box(&[layer_ids[2]]).unwrap().unwrap();
+
+		assert!((min.x - 170.).abs() < 1e-10, "Expected the rect's left edge at 170 but it is at {}", min.x);
+		assert!((max.x - 230.).abs() < 1e-10, "Expected the rect's right edge at 230 but it is at {}", max.x);
+	}
+
+	#[test]
+	/// - draw two rects and select both
+	/// - scale each of them by 2 about its own center
+	/// - assert that each rect grew in place
+	/// - undo once and assert that both rects are back to their original size
+	fn transform_each_scales_layers_in_place() {
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		editor.draw_rect(0., 0., 100., 100.);
+		editor.draw_rect(200., 0., 300., 100.);
+
+		let layer_ids = editor.root_layer_ids();
+		editor.handle_message(DocumentMessage::SetSelectedLayers {
+			replacement_selected_layers: layer_ids.iter().map(|id| vec![*id]).collect(),
+		});
+
+		editor.handle_message(TransformLayerMessage::BeginScale { transform_each: true });
+		editor.handle_message(TransformLayerMessage::TypeDigit { digit: 2 });
+		editor.handle_message(TransformLayerMessage::ApplyTransformOperation);
+
+		let bounds = |editor: &Editor, id: LayerId| {
+			let document = &editor.active_document().graphene_document;
+			document.viewport_bounding_box(&[id]).unwrap().unwrap()
+		};
+		let assert_bounds = |[min, max]: [DVec2; 2], expected_min: DVec2, expected_max: DVec2| {
+			let close = (min - expected_min).length() < 1e-10 && (max - expected_max).length() < 1e-10;
+			assert!(close, "Expected {:?} but got {:?}", [expected_min, expected_max], [min, max]);
+		};
+
+		assert_bounds(bounds(&editor, layer_ids[0]), DVec2::new(-50., -50.), DVec2::new(150., 150.));
+		assert_bounds(bounds(&editor, layer_ids[1]), DVec2::new(150., -50.), DVec2::new(350., 150.));
+
+		editor.handle_message(DocumentMessage::Undo);
+
+		assert_bounds(bounds(&editor, layer_ids[0]), DVec2::new(0., 0.), DVec2::new(100., 100.));
+		assert_bounds(bounds(&editor, layer_ids[1]), DVec2::new(200., 0.), DVec2::new(300., 100.));
+	}
+
+	#[test]
+	/// - draw two rects and select both, then randomize their transforms with a seed, undo, and randomize again with the same and with a different seed
+	/// - assert that each layer gets its own change, a single undo restores them all, and only the same seed gives the same result
+	fn randomize_transforms_is_deterministic_and_undoable() {
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		editor.draw_rect(0., 0., 100., 100.);
+		editor.draw_rect(200., 0., 300., 100.);
+
+		let layer_ids = editor.root_layer_ids();
+		editor.handle_message(DocumentMessage::SetSelectedLayers {
+			replacement_selected_layers: layer_ids.iter().map(|id| vec![*id]).collect(),
+		});
+
+		let transforms = |editor: &Editor| {
+			let document = &editor.active_document().graphene_document;
+			layer_ids.iter().map(|id| document.layer(&[*id]).unwrap().transform).collect::<Vec<_>>()
+		};
+		let randomize = |seed| DocumentMessage::RandomizeTransforms {
+			position_jitter: 10.,
+			rotation_jitter: 15.,
+			scale_jitter: 10.,
+			seed,
+		};
+		let original = transforms(&editor);
+
+		editor.handle_message(randomize(42));
+		let randomized = transforms(&editor);
+		assert_ne!(randomized, original);
+		assert_ne!(randomized[0], randomized[1]);
+
+		// A single undo restores every layer
+		editor.handle_message(DocumentMessage::Undo);
+		assert_eq!(transforms(&editor), original);
+
+		// The same seed reproduces the same result, while a different one doesn't
+		editor.handle_message(randomize(42));
+		assert_eq!(transforms(&editor), randomized);
+		editor.handle_message(DocumentMessage::Undo);
+		editor.handle_message(randomize(7));
+		assert_ne!(transforms(&editor), randomized);
+	}
+
+	#[test]
+	/// - create rect, shape and ellipse
+	/// - select ellipse and rect
+	/// - copy
+	/// - delete
+	/// - create another rect
+	/// - paste
+	/// - paste
+	fn copy_paste_deleted_layers() {
+		init_logger();
+		let mut editor = create_editor_with_three_layers();
+
+		const ELLIPSE_INDEX: usize = 2;
+		const SHAPE_INDEX: usize = 1;
+		const RECT_INDEX: usize = 0;
+
+		let document_before_copy = editor.active_document().graphene_document.clone();
+		let rect_id = document_before_copy.root.as_folder().unwrap().layer_ids[RECT_INDEX];
+		let ellipse_id = document_before_copy.root.as_folder().unwrap().layer_ids[ELLIPSE_INDEX];
+
+		editor.handle_message(DocumentMessage::SetSelectedLayers {
+			replacement_selected_layers: vec![vec![rect_id], vec![ellipse_id]],
+		});
+		editor.handle_message(PortfolioMessage::Copy { clipboard: Clipboard::User });
+		editor.handle_message(DocumentMessage::DeleteSelectedLayers);
+		editor.draw_rect(0., 800., 12., 200.);
+		editor.handle_message(PortfolioMessage::PasteIntoFolder {
+			clipboard: Clipboard::User,
+			folder_path: vec![],
+			insert_index: -1,
+		});
+		editor.handle_message(PortfolioMessage::PasteIntoFolder {
+			clipboard: Clipboard::User,
+			folder_path: vec![],
+			insert_index: -1,
+		});
+
+		let document_after_copy = editor.active_document().graphene_document.clone();
+
+		let layers_before_copy = document_before_copy.root.as_folder().unwrap().layers();
+		let layers_after_copy = document_after_copy.root.as_folder().unwrap().layers();
+
+		assert_eq!(layers_before_copy.len(), 3);
+		assert_eq!(layers_after_copy.len(), 6);
+
+		let rect_before_copy = &layers_before_copy[RECT_INDEX];
+		let ellipse_before_copy = &layers_before_copy[ELLIPSE_INDEX];
+
+		assert_eq!(layers_after_copy[0], layers_before_copy[SHAPE_INDEX]);
+		assert_eq!(&layers_after_copy[2], rect_before_copy);
+		assert_eq!(&layers_after_copy[3], ellipse_before_copy);
+		assert_eq!(&layers_after_copy[4], rect_before_copy);
+		assert_eq!(&layers_after_copy[5], ellipse_before_copy);
+	}
+
+	#[test]
+	#[ignore] // TODO: Re-enable test, see issue #444 (https://github.com/GraphiteEditor/Graphite/pull/444)
+	/// - create rect, shape and ellipse
+	/// - select ellipse and rect
+	/// - move them down and back up again
+	fn move_selection() {
+		init_logger();
+		let mut editor = create_editor_with_three_layers();
+
+		fn map_to_vec(paths: Vec<&[LayerId]>) -> Vec<Vec<LayerId>> {
+			paths.iter().map(|layer| layer.to_vec()).collect::<Vec<_>>()
+		}
+		let sorted_layers = map_to_vec(editor.active_document().all_layers_sorted());
+		println!("Sorted layers: {:?}", sorted_layers);
+
+		let verify_order = |handler: &mut DocumentMessageHandler| {
+			(
+				map_to_vec(handler.all_layers_sorted()),
+				map_to_vec(handler.non_selected_layers_sorted()),
+				map_to_vec(handler.selected_layers_sorted()),
+			)
+		};
+
+		editor.handle_message(DocumentMessage::SetSelectedLayers {
+			replacement_selected_layers: sorted_layers[..2].to_vec(),
+		});
+
+		editor.handle_message(DocumentMessage::ReorderSelectedLayers { relative_index_offset: 1 });
+		let (all, non_selected, selected) = verify_order(editor.dispatcher.message_handlers.portfolio_message_handler.active_document_mut());
+		assert_eq!(all, non_selected.into_iter().chain(selected.into_iter()).collect::<Vec<_>>());
+
+		editor.handle_message(DocumentMessage::ReorderSelectedLayers { relative_index_offset: -1 });
+		let (all, non_selected, selected) = verify_order(editor.dispatcher.message_handlers.portfolio_message_handler.active_document_mut());
+		assert_eq!(all, selected.into_iter().chain(non_selected.into_iter()).collect::<Vec<_>>());
+
+		editor.handle_message(DocumentMessage::ReorderSelectedLayers { relative_index_offset: isize::MAX });
+		let (all, non_selected, selected) = verify_order(editor.dispatcher.message_handlers.portfolio_message_handler.active_document_mut());
+		assert_eq!(all, non_selected.into_iter().chain(selected.into_iter()).collect::<Vec<_>>());
+	}
+
+	#[test]
+	/// - draw an ellipse, then drag the outgoing handle of its bottom point with the Path tool, with handle mirroring on and then off
+	/// - assert that the drag hints offer to toggle the setting, and that with mirroring on the incoming handle turns to stay opposite it, and with mirroring off the incoming handle stays put
+	fn path_tool_handle_mirroring_follows_setting() {
+		use crate::input::input_preprocessor::ModifierKeys;
+		use crate::input::mouse::{EditorMouseState, MouseKeys, ScrollDelta};
+		use graphene::layers::layer_info::LayerDataType;
+		use kurbo::PathEl;
+
+		/// The incoming handle, anchor, and outgoing handle of the point where the ellipse's first curve ends, in viewport space
+		fn smooth_point(editor: &Editor) -> (DVec2, DVec2, DVec2) {
+			let document = &editor.active_document().graphene_document;
+			let ellipse_id = editor.last_layer_id();
+			let shape = match &document.layer(&[ellipse_id]).unwrap().data {
+				LayerDataType::Shape(shape) => shape,
+				_ => panic!("The Ellipse tool should draw a shape"),
+			};
+			let transform = document.multiply_transforms(&[ellipse_id]).unwrap();
+			let to_viewport = |point: &kurbo::Point| transform.transform_point2(DVec2::new(point.x, point.y));
+
+			match (shape.path.elements()[1], shape.path.elements()[2]) {
+				(PathEl::CurveTo(_, incoming, anchor), PathEl::CurveTo(outgoing, _, _)) => (to_viewport(&incoming), to_viewport(&anchor), to_viewport(&outgoing)),
+				_ => panic!("An ellipse should be made of curves"),
+			}
+		}
+
+		for mirror_handles in [true, false] {
+			init_logger();
+			set_uuid_seed(0);
+			let mut editor = Editor::new();
+			editor.handle_message(GlobalMessage::SetHandleMirroring { enabled: mirror_handles });
+			editor.draw_ellipse(0., 0., 100., 100.);
+			editor.select_tool(ToolType::Path);
+
+			let (incoming, anchor, outgoing) = smooth_point(&editor);
+			let target = outgoing + DVec2::new(5., 20.);
+
+			editor.move_mouse(outgoing.x, outgoing.y);
+			editor.lmb_mousedown(outgoing.x, outgoing.y);
+			let responses = editor.handle_message(ToolMessage::UpdateHints);
+			let toggle_hint = responses.iter().find_map(|response| match response {
+				FrontendMessage::UpdateInputHints { hint_data } => hint_data
+					.0
+					.iter()
+					.flat_map(|group| group.0.iter())
+					.find(|hint| hint.label.ends_with("(Toggle)"))
+					.map(|hint| hint.label.clone()),
+				_ => None,
+			});
+			let expected_hint = if mirror_handles { "Split/Align Handles (Toggle)" } else { "Mirror Handles (Toggle)" };
+			assert_eq!(toggle_hint.as_deref(), Some(expected_hint));
+
+			let editor_mouse_state = EditorMouseState {
+				editor_position: target,
+				mouse_keys: MouseKeys::LEFT,
+				scroll_delta: ScrollDelta::default(),
+				pressure: None,
+			};
+			editor.input(InputPreprocessorMessage::PointerMove {
+				editor_mouse_state,
+				modifier_keys: ModifierKeys::default(),
+			});
+			editor.mouseup(EditorMouseState {
+				editor_position: target,
+				mouse_keys: MouseKeys::empty(),
+				scroll_delta: ScrollDelta::default(),
+				pressure: None,
+			});
+
+			let (new_incoming, new_anchor, new_outgoing) = smooth_point(&editor);
+			assert!(new_anchor.distance(anchor) < 1e-6);
+			assert!(new_outgoing.distance(target) < 1e-6, "The dragged handle should follow the pointer");
+
+			if mirror_handles {
+				let (incoming_arm, outgoing_arm) = (new_incoming - anchor, new_outgoing - anchor);
+				assert!(incoming_arm.perp_dot(outgoing_arm).abs() < 1e-6, "The handles should stay collinear");
+				assert!(incoming_arm.dot(outgoing_arm) < 0., "The handles should stay on opposite sides of the anchor");
+				assert!((incoming_arm.length() - incoming.distance(anchor)).abs() < 1e-6, "The incoming handle should keep its length");
+			} else {
+				assert!(new_incoming.distance(incoming) < 1e-6, "Only the dragged handle should move");
+			}
+		}
+	}
+
+	#[test]
+	/// - draw two rects, group them, and hide the second one
+	/// - assert that the cage, snapping, and zoom-to-fit bounds of the group only wrap the visible rect, while the full bounds still include the hidden one
+	fn group_bounds_ignore_hidden_layers() {
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+		editor.draw_rect(0., 0., 100., 100.);
+		editor.draw_rect(200., 200., 300., 300.);
+		editor.handle_message(DocumentMessage::SelectAllLayers);
+		editor.handle_message(DocumentMessage::GroupSelectedLayers);
+
+		let document = editor.active_document();
+		let folder_path = vec![document.graphene_document.root.as_folder().unwrap().layer_ids[0]];
+		let child_ids = document.graphene_document.folder(&folder_path).unwrap().layer_ids.clone();
+		let visible_path = [folder_path.clone(), vec![child_ids[0]]].concat();
+		let hidden_path = [folder_path.clone(), vec![child_ids[1]]].concat();
+
+		editor.handle_message(DocumentMessage::ToggleLayerVisibility { layer_path: hidden_path.clone() });
+		editor.handle_message(DocumentMessage::SetSelectedLayers {
+			replacement_selected_layers: vec![folder_path.clone()],
+		});
+
+		let document = editor.active_document();
+		let visible_bounds = document.graphene_document.viewport_bounding_box(&visible_path).unwrap().unwrap();
+		let hidden_bounds = document.graphene_document.viewport_bounding_box(&hidden_path).unwrap().unwrap();
+
+		assert_eq!(document.selected_visible_layers_bounding_box(), Some(visible_bounds));
+		assert_eq!(document.layer_viewport_bounding_box(&folder_path, false), Some(visible_bounds));
+		assert_eq!(document.document_bounds(), Some(visible_bounds));
+
+		let full_bounds = document.graphene_document.viewport_bounding_box(&folder_path).unwrap();
+		assert_eq!(full_bounds, Some([visible_bounds[0].min(hidden_bounds[0]), visible_bounds[1].max(hidden_bounds[1])]));
+	}
+
+	#[test]
+	/// - draw a rect with a drag that passes through several pointer positions, and another with a single move straight to the same end
+	/// - assert that the long drag saved only one undo step and ended with the same transform as the single move
+	fn dragging_through_many_positions_keeps_one_undo_step() {
+		use crate::input::input_preprocessor::ModifierKeys;
+		use crate::input::mouse::MouseKeys;
+
+		fn rect_transform(editor: &Editor) -> DAffine2 {
+			let document = &editor.active_document().graphene_document;
+			let rect_id = editor.last_layer_id();
+			document.layer(&[rect_id]).unwrap().transform
+		}
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+		editor.select_tool(ToolType::Rectangle);
+
+		let modifier_keys = ModifierKeys::default();
+		editor.move_mouse(10., 10.);
+		editor.pointer_down((10., 10.), MouseKeys::LEFT, modifier_keys);
+		for (x, y) in [(40., 30.), (150., 20.), (-30., 120.), (70., 50.), (90., 80.)] {
+			editor.pointer_move((x, y), MouseKeys::LEFT, modifier_keys);
+		}
+		editor.pointer_up((90., 80.), MouseKeys::empty(), modifier_keys);
+
+		let document = editor.active_document();
+		assert_eq!(document.document_undo_history.len(), 1);
+		let dragged_transform = rect_transform(&editor);
+
+		let mut single_move_editor = Editor::new();
+		single_move_editor.drag_tool(ToolType::Rectangle, 10., 10., 90., 80.);
+		assert_eq!(dragged_transform, rect_transform(&single_move_editor));
+
+		editor.handle_message(DocumentMessage::Undo);
+		let document = editor.active_document();
+		assert!(document.graphene_document.root.as_folder().unwrap().layer_ids.is_empty());
+	}
+
+	#[test]
+	/// - create rect, shape and ellipse
+	/// - copy the rect, then paste it in place of the shape
+	/// - assert that the rect copy took the shape's place in the stack, centered on where the shape was, in a single undo step
+	/// - assert that with nothing selected it pastes normally
+	fn paste_replace_swaps_selected_layers() {
+		init_logger();
+		let mut editor = create_editor_with_three_layers();
+
+		let document = editor.active_document();
+		let layer_ids = editor.root_layer_ids();
+		let rect_layer = document.graphene_document.layer(&[layer_ids[0]]).unwrap().clone();
+		let [shape_min, shape_max] = document.graphene_document.viewport_bounding_box(&[layer_ids[1]]).unwrap().unwrap();
+		let undo_steps = document.document_undo_history.len();
+
+		editor.handle_message(DocumentMessage::SetSelectedLayers {
+			replacement_selected_layers: vec![vec![layer_ids[0]]],
+		});
+		editor.handle_message(PortfolioMessage::Copy { clipboard: Clipboard::User });
+		editor.handle_message(DocumentMessage::SetSelectedLayers {
+			replacement_selected_layers: vec![vec![layer_ids[1]]],
+		});
+		editor.handle_message(PortfolioMessage::PasteReplace { clipboard: Clipboard::User });
+
+		let document = editor.active_document();
+		let new_layer_ids = editor.root_layer_ids();
+		assert_eq!(new_layer_ids.len(), 3);
+		assert_eq!((new_layer_ids[0], new_layer_ids[2]), (layer_ids[0], layer_ids[2]));
+		assert!(!new_layer_ids.contains(&layer_ids[1]));
+		assert_eq!(document.selected_layers().collect::<Vec<_>>(), vec![&[new_layer_ids[1]][..]]);
+
+		let pasted_layer = document.graphene_document.layer(&[new_layer_ids[1]]).unwrap();
+		assert_eq!(pasted_layer.data, rect_layer.data);
+		let [pasted_min, pasted_max] = document.graphene_document.viewport_bounding_box(&[new_layer_ids[1]]).unwrap().unwrap();
+		assert!(((pasted_min + pasted_max) / 2.).distance((shape_min + shape_max) / 2.) < 1e-10);
+		assert_eq!(document.document_undo_history.len(), undo_steps + 1);
+
+		editor.handle_message(DocumentMessage::Undo);
+		let document = editor.active_document();
+		assert_eq!(document.graphene_document.root.as_folder().unwrap().layer_ids, layer_ids);
+
+		editor.handle_message(DocumentMessage::DeselectAllLayers);
+		editor.handle_message(PortfolioMessage::PasteReplace { clipboard: Clipboard::User });
+		let document = editor.active_document();
+		let layers = document.graphene_document.root.as_folder().unwrap().layers();
+		assert_eq!(layers.len(), 4);
+		assert_eq!(layers[3], rect_layer);
+	}
+
+	#[test]
+	/// - create rect, shape and ellipse
+	/// - select the shape, which the ellipse overlaps, and copy it as SVG
+	/// - assert that the SVG only holds the shape and its view box fits the shape's bounds
+	/// - assert that nothing is copied without a selection
+	fn selection_exports_to_svg_without_other_layers() {
+		use crate::document::utility_types::ExportScope;
+
+		init_logger();
+		let mut editor = create_editor_with_three_layers();
+
+		fn copied_svg(responses: Vec<FrontendMessage>) -> Option<String> {
+			responses.into_iter().find_map(|message| match message {
+				FrontendMessage::TriggerTextCopy { copy_text } => Some(copy_text),
+				_ => None,
+			})
+		}
+
+		let document = editor.active_document();
+		let shape_id = document.graphene_document.root.as_folder().unwrap().layer_ids[1];
+		let [min, max] = document.graphene_document.viewport_bounding_box(&[shape_id]).unwrap().unwrap();
+
+		editor.handle_message(DocumentMessage::SetSelectedLayers {
+			replacement_selected_layers: vec![vec![shape_id]],
+		});
+		let svg = copied_svg(editor.handle_message(DocumentMessage::CopyAsSvg { scope: ExportScope::Selection })).unwrap();
+
+		let size = max - min;
+		assert!(svg.contains(&format!(r#"viewBox="{} {} {} {}""#, min.x, min.y, size.x, size.y)));
+		assert!(svg.contains(&Color::BLUE.rgb_hex()));
+		assert!(!svg.contains(&Color::RED.rgb_hex()));
+		assert!(!svg.contains(&Color::GREEN.rgb_hex()));
+
+		editor.handle_message(DocumentMessage::DeselectAllLayers);
+		assert_eq!(copied_svg(editor.handle_message(DocumentMessage::CopyAsSvg { scope: ExportScope::Selection })), None);
+	}
+
+	#[test]
+	/// - start drawing an ellipse from its center
+	/// - assert that the readout next to the cursor shows the doubled width and height
+	/// - finish the drag and assert that the readout is gone
+	fn shape_readout_shows_size_while_drawing() {
+		use crate::input::input_preprocessor::ModifierKeys;
+		use crate::input::mouse::MouseKeys;
+
+		init_logger();
+		let mut editor = Editor::new();
+
+		fn readouts(editor: &Editor) -> Vec<String> {
+			let document = editor.active_document();
+			let folder = document.overlays_message_handler.overlays_graphene_document.root.as_folder().unwrap();
+			folder.layers().iter().filter_map(|layer| layer.as_text().ok()).map(|text| text.text.clone()).collect()
+		}
+
+		editor.select_tool(ToolType::Ellipse);
+		let modifier_keys = ModifierKeys::ALT;
+		editor.pointer_move((100., 100.), MouseKeys::empty(), modifier_keys);
+		editor.pointer_down((100., 100.), MouseKeys::LEFT, modifier_keys);
+		editor.pointer_move((160., 140.), MouseKeys::LEFT, modifier_keys);
+		assert_eq!(readouts(&editor), vec!["120 × 80".to_string()]);
+
+		editor.pointer_up((160., 140.), MouseKeys::empty(), modifier_keys);
+		assert!(readouts(&editor).is_empty());
+	}
+
+	#[test]
+	/// - add two artboards and draw two rects over the second one
+	/// - center the rects vertically, then fully, on their artboard
+	/// - assert that the rects keep their offset from each other and end up centered on the artboard they're over
+	fn selection_centers_on_nearest_artboard() {
+		use crate::document::utility_types::AlignAxis;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		editor.handle_message(ArtboardMessage::AddArtboard {
+			id: None,
+			position: (0., 0.),
+			size: (100., 100.),
+		});
+		editor.handle_message(ArtboardMessage::AddArtboard {
+			id: None,
+			position: (200., 0.),
+			size: (400., 300.),
+		});
+		editor.draw_rect(210., 10., 230., 30.);
+		editor.draw_rect(250., 20., 260., 40.);
+		editor.handle_message(DocumentMessage::SelectAllLayers);
+
+		fn selection_bounds(editor: &Editor) -> Vec<[DVec2; 2]> {
+			let document = &editor.active_document().graphene_document;
+			let layer_ids = &document.root.as_folder().unwrap().layer_ids;
+			layer_ids.iter().map(|&id| document.viewport_bounding_box(&[id]).unwrap().unwrap()).collect()
+		}
+		let before = selection_bounds(&editor);
+		let combined_center = (before[0][0].min(before[1][0]) + before[0][1].max(before[1][1])) / 2.;
+		let shifted = |bounds: &[[DVec2; 2]], offset: DVec2| -> Vec<[DVec2; 2]> { bounds.iter().map(|[min, max]| [*min + offset, *max + offset]).collect() };
+
+		// The selection is over the second artboard, whose center is at (400, 150)
+		editor.handle_message(DocumentMessage::CenterOnArtboard { axis: Some(AlignAxis::Y) });
+		let vertical = selection_bounds(&editor);
+		assert_eq!(vertical, shifted(&before, DVec2::new(0., 150. - combined_center.y)));
+
+		editor.handle_message(DocumentMessage::CenterOnArtboard { axis: None });
+		assert_eq!(selection_bounds(&editor), shifted(&vertical, DVec2::new(400. - combined_center.x, 0.)));
+
+		editor.handle_message(DocumentMessage::Undo);
+		assert_eq!(selection_bounds(&editor), vertical);
+	}
+
+	#[test]
+	/// - add an artboard, draw a rect over it and another far away from it, and hide the far one
+	/// - select both rects and center them on the artboard
+	/// - assert that the visible rect ends up centered on the artboard, with the hidden one moved along with it
+	fn centering_on_artboard_ignores_hidden_layers() {
+		use crate::document::utility_types::AlignAxis;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		editor.handle_message(ArtboardMessage::AddArtboard {
+			id: None,
+			position: (0., 0.),
+			size: (200., 200.),
+		});
+		editor.draw_rect(10., 10., 50., 50.);
+		editor.draw_rect(1000., 1000., 1100., 1100.);
+		let [visible_id, hidden_id] = [editor.root_layer_ids()[0], editor.last_layer_id()];
+		editor.handle_message(DocumentMessage::ToggleLayerVisibility { layer_path: vec![hidden_id] });
+		editor.handle_message(DocumentMessage::SelectAllLayers);
+
+		let bounds = |editor: &Editor, id: LayerId| editor.active_document().graphene_document.viewport_bounding_box(&[id]).unwrap().unwrap();
+		let hidden_before = bounds(&editor, hidden_id);
+
+		editor.handle_message(DocumentMessage::CenterOnArtboard { axis: None });
+		assert_eq!(bounds(&editor, visible_id), [DVec2::new(80., 80.), DVec2::new(120., 120.)]);
+		let offset = DVec2::new(70., 70.);
+		assert_eq!(bounds(&editor, hidden_id), [hidden_before[0] + offset, hidden_before[1] + offset]);
+
+		editor.handle_message(DocumentMessage::CenterOnArtboard { axis: Some(AlignAxis::X) });
+		assert_eq!(bounds(&editor, visible_id), [DVec2::new(80., 80.), DVec2::new(120., 120.)]);
+	}
+
+	#[test]
+	/// - draw an ellipse and edit it with the Path tool set to only show the handles of selected points
+	/// - assert that the handles are hidden until a point is selected, which reveals its own handles
+	/// - switch back to showing all handles and assert that none are hidden
+	fn path_tool_shows_only_selected_handles() {
+		use crate::input::mouse::{EditorMouseState, MouseKeys, ScrollDelta};
+		use crate::viewport_tools::tools::path::{PathMessage, PathOptionsUpdate};
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+		editor.draw_ellipse(0., 0., 100., 100.);
+
+		fn hidden_overlays(editor: &Editor) -> usize {
+			let document = editor.active_document();
+			let folder = document.overlays_message_handler.overlays_graphene_document.root.as_folder().unwrap();
+			folder.layers().iter().filter(|layer| !layer.visible).count()
+		}
+
+		editor.select_tool(ToolType::Path);
+		assert_eq!(hidden_overlays(&editor), 0);
+
+		editor.handle_message(PathMessage::UpdateOptions(PathOptionsUpdate::ShowAllHandles(false)));
+		let all_hidden = hidden_overlays(&editor);
+		assert!(all_hidden > 0);
+
+		// The ellipse's bottommost point, which is smooth and so has two handles
+		let document = &editor.active_document().graphene_document;
+		let ellipse_id = editor.last_layer_id();
+		let anchor = document.multiply_transforms(&[ellipse_id]).unwrap().transform_point2(DVec2::new(0.5, 1.));
+		editor.move_mouse(anchor.x, anchor.y);
+		editor.lmb_mousedown(anchor.x, anchor.y);
+		editor.mouseup(EditorMouseState {
+			editor_position: anchor,
+			mouse_keys: MouseKeys::empty(),
+			scroll_delta: ScrollDelta::default(),
+			pressure: None,
+		});
+		assert!(hidden_overlays(&editor) < all_hidden, "Selecting a point should reveal its handles");
+
+		editor.handle_message(PathMessage::UpdateOptions(PathOptionsUpdate::ShowAllHandles(true)));
+		assert_eq!(hidden_overlays(&editor), 0);
+	}
+
+	#[test]
+	/// - draw a line with an arrowhead at its end and a circle at its start
+	/// - assert that the line and its caps are grouped, with the arrow's tip on the end of the line and the circle centered on its start
+	/// - assert that the line stops at the base of the arrowhead
+	fn line_tool_draws_caps() {
+		use crate::viewport_tools::tools::line::{LineCap, LineMessage, LineOptionsUpdate};
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		editor.select_tool(ToolType::Line);
+		editor.handle_message(LineMessage::UpdateOptions(LineOptionsUpdate::LineWeight(4)));
+		editor.handle_message(LineMessage::UpdateOptions(LineOptionsUpdate::StartCap(LineCap::Circle)));
+		editor.handle_message(LineMessage::UpdateOptions(LineOptionsUpdate::EndCap(LineCap::Arrow)));
+		editor.drag_tool(ToolType::Line, 100., 200., 100., 100.);
+
+		let document = &editor.active_document().graphene_document;
+		let root_ids = &document.root.as_folder().unwrap().layer_ids;
+		assert_eq!(root_ids.len(), 1);
+		let folder_id = root_ids[0];
+		let layer_ids = &document.folder(&[folder_id]).unwrap().layer_ids;
+		assert_eq!(layer_ids.len(), 3, "The folder should hold the line and both of its caps");
+		let paths: Vec<Vec<LayerId>> = layer_ids.iter().map(|&id| vec![folder_id, id]).collect();
+
+		// The line is drawn upward, and an arrowhead for a weight of 4 is 12 long
+		let line_transform = document.multiply_transforms(&paths[0]).unwrap();
+		assert!(line_transform.transform_point2(DVec2::ZERO).abs_diff_eq(DVec2::new(100., 200.), 1e-10));
+		assert!(line_transform.transform_point2(DVec2::X).abs_diff_eq(DVec2::new(100., 112.), 1e-10));
+
+		let [circle_min, circle_max] = document.viewport_bounding_box(&paths[1]).unwrap().unwrap();
+		assert!(((circle_min + circle_max) / 2.).abs_diff_eq(DVec2::new(100., 200.), 1e-10));
+		assert!((circle_max - circle_min).abs_diff_eq(DVec2::splat(8.), 1e-10));
+
+		let [arrow_min, arrow_max] = document.viewport_bounding_box(&paths[2]).unwrap().unwrap();
+		assert!(arrow_min.abs_diff_eq(DVec2::new(94., 100.), 1e-10));
+		assert!(arrow_max.abs_diff_eq(DVec2::new(106., 112.), 1e-10));
+	}
+
+	#[test]
+	/// - draw a dashed line, then switch back to solid and draw another
+	/// - assert that the first line's stroke is dashed in multiples of the weight and renders a dash array, and the second is solid
+	fn line_tool_draws_dashed_lines() {
+		use crate::viewport_tools::tools::line::{DashPreset, LineMessage, LineOptionsUpdate};
+		use graphene::layers::layer_info::LayerDataType;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		editor.select_tool(ToolType::Line);
+		editor.handle_message(LineMessage::UpdateOptions(LineOptionsUpdate::LineWeight(4)));
+		editor.handle_message(LineMessage::UpdateOptions(LineOptionsUpdate::DashPreset(DashPreset::Dashed)));
+		editor.drag_tool(ToolType::Line, 0., 0., 100., 0.);
+		editor.handle_message(LineMessage::UpdateOptions(LineOptionsUpdate::DashPreset(DashPreset::Solid)));
+		editor.drag_tool(ToolType::Line, 0., 50., 100., 50.);
+
+		let document = &editor.active_document().graphene_document;
+		let strokes: Vec<Stroke> = document
+			.root
+			.as_folder()
+			.unwrap()
+			.layers()
+			.iter()
+			.map(|layer| match &layer.data {
+				LayerDataType::Shape(shape) => shape.style.stroke().unwrap(),
+				_ => panic!("The Line tool should draw a shape"),
+			})
+			.collect();
+
+		assert_eq!(strokes[0].dash_pattern(), &[12., 8.]);
+		assert!(strokes[0].render().contains(r#"stroke-dasharray="12 8""#));
+		assert!(strokes[1].dash_pattern().is_empty());
+		assert!(!strokes[1].render().contains("stroke-dasharray"));
+	}
+
+	#[test]
+	/// - draw a line, then set its angle to 90° and its length to 50 in the Line tool's inputs
+	/// - assert that the line keeps its start and points straight up with the new length
+	fn line_tool_sets_exact_length_and_angle() {
+		use crate::viewport_tools::tools::line::LineMessage;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		editor.drag_tool(ToolType::Line, 10., 10., 110., 10.);
+		editor.handle_message(LineMessage::SetDimensions { length: None, angle: Some(90.) });
+		editor.handle_message(LineMessage::SetDimensions { length: Some(50.), angle: None });
+
+		let document = &editor.active_document().graphene_document;
+		let line = document.root.as_folder().unwrap().layers()[0].transform;
+		let start = line.transform_point2(DVec2::ZERO);
+		let end = line.transform_point2(DVec2::X);
+
+		// The line keeps its start, and points straight up, since the Y axis points down
+		assert!(start.abs_diff_eq(DVec2::new(10., 10.), 1e-6), "The line should start at (10, 10), but starts at {}", start);
+		assert!(end.abs_diff_eq(DVec2::new(10., -40.), 1e-6), "The line should end at (10, -40), but ends at {}", end);
+	}
+
+	#[test]
+	/// - draw two chained lines in polyline mode with the Line tool, panning the canvas between them, then end the chain with Escape
+	/// - start another chain and abort it
+	/// - assert that only the first chain is kept, as a single path through all its points, which stayed put in the document when the canvas was panned
+	fn line_tool_chains_lines_into_a_polyline() {
+		use crate::input::keyboard::Key;
+		use crate::viewport_tools::tools::line::{LineMessage, LineOptionsUpdate};
+		use graphene::layers::layer_info::LayerDataType;
+		use kurbo::PathEl;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		editor.select_tool(ToolType::Line);
+		editor.handle_message(LineMessage::UpdateOptions(LineOptionsUpdate::Polyline(true)));
+		let expected = [(10., 10.), (110., 10.), (110., 110.)].map(|(x, y)| editor.active_document().viewport_to_document(DVec2::new(x, y)));
+		editor.drag_tool(ToolType::Line, 10., 10., 110., 10.);
+		editor.handle_message(MovementMessage::TranslateCanvas { delta: DVec2::new(50., 30.) });
+		let corner = editor.active_document().document_to_viewport(expected[2]);
+		editor.drag_tool(ToolType::Line, 0., 0., corner.x, corner.y);
+		let modifier_keys = ModifierKeys::empty();
+		editor.input(InputPreprocessorMessage::KeyDown { key: Key::KeyEscape, modifier_keys });
+
+		editor.drag_tool(ToolType::Line, 200., 200., 300., 200.);
+		editor.handle_message(LineMessage::Abort);
+
+		let document = &editor.active_document().graphene_document;
+		let layers = document.root.as_folder().unwrap().layers();
+		assert_eq!(layers.len(), 1, "Only the finished chain should be kept");
+		let shape = match &layers[0].data {
+			LayerDataType::Shape(shape) => shape,
+			_ => panic!("The Line tool should draw a shape"),
+		};
+		let transform = layers[0].transform;
+		let document_points: Vec<DVec2> = shape
+			.path
+			.elements()
+			.iter()
+			.filter_map(|element| match element {
+				PathEl::MoveTo(point) | PathEl::LineTo(point) => Some(transform.transform_point2(DVec2::new(point.x, point.y))),
+				_ => None,
+			})
+			.collect();
+
+		assert_eq!(document_points.len(), expected.len());
+		assert!(document_points.iter().zip(expected).all(|(point, expected)| point.distance(expected) < 1e-6));
+	}
+
+	#[test]
+	/// - draw a rectangle, then draw lines ending near its corner, with the default and a smaller anchor snap tolerance
+	/// - assert that the end of the line snaps onto the corner only within the tolerance, and its start stays put
+	fn line_tool_snaps_to_anchor_points() {
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+		editor.draw_rect(0., 0., 100., 100.);
+
+		fn last_line_ends(editor: &Editor) -> (DVec2, DVec2) {
+			let document = &editor.active_document().graphene_document;
+			let transform = document.root.transform * editor.last_layer().transform;
+			(transform.transform_point2(DVec2::ZERO), transform.transform_point2(DVec2::X))
+		}
+
+		editor.drag_tool(ToolType::Line, 200., 200., 103., 104.);
+		let (start, end) = last_line_ends(&editor);
+		assert!(start.abs_diff_eq(DVec2::new(200., 200.), 1e-6), "The start of the line should stay put, but is at {}", start);
+		assert!(end.abs_diff_eq(DVec2::new(100., 100.), 1e-6), "The end of the line should snap onto the corner, but is at {}", end);
+
+		editor.handle_message(GlobalMessage::SetAnchorSnapTolerance { tolerance: 2. });
+		editor.drag_tool(ToolType::Line, 200., 200., 103., 104.);
+		let (_, end) = last_line_ends(&editor);
+		assert!(end.distance(DVec2::new(100., 100.)) > 1., "The end of the line should snap only within the tolerance, not to {}", end);
+	}
+
+	#[test]
+	/// - draw a pie slice with the Ellipse tool, then drag the handle on the end of its arc to the left side of the ellipse
+	/// - assert that the slice keeps its start angle while its sweep shrinks from three quarters to half a turn
+	fn ellipse_tool_drags_arc_end_handle() {
+		use crate::viewport_tools::tools::ellipse::{EllipseMessage, EllipseMode, EllipseOptionsUpdate};
+		use graphene::layers::layer_info::LayerDataType;
+		use graphene::layers::simple_shape::EllipseArc;
+		use std::f64::consts::PI;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		fn last_arc(editor: &Editor) -> EllipseArc {
+			match &editor.last_layer().data {
+				LayerDataType::Shape(shape) => shape.arc().expect("The Ellipse tool should draw an arc in Pie mode"),
+				_ => panic!("The Ellipse tool should draw a shape"),
+			}
+		}
+
+		editor.select_tool(ToolType::Ellipse);
+		editor.handle_message(EllipseMessage::UpdateOptions(EllipseOptionsUpdate::Mode(EllipseMode::Pie)));
+		editor.drag_tool(ToolType::Ellipse, 0., 0., 100., 100.);
+		let arc = last_arc(&editor);
+		assert_eq!(arc.start_angle, 0.);
+		assert!((arc.sweep_angle - 1.5 * PI).abs() < 1e-6, "The slice should sweep the default 270°, not {}", arc.sweep_angle);
+
+		// The default end angle of 270° puts the end handle at the top of the ellipse
+		editor.drag_tool(ToolType::Ellipse, 50., 0., 0., 50.);
+		let document = &editor.active_document().graphene_document;
+		assert_eq!(document.root.as_folder().unwrap().layers().len(), 1, "Dragging a handle shouldn't draw another ellipse");
+		let arc = last_arc(&editor);
+		assert_eq!(arc.start_angle, 0.);
+		assert!((arc.sweep_angle - PI).abs() < 1e-6, "The end should follow the handle, but the sweep is {}", arc.sweep_angle);
+	}
+
+	#[test]
+	/// - draw a rect, then with Alt and Shift held drag out an ellipse from a point beside it, ending just short of the rect's right edge
+	/// - assert that the ellipse is a circle centered on where the drag began, sized by the snapped pointer position
+	fn ellipse_tool_draws_centered_circle_with_alt_and_shift() {
+		use crate::input::input_preprocessor::ModifierKeys;
+		use crate::input::mouse::MouseKeys;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+		editor.draw_rect(0., 0., 100., 100.);
+
+		editor.select_tool(ToolType::Ellipse);
+		let modifier_keys = ModifierKeys::ALT | ModifierKeys::SHIFT;
+		editor.pointer_move((200., 200.), MouseKeys::empty(), modifier_keys);
+		editor.pointer_down((200., 200.), MouseKeys::LEFT, modifier_keys);
+		// The pointer snaps onto the rect's right edge at x = 100, making the circle's radius 100 rather than 98
+		editor.pointer_move((102., 230.), MouseKeys::LEFT, modifier_keys);
+		editor.pointer_up((102., 230.), MouseKeys::empty(), modifier_keys);
+
+		let document = &editor.active_document().graphene_document;
+		let ellipse = editor.last_layer();
+		let transform = document.root.transform * ellipse.transform;
+		let center = transform.transform_point2(DVec2::splat(0.5));
+		let size = (transform.transform_point2(DVec2::ONE) - transform.transform_point2(DVec2::ZERO)).abs();
+		assert!(center.abs_diff_eq(DVec2::new(200., 200.), 1e-6), "The circle should be centered on the drag start, not {}", center);
+		assert!(size.abs_diff_eq(DVec2::splat(200.), 1e-6), "The circle should have a diameter of 200, not {}", size);
+	}
+
+	#[test]
+	/// - draw a 50×30 ellipse, then enter a width and height of 100 for it
+	/// - assert that it becomes a 100×100 circle with its top left corner where it was
+	fn ellipse_tool_sets_exact_size() {
+		use crate::viewport_tools::tools::ellipse::EllipseMessage;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		editor.drag_tool(ToolType::Ellipse, 10., 20., 60., 50.);
+		editor.handle_message(EllipseMessage::SetDimensions { width: Some(100.), height: None });
+		editor.handle_message(EllipseMessage::SetDimensions { width: None, height: Some(100.) });
+
+		let document = &editor.active_document().graphene_document;
+		let ellipse = editor.last_layer();
+		let transform = document.root.transform * ellipse.transform;
+		let (top_left, bottom_right) = (transform.transform_point2(DVec2::ZERO), transform.transform_point2(DVec2::ONE));
+		assert!(top_left.abs_diff_eq(DVec2::new(10., 20.), 1e-6), "The top left corner should stay put, but is at {}", top_left);
+		assert!(bottom_right.abs_diff_eq(DVec2::new(110., 120.), 1e-6), "The ellipse should be 100×100, but ends at {}", bottom_right);
+	}
+
+	#[test]
+	/// - lock the Ellipse tool's aspect ratio to 2, then on a zoomed and rotated canvas drag out an ellipse wider than it is tall
+	/// - assert that in the document the ellipse is exactly twice as wide as it is tall, sized by the dragged width
+	fn ellipse_tool_locks_aspect_ratio_in_document_space() {
+		use crate::viewport_tools::tools::ellipse::{EllipseMessage, EllipseOptionsUpdate};
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		editor.handle_message(MovementMessage::SetCanvasZoom { zoom_factor: 2. });
+		editor.handle_message(MovementMessage::SetCanvasRotationDegrees { angle_degrees: 30. });
+		editor.select_tool(ToolType::Ellipse);
+		editor.handle_message(EllipseMessage::UpdateOptions(EllipseOptionsUpdate::Ratio(2.)));
+		editor.handle_message(EllipseMessage::UpdateOptions(EllipseOptionsUpdate::LockRatio(true)));
+		editor.drag_tool(ToolType::Ellipse, 300., 300., 500., 340.);
+
+		let document = &editor.active_document().graphene_document;
+		let ellipse = editor.last_layer();
+		let size = DVec2::new(ellipse.transform.matrix2.x_axis.length(), ellipse.transform.matrix2.y_axis.length());
+		assert!(size.abs_diff_eq(DVec2::new(100., 50.), 1e-6), "The ellipse should be 100×50 in the document, not {}", size);
+	}
+
+	#[test]
+	/// - draw a rect, then drag the handle inside its top left corner diagonally inwards, and then past the middle
+	/// - assert that all its corners are rounded by how far the handle moved, up to half the rect's side
+	fn rectangle_tool_drags_corner_radius_handle() {
+		use graphene::layers::layer_info::LayerDataType;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		fn corner_radii(editor: &Editor) -> Option<[f64; 4]> {
+			match &editor.last_layer().data {
+				LayerDataType::Shape(shape) => shape.corner_radii(),
+				_ => panic!("The Rectangle tool should draw a shape"),
+			}
+		}
+
+		editor.drag_tool(ToolType::Rectangle, 0., 0., 100., 100.);
+		assert_eq!(corner_radii(&editor), Some([0.; 4]));
+
+		// Without rounding, the handle is inset from the corner so it can be grabbed
+		editor.drag_tool(ToolType::Rectangle, 12., 12., 30., 30.);
+		let document = &editor.active_document().graphene_document;
+		assert_eq!(document.root.as_folder().unwrap().layers().len(), 1, "Dragging the handle shouldn't draw another rect");
+		let radii = corner_radii(&editor).unwrap();
+		assert!(radii.iter().all(|radius| (radius - 30.).abs() < 1e-6), "The corners should follow the handle, not be {:?}", radii);
+
+		editor.drag_tool(ToolType::Rectangle, 30., 30., 90., 90.);
+		let radii = corner_radii(&editor).unwrap();
+		assert!(radii.iter().all(|radius| (radius - 50.).abs() < 1e-6), "The radius should stop at half the side, not be {:?}", radii);
+	}
+
+	#[test]
+	/// - with independent corners, round only the top two corners of a new rect, then switch back to a single radius
+	/// - assert that only the top corners get curves, and that the single radius then applies to all four corners of the selected rect
+	fn rectangle_tool_rounds_corners_independently() {
+		use crate::viewport_tools::tools::rectangle::{RectangleMessage, RectangleOptionsUpdate};
+		use graphene::layers::layer_info::LayerDataType;
+		use kurbo::PathEl;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		fn corners(editor: &Editor) -> ([f64; 4], usize) {
+			match &editor.last_layer().data {
+				LayerDataType::Shape(shape) => {
+					let curves = shape.path.elements().iter().filter(|element| matches!(element, PathEl::CurveTo(..))).count();
+					(shape.corner_radii().unwrap(), curves)
+				}
+				_ => panic!("The Rectangle tool should draw a shape"),
+			}
+		}
+
+		editor.select_tool(ToolType::Rectangle);
+		editor.handle_message(RectangleMessage::UpdateOptions(RectangleOptionsUpdate::IndependentCorners(true)));
+		editor.handle_message(RectangleMessage::UpdateOptions(RectangleOptionsUpdate::IndependentCornerRadius { corner: 0, radius: 20. }));
+		editor.handle_message(RectangleMessage::UpdateOptions(RectangleOptionsUpdate::IndependentCornerRadius { corner: 1, radius: 20. }));
+		editor.drag_tool(ToolType::Rectangle, 0., 0., 100., 100.);
+		assert_eq!(corners(&editor), ([20., 20., 0., 0.], 2));
+
+		editor.handle_message(RectangleMessage::UpdateOptions(RectangleOptionsUpdate::IndependentCorners(false)));
+		editor.handle_message(RectangleMessage::UpdateOptions(RectangleOptionsUpdate::CornerRadius(10.)));
+		assert_eq!(corners(&editor), ([10.; 4], 4));
+	}
+
+	#[test]
+	/// - draw a rect and type in a new position and size for it, then draw another and give both selected rects a new width
+	/// - assert that the rect's top left corner and size in the document match what was typed, and that the width applies to both rects
+	fn rectangle_tool_sets_exact_bounds() {
+		use crate::viewport_tools::tools::rectangle::RectangleMessage;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		fn corners(editor: &Editor) -> Vec<(DVec2, DVec2)> {
+			let document = &editor.active_document().graphene_document;
+			let layers = document.root.as_folder().unwrap().layers();
+			layers
+				.iter()
+				.map(|layer| (layer.transform.transform_point2(DVec2::ZERO), layer.transform.transform_point2(DVec2::ONE)))
+				.collect()
+		}
+
+		editor.drag_tool(ToolType::Rectangle, 10., 20., 60., 50.);
+		editor.handle_message(RectangleMessage::SetBounds {
+			x: Some(100.),
+			y: Some(200.),
+			width: None,
+			height: None,
+		});
+		editor.handle_message(RectangleMessage::SetBounds {
+			x: None,
+			y: None,
+			width: Some(80.),
+			height: Some(40.),
+		});
+		let (top_left, bottom_right) = corners(&editor)[0];
+		assert!(top_left.abs_diff_eq(DVec2::new(100., 200.), 1e-6), "The rect should be moved to the typed position, not {}", top_left);
+		assert!(bottom_right.abs_diff_eq(DVec2::new(180., 240.), 1e-6), "The rect should be 80×40, but ends at {}", bottom_right);
+
+		editor.drag_tool(ToolType::Rectangle, 300., 300., 320., 320.);
+		editor.handle_message(DocumentMessage::SelectAllLayers);
+		editor.handle_message(RectangleMessage::SetBounds {
+			x: None,
+			y: None,
+			width: Some(30.),
+			height: None,
+		});
+		for (top_left, bottom_right) in corners(&editor) {
+			let width = bottom_right.x - top_left.x;
+			assert!((width - 30.).abs() < 1e-6, "Every selected rect should be 30 wide, not {}", width);
+		}
+	}
+
+	#[test]
+	/// - start drawing a rect, then hold Shift mid-drag, and then release it without letting go of the mouse
+	/// - assert that holding Shift updates the hints to show the square constraint and adds a guide overlay, and releasing it undoes both
+	fn rectangle_tool_shows_square_constraint() {
+		use crate::input::input_preprocessor::ModifierKeys;
+		use crate::input::mouse::{EditorMouseState, MouseKeys, ScrollDelta};
+
+		init_logger();
+		let mut editor = Editor::new();
+
+		fn shift_hint(responses: &[FrontendMessage]) -> Option<String> {
+			responses.iter().rev().find_map(|response| match response {
+				FrontendMessage::UpdateInputHints { hint_data } => hint_data.0.first().and_then(|group| group.0.first()).map(|hint| hint.label.clone()),
+				_ => None,
+			})
+		}
+		fn overlay_count(editor: &Editor) -> usize {
+			let document = editor.active_document();
+			document.overlays_message_handler.overlays_graphene_document.root.as_folder().unwrap().layers().len()
+		}
+
+		editor.select_tool(ToolType::Rectangle);
+		let pointer_move = |x: f64, y: f64, modifier_keys: ModifierKeys| InputPreprocessorMessage::PointerMove {
+			editor_mouse_state: EditorMouseState {
+				editor_position: (x, y).into(),
+				mouse_keys: MouseKeys::LEFT,
+				scroll_delta: ScrollDelta::default(),
+				pressure: None,
+			},
+			modifier_keys,
+		};
+		editor.move_mouse(100., 100.);
+		editor.lmb_mousedown(100., 100.);
+		editor.input(pointer_move(160., 130., ModifierKeys::empty()));
+		let overlays = overlay_count(&editor);
+
+		let responses = editor.handle_message(pointer_move(160., 130., ModifierKeys::SHIFT));
+		assert_eq!(shift_hint(&responses).as_deref(), Some("Constrained to Square"));
+		assert_eq!(overlay_count(&editor), overlays + 1, "The square constraint should be shown by a guide");
+
+		let responses = editor.handle_message(pointer_move(160., 130., ModifierKeys::empty()));
+		assert_eq!(shift_hint(&responses).as_deref(), Some("Constrain Square"));
+		assert_eq!(overlay_count(&editor), overlays, "The guide should be removed once Shift is released");
+	}
+
+	#[test]
+	/// - draw a shape with too few sides chosen, then choose too many sides while it's selected
+	/// - assert that the side count is clamped both times and that the selected shape is regenerated with the new count in its same layer transform
+	fn shape_tool_changes_sides_of_selected_polygon() {
+		use crate::viewport_tools::tools::shape::{ShapeMessage, ShapeOptionsUpdate};
+		use graphene::layers::layer_info::LayerDataType;
+		use graphene::layers::simple_shape::ShapeParameters;
+		use kurbo::PathEl;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		fn polygon(editor: &Editor) -> (Option<u8>, usize, DAffine2) {
+			let layer = editor.last_layer();
+			match &layer.data {
+				LayerDataType::Shape(shape) => {
+					let points = shape.path.elements().iter().filter(|element| matches!(element, PathEl::MoveTo(_) | PathEl::LineTo(_))).count();
+					let sides = match shape.parametric {
+						Some(ShapeParameters::Polygon { sides, .. }) => Some(sides),
+						_ => None,
+					};
+					(sides, points, layer.transform)
+				}
+				_ => panic!("The Shape tool should draw a shape"),
+			}
+		}
+
+		editor.select_tool(ToolType::Shape);
+		editor.handle_message(ShapeMessage::UpdateOptions(ShapeOptionsUpdate::Sides(1)));
+		editor.drag_tool(ToolType::Shape, 0., 0., 100., 100.);
+		let (sides, points, transform) = polygon(&editor);
+		assert_eq!((sides, points), (Some(3), 3));
+
+		editor.handle_message(ShapeMessage::UpdateOptions(ShapeOptionsUpdate::Sides(u8::MAX)));
+		assert_eq!(polygon(&editor), (Some(64), 64, transform));
+	}
+
+	#[test]
+	/// - draw a four-pointed star, then drag the handle on its first inner vertex towards its center
+	/// - assert that the star's points are centered in the drawn box and that its inner radius follows the handle
+	fn shape_tool_drags_star_inner_radius_handle() {
+		use crate::viewport_tools::tools::shape::{ShapeMessage, ShapeMode, ShapeOptionsUpdate};
+		use graphene::layers::layer_info::LayerDataType;
+		use graphene::layers::simple_shape::ShapeParameters;
+		use kurbo::PathEl;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		fn star(editor: &Editor) -> (f64, Vec<DVec2>) {
+			let document = &editor.active_document().graphene_document;
+			let layers = document.root.as_folder().unwrap().layers();
+			assert_eq!(layers.len(), 1, "Dragging the handle shouldn't draw another shape");
+			match &layers[0].data {
+				LayerDataType::Shape(shape) => {
+					let vertices = shape
+						.path
+						.elements()
+						.iter()
+						.filter_map(|element| match element {
+							PathEl::MoveTo(point) | PathEl::LineTo(point) => Some(layers[0].transform.transform_point2(DVec2::new(point.x, point.y))),
+							_ => None,
+						})
+						.collect();
+					let inner_radius = match shape.parametric {
+						Some(ShapeParameters::Polygon {
+							star_inner_radius: Some(inner_radius),
+							..
+						}) => inner_radius,
+						_ => panic!("The Shape tool should draw a star in Star mode"),
+					};
+					(inner_radius, vertices)
+				}
+				_ => panic!("The Shape tool should draw a shape"),
+			}
+		}
+
+		editor.select_tool(ToolType::Shape);
+		editor.handle_message(ShapeMessage::UpdateOptions(ShapeOptionsUpdate::Mode(ShapeMode::Star)));
+		editor.handle_message(ShapeMessage::UpdateOptions(ShapeOptionsUpdate::Sides(4)));
+		editor.drag_tool(ToolType::Shape, 0., 0., 100., 100.);
+		let (inner_radius, vertices) = star(&editor);
+		assert_eq!((inner_radius, vertices.len()), (0.5, 8));
+		let center = DVec2::splat(50.);
+		for point in vertices.iter().step_by(2) {
+			assert!((point.distance(center) - 50.).abs() < 1e-6, "Each point should touch the edge of the drawn box, not be at {}", point);
+		}
+
+		// The first inner vertex is a quarter of the way around, between the top and right points
+		let direction = DVec2::new(1., -1.).normalize();
+		let handle = center + direction * 25.;
+		let target = center + direction * 10.;
+		editor.drag_tool(ToolType::Shape, handle.x, handle.y, target.x, target.y);
+		let (inner_radius, vertices) = star(&editor);
+		assert!((inner_radius - 0.2).abs() < 1e-6, "The inner radius should follow the handle, not be {}", inner_radius);
+		assert!(vertices[1].distance(target) < 1e-6, "The inner vertex should be under the pointer, not at {}", vertices[1]);
+	}
+
+	#[test]
+	/// - draw a diamond with rounded corners, then a star with a corner radius too big for its edges, and give the star another point
+	/// - assert that the diamond's corners are cut back by exactly the radius and that the star's fillets meet in the middle of its edges without overlapping
+	fn shape_tool_rounds_polygon_and_star_corners() {
+		use crate::viewport_tools::tools::shape::{ShapeMessage, ShapeMode, ShapeOptionsUpdate};
+		use graphene::layers::layer_info::LayerDataType;
+		use kurbo::PathEl;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		/// The ends of each straight and curved segment of the last layer's outline in document space, marked by whether they end a curve
+		fn segment_ends(editor: &Editor) -> Vec<(DVec2, bool)> {
+			let layer = editor.last_layer();
+			let point = |point: &kurbo::Point| layer.transform.transform_point2(DVec2::new(point.x, point.y));
+			match &layer.data {
+				LayerDataType::Shape(shape) => shape
+					.path
+					.elements()
+					.iter()
+					.filter_map(|element| match element {
+						PathEl::MoveTo(end) | PathEl::LineTo(end) => Some((point(end), false)),
+						PathEl::CurveTo(_, _, end) => Some((point(end), true)),
+						_ => None,
+					})
+					.collect(),
+				_ => panic!("The Shape tool should draw a shape"),
+			}
+		}
+
+		editor.select_tool(ToolType::Shape);
+		editor.handle_message(ShapeMessage::UpdateOptions(ShapeOptionsUpdate::Sides(4)));
+		editor.handle_message(ShapeMessage::UpdateOptions(ShapeOptionsUpdate::CornerRadius(10.)));
+		editor.drag_tool(ToolType::Shape, 0., 0., 100., 100.);
+		let vertices = [DVec2::new(100., 50.), DVec2::new(50., 0.), DVec2::new(0., 50.), DVec2::new(50., 100.)];
+		let ends = segment_ends(&editor);
+		assert_eq!(ends.iter().filter(|(_, curve)| *curve).count(), 4);
+		for (end, _) in ends {
+			let distance = vertices.iter().map(|vertex| vertex.distance(end)).fold(f64::INFINITY, f64::min);
+			assert!((distance - 10.).abs() < 1e-6, "Each corner should be cut back by the radius, but {} is {} away", end, distance);
+		}
+
+		editor.handle_message(ShapeMessage::UpdateOptions(ShapeOptionsUpdate::Mode(ShapeMode::Star)));
+		editor.handle_message(ShapeMessage::UpdateOptions(ShapeOptionsUpdate::CornerRadius(1000.)));
+		editor.drag_tool(ToolType::Shape, 200., 0., 300., 100.);
+		editor.handle_message(ShapeMessage::UpdateOptions(ShapeOptionsUpdate::Sides(5)));
+		let ends = segment_ends(&editor);
+		assert_eq!(ends.iter().filter(|(_, curve)| *curve).count(), 10, "Both the outer and inner vertices should be rounded");
+		for pair in ends.windows(2) {
+			if let [(curve_end, true), (line_end, false)] = pair {
+				assert!(curve_end.distance(*line_end) < 1e-6, "The fillets should meet, not leave an edge to {}", line_end);
+			}
+		}
+	}
+
+	#[test]
+	/// - draw a triangle and turn it with the rotation option, then draw one with Shift held and another without
+	/// - assert that the triangle turns about its center, and that the next triangles keep the rotation, snapped to 15° only while Shift is held
+	fn shape_tool_rotates_polygons_about_their_centers() {
+		use crate::input::input_preprocessor::ModifierKeys;
+		use crate::input::mouse::MouseKeys;
+		use crate::viewport_tools::tools::shape::{ShapeMessage, ShapeOptionsUpdate};
+		use graphene::layers::layer_info::LayerDataType;
+		use graphene::layers::simple_shape::ShapeParameters;
+		use kurbo::PathEl;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		/// The rotation, center, and direction from the center to the first vertex of the last triangle
+		fn triangle(editor: &Editor) -> (f64, DVec2, DVec2) {
+			let layer = editor.last_layer();
+			match &layer.data {
+				LayerDataType::Shape(shape) => {
+					let vertices: Vec<_> = shape
+						.path
+						.elements()
+						.iter()
+						.filter_map(|element| match element {
+							PathEl::MoveTo(point) | PathEl::LineTo(point) => Some(layer.transform.transform_point2(DVec2::new(point.x, point.y))),
+							_ => None,
+						})
+						.collect();
+					let center = vertices.iter().fold(DVec2::ZERO, |sum, vertex| sum + *vertex) / vertices.len() as f64;
+					let rotation = match shape.parametric {
+						Some(ShapeParameters::Polygon { rotation, .. }) => rotation,
+						_ => panic!("The Shape tool should draw a polygon"),
+					};
+					(rotation, center, (vertices[0] - center).normalize())
+				}
+				_ => panic!("The Shape tool should draw a shape"),
+			}
+		}
+
+		editor.select_tool(ToolType::Shape);
+		editor.handle_message(ShapeMessage::UpdateOptions(ShapeOptionsUpdate::Sides(3)));
+		editor.drag_tool(ToolType::Shape, 0., 0., 100., 100.);
+		let (rotation, center, direction) = triangle(&editor);
+		assert_eq!(rotation, 0.);
+
+		editor.handle_message(ShapeMessage::UpdateOptions(ShapeOptionsUpdate::Rotation(10.)));
+		let (rotation, rotated_center, rotated_direction) = triangle(&editor);
+		assert!((rotation - 10_f64.to_radians()).abs() < 1e-9);
+		assert!(rotated_center.abs_diff_eq(center, 1e-6), "The triangle should turn about its center, not move to {}", rotated_center);
+		let turned = direction.angle_between(rotated_direction);
+		assert!((turned - 10_f64.to_radians()).abs() < 1e-6, "The first vertex should turn 10° clockwise, not {} radians", turned);
+
+		let modifier_keys = ModifierKeys::SHIFT;
+		editor.pointer_move((200., 0.), MouseKeys::empty(), modifier_keys);
+		editor.pointer_down((200., 0.), MouseKeys::LEFT, modifier_keys);
+		editor.pointer_move((300., 100.), MouseKeys::LEFT, modifier_keys);
+		editor.pointer_up((300., 100.), MouseKeys::empty(), modifier_keys);
+		let (rotation, ..) = triangle(&editor);
+		assert!((rotation - 15_f64.to_radians()).abs() < 1e-9, "Holding Shift should snap the rotation to 15°, not {} radians", rotation);
+
+		editor.drag_tool(ToolType::Shape, 0., 200., 100., 300.);
+		let (rotation, ..) = triangle(&editor);
+		assert!((rotation - 10_f64.to_radians()).abs() < 1e-9, "The rotation should carry over, not be {} radians", rotation);
+	}
+
+	#[test]
+	/// - click out a spline, double-clicking one of its points along the way, and double-click its first point, then draw another with the Closed option and end it with Enter
+	/// - assert that both splines are closed loops through their points with no corner where they close, so the double-click along the way placed a single point without ending the spline
+	fn spline_tool_closes_smooth_loops() {
+		use crate::input::input_preprocessor::ModifierKeys;
+		use crate::input::keyboard::Key;
+		use crate::viewport_tools::tools::spline::{SplineMessage, SplineOptionsUpdate};
+		use graphene::layers::layer_info::LayerDataType;
+		use kurbo::PathEl;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		/// Asserts that the last layer is a closed loop of curves through the given points that's smooth where it closes
+		fn assert_smooth_loop(editor: &Editor, points: &[DVec2]) {
+			let shape = match &editor.last_layer().data {
+				LayerDataType::Shape(shape) => shape,
+				_ => panic!("The Spline tool should draw a shape"),
+			};
+			assert!(shape.closed, "The spline should be closed so it's filled correctly");
+
+			let point = |point: &kurbo::Point| DVec2::new(point.x, point.y);
+			let elements = shape.path.elements();
+			assert_eq!(elements.len(), points.len() + 2, "The path should move to the first point, curve through the rest and back, and close");
+			let (first_handle, last_handle) = match (elements[1], elements[points.len()]) {
+				(PathEl::CurveTo(first_handle, _, _), PathEl::CurveTo(_, last_handle, end)) => {
+					assert!(point(&end).abs_diff_eq(points[0], 1e-6), "The last curve should end back at the first point");
+					(point(&first_handle), point(&last_handle))
+				}
+				_ => panic!("The spline should be made of curves"),
+			};
+			for (element, expected) in elements[1..=points.len()].iter().zip(points.iter().cycle().skip(1)) {
+				if let PathEl::CurveTo(_, _, end) = element {
+					assert!(point(end).abs_diff_eq(*expected, 1e-6), "The curves should pass through {}", expected);
+				}
+			}
+			let (into_start, out_of_start) = (points[0] - last_handle, first_handle - points[0]);
+			assert!(into_start.abs_diff_eq(out_of_start, 1e-6), "The loop shouldn't have a corner where it closes");
+		}
+
+		editor.select_tool(ToolType::Spline);
+		let points = [DVec2::new(0., 0.), DVec2::new(100., 0.), DVec2::new(100., 100.), DVec2::new(50., 150.)];
+		for point in &points[..3] {
+			editor.click(point.x, point.y);
+		}
+		editor.double_click(points[3].x, points[3].y);
+		editor.double_click(0., 0.);
+		assert_smooth_loop(&editor, &points);
+
+		editor.handle_message(SplineMessage::UpdateOptions(SplineOptionsUpdate::Closed(true)));
+		let points = [DVec2::new(300., 300.), DVec2::new(400., 350.), DVec2::new(350., 450.), DVec2::new(300., 400.)];
+		for point in points {
+			editor.click(point.x, point.y);
+		}
+		editor.input(InputPreprocessorMessage::KeyDown {
+			key: Key::KeyEnter,
+			modifier_keys: ModifierKeys::empty(),
+		});
+		assert_smooth_loop(&editor, &points);
+	}
+
+	#[test]
+	/// - draw a spline through three points, then set the tension of the selected spline to none and then to full
+	/// - assert that the points stay put while the handles collapse onto them with no tension, and reach twice as far with full tension
+	fn spline_tool_tension_moves_handles_of_selected_spline() {
+		use crate::input::input_preprocessor::ModifierKeys;
+		use crate::input::keyboard::Key;
+		use crate::input::mouse::{EditorMouseState, MouseKeys, ScrollDelta};
+		use crate::viewport_tools::tools::spline::{SplineMessage, SplineOptionsUpdate};
+		use graphene::layers::layer_info::LayerDataType;
+		use kurbo::PathEl;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		/// Each curve of the spline as its start point, its two handles, and its end point
+		fn curves(editor: &Editor) -> Vec<[DVec2; 4]> {
+			let shape = match &editor.last_layer().data {
+				LayerDataType::Shape(shape) => shape,
+				_ => panic!("The Spline tool should draw a shape"),
+			};
+			let point = |point: &kurbo::Point| DVec2::new(point.x, point.y);
+			let mut start = DVec2::ZERO;
+			let mut curves = Vec::new();
+			for element in shape.path.elements() {
+				match element {
+					PathEl::MoveTo(point_to) => start = point(point_to),
+					PathEl::CurveTo(handle_start, handle_end, end) => {
+						curves.push([start, point(handle_start), point(handle_end), point(end)]);
+						start = point(end);
+					}
+					_ => panic!("The spline should be made of curves"),
+				}
+			}
+			curves
+		}
+
+		editor.select_tool(ToolType::Spline);
+		for (x, y) in [(0., 0.), (100., 50.), (200., 0.)] {
+			editor.move_mouse(x, y);
+			editor.lmb_mousedown(x, y);
+			editor.mouseup(EditorMouseState {
+				editor_position: (x, y).into(),
+				mouse_keys: MouseKeys::empty(),
+				scroll_delta: ScrollDelta::default(),
+				pressure: None,
+			});
+		}
+		editor.input(InputPreprocessorMessage::KeyDown {
+			key: Key::KeyEnter,
+			modifier_keys: ModifierKeys::empty(),
+		});
+		let natural = curves(&editor);
+		assert_eq!(natural.len(), 2);
+
+		editor.handle_message(SplineMessage::UpdateOptions(SplineOptionsUpdate::Tension(0.)));
+		for ([start, handle_start, handle_end, end], [natural_start, .., natural_end]) in curves(&editor).into_iter().zip(&natural) {
+			assert!(start.abs_diff_eq(*natural_start, 1e-6) && end.abs_diff_eq(*natural_end, 1e-6), "The points shouldn't move");
+			assert!(handle_start.abs_diff_eq(start, 1e-6) && handle_end.abs_diff_eq(end, 1e-6), "No tension should give straight segments");
+		}
+
+		editor.handle_message(SplineMessage::UpdateOptions(SplineOptionsUpdate::Tension(1.)));
+		for ([start, handle_start, handle_end, end], [natural_start, natural_handle_start, natural_handle_end, natural_end]) in curves(&editor).into_iter().zip(&natural) {
+			assert!(start.abs_diff_eq(*natural_start, 1e-6) && end.abs_diff_eq(*natural_end, 1e-6), "The points shouldn't move");
+			assert!(handle_start.abs_diff_eq(start + 2. * (*natural_handle_start - start), 1e-6), "Full tension should reach twice as far");
+			assert!(handle_end.abs_diff_eq(end + 2. * (*natural_handle_end - end), 1e-6), "Full tension should reach twice as far");
+		}
+	}
+
+	#[test]
+	/// - draw a spline through three points, convert it to a path, change the tension, and then undo
+	/// - assert that the path keeps the curves as drawn, ignores the tension, and the undo brings back the spline in one step
+	fn spline_tool_converts_splines_to_paths() {
+		use crate::input::input_preprocessor::ModifierKeys;
+		use crate::input::keyboard::Key;
+		use crate::input::mouse::{EditorMouseState, MouseKeys, ScrollDelta};
+		use crate::viewport_tools::tools::spline::{SplineMessage, SplineOptionsUpdate};
+		use graphene::layers::layer_info::LayerDataType;
+		use graphene::layers::simple_shape::Shape;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		fn shape(editor: &Editor) -> Shape {
+			match &editor.last_layer().data {
+				LayerDataType::Shape(shape) => shape.clone(),
+				_ => panic!("The Spline tool should draw a shape"),
+			}
+		}
+
+		editor.select_tool(ToolType::Spline);
+		for (x, y) in [(0., 0.), (100., 50.), (200., 0.)] {
+			editor.move_mouse(x, y);
+			editor.lmb_mousedown(x, y);
+			editor.mouseup(EditorMouseState {
+				editor_position: (x, y).into(),
+				mouse_keys: MouseKeys::empty(),
+				scroll_delta: ScrollDelta::default(),
+				pressure: None,
+			});
+		}
+		editor.input(InputPreprocessorMessage::KeyDown {
+			key: Key::KeyEnter,
+			modifier_keys: ModifierKeys::empty(),
+		});
+		let spline = shape(&editor);
+		assert!(spline.spline_tension().is_some());
+
+		editor.handle_message(SplineMessage::ConvertToPath);
+		let converted = shape(&editor);
+		assert_eq!(converted.spline_tension(), None, "The layer should no longer be a spline");
+		assert_eq!(converted.path, spline.path, "Converting should keep the curves exactly as they were drawn");
+
+		editor.handle_message(SplineMessage::UpdateOptions(SplineOptionsUpdate::Tension(1.)));
+		assert_eq!(shape(&editor).path, spline.path, "The converted path's handles shouldn't follow the tension anymore");
+
+		editor.handle_message(DocumentMessage::Undo);
+		assert_eq!(shape(&editor).spline_tension(), spline.spline_tension(), "Undoing should bring the spline back in one step");
+	}
+
+	#[test]
+	/// - place three spline points, take back the last one with Backspace, and confirm
+	/// - start another spline with one point and take it back
+	/// - assert that the first spline keeps only the remaining points, and the second is removed and ends the drawing
+	fn spline_tool_backspace_removes_last_point() {
+		use crate::input::input_preprocessor::ModifierKeys;
+		use crate::input::keyboard::Key;
+		use graphene::layers::layer_info::LayerDataType;
+		use kurbo::PathEl;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		editor.select_tool(ToolType::Spline);
+		editor.click(0., 0.);
+		editor.click(100., 50.);
+		editor.click(200., 0.);
+		editor.move_mouse(150., 100.);
+		editor.press_key(Key::KeyBackspace, ModifierKeys::empty());
+		editor.press_key(Key::KeyEnter, ModifierKeys::empty());
+
+		let document = &editor.active_document().graphene_document;
+		let layers = document.root.as_folder().unwrap().layers();
+		assert_eq!(layers.len(), 1);
+		match &layers[0].data {
+			LayerDataType::Shape(shape) => assert_eq!(
+				shape.path.elements(),
+				[PathEl::MoveTo((0., 0.).into()), PathEl::LineTo((100., 50.).into())],
+				"Only the points that weren't taken back should be left"
+			),
+			_ => panic!("The Spline tool should draw a shape"),
+		}
+
+		editor.click(300., 300.);
+		editor.press_key(Key::KeyBackspace, ModifierKeys::empty());
+		assert!(!editor.dispatcher.message_handlers.tool_message_handler.active_tool_is_busy(), "Removing the only point should end it");
+		let document = &editor.active_document().graphene_document;
+		assert_eq!(document.root.as_folder().unwrap().layers().len(), 1, "A spline with no points left shouldn't be kept");
+	}
+
+	#[test]
+	/// - draw an L-shaped freehand stroke with a slight wobble, then draw it again with no simplification tolerance
+	/// - assert that the wobble is simplified away leaving only the corner, while with no tolerance every point is kept
+	fn freehand_tool_simplifies_strokes() {
+		use crate::input::mouse::{EditorMouseState, MouseKeys, ScrollDelta};
+		use crate::viewport_tools::tools::freehand::{FreehandMessage, FreehandMessageOptionsUpdate};
+		use graphene::layers::layer_info::LayerDataType;
+		use kurbo::PathEl;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		/// Draws an L-shaped stroke with a little wobble along the way, and returns the path of the finished stroke
+		fn draw_stroke(editor: &mut Editor, y: f64) -> Vec<PathEl> {
+			editor.move_mouse(0., y);
+			editor.lmb_mousedown(0., y);
+			for i in 1..=10 {
+				editor.move_mouse(i as f64 * 10., y + (i % 2) as f64 * 0.5);
+			}
+			for i in 1..=10 {
+				editor.move_mouse(100., y + i as f64 * 10.);
+			}
+			editor.mouseup(EditorMouseState {
+				editor_position: (100., y + 100.).into(),
+				mouse_keys: MouseKeys::empty(),
+				scroll_delta: ScrollDelta::default(),
+				pressure: None,
+			});
+
+			match &editor.last_layer().data {
+				LayerDataType::Shape(shape) => shape.path.elements().to_vec(),
+				_ => panic!("The Freehand tool should draw a shape"),
+			}
+		}
+
+		editor.select_tool(ToolType::Freehand);
+		let simplified = draw_stroke(&mut editor, 0.);
+		let anchors: Vec<_> = simplified
+			.iter()
+			.map(|element| match element {
+				PathEl::MoveTo(point) | PathEl::CurveTo(_, _, point) => (point.x, point.y),
+				_ => panic!("The stroke should curve through the points kept"),
+			})
+			.collect();
+		assert_eq!(anchors, [(0., 0.), (100., 0.), (100., 100.)], "The wobble should be smoothed away, leaving only the corner");
+
+		editor.handle_message(FreehandMessage::UpdateOptions(FreehandMessageOptionsUpdate::Tolerance(0.)));
+		let detailed = draw_stroke(&mut editor, 200.);
+		assert_eq!(detailed.len(), 21, "With no tolerance, every point drawn should be kept");
+	}
+
+	#[test]
+	/// - draw a freehand stroke with a pen, pressing harder along the way
+	/// - assert that the stroke is drawn as a filled outline, which widens from the minimum to the maximum width as the pressure rises
+	fn freehand_tool_varies_width_with_pen_pressure() {
+		use crate::input::input_preprocessor::ModifierKeys;
+		use crate::input::mouse::{EditorMouseState, MouseKeys, ScrollDelta};
+		use crate::viewport_tools::tools::freehand::{FreehandMessage, FreehandMessageOptionsUpdate};
+		use graphene::layers::layer_info::LayerDataType;
+		use kurbo::PathEl;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		editor.select_tool(ToolType::Freehand);
+		editor.handle_message(FreehandMessage::UpdateOptions(FreehandMessageOptionsUpdate::Tolerance(0.)));
+		let pen = |x: f64, pressure: f64, mouse_keys: MouseKeys| EditorMouseState {
+			editor_position: (x, 0.).into(),
+			mouse_keys,
+			scroll_delta: ScrollDelta::default(),
+			pressure: Some(pressure),
+		};
+		let modifier_keys = ModifierKeys::empty();
+		editor.input(InputPreprocessorMessage::PointerMove {
+			editor_mouse_state: pen(0., 0., MouseKeys::empty()),
+			modifier_keys,
+		});
+		editor.input(InputPreprocessorMessage::PointerDown {
+			editor_mouse_state: pen(0., 0., MouseKeys::LEFT),
+			modifier_keys,
+		});
+		for i in 1..=10 {
+			editor.input(InputPreprocessorMessage::PointerMove {
+				editor_mouse_state: pen(i as f64 * 10., i as f64 / 10., MouseKeys::LEFT),
+				modifier_keys,
+			});
+		}
+		editor.input(InputPreprocessorMessage::PointerUp {
+			editor_mouse_state: pen(100., 1., MouseKeys::empty()),
+			modifier_keys,
+		});
+
+		let shape = match &editor.last_layer().data {
+			LayerDataType::Shape(shape) => shape,
+			_ => panic!("The Freehand tool should draw a shape"),
+		};
+		assert!(shape.style.fill().is_some() && shape.style.stroke().is_none(), "A pen stroke should be filled in as an outline");
+
+		// The outline goes along one side of the 11 points drawn and back along the other
+		let elements = shape.path.elements();
+		assert_eq!(elements.len(), 2 * 11 + 1);
+		let y = |element: &PathEl| match element {
+			PathEl::MoveTo(point) | PathEl::LineTo(point) => point.y,
+			_ => panic!("The outline should be made of straight segments"),
+		};
+		let widths: Vec<_> = (0..11).map(|i| y(&elements[i]) - y(&elements[21 - i])).collect();
+		assert!((widths[0] - 1.).abs() < 1e-6, "The lightest pressure should give the minimum width, not {}", widths[0]);
+		assert!((widths[10] - 10.).abs() < 1e-6, "The hardest pressure should give the maximum width, not {}", widths[10]);
+		assert!(widths.windows(2).all(|pair| pair[0] < pair[1]), "The stroke should widen as the pen presses harder");
+	}
+
+	#[test]
+	/// - turn on smoothing, then draw a freehand stroke while holding the pointer in place
+	/// - assert that each new point only moves partway towards the pointer
+	fn freehand_tool_smooths_strokes_while_drawing() {
+		use crate::input::mouse::{EditorMouseState, MouseKeys, ScrollDelta};
+		use crate::viewport_tools::tools::freehand::{FreehandMessage, FreehandMessageOptionsUpdate};
+		use graphene::layers::layer_info::LayerDataType;
+		use kurbo::PathEl;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		editor.select_tool(ToolType::Freehand);
+		editor.handle_message(FreehandMessage::UpdateOptions(FreehandMessageOptionsUpdate::Tolerance(0.)));
+		editor.handle_message(FreehandMessage::UpdateOptions(FreehandMessageOptionsUpdate::Smoothing(0.5)));
+		editor.move_mouse(0., 0.);
+		editor.lmb_mousedown(0., 0.);
+		for _ in 0..3 {
+			editor.move_mouse(100., 0.);
+		}
+		editor.mouseup(EditorMouseState {
+			editor_position: (100., 0.).into(),
+			mouse_keys: MouseKeys::empty(),
+			scroll_delta: ScrollDelta::default(),
+			pressure: None,
+		});
+
+		let shape = match &editor.last_layer().data {
+			LayerDataType::Shape(shape) => shape,
+			_ => panic!("The Freehand tool should draw a shape"),
+		};
+		let xs: Vec<_> = shape
+			.path
+			.elements()
+			.iter()
+			.map(|element| match element {
+				PathEl::MoveTo(point) | PathEl::LineTo(point) => point.x,
+				_ => panic!("The stroke should be a polyline"),
+			})
+			.collect();
+		assert_eq!(xs, [0., 50., 75., 87.5], "Each point should only move halfway from the last one towards the pointer");
+	}
+
+	#[test]
+	/// - draw a freehand stroke around a square, ending close to where it started
+	/// - assert that it closes into a filled loop, dropping the points drawn over the start
+	fn freehand_tool_closes_loops_ending_near_start() {
+		use crate::input::mouse::{EditorMouseState, MouseKeys, ScrollDelta};
+		use crate::viewport_tools::tools::freehand::{FreehandMessage, FreehandMessageOptionsUpdate};
+		use graphene::layers::layer_info::LayerDataType;
+		use kurbo::PathEl;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		editor.select_tool(ToolType::Freehand);
+		editor.handle_message(FreehandMessage::UpdateOptions(FreehandMessageOptionsUpdate::Tolerance(0.)));
+		editor.move_mouse(0., 0.);
+		editor.lmb_mousedown(0., 0.);
+		for (x, y) in [(100., 0.), (100., 100.), (0., 100.), (2., 4.), (3., 3.)] {
+			editor.move_mouse(x, y);
+		}
+		editor.mouseup(EditorMouseState {
+			editor_position: (3., 3.).into(),
+			mouse_keys: MouseKeys::empty(),
+			scroll_delta: ScrollDelta::default(),
+			pressure: None,
+		});
+
+		let shape = match &editor.last_layer().data {
+			LayerDataType::Shape(shape) => shape,
+			_ => panic!("The Freehand tool should draw a shape"),
+		};
+		assert!(shape.closed && shape.style.fill().is_some(), "A stroke ending on its start should close into a filled loop");
+		assert_eq!(
+			shape.path.elements(),
+			[
+				PathEl::MoveTo((0., 0.).into()),
+				PathEl::LineTo((100., 0.).into()),
+				PathEl::LineTo((100., 100.).into()),
+				PathEl::LineTo((0., 100.).into()),
+				PathEl::ClosePath,
+			],
+			"The points drawn over the start should be dropped so the loop closes right on it"
+		);
+	}
+
+	#[test]
+	/// - click four anchors with the Pen tool, the last one close to the first, then do the same with only two anchors before returning to the start
+	/// - assert that clicking on the start closes the path and finishes it, even with only two anchors
+	fn pen_tool_closes_path_on_start_anchor() {
+		use graphene::layers::layer_info::LayerDataType;
+		use kurbo::PathEl;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		fn last_path(editor: &Editor) -> (bool, Vec<PathEl>) {
+			match &editor.last_layer().data {
+				LayerDataType::Shape(shape) => (shape.closed, shape.path.elements().to_vec()),
+				_ => panic!("The Pen tool should draw a shape"),
+			}
+		}
+
+		editor.select_tool(ToolType::Pen);
+		for (x, y) in [(0., 0.), (100., 0.), (100., 100.), (3., 2.)] {
+			editor.click(x, y);
+		}
+		assert!(!editor.dispatcher.message_handlers.tool_message_handler.active_tool_is_busy(), "Closing the path should finish it");
+		assert_eq!(
+			last_path(&editor),
+			(
+				true,
+				vec![
+					PathEl::MoveTo((0., 0.).into()),
+					PathEl::LineTo((100., 0.).into()),
+					PathEl::LineTo((100., 100.).into()),
+					PathEl::ClosePath
+				]
+			)
+		);
+
+		// Even a path of two anchors closes into a valid, if flat, loop
+		for (x, y) in [(300., 300.), (400., 300.), (301., 301.)] {
+			editor.click(x, y);
+		}
+		assert_eq!(
+			last_path(&editor),
+			(true, vec![PathEl::MoveTo((300., 300.).into()), PathEl::LineTo((400., 300.).into()), PathEl::ClosePath])
+		);
+	}
+
+	#[test]
+	/// - draw a path with the Pen tool, dragging out mirrored handles at one anchor and breaking them apart with Alt at another
+	/// - assert that the committed path curves through those handles
+	fn pen_tool_drags_out_handles() {
+		use crate::input::input_preprocessor::ModifierKeys;
+		use crate::input::mouse::MouseKeys;
+		use graphene::layers::layer_info::LayerDataType;
+		use kurbo::{PathEl, Point};
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+		editor.select_tool(ToolType::Pen);
+
+		// Places an anchor at the first position, then drags through the rest, with Alt held for the drags that say so
+		let mut place = |anchor: (f64, f64), drags: &[((f64, f64), bool)]| {
+			let modifier_keys = ModifierKeys::empty();
+			editor.pointer_move((anchor.0, anchor.1), MouseKeys::empty(), modifier_keys);
+			editor.pointer_down((anchor.0, anchor.1), MouseKeys::LEFT, modifier_keys);
+			let mut end = anchor;
+			for &((x, y), alt) in drags {
+				let modifier_keys = if alt { ModifierKeys::ALT } else { ModifierKeys::empty() };
+				editor.pointer_move((x, y), MouseKeys::LEFT, modifier_keys);
+				end = (x, y);
+			}
+			editor.pointer_up((end.0, end.1), MouseKeys::empty(), modifier_keys);
+		};
+		place((0., 0.), &[]);
+		place((100., 0.), &[((100., 50.), false)]);
+		place((200., 0.), &[]);
+		place((300., 0.), &[((300., 50.), false), ((350., 0.), true)]);
+		place((400., 0.), &[]);
+		editor.handle_message(PenMessage::Confirm);
+
+		let shape = match &editor.last_layer().data {
+			LayerDataType::Shape(shape) => shape,
+			_ => panic!("The Pen tool should draw a shape"),
+		};
+		let point = |x: f64, y: f64| Point::new(x, y);
+		assert_eq!(
+			shape.path.elements(),
+			[
+				PathEl::MoveTo(point(0., 0.)),
+				PathEl::CurveTo(point(0., 0.), point(100., -50.), point(100., 0.)),
+				PathEl::CurveTo(point(100., 50.), point(200., 0.), point(200., 0.)),
+				PathEl::CurveTo(point(200., 0.), point(300., -50.), point(300., 0.)),
+				PathEl::CurveTo(point(350., 0.), point(400., 0.), point(400., 0.)),
+			]
+		);
+	}
+
+	#[test]
+	/// - place two anchors with the Pen tool, dragging out handles at the second, then move the pointer on and pan the canvas
+	/// - finish the path, then start another one
+	/// - assert that an overlay previews the curved segment to the pointer, following the canvas, until the path is finished, and that it's reused for the next path
+	fn pen_tool_previews_next_segment() {
+		use crate::input::mouse::{EditorMouseState, MouseKeys, ScrollDelta};
+		use graphene::layers::layer_info::LayerDataType;
+		use kurbo::{PathEl, Point};
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+		editor.select_tool(ToolType::Pen);
+
+		editor.move_mouse(0., 0.);
+		editor.lmb_mousedown(0., 0.);
+		editor.mouseup(EditorMouseState {
+			editor_position: (0., 0.).into(),
+			mouse_keys: MouseKeys::empty(),
+			scroll_delta: ScrollDelta::default(),
+			pressure: None,
+		});
+		editor.move_mouse(100., 0.);
+		editor.lmb_mousedown(100., 0.);
+		editor.input(InputPreprocessorMessage::PointerMove {
+			editor_mouse_state: EditorMouseState {
+				editor_position: (100., 50.).into(),
+				mouse_keys: MouseKeys::LEFT,
+				scroll_delta: ScrollDelta::default(),
+				pressure: None,
+			},
+			modifier_keys: Default::default(),
+		});
+		editor.mouseup(EditorMouseState {
+			editor_position: (100., 50.).into(),
+			mouse_keys: MouseKeys::empty(),
+			scroll_delta: ScrollDelta::default(),
+			pressure: None,
+		});
+		editor.move_mouse(200., 0.);
+
+		/// The paths of the overlay shapes that are shown, leaving out those collapsed while they wait to be reused
+		fn overlay_shapes(editor: &Editor) -> Vec<Vec<PathEl>> {
+			let document = editor.active_document();
+			let folder = document.overlays_message_handler.overlays_graphene_document.root.as_folder().unwrap();
+			folder
+				.layers()
+				.iter()
+				.filter_map(|layer| match &layer.data {
+					LayerDataType::Shape(shape) if layer.transform != DAffine2::ZERO => Some(shape.path.elements().to_vec()),
+					_ => None,
+				})
+				.collect()
+		}
+		let point = |x: f64, y: f64| Point::new(x, y);
+		let segment = vec![PathEl::MoveTo(point(100., 0.)), PathEl::CurveTo(point(100., 50.), point(200., 0.), point(200., 0.))];
+		assert!(
+			overlay_shapes(&editor).contains(&segment),
+			"The segment to the pointer should be previewed, curving along the last handle"
+		);
+
+		editor.handle_message(MovementMessage::TranslateCanvas { delta: DVec2::new(30., 20.) });
+		let offset = editor.active_document().document_to_viewport(DVec2::ZERO);
+		let panned = |x: f64, y: f64| point(x + offset.x, y + offset.y);
+		let panned_segment = vec![PathEl::MoveTo(panned(100., 0.)), PathEl::CurveTo(panned(100., 50.), panned(200., 0.), panned(200., 0.))];
+		assert!(overlay_shapes(&editor).contains(&panned_segment), "The preview should move along with the canvas");
+
+		editor.handle_message(PenMessage::Confirm);
+		assert!(!overlay_shapes(&editor).contains(&panned_segment), "The preview should be cleared once the path is finished");
+
+		let overlay_count = |editor: &Editor| editor.active_document().overlays_message_handler.overlays_graphene_document.root.as_folder().unwrap().layers().len();
+		let overlays = overlay_count(&editor);
+		editor.move_mouse(300., 300.);
+		editor.lmb_mousedown(300., 300.);
+		editor.mouseup(EditorMouseState {
+			editor_position: (300., 300.).into(),
+			mouse_keys: MouseKeys::empty(),
+			scroll_delta: ScrollDelta::default(),
+			pressure: None,
+		});
+		editor.move_mouse(400., 300.);
+		assert_eq!(overlay_count(&editor), overlays, "The preview of the next path should reuse the overlay of the last one");
+		editor.handle_message(PenMessage::Abort);
+
+		match &editor.last_layer().data {
+			LayerDataType::Shape(shape) => assert_eq!(shape.path.elements().len(), 2, "The previewed segment shouldn't be committed"),
+			_ => panic!("The Pen tool should draw a shape"),
+		}
+	}
+
+	#[test]
+	/// - place three anchors with the Pen tool, take back the last with Backspace, and finish the path
+	/// - assert that only the remaining anchors are kept, that taking back a lone anchor discards its path, and that undo removes the whole path
+	fn pen_tool_backspace_removes_last_point() {
+		use crate::input::input_preprocessor::ModifierKeys;
+		use crate::input::keyboard::Key;
+		use graphene::layers::layer_info::LayerDataType;
+		use kurbo::PathEl;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		fn layer_count(editor: &Editor) -> usize {
+			let document = &editor.active_document().graphene_document;
+			document.root.as_folder().unwrap().layers().len()
+		}
+
+		editor.draw_rect(300., 300., 400., 400.);
+		editor.select_tool(ToolType::Pen);
+		editor.click(0., 0.);
+		editor.click(100., 50.);
+		editor.click(200., 0.);
+		editor.move_mouse(150., 100.);
+		editor.press_key(Key::KeyBackspace, ModifierKeys::empty());
+		editor.press_key(Key::KeyEnter, ModifierKeys::empty());
+
+		assert_eq!(layer_count(&editor), 2);
+		let document = &editor.active_document().graphene_document;
+		match &document.root.as_folder().unwrap().layers()[1].data {
+			LayerDataType::Shape(shape) => assert_eq!(
+				shape.path.elements(),
+				[PathEl::MoveTo((0., 0.).into()), PathEl::LineTo((100., 50.).into())],
+				"Only the anchors that weren't taken back should be left"
+			),
+			_ => panic!("The Pen tool should draw a shape"),
+		}
+
+		editor.click(500., 500.);
+		editor.press_key(Key::KeyBackspace, ModifierKeys::empty());
+		assert!(
+			!editor.dispatcher.message_handlers.tool_message_handler.active_tool_is_busy(),
+			"Removing the only anchor should end the path"
+		);
+		assert_eq!(layer_count(&editor), 2, "A path with no anchors left shouldn't be kept");
+
+		editor.handle_message(DocumentMessage::Undo);
+		assert_eq!(layer_count(&editor), 1, "Undo should take back the whole finished path rather than its anchors");
+	}
+
+	#[test]
+	/// - add a text layer, select it, and pick the serif font from the Text tool's options, then undo and edit the text in place
+	/// - assert that the layer's font changes in one undo step and that the rendered artwork references it by name
+	/// - assert that text in the default font is edited in the embedded font rather than any sans-serif font
+	fn text_tool_sets_font_of_selected_text() {
+		use graphene::layers::style::{Fill, ViewMode};
+		use graphene::layers::text::DEFAULT_FONT_FAMILY;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		editor.handle_message(Operation::AddText {
+			path: vec![1],
+			transform: DAffine2::IDENTITY.to_cols_array(),
+			insert_index: -1,
+			text: "Hello <world>".into(),
+			style: PathStyle::new(None, Some(Fill::new(Color::BLACK))),
+			size: 14.,
+		});
+		editor.handle_message(DocumentMessage::SetSelectedLayers {
+			replacement_selected_layers: vec![vec![1]],
+		});
+		editor.select_tool(ToolType::Text);
+
+		let font_family = |editor: &Editor| {
+			let document = &editor.active_document().graphene_document;
+			document.layer(&[1]).unwrap().as_text().unwrap().font_family.clone()
+		};
+		assert_eq!(font_family(&editor), DEFAULT_FONT_FAMILY, "Text should start out in the default font");
+
+		editor.handle_message(TextMessage::SetFont { font_family: "serif".into() });
+		assert_eq!(font_family(&editor), "serif");
+
+		let svg = editor
+			.dispatcher
+			.message_handlers
+			.portfolio_message_handler
+			.active_document_mut()
+			.graphene_document
+			.render_root(ViewMode::Normal);
+		assert!(svg.contains(r#"font-family="serif""#), "The font should be referenced in the artwork");
+		assert!(svg.contains("Hello &lt;world&gt;"), "The text should be escaped in the artwork");
+
+		editor.handle_message(DocumentMessage::Undo);
+		assert_eq!(font_family(&editor), DEFAULT_FONT_FAMILY, "Undo should restore the previous font");
+
+		editor.handle_message(Operation::SetTextEditability { path: vec![1], editable: true });
+		let svg = editor
+			.dispatcher
+			.message_handlers
+			.portfolio_message_handler
+			.active_document_mut()
+			.graphene_document
+			.render_root(ViewMode::Normal);
+		assert!(svg.contains("font-family: 'Source Sans Pro'"), "Text being edited should be shown in the embedded font");
+		assert!(!svg.contains("font-family: sans-serif"), "Text being edited shouldn't fall back on any sans-serif font");
+	}
+
+	#[test]
+	/// - add a text layer, select it, and change the font size in the Text tool's options
+	/// - assert that the selected text grows to the new size, and that new text is created at that size too
+	fn text_tool_sets_font_size_of_selected_and_new_text() {
+		use crate::input::mouse::{EditorMouseState, MouseKeys, ScrollDelta};
+		use crate::viewport_tools::tools::text::TextOptionsUpdate;
+		use graphene::layers::style::Fill;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		editor.handle_message(Operation::AddText {
+			path: vec![1],
+			transform: DAffine2::IDENTITY.to_cols_array(),
+			insert_index: -1,
+			text: "Hello".into(),
+			style: PathStyle::new(None, Some(Fill::new(Color::BLACK))),
+			size: 14.,
+		});
+		editor.handle_message(DocumentMessage::SetSelectedLayers {
+			replacement_selected_layers: vec![vec![1]],
+		});
+		editor.select_tool(ToolType::Text);
+		editor.handle_message(TextMessage::UpdateOptions(TextOptionsUpdate::FontSize(28)));
+
+		let document = &editor.active_document().graphene_document;
+		let text = document.layer(&[1]).unwrap().as_text().unwrap();
+		assert_eq!(text.size, 28.);
+		assert_eq!(text.bounding_box(&text.text).height(), 28., "The text should be laid out again at the new size");
+
+		editor.handle_message(DocumentMessage::DeselectAllLayers);
+		editor.lmb_mousedown(500., 500.);
+		editor.mouseup(EditorMouseState {
+			editor_position: (500., 500.).into(),
+			mouse_keys: MouseKeys::empty(),
+			scroll_delta: ScrollDelta::default(),
+			pressure: None,
+		});
+
+		let new_text = editor.last_layer().as_text().unwrap();
+		assert_eq!(new_text.size, 28., "New text should be created at the chosen size");
+	}
+
+	#[test]
+	/// - turn on wrapping in the Text tool's options for selected multi-line text with an empty line, in a font referenced by name
+	/// - assert that the artwork has a line for each wrapped line, keeping the empty one, and that the bounds grow to fit them
+	fn text_tool_wraps_selected_text() {
+		use crate::viewport_tools::tools::text::TextOptionsUpdate;
+		use graphene::layers::style::{Fill, ViewMode};
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		editor.handle_message(Operation::AddText {
+			path: vec![1],
+			transform: DAffine2::IDENTITY.to_cols_array(),
+			insert_index: -1,
+			text: "aaa bbb\n\nccc".into(),
+			style: PathStyle::new(None, Some(Fill::new(Color::BLACK))),
+			size: 14.,
+		});
+		editor.handle_message(DocumentMessage::SetSelectedLayers {
+			replacement_selected_layers: vec![vec![1]],
+		});
+		editor.select_tool(ToolType::Text);
+		editor.handle_message(TextMessage::SetFont { font_family: "serif".into() });
+		editor.handle_message(TextMessage::UpdateOptions(TextOptionsUpdate::WrapWidth(30.)));
+
+		let height = |editor: &Editor| {
+			let document = &editor.active_document().graphene_document;
+			let text = document.layer(&[1]).unwrap().as_text().unwrap();
+			text.bounding_box(&text.text).height()
+		};
+		assert_eq!(height(&editor), 3. * 14., "The width shouldn't apply until wrapping is turned on");
+
+		editor.handle_message(TextMessage::UpdateOptions(TextOptionsUpdate::Wrap(true)));
+		assert_eq!(height(&editor), 4. * 14., "The first line should wrap in two");
+
+		let svg = editor
+			.dispatcher
+			.message_handlers
+			.portfolio_message_handler
+			.active_document_mut()
+			.graphene_document
+			.render_root(ViewMode::Normal);
+		let lines: Vec<&str> = svg.split("<tspan").skip(1).map(|tspan| &tspan[tspan.find('>').unwrap() + 1..tspan.find("</tspan>").unwrap()]).collect();
+		assert_eq!(lines, ["aaa", "bbb", "", "ccc"]);
+	}
+
+	#[test]
+	/// - start typing new text with the Text tool, press Escape, and then hand back the typed text with a newline
+	/// - assert that Escape keeps the text and commits it rather than discarding it
+	fn text_tool_escape_commits_multi_line_text() {
+		use crate::input::input_preprocessor::ModifierKeys;
+		use crate::input::keyboard::Key;
+		use crate::input::mouse::{EditorMouseState, MouseKeys, ScrollDelta};
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		editor.select_tool(ToolType::Text);
+		editor.lmb_mousedown(50., 50.);
+		editor.mouseup(EditorMouseState {
+			editor_position: (50., 50.).into(),
+			mouse_keys: MouseKeys::empty(),
+			scroll_delta: ScrollDelta::default(),
+			pressure: None,
+		});
+		editor.input(InputPreprocessorMessage::KeyDown {
+			key: Key::KeyEscape,
+			modifier_keys: ModifierKeys::empty(),
+		});
+		assert!(
+			editor.dispatcher.message_handlers.tool_message_handler.active_tool_is_busy(),
+			"Escape should wait for the typed text to commit it"
+		);
+
+		editor.handle_message(TextMessage::TextChange { new_text: "first\n\nthird".into() });
+		assert!(!editor.dispatcher.message_handlers.tool_message_handler.active_tool_is_busy());
+
+		let text = editor.last_layer().as_text().unwrap();
+		assert_eq!(text.text, "first\n\nthird");
+		assert_eq!(text.bounding_box(&text.text).height(), 3. * 14., "The empty line should keep its space");
+	}
+
+	#[test]
+	/// - right-align and then justify selected multi-line text in a font referenced by name, using the Text tool's options
+	/// - assert that every line is anchored at the right edge of the box, that only the wrapped line is stretched when justified, and that undo restores left alignment
+	fn text_tool_aligns_selected_text() {
+		use crate::viewport_tools::tools::text::TextOptionsUpdate;
+		use graphene::layers::style::{Fill, ViewMode};
+		use graphene::layers::text::TextAlignment;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		editor.handle_message(Operation::AddText {
+			path: vec![1],
+			transform: DAffine2::IDENTITY.to_cols_array(),
+			insert_index: -1,
+			text: "aaa bbb ccc\nd".into(),
+			style: PathStyle::new(None, Some(Fill::new(Color::BLACK))),
+			size: 14.,
+		});
+		editor.handle_message(DocumentMessage::SetSelectedLayers {
+			replacement_selected_layers: vec![vec![1]],
+		});
+		editor.select_tool(ToolType::Text);
+		editor.handle_message(TextMessage::SetFont { font_family: "serif".into() });
+
+		let tspans = |editor: &mut Editor| -> Vec<String> {
+			let svg = editor
+				.dispatcher
+				.message_handlers
+				.portfolio_message_handler
+				.active_document_mut()
+				.graphene_document
+				.render_root(ViewMode::Normal);
+			svg.split("<tspan").skip(1).map(|tspan| tspan[..tspan.find('>').unwrap()].to_string()).collect()
+		};
+		let width = |editor: &Editor| {
+			let document = &editor.active_document().graphene_document;
+			let text = document.layer(&[1]).unwrap().as_text().unwrap();
+			text.bounding_box(&text.text).width()
+		};
+		let left_width = width(&editor);
+
+		editor.handle_message(TextMessage::SetAlignment { alignment: TextAlignment::Right });
+		let lines = tspans(&mut editor);
+		assert_eq!(lines.len(), 2);
+		assert!(lines.iter().all(|tspan| tspan.contains(r#"text-anchor="end""#)), "Every line should be anchored at its end");
+		let x = |tspan: &str| tspan[tspan.find("x=").unwrap()..].split('"').nth(1).unwrap().to_string();
+		assert_eq!(x(&lines[0]), x(&lines[1]), "The lines should end at the same edge");
+		assert!((width(&editor) - left_width).abs() < 1e-6, "Right-aligned lines should stay within the box of the longest line");
+
+		editor.handle_message(TextMessage::UpdateOptions(TextOptionsUpdate::WrapWidth(60.)));
+		editor.handle_message(TextMessage::UpdateOptions(TextOptionsUpdate::Wrap(true)));
+		editor.handle_message(TextMessage::SetAlignment { alignment: TextAlignment::Justify });
+		let lines = tspans(&mut editor);
+		assert!(lines.len() > 2, "The first paragraph should wrap");
+		assert!(lines[0].contains(r#"textLength="60""#), "A wrapped line should be stretched across the box");
+		assert!(!lines.last().unwrap().contains("textLength"), "The last line of a paragraph shouldn't be stretched");
+
+		editor.handle_message(DocumentMessage::Undo);
+		let document = &editor.active_document().graphene_document;
+		assert_eq!(
+			document.layer(&[1]).unwrap().as_text().unwrap().alignment,
+			TextAlignment::Right,
+			"Undo should take back one alignment change at a time"
+		);
+	}
+
+	#[test]
+	/// - type text with the Text tool, style it, and click it again to change what it says, then start new text and abandon it, and finally abandon a change of size while editing
+	/// - assert that the edit changes the same layer in its own undo step, picks up the layer's font and size, and that abandoning an edit restores the original text
+	fn text_tool_edits_existing_text() {
+		use crate::viewport_tools::tools::text::TextOptionsUpdate;
+		use graphene::layers::text::{Text, DEFAULT_FONT_FAMILY};
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		fn texts(editor: &Editor) -> Vec<Text> {
+			let document = &editor.active_document().graphene_document;
+			document.root.as_folder().unwrap().layers().iter().map(|layer| layer.as_text().unwrap().clone()).collect()
+		}
+		let busy = |editor: &Editor| editor.dispatcher.message_handlers.tool_message_handler.active_tool_is_busy();
+
+		editor.select_tool(ToolType::Text);
+		editor.click(50., 50.);
+		editor.handle_message(TextMessage::TextChange { new_text: "Hello".into() });
+		editor.handle_message(TextMessage::SetFont { font_family: "serif".into() });
+		editor.handle_message(TextMessage::UpdateOptions(TextOptionsUpdate::FontSize(20)));
+
+		editor.handle_message(DocumentMessage::DeselectAllLayers);
+		editor.handle_message(TextMessage::SetFont {
+			font_family: DEFAULT_FONT_FAMILY.into(),
+		});
+		editor.handle_message(TextMessage::UpdateOptions(TextOptionsUpdate::FontSize(14)));
+
+		editor.click(55., 55.);
+		assert!(busy(&editor), "Clicking the text should start editing it");
+		editor.handle_message(TextMessage::TextChange { new_text: "Goodbye".into() });
+		let layers = texts(&editor);
+		assert_eq!(layers.len(), 1, "The edit shouldn't add another layer");
+		assert_eq!(layers[0].text, "Goodbye");
+
+		editor.handle_message(DocumentMessage::Undo);
+		let layers = texts(&editor);
+		assert_eq!(layers.len(), 1, "Undo should only take back the edit");
+		assert_eq!(layers[0].text, "Hello");
+
+		editor.click(300., 300.);
+		let layers = texts(&editor);
+		assert_eq!(layers.len(), 2);
+		assert_eq!(layers[1].font_family, "serif", "The edited text's font should carry over to the tool");
+		assert_eq!(layers[1].size, 20., "The edited text's size should carry over to the tool");
+		editor.handle_message(ToolMessage::AbortCurrentTool);
+		assert_eq!(texts(&editor).len(), 1, "Abandoning new text should remove it");
+
+		editor.click(55., 55.);
+		editor.handle_message(TextMessage::UpdateOptions(TextOptionsUpdate::FontSize(30)));
+		assert_eq!(texts(&editor)[0].size, 30.);
+		editor.handle_message(ToolMessage::AbortCurrentTool);
+		assert!(!busy(&editor));
+		let layers = texts(&editor);
+		assert_eq!(layers[0].size, 20., "Abandoning the edit should restore the original size");
+		assert_eq!(layers[0].text, "Hello");
+	}
+
+	#[test]
+	/// - make selected text bold and then italic from the Text tool's options, then change its font, and then select other text and back again
+	/// - assert that the styles combine in the layer, and that the embedded font is thickened and slanted while another family is styled in the artwork
+	/// - assert that the toggles follow the selected text
+	fn text_tool_sets_bold_and_italic_style() {
+		use crate::layout::widgets::{Widget, WidgetLayout};
+		use graphene::layers::style::{Fill, ViewMode};
+		use graphene::layers::text::Text;
+		use kurbo::Shape;
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+
+		for (id, text) in [(1, "Hello"), (2, "World")] {
+			editor.handle_message(Operation::AddText {
+				path: vec![id],
+				transform: DAffine2::from_translation(DVec2::new(0., id as f64 * 50.)).to_cols_array(),
+				insert_index: -1,
+				text: text.into(),
+				style: PathStyle::new(None, Some(Fill::new(Color::BLACK))),
+				size: 14.,
+			});
+		}
+		editor.handle_message(DocumentMessage::SetSelectedLayers {
+			replacement_selected_layers: vec![vec![1]],
+		});
+		editor.select_tool(ToolType::Text);
+
+		let style = |editor: &Editor, id: LayerId| {
+			let document = &editor.active_document().graphene_document;
+			let text = document.layer(&[id]).unwrap().as_text().unwrap();
+			(text.bold, text.italic)
+		};
+		editor.handle_message(TextMessage::SetStyle { bold: Some(true), italic: None });
+		assert_eq!(style(&editor, 1), (true, false));
+		editor.handle_message(TextMessage::SetStyle { bold: None, italic: Some(true) });
+		assert_eq!(style(&editor, 1), (true, true), "Bold and italic should combine");
+		assert_eq!(style(&editor, 2), (false, false), "Text that isn't selected should be left alone");
+
+		fn render(editor: &mut Editor) -> String {
+			let document = editor.dispatcher.message_handlers.portfolio_message_handler.active_document_mut();
+			document.graphene_document.render_root(ViewMode::Normal)
+		}
+		let svg = render(&mut editor);
+		assert!(!svg.contains("<text"), "Text in the default family should stay drawn in the embedded font");
+		assert!(svg.contains(r#"stroke-linejoin="round""#), "Bold text should be thickened");
+		let upright = Text::new("Hello".into(), PathStyle::default(), 14.).to_bez_path_nonmut().bounding_box();
+		let slanted = editor.active_document().graphene_document.layer(&[1]).unwrap().as_text().unwrap().to_bez_path_nonmut().bounding_box();
+		assert!(slanted.x1 > upright.x1 + 1., "Italic text should lean over, reaching further right than upright text");
+
+		editor.handle_message(TextMessage::SetFont { font_family: "serif".into() });
+		let svg = render(&mut editor);
+		assert!(svg.contains(r#"font-weight="bold" font-style="italic""#), "The style of another font family should be in the artwork");
+
+		let toggles = |responses: Vec<FrontendMessage>| {
+			let layout = responses
+				.into_iter()
+				.filter_map(|response| match response {
+					FrontendMessage::UpdateToolOptionsLayout { layout, .. } => Some(layout),
+					_ => None,
+				})
+				.last()
+				.expect("The tool options should be updated");
+			let checked = |icon: &str| {
+				WidgetLayout::new(layout.clone())
+					.iter()
+					.find_map(|holder| match &holder.widget {
+						Widget::OptionalInput(optional_input) if optional_input.icon == icon => Some(optional_input.checked),
+						_ => None,
+					})
+					.unwrap()
+			};
+			(checked("TextBold"), checked("TextItalic"))
+		};
+		let responses = editor.handle_message(DocumentMessage::SetSelectedLayers {
+			replacement_selected_layers: vec![vec![2]],
+		});
+		assert_eq!(toggles(responses), (false, false), "The toggles should show the selected text's style");
+		let responses = editor.handle_message(DocumentMessage::SetSelectedLayers {
+			replacement_selected_layers: vec![vec![1]],
+		});
+		assert_eq!(toggles(responses), (true, true));
+	}
+
+	#[test]
+	/// - draw a black rect, then drag a linear gradient across it with the Fill tool using red as the primary color
+	/// - assert that the rect gets a red to white gradient rendered from an SVG definition, undone in one step
+	/// - switch back to solid fills and click the rect, asserting it gets a plain red fill
+	/// - add text and click it with a radial gradient, asserting that text gets gradients too
+	fn fill_tool_drags_gradients_across_shapes() {
+		use crate::input::mouse::EditorMouseState;
+		use crate::viewport_tools::tools::fill::FillOptionsUpdate;
+		use graphene::layers::style::{Fill, GradientType, ViewMode};
+
+		init_logger();
+		set_uuid_seed(0);
+		let mut editor = Editor::new();
+		editor.select_primary_color(Color::BLACK);
+		editor.draw_rect(0., 0., 100., 100.);
+		editor.select_primary_color(Color::RED);
+		editor.select_tool(ToolType::Fill);
+
+		editor.handle_message(FillMessage::UpdateOptions(FillOptionsUpdate::GradientType(Some(GradientType::Linear))));
+		editor.move_mouse(10., 50.);
+		editor.lmb_mousedown(10., 50.);
+		editor.move_mouse(90., 50.);
+		editor.mouseup(EditorMouseState::from_editor_position(90., 50.));
+		assert!(!editor.dispatcher.message_handlers.tool_message_handler.active_tool_is_busy());
+
+		let gradient = editor.last_shape_style().fill().and_then(|fill| fill.gradient()).expect("The rect should be filled with a gradient");
+		assert_eq!(gradient.gradient_type, GradientType::Linear);
+		assert_eq!(gradient.stops(), &[(0., Color::RED), (1., Color::WHITE)]);
+		let svg = editor
+			.dispatcher
+			.message_handlers
+			.portfolio_message_handler
+			.active_document_mut()
+			.graphene_document
+			.render_root(ViewMode::Normal);
+		assert!(svg.contains("<linearGradient"), "The gradient should be defined in the artwork");
+		assert!(svg.contains(r#"fill="url(#gradient-"#), "The rect should refer to the gradient's definition");
+
+		editor.handle_message(DocumentMessage::Undo);
+		let fill = editor.last_shape_style().fill().unwrap();
+		assert_eq!((fill.color(), fill.gradient()), (Color::BLACK, None), "Undo should restore the solid fill from before the drag");
+
+		editor.handle_message(FillMessage::UpdateOptions(FillOptionsUpdate::GradientType(None)));
+		editor.move_mouse(50., 50.);
+		editor.lmb_mousedown(50., 50.);
+		editor.mouseup(EditorMouseState::from_editor_position(50., 50.));
+		let fill = editor.last_shape_style().fill().unwrap();
+		assert_eq!((fill.color(), fill.gradient()), (Color::RED, None));
+
+		editor.handle_message(Operation::AddText {
+			path: vec![1],
+			transform: DAffine2::from_translation(DVec2::new(0., 200.)).to_cols_array(),
+			insert_index: -1,
+			text: "Hello".into(),
+			style: PathStyle::new(None, Some(Fill::new(Color::BLACK))),
+			size: 40.,
+		});
+		editor.handle_message(FillMessage::UpdateOptions(FillOptionsUpdate::GradientType(Some(GradientType::Radial))));
+		editor.move_mouse(20., 220.);
+		editor.lmb_mousedown(20., 220.);
+		editor.mouseup(EditorMouseState::from_editor_position(20., 220.));
+		let document = &editor.active_document().graphene_document;
+		let text = document.layer(&[1]).unwrap().as_text().unwrap();
+		let gradient = text.style.fill().and_then(|fill| fill.gradient()).expect("Text should be filled with a gradient too");
+		assert_eq!(gradient.gradient_type, GradientType::Radial);
 	}
 }
@@ -13,33 +13,70 @@ pub const VIEWPORT_ZOOM_LEVELS: [f64; 74] = [
 
 pub const VIEWPORT_SCROLL_RATE: f64 = 0.6;
 
-pub const VIEWPORT_ROTATE_SNAP_INTERVAL: f64 = 15.;
+pub const AUTO_PAN_MARGIN: f64 = 24.;
+pub const AUTO_PAN_SPEED: f64 = 800.;
 
 pub const SNAP_TOLERANCE: f64 = 3.;
 pub const SNAP_OVERLAY_FADE_DISTANCE: f64 = 20.;
 pub const SNAP_OVERLAY_UNSNAPPED_OPACITY: f64 = 0.4;
+// How opaque the guide drawn along the extension of a line is while a position snaps onto it
+pub const SNAP_EXTENSION_GUIDE_OPACITY: f64 = 0.25;
+// How close, in viewport pixels, the gaps on both sides of a dragged layer must be to count as equally spaced
+pub const EQUAL_SPACING_EPSILON: f64 = 0.001;
+// How close, in viewport pixels, the pointer must come to a point snap target, such as the start of the path being drawn, to snap onto it
+pub const SNAP_POINT_TOLERANCE: f64 = 8.;
+// How close, in viewport pixels, the pointer must come to an anchor point of another shape to snap onto it, by default
+pub const DEFAULT_ANCHOR_SNAP_TOLERANCE: f64 = 8.;
+pub const SNAP_POINT_INDICATOR_SIZE: f64 = 12.;
+pub const SNAP_REFERENCE_POINT_SIZE: f64 = 6.;
+// How far along the line between the two snap reference points their snap target lies, by default
+pub const DEFAULT_SNAP_REFERENCE_FRACTION: f64 = 0.5;
+// How far, in viewport pixels, from the pointer the snap targets are marked when the snap target markers are turned on for debugging
+pub const SNAP_TARGET_MARKER_RADIUS: f64 = 120.;
+pub const SNAP_TARGET_MARKER_SIZE: f64 = 5.;
 
 pub const DRAG_THRESHOLD: f64 = 1.;
 
 // Transforming layer
-pub const ROTATE_SNAP_ANGLE: f64 = 15.;
+pub const DEFAULT_ROTATE_SNAP_ANGLE: f64 = 15.;
+pub const DEFAULT_AXIS_SNAP_TOLERANCE: f64 = 1.;
 pub const SCALE_SNAP_INTERVAL: f64 = 0.1;
 pub const SLOWING_DIVISOR: f64 = 10.;
 
 // Select tool
-pub const SELECTION_TOLERANCE: f64 = 1.;
+pub const DEFAULT_SELECTION_TOLERANCE: f64 = 1.;
 pub const SELECTION_DRAG_ANGLE: f64 = 90.;
+pub const AXIS_LOCK_DRAG_THRESHOLD: f64 = 4.;
+pub const MEASUREMENT_LABEL_FONT_SIZE: f64 = 12.;
+pub const MEASUREMENT_LABEL_OFFSET: f64 = 4.;
+pub const SELECTION_OUTLINE_WIDTH: f64 = 1.;
 
 // Transformation cage
 pub const BOUNDS_SELECT_THRESHOLD: f64 = 10.;
 pub const BOUNDS_ROTATE_THRESHOLD: f64 = 40.;
+pub const BOUNDS_CORNER_GRAB_RADIUS: f64 = 12.;
+pub const PIVOT_MARKER_SIZE: f64 = 10.;
+pub const PIVOT_GRAB_RADIUS: f64 = 8.;
 
 // Path tool
 pub const VECTOR_MANIPULATOR_ANCHOR_MARKER_SIZE: f64 = 5.;
-pub const SELECTION_THRESHOLD: f64 = 10.;
+pub const DEFAULT_POINT_SELECTION_THRESHOLD: f64 = 10.;
 
-// Line tool
-pub const LINE_ROTATE_SNAP_ANGLE: f64 = 15.;
+// Line and shape tools
+pub const READOUT_CURSOR_OFFSET: f64 = 16.;
+// The size of the handles for editing a selected shape, like the ends of an arc, and how far from them, in viewport pixels, a drag grabs one
+pub const SHAPE_HANDLE_SIZE: f64 = 10.;
+pub const SHAPE_HANDLE_GRAB_RADIUS: f64 = 8.;
+// How far in viewport pixels the corner radius handle of a rectangle sits inside the corner while it has no rounding
+pub const CORNER_RADIUS_HANDLE_MIN_INSET: f64 = 12.;
+// The most sides the Shape tool draws a regular polygon with, beyond which it looks no different from a circle
+pub const MAX_POLYGON_SIDES: u8 = 64;
+
+// Freehand tool
+// The most the Freehand tool smooths the stroke being drawn, short of the stroke barely following the pointer at all
+pub const MAX_FREEHAND_SMOOTHING: f64 = 0.95;
+// How close, in viewport pixels, a Freehand stroke must end to where it started to close into a loop, by default
+pub const DEFAULT_FREEHAND_CLOSE_DISTANCE: f64 = 8.;
 
 // Scrollbars
 pub const SCROLLBAR_SPACING: f64 = 0.1;
@@ -49,6 +86,10 @@ pub const SCALE_EFFECT: f64 = 0.5;
 pub const DEFAULT_DOCUMENT_NAME: &str = "Untitled Document";
 pub const FILE_SAVE_SUFFIX: &str = ".graphite";
 pub const FILE_EXPORT_SUFFIX: &str = ".svg";
+pub const DEFAULT_MAX_RECENT_DOCUMENTS: usize = 10;
+pub const DEFAULT_MAX_UNDO_STEPS: usize = 100;
+// Edits of the same kind made within this many milliseconds of each other are merged into a single undo step
+pub const DEFAULT_UNDO_COALESCE_WINDOW: u64 = 500;
 
 // Colors
 pub const COLOR_ACCENT: Color = Color::from_unsafe(0x00 as f32 / 255., 0xA8 as f32 / 255., 0xFF as f32 / 255.);
@@ -56,3 +97,7 @@ pub const COLOR_ACCENT: Color = Color::from_unsafe(0x00 as f32 / 255., 0xA8 as f
 // Document
 pub const GRAPHITE_DOCUMENT_VERSION: &str = "0.0.2";
 pub const VIEWPORT_ZOOM_TO_FIT_PADDING_SCALE_FACTOR: f32 = 1.05;
+// A second digit typed within this many milliseconds of the first combines with it into a two-digit opacity percentage
+pub const OPACITY_DIGIT_WINDOW: u64 = 500;
+// The width of the stroke given to a shape without one when a color is dropped onto it as its stroke
+pub const DROPPED_STROKE_WIDTH: f32 = 1.;
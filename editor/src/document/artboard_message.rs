@@ -1,5 +1,6 @@
 use crate::message_prelude::*;
 
+use graphene::color::Color;
 use graphene::Operation as DocumentOperation;
 
 use serde::{Deserialize, Serialize};
@@ -18,12 +19,22 @@ pub enum ArtboardMessage {
 		position: (f64, f64),
 		size: (f64, f64),
 	},
+	DeleteArtboard {
+		artboard: LayerId,
+	},
 	RenderArtboards,
 	ResizeArtboard {
 		artboard: Vec<LayerId>,
 		position: (f64, f64),
 		size: (f64, f64),
 	},
+	SetArtboardBackground {
+		artboard: LayerId,
+		color: Color,
+	},
+	SetCanvasBackground {
+		color: Color,
+	},
 }
 
 impl From<DocumentOperation> for ArtboardMessage {
@@ -1,11 +1,13 @@
+use super::utility_types::{ArtboardBackgrounds, NewDocumentArtboard};
 use crate::message_prelude::*;
 
 use graphene::color::Color;
 use graphene::document::Document as GrapheneDocument;
+use graphene::layers::layer_info::LayerDataType;
 use graphene::layers::style::{self, Fill, ViewMode};
 use graphene::Operation as DocumentOperation;
 
-use glam::DAffine2;
+use glam::{DAffine2, DVec2};
 use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 
@@ -13,12 +15,66 @@ use std::collections::VecDeque;
 pub struct ArtboardMessageHandler {
 	pub artboards_graphene_document: GrapheneDocument,
 	pub artboard_ids: Vec<LayerId>,
+	/// The color shown behind the layers on the infinite canvas of a document without artboards, or `None` for white
+	pub canvas_background: Option<Color>,
 }
 
 impl ArtboardMessageHandler {
 	pub fn is_infinite_canvas(&self) -> bool {
 		self.artboard_ids.is_empty()
 	}
+
+	pub fn backgrounds(&self) -> ArtboardBackgrounds {
+		let artboard_color = |id: LayerId| match self.artboards_graphene_document.layer(&[id]).map(|layer| &layer.data) {
+			Ok(LayerDataType::Shape(shape)) => shape.style.fill().map(|fill| fill.color()),
+			_ => None,
+		};
+
+		ArtboardBackgrounds {
+			canvas: self.canvas_background,
+			artboards: self.artboard_ids.iter().filter_map(|&id| Some((id, artboard_color(id)?))).collect(),
+		}
+	}
+
+	/// Restores background colors saved with an undo step, skipping any artboard which has since been deleted.
+	pub fn restore_backgrounds(&mut self, backgrounds: &ArtboardBackgrounds) {
+		self.canvas_background = backgrounds.canvas;
+
+		for &(id, color) in &backgrounds.artboards {
+			if !self.artboard_ids.contains(&id) {
+				continue;
+			}
+			if let Err(e) = self.artboards_graphene_document.handle_operation(&DocumentOperation::SetLayerFill { path: vec![id], color }) {
+				log::error!("Artboard Error: {:?}", e);
+			}
+		}
+	}
+
+	/// Adds the artboard a new document starts with, centered on the document origin, which starts out in the middle of the viewport.
+	/// The operations are applied right away rather than sent as messages, since the document isn't open yet.
+	pub fn add_starting_artboard(&mut self, artboard: NewDocumentArtboard) {
+		let artboard_id = generate_uuid();
+		self.artboard_ids.push(artboard_id);
+
+		let size = DVec2::from(artboard.size);
+		let operations = [
+			DocumentOperation::AddRect {
+				path: vec![artboard_id],
+				insert_index: -1,
+				transform: DAffine2::from_scale_angle_translation(size, 0., -size / 2.).to_cols_array(),
+				style: style::PathStyle::new(None, Some(Fill::new(artboard.background))),
+			},
+			DocumentOperation::RenameLayer {
+				layer_path: vec![artboard_id],
+				new_name: artboard.name,
+			},
+		];
+		for operation in operations {
+			if let Err(e) = self.artboards_graphene_document.handle_operation(&operation) {
+				log::error!("Artboard Error: {:?}", e);
+			}
+		}
+	}
 }
 
 impl MessageHandler<ArtboardMessage, ()> for ArtboardMessageHandler {
@@ -55,12 +111,20 @@ impl MessageHandler<ArtboardMessage, ()> for ArtboardMessageHandler {
 
 				responses.push_back(DocumentMessage::RenderDocument.into());
 			}
+			DeleteArtboard { artboard } => {
+				self.artboard_ids.retain(|&id| id != artboard);
+
+				responses.push_back(ArtboardMessage::DispatchOperation(Box::new(DocumentOperation::DeleteLayer { path: vec![artboard] })).into());
+
+				responses.push_back(DocumentMessage::RenderDocument.into());
+			}
 			RenderArtboards => {
 				// Render an infinite canvas if there are no artboards
 				if self.artboard_ids.is_empty() {
+					let background = Fill::new(self.canvas_background.unwrap_or(Color::WHITE));
 					responses.push_back(
 						FrontendMessage::UpdateDocumentArtboards {
-							svg: r##"<rect width="100%" height="100%" fill="#ffffff" />"##.to_string(),
+							svg: format!(r#"<rect width="100%" height="100%"{} />"#, Fill::render(Some(background))),
 						}
 						.into(),
 					)
@@ -82,6 +146,16 @@ impl MessageHandler<ArtboardMessage, ()> for ArtboardMessageHandler {
 					.into(),
 				);
 
+				responses.push_back(DocumentMessage::RenderDocument.into());
+			}
+			SetArtboardBackground { artboard, color } => {
+				responses.push_back(ArtboardMessage::DispatchOperation(Box::new(DocumentOperation::SetLayerFill { path: vec![artboard], color })).into());
+
+				responses.push_back(DocumentMessage::RenderDocument.into());
+			}
+			SetCanvasBackground { color } => {
+				self.canvas_background = Some(color);
+
 				responses.push_back(DocumentMessage::RenderDocument.into());
 			}
 		}
@@ -1,9 +1,10 @@
 use super::layer_panel::LayerMetadata;
-use super::utility_types::{AlignAggregate, AlignAxis, FlipAxis};
+use super::utility_types::{AlignAggregate, AlignAxis, ExportScope, FlipAxis, StyleMatchCriterion, TransformInputMode};
 use crate::message_prelude::*;
 
+use graphene::color::Color;
 use graphene::layers::blend_mode::BlendMode;
-use graphene::layers::style::ViewMode;
+use graphene::layers::style::{PathStyle, ViewMode};
 use graphene::LayerId;
 use graphene::Operation as DocumentOperation;
 
@@ -38,7 +39,21 @@ pub enum DocumentMessage {
 		axis: AlignAxis,
 		aggregate: AlignAggregate,
 	},
+	ApplyColorAtPosition {
+		color: Color,
+		position: (f64, f64),
+		apply_to_stroke: bool,
+		/// How far from the position, in viewport pixels, a shape is still hit
+		tolerance: f64,
+	},
+	CenterOnArtboard {
+		axis: Option<AlignAxis>,
+	},
+	ClearSnapReferencePoints,
 	CommitTransaction,
+	CopyAsSvg {
+		scope: ExportScope,
+	},
 	CreateEmptyFolder {
 		container_path: Vec<LayerId>,
 	},
@@ -54,7 +69,13 @@ pub enum DocumentMessage {
 	DocumentHistoryForward,
 	DocumentStructureChanged,
 	DuplicateSelectedLayers,
-	ExportDocument,
+	EnterFolder {
+		folder_path: Vec<LayerId>,
+	},
+	ExitFolder,
+	ExportDocument {
+		scope: ExportScope,
+	},
 	FlipSelectedLayers {
 		flip_axis: FlipAxis,
 	},
@@ -74,6 +95,18 @@ pub enum DocumentMessage {
 		delta_x: f64,
 		delta_y: f64,
 	},
+	PlaceSnapReferencePoint,
+	PositionSelectedLayers {
+		axis: AlignAxis,
+		value: f64,
+		mode: TransformInputMode,
+	},
+	RandomizeTransforms {
+		position_jitter: f64,
+		rotation_jitter: f64,
+		scale_jitter: f64,
+		seed: u64,
+	},
 	Redo,
 	RenameLayer {
 		layer_path: Vec<LayerId>,
@@ -92,9 +125,15 @@ pub enum DocumentMessage {
 		ctrl: bool,
 		shift: bool,
 	},
+	SetAspectLockForSelectedLayers {
+		aspect_locked: bool,
+	},
 	SetBlendModeForSelectedLayers {
 		blend_mode: BlendMode,
 	},
+	SetFillForSelectedLayers {
+		color: Color,
+	},
 	SetLayerExpansion {
 		layer_path: Vec<LayerId>,
 		set_expanded: bool,
@@ -115,6 +154,13 @@ pub enum DocumentMessage {
 	SetSnapping {
 		snap: bool,
 	},
+	SetSnapReferenceFraction {
+		fraction: f64,
+	},
+	SetStyleForMatchingLayers {
+		style: PathStyle,
+		criterion: StyleMatchCriterion,
+	},
 	SetTexboxEditability {
 		path: Vec<LayerId>,
 		editable: bool,
@@ -129,6 +175,9 @@ pub enum DocumentMessage {
 	ToggleLayerVisibility {
 		layer_path: Vec<LayerId>,
 	},
+	TypeOpacityDigit {
+		digit: u8,
+	},
 	Undo,
 	UngroupLayers {
 		folder_path: Vec<LayerId>,
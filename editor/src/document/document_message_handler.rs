@@ -1,10 +1,11 @@
 use super::clipboards::Clipboard;
 use super::layer_panel::{layer_panel_entry, LayerDataTypeDiscriminant, LayerMetadata, LayerPanelEntry, RawBuffer};
-use super::utility_types::{AlignAggregate, AlignAxis, DocumentSave, FlipAxis};
+use super::utility_types::{AlignAggregate, AlignAxis, DocumentSave, ExportScope, FlipAxis, TransformInputMode};
 use super::vectorize_layer_metadata;
 use super::{ArtboardMessageHandler, MovementMessageHandler, OverlaysMessageHandler, TransformLayerMessageHandler};
 use crate::consts::{
-	ASYMPTOTIC_EFFECT, DEFAULT_DOCUMENT_NAME, FILE_EXPORT_SUFFIX, FILE_SAVE_SUFFIX, GRAPHITE_DOCUMENT_VERSION, SCALE_EFFECT, SCROLLBAR_SPACING, VIEWPORT_ZOOM_TO_FIT_PADDING_SCALE_FACTOR,
+	ASYMPTOTIC_EFFECT, DEFAULT_DOCUMENT_NAME, DEFAULT_MAX_UNDO_STEPS, DEFAULT_UNDO_COALESCE_WINDOW, DROPPED_STROKE_WIDTH, FILE_EXPORT_SUFFIX, FILE_SAVE_SUFFIX, GRAPHITE_DOCUMENT_VERSION,
+	OPACITY_DIGIT_WINDOW, SCALE_EFFECT, SCROLLBAR_SPACING, VIEWPORT_ZOOM_TO_FIT_PADDING_SCALE_FACTOR,
 };
 use crate::input::InputPreprocessorMessageHandler;
 use crate::layout::widgets::{
@@ -12,17 +13,21 @@ use crate::layout::widgets::{
 	WidgetCallback, WidgetHolder, WidgetLayout,
 };
 use crate::message_prelude::*;
+use crate::viewport_tools::snapping::SnapReferencePoints;
 use crate::viewport_tools::vector_editor::vector_shape::VectorShape;
 use crate::EditorError;
 
 use graphene::document::Document as GrapheneDocument;
+use graphene::intersection::Quad;
 use graphene::layers::folder::Folder;
-use graphene::layers::layer_info::LayerDataType;
-use graphene::layers::style::ViewMode;
+use graphene::layers::layer_info::{Layer, LayerData, LayerDataType};
+use graphene::layers::style::{Fill, Stroke, ViewMode};
 use graphene::{DocumentError, DocumentResponse, LayerId, Operation as DocumentOperation};
 
 use glam::{DAffine2, DVec2};
 use log::warn;
+use rand_chacha::rand_core::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::collections::VecDeque;
@@ -34,6 +39,27 @@ pub struct DocumentMessageHandler {
 	pub document_undo_history: Vec<DocumentSave>,
 	#[serde(skip)]
 	pub document_redo_history: Vec<DocumentSave>,
+	/// The most undo steps kept in the history, beyond which the oldest are dropped
+	#[serde(skip)]
+	pub max_undo_steps: usize,
+	/// How many milliseconds apart edits of the same kind can be to still be merged into a single undo step
+	#[serde(skip)]
+	pub undo_coalesce_window: u64,
+	/// The kind and time of the most recent edit which saved an undo step, or `None` if the next edit should start a new step regardless
+	#[serde(skip)]
+	last_edit: Option<(DocumentMessageDiscriminant, u64)>,
+	/// The kind and time of the message being handled, if the frontend has sent the time
+	#[serde(skip)]
+	current_edit: Option<(DocumentMessageDiscriminant, u64)>,
+	/// The kind of message being handled, which names the undo step it saves, or `None` for a transaction, which is named after its first operation
+	#[serde(skip)]
+	current_step: Option<DocumentMessageDiscriminant>,
+	/// Whether the undo step saved by the transaction in progress still needs to be named after its first operation
+	#[serde(skip)]
+	naming_transaction: bool,
+	/// How many transactions have been started and not yet committed or aborted
+	#[serde(skip)]
+	pub open_transactions: usize,
 	pub saved_document_identifier: u64,
 	pub name: String,
 	#[serde(with = "vectorize_layer_metadata")]
@@ -41,12 +67,24 @@ pub struct DocumentMessageHandler {
 	layer_range_selection_reference: Vec<LayerId>,
 	movement_handler: MovementMessageHandler,
 	#[serde(skip)]
-	overlays_message_handler: OverlaysMessageHandler,
+	pub overlays_message_handler: OverlaysMessageHandler,
 	pub artboard_message_handler: ArtboardMessageHandler,
 	#[serde(skip)]
 	transform_layer_handler: TransformLayerMessageHandler,
+	/// The folder the user has entered to edit its contents, where new and pasted layers are inserted. Empty for the document root.
+	#[serde(skip)]
+	entered_folder: Vec<LayerId>,
 	pub overlays_visible: bool,
 	pub snapping_enabled: bool,
+	/// The temporary points placed by the user for snapping between them, which aren't saved with the document
+	#[serde(skip)]
+	pub snap_reference_points: SnapReferencePoints,
+	/// Whether the snap targets near the pointer are marked while snapping, to help debug why something snaps where it does
+	#[serde(skip)]
+	pub snap_targets_visible: bool,
+	/// The digit most recently typed to set the opacity of the selected layers and when it was typed, which a second digit typed soon enough combines with
+	#[serde(skip)]
+	last_opacity_digit: Option<(u8, u64)>,
 	pub view_mode: ViewMode,
 	pub version: String,
 }
@@ -57,6 +95,13 @@ impl Default for DocumentMessageHandler {
 			graphene_document: GrapheneDocument::default(),
 			document_undo_history: Vec::new(),
 			document_redo_history: Vec::new(),
+			max_undo_steps: DEFAULT_MAX_UNDO_STEPS,
+			undo_coalesce_window: DEFAULT_UNDO_COALESCE_WINDOW,
+			last_edit: None,
+			current_edit: None,
+			current_step: None,
+			naming_transaction: false,
+			open_transactions: 0,
 			saved_document_identifier: 0,
 			name: String::from("Untitled Document"),
 			layer_metadata: vec![(vec![], LayerMetadata::new(true))].into_iter().collect(),
@@ -65,7 +110,11 @@ impl Default for DocumentMessageHandler {
 			overlays_message_handler: OverlaysMessageHandler::default(),
 			artboard_message_handler: ArtboardMessageHandler::default(),
 			transform_layer_handler: TransformLayerMessageHandler::default(),
+			entered_folder: Vec::new(),
 			snapping_enabled: true,
+			snap_reference_points: SnapReferencePoints::default(),
+			snap_targets_visible: false,
+			last_opacity_digit: None,
 			overlays_visible: true,
 			view_mode: ViewMode::default(),
 			version: GRAPHITE_DOCUMENT_VERSION.to_string(),
@@ -135,13 +184,26 @@ impl DocumentMessageHandler {
 
 	pub fn selected_visible_layers_bounding_box(&self) -> Option<[DVec2; 2]> {
 		let paths = self.selected_visible_layers();
-		self.graphene_document.combined_viewport_bounding_box(paths)
+		self.graphene_document.combined_viewport_bounding_box(paths, true)
 	}
 
 	pub fn artboard_bounding_box_and_transform(&self, path: &[LayerId]) -> Option<([DVec2; 2], DAffine2)> {
 		self.artboard_message_handler.artboards_graphene_document.bounding_box_and_transform(path).unwrap_or(None)
 	}
 
+	/// The center of the artboard containing `position`, or of the one nearest to it, in viewport space
+	pub fn nearest_artboard_center(&self, position: DVec2) -> Option<DVec2> {
+		let artboards = &self.artboard_message_handler;
+		let distance = |[min, max]: [DVec2; 2]| position.clamp(min, max).distance(position);
+
+		artboards
+			.artboard_ids
+			.iter()
+			.filter_map(|&id| artboards.artboards_graphene_document.viewport_bounding_box(&[id]).ok()?)
+			.min_by(|&a, &b| distance(a).partial_cmp(&distance(b)).unwrap_or(std::cmp::Ordering::Equal))
+			.map(|[min, max]| min.lerp(max, 0.5))
+	}
+
 	/// Create a new vector shape representation with the underlying kurbo data, VectorManipulatorShape
 	pub fn selected_visible_layers_vector_shapes(&self, responses: &mut VecDeque<Message>) -> Vec<VectorShape> {
 		let shapes = self.selected_layers().filter_map(|path_to_shape| {
@@ -179,6 +241,14 @@ impl DocumentMessageHandler {
 		self.sort_layers(unique_layers.iter().copied())
 	}
 
+	/// The rotation, in document space, of the topmost selected visible layer's horizontal axis.
+	pub fn selected_layers_axis_angle(&self) -> Option<f64> {
+		let path = self.selected_visible_layers().max_by_key(|path| self.graphene_document.indices_for_path(path).unwrap_or_default())?;
+		let transform = self.graphene_document.root.transform.inverse() * self.graphene_document.multiply_transforms(path).ok()?;
+		let axis = transform.transform_vector2(DVec2::X);
+		(axis != DVec2::ZERO).then(|| DVec2::X.angle_between(axis))
+	}
+
 	pub fn selected_layers_contains(&self, path: &[LayerId]) -> bool {
 		self.layer_metadata.get(path).map(|layer| layer.selected).unwrap_or(false)
 	}
@@ -207,11 +277,53 @@ impl DocumentMessageHandler {
 		})
 	}
 
-	/// Returns the bounding boxes for all visible layers and artboards, optionally excluding any paths.
+	/// Converts a position in the viewport, such as the pointer's, to document space by undoing the canvas pan, zoom, and rotation.
+	pub fn viewport_to_document(&self, viewport_position: DVec2) -> DVec2 {
+		self.graphene_document.root.transform.inverse().transform_point2(viewport_position)
+	}
+
+	/// Converts a position in document space to where it's shown in the viewport, the inverse of [`Self::viewport_to_document`].
+	pub fn document_to_viewport(&self, document_position: DVec2) -> DVec2 {
+		self.graphene_document.root.transform.transform_point2(document_position)
+	}
+
+	/// Converts a distance and direction in the viewport, such as a drag, to document space by undoing the canvas zoom and rotation, but not the pan.
+	pub fn viewport_to_document_vector(&self, viewport_vector: DVec2) -> DVec2 {
+		self.graphene_document.root.transform.inverse().transform_vector2(viewport_vector)
+	}
+
+	/// Converts a distance and direction in document space to how it's shown in the viewport, the inverse of [`Self::viewport_to_document_vector`].
+	pub fn document_to_viewport_vector(&self, document_vector: DVec2) -> DVec2 {
+		self.graphene_document.root.transform.transform_vector2(document_vector)
+	}
+
+	/// The axis-aligned bounding box of a layer in document space, accounting for the transforms of the layer and all of its parents.
+	/// With `include_stroke`, the box also covers the width of the layer's stroke (or the strokes of the layers within a folder), matching what is visibly drawn.
+	/// The box of a folder only covers the visible layers within it.
+	/// Returns `None` if the layer doesn't exist or is empty.
+	pub fn layer_bounding_box(&self, path: &[LayerId], include_stroke: bool) -> Option<[DVec2; 2]> {
+		let parent_transform = self.graphene_document.root.transform.inverse() * self.parent_viewport_transform(path)?;
+		transformed_bounding_box(self.graphene_document.layer(path).ok()?, parent_transform, include_stroke)
+	}
+
+	/// The same as [`Self::layer_bounding_box`], but in viewport space.
+	pub fn layer_viewport_bounding_box(&self, path: &[LayerId], include_stroke: bool) -> Option<[DVec2; 2]> {
+		transformed_bounding_box(self.graphene_document.layer(path).ok()?, self.parent_viewport_transform(path)?, include_stroke)
+	}
+
+	/// The transform from the space of the layer's parent folder into viewport space, which is the identity for the root folder.
+	fn parent_viewport_transform(&self, path: &[LayerId]) -> Option<DAffine2> {
+		match path.split_last() {
+			Some((_, parent_path)) => self.graphene_document.multiply_transforms(parent_path).ok(),
+			None => Some(DAffine2::IDENTITY),
+		}
+	}
+
+	/// Returns the bounding boxes, including strokes, for all visible layers and artboards, optionally excluding any paths.
 	pub fn bounding_boxes<'a>(&'a self, ignore_document: Option<&'a Vec<Vec<LayerId>>>, ignore_artboard: Option<LayerId>) -> impl Iterator<Item = [DVec2; 2]> + 'a {
 		self.visible_layers()
 			.filter(move |path| ignore_document.map_or(true, |ignore_document| !ignore_document.iter().any(|ig| ig.as_slice() == *path)))
-			.filter_map(|path| self.graphene_document.viewport_bounding_box(path).ok()?)
+			.filter_map(|path| self.layer_viewport_bounding_box(path, true))
 			.chain(
 				self.artboard_message_handler
 					.artboard_ids
@@ -221,6 +333,42 @@ impl DocumentMessageHandler {
 			)
 	}
 
+	/// Returns the endpoints in viewport coords of every visible layer that is a single straight segment, such as those drawn with the Line tool.
+	pub fn line_segments(&self) -> impl Iterator<Item = [DVec2; 2]> + '_ {
+		self.visible_layers().filter_map(|path| {
+			let shape = match &self.graphene_document.layer(path).ok()?.data {
+				LayerDataType::Shape(shape) => shape,
+				_ => return None,
+			};
+			let transform = self.graphene_document.multiply_transforms(path).ok()?;
+			match shape.path.elements() {
+				[kurbo::PathEl::MoveTo(start), kurbo::PathEl::LineTo(end)] => Some([start, end].map(|point| transform.transform_point2(DVec2::new(point.x, point.y)))),
+				_ => None,
+			}
+		})
+	}
+
+	/// The anchor points of every visible shape, such as the corners of a rectangle, in viewport coords.
+	pub fn anchor_points(&self) -> impl Iterator<Item = DVec2> + '_ {
+		self.visible_layers()
+			.filter_map(|path| {
+				let shape = match &self.graphene_document.layer(path).ok()?.data {
+					LayerDataType::Shape(shape) => shape,
+					_ => return None,
+				};
+				let transform = self.graphene_document.multiply_transforms(path).ok()?;
+				Some((shape, transform))
+			})
+			.flat_map(|(shape, transform)| {
+				shape.path.elements().iter().filter_map(move |element| match element {
+					kurbo::PathEl::MoveTo(point) | kurbo::PathEl::LineTo(point) | kurbo::PathEl::QuadTo(_, point) | kurbo::PathEl::CurveTo(_, _, point) => {
+						Some(transform.transform_point2(DVec2::new(point.x, point.y)))
+					}
+					kurbo::PathEl::ClosePath => None,
+				})
+			})
+	}
+
 	fn serialize_structure(&self, folder: &Folder, structure: &mut Vec<u64>, data: &mut Vec<LayerId>, path: &mut Vec<LayerId>) {
 		let mut space = 0;
 		for (id, layer) in folder.layer_ids.iter().zip(folder.layers()).rev() {
@@ -338,31 +486,75 @@ impl DocumentMessageHandler {
 	}
 
 	pub fn backup(&mut self, responses: &mut VecDeque<Message>) {
+		// Merge a rapid series of edits of the same kind, like dragging a slider or holding an arrow key to nudge, into the undo step saved by the first of them
+		let edit = self.current_edit.take();
+		let coalesce = match (self.last_edit, edit) {
+			(Some((last_kind, last_time)), Some((kind, time))) => kind == last_kind && time.saturating_sub(last_time) < self.undo_coalesce_window,
+			_ => false,
+		};
+		self.last_edit = edit;
+		if coalesce && !self.document_undo_history.is_empty() {
+			return;
+		}
+
 		self.document_redo_history.clear();
-		self.document_undo_history.push((self.graphene_document.clone(), self.layer_metadata.clone()));
+		let name = self.current_step.map(|kind| step_name(&kind.local_name()));
+		self.document_undo_history
+			.push((self.graphene_document.clone(), self.layer_metadata.clone(), self.artboard_message_handler.backgrounds(), name));
+		self.limit_undo_history();
+		self.update_undo_redo_state(responses);
 
 		// Push the UpdateOpenDocumentsList message to the bus in order to update the save status of the open documents
 		responses.push_back(PortfolioMessage::UpdateOpenDocumentsList.into());
 	}
 
+	/// Drops the oldest undo steps beyond the maximum history size.
+	pub fn limit_undo_history(&mut self) {
+		let excess = self.document_undo_history.len().saturating_sub(self.max_undo_steps);
+		self.document_undo_history.drain(..excess);
+	}
+
+	/// Tells the frontend whether there are steps to undo and redo, so it can enable or disable its undo and redo buttons, and what they'd undo or redo.
+	pub fn update_undo_redo_state(&self, responses: &mut VecDeque<Message>) {
+		let label = |action: &str, history: &[DocumentSave]| history.last().and_then(|(_, _, _, name)| name.as_ref()).map(|name| format!("{} {}", action, name));
+
+		responses.push_back(
+			FrontendMessage::UpdateUndoRedoState {
+				can_undo: !self.document_undo_history.is_empty(),
+				can_redo: !self.document_redo_history.is_empty(),
+				undo_label: label("Undo", &self.document_undo_history),
+				redo_label: label("Redo", &self.document_redo_history),
+			}
+			.into(),
+		);
+	}
+
 	pub fn rollback(&mut self, responses: &mut VecDeque<Message>) -> Result<(), EditorError> {
+		// The backup must be a new undo step, since it's immediately undone
+		self.last_edit = None;
 		self.backup(responses);
 		self.undo(responses)
 		// TODO: Consider if we should check if the document is saved
 	}
 
 	pub fn undo(&mut self, responses: &mut VecDeque<Message>) -> Result<(), EditorError> {
+		// The next edit starts a new undo step, rather than merging into the one before the undone step
+		self.last_edit = None;
+
 		// Push the UpdateOpenDocumentsList message to the bus in order to update the save status of the open documents
 		responses.push_back(PortfolioMessage::UpdateOpenDocumentsList.into());
 
 		match self.document_undo_history.pop() {
-			Some((document, layer_metadata)) => {
+			Some((document, layer_metadata, backgrounds, name)) => {
 				let document = std::mem::replace(&mut self.graphene_document, document);
 				let layer_metadata = std::mem::replace(&mut self.layer_metadata, layer_metadata);
-				self.document_redo_history.push((document, layer_metadata));
+				let current_backgrounds = self.artboard_message_handler.backgrounds();
+				self.artboard_message_handler.restore_backgrounds(&backgrounds);
+				self.document_redo_history.push((document, layer_metadata, current_backgrounds, name));
 				for layer in self.layer_metadata.keys() {
 					responses.push_back(DocumentMessage::LayerChanged { affected_layer_path: layer.clone() }.into())
 				}
+				self.update_undo_redo_state(responses);
 				Ok(())
 			}
 			None => Err(EditorError::NoTransactionInProgress),
@@ -370,17 +562,22 @@ impl DocumentMessageHandler {
 	}
 
 	pub fn redo(&mut self, responses: &mut VecDeque<Message>) -> Result<(), EditorError> {
+		self.last_edit = None;
+
 		// Push the UpdateOpenDocumentsList message to the bus in order to update the save status of the open documents
 		responses.push_back(PortfolioMessage::UpdateOpenDocumentsList.into());
 
 		match self.document_redo_history.pop() {
-			Some((document, layer_metadata)) => {
+			Some((document, layer_metadata, backgrounds, name)) => {
 				let document = std::mem::replace(&mut self.graphene_document, document);
 				let layer_metadata = std::mem::replace(&mut self.layer_metadata, layer_metadata);
-				self.document_undo_history.push((document, layer_metadata));
+				let current_backgrounds = self.artboard_message_handler.backgrounds();
+				self.artboard_message_handler.restore_backgrounds(&backgrounds);
+				self.document_undo_history.push((document, layer_metadata, current_backgrounds, name));
 				for layer in self.layer_metadata.keys() {
 					responses.push_back(DocumentMessage::LayerChanged { affected_layer_path: layer.clone() }.into())
 				}
+				self.update_undo_redo_state(responses);
 				Ok(())
 			}
 			None => Err(EditorError::NoTransactionInProgress),
@@ -392,7 +589,7 @@ impl DocumentMessageHandler {
 		// This is useful since when the document is empty the identifier will be 0
 		self.document_undo_history
 			.last()
-			.map(|(graphene_document, _)| graphene_document.current_state_identifier())
+			.map(|(graphene_document, _, _, _)| graphene_document.current_state_identifier())
 			.unwrap_or(0)
 	}
 
@@ -452,18 +649,63 @@ impl DocumentMessageHandler {
 
 	pub fn document_bounds(&self) -> Option<[DVec2; 2]> {
 		if self.artboard_message_handler.is_infinite_canvas() {
-			self.graphene_document.viewport_bounding_box(&[]).ok().flatten()
+			self.graphene_document.viewport_bounding_box_with_visibility(&[], true).ok().flatten()
 		} else {
 			self.artboard_message_handler.artboards_graphene_document.viewport_bounding_box(&[]).ok().flatten()
 		}
 	}
 
+	/// Serializes the part of the document given by `scope` as an SVG whose view box fits it, or returns `None` if there is nothing in the scope to export.
+	pub fn export_svg(&mut self, scope: ExportScope, ipp: &InputPreprocessorMessageHandler) -> Option<String> {
+		let (bbox, artwork) = match scope {
+			ExportScope::Document => {
+				let bbox = self.document_bounds().unwrap_or_else(|| [DVec2::ZERO, ipp.viewport_bounds.size()]);
+				(bbox, self.graphene_document.render_root(self.view_mode))
+			}
+			ExportScope::Selection => {
+				let bbox = self.selected_visible_layers_bounding_box()?;
+				let paths: Vec<Vec<LayerId>> = self.selected_layers_without_children().iter().map(|path| path.to_vec()).collect();
+				(bbox, self.graphene_document.render_layers(&paths, self.view_mode))
+			}
+			ExportScope::Artboard { artboard } => {
+				let bbox = self.artboard_message_handler.artboards_graphene_document.viewport_bounding_box(&[artboard]).ok()??;
+				(bbox, self.graphene_document.render_root(self.view_mode))
+			}
+		};
+
+		let size = bbox[1] - bbox[0];
+		Some(format!(
+			r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}">{}{}</svg>"#,
+			bbox[0].x, bbox[0].y, size.x, size.y, "\n", artwork
+		))
+	}
+
+	/// The folder the user has entered to edit its contents, or the root if none is entered or it no longer exists
+	pub fn edit_context(&self) -> &[LayerId] {
+		match self.graphene_document.folder(&self.entered_folder) {
+			Ok(_) => self.entered_folder.as_slice(),
+			Err(_) => &[],
+		}
+	}
+
+	/// Calculate the folder that new layers should be inserted into.
+	/// Depends on the selected layers as well as their types (Folder/Non-Folder), but stays within the entered folder.
+	pub fn get_folder_for_new_layer(&self) -> Vec<LayerId> {
+		// If the selected layers dont actually exist, the root folder will be returned
+		let selection_folder = self.graphene_document.shallowest_common_folder(self.selected_layers()).map_or(vec![], |v| v.to_vec());
+
+		let edit_context = self.edit_context();
+		if selection_folder.starts_with(edit_context) {
+			selection_folder
+		} else {
+			edit_context.to_vec()
+		}
+	}
+
 	/// Calculate the path that new layers should be inserted to.
 	/// Depends on the selected layers as well as their types (Folder/Non-Folder)
 	pub fn get_path_for_new_layer(&self) -> Vec<u64> {
-		// If the selected layers dont actually exist, a new uuid for the
-		// root folder will be returned
-		let mut path = self.graphene_document.shallowest_common_folder(self.selected_layers()).map_or(vec![], |v| v.to_vec());
+		let mut path = self.get_folder_for_new_layer();
 		path.push(generate_uuid());
 		path
 	}
@@ -507,7 +749,7 @@ impl PropertyHolder for DocumentMessageHandler {
 					icon: "Overlays".into(),
 					tooltip: "Overlays".into(),
 					on_update: WidgetCallback::new(|updated_optional_input| {
-						DocumentMessage::SetOverlaysVisibility {
+						GlobalMessage::SetOverlaysVisible {
 							visible: updated_optional_input.checked,
 						}
 						.into()
@@ -557,14 +799,9 @@ impl PropertyHolder for DocumentMessageHandler {
 				})),
 				WidgetHolder::new(Widget::NumberInput(NumberInput {
 					unit: "°".into(),
-					value: self.movement_handler.tilt / (std::f64::consts::PI / 180.),
+					value: self.movement_handler.snapped_angle().to_degrees(),
 					increment_factor: 15.,
-					on_update: WidgetCallback::new(|number_input| {
-						MovementMessage::SetCanvasRotation {
-							angle_radians: number_input.value * (std::f64::consts::PI / 180.),
-						}
-						.into()
-					}),
+					on_update: WidgetCallback::new(|number_input| MovementMessage::SetCanvasRotationDegrees { angle_degrees: number_input.value }.into()),
 					..NumberInput::default()
 				})),
 				WidgetHolder::new(Widget::Separator(Separator {
@@ -622,49 +859,71 @@ impl MessageHandler<DocumentMessage, &InputPreprocessorMessageHandler> for Docum
 	fn process_action(&mut self, message: DocumentMessage, ipp: &InputPreprocessorMessageHandler, responses: &mut VecDeque<Message>) {
 		use DocumentMessage::*;
 
+		// Transactions may be aborted by undoing them, so they always need an undo step of their own rather than being merged into the previous one
+		let kind = message.to_discriminant();
+		self.current_edit = ipp.time.filter(|_| kind != DocumentMessageDiscriminant::StartTransaction).map(|time| (kind, time));
+		self.current_step = Some(kind).filter(|&kind| kind != DocumentMessageDiscriminant::StartTransaction);
+
 		#[remain::sorted]
 		match message {
 			// Sub-messages
 			#[remain::unsorted]
-			DispatchOperation(op) => match self.graphene_document.handle_operation(&op) {
-				Ok(Some(document_responses)) => {
-					for response in document_responses {
-						match &response {
-							DocumentResponse::FolderChanged { path } => responses.push_back(FolderChanged { affected_folder_path: path.clone() }.into()),
-							DocumentResponse::DeletedLayer { path } => {
-								self.layer_metadata.remove(path);
-							}
-							DocumentResponse::LayerChanged { path } => responses.push_back(LayerChanged { affected_layer_path: path.clone() }.into()),
-							DocumentResponse::CreatedLayer { path } => {
-								if self.layer_metadata.contains_key(path) {
-									log::warn!("CreatedLayer overrides existing layer metadata.");
-								}
-								self.layer_metadata.insert(path.clone(), LayerMetadata::new(false));
+			DispatchOperation(op) => {
+				// A transaction's undo step is named after the first thing it does to the document
+				if std::mem::take(&mut self.naming_transaction) {
+					if let Some((_, _, _, name)) = self.document_undo_history.last_mut() {
+						*name = Some(operation_name(&op));
+					}
+				}
 
-								responses.push_back(LayerChanged { affected_layer_path: path.clone() }.into());
-								self.layer_range_selection_reference = path.clone();
-								responses.push_back(
-									AddSelectedLayers {
-										additional_layers: vec![path.clone()],
+				match self.graphene_document.handle_operation(&op) {
+					Ok(Some(document_responses)) => {
+						for response in document_responses {
+							match &response {
+								DocumentResponse::FolderChanged { path } => responses.push_back(FolderChanged { affected_folder_path: path.clone() }.into()),
+								DocumentResponse::DeletedLayer { path } => {
+									self.layer_metadata.remove(path);
+								}
+								DocumentResponse::LayerChanged { path } => responses.push_back(LayerChanged { affected_layer_path: path.clone() }.into()),
+								DocumentResponse::CreatedLayer { path } => {
+									if self.layer_metadata.contains_key(path) {
+										log::warn!("CreatedLayer overrides existing layer metadata.");
 									}
-									.into(),
-								);
-							}
-							DocumentResponse::DocumentChanged => responses.push_back(RenderDocument.into()),
-						};
-						responses.push_back(ToolMessage::DocumentIsDirty.into());
+									self.layer_metadata.insert(path.clone(), LayerMetadata::new(false));
+
+									responses.push_back(LayerChanged { affected_layer_path: path.clone() }.into());
+									self.layer_range_selection_reference = path.clone();
+									responses.push_back(
+										AddSelectedLayers {
+											additional_layers: vec![path.clone()],
+										}
+										.into(),
+									);
+								}
+								DocumentResponse::DocumentChanged => responses.push_back(RenderDocument.into()),
+							};
+							responses.push_back(ToolMessage::DocumentIsDirty.into());
+						}
 					}
+					Err(e) => log::error!("DocumentError: {:?}", e),
+					Ok(_) => (),
 				}
-				Err(e) => log::error!("DocumentError: {:?}", e),
-				Ok(_) => (),
-			},
+			}
 			#[remain::unsorted]
 			Artboard(message) => {
+				// A transaction which changes a background before any layer is named after that change
+				if std::mem::take(&mut self.naming_transaction) {
+					if let Some((_, _, _, name)) = self.document_undo_history.last_mut() {
+						*name = Some(step_name(&message.to_discriminant().local_name()));
+					}
+				}
+
 				self.artboard_message_handler.process_action(message, (), responses);
 			}
 			#[remain::unsorted]
 			Movement(message) => {
-				self.movement_handler.process_action(message, (&self.graphene_document, ipp), responses);
+				let selection_angle = self.selected_layers_axis_angle();
+				self.movement_handler.process_action(message, (&self.graphene_document, ipp, selection_angle), responses);
 			}
 			#[remain::unsorted]
 			Overlays(message) => {
@@ -679,6 +938,8 @@ impl MessageHandler<DocumentMessage, &InputPreprocessorMessageHandler> for Docum
 
 			// Messages
 			AbortTransaction => {
+				self.naming_transaction = false;
+				self.open_transactions = self.open_transactions.saturating_sub(1);
 				self.undo(responses).unwrap_or_else(|e| log::warn!("{}", e));
 				responses.extend([RenderDocument.into(), DocumentStructureChanged.into()]);
 			}
@@ -702,7 +963,7 @@ impl MessageHandler<DocumentMessage, &InputPreprocessorMessageHandler> for Docum
 					AlignAxis::Y => DVec2::Y,
 				};
 				let lerp = |bbox: &[DVec2; 2]| bbox[0].lerp(bbox[1], 0.5);
-				if let Some(combined_box) = self.graphene_document.combined_viewport_bounding_box(self.selected_layers()) {
+				if let Some(combined_box) = self.graphene_document.combined_viewport_bounding_box(self.selected_layers(), false) {
 					let aggregated = match aggregate {
 						AlignAggregate::Min => combined_box[0],
 						AlignAggregate::Max => combined_box[1],
@@ -727,7 +988,94 @@ impl MessageHandler<DocumentMessage, &InputPreprocessorMessageHandler> for Docum
 					responses.push_back(ToolMessage::DocumentIsDirty.into());
 				}
 			}
-			CommitTransaction => (),
+			ApplyColorAtPosition {
+				color,
+				position,
+				apply_to_stroke,
+				tolerance,
+			} => {
+				let position = DVec2::from(position);
+				let tolerance = DVec2::splat(tolerance);
+				let quad = Quad::from_box([position - tolerance, position + tolerance]);
+
+				// Only shapes have a fill and stroke, so other layers there are looked past to the topmost shape
+				let shape_style = |path: &[LayerId]| match self.graphene_document.layer(path).map(|layer| &layer.data) {
+					Ok(LayerDataType::Shape(shape)) => Some(shape.style),
+					_ => None,
+				};
+				let topmost_shape = self.graphene_document.intersects_quad_root(quad).into_iter().rev().find_map(|path| Some((shape_style(&path)?, path)));
+
+				let change: Message = match topmost_shape {
+					Some((mut style, path)) => {
+						if apply_to_stroke {
+							// A recolored stroke keeps its width and dashes
+							let stroke = match style.stroke() {
+								Some(stroke) => Stroke::new(color, stroke.width()).with_dash_pattern(stroke.dash_pattern()),
+								None => Stroke::new(color, DROPPED_STROKE_WIDTH),
+							};
+							style.set_stroke(stroke);
+						} else {
+							style.set_fill(Fill::new(color));
+						}
+						DocumentOperation::SetLayerStyle { path, style }.into()
+					}
+					// Empty canvas takes the color as the background of the artboard there, or of the whole canvas when there are no artboards
+					None => match self.artboard_message_handler.artboards_graphene_document.intersects_quad_root(quad).last() {
+						Some(artboard_path) => ArtboardMessage::SetArtboardBackground { artboard: artboard_path[0], color }.into(),
+						None if self.artboard_message_handler.is_infinite_canvas() => ArtboardMessage::SetCanvasBackground { color }.into(),
+						None => return,
+					},
+				};
+				responses.extend([StartTransaction.into(), change, CommitTransaction.into()]);
+			}
+			CenterOnArtboard { axis } => {
+				let selected_paths: Vec<Vec<LayerId>> = GrapheneDocument::shallowest_unique_layers(self.selected_layers()).into_iter().map(|path| path.to_vec()).collect();
+				// Hidden layers move along with the rest of the selection, but don't count towards its bounds
+				let visible_paths = selected_paths
+					.iter()
+					.map(|path| path.as_slice())
+					.filter(|path| self.graphene_document.layer(path).map_or(false, |layer| layer.visible));
+				let combined_box = match self.graphene_document.combined_viewport_bounding_box(visible_paths, false) {
+					Some(combined_box) => combined_box,
+					None => return,
+				};
+				let center = combined_box[0].lerp(combined_box[1], 0.5);
+
+				// Without any artboards, the selection is centered on the document origin instead
+				let target = self.nearest_artboard_center(center).unwrap_or(self.graphene_document.root.transform.translation);
+				let axis = match axis {
+					Some(AlignAxis::X) => DVec2::X,
+					Some(AlignAxis::Y) => DVec2::Y,
+					None => DVec2::ONE,
+				};
+				// The whole selection is moved by the same amount so the layers keep their positions relative to each other
+				let translation = (target - center) * axis;
+
+				self.backup(responses);
+				for path in selected_paths {
+					responses.push_back(
+						DocumentOperation::TransformLayerInViewport {
+							path,
+							transform: DAffine2::from_translation(translation).to_cols_array(),
+						}
+						.into(),
+					);
+				}
+				responses.push_back(ToolMessage::DocumentIsDirty.into());
+			}
+			ClearSnapReferencePoints => {
+				self.snap_reference_points.clear();
+				self.snap_reference_points.render_overlays(self.graphene_document.root.transform, responses);
+			}
+			CommitTransaction => {
+				self.naming_transaction = false;
+				self.open_transactions = self.open_transactions.saturating_sub(1);
+				self.update_undo_redo_state(responses);
+			}
+			CopyAsSvg { scope } => match self.export_svg(scope, ipp) {
+				Some(copy_text) => responses.push_back(FrontendMessage::TriggerTextCopy { copy_text }.into()),
+				None => log::warn!("Nothing to copy as SVG for {:?}", scope),
+			},
 			CreateEmptyFolder { mut container_path } => {
 				let id = generate_uuid();
 				container_path.push(id);
@@ -780,29 +1128,25 @@ impl MessageHandler<DocumentMessage, &InputPreprocessorMessageHandler> for Docum
 					responses.push_back(DocumentOperation::DuplicateLayer { path: path.to_vec() }.into());
 				}
 			}
-			ExportDocument => {
+			EnterFolder { folder_path } => match self.graphene_document.folder(&folder_path) {
+				Ok(_) => self.entered_folder = folder_path,
+				Err(e) => log::warn!("Could not enter folder {:?}: {:?}", folder_path, e),
+			},
+			ExitFolder => {
+				let mut entered_folder = self.edit_context().to_vec();
+				entered_folder.pop();
+				self.entered_folder = entered_folder;
+			}
+			ExportDocument { scope } => {
 				// TODO(MFISH33): Add Dialog to select artboards
-				let bbox = self.document_bounds().unwrap_or_else(|| [DVec2::ZERO, ipp.viewport_bounds.size()]);
-				let size = bbox[1] - bbox[0];
 				let name = match self.name.ends_with(FILE_SAVE_SUFFIX) {
 					true => self.name.clone().replace(FILE_SAVE_SUFFIX, FILE_EXPORT_SUFFIX),
 					false => self.name.clone() + FILE_EXPORT_SUFFIX,
 				};
-				responses.push_back(
-					FrontendMessage::TriggerFileDownload {
-						document: format!(
-							r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}">{}{}</svg>"#,
-							bbox[0].x,
-							bbox[0].y,
-							size.x,
-							size.y,
-							"\n",
-							self.graphene_document.render_root(self.view_mode)
-						),
-						name,
-					}
-					.into(),
-				)
+				match self.export_svg(scope, ipp) {
+					Some(document) => responses.push_back(FrontendMessage::TriggerFileDownload { document, name }.into()),
+					None => log::warn!("Nothing to export for {:?}", scope),
+				}
 			}
 			FlipSelectedLayers { flip_axis } => {
 				self.backup(responses);
@@ -810,7 +1154,7 @@ impl MessageHandler<DocumentMessage, &InputPreprocessorMessageHandler> for Docum
 					FlipAxis::X => DVec2::new(-1., 1.),
 					FlipAxis::Y => DVec2::new(1., -1.),
 				};
-				if let Some([min, max]) = self.graphene_document.combined_viewport_bounding_box(self.selected_layers()) {
+				if let Some([min, max]) = self.graphene_document.combined_viewport_bounding_box(self.selected_layers(), false) {
 					let center = (max + min) / 2.;
 					let bbox_trans = DAffine2::from_translation(-center);
 					for path in self.selected_layers() {
@@ -901,9 +1245,80 @@ impl MessageHandler<DocumentMessage, &InputPreprocessorMessageHandler> for Docum
 				}
 				responses.push_back(ToolMessage::DocumentIsDirty.into());
 			}
+			PlaceSnapReferencePoint => {
+				self.snap_reference_points.place(self.viewport_to_document(ipp.mouse.position));
+				self.snap_reference_points.render_overlays(self.graphene_document.root.transform, responses);
+			}
+			PositionSelectedLayers { axis, value, mode } => {
+				let paths: Vec<_> = self.selected_layers_without_children().into_iter().map(|path| path.to_vec()).collect();
+				let combined_box = paths.iter().filter_map(|path| self.layer_bounding_box(path, false)).reduce(|a, b| [a[0].min(b[0]), a[1].max(b[1])]);
+
+				if let Some([min, _]) = combined_box {
+					self.backup(responses);
+
+					let axis = match axis {
+						AlignAxis::X => DVec2::X,
+						AlignAxis::Y => DVec2::Y,
+					};
+					// An absolute value is where the left or top edge of the whole selection goes, while a relative value is how far to move each layer
+					let distance = match mode {
+						TransformInputMode::Absolute => value - min.dot(axis),
+						TransformInputMode::Relative => value,
+					};
+					let translation = self.document_to_viewport_vector(distance * axis);
+
+					for path in paths {
+						let operation = DocumentOperation::TransformLayerInViewport {
+							path,
+							transform: DAffine2::from_translation(translation).to_cols_array(),
+						};
+						responses.push_back(operation.into());
+					}
+					responses.push_back(ToolMessage::DocumentIsDirty.into());
+				}
+			}
+			RandomizeTransforms {
+				position_jitter,
+				rotation_jitter,
+				scale_jitter,
+				seed,
+			} => {
+				// Offsets each layer about its own center by up to the given distance, angle in degrees, and scale in percent
+				self.backup(responses);
+
+				// Seeded, so randomizing the same selection with the same seed always produces the same result
+				let mut rng = ChaCha20Rng::seed_from_u64(seed);
+				// A uniformly distributed value in `-range..range`, built from the top 53 bits so it fits exactly in an `f64`
+				let mut jitter = |range: f64| ((rng.next_u64() >> 11) as f64 / (1_u64 << 53) as f64 * 2. - 1.) * range;
+
+				let layers: Vec<_> = self.selected_layers_without_children().into_iter().map(|path| path.to_vec()).collect();
+				for path in layers {
+					// Always draw every value, so a layer without bounds doesn't shift the offsets of the layers after it
+					let offset = self.document_to_viewport_vector(DVec2::new(jitter(position_jitter), jitter(position_jitter)));
+					let angle = jitter(rotation_jitter).to_radians();
+					let scale = 1. + jitter(scale_jitter) / 100.;
+
+					let center = match self.graphene_document.viewport_bounding_box(&path) {
+						Ok(Some([min, max])) => (min + max) / 2.,
+						_ => continue,
+					};
+					let transform = DAffine2::from_translation(center + offset) * DAffine2::from_angle(angle) * DAffine2::from_scale(DVec2::splat(scale)) * DAffine2::from_translation(-center);
+					let operation = DocumentOperation::TransformLayerInViewport {
+						path,
+						transform: transform.to_cols_array(),
+					};
+					responses.push_back(operation.into());
+				}
+				responses.push_back(ToolMessage::DocumentIsDirty.into());
+			}
 			Redo => {
-				responses.push_back(SelectMessage::Abort.into());
-				responses.push_back(DocumentHistoryForward.into());
+				// An interaction in progress is only rolled back, rather than redoing a step on top of it
+				if self.open_transactions > 0 {
+					responses.push_back(ToolMessage::AbortCurrentTool.into());
+				} else {
+					responses.push_back(SelectMessage::Abort.into());
+					responses.push_back(DocumentHistoryForward.into());
+				}
 				responses.push_back(ToolMessage::DocumentIsDirty.into());
 				responses.push_back(RenderDocument.into());
 				responses.push_back(FolderChanged { affected_folder_path: vec![] }.into());
@@ -918,6 +1333,9 @@ impl MessageHandler<DocumentMessage, &InputPreprocessorMessageHandler> for Docum
 				);
 				responses.push_back(ArtboardMessage::RenderArtboards.into());
 
+				// The snap reference point overlays are placed in viewport space, so they are moved to follow any change to the canvas transform
+				self.snap_reference_points.render_overlays(self.graphene_document.root.transform, responses);
+
 				let document_transform_scale = self.movement_handler.snapped_scale();
 				let scale = 0.5 + ASYMPTOTIC_EFFECT + document_transform_scale * SCALE_EFFECT;
 				let viewport_size = ipp.viewport_bounds.size();
@@ -1018,6 +1436,7 @@ impl MessageHandler<DocumentMessage, &InputPreprocessorMessageHandler> for Docum
 				responses.push_back(PortfolioMessage::AutoSaveActiveDocument.into());
 				// Update the save status of the just saved document
 				responses.push_back(PortfolioMessage::UpdateOpenDocumentsList.into());
+				responses.push_back(PortfolioMessage::PushRecentActiveDocument.into());
 
 				let name = match self.name.ends_with(FILE_SAVE_SUFFIX) {
 					true => self.name.clone(),
@@ -1083,12 +1502,29 @@ impl MessageHandler<DocumentMessage, &InputPreprocessorMessageHandler> for Docum
 					}
 				}
 			}
+			SetAspectLockForSelectedLayers { aspect_locked } => {
+				self.backup(responses);
+
+				for path in self.selected_layers().map(|path| path.to_vec()) {
+					responses.push_back(DocumentOperation::SetLayerAspectLock { path, aspect_locked }.into());
+				}
+			}
 			SetBlendModeForSelectedLayers { blend_mode } => {
 				self.backup(responses);
 				for path in self.layer_metadata.iter().filter_map(|(path, data)| data.selected.then(|| path.clone())) {
 					responses.push_back(DocumentOperation::SetLayerBlendMode { path, blend_mode }.into());
 				}
 			}
+			SetFillForSelectedLayers { color } => {
+				self.backup(responses);
+
+				// Only shapes have a fill, so other selected layers such as folders and text are left as they are
+				let is_shape = |path: &&[LayerId]| matches!(self.graphene_document.layer(path).map(|layer| &layer.data), Ok(LayerDataType::Shape(_)));
+				let shape_paths: Vec<_> = self.selected_layers().filter(is_shape).map(|path| path.to_vec()).collect();
+				for path in shape_paths {
+					responses.push_back(DocumentOperation::SetLayerFill { path, color }.into());
+				}
+			}
 			SetLayerExpansion { layer_path, set_expanded } => {
 				self.layer_metadata_mut(&layer_path).expanded = set_expanded;
 				responses.push_back(DocumentStructureChanged.into());
@@ -1128,6 +1564,29 @@ impl MessageHandler<DocumentMessage, &InputPreprocessorMessageHandler> for Docum
 			SetSnapping { snap } => {
 				self.snapping_enabled = snap;
 			}
+			SetSnapReferenceFraction { fraction } => {
+				self.snap_reference_points.fraction = fraction.clamp(0., 1.);
+			}
+			SetStyleForMatchingLayers { style, criterion } => {
+				// Only shapes have a style, so the criteria compare against the selected shapes and other layers are never matched
+				let shape_style = |path: &[LayerId]| match self.graphene_document.layer(path).map(|layer| &layer.data) {
+					Ok(LayerDataType::Shape(shape)) => Some(shape.style),
+					_ => None,
+				};
+				let selected_styles: Vec<_> = self.selected_layers().filter_map(shape_style).collect();
+				let matching_paths: Vec<_> = self
+					.all_layers()
+					.filter(|&path| shape_style(path).map_or(false, |layer_style| criterion.matches(&layer_style, &selected_styles)))
+					.map(|path| path.to_vec())
+					.collect();
+
+				if !matching_paths.is_empty() {
+					self.backup(responses);
+					for path in matching_paths {
+						responses.push_back(DocumentOperation::SetLayerStyle { path, style }.into());
+					}
+				}
+			}
 			SetTexboxEditability { path, editable } => {
 				let text = self.graphene_document.layer(&path).unwrap().as_text().unwrap();
 				responses.push_back(DocumentOperation::SetTextEditability { path, editable }.into());
@@ -1136,7 +1595,6 @@ impl MessageHandler<DocumentMessage, &InputPreprocessorMessageHandler> for Docum
 						FrontendMessage::DisplayEditableTextbox {
 							text: text.text.clone(),
 							line_width: text.line_width,
-							font_size: text.size,
 						}
 						.into(),
 					);
@@ -1148,7 +1606,11 @@ impl MessageHandler<DocumentMessage, &InputPreprocessorMessageHandler> for Docum
 				self.view_mode = view_mode;
 				responses.push_front(DocumentMessage::DirtyRenderDocument.into());
 			}
-			StartTransaction => self.backup(responses),
+			StartTransaction => {
+				self.backup(responses);
+				self.naming_transaction = true;
+				self.open_transactions += 1;
+			}
 			ToggleLayerExpansion { layer_path } => {
 				self.layer_metadata_mut(&layer_path).expanded ^= true;
 				responses.push_back(DocumentStructureChanged.into());
@@ -1158,9 +1620,34 @@ impl MessageHandler<DocumentMessage, &InputPreprocessorMessageHandler> for Docum
 				responses.push_back(DocumentOperation::ToggleLayerVisibility { path: layer_path }.into());
 				responses.push_back(ToolMessage::DocumentIsDirty.into());
 			}
+			TypeOpacityDigit { digit } => {
+				// A single digit sets the opacity in steps of 10%, with 0 meaning 100%, while two digits typed in quick succession set it to that exact percentage
+				let previous_digit = self
+					.last_opacity_digit
+					.take()
+					.filter(|&(_, time)| ipp.time.map_or(false, |now| now.saturating_sub(time) < OPACITY_DIGIT_WINDOW));
+				let percentage = match previous_digit {
+					Some((tens, _)) => tens * 10 + digit,
+					None => {
+						self.last_opacity_digit = ipp.time.map(|time| (digit, time));
+						if digit == 0 {
+							100
+						} else {
+							digit * 10
+						}
+					}
+				};
+
+				responses.push_back(SetOpacityForSelectedLayers { opacity: percentage as f64 / 100. }.into());
+			}
 			Undo => {
-				responses.push_back(SelectMessage::Abort.into());
-				responses.push_back(DocumentHistoryBackward.into());
+				// An interaction in progress is only rolled back, since its transaction is the last step in the history
+				if self.open_transactions > 0 {
+					responses.push_back(ToolMessage::AbortCurrentTool.into());
+				} else {
+					responses.push_back(SelectMessage::Abort.into());
+					responses.push_back(DocumentHistoryBackward.into());
+				}
 				responses.push_back(ToolMessage::DocumentIsDirty.into());
 				responses.push_back(RenderDocument.into());
 				responses.push_back(FolderChanged { affected_folder_path: vec![] }.into());
@@ -1226,10 +1713,20 @@ impl MessageHandler<DocumentMessage, &InputPreprocessorMessageHandler> for Docum
 			ExportDocument,
 			SaveDocument,
 			SetSnapping,
+			PlaceSnapReferencePoint,
 			DebugPrintDocument,
 			ZoomCanvasToFitAll,
+			EnterFolder,
 		);
 
+		if !self.edit_context().is_empty() {
+			common.extend(actions!(DocumentMessageDiscriminant; ExitFolder));
+		}
+
+		if !self.snap_reference_points.is_empty() {
+			common.extend(actions!(DocumentMessageDiscriminant; ClearSnapReferencePoints));
+		}
+
 		if self.layer_metadata.values().any(|data| data.selected) {
 			let select = actions!(DocumentMessageDiscriminant;
 				DeleteSelectedLayers,
@@ -1238,6 +1735,8 @@ impl MessageHandler<DocumentMessage, &InputPreprocessorMessageHandler> for Docum
 				ReorderSelectedLayers,
 				GroupSelectedLayers,
 				UngroupSelectedLayers,
+				SetAspectLockForSelectedLayers,
+				TypeOpacityDigit,
 			);
 			common.extend(select);
 		}
@@ -1246,3 +1745,57 @@ impl MessageHandler<DocumentMessage, &InputPreprocessorMessageHandler> for Docum
 		common
 	}
 }
+
+/// The bounding box of a layer placed in its parent by the parent's transform, optionally grown by half the stroke width on every side to cover the stroke.
+fn transformed_bounding_box(layer: &Layer, parent_transform: DAffine2, include_stroke: bool) -> Option<[DVec2; 2]> {
+	let transform = parent_transform * layer.transform;
+	let stroke = match &layer.data {
+		// Folders don't have a stroke of their own, so combine the bounds of the visible layers within them instead
+		LayerDataType::Folder(folder) => {
+			return folder
+				.layers()
+				.iter()
+				.filter(|layer| layer.visible)
+				.filter_map(|layer| transformed_bounding_box(layer, transform, include_stroke))
+				.reduce(|a, b| [a[0].min(b[0]), a[1].max(b[1])]);
+		}
+		LayerDataType::Shape(shape) if shape.path.elements().is_empty() => return None,
+		LayerDataType::Shape(shape) => shape.style.stroke(),
+		LayerDataType::Text(text) => text.style.stroke(),
+	};
+
+	let [min, max] = layer.data.bounding_box(transform)?;
+	match stroke.filter(|_| include_stroke) {
+		Some(stroke) => {
+			// Strokes are drawn in the space of the layer's parent, so their width is scaled by the parent's transform but not the layer's own.
+			// A circle of the stroke's radius extends as far along each axis as the length of the corresponding row of that transform.
+			let matrix = parent_transform.matrix2;
+			let extent = DVec2::new(DVec2::new(matrix.x_axis.x, matrix.y_axis.x).length(), DVec2::new(matrix.x_axis.y, matrix.y_axis.y).length());
+			let padding = extent * stroke.width() as f64 / 2.;
+			Some([min - padding, max + padding])
+		}
+		None => Some([min, max]),
+	}
+}
+
+/// Spells out the name of a message or operation, like `DeleteSelectedLayers`, as the words naming its undo step, like "Delete Selected Layers".
+fn step_name(name: &str) -> String {
+	// Messages handled by a child handler are named after their path through it, like `Movement.RotateCanvasBegin`
+	let name = name.rsplit('.').next().unwrap_or(name);
+
+	let mut words = String::new();
+	for (index, character) in name.chars().enumerate() {
+		if index > 0 && character.is_uppercase() {
+			words.push(' ');
+		}
+		words.push(character);
+	}
+	words
+}
+
+/// Names the undo step of a transaction after the operation it starts with, like "Add Rect".
+fn operation_name(operation: &DocumentOperation) -> String {
+	// The operation's variant name is the start of its debug representation
+	let debug = format!("{:?}", operation);
+	step_name(debug.split(|character: char| !character.is_alphanumeric()).next().unwrap_or_default())
+}
@@ -21,6 +21,7 @@ pub enum MovementMessage {
 	},
 	PointerMove {
 		snap_angle: Key,
+		snap_angle_to_selection: Key,
 		wait_for_snap_angle_release: bool,
 		snap_zoom: Key,
 		zoom_from_viewport: Option<DVec2>,
@@ -29,9 +30,13 @@ pub enum MovementMessage {
 	SetCanvasRotation {
 		angle_radians: f64,
 	},
+	SetCanvasRotationDegrees {
+		angle_degrees: f64,
+	},
 	SetCanvasZoom {
 		zoom_factor: f64,
 	},
+	StraightenCanvasToSelection,
 	TransformCanvasEnd,
 	TranslateCanvas {
 		delta: DVec2,
@@ -1,4 +1,4 @@
-use crate::consts::{VIEWPORT_ROTATE_SNAP_INTERVAL, VIEWPORT_SCROLL_RATE, VIEWPORT_ZOOM_LEVELS, VIEWPORT_ZOOM_MOUSE_RATE, VIEWPORT_ZOOM_SCALE_MAX, VIEWPORT_ZOOM_SCALE_MIN, VIEWPORT_ZOOM_WHEEL_RATE};
+use crate::consts::{DEFAULT_ROTATE_SNAP_ANGLE, VIEWPORT_SCROLL_RATE, VIEWPORT_ZOOM_LEVELS, VIEWPORT_ZOOM_MOUSE_RATE, VIEWPORT_ZOOM_SCALE_MAX, VIEWPORT_ZOOM_SCALE_MIN, VIEWPORT_ZOOM_WHEEL_RATE};
 use crate::frontend::utility_types::MouseCursorIcon;
 use crate::input::keyboard::Key;
 use crate::input::mouse::{ViewportBounds, ViewportPosition};
@@ -12,6 +12,7 @@ use graphene::Operation as DocumentOperation;
 use glam::{DAffine2, DVec2};
 use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
+use std::f64::consts::FRAC_PI_2;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MovementMessageHandler {
@@ -19,6 +20,11 @@ pub struct MovementMessageHandler {
 	panning: bool,
 	snap_tilt: bool,
 	snap_tilt_released: bool,
+	/// Increment in degrees that the rotation snaps to, taken from the user's setting while tilting
+	#[serde(skip, default = "default_snap_tilt_angle")]
+	snap_tilt_angle: f64,
+	/// While set, the rotation snaps in 90° steps from this angle, which is the tilt that makes the selection axis-aligned
+	snap_tilt_to_selection: Option<f64>,
 
 	pub tilt: f64,
 	tilting: bool,
@@ -37,6 +43,8 @@ impl Default for MovementMessageHandler {
 			panning: false,
 			snap_tilt: false,
 			snap_tilt_released: false,
+			snap_tilt_angle: DEFAULT_ROTATE_SNAP_ANGLE,
+			snap_tilt_to_selection: None,
 
 			tilt: 0.,
 			tilting: false,
@@ -50,11 +58,18 @@ impl Default for MovementMessageHandler {
 	}
 }
 
+fn default_snap_tilt_angle() -> f64 {
+	DEFAULT_ROTATE_SNAP_ANGLE
+}
+
 impl MovementMessageHandler {
 	pub fn snapped_angle(&self) -> f64 {
-		let increment_radians: f64 = VIEWPORT_ROTATE_SNAP_INTERVAL.to_radians();
 		if self.snap_tilt {
-			(self.tilt / increment_radians).round() * increment_radians
+			let (offset, increment_radians) = match self.snap_tilt_to_selection {
+				Some(offset) => (offset, FRAC_PI_2),
+				None => (0., self.snap_tilt_angle.to_radians()),
+			};
+			offset + ((self.tilt - offset) / increment_radians).round() * increment_radians
 		} else {
 			self.tilt
 		}
@@ -113,12 +128,14 @@ impl MovementMessageHandler {
 	}
 }
 
-impl MessageHandler<MovementMessage, (&Document, &InputPreprocessorMessageHandler)> for MovementMessageHandler {
+impl MessageHandler<MovementMessage, (&Document, &InputPreprocessorMessageHandler, Option<f64>)> for MovementMessageHandler {
 	#[remain::check]
-	fn process_action(&mut self, message: MovementMessage, data: (&Document, &InputPreprocessorMessageHandler), responses: &mut VecDeque<Message>) {
+	fn process_action(&mut self, message: MovementMessage, data: (&Document, &InputPreprocessorMessageHandler, Option<f64>), responses: &mut VecDeque<Message>) {
 		use MovementMessage::*;
 
-		let (document, ipp) = data;
+		let (document, ipp, selection_angle) = data;
+		// Rotating the canvas by the negated selection angle brings the selection's axis back to horizontal
+		let selection_aligned_tilt = selection_angle.map(|angle| -angle);
 
 		#[remain::sorted]
 		match message {
@@ -168,6 +185,7 @@ impl MessageHandler<MovementMessage, (&Document, &InputPreprocessorMessageHandle
 			}
 			PointerMove {
 				snap_angle,
+				snap_angle_to_selection,
 				wait_for_snap_angle_release,
 				snap_zoom,
 				zoom_from_viewport,
@@ -179,13 +197,16 @@ impl MessageHandler<MovementMessage, (&Document, &InputPreprocessorMessageHandle
 				}
 
 				if self.tilting {
-					let new_snap = ipp.keyboard.get(snap_angle as usize);
+					let new_snap_to_selection = selection_aligned_tilt.filter(|_| ipp.is_pressed(snap_angle_to_selection));
+					let new_snap = ipp.is_pressed(snap_angle) || new_snap_to_selection.is_some();
 					if !(wait_for_snap_angle_release && new_snap && !self.snap_tilt_released) {
 						// When disabling snap, keep the viewed rotation as it was previously.
 						if !new_snap && self.snap_tilt {
 							self.tilt = self.snapped_angle();
 						}
 						self.snap_tilt = new_snap;
+						self.snap_tilt_angle = ipp.rotate_snap_angle;
+						self.snap_tilt_to_selection = new_snap_to_selection;
 						self.snap_tilt_released = true;
 					}
 
@@ -202,7 +223,7 @@ impl MessageHandler<MovementMessage, (&Document, &InputPreprocessorMessageHandle
 				if self.zooming {
 					let zoom_start = self.snapped_scale();
 
-					let new_snap = ipp.keyboard.get(snap_zoom as usize);
+					let new_snap = ipp.is_pressed(snap_zoom);
 					// When disabling snap, keep the viewed zoom as it was previously
 					if !new_snap && self.snap_zoom {
 						self.zoom = self.snapped_scale();
@@ -228,12 +249,20 @@ impl MessageHandler<MovementMessage, (&Document, &InputPreprocessorMessageHandle
 				responses.push_back(FrontendMessage::UpdateMouseCursor { cursor: MouseCursorIcon::Default }.into());
 				responses.push_back(
 					FrontendMessage::UpdateInputHints {
-						hint_data: HintData(vec![HintGroup(vec![HintInfo {
-							key_groups: vec![KeysGroup(vec![Key::KeyControl])],
-							mouse: None,
-							label: String::from("Snap 15°"),
-							plus: false,
-						}])]),
+						hint_data: HintData(vec![HintGroup(vec![
+							HintInfo {
+								key_groups: vec![KeysGroup(vec![Key::KeyPrimaryModifier])],
+								mouse: None,
+								label: String::from("Snap Angle"),
+								plus: false,
+							},
+							HintInfo {
+								key_groups: vec![KeysGroup(vec![Key::KeyShift])],
+								mouse: None,
+								label: String::from("Snap to Selection"),
+								plus: false,
+							},
+						])]),
 					}
 					.into(),
 				);
@@ -248,6 +277,19 @@ impl MessageHandler<MovementMessage, (&Document, &InputPreprocessorMessageHandle
 				responses.push_back(FrontendMessage::UpdateCanvasRotation { angle_radians: self.snapped_angle() }.into());
 				responses.push_back(PortfolioMessage::UpdateDocumentBar.into());
 			}
+			SetCanvasRotationDegrees { angle_degrees } => {
+				// Wrap the angle into the range -180° to 180°, where 180° is kept rather than becoming -180°
+				let normalized_degrees = 180. - (180. - angle_degrees).rem_euclid(360.);
+
+				// The typed angle is exact, so it shouldn't be snapped to the rotation interval
+				self.snap_tilt = false;
+				responses.push_back(
+					SetCanvasRotation {
+						angle_radians: normalized_degrees.to_radians(),
+					}
+					.into(),
+				);
+			}
 			SetCanvasZoom { zoom_factor } => {
 				self.zoom = zoom_factor.clamp(VIEWPORT_ZOOM_SCALE_MIN, VIEWPORT_ZOOM_SCALE_MAX);
 				responses.push_back(FrontendMessage::UpdateCanvasZoom { factor: self.snapped_scale() }.into());
@@ -256,6 +298,15 @@ impl MessageHandler<MovementMessage, (&Document, &InputPreprocessorMessageHandle
 				responses.push_back(PortfolioMessage::UpdateDocumentBar.into());
 				self.create_document_transform(&ipp.viewport_bounds, responses);
 			}
+			StraightenCanvasToSelection => {
+				if let Some(offset) = selection_aligned_tilt {
+					// Pick whichever of the four axis-aligned orientations is closest to the current view
+					let angle_radians = offset + ((self.snapped_angle() - offset) / FRAC_PI_2).round() * FRAC_PI_2;
+
+					self.snap_tilt = false;
+					responses.push_back(SetCanvasRotation { angle_radians }.into());
+				}
+			}
 			TransformCanvasEnd => {
 				self.tilt = self.snapped_angle();
 				self.zoom = self.snapped_scale();
@@ -264,6 +315,7 @@ impl MessageHandler<MovementMessage, (&Document, &InputPreprocessorMessageHandle
 				responses.push_back(ToolMessage::UpdateHints.into());
 				self.snap_tilt = false;
 				self.snap_tilt_released = false;
+				self.snap_tilt_to_selection = None;
 				self.snap_zoom = false;
 				self.panning = false;
 				self.tilting = false;
@@ -291,13 +343,21 @@ impl MessageHandler<MovementMessage, (&Document, &InputPreprocessorMessageHandle
 				self.create_document_transform(&ipp.viewport_bounds, responses);
 			}
 			WheelCanvasTranslate { use_y_as_x } => {
+				let scroll_delta = ipp.mouse.scroll_delta;
 				let delta = match use_y_as_x {
-					false => -ipp.mouse.scroll_delta.as_dvec2(),
-					true => (-ipp.mouse.scroll_delta.y as f64, 0.).into(),
+					false => -scroll_delta.as_dvec2(),
+					// Some browsers already turn a vertical scroll into a horizontal one while Shift is held
+					true => (-(if scroll_delta.y != 0 { scroll_delta.y } else { scroll_delta.x }) as f64, 0.).into(),
 				} * VIEWPORT_SCROLL_RATE;
 				responses.push_back(TranslateCanvas { delta }.into());
 			}
 			WheelCanvasZoom => {
+				// A purely horizontal scroll, such as from a trackpad, isn't clearly zooming in or out, so it pans instead
+				if ipp.mouse.scroll_delta.y == 0 {
+					responses.push_back(WheelCanvasTranslate { use_y_as_x: false }.into());
+					return;
+				}
+
 				let scroll = ipp.mouse.scroll_delta.scroll_delta();
 				let mut zoom_factor = 1. + scroll.abs() * VIEWPORT_ZOOM_WHEEL_RATE;
 				if ipp.mouse.scroll_delta.y > 0 {
@@ -312,7 +372,7 @@ impl MessageHandler<MovementMessage, (&Document, &InputPreprocessorMessageHandle
 				responses.push_back(
 					FrontendMessage::UpdateInputHints {
 						hint_data: HintData(vec![HintGroup(vec![HintInfo {
-							key_groups: vec![KeysGroup(vec![Key::KeyControl])],
+							key_groups: vec![KeysGroup(vec![Key::KeyPrimaryModifier])],
 							mouse: None,
 							label: String::from("Snap Increments"),
 							plus: false,
@@ -334,6 +394,8 @@ impl MessageHandler<MovementMessage, (&Document, &InputPreprocessorMessageHandle
 			ZoomCanvasBegin,
 			SetCanvasZoom,
 			SetCanvasRotation,
+			SetCanvasRotationDegrees,
+			StraightenCanvasToSelection,
 			WheelCanvasZoom,
 			IncreaseCanvasZoom,
 			DecreaseCanvasZoom,
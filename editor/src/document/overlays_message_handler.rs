@@ -17,8 +17,13 @@ impl MessageHandler<OverlaysMessage, bool> for OverlaysMessageHandler {
 		match message {
 			// Sub-messages
 			#[remain::unsorted]
+			// The operation is still applied while the overlays are hidden so they can be restored as soon as they are shown again
 			DispatchOperation(operation) => match self.overlays_graphene_document.handle_operation(&operation) {
-				Ok(_) => responses.push_back(OverlaysMessage::Rerender.into()),
+				Ok(_) => {
+					if overlays_visible {
+						responses.push_back(OverlaysMessage::Rerender.into())
+					}
+				}
 				Err(e) => log::error!("OverlaysError: {:?}", e),
 			},
 
@@ -1,4 +1,5 @@
 use super::clipboards::Clipboard;
+use super::utility_types::NewDocumentArtboard;
 use crate::message_prelude::*;
 
 use graphene::LayerId;
@@ -34,7 +35,9 @@ pub enum PortfolioMessage {
 	Cut {
 		clipboard: Clipboard,
 	},
-	NewDocument,
+	NewDocument {
+		artboard: Option<NewDocumentArtboard>,
+	},
 	NextDocument,
 	OpenDocument,
 	OpenDocumentFile {
@@ -47,6 +50,9 @@ pub enum PortfolioMessage {
 		document_is_saved: bool,
 		document_serialized_content: String,
 	},
+	OpenRecentDocument {
+		document_id: u64,
+	},
 	Paste {
 		clipboard: Clipboard,
 	},
@@ -55,7 +61,14 @@ pub enum PortfolioMessage {
 		folder_path: Vec<LayerId>,
 		insert_index: isize,
 	},
+	PasteReplace {
+		clipboard: Clipboard,
+	},
 	PrevDocument,
+	PushRecentActiveDocument,
+	PushRecentDocument {
+		document_id: u64,
+	},
 	RequestAboutGraphiteDialog,
 	SelectDocument {
 		document_id: u64,
@@ -63,6 +76,20 @@ pub enum PortfolioMessage {
 	SetActiveDcoument {
 		document_id: u64,
 	},
+	SetMaxRecentDocuments {
+		max_recent_documents: usize,
+	},
+	SetOverlaysVisibility {
+		visible: bool,
+	},
+	SetSnapTargetsVisibility {
+		visible: bool,
+	},
+	SetUndoHistoryLimits {
+		max_undo_steps: usize,
+		coalesce_window: u64,
+	},
 	UpdateDocumentBar,
 	UpdateOpenDocumentsList,
+	UpdateRecentDocumentsList,
 }
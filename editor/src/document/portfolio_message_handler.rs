@@ -1,7 +1,7 @@
 use super::clipboards::{CopyBufferEntry, CLIPBOARD_COUNT};
 use super::DocumentMessageHandler;
-use crate::consts::{DEFAULT_DOCUMENT_NAME, GRAPHITE_DOCUMENT_VERSION};
-use crate::frontend::utility_types::FrontendDocumentDetails;
+use crate::consts::{DEFAULT_DOCUMENT_NAME, DEFAULT_MAX_RECENT_DOCUMENTS, DEFAULT_MAX_UNDO_STEPS, DEFAULT_UNDO_COALESCE_WINDOW, GRAPHITE_DOCUMENT_VERSION};
+use crate::frontend::utility_types::{FrontendDocumentDetails, FrontendRecentDocument};
 use crate::input::InputPreprocessorMessageHandler;
 use crate::layout::layout_message::LayoutTarget;
 use crate::layout::widgets::PropertyHolder;
@@ -9,15 +9,31 @@ use crate::message_prelude::*;
 
 use graphene::Operation as DocumentOperation;
 
+use glam::{DAffine2, DVec2};
 use log::warn;
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, VecDeque};
 
+/// An entry in the recent documents list, which keeps the document's content so it can be reopened after its tab was closed.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct RecentDocument {
+	pub id: u64,
+	pub name: String,
+	pub serialized_content: String,
+}
+
 #[derive(Debug, Clone)]
 pub struct PortfolioMessageHandler {
 	documents: HashMap<u64, DocumentMessageHandler>,
 	document_ids: Vec<u64>,
 	active_document_id: u64,
 	copy_buffer: [Vec<CopyBufferEntry>; CLIPBOARD_COUNT as usize],
+	recent_documents: VecDeque<RecentDocument>,
+	max_recent_documents: usize,
+	overlays_visible: bool,
+	snap_targets_visible: bool,
+	max_undo_steps: usize,
+	undo_coalesce_window: u64,
 }
 
 impl PortfolioMessageHandler {
@@ -53,7 +69,12 @@ impl PortfolioMessageHandler {
 	}
 
 	// TODO Fix how this doesn't preserve tab order upon loading new document from file>load
-	fn load_document(&mut self, new_document: DocumentMessageHandler, document_id: u64, replace_first_empty: bool, responses: &mut VecDeque<Message>) {
+	fn load_document(&mut self, mut new_document: DocumentMessageHandler, document_id: u64, replace_first_empty: bool, responses: &mut VecDeque<Message>) {
+		new_document.overlays_visible = self.overlays_visible;
+		new_document.snap_targets_visible = self.snap_targets_visible;
+		new_document.max_undo_steps = self.max_undo_steps;
+		new_document.undo_coalesce_window = self.undo_coalesce_window;
+
 		// Special case when loading a document on an empty page
 		if replace_first_empty && self.active_document().is_unmodified_default() {
 			responses.push_back(ToolMessage::AbortCurrentTool.into());
@@ -106,6 +127,28 @@ impl PortfolioMessageHandler {
 	fn document_index(&self, document_id: u64) -> usize {
 		self.document_ids.iter().position(|id| id == &document_id).expect("Active document is missing from document ids")
 	}
+
+	/// Returns the recent documents, with the most recently opened or saved document first.
+	pub fn recent_documents(&self) -> impl Iterator<Item = &RecentDocument> {
+		self.recent_documents.iter()
+	}
+
+	/// Moves the document to the top of the recent documents list, adding it if it is not already present.
+	fn push_recent_document(&mut self, document_id: u64) {
+		let document = match self.documents.get(&document_id) {
+			Some(document) => document,
+			None => return warn!("Tried to add the missing document {} to the recent documents", document_id),
+		};
+		let entry = RecentDocument {
+			id: document_id,
+			name: document.name.clone(),
+			serialized_content: document.serialize_document(),
+		};
+
+		self.recent_documents.retain(|recent| recent.id != document_id);
+		self.recent_documents.push_front(entry);
+		self.recent_documents.truncate(self.max_recent_documents);
+	}
 }
 
 impl Default for PortfolioMessageHandler {
@@ -121,6 +164,12 @@ impl Default for PortfolioMessageHandler {
 			document_ids: vec![starting_key],
 			copy_buffer: [EMPTY_VEC; CLIPBOARD_COUNT as usize],
 			active_document_id: starting_key,
+			recent_documents: VecDeque::new(),
+			max_recent_documents: DEFAULT_MAX_RECENT_DOCUMENTS,
+			overlays_visible: true,
+			snap_targets_visible: false,
+			max_undo_steps: DEFAULT_MAX_UNDO_STEPS,
+			undo_coalesce_window: DEFAULT_UNDO_COALESCE_WINDOW,
 		}
 	}
 }
@@ -163,7 +212,7 @@ impl MessageHandler<PortfolioMessage, &InputPreprocessorMessageHandler> for Port
 				self.document_ids.clear();
 
 				// Create a new blank document
-				responses.push_back(NewDocument.into());
+				responses.push_back(NewDocument { artboard: None }.into());
 			}
 			CloseAllDocumentsWithConfirmation => {
 				responses.push_back(FrontendMessage::DisplayConfirmationToCloseAllDocuments.into());
@@ -206,6 +255,7 @@ impl MessageHandler<PortfolioMessage, &InputPreprocessorMessageHandler> for Port
 
 				responses.push_back(FrontendMessage::UpdateOpenDocumentsList { open_documents }.into());
 				responses.push_back(FrontendMessage::UpdateActiveDocument { document_id: self.active_document_id }.into());
+				self.active_document().update_undo_redo_state(responses);
 				responses.push_back(FrontendMessage::TriggerIndexedDbRemoveDocument { document_id }.into());
 				responses.push_back(RenderDocument.into());
 				responses.push_back(DocumentMessage::DocumentStructureChanged.into());
@@ -244,9 +294,13 @@ impl MessageHandler<PortfolioMessage, &InputPreprocessorMessageHandler> for Port
 				responses.push_back(Copy { clipboard }.into());
 				responses.push_back(DeleteSelectedLayers.into());
 			}
-			NewDocument => {
+			NewDocument { artboard } => {
 				let name = self.generate_new_document_name();
-				let new_document = DocumentMessageHandler::with_name(name, ipp);
+				let mut new_document = DocumentMessageHandler::with_name(name, ipp);
+				// Messages sent now would be handled by the previously active document, so the artboard is added before the new one is opened
+				if let Some(artboard) = artboard {
+					new_document.artboard_message_handler.add_starting_artboard(artboard);
+				}
 				let document_id = generate_uuid();
 				responses.push_back(ToolMessage::AbortCurrentTool.into());
 				self.load_document(new_document, document_id, false, responses);
@@ -286,6 +340,8 @@ impl MessageHandler<PortfolioMessage, &InputPreprocessorMessageHandler> for Port
 					Ok(mut document) => {
 						document.set_save_state(document_is_saved);
 						self.load_document(document, document_id, true, responses);
+						self.push_recent_document(document_id);
+						responses.push_back(UpdateRecentDocumentsList.into());
 					}
 					Err(e) => responses.push_back(
 						FrontendMessage::DisplayDialogError {
@@ -296,18 +352,31 @@ impl MessageHandler<PortfolioMessage, &InputPreprocessorMessageHandler> for Port
 					),
 				}
 			}
+			OpenRecentDocument { document_id } => {
+				if self.documents.contains_key(&document_id) {
+					responses.push_back(PortfolioMessage::SelectDocument { document_id }.into());
+				} else if let Some(recent) = self.recent_documents.iter().find(|recent| recent.id == document_id) {
+					responses.push_back(
+						PortfolioMessage::OpenDocumentFileWithId {
+							document_id,
+							document_name: recent.name.clone(),
+							document_is_saved: true,
+							document_serialized_content: recent.serialized_content.clone(),
+						}
+						.into(),
+					);
+				} else {
+					warn!("Tried to open the recent document {} which is not in the recent documents list", document_id);
+				}
+			}
 			Paste { clipboard } => {
-				let document = self.active_document();
-				let shallowest_common_folder = document
-					.graphene_document
-					.shallowest_common_folder(document.selected_layers())
-					.expect("While pasting, the selected layers did not exist while attempting to find the appropriate folder path for insertion");
+				let folder_path = self.active_document().get_folder_for_new_layer();
 				responses.push_back(DeselectAllLayers.into());
 				responses.push_back(StartTransaction.into());
 				responses.push_back(
 					PasteIntoFolder {
 						clipboard,
-						folder_path: shallowest_common_folder.to_vec(),
+						folder_path,
 						insert_index: -1,
 					}
 					.into(),
@@ -351,6 +420,67 @@ impl MessageHandler<PortfolioMessage, &InputPreprocessorMessageHandler> for Port
 					}
 				}
 			}
+			PasteReplace { clipboard } => {
+				let document = self.active_document();
+				let entries = &self.copy_buffer[clipboard as usize];
+				let replaced_layers = document.selected_layers_without_children();
+
+				match replaced_layers.last() {
+					// The replacement takes the place of the topmost replaced layer, just above it in its folder
+					Some(topmost_layer) if !entries.is_empty() => {
+						let (folder_path, topmost_id) = topmost_layer.split_at(topmost_layer.len() - 1);
+						let graphene_document = &document.graphene_document;
+						let insert_index = graphene_document
+							.folder(folder_path)
+							.and_then(|folder| folder.position_of_layer(topmost_id[0]))
+							.map_or(-1, |index| index as isize + 1);
+
+						// Move the pasted layers so they're centered on the layers they replace
+						let folder_transform = graphene_document.multiply_transforms(folder_path).unwrap_or(DAffine2::IDENTITY);
+						let pasted_bounds = entries
+							.iter()
+							.filter_map(|entry| entry.layer.current_bounding_box_with_transform(folder_transform * entry.layer.transform))
+							.reduce(|a, b| [a[0].min(b[0]), a[1].max(b[1])]);
+						let replaced_bounds = graphene_document.combined_viewport_bounding_box(replaced_layers.iter().copied(), true);
+						let offset = match (pasted_bounds, replaced_bounds) {
+							(Some([pasted_min, pasted_max]), Some([replaced_min, replaced_max])) => (replaced_min + replaced_max) / 2. - (pasted_min + pasted_max) / 2.,
+							_ => DVec2::ZERO,
+						};
+						let shift = folder_transform.inverse() * DAffine2::from_translation(offset) * folder_transform;
+
+						responses.push_back(StartTransaction.into());
+						responses.push_back(DeselectAllLayers.into());
+						// Each layer inserted at the same index goes below the ones inserted after it, so inserting in reverse keeps the copied stacking order
+						for entry in entries.iter().rev() {
+							let destination_path = [folder_path.to_vec(), vec![generate_uuid()]].concat();
+							let mut layer = entry.layer.clone();
+							layer.transform = shift * layer.transform;
+
+							responses.push_back(
+								DocumentOperation::InsertLayer {
+									layer,
+									destination_path: destination_path.clone(),
+									insert_index,
+								}
+								.into(),
+							);
+							responses.push_back(
+								DocumentMessage::UpdateLayerMetadata {
+									layer_path: destination_path,
+									layer_metadata: entry.layer_metadata,
+								}
+								.into(),
+							);
+						}
+						for path in replaced_layers {
+							responses.push_back(DocumentOperation::DeleteLayer { path: path.to_vec() }.into());
+						}
+						responses.push_back(CommitTransaction.into());
+					}
+					// With nothing selected to replace, or nothing to replace it with, this is a normal paste
+					_ => responses.push_back(Paste { clipboard }.into()),
+				}
+			}
 			PrevDocument => {
 				let len = self.document_ids.len();
 				let current_index = self.document_index(self.active_document_id);
@@ -358,6 +488,11 @@ impl MessageHandler<PortfolioMessage, &InputPreprocessorMessageHandler> for Port
 				let prev_id = self.document_ids[prev_index];
 				responses.push_back(PortfolioMessage::SelectDocument { document_id: prev_id }.into());
 			}
+			PushRecentActiveDocument => responses.push_back(PortfolioMessage::PushRecentDocument { document_id: self.active_document_id }.into()),
+			PushRecentDocument { document_id } => {
+				self.push_recent_document(document_id);
+				responses.push_back(UpdateRecentDocumentsList.into());
+			}
 			RequestAboutGraphiteDialog => {
 				responses.push_back(FrontendMessage::DisplayDialogAboutGraphite.into());
 			}
@@ -370,6 +505,7 @@ impl MessageHandler<PortfolioMessage, &InputPreprocessorMessageHandler> for Port
 				responses.push_back(SetActiveDcoument { document_id }.into());
 
 				responses.push_back(FrontendMessage::UpdateActiveDocument { document_id }.into());
+				self.documents.get(&document_id).unwrap().update_undo_redo_state(responses);
 				responses.push_back(RenderDocument.into());
 				responses.push_back(DocumentMessage::DocumentStructureChanged.into());
 				for layer in self.documents.get(&document_id).unwrap().layer_metadata.keys() {
@@ -381,6 +517,37 @@ impl MessageHandler<PortfolioMessage, &InputPreprocessorMessageHandler> for Port
 			SetActiveDcoument { document_id } => {
 				self.active_document_id = document_id;
 			}
+			SetMaxRecentDocuments { max_recent_documents } => {
+				self.max_recent_documents = max_recent_documents;
+				self.recent_documents.truncate(max_recent_documents);
+				responses.push_back(UpdateRecentDocumentsList.into());
+			}
+			SetOverlaysVisibility { visible } => {
+				// Hiding the overlays only affects their rendering, so the selection and the overlay layers are kept intact
+				self.overlays_visible = visible;
+				for document in self.documents.values_mut() {
+					document.overlays_visible = visible;
+				}
+				responses.push_back(OverlaysMessage::Rerender.into());
+				responses.push_back(PortfolioMessage::UpdateDocumentBar.into());
+			}
+			SetSnapTargetsVisibility { visible } => {
+				// The markers are drawn by the tools' snap handlers the next time they snap, so there's nothing to rerender here
+				self.snap_targets_visible = visible;
+				for document in self.documents.values_mut() {
+					document.snap_targets_visible = visible;
+				}
+			}
+			SetUndoHistoryLimits { max_undo_steps, coalesce_window } => {
+				self.max_undo_steps = max_undo_steps;
+				self.undo_coalesce_window = coalesce_window;
+				for document in self.documents.values_mut() {
+					document.max_undo_steps = max_undo_steps;
+					document.undo_coalesce_window = coalesce_window;
+					document.limit_undo_history();
+				}
+				self.active_document().update_undo_redo_state(responses);
+			}
 			UpdateDocumentBar => {
 				let active_document = self.active_document();
 				active_document.register_properties(responses, LayoutTarget::DocumentBar)
@@ -400,6 +567,17 @@ impl MessageHandler<PortfolioMessage, &InputPreprocessorMessageHandler> for Port
 					.collect::<Vec<_>>();
 				responses.push_back(FrontendMessage::UpdateOpenDocumentsList { open_documents }.into());
 			}
+			UpdateRecentDocumentsList => {
+				let recent_documents = self
+					.recent_documents
+					.iter()
+					.map(|recent| FrontendRecentDocument {
+						name: recent.name.clone(),
+						id: recent.id,
+					})
+					.collect::<Vec<_>>();
+				responses.push_back(FrontendMessage::UpdateRecentDocumentsList { recent_documents }.into());
+			}
 		}
 	}
 
@@ -413,6 +591,8 @@ impl MessageHandler<PortfolioMessage, &InputPreprocessorMessageHandler> for Port
 			PrevDocument,
 			PasteIntoFolder,
 			Paste,
+			PasteReplace,
+			OpenRecentDocument,
 		);
 
 		if self.active_document().layer_metadata.values().any(|data| data.selected) {
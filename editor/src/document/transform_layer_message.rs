@@ -9,8 +9,8 @@ use serde::{Deserialize, Serialize};
 pub enum TransformLayerMessage {
 	ApplyTransformOperation,
 	BeginGrab,
-	BeginRotate,
-	BeginScale,
+	BeginRotate { transform_each: bool },
+	BeginScale { transform_each: bool },
 	CancelTransformOperation,
 	ConstrainX,
 	ConstrainY,
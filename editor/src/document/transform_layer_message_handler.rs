@@ -1,5 +1,5 @@
 use super::layer_panel::LayerMetadata;
-use super::transformation::{Axis, OriginalTransforms, Selected, TransformOperation, Typing};
+use super::transformation::{Axis, OriginalTransforms, Rotation, Selected, TransformOperation, Typing};
 use crate::consts::SLOWING_DIVISOR;
 use crate::input::mouse::ViewportPosition;
 use crate::input::InputPreprocessorMessageHandler;
@@ -13,6 +13,7 @@ use std::collections::{HashMap, VecDeque};
 #[derive(Debug, Clone, Default, PartialEq)]
 pub struct TransformLayerMessageHandler {
 	transform_operation: TransformOperation,
+	transform_each: bool,
 
 	slow: bool,
 	snap: bool,
@@ -39,6 +40,7 @@ impl MessageHandler<TransformLayerMessage, (&mut HashMap<Vec<LayerId>, LayerMeta
 
 		let selected_layers = layer_metadata.iter().filter_map(|(layer_path, data)| data.selected.then(|| layer_path)).collect::<Vec<_>>();
 		let mut selected = Selected::new(&mut self.original_transforms, &mut self.pivot, &selected_layers, responses, document);
+		selected.transform_each = self.transform_each;
 
 		let mut begin_operation = |operation: TransformOperation, typing: &mut Typing, mouse_position: &mut DVec2, start_mouse: &mut DVec2| {
 			if operation != TransformOperation::None {
@@ -46,6 +48,9 @@ impl MessageHandler<TransformLayerMessage, (&mut HashMap<Vec<LayerId>, LayerMeta
 				typing.clear();
 			} else {
 				*selected.pivot = selected.calculate_pivot();
+
+				// Switching between grabbing, rotating, and scaling continues the same transform, so it's all undone in one step
+				selected.responses.push_back(DocumentMessage::StartTransaction.into());
 			}
 
 			*mouse_position = ipp.mouse.position;
@@ -59,6 +64,9 @@ impl MessageHandler<TransformLayerMessage, (&mut HashMap<Vec<LayerId>, LayerMeta
 				self.typing.clear();
 
 				self.transform_operation = TransformOperation::None;
+				self.transform_each = false;
+
+				responses.push_back(DocumentMessage::CommitTransaction.into());
 
 				responses.push_back(ToolMessage::DocumentIsDirty.into());
 			}
@@ -69,29 +77,38 @@ impl MessageHandler<TransformLayerMessage, (&mut HashMap<Vec<LayerId>, LayerMeta
 
 				begin_operation(self.transform_operation, &mut self.typing, &mut self.mouse_position, &mut self.start_mouse);
 
+				// Moving each layer by the same amount is no different from moving them together
 				self.transform_operation = TransformOperation::Grabbing(Default::default());
+				self.transform_each = false;
 
 				responses.push_back(ToolMessage::DocumentIsDirty.into());
 			}
-			BeginRotate => {
-				if let TransformOperation::Rotating(_) = self.transform_operation {
+			BeginRotate { transform_each } => {
+				if matches!(self.transform_operation, TransformOperation::Rotating(_)) && self.transform_each == transform_each {
 					return;
 				}
 
 				begin_operation(self.transform_operation, &mut self.typing, &mut self.mouse_position, &mut self.start_mouse);
 
-				self.transform_operation = TransformOperation::Rotating(Default::default());
+				self.transform_operation = TransformOperation::Rotating(Rotation {
+					snap_angle: ipp.rotate_snap_angle,
+					axis_snap_tolerance: ipp.axis_snap_tolerance,
+					..Default::default()
+				});
+				self.transform_each = transform_each;
 
 				responses.push_back(ToolMessage::DocumentIsDirty.into());
 			}
-			BeginScale => {
-				if let TransformOperation::Scaling(_) = self.transform_operation {
+			BeginScale { transform_each } => {
+				if matches!(self.transform_operation, TransformOperation::Scaling(_)) && self.transform_each == transform_each {
 					return;
 				}
 
 				begin_operation(self.transform_operation, &mut self.typing, &mut self.mouse_position, &mut self.start_mouse);
 
 				self.transform_operation = TransformOperation::Scaling(Default::default());
+				self.transform_each = transform_each;
+				selected.transform_each = transform_each;
 				self.transform_operation.apply_transform_operation(&mut selected, self.snap);
 
 				responses.push_back(ToolMessage::DocumentIsDirty.into());
@@ -103,15 +120,19 @@ impl MessageHandler<TransformLayerMessage, (&mut HashMap<Vec<LayerId>, LayerMeta
 				self.typing.clear();
 
 				self.transform_operation = TransformOperation::None;
+				self.transform_each = false;
+
+				// The layers are already back where they started, so this just drops the undo step
+				responses.push_back(DocumentMessage::AbortTransaction.into());
 
 				responses.push_back(ToolMessage::DocumentIsDirty.into());
 			}
 			ConstrainX => self.transform_operation.constrain_axis(Axis::X, &mut selected, self.snap),
 			ConstrainY => self.transform_operation.constrain_axis(Axis::Y, &mut selected, self.snap),
 			PointerMove { slow_key, snap_key } => {
-				self.slow = ipp.keyboard.get(slow_key as usize);
+				self.slow = ipp.is_pressed(slow_key);
 
-				let new_snap = ipp.keyboard.get(snap_key as usize);
+				let new_snap = ipp.is_pressed(snap_key);
 				if new_snap != self.snap {
 					self.snap = new_snap;
 					self.transform_operation.apply_transform_operation(&mut selected, self.snap);
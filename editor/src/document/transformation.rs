@@ -1,4 +1,4 @@
-use crate::consts::{ROTATE_SNAP_ANGLE, SCALE_SNAP_INTERVAL};
+use crate::consts::SCALE_SNAP_INTERVAL;
 use crate::message_prelude::*;
 
 use graphene::document::Document;
@@ -73,6 +73,10 @@ impl Translation {
 pub struct Rotation {
 	pub dragged_angle: f64,
 	pub typed_angle: Option<f64>,
+	/// Increment in degrees the dragged angle snaps to
+	pub snap_angle: f64,
+	/// How close in degrees the dragged angle must bring the layers to 0°, 90°, 180°, or 270° to straighten them onto it
+	pub axis_snap_tolerance: f64,
 }
 
 impl Rotation {
@@ -80,7 +84,7 @@ impl Rotation {
 		if let Some(value) = self.typed_angle {
 			value.to_radians()
 		} else if snap {
-			let snap_resolution = ROTATE_SNAP_ANGLE.to_radians();
+			let snap_resolution = self.snap_angle.to_radians();
 			(self.dragged_angle / snap_resolution).round() * snap_resolution
 		} else {
 			self.dragged_angle
@@ -92,6 +96,8 @@ impl Rotation {
 		Self {
 			dragged_angle: self.dragged_angle + delta,
 			typed_angle: None,
+			snap_angle: self.snap_angle,
+			axis_snap_tolerance: self.axis_snap_tolerance,
 		}
 	}
 }
@@ -154,7 +160,14 @@ impl TransformOperation {
 		if self != &TransformOperation::None {
 			let transformation = match self {
 				TransformOperation::Grabbing(translation) => DAffine2::from_translation(translation.to_dvec()),
-				TransformOperation::Rotating(rotation) => DAffine2::from_angle(rotation.to_f64(snapping)),
+				TransformOperation::Rotating(rotation) => {
+					let angle = rotation.to_f64(snapping);
+					// A typed angle is applied exactly, while a dragged one is straightened like rotating with the Select tool's handles
+					match rotation.typed_angle {
+						Some(_) => DAffine2::from_angle(angle),
+						None => DAffine2::from_angle(selected.snap_rotation_to_axes(angle, rotation.axis_snap_tolerance)),
+					}
+				}
 				TransformOperation::Scaling(scale) => DAffine2::from_scale(scale.to_dvec(snapping)),
 				TransformOperation::None => unreachable!(),
 			};
@@ -192,6 +205,8 @@ pub struct Selected<'a> {
 	pub document: &'a Document,
 	pub original_transforms: &'a mut OriginalTransforms,
 	pub pivot: &'a mut DVec2,
+	/// Transform each layer about its own center instead of about the shared pivot
+	pub transform_each: bool,
 }
 
 impl<'a> Selected<'a> {
@@ -207,6 +222,7 @@ impl<'a> Selected<'a> {
 			document,
 			original_transforms,
 			pivot,
+			transform_each: false,
 		}
 	}
 
@@ -231,10 +247,42 @@ impl<'a> Selected<'a> {
 		xy_summation / self.selected.len() as f64
 	}
 
+	/// Adjusts a rotation `angle` (in radians) so the first selected layer lands exactly on 0°, 90°, 180°, or 270° in document space when it would end up within `tolerance` degrees of one
+	pub fn snap_rotation_to_axes(&self, angle: f64, tolerance: f64) -> f64 {
+		let layer_path = match self.selected.first() {
+			Some(layer_path) => layer_path,
+			None => return angle,
+		};
+		let original_layer_transform = match self.original_transforms.get(*layer_path) {
+			Some(transform) => *transform,
+			None => return angle,
+		};
+
+		let parent_folder_path = &layer_path[..layer_path.len() - 1];
+		// A layer that's no longer in the document is left as it is rather than straightened
+		let to = match self.document.generate_transform_across_scope(parent_folder_path, None) {
+			Ok(to) => to,
+			Err(_) => return angle,
+		};
+		// The canvas rotation is left out so "axis-aligned" means straight in the document, not on screen
+		let document_transform = self.document.root.transform.inverse() * to * original_layer_transform;
+		let x_axis = document_transform.matrix2.x_axis;
+
+		let end_angle = x_axis.y.atan2(x_axis.x) + angle;
+		let quarter_turn = std::f64::consts::FRAC_PI_2;
+		let offset = (end_angle / quarter_turn).round() * quarter_turn - end_angle;
+
+		if offset.abs() < tolerance.to_radians() {
+			angle + offset
+		} else {
+			angle
+		}
+	}
+
 	pub fn update_transforms(&mut self, delta: DAffine2) {
 		if !self.selected.is_empty() {
-			let pivot = DAffine2::from_translation(*self.pivot);
-			let transformation = pivot * delta * pivot.inverse();
+			let about = |pivot: DVec2| DAffine2::from_translation(pivot) * delta * DAffine2::from_translation(-pivot);
+			let shared_transformation = about(*self.pivot);
 
 			// TODO: Cache the result of `shallowest_unique_layers` to avoid this heavy computation every frame of movement, see https://github.com/GraphiteEditor/Graphite/pull/481
 			for layer_path in Document::shallowest_unique_layers(self.selected.iter()) {
@@ -242,6 +290,14 @@ impl<'a> Selected<'a> {
 				let original_layer_transforms = *self.original_transforms.get(*layer_path).unwrap();
 
 				let to = self.document.generate_transform_across_scope(parent_folder_path, None).unwrap();
+				let transformation = if self.transform_each {
+					// The center of the layer from before the transform began, so it stays put as the layer turns or grows around it
+					let original_viewport_transform = to * original_layer_transforms;
+					let bounds = self.document.layer(layer_path).unwrap().current_bounding_box_with_transform(original_viewport_transform);
+					about(bounds.map_or(original_viewport_transform.translation, |[min, max]| (min + max) / 2.))
+				} else {
+					shared_transformation
+				};
 				let new = to.inverse() * transformation * to * original_layer_transforms;
 
 				self.responses.push_back(
@@ -1,11 +1,22 @@
 pub use super::layer_panel::{layer_panel_entry, LayerMetadata, LayerPanelEntry, RawBuffer};
+use graphene::color::Color;
 use graphene::document::Document as GrapheneDocument;
+use graphene::layers::style::PathStyle;
 use graphene::LayerId;
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-pub type DocumentSave = (GrapheneDocument, HashMap<Vec<LayerId>, LayerMetadata>);
+/// The document, its layer metadata, and the background colors from before an undoable step, along with the name of that step once it's known.
+pub type DocumentSave = (GrapheneDocument, HashMap<Vec<LayerId>, LayerMetadata>, ArtboardBackgrounds, Option<String>);
+
+/// The background colors of the infinite canvas and of each artboard, which are saved with each undo step.
+/// The artboards themselves aren't part of the undo history, so only their colors are restored.
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct ArtboardBackgrounds {
+	pub canvas: Option<Color>,
+	pub artboards: Vec<(LayerId, Color)>,
+}
 
 #[derive(PartialEq, Clone, Debug, Serialize, Deserialize, Hash)]
 pub enum FlipAxis {
@@ -26,3 +37,83 @@ pub enum AlignAggregate {
 	Center,
 	Average,
 }
+
+/// Which part of the document an SVG export covers, with the exported view box fitting it.
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize, Hash)]
+pub enum ExportScope {
+	/// All the artwork, fit to the artboards or, on an infinite canvas, to the artwork itself
+	Document,
+	/// Only the selected layers, fit tightly to their bounds, leaving out every other layer even where it overlaps them
+	Selection,
+	/// The artwork within the bounds of a single artboard
+	Artboard { artboard: LayerId },
+}
+
+/// Whether a numeric transform input sets the value for the selection as a whole, or offsets each selected layer by it.
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize, Hash)]
+pub enum TransformInputMode {
+	Absolute,
+	Relative,
+}
+
+impl Default for TransformInputMode {
+	fn default() -> Self {
+		Self::Absolute
+	}
+}
+
+/// Which shapes a style is applied to, by what their current style has in common with the styles of the selected shapes.
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize, Hash)]
+pub enum StyleMatchCriterion {
+	/// Every shape in the document, regardless of the selection
+	AnyShape,
+	SameFill,
+	SameStroke,
+	SameStyle,
+}
+
+impl StyleMatchCriterion {
+	/// Whether a shape with the given style matches the criterion for any of the selected shapes' styles.
+	pub fn matches(self, style: &PathStyle, selected_styles: &[PathStyle]) -> bool {
+		match self {
+			StyleMatchCriterion::AnyShape => true,
+			StyleMatchCriterion::SameFill => selected_styles.iter().any(|selected| selected.fill() == style.fill()),
+			StyleMatchCriterion::SameStroke => selected_styles.iter().any(|selected| selected.stroke() == style.stroke()),
+			StyleMatchCriterion::SameStyle => selected_styles.contains(style),
+		}
+	}
+}
+
+/// Common sizes for the artboard a new document starts with.
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize, Hash)]
+pub enum ArtboardPreset {
+	A4,
+	FullHd,
+	Square,
+}
+
+/// The artboard a new document starts with, so there's a page to work on rather than only the infinite canvas.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct NewDocumentArtboard {
+	pub name: String,
+	/// The width and height in document pixels
+	pub size: (f64, f64),
+	pub background: Color,
+}
+
+impl NewDocumentArtboard {
+	pub fn from_preset(preset: ArtboardPreset) -> Self {
+		let (name, size) = match preset {
+			// A4 paper at 96 pixels per inch
+			ArtboardPreset::A4 => ("A4", (794., 1123.)),
+			ArtboardPreset::FullHd => ("1080p", (1920., 1080.)),
+			ArtboardPreset::Square => ("Square", (1080., 1080.)),
+		};
+
+		Self {
+			name: name.into(),
+			size,
+			background: Color::WHITE,
+		}
+	}
+}
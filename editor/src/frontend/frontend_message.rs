@@ -1,4 +1,4 @@
-use super::utility_types::{FrontendDocumentDetails, MouseCursorIcon};
+use super::utility_types::{FrontendDocumentDetails, FrontendRecentDocument, MouseCursorIcon};
 use crate::document::layer_panel::{LayerPanelEntry, RawBuffer};
 use crate::layout::layout_message::LayoutTarget;
 use crate::layout::widgets::SubLayout;
@@ -6,6 +6,7 @@ use crate::message_prelude::*;
 use crate::misc::HintData;
 use crate::Color;
 
+use glam::DVec2;
 use serde::{Deserialize, Serialize};
 
 #[remain::sorted]
@@ -14,37 +15,127 @@ use serde::{Deserialize, Serialize};
 pub enum FrontendMessage {
 	// Display prefix: make the frontend show something, like a dialog
 	DisplayConfirmationToCloseAllDocuments,
-	DisplayConfirmationToCloseDocument { document_id: u64 },
+	DisplayConfirmationToCloseDocument {
+		document_id: u64,
+	},
 	DisplayDialogAboutGraphite,
-	DisplayDialogComingSoon { issue: Option<i32> },
-	DisplayDialogError { title: String, description: String },
-	DisplayDialogPanic { panic_info: String, title: String, description: String },
-	DisplayDocumentLayerTreeStructure { data_buffer: RawBuffer },
-	DisplayEditableTextbox { text: String, line_width: Option<f64>, font_size: f64 },
+	DisplayDialogComingSoon {
+		issue: Option<i32>,
+	},
+	DisplayDialogError {
+		title: String,
+		description: String,
+	},
+	DisplayDialogPanic {
+		panic_info: String,
+		title: String,
+		description: String,
+	},
+	DisplayDocumentLayerTreeStructure {
+		data_buffer: RawBuffer,
+	},
+	DisplayEditableTextbox {
+		text: String,
+		line_width: Option<f64>,
+	},
 	DisplayRemoveEditableTextbox,
 
 	// Trigger prefix: cause a browser API to do something
-	TriggerFileDownload { document: String, name: String },
+	TriggerFileDownload {
+		document: String,
+		name: String,
+	},
 	TriggerFileUpload,
-	TriggerIndexedDbRemoveDocument { document_id: u64 },
-	TriggerIndexedDbWriteDocument { document: String, details: FrontendDocumentDetails, version: String },
+	TriggerIndexedDbRemoveDocument {
+		document_id: u64,
+	},
+	TriggerIndexedDbWriteDocument {
+		document: String,
+		details: FrontendDocumentDetails,
+		version: String,
+	},
+	TriggerSaveSwatchLibrary {
+		swatch_library: String,
+	},
 	TriggerTextCommit,
+	TriggerTextCopy {
+		copy_text: String,
+	},
 
 	// Update prefix: give the frontend a new value or state for it to use
-	UpdateActiveDocument { document_id: u64 },
-	UpdateActiveTool { tool_name: String },
-	UpdateCanvasRotation { angle_radians: f64 },
-	UpdateCanvasZoom { factor: f64 },
-	UpdateDocumentArtboards { svg: String },
-	UpdateDocumentArtwork { svg: String },
-	UpdateDocumentBarLayout { layout_target: LayoutTarget, layout: SubLayout },
-	UpdateDocumentLayer { data: LayerPanelEntry },
-	UpdateDocumentOverlays { svg: String },
-	UpdateDocumentRulers { origin: (f64, f64), spacing: f64, interval: f64 },
-	UpdateDocumentScrollbars { position: (f64, f64), size: (f64, f64), multiplier: (f64, f64) },
-	UpdateInputHints { hint_data: HintData },
-	UpdateMouseCursor { cursor: MouseCursorIcon },
-	UpdateOpenDocumentsList { open_documents: Vec<FrontendDocumentDetails> },
-	UpdateToolOptionsLayout { layout_target: LayoutTarget, layout: SubLayout },
-	UpdateWorkingColors { primary: Color, secondary: Color },
+	UpdateActiveDocument {
+		document_id: u64,
+	},
+	UpdateActiveTool {
+		tool_name: String,
+	},
+	UpdateCanvasRotation {
+		angle_radians: f64,
+	},
+	UpdateCanvasZoom {
+		factor: f64,
+	},
+	UpdateDocumentArtboards {
+		svg: String,
+	},
+	UpdateDocumentArtwork {
+		svg: String,
+	},
+	UpdateDocumentBarLayout {
+		layout_target: LayoutTarget,
+		layout: SubLayout,
+	},
+	UpdateDocumentLayer {
+		data: LayerPanelEntry,
+	},
+	UpdateDocumentOverlays {
+		svg: String,
+	},
+	UpdateDocumentRulers {
+		origin: (f64, f64),
+		spacing: f64,
+		interval: f64,
+	},
+	UpdateDocumentScrollbars {
+		position: (f64, f64),
+		size: (f64, f64),
+		multiplier: (f64, f64),
+	},
+	UpdateFrameTicksRequested {
+		requested: bool,
+	},
+	UpdateInputHints {
+		hint_data: HintData,
+	},
+	UpdateMouseCursor {
+		cursor: MouseCursorIcon,
+	},
+	UpdateOpenDocumentsList {
+		open_documents: Vec<FrontendDocumentDetails>,
+	},
+	UpdateRecentDocumentsList {
+		recent_documents: Vec<FrontendRecentDocument>,
+	},
+	UpdateSelectionBounds {
+		bounds: Option<[DVec2; 2]>,
+		rotation: f64,
+	},
+	UpdateSwatchLibraryLayout {
+		layout_target: LayoutTarget,
+		layout: SubLayout,
+	},
+	UpdateToolOptionsLayout {
+		layout_target: LayoutTarget,
+		layout: SubLayout,
+	},
+	UpdateUndoRedoState {
+		can_undo: bool,
+		can_redo: bool,
+		undo_label: Option<String>,
+		redo_label: Option<String>,
+	},
+	UpdateWorkingColors {
+		primary: Color,
+		secondary: Color,
+	},
 }
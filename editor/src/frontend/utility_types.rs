@@ -7,9 +7,16 @@ pub struct FrontendDocumentDetails {
 	pub id: u64,
 }
 
+#[derive(PartialEq, Clone, Deserialize, Serialize, Debug)]
+pub struct FrontendRecentDocument {
+	pub name: String,
+	pub id: u64,
+}
+
 #[derive(Clone, Copy, Debug, Eq, Deserialize, PartialEq, Serialize)]
 pub enum MouseCursorIcon {
 	Default,
+	Pointer,
 	ZoomIn,
 	ZoomOut,
 	Grabbing,
@@ -1,12 +1,39 @@
+use super::swatches::SwatchTarget;
+use crate::input::input_mapper::ModifierBindings;
+use crate::input::input_preprocessor::{Platform, WheelBehavior};
 use crate::message_prelude::*;
 
+use graphene::color::Color;
+
 use serde::{Deserialize, Serialize};
 
 #[remain::sorted]
 #[impl_message(Message, Global)]
-#[derive(PartialEq, Clone, Debug, Hash, Serialize, Deserialize)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub enum GlobalMessage {
+	AddSwatch { name: String, color: Color },
+	ApplySwatch { index: usize, target: SwatchTarget },
+	ImportPalette { palette: String },
+	LoadSwatchLibrary { swatch_library: String },
 	LogDebug,
 	LogInfo,
 	LogTrace,
+	PickSwatch { index: usize },
+	RemoveSwatch { index: usize },
+	RenameSwatch { index: usize, name: String },
+	SetAnchorSnapTolerance { tolerance: f64 },
+	SetAutoPan { margin: f64, speed: f64 },
+	SetAxisSnapTolerance { tolerance: f64 },
+	SetDeselectOnEmptyClick { enabled: bool },
+	SetHandleMirroring { enabled: bool },
+	SetModifierBindings { bindings: ModifierBindings },
+	SetOverlaysVisible { visible: bool },
+	SetPixelSnapping { enabled: bool },
+	SetPlatform { platform: Platform },
+	SetRotateSnapAngle { angle: f64 },
+	SetSelectionOutlineStyle { color: Color, width: f64 },
+	SetSelectionTolerance { tolerance: f64, point_threshold: f64 },
+	SetSnapTargetsVisible { visible: bool },
+	SetSwatchTarget { target: SwatchTarget },
+	SetWheelBehavior { wheel_behavior: WheelBehavior },
 }
@@ -1,17 +1,57 @@
+use super::swatches::{Swatch, SwatchLibrary, SwatchTarget};
+use crate::layout::layout_message::LayoutTarget;
+use crate::layout::widgets::PropertyHolder;
 use crate::message_prelude::*;
 
 use std::collections::VecDeque;
 
 #[derive(Debug, Default)]
-pub struct GlobalMessageHandler {}
+pub struct GlobalMessageHandler {
+	swatch_library: SwatchLibrary,
+}
 
 impl MessageHandler<GlobalMessage, ()> for GlobalMessageHandler {
 	#[remain::check]
-	fn process_action(&mut self, message: GlobalMessage, _data: (), _responses: &mut VecDeque<Message>) {
+	fn process_action(&mut self, message: GlobalMessage, _data: (), responses: &mut VecDeque<Message>) {
 		use GlobalMessage::*;
 
 		#[remain::sorted]
 		match message {
+			AddSwatch { name, color } => {
+				self.swatch_library.swatches.push(Swatch { name, color });
+				self.swatch_library.commit_changes(responses);
+			}
+			ApplySwatch { index, target } => match self.swatch_library.get(index) {
+				Ok(swatch) => {
+					let color = swatch.color;
+					let message: Message = match target {
+						SwatchTarget::PrimaryColor => ToolMessage::SelectPrimaryColor { color }.into(),
+						SwatchTarget::SelectedLayers => DocumentMessage::SetFillForSelectedLayers { color }.into(),
+					};
+					responses.push_back(message);
+				}
+				Err(error) => log::warn!("Failed to apply swatch: {}", error),
+			},
+			ImportPalette { palette } => match SwatchLibrary::parse_palette(&palette) {
+				Ok(swatches) => {
+					self.swatch_library.swatches.extend(swatches);
+					self.swatch_library.commit_changes(responses);
+				}
+				Err(error) => responses.push_back(
+					FrontendMessage::DisplayDialogError {
+						title: "Failed to import palette".to_string(),
+						description: error.to_string(),
+					}
+					.into(),
+				),
+			},
+			LoadSwatchLibrary { swatch_library } => match serde_json::from_str::<SwatchLibrary>(&swatch_library) {
+				Ok(loaded) => {
+					self.swatch_library.swatches = loaded.swatches;
+					self.swatch_library.register_properties(responses, LayoutTarget::SwatchLibrary);
+				}
+				Err(error) => log::warn!("Failed to load the saved swatch library: {}", error),
+			},
 			LogDebug => {
 				log::set_max_level(log::LevelFilter::Debug);
 				log::info!("Set log verbosity to debug");
@@ -24,8 +64,73 @@ impl MessageHandler<GlobalMessage, ()> for GlobalMessageHandler {
 				log::set_max_level(log::LevelFilter::Trace);
 				log::info!("Set log verbosity to trace");
 			}
+			PickSwatch { index } => {
+				self.swatch_library.picked_index = index;
+				let target = self.swatch_library.picked_target;
+				responses.push_back(ApplySwatch { index, target }.into());
+			}
+			RemoveSwatch { index } => {
+				if index < self.swatch_library.swatches.len() {
+					self.swatch_library.swatches.remove(index);
+					self.swatch_library.commit_changes(responses);
+				} else {
+					log::warn!("Tried to remove nonexistent swatch at index {}", index);
+				}
+			}
+			RenameSwatch { index, name } => match self.swatch_library.get_mut(index) {
+				Ok(swatch) => {
+					swatch.name = name;
+					self.swatch_library.commit_changes(responses);
+				}
+				Err(error) => log::warn!("Failed to rename swatch: {}", error),
+			},
+			SetAnchorSnapTolerance { tolerance } => {
+				responses.push_back(ToolMessage::SetAnchorSnapTolerance { tolerance }.into());
+			}
+			SetAutoPan { margin, speed } => {
+				responses.push_back(ToolMessage::SetAutoPan { margin, speed }.into());
+			}
+			SetAxisSnapTolerance { tolerance } => {
+				responses.push_back(InputPreprocessorMessage::SetAxisSnapTolerance { tolerance }.into());
+			}
+			SetDeselectOnEmptyClick { enabled } => {
+				responses.push_back(ToolMessage::SetDeselectOnEmptyClick { enabled }.into());
+			}
+			SetHandleMirroring { enabled } => {
+				responses.push_back(ToolMessage::SetHandleMirroring { enabled }.into());
+			}
+			SetModifierBindings { bindings } => {
+				responses.push_back(InputPreprocessorMessage::SetModifierBindings { bindings }.into());
+			}
+			SetOverlaysVisible { visible } => {
+				responses.push_back(PortfolioMessage::SetOverlaysVisibility { visible }.into());
+			}
+			SetPixelSnapping { enabled } => {
+				responses.push_back(ToolMessage::SetPixelSnapping { enabled }.into());
+			}
+			SetPlatform { platform } => {
+				responses.push_back(InputPreprocessorMessage::SetPlatform { platform }.into());
+			}
+			SetRotateSnapAngle { angle } => {
+				responses.push_back(InputPreprocessorMessage::SetRotateSnapAngle { angle }.into());
+			}
+			SetSelectionOutlineStyle { color, width } => {
+				responses.push_back(ToolMessage::SetSelectionOutlineStyle { color, width }.into());
+			}
+			SetSelectionTolerance { tolerance, point_threshold } => {
+				responses.push_back(ToolMessage::SetSelectionTolerance { tolerance, point_threshold }.into());
+			}
+			SetSnapTargetsVisible { visible } => {
+				responses.push_back(PortfolioMessage::SetSnapTargetsVisibility { visible }.into());
+			}
+			SetSwatchTarget { target } => {
+				self.swatch_library.picked_target = target;
+			}
+			SetWheelBehavior { wheel_behavior } => {
+				responses.push_back(InputPreprocessorMessage::SetWheelBehavior { wheel_behavior }.into());
+			}
 		}
 	}
 
-	advertise_actions!(GlobalMessageDiscriminant; LogInfo, LogDebug, LogTrace);
+	advertise_actions!(GlobalMessageDiscriminant; AddSwatch, ApplySwatch, ImportPalette, LoadSwatchLibrary, LogInfo, LogDebug, LogTrace, PickSwatch, RemoveSwatch, RenameSwatch, SetAnchorSnapTolerance, SetAutoPan, SetAxisSnapTolerance, SetDeselectOnEmptyClick, SetHandleMirroring, SetModifierBindings, SetOverlaysVisible, SetPixelSnapping, SetPlatform, SetRotateSnapAngle, SetSelectionOutlineStyle, SetSelectionTolerance, SetSnapTargetsVisible, SetSwatchTarget, SetWheelBehavior);
 }
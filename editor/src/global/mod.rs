@@ -1,6 +1,8 @@
 mod global_message;
 mod global_message_handler;
 
+pub mod swatches;
+
 #[doc(inline)]
 pub use global_message::{GlobalMessage, GlobalMessageDiscriminant};
 #[doc(inline)]
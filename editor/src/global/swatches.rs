@@ -0,0 +1,136 @@
+use crate::layout::layout_message::LayoutTarget;
+use crate::layout::widgets::{DropdownEntryData, DropdownInput, LayoutRow, PropertyHolder, RadioEntryData, RadioInput, Widget, WidgetCallback, WidgetHolder, WidgetLayout};
+use crate::message_prelude::*;
+use crate::EditorError;
+
+use graphene::color::Color;
+
+use serde::{Deserialize, Serialize};
+
+/// A color in the swatch library, along with the name it's listed under.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Swatch {
+	pub name: String,
+	pub color: Color,
+}
+
+/// Where the color of a swatch goes when it's applied.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum SwatchTarget {
+	PrimaryColor,
+	SelectedLayers,
+}
+
+impl Default for SwatchTarget {
+	fn default() -> Self {
+		Self::PrimaryColor
+	}
+}
+
+/// The user's library of named colors, which is saved along with the other editor settings.
+#[derive(PartialEq, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SwatchLibrary {
+	pub swatches: Vec<Swatch>,
+	/// The swatch most recently picked from the library, shown as the selected entry in its dropdown
+	#[serde(skip)]
+	pub picked_index: usize,
+	/// Where picking a swatch from the dropdown applies its color
+	#[serde(skip)]
+	pub picked_target: SwatchTarget,
+}
+
+impl SwatchLibrary {
+	/// Parses a palette made of one swatch per line, written as a hex color (with an optional # prefix) followed by the swatch's name.
+	/// Blank lines and lines starting with `//` are skipped, and a swatch without a name is named after its hex color.
+	///
+	/// ```text
+	/// // Brand colors
+	/// #E52E1B Signal Red
+	/// 1B4B8CFF Deep Blue
+	/// ```
+	pub fn parse_palette(palette: &str) -> Result<Vec<Swatch>, EditorError> {
+		palette
+			.lines()
+			.enumerate()
+			.map(|(index, line)| (index + 1, line.trim()))
+			.filter(|(_, line)| !line.is_empty() && !line.starts_with("//"))
+			.map(|(line_number, line)| {
+				let (hex, name) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+				let color = Color::from_hex_str(hex).ok_or_else(|| EditorError::Misc(format!("Line {} does not start with a valid hex color: \"{}\"", line_number, line)))?;
+				let name = match name.trim() {
+					"" => hex.trim_start_matches('#').to_uppercase(),
+					name => name.to_string(),
+				};
+				Ok(Swatch { name, color })
+			})
+			.collect()
+	}
+
+	pub fn get(&self, index: usize) -> Result<&Swatch, EditorError> {
+		self.swatches.get(index).ok_or_else(|| EditorError::Misc(format!("There is no swatch at index {}", index)))
+	}
+
+	pub fn get_mut(&mut self, index: usize) -> Result<&mut Swatch, EditorError> {
+		self.swatches.get_mut(index).ok_or_else(|| EditorError::Misc(format!("There is no swatch at index {}", index)))
+	}
+
+	/// Sends the library's widgets to the frontend and asks for the library to be saved, after the swatches have changed.
+	pub fn commit_changes(&mut self, responses: &mut VecDeque<Message>) {
+		self.picked_index = self.picked_index.min(self.swatches.len().saturating_sub(1));
+		self.register_properties(responses, LayoutTarget::SwatchLibrary);
+
+		let swatch_library = serde_json::to_string(self).expect("Failed to serialize the swatch library");
+		responses.push_back(FrontendMessage::TriggerSaveSwatchLibrary { swatch_library }.into());
+	}
+}
+
+impl PropertyHolder for SwatchLibrary {
+	fn properties(&self) -> WidgetLayout {
+		if self.swatches.is_empty() {
+			return WidgetLayout::default();
+		}
+
+		let swatch_entries = self
+			.swatches
+			.iter()
+			.enumerate()
+			.map(|(index, swatch)| DropdownEntryData {
+				value: index.to_string(),
+				label: format!("{} #{}", swatch.name, swatch.color.rgb_hex()),
+				on_update: WidgetCallback::new(|entry: &DropdownEntryData| GlobalMessage::PickSwatch { index: entry.value.parse().unwrap() }.into()),
+			})
+			.collect();
+
+		WidgetLayout::new(vec![LayoutRow::Row {
+			name: "".into(),
+			widgets: vec![
+				WidgetHolder::new(Widget::DropdownInput(DropdownInput {
+					entries: vec![swatch_entries],
+					selected_index: self.picked_index as u32,
+				})),
+				WidgetHolder::new(Widget::RadioInput(RadioInput {
+					selected_index: match self.picked_target {
+						SwatchTarget::PrimaryColor => 0,
+						SwatchTarget::SelectedLayers => 1,
+					},
+					entries: vec![
+						RadioEntryData {
+							value: "primary".into(),
+							label: "Primary".into(),
+							tooltip: "Picking a swatch sets the primary color".into(),
+							on_update: WidgetCallback::new(|_| GlobalMessage::SetSwatchTarget { target: SwatchTarget::PrimaryColor }.into()),
+							..RadioEntryData::default()
+						},
+						RadioEntryData {
+							value: "selection".into(),
+							label: "Selection".into(),
+							tooltip: "Picking a swatch fills the selected layers".into(),
+							on_update: WidgetCallback::new(|_| GlobalMessage::SetSwatchTarget { target: SwatchTarget::SelectedLayers }.into()),
+							..RadioEntryData::default()
+						},
+					],
+				})),
+			],
+		}])
+	}
+}
@@ -1,35 +1,104 @@
+use super::input_preprocessor::WheelBehavior;
 use super::keyboard::{Key, KeyStates, NUMBER_OF_KEYS};
 use crate::document::clipboards::Clipboard;
+use crate::document::utility_types::ExportScope;
 use crate::message_prelude::*;
 use crate::viewport_tools::tool::ToolType;
 
 use glam::DVec2;
+use serde::{Deserialize, Serialize};
 
 const NUDGE_AMOUNT: f64 = 1.;
 const SHIFT_NUDGE_AMOUNT: f64 = 10.;
 
+/// The keys held to change how the tools behave, which can be reassigned to suit other keyboard layouts or left-handed use.
+/// Every tool modifier other than the primary modifier goes through these, so they're the only keys the tools read while they're held.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash, Serialize, Deserialize)]
+pub struct ModifierBindings {
+	/// Held to draw or resize from the center instead of a corner, and for the tools' other secondary actions like rotating freely or breaking handles
+	pub center: Key,
+	/// Held to constrain the proportions, axis, or angle of what's being drawn or moved, and to add to the selection or zoom out
+	pub constrain: Key,
+}
+
+impl Default for ModifierBindings {
+	fn default() -> Self {
+		Self {
+			center: Key::KeyAlt,
+			constrain: Key::KeyShift,
+		}
+	}
+}
+
+impl ModifierBindings {
+	/// Keys that the tools using these bindings already give another meaning while they're held, so they can't be bound as well
+	const RESERVED_KEYS: [Key; 9] = [
+		Key::UnknownKey,
+		Key::KeyR,
+		Key::KeyControl,
+		Key::KeyPrimaryModifier,
+		Key::KeySpace,
+		Key::KeyEscape,
+		Key::Lmb,
+		Key::Rmb,
+		Key::Mmb,
+	];
+
+	/// Describes each way the bindings clash with each other or with keys the tools already use, so they can only be applied if there are none.
+	pub fn conflicts(&self) -> Vec<String> {
+		let mut conflicts = Vec::new();
+
+		if self.center == self.constrain {
+			conflicts.push(format!("{:?} is bound to both center and constrain", self.center));
+		}
+		for (role, key) in [("center", self.center), ("constrain", self.constrain)] {
+			if Self::RESERVED_KEYS.contains(&key) {
+				conflicts.push(format!("{:?} can't be bound to {} because the tools already use it", key, role));
+			}
+		}
+
+		conflicts
+	}
+}
+
 #[derive(Debug, Clone)]
 pub struct Mapping {
+	/// The tool modifier keys this mapping was built with
+	pub bindings: ModifierBindings,
 	pub key_up: [KeyMappingEntries; NUMBER_OF_KEYS],
 	pub key_down: [KeyMappingEntries; NUMBER_OF_KEYS],
 	pub pointer_move: KeyMappingEntries,
+	/// Used when the plain mouse wheel scrolls the canvas
 	pub mouse_scroll: KeyMappingEntries,
+	/// Used instead of `mouse_scroll` when the plain mouse wheel zooms the canvas
+	pub mouse_scroll_zoom: KeyMappingEntries,
 	pub double_click: KeyMappingEntries,
+	pub frame_tick: KeyMappingEntries,
 }
 
 impl Default for Mapping {
 	fn default() -> Self {
+		Self::new(ModifierBindings::default())
+	}
+}
+
+impl Mapping {
+	/// Builds the mapping with the tools' center and constrain modifiers bound to the given keys.
+	pub fn new(bindings: ModifierBindings) -> Self {
 		use input_mapper_macros::{entry, mapping, modifiers};
 		use Key::*;
 
+		let ModifierBindings { center, constrain } = bindings;
+
 		// WARNING!
 		// If a new mapping isn't being handled (and perhaps another lower-precedence one is instead), make sure to advertise
 		// it as an available action in the respective message handler file (such as the bottom of `document_message_handler.rs`).
 
 		let mappings = mapping![
 			// Higher priority than entries in sections below
-			entry! {action=PortfolioMessage::Paste { clipboard: Clipboard::User }, key_down=KeyV, modifiers=[KeyControl]},
-			entry! {action=MovementMessage::PointerMove { snap_angle: KeyControl, wait_for_snap_angle_release: true, snap_zoom: KeyControl, zoom_from_viewport: None }, message=InputMapperMessage::PointerMove},
+			entry! {action=PortfolioMessage::PasteReplace { clipboard: Clipboard::User }, key_down=KeyV, modifiers=[KeyPrimaryModifier, KeyShift]},
+			entry! {action=PortfolioMessage::Paste { clipboard: Clipboard::User }, key_down=KeyV, modifiers=[KeyPrimaryModifier]},
+			entry! {action=MovementMessage::PointerMove { snap_angle: KeyPrimaryModifier, snap_angle_to_selection: KeyShift, wait_for_snap_angle_release: true, snap_zoom: KeyPrimaryModifier, zoom_from_viewport: None }, message=InputMapperMessage::PointerMove},
 			// Transform layers
 			entry! {action=TransformLayerMessage::ApplyTransformOperation, key_down=KeyEnter},
 			entry! {action=TransformLayerMessage::ApplyTransformOperation, key_down=Lmb},
@@ -41,22 +110,27 @@ impl Default for Mapping {
 			entry! {action=TransformLayerMessage::TypeNegate, key_down=KeyMinus},
 			entry! {action=TransformLayerMessage::TypeDecimalPoint, key_down=KeyComma},
 			entry! {action=TransformLayerMessage::TypeDecimalPoint, key_down=KeyPeriod},
-			entry! {action=TransformLayerMessage::PointerMove { slow_key: KeyShift, snap_key: KeyControl }, triggers=[KeyShift, KeyControl]},
+			entry! {action=TransformLayerMessage::PointerMove { slow_key: KeyShift, snap_key: KeyPrimaryModifier }, triggers=[KeyShift, KeyPrimaryModifier]},
+			// Snap reference points
+			entry! {action=DocumentMessage::PlaceSnapReferencePoint, key_down=Lmb, modifiers=[KeyPrimaryModifier, KeyAlt]},
 			// Select
-			entry! {action=SelectMessage::PointerMove { axis_align: KeyShift, snap_angle: KeyControl, center: KeyAlt }, message=InputMapperMessage::PointerMove},
-			entry! {action=SelectMessage::DragStart { add_to_selection: KeyShift }, key_down=Lmb},
-			entry! {action=SelectMessage::DragStop, key_up=Lmb},
+			entry! {action=SelectMessage::PointerMove { axis_align: constrain, snap_angle: KeyPrimaryModifier, free_rotate: center, center, measure: center, enclose: center }, message=InputMapperMessage::PointerMove},
+			entry! {action=SelectMessage::DragStart { add_to_selection: constrain }, key_down=Lmb},
+			entry! {action=SelectMessage::DragStop { enclose: center }, key_up=Lmb},
+			entry! {action=SelectMessage::AutoPan, message=InputMapperMessage::FrameTick},
 			entry! {action=SelectMessage::EditLayer, message=InputMapperMessage::DoubleClick},
 			entry! {action=SelectMessage::Abort, key_down=Rmb},
 			entry! {action=SelectMessage::Abort, key_down=KeyEscape},
 			// Crop
 			entry! {action=CropMessage::PointerDown, key_down=Lmb},
-			entry! {action=CropMessage::PointerMove { constrain_axis_or_aspect: KeyShift, center: KeyAlt }, message=InputMapperMessage::PointerMove},
+			entry! {action=CropMessage::PointerMove { constrain_axis_or_aspect: constrain, center }, message=InputMapperMessage::PointerMove},
 			entry! {action=CropMessage::PointerUp, key_up=Lmb},
+			entry! {action=CropMessage::Abort, key_down=Rmb},
+			entry! {action=CropMessage::Abort, key_down=KeyEscape},
 			// Navigate
-			entry! {action=NavigateMessage::ClickZoom { zoom_in: false }, key_up=Lmb, modifiers=[KeyShift]},
+			entry! {action=NavigateMessage::ClickZoom { zoom_in: false }, key_up=Lmb, modifiers=[constrain]},
 			entry! {action=NavigateMessage::ClickZoom { zoom_in: true }, key_up=Lmb},
-			entry! {action=NavigateMessage::PointerMove { snap_angle: KeyControl, snap_zoom: KeyControl }, message=InputMapperMessage::PointerMove},
+			entry! {action=NavigateMessage::PointerMove { snap_angle: KeyPrimaryModifier, snap_angle_to_selection: constrain, snap_zoom: KeyPrimaryModifier }, message=InputMapperMessage::PointerMove},
 			entry! {action=NavigateMessage::TranslateCanvasBegin, key_down=Mmb},
 			entry! {action=NavigateMessage::RotateCanvasBegin, key_down=Rmb},
 			entry! {action=NavigateMessage::ZoomCanvasBegin, key_down=Lmb},
@@ -68,40 +142,48 @@ impl Default for Mapping {
 			entry! {action=EyedropperMessage::RightMouseDown, key_down=Rmb},
 			// Text
 			entry! {action=TextMessage::Interact, key_up=Lmb},
-			entry! {action=TextMessage::Abort, key_down=KeyEscape},
-			entry! {action=TextMessage::CommitText, key_down=KeyEnter, modifiers=[KeyControl]},
+			entry! {action=TextMessage::CommitText, key_down=KeyEscape},
+			entry! {action=TextMessage::CommitText, key_down=KeyEnter, modifiers=[KeyPrimaryModifier]},
 			// Rectangle
 			entry! {action=RectangleMessage::DragStart, key_down=Lmb},
 			entry! {action=RectangleMessage::DragStop, key_up=Lmb},
+			entry! {action=RectangleMessage::AutoPan, message=InputMapperMessage::FrameTick},
 			entry! {action=RectangleMessage::Abort, key_down=Rmb},
 			entry! {action=RectangleMessage::Abort, key_down=KeyEscape},
-			entry! {action=RectangleMessage::Resize { center: KeyAlt, lock_ratio: KeyShift }, triggers=[KeyAlt, KeyShift]},
+			entry! {action=RectangleMessage::Resize { center, lock_ratio: constrain, rotate: KeyR, snap_angle: KeyPrimaryModifier }, triggers=[center, constrain, KeyPrimaryModifier, KeyR]},
 			// Ellipse
 			entry! {action=EllipseMessage::DragStart, key_down=Lmb},
 			entry! {action=EllipseMessage::DragStop, key_up=Lmb},
+			entry! {action=EllipseMessage::AutoPan, message=InputMapperMessage::FrameTick},
 			entry! {action=EllipseMessage::Abort, key_down=Rmb},
 			entry! {action=EllipseMessage::Abort, key_down=KeyEscape},
-			entry! {action=EllipseMessage::Resize { center: KeyAlt, lock_ratio: KeyShift }, triggers=[KeyAlt, KeyShift]},
+			entry! {action=EllipseMessage::Resize { adjust_end_angle: KeyPrimaryModifier, center, lock_ratio: constrain, rotate: KeyR, snap_angle: KeyPrimaryModifier }, triggers=[center, constrain, KeyPrimaryModifier, KeyR]},
 			// Shape
 			entry! {action=ShapeMessage::DragStart, key_down=Lmb},
 			entry! {action=ShapeMessage::DragStop, key_up=Lmb},
+			entry! {action=ShapeMessage::AutoPan, message=InputMapperMessage::FrameTick},
 			entry! {action=ShapeMessage::Abort, key_down=Rmb},
 			entry! {action=ShapeMessage::Abort, key_down=KeyEscape},
-			entry! {action=ShapeMessage::Resize { center: KeyAlt, lock_ratio: KeyShift }, triggers=[KeyAlt, KeyShift]},
+			entry! {action=ShapeMessage::Resize { center, lock_ratio: constrain, rotate: KeyR, snap_angle: KeyPrimaryModifier }, triggers=[center, constrain, KeyPrimaryModifier, KeyR]},
 			// Line
 			entry! {action=LineMessage::DragStart, key_down=Lmb},
 			entry! {action=LineMessage::DragStop, key_up=Lmb},
+			entry! {action=LineMessage::AutoPan, message=InputMapperMessage::FrameTick},
+			entry! {action=LineMessage::Confirm, key_down=KeyEnter},
+			entry! {action=LineMessage::Confirm, key_down=KeyEscape},
+			entry! {action=LineMessage::Confirm, message=InputMapperMessage::DoubleClick},
 			entry! {action=LineMessage::Abort, key_down=Rmb},
 			entry! {action=LineMessage::Abort, key_down=KeyEscape},
-			entry! {action=LineMessage::Redraw { center: KeyAlt, lock_angle: KeyControl, snap_angle: KeyShift }, triggers=[KeyAlt, KeyShift, KeyControl]},
+			entry! {action=LineMessage::Redraw { center, lock_angle: KeyPrimaryModifier, snap_angle: constrain }, triggers=[center, constrain, KeyPrimaryModifier]},
 			// Path
-			entry! {action=PathMessage::DragStart { add_to_selection: KeyShift }, key_down=Lmb},
-			entry! {action=PathMessage::PointerMove { alt_mirror_angle: KeyAlt, shift_mirror_distance: KeyShift }, message=InputMapperMessage::PointerMove},
+			entry! {action=PathMessage::DragStart { add_to_selection: constrain }, key_down=Lmb},
+			entry! {action=PathMessage::PointerMove { alt_mirror_angle: center, shift_mirror_distance: constrain }, message=InputMapperMessage::PointerMove},
 			entry! {action=PathMessage::DragStop, key_up=Lmb},
 			// Pen
-			entry! {action=PenMessage::PointerMove, message=InputMapperMessage::PointerMove},
+			entry! {action=PenMessage::PointerMove { break_handle: center }, message=InputMapperMessage::PointerMove},
 			entry! {action=PenMessage::DragStart, key_down=Lmb},
 			entry! {action=PenMessage::DragStop, key_up=Lmb},
+			entry! {action=PenMessage::RemoveLastPoint, key_down=KeyBackspace},
 			entry! {action=PenMessage::Confirm, key_down=Rmb},
 			entry! {action=PenMessage::Confirm, key_down=KeyEscape},
 			entry! {action=PenMessage::Confirm, key_down=KeyEnter},
@@ -113,12 +195,18 @@ impl Default for Mapping {
 			entry! {action=SplineMessage::PointerMove, message=InputMapperMessage::PointerMove},
 			entry! {action=SplineMessage::DragStart, key_down=Lmb},
 			entry! {action=SplineMessage::DragStop, key_up=Lmb},
+			entry! {action=SplineMessage::Close, message=InputMapperMessage::DoubleClick},
+			entry! {action=SplineMessage::RemoveLastPoint, key_down=KeyBackspace},
 			entry! {action=SplineMessage::Confirm, key_down=Rmb},
 			entry! {action=SplineMessage::Confirm, key_down=KeyEscape},
 			entry! {action=SplineMessage::Confirm, key_down=KeyEnter},
 			// Fill
 			entry! {action=FillMessage::LeftMouseDown, key_down=Lmb},
 			entry! {action=FillMessage::RightMouseDown, key_down=Rmb},
+			entry! {action=FillMessage::PointerMove, message=InputMapperMessage::PointerMove},
+			entry! {action=FillMessage::DragStop, key_up=Lmb},
+			entry! {action=FillMessage::DragStop, key_up=Rmb},
+			entry! {action=FillMessage::Abort, key_down=KeyEscape},
 			// Tool Actions
 			entry! {action=ToolMessage::ActivateTool { tool_type: ToolType::Select }, key_down=KeyV},
 			entry! {action=ToolMessage::ActivateTool { tool_type: ToolType::Navigate }, key_down=KeyZ},
@@ -133,42 +221,50 @@ impl Default for Mapping {
 			entry! {action=ToolMessage::ActivateTool { tool_type: ToolType::Ellipse }, key_down=KeyE},
 			entry! {action=ToolMessage::ActivateTool { tool_type: ToolType::Shape }, key_down=KeyY},
 			// Colors
-			entry! {action=ToolMessage::ResetColors, key_down=KeyX, modifiers=[KeyShift, KeyControl]},
+			entry! {action=ToolMessage::ResetColors, key_down=KeyX, modifiers=[KeyShift, KeyPrimaryModifier]},
 			entry! {action=ToolMessage::SwapColors, key_down=KeyX, modifiers=[KeyShift]},
 			// Editor Actions
-			entry! {action=FrontendMessage::TriggerFileUpload, key_down=KeyO, modifiers=[KeyControl]},
+			entry! {action=FrontendMessage::TriggerFileUpload, key_down=KeyO, modifiers=[KeyPrimaryModifier]},
 			// Document Actions
-			entry! {action=PortfolioMessage::Paste { clipboard: Clipboard::User }, key_down=KeyV, modifiers=[KeyControl]},
-			entry! {action=DocumentMessage::Redo, key_down=KeyZ, modifiers=[KeyControl, KeyShift]},
-			entry! {action=DocumentMessage::Undo, key_down=KeyZ, modifiers=[KeyControl]},
-			entry! {action=DocumentMessage::DeselectAllLayers, key_down=KeyA, modifiers=[KeyControl, KeyAlt]},
-			entry! {action=DocumentMessage::SelectAllLayers, key_down=KeyA, modifiers=[KeyControl]},
-			entry! {action=DocumentMessage::CreateEmptyFolder { container_path: vec![] }, key_down=KeyN, modifiers=[KeyControl, KeyShift]},
+			entry! {action=PortfolioMessage::PasteReplace { clipboard: Clipboard::User }, key_down=KeyV, modifiers=[KeyPrimaryModifier, KeyShift]},
+			entry! {action=PortfolioMessage::Paste { clipboard: Clipboard::User }, key_down=KeyV, modifiers=[KeyPrimaryModifier]},
+			entry! {action=DocumentMessage::Redo, key_down=KeyZ, modifiers=[KeyPrimaryModifier, KeyShift]},
+			entry! {action=DocumentMessage::Undo, key_down=KeyZ, modifiers=[KeyPrimaryModifier]},
+			entry! {action=DocumentMessage::DeselectAllLayers, key_down=KeyA, modifiers=[KeyPrimaryModifier, KeyAlt]},
+			// Below the tools' Escape entries so it doesn't keep them from aborting an interaction in progress
+			entry! {action=DocumentMessage::ClearSnapReferencePoints, key_down=KeyEscape},
+			entry! {action=DocumentMessage::DeselectAllLayers, key_down=KeyEscape},
+			entry! {action=DocumentMessage::SelectAllLayers, key_down=KeyA, modifiers=[KeyPrimaryModifier]},
+			entry! {action=DocumentMessage::CreateEmptyFolder { container_path: vec![] }, key_down=KeyN, modifiers=[KeyPrimaryModifier, KeyShift]},
 			entry! {action=DocumentMessage::DeleteSelectedLayers, key_down=KeyDelete},
 			entry! {action=DocumentMessage::DeleteSelectedLayers, key_down=KeyX},
 			entry! {action=DocumentMessage::DeleteSelectedLayers, key_down=KeyBackspace},
-			entry! {action=DocumentMessage::ExportDocument, key_down=KeyE, modifiers=[KeyControl]},
-			entry! {action=DocumentMessage::SaveDocument, key_down=KeyS, modifiers=[KeyControl]},
-			entry! {action=DocumentMessage::SaveDocument, key_down=KeyS, modifiers=[KeyControl, KeyShift]},
-			entry! {action=DocumentMessage::DebugPrintDocument, key_down=Key9},
-			entry! {action=DocumentMessage::ZoomCanvasToFitAll, key_down=Key0, modifiers=[KeyControl]},
+			entry! {action=DocumentMessage::ExportDocument { scope: ExportScope::Document }, key_down=KeyE, modifiers=[KeyPrimaryModifier]},
+			entry! {action=DocumentMessage::SaveDocument, key_down=KeyS, modifiers=[KeyPrimaryModifier]},
+			entry! {action=DocumentMessage::SaveDocument, key_down=KeyS, modifiers=[KeyPrimaryModifier, KeyShift]},
+			// Held with Alt, like the log levels below, since the digits on their own set the opacity of the selected layers
+			entry! {action=DocumentMessage::DebugPrintDocument, key_down=Key9, modifiers=[KeyAlt]},
+			entry! {action=DocumentMessage::ZoomCanvasToFitAll, key_down=Key0, modifiers=[KeyPrimaryModifier]},
 			// Initiate Transform Layers
 			entry! {action=TransformLayerMessage::BeginGrab, key_down=KeyG},
-			entry! {action=TransformLayerMessage::BeginRotate, key_down=KeyR},
-			entry! {action=TransformLayerMessage::BeginScale, key_down=KeyS},
+			entry! {action=TransformLayerMessage::BeginRotate { transform_each: true }, key_down=KeyR, modifiers=[KeyAlt]},
+			entry! {action=TransformLayerMessage::BeginRotate { transform_each: false }, key_down=KeyR},
+			entry! {action=TransformLayerMessage::BeginScale { transform_each: true }, key_down=KeyS, modifiers=[KeyAlt]},
+			entry! {action=TransformLayerMessage::BeginScale { transform_each: false }, key_down=KeyS},
 			// Document movement
-			entry! {action=MovementMessage::RotateCanvasBegin, key_down=Mmb, modifiers=[KeyControl]},
+			entry! {action=MovementMessage::StraightenCanvasToSelection, key_down=KeyR, modifiers=[KeyShift]},
+			entry! {action=MovementMessage::RotateCanvasBegin, key_down=Mmb, modifiers=[KeyPrimaryModifier]},
 			entry! {action=MovementMessage::ZoomCanvasBegin, key_down=Mmb, modifiers=[KeyShift]},
 			entry! {action=MovementMessage::TranslateCanvasBegin, key_down=Mmb},
 			entry! {action=MovementMessage::TransformCanvasEnd, key_up=Mmb},
 			entry! {action=MovementMessage::TranslateCanvasBegin, key_down=Lmb, modifiers=[KeySpace]},
 			entry! {action=MovementMessage::TransformCanvasEnd, key_up=Lmb, modifiers=[KeySpace]},
-			entry! {action=MovementMessage::IncreaseCanvasZoom { center_on_mouse: false }, key_down=KeyPlus, modifiers=[KeyControl]},
-			entry! {action=MovementMessage::IncreaseCanvasZoom { center_on_mouse: false }, key_down=KeyEquals, modifiers=[KeyControl]},
-			entry! {action=MovementMessage::DecreaseCanvasZoom { center_on_mouse: false }, key_down=KeyMinus, modifiers=[KeyControl]},
-			entry! {action=MovementMessage::SetCanvasZoom { zoom_factor: 1. }, key_down=Key1, modifiers=[KeyControl]},
-			entry! {action=MovementMessage::SetCanvasZoom { zoom_factor: 2. }, key_down=Key2, modifiers=[KeyControl]},
-			entry! {action=MovementMessage::WheelCanvasZoom, message=InputMapperMessage::MouseScroll, modifiers=[KeyControl]},
+			entry! {action=MovementMessage::IncreaseCanvasZoom { center_on_mouse: false }, key_down=KeyPlus, modifiers=[KeyPrimaryModifier]},
+			entry! {action=MovementMessage::IncreaseCanvasZoom { center_on_mouse: false }, key_down=KeyEquals, modifiers=[KeyPrimaryModifier]},
+			entry! {action=MovementMessage::DecreaseCanvasZoom { center_on_mouse: false }, key_down=KeyMinus, modifiers=[KeyPrimaryModifier]},
+			entry! {action=MovementMessage::SetCanvasZoom { zoom_factor: 1. }, key_down=Key1, modifiers=[KeyPrimaryModifier]},
+			entry! {action=MovementMessage::SetCanvasZoom { zoom_factor: 2. }, key_down=Key2, modifiers=[KeyPrimaryModifier]},
+			entry! {action=MovementMessage::WheelCanvasZoom, message=InputMapperMessage::MouseScroll, modifiers=[KeyPrimaryModifier]},
 			entry! {action=MovementMessage::WheelCanvasTranslate { use_y_as_x: true }, message=InputMapperMessage::MouseScroll, modifiers=[KeyShift]},
 			entry! {action=MovementMessage::WheelCanvasTranslate { use_y_as_x: false }, message=InputMapperMessage::MouseScroll},
 			entry! {action=MovementMessage::TranslateCanvasByViewportFraction { delta: DVec2::new(1., 0.) }, key_down=KeyPageUp, modifiers=[KeyShift]},
@@ -176,16 +272,16 @@ impl Default for Mapping {
 			entry! {action=MovementMessage::TranslateCanvasByViewportFraction { delta: DVec2::new(0., 1.) }, key_down=KeyPageUp},
 			entry! {action=MovementMessage::TranslateCanvasByViewportFraction { delta: DVec2::new(0., -1.) }, key_down=KeyPageDown},
 			// Document actions
-			entry! {action=PortfolioMessage::NewDocument, key_down=KeyN, modifiers=[KeyControl]},
-			entry! {action=PortfolioMessage::NextDocument, key_down=KeyTab, modifiers=[KeyControl]},
-			entry! {action=PortfolioMessage::PrevDocument, key_down=KeyTab, modifiers=[KeyControl, KeyShift]},
-			entry! {action=PortfolioMessage::CloseAllDocumentsWithConfirmation, key_down=KeyW, modifiers=[KeyControl, KeyAlt]},
-			entry! {action=PortfolioMessage::CloseActiveDocumentWithConfirmation, key_down=KeyW, modifiers=[KeyControl]},
-			entry! {action=DocumentMessage::DuplicateSelectedLayers, key_down=KeyD, modifiers=[KeyControl]},
-			entry! {action=PortfolioMessage::Copy { clipboard: Clipboard::User }, key_down=KeyC, modifiers=[KeyControl]},
-			entry! {action=PortfolioMessage::Cut { clipboard: Clipboard::User }, key_down=KeyX, modifiers=[KeyControl]},
-			entry! {action=DocumentMessage::GroupSelectedLayers, key_down=KeyG, modifiers=[KeyControl]},
-			entry! {action=DocumentMessage::UngroupSelectedLayers, key_down=KeyG, modifiers=[KeyControl, KeyShift]},
+			entry! {action=PortfolioMessage::NewDocument { artboard: None }, key_down=KeyN, modifiers=[KeyPrimaryModifier]},
+			entry! {action=PortfolioMessage::NextDocument, key_down=KeyTab, modifiers=[KeyPrimaryModifier]},
+			entry! {action=PortfolioMessage::PrevDocument, key_down=KeyTab, modifiers=[KeyPrimaryModifier, KeyShift]},
+			entry! {action=PortfolioMessage::CloseAllDocumentsWithConfirmation, key_down=KeyW, modifiers=[KeyPrimaryModifier, KeyAlt]},
+			entry! {action=PortfolioMessage::CloseActiveDocumentWithConfirmation, key_down=KeyW, modifiers=[KeyPrimaryModifier]},
+			entry! {action=DocumentMessage::DuplicateSelectedLayers, key_down=KeyD, modifiers=[KeyPrimaryModifier]},
+			entry! {action=PortfolioMessage::Copy { clipboard: Clipboard::User }, key_down=KeyC, modifiers=[KeyPrimaryModifier]},
+			entry! {action=PortfolioMessage::Cut { clipboard: Clipboard::User }, key_down=KeyX, modifiers=[KeyPrimaryModifier]},
+			entry! {action=DocumentMessage::GroupSelectedLayers, key_down=KeyG, modifiers=[KeyPrimaryModifier]},
+			entry! {action=DocumentMessage::UngroupSelectedLayers, key_down=KeyG, modifiers=[KeyPrimaryModifier, KeyShift]},
 			// Nudging
 			entry! {action=DocumentMessage::NudgeSelectedLayers { delta_x: -SHIFT_NUDGE_AMOUNT, delta_y: -SHIFT_NUDGE_AMOUNT }, key_down=KeyArrowUp, modifiers=[KeyShift, KeyArrowLeft]},
 			entry! {action=DocumentMessage::NudgeSelectedLayers { delta_x: SHIFT_NUDGE_AMOUNT, delta_y: -SHIFT_NUDGE_AMOUNT }, key_down=KeyArrowUp, modifiers=[KeyShift, KeyArrowRight]},
@@ -212,21 +308,38 @@ impl Default for Mapping {
 			entry! {action=DocumentMessage::NudgeSelectedLayers { delta_x: NUDGE_AMOUNT, delta_y: NUDGE_AMOUNT }, key_down=KeyArrowRight, modifiers=[KeyArrowDown]},
 			entry! {action=DocumentMessage::NudgeSelectedLayers { delta_x: NUDGE_AMOUNT, delta_y: 0. }, key_down=KeyArrowRight},
 			// Reorder Layers
-			entry! {action=DocumentMessage::ReorderSelectedLayers { relative_index_offset: isize::MAX }, key_down=KeyRightCurlyBracket, modifiers=[KeyControl]}, // TODO: Use KeyRightBracket with ctrl+shift modifiers once input system is fixed
-			entry! {action=DocumentMessage::ReorderSelectedLayers { relative_index_offset: 1 }, key_down=KeyRightBracket, modifiers=[KeyControl]},
-			entry! {action=DocumentMessage::ReorderSelectedLayers { relative_index_offset: -1 }, key_down=KeyLeftBracket, modifiers=[KeyControl]},
-			entry! {action=DocumentMessage::ReorderSelectedLayers { relative_index_offset: isize::MIN }, key_down=KeyLeftCurlyBracket, modifiers=[KeyControl]}, // TODO: Use KeyLeftBracket with ctrl+shift modifiers once input system is fixed
+			entry! {action=DocumentMessage::ReorderSelectedLayers { relative_index_offset: isize::MAX }, key_down=KeyRightCurlyBracket, modifiers=[KeyPrimaryModifier]}, // TODO: Use KeyRightBracket with ctrl+shift modifiers once input system is fixed
+			entry! {action=DocumentMessage::ReorderSelectedLayers { relative_index_offset: 1 }, key_down=KeyRightBracket, modifiers=[KeyPrimaryModifier]},
+			entry! {action=DocumentMessage::ReorderSelectedLayers { relative_index_offset: -1 }, key_down=KeyLeftBracket, modifiers=[KeyPrimaryModifier]},
+			entry! {action=DocumentMessage::ReorderSelectedLayers { relative_index_offset: isize::MIN }, key_down=KeyLeftCurlyBracket, modifiers=[KeyPrimaryModifier]}, // TODO: Use KeyLeftBracket with ctrl+shift modifiers once input system is fixed
 			// Global Actions
-			entry! {action=GlobalMessage::LogInfo, key_down=Key1},
-			entry! {action=GlobalMessage::LogDebug, key_down=Key2},
-			entry! {action=GlobalMessage::LogTrace, key_down=Key3},
+			entry! {action=GlobalMessage::LogInfo, key_down=Key1, modifiers=[KeyAlt]},
+			entry! {action=GlobalMessage::LogDebug, key_down=Key2, modifiers=[KeyAlt]},
+			entry! {action=GlobalMessage::LogTrace, key_down=Key3, modifiers=[KeyAlt]},
+		];
+		let (mut key_up, mut key_down, mut pointer_move, mut mouse_scroll, mut double_click, frame_tick) = mappings;
+
+		// Used in place of the mouse wheel entries above when the plain wheel is set to zoom, with the primary modifier scrolling instead
+		let (_, _, _, mut mouse_scroll_zoom, _, _) = mapping![
+			entry! {action=MovementMessage::WheelCanvasTranslate { use_y_as_x: false }, message=InputMapperMessage::MouseScroll, modifiers=[KeyPrimaryModifier]},
+			entry! {action=MovementMessage::WheelCanvasTranslate { use_y_as_x: true }, message=InputMapperMessage::MouseScroll, modifiers=[KeyShift]},
+			entry! {action=MovementMessage::WheelCanvasZoom, message=InputMapperMessage::MouseScroll},
 		];
-		let (mut key_up, mut key_down, mut pointer_move, mut mouse_scroll, mut double_click) = mappings;
 
 		// TODO: Hardcode these 10 lines into 10 lines of declarations, or make this use a macro to do all 10 in one line
 		const NUMBER_KEYS: [Key; 10] = [Key0, Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9];
+		// Typing a number into a transformation in progress comes first, then setting the opacity of the selected layers
 		for (i, key) in NUMBER_KEYS.iter().enumerate() {
-			key_down[*key as usize].0.insert(
+			let entries = &mut key_down[*key as usize].0;
+			entries.insert(
+				0,
+				MappingEntry {
+					action: DocumentMessage::TypeOpacityDigit { digit: i as u8 }.into(),
+					trigger: InputMapperMessage::KeyDown(*key),
+					modifiers: modifiers! {},
+				},
+			);
+			entries.insert(
 				0,
 				MappingEntry {
 					action: TransformLayerMessage::TypeDigit { digit: i as u8 }.into(),
@@ -244,19 +357,21 @@ impl Default for Mapping {
 		}
 		sort(&mut pointer_move);
 		sort(&mut mouse_scroll);
+		sort(&mut mouse_scroll_zoom);
 		sort(&mut double_click);
 
 		Self {
+			bindings,
 			key_up,
 			key_down,
 			pointer_move,
 			mouse_scroll,
+			mouse_scroll_zoom,
 			double_click,
+			frame_tick,
 		}
 	}
-}
 
-impl Mapping {
 	pub fn match_message(&self, message: InputMapperMessage, keys: &KeyStates, actions: ActionList) -> Option<Message> {
 		use InputMapperMessage::*;
 
@@ -264,11 +379,26 @@ impl Mapping {
 			KeyDown(key) => &self.key_down[key as usize],
 			KeyUp(key) => &self.key_up[key as usize],
 			DoubleClick => &self.double_click,
+			FrameTick => return self.match_frame_tick(keys, actions).into_iter().next(),
 			MouseScroll => &self.mouse_scroll,
 			PointerMove => &self.pointer_move,
 		};
+		list.match_mapping_with_fallback(keys, actions)
+	}
+
+	/// Mouse wheel events are matched against the entries for the chosen wheel behavior.
+	pub fn match_mouse_scroll(&self, keys: &KeyStates, wheel_behavior: WheelBehavior, actions: ActionList) -> Option<Message> {
+		let list = match wheel_behavior {
+			WheelBehavior::Scroll => &self.mouse_scroll,
+			WheelBehavior::Zoom => &self.mouse_scroll_zoom,
+		};
 		list.match_mapping(keys, actions)
 	}
+
+	/// Unlike the other input events, a frame tick is delivered to every handler that currently advertises a frame tick action.
+	pub fn match_frame_tick(&self, keys: &KeyStates, actions: ActionList) -> Vec<Message> {
+		self.frame_tick.0.iter().filter(|entry| entry.matches(keys, &actions)).map(|entry| entry.action.clone()).collect()
+	}
 }
 
 #[derive(PartialEq, Clone, Debug)]
@@ -278,18 +408,40 @@ pub struct MappingEntry {
 	pub action: Message,
 }
 
+impl MappingEntry {
+	fn matches(&self, keys: &KeyStates, actions: &ActionList) -> bool {
+		let all_required_modifiers_pressed = ((*keys & self.modifiers) ^ self.modifiers).is_empty();
+		all_required_modifiers_pressed && actions.iter().flatten().any(|action| self.action.to_discriminant() == *action)
+	}
+}
+
 #[derive(Debug, Clone)]
 pub struct KeyMappingEntries(pub Vec<MappingEntry>);
 
 impl KeyMappingEntries {
 	fn match_mapping(&self, keys: &KeyStates, actions: ActionList) -> Option<Message> {
-		for entry in self.0.iter() {
-			let all_required_modifiers_pressed = ((*keys & entry.modifiers) ^ entry.modifiers).is_empty();
-			if all_required_modifiers_pressed && actions.iter().flatten().any(|action| entry.action.to_discriminant() == *action) {
-				return Some(entry.action.clone());
+		self.0.iter().find(|entry| entry.matches(keys, &actions)).map(|entry| entry.action.clone())
+	}
+
+	/// Like `match_mapping`, but when a tool wins the input over a lower-precedence canvas navigation entry that also matches,
+	/// the navigation is sent along as a fallback for the tool message handler to use if the tool doesn't consume the input.
+	fn match_mapping_with_fallback(&self, keys: &KeyStates, actions: ActionList) -> Option<Message> {
+		let mut matching = self.0.iter().filter(|entry| entry.matches(keys, &actions)).map(|entry| entry.action.clone());
+
+		match matching.next()? {
+			Message::Tool(message) => {
+				let fallback = matching.find_map(|action| match action {
+					Message::Portfolio(PortfolioMessage::Document(DocumentMessage::Movement(movement))) => Some(movement),
+					_ => None,
+				});
+
+				match fallback {
+					Some(fallback) => Some(ToolMessage::HandleInput { message: Box::new(message), fallback }.into()),
+					None => Some(message.into()),
+				}
 			}
+			action => Some(action),
 		}
-		None
 	}
 
 	fn push(&mut self, entry: MappingEntry) {
@@ -314,32 +466,32 @@ impl Default for KeyMappingEntries {
 
 mod input_mapper_macros {
 	macro_rules! modifiers {
-		($($m:ident),*) => {{
+		($($m:expr),*) => {{
 			#[allow(unused_mut)]
 			let mut state = KeyStates::new();
 			$(
-				state.set(Key::$m as usize);
+				state.set($m as usize);
 			)*
 			state
 		}};
 	}
 
 	macro_rules! entry {
-		{action=$action:expr, key_down=$key:ident $(, modifiers=[$($m:ident),* $(,)?])?} => {{
+		{action=$action:expr, key_down=$key:ident $(, modifiers=[$($m:expr),* $(,)?])?} => {{
 			entry!{action=$action, message=InputMapperMessage::KeyDown(Key::$key) $(, modifiers=[$($m),*])?}
 		}};
-		{action=$action:expr, key_up=$key:ident $(, modifiers=[$($m:ident),* $(,)?])?} => {{
+		{action=$action:expr, key_up=$key:ident $(, modifiers=[$($m:expr),* $(,)?])?} => {{
 			entry!{action=$action, message=InputMapperMessage::KeyUp(Key::$key) $(, modifiers=[$($m),* ])?}
 		}};
-		{action=$action:expr, message=$message:expr $(, modifiers=[$($m:ident),* $(,)?])?} => {{
+		{action=$action:expr, message=$message:expr $(, modifiers=[$($m:expr),* $(,)?])?} => {{
 			&[MappingEntry {trigger: $message, modifiers: modifiers!($($($m),*)?), action: $action.into()}]
 		}};
-		{action=$action:expr, triggers=[$($m:ident),* $(,)?]} => {{
+		{action=$action:expr, triggers=[$($m:expr),* $(,)?]} => {{
 			&[
 				MappingEntry {trigger:InputMapperMessage::PointerMove, action: $action.into(), modifiers: modifiers!()},
 				$(
-				MappingEntry {trigger:InputMapperMessage::KeyDown(Key::$m), action: $action.into(), modifiers: modifiers!()},
-				MappingEntry {trigger:InputMapperMessage::KeyUp(Key::$m), action: $action.into(), modifiers: modifiers!()},
+				MappingEntry {trigger:InputMapperMessage::KeyDown($m), action: $action.into(), modifiers: modifiers!()},
+				MappingEntry {trigger:InputMapperMessage::KeyUp($m), action: $action.into(), modifiers: modifiers!()},
 				)*
 			]
 		}};
@@ -353,6 +505,7 @@ mod input_mapper_macros {
 			let mut pointer_move: KeyMappingEntries = Default::default();
 			let mut mouse_scroll: KeyMappingEntries = Default::default();
 			let mut double_click: KeyMappingEntries = Default::default();
+			let mut frame_tick: KeyMappingEntries = Default::default();
 			$(
 				for entry in $entry {
 					let arr = match entry.trigger {
@@ -361,11 +514,12 @@ mod input_mapper_macros {
 						InputMapperMessage::MouseScroll => &mut mouse_scroll,
 						InputMapperMessage::PointerMove => &mut pointer_move,
 						InputMapperMessage::DoubleClick => &mut double_click,
+						InputMapperMessage::FrameTick => &mut frame_tick,
 					};
 					arr.push(entry.clone());
 				}
 			)*
-			(key_up, key_down, pointer_move, mouse_scroll, double_click)
+			(key_up, key_down, pointer_move, mouse_scroll, double_click, frame_tick)
 		}};
 	}
 
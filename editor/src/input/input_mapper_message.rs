@@ -17,6 +17,7 @@ pub enum InputMapperMessage {
 
 	// Messages
 	DoubleClick,
+	FrameTick,
 	MouseScroll,
 	PointerMove,
 }
@@ -1,5 +1,5 @@
 use super::input_mapper::Mapping;
-use super::keyboard::Key;
+use super::keyboard::{Key, KeyStates};
 use super::InputPreprocessorMessageHandler;
 use crate::message_prelude::*;
 
@@ -24,19 +24,34 @@ impl InputMapperMessageHandler {
 			.filter_map(|(i, m)| {
 				let ma = m.0.iter().find_map(|m| actions.find_map(|a| (a == m.action.to_discriminant()).then(|| m.action.to_discriminant())));
 
-				ma.map(|a| unsafe { (std::mem::transmute_copy::<usize, Key>(&i), a) })
+				ma.map(|a| (Key::from_index(i).expect("Key mappings should have one entry per key"), a))
 			})
 			.for_each(|(k, a)| {
 				let _ = write!(output, "{}: {}, ", k.to_discriminant().local_name(), a.local_name().split('.').last().unwrap());
 			});
 		output.replace("Key", "")
 	}
+
+	/// Whether any handler currently advertises an action that is mapped to the frame tick, meaning that it is animating.
+	pub fn frame_ticks_requested(&self, actions: ActionList) -> bool {
+		!self.mapping.match_frame_tick(&KeyStates::new(), actions).is_empty()
+	}
 }
 
 impl MessageHandler<InputMapperMessage, (&InputPreprocessorMessageHandler, ActionList)> for InputMapperMessageHandler {
 	fn process_action(&mut self, message: InputMapperMessage, data: (&InputPreprocessorMessageHandler, ActionList), responses: &mut VecDeque<Message>) {
 		let (input, actions) = data;
-		if let Some(message) = self.mapping.match_message(message, &input.keyboard, actions) {
+
+		// Rebuild the mapping around the tool modifiers once they've been reassigned
+		if self.mapping.bindings != input.modifier_bindings {
+			self.mapping = Mapping::new(input.modifier_bindings);
+		}
+
+		if message == InputMapperMessage::FrameTick {
+			responses.extend(self.mapping.match_frame_tick(&input.keyboard, actions));
+		} else if message == InputMapperMessage::MouseScroll {
+			responses.extend(self.mapping.match_mouse_scroll(&input.keyboard, input.wheel_behavior, actions));
+		} else if let Some(message) = self.mapping.match_message(message, &input.keyboard, actions) {
 			responses.push_back(message);
 		}
 	}
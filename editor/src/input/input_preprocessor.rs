@@ -1,6 +1,8 @@
 #[doc(inline)]
 pub use graphene::DocumentResponse;
 
+use super::keyboard::Key;
+
 use bitflags::bitflags;
 use serde::{Deserialize, Serialize};
 
@@ -16,16 +18,58 @@ bitflags! {
 		const CONTROL = 0b0000_0001;
 		const SHIFT   = 0b0000_0010;
 		const ALT     = 0b0000_0100;
+		const COMMAND = 0b0000_1000;
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Platform {
+	Unknown,
+	Windows,
+	Mac,
+	Linux,
+}
+
+impl Platform {
+	/// The physical key that acts as the primary modifier for shortcuts on this platform.
+	pub fn primary_modifier(&self) -> Key {
+		match self {
+			Platform::Mac => Key::KeyCommand,
+			_ => Key::KeyControl,
+		}
+	}
+}
+
+impl Default for Platform {
+	fn default() -> Self {
+		Platform::Unknown
+	}
+}
+
+/// What the mouse wheel does to the canvas when no modifier key is held. The primary modifier key switches it to the other behavior.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WheelBehavior {
+	/// Pans the canvas, as in document apps
+	Scroll,
+	/// Zooms the canvas around the cursor, as in design apps
+	Zoom,
+}
+
+impl Default for WheelBehavior {
+	fn default() -> Self {
+		WheelBehavior::Scroll
 	}
 }
 
 #[cfg(test)]
 mod test {
-	use crate::input::input_preprocessor::ModifierKeys;
-	use crate::input::keyboard::Key;
+	use crate::document::clipboards::Clipboard;
+	use crate::input::input_mapper::Mapping;
+	use crate::input::input_preprocessor::{ModifierKeys, Platform, WheelBehavior};
+	use crate::input::keyboard::{Key, KeyStates, NUMBER_OF_KEYS};
 	use crate::input::mouse::EditorMouseState;
 	use crate::input::{InputMapperMessage, InputPreprocessorMessage, InputPreprocessorMessageHandler};
-	use crate::message_prelude::MessageHandler;
+	use crate::message_prelude::*;
 
 	use std::collections::VecDeque;
 
@@ -111,4 +155,114 @@ mod test {
 		assert!(responses.contains(&InputMapperMessage::KeyDown(Key::KeyControl).into()));
 		assert!(responses.contains(&InputMapperMessage::KeyDown(Key::KeyControl).into()));
 	}
+
+	#[test]
+	fn pressed_keys_follow_presses_and_releases() {
+		let mut input_preprocessor = InputPreprocessorMessageHandler::default();
+		let mut responses = VecDeque::new();
+
+		let message = InputPreprocessorMessage::KeyDown {
+			key: Key::KeyA,
+			modifier_keys: ModifierKeys::SHIFT,
+		};
+		input_preprocessor.process_action(message, (), &mut responses);
+		assert!(input_preprocessor.is_pressed(Key::KeyA));
+		assert!(input_preprocessor.is_pressed(Key::KeyShift));
+		assert!(!input_preprocessor.is_pressed(Key::KeyB));
+		assert_eq!(input_preprocessor.pressed_keys().collect::<Vec<_>>(), vec![Key::KeyA, Key::KeyShift]);
+
+		let message = InputPreprocessorMessage::KeyUp {
+			key: Key::KeyA,
+			modifier_keys: ModifierKeys::empty(),
+		};
+		input_preprocessor.process_action(message, (), &mut responses);
+		assert!(!input_preprocessor.is_pressed(Key::KeyA));
+		assert!(!input_preprocessor.is_pressed(Key::KeyShift));
+		assert_eq!(input_preprocessor.pressed_keys().next(), None);
+
+		assert_eq!(Key::from_index(Key::KeyPrimaryModifier as usize), Some(Key::KeyPrimaryModifier));
+		assert_eq!(Key::from_index(NUMBER_OF_KEYS), None);
+	}
+
+	#[test]
+	fn primary_modifier_follows_platform() {
+		let mut input_preprocessor = InputPreprocessorMessageHandler::default();
+		let mut responses = VecDeque::new();
+
+		input_preprocessor.process_action(InputPreprocessorMessage::SetPlatform { platform: Platform::Mac }, (), &mut responses);
+
+		let editor_mouse_state = EditorMouseState::new();
+		let message = InputPreprocessorMessage::PointerMove {
+			editor_mouse_state,
+			modifier_keys: ModifierKeys::CONTROL,
+		};
+		input_preprocessor.process_action(message, (), &mut responses);
+		assert!(!input_preprocessor.keyboard.get(Key::KeyPrimaryModifier as usize));
+
+		let editor_mouse_state = EditorMouseState::new();
+		let message = InputPreprocessorMessage::PointerMove {
+			editor_mouse_state,
+			modifier_keys: ModifierKeys::COMMAND,
+		};
+		input_preprocessor.process_action(message, (), &mut responses);
+		assert!(input_preprocessor.keyboard.get(Key::KeyPrimaryModifier as usize));
+		assert!(responses.contains(&InputMapperMessage::KeyDown(Key::KeyPrimaryModifier).into()));
+	}
+
+	#[test]
+	fn paste_shortcut_resolves_per_platform() {
+		let mapping = Mapping::default();
+		let paste: Message = PortfolioMessage::Paste { clipboard: Clipboard::User }.into();
+		let actions = || vec![vec![paste.to_discriminant()]];
+
+		for (platform, primary_modifier_keys, other_modifier_keys) in [
+			(Platform::Windows, ModifierKeys::CONTROL, ModifierKeys::COMMAND),
+			(Platform::Mac, ModifierKeys::COMMAND, ModifierKeys::CONTROL),
+		] {
+			let mut input_preprocessor = InputPreprocessorMessageHandler::default();
+			let mut responses = VecDeque::new();
+			input_preprocessor.process_action(InputPreprocessorMessage::SetPlatform { platform }, (), &mut responses);
+
+			let message = InputPreprocessorMessage::KeyDown {
+				key: Key::KeyV,
+				modifier_keys: other_modifier_keys,
+			};
+			input_preprocessor.process_action(message, (), &mut responses);
+			assert_eq!(mapping.match_message(InputMapperMessage::KeyDown(Key::KeyV), &input_preprocessor.keyboard, actions()), None);
+
+			let message = InputPreprocessorMessage::KeyDown {
+				key: Key::KeyV,
+				modifier_keys: primary_modifier_keys,
+			};
+			input_preprocessor.process_action(message, (), &mut responses);
+			assert_eq!(
+				mapping.match_message(InputMapperMessage::KeyDown(Key::KeyV), &input_preprocessor.keyboard, actions()),
+				Some(paste.clone())
+			);
+		}
+	}
+
+	#[test]
+	fn wheel_behavior_swaps_scroll_and_zoom() {
+		let mapping = Mapping::default();
+		let zoom: Message = MovementMessage::WheelCanvasZoom.into();
+		let scroll: Message = MovementMessage::WheelCanvasTranslate { use_y_as_x: false }.into();
+		let horizontal_scroll: Message = MovementMessage::WheelCanvasTranslate { use_y_as_x: true }.into();
+		let actions = || vec![vec![zoom.to_discriminant(), scroll.to_discriminant()]];
+
+		let plain = KeyStates::new();
+		let mut primary_modifier = KeyStates::new();
+		primary_modifier.set(Key::KeyPrimaryModifier as usize);
+		let mut shift = KeyStates::new();
+		shift.set(Key::KeyShift as usize);
+
+		assert_eq!(mapping.match_mouse_scroll(&plain, WheelBehavior::Scroll, actions()), Some(scroll.clone()));
+		assert_eq!(mapping.match_mouse_scroll(&primary_modifier, WheelBehavior::Scroll, actions()), Some(zoom.clone()));
+		assert_eq!(mapping.match_mouse_scroll(&plain, WheelBehavior::Zoom, actions()), Some(zoom));
+		assert_eq!(mapping.match_mouse_scroll(&primary_modifier, WheelBehavior::Zoom, actions()), Some(scroll));
+
+		for wheel_behavior in [WheelBehavior::Scroll, WheelBehavior::Zoom] {
+			assert_eq!(mapping.match_mouse_scroll(&shift, wheel_behavior, actions()), Some(horizontal_scroll.clone()));
+		}
+	}
 }
@@ -1,4 +1,5 @@
-use super::input_preprocessor::ModifierKeys;
+use super::input_mapper::ModifierBindings;
+use super::input_preprocessor::{ModifierKeys, Platform, WheelBehavior};
 use super::keyboard::Key;
 use super::mouse::{EditorMouseState, ViewportBounds};
 use crate::message_prelude::*;
@@ -14,10 +15,17 @@ use serde::{Deserialize, Serialize};
 pub enum InputPreprocessorMessage {
 	BoundsOfViewports { bounds_of_viewports: Vec<ViewportBounds> },
 	DoubleClick { editor_mouse_state: EditorMouseState, modifier_keys: ModifierKeys },
+	FrameTick { delta_time: f64 },
 	KeyDown { key: Key, modifier_keys: ModifierKeys },
 	KeyUp { key: Key, modifier_keys: ModifierKeys },
 	MouseScroll { editor_mouse_state: EditorMouseState, modifier_keys: ModifierKeys },
 	PointerDown { editor_mouse_state: EditorMouseState, modifier_keys: ModifierKeys },
 	PointerMove { editor_mouse_state: EditorMouseState, modifier_keys: ModifierKeys },
 	PointerUp { editor_mouse_state: EditorMouseState, modifier_keys: ModifierKeys },
+	SetAxisSnapTolerance { tolerance: f64 },
+	SetModifierBindings { bindings: ModifierBindings },
+	SetPlatform { platform: Platform },
+	SetRotateSnapAngle { angle: f64 },
+	SetTime { timestamp: u64 },
+	SetWheelBehavior { wheel_behavior: WheelBehavior },
 }
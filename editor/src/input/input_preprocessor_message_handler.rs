@@ -1,16 +1,47 @@
-use super::input_preprocessor::{KeyPosition, ModifierKeys};
-use super::keyboard::{Key, KeyStates};
+use super::input_mapper::ModifierBindings;
+use super::input_preprocessor::{KeyPosition, ModifierKeys, Platform, WheelBehavior};
+use super::keyboard::{Key, KeyStates, NUMBER_OF_KEYS};
 use super::mouse::{MouseKeys, MouseState, ViewportBounds};
+use crate::consts::{DEFAULT_AXIS_SNAP_TOLERANCE, DEFAULT_ROTATE_SNAP_ANGLE};
 use crate::message_prelude::*;
 
 #[doc(inline)]
 pub use graphene::DocumentResponse;
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct InputPreprocessorMessageHandler {
 	pub keyboard: KeyStates,
 	pub mouse: MouseState,
 	pub viewport_bounds: ViewportBounds,
+	pub platform: Platform,
+	pub wheel_behavior: WheelBehavior,
+	/// Milliseconds elapsed between the two most recent animation frames
+	pub frame_delta_time: f64,
+	/// Milliseconds since the Unix epoch when the frontend sent the message being handled, or `None` if the frontend has never said
+	pub time: Option<u64>,
+	/// Increment in degrees that rotations snap to while snapping is held, shared by canvas rotation, layer rotation, and the Line tool
+	pub rotate_snap_angle: f64,
+	/// How close in degrees a rotated layer must come to 0°, 90°, 180°, or 270° to be straightened onto it, or 0 to never straighten
+	pub axis_snap_tolerance: f64,
+	/// The keys the tools read as their center and constrain modifiers, which the input mapper rebuilds its mapping from when they change
+	pub modifier_bindings: ModifierBindings,
+}
+
+impl Default for InputPreprocessorMessageHandler {
+	fn default() -> Self {
+		Self {
+			keyboard: KeyStates::default(),
+			mouse: MouseState::default(),
+			viewport_bounds: ViewportBounds::default(),
+			platform: Platform::default(),
+			wheel_behavior: WheelBehavior::default(),
+			frame_delta_time: 0.,
+			time: None,
+			rotate_snap_angle: DEFAULT_ROTATE_SNAP_ANGLE,
+			axis_snap_tolerance: DEFAULT_AXIS_SNAP_TOLERANCE,
+			modifier_bindings: ModifierBindings::default(),
+		}
+	}
 }
 
 impl MessageHandler<InputPreprocessorMessage, ()> for InputPreprocessorMessageHandler {
@@ -57,6 +88,11 @@ impl MessageHandler<InputPreprocessorMessage, ()> for InputPreprocessorMessageHa
 
 				responses.push_back(InputMapperMessage::DoubleClick.into());
 			}
+			InputPreprocessorMessage::FrameTick { delta_time } => {
+				self.frame_delta_time = delta_time;
+
+				responses.push_back(InputMapperMessage::FrameTick.into());
+			}
 			InputPreprocessorMessage::KeyDown { key, modifier_keys } => {
 				self.handle_modifier_keys(modifier_keys, responses);
 				self.keyboard.set(key as usize);
@@ -81,6 +117,7 @@ impl MessageHandler<InputPreprocessorMessage, ()> for InputPreprocessorMessageHa
 
 				let mouse_state = editor_mouse_state.to_mouse_state(&self.viewport_bounds);
 				self.mouse.position = mouse_state.position;
+				self.mouse.pressure = mouse_state.pressure;
 
 				if let Some(message) = self.translate_mouse_event(mouse_state, KeyPosition::Pressed) {
 					responses.push_back(message);
@@ -91,6 +128,7 @@ impl MessageHandler<InputPreprocessorMessage, ()> for InputPreprocessorMessageHa
 
 				let mouse_state = editor_mouse_state.to_mouse_state(&self.viewport_bounds);
 				self.mouse.position = mouse_state.position;
+				self.mouse.pressure = mouse_state.pressure;
 
 				responses.push_back(InputMapperMessage::PointerMove.into());
 			}
@@ -99,11 +137,53 @@ impl MessageHandler<InputPreprocessorMessage, ()> for InputPreprocessorMessageHa
 
 				let mouse_state = editor_mouse_state.to_mouse_state(&self.viewport_bounds);
 				self.mouse.position = mouse_state.position;
+				self.mouse.pressure = mouse_state.pressure;
 
 				if let Some(message) = self.translate_mouse_event(mouse_state, KeyPosition::Released) {
 					responses.push_back(message);
 				}
 			}
+			InputPreprocessorMessage::SetAxisSnapTolerance { tolerance } => {
+				self.axis_snap_tolerance = tolerance;
+			}
+			InputPreprocessorMessage::SetModifierBindings { bindings } => {
+				// Keep the current bindings rather than apply ones that would leave a tool unable to tell two of its modifiers apart
+				let conflicts = bindings.conflicts();
+				if conflicts.is_empty() {
+					self.modifier_bindings = bindings;
+					// The hints name the bound keys
+					responses.push_back(ToolMessage::UpdateHints.into());
+				} else {
+					responses.push_back(
+						FrontendMessage::DisplayDialogError {
+							title: "Conflicting key bindings".to_string(),
+							description: conflicts.join("\n"),
+						}
+						.into(),
+					);
+				}
+			}
+			InputPreprocessorMessage::SetPlatform { platform } => {
+				self.platform = platform;
+
+				// Keep the logical key in sync if the new primary modifier is already being held
+				let primary_modifier_down = self.is_pressed(platform.primary_modifier());
+				self.handle_modifier_key(Key::KeyPrimaryModifier, primary_modifier_down, responses);
+			}
+			InputPreprocessorMessage::SetRotateSnapAngle { angle } => {
+				// Angles are snapped by dividing by this, so it must be a positive number
+				if angle.is_finite() && angle > 0. {
+					self.rotate_snap_angle = angle;
+				} else {
+					log::warn!("Ignored the invalid rotation snap angle {}", angle);
+				}
+			}
+			InputPreprocessorMessage::SetTime { timestamp } => {
+				self.time = Some(timestamp);
+			}
+			InputPreprocessorMessage::SetWheelBehavior { wheel_behavior } => {
+				self.wheel_behavior = wheel_behavior;
+			}
 		};
 	}
 
@@ -114,6 +194,16 @@ impl MessageHandler<InputPreprocessorMessage, ()> for InputPreprocessorMessageHa
 }
 
 impl InputPreprocessorMessageHandler {
+	/// Whether the key, mouse button, or logical key like [Key::KeyPrimaryModifier] is currently held down.
+	pub fn is_pressed(&self, key: Key) -> bool {
+		self.keyboard.get(key as usize)
+	}
+
+	/// Every key currently held down, in the order they are declared in [Key].
+	pub fn pressed_keys(&self) -> impl Iterator<Item = Key> + '_ {
+		(0..NUMBER_OF_KEYS).filter(|&index| self.keyboard.get(index)).filter_map(Key::from_index)
+	}
+
 	fn translate_mouse_event(&mut self, new_state: MouseState, position: KeyPosition) -> Option<Message> {
 		// Calculate the difference between the two key states (binary xor)
 		let difference = self.mouse.mouse_keys ^ new_state.mouse_keys;
@@ -141,10 +231,14 @@ impl InputPreprocessorMessageHandler {
 		self.handle_modifier_key(Key::KeyControl, modifier_keys.contains(ModifierKeys::CONTROL), responses);
 		self.handle_modifier_key(Key::KeyShift, modifier_keys.contains(ModifierKeys::SHIFT), responses);
 		self.handle_modifier_key(Key::KeyAlt, modifier_keys.contains(ModifierKeys::ALT), responses);
+		self.handle_modifier_key(Key::KeyCommand, modifier_keys.contains(ModifierKeys::COMMAND), responses);
+
+		let primary_modifier_down = self.is_pressed(self.platform.primary_modifier());
+		self.handle_modifier_key(Key::KeyPrimaryModifier, primary_modifier_down, responses);
 	}
 
 	fn handle_modifier_key(&mut self, key: Key, key_is_down: bool, responses: &mut VecDeque<Message>) {
-		let key_was_down = self.keyboard.get(key as usize);
+		let key_was_down = self.is_pressed(key);
 
 		if key_was_down && !key_is_down {
 			self.keyboard.unset(key as usize);
@@ -18,6 +18,7 @@ pub type KeyStates = BitVector<KEY_MASK_STORAGE_LENGTH>;
 // TODO: Consider renaming to `KeyMessage` for consistency with other messages that implement `#[impl_message(..)]`
 #[impl_message(Message, InputMapperMessage, KeyDown)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[repr(u8)]
 pub enum Key {
 	UnknownKey,
 
@@ -87,6 +88,11 @@ pub enum Key {
 	KeyPageDown,
 	KeyComma,
 	KeyPeriod,
+	KeyCommand,
+
+	// Logical keys
+	/// Set together with the physical modifier key used for shortcuts on the current platform: Command on Mac and Control elsewhere
+	KeyPrimaryModifier,
 
 	// This has to be the last element in the enum
 	NumKeys,
@@ -94,6 +100,14 @@ pub enum Key {
 
 pub const NUMBER_OF_KEYS: usize = Key::NumKeys as usize;
 
+impl Key {
+	/// Returns the key stored at the given index of [KeyStates], or `None` if the index is past the last key.
+	pub fn from_index(index: usize) -> Option<Key> {
+		// SAFETY: `Key` is a fieldless `u8` enum without explicit discriminants, so every index below `NumKeys` is the discriminant of a variant
+		(index < NUMBER_OF_KEYS).then(|| unsafe { std::mem::transmute::<u8, Key>(index as u8) })
+	}
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum MouseMotion {
 	None,
@@ -57,6 +57,8 @@ pub struct MouseState {
 	pub position: ViewportPosition,
 	pub mouse_keys: MouseKeys,
 	pub scroll_delta: ScrollDelta,
+	/// How hard a pen is pressed, from 0 to 1, or `None` for pointers like mice that don't sense pressure
+	pub pressure: Option<f64>,
 }
 
 impl MouseState {
@@ -69,6 +71,7 @@ impl MouseState {
 			position: (x, y).into(),
 			mouse_keys: MouseKeys::default(),
 			scroll_delta: ScrollDelta::default(),
+			pressure: None,
 		}
 	}
 
@@ -79,6 +82,7 @@ impl MouseState {
 			position,
 			mouse_keys,
 			scroll_delta: ScrollDelta::default(),
+			pressure: None,
 		}
 	}
 }
@@ -88,6 +92,8 @@ pub struct EditorMouseState {
 	pub editor_position: EditorPosition,
 	pub mouse_keys: MouseKeys,
 	pub scroll_delta: ScrollDelta,
+	/// How hard a pen is pressed, from 0 to 1, or `None` for pointers like mice that don't sense pressure
+	pub pressure: Option<f64>,
 }
 
 impl EditorMouseState {
@@ -100,6 +106,7 @@ impl EditorMouseState {
 			editor_position: (x, y).into(),
 			mouse_keys: MouseKeys::default(),
 			scroll_delta: ScrollDelta::default(),
+			pressure: None,
 		}
 	}
 
@@ -110,6 +117,7 @@ impl EditorMouseState {
 			editor_position,
 			mouse_keys,
 			scroll_delta: ScrollDelta::default(),
+			pressure: None,
 		}
 	}
 
@@ -118,6 +126,7 @@ impl EditorMouseState {
 			position: self.editor_position - active_viewport_bounds.top_left,
 			mouse_keys: self.mouse_keys,
 			scroll_delta: self.scroll_delta,
+			pressure: self.pressure,
 		}
 	}
 }
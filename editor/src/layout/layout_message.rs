@@ -16,6 +16,7 @@ pub enum LayoutMessage {
 #[repr(u8)]
 pub enum LayoutTarget {
 	DocumentBar,
+	SwatchLibrary,
 	ToolOptions,
 
 	// KEEP THIS ENUM LAST
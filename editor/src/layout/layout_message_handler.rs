@@ -23,6 +23,10 @@ impl LayoutMessageHandler {
 				layout_target,
 				layout: widget_layout.layout.clone(),
 			},
+			LayoutTarget::SwatchLibrary => FrontendMessage::UpdateSwatchLibraryLayout {
+				layout_target,
+				layout: widget_layout.layout.clone(),
+			},
 			LayoutTarget::LayoutTargetLength => panic!("`LayoutTargetLength` is not a valid Layout Target and is used for array indexing"),
 		};
 		responses.push_back(message.into());
@@ -42,6 +46,18 @@ impl MessageHandler<LayoutMessage, ()> for LayoutMessageHandler {
 				let layout = &mut self.layouts[layout_target as usize];
 				let widget_holder = layout.iter_mut().find(|widget| widget.widget_id == widget_id).expect("Received invalid widget_id from the frontend");
 				match &mut widget_holder.widget {
+					Widget::DropdownInput(dropdown_input) => {
+						let update_value = value.as_u64().expect("DropdownInput update was not of type: u64");
+						dropdown_input.selected_index = update_value as u32;
+						let entry = dropdown_input
+							.entries
+							.iter()
+							.flatten()
+							.nth(update_value as usize)
+							.expect("DropdownInput update was not a valid entry index");
+						let callback_message = (entry.on_update.callback)(entry);
+						responses.push_back(callback_message);
+					}
 					Widget::NumberInput(number_input) => match value {
 						Value::Number(num) => {
 							let update_value = num.as_f64().unwrap();
@@ -76,6 +92,10 @@ impl MessageHandler<LayoutMessage, ()> for LayoutMessageHandler {
 						let callback_message = (radio_input.entries[update_value as usize].on_update.callback)(&());
 						responses.push_back(callback_message);
 					}
+					Widget::TextButton(text_button) => {
+						let callback_message = (text_button.on_update.callback)(text_button);
+						responses.push_back(callback_message);
+					}
 				};
 				self.send_layout(layout_target, responses);
 			}
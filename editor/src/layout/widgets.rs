@@ -157,12 +157,14 @@ impl<T> Default for WidgetCallback<T> {
 #[remain::sorted]
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Widget {
+	DropdownInput(DropdownInput),
 	IconButton(IconButton),
 	NumberInput(NumberInput),
 	OptionalInput(OptionalInput),
 	PopoverButton(PopoverButton),
 	RadioInput(RadioInput),
 	Separator(Separator),
+	TextButton(TextButton),
 }
 
 #[derive(Clone, Serialize, Deserialize, Derivative)]
@@ -226,6 +228,28 @@ pub enum SeparatorType {
 	List,
 }
 
+#[derive(Clone, Serialize, Deserialize, Derivative, Default)]
+#[derivative(Debug, PartialEq)]
+pub struct DropdownInput {
+	/// The entries of the dropdown menu, grouped into sections which are divided by separators
+	#[serde(rename = "menuEntries")]
+	pub entries: Vec<Vec<DropdownEntryData>>,
+
+	// This uses `u32` instead of `usize` since it will be serialized as a normal JS number
+	#[serde(rename = "selectedIndex")]
+	pub selected_index: u32,
+}
+
+#[derive(Clone, Serialize, Deserialize, Derivative, Default)]
+#[derivative(Debug, PartialEq)]
+pub struct DropdownEntryData {
+	pub value: String,
+	pub label: String,
+	#[serde(skip)]
+	#[derivative(Debug = "ignore", PartialEq = "ignore")]
+	pub on_update: WidgetCallback<DropdownEntryData>,
+}
+
 #[derive(Clone, Serialize, Deserialize, Derivative, Default)]
 #[derivative(Debug, PartialEq)]
 pub struct IconButton {
@@ -240,6 +264,16 @@ pub struct IconButton {
 	pub on_update: WidgetCallback<IconButton>,
 }
 
+#[derive(Clone, Serialize, Deserialize, Derivative, Default)]
+#[derivative(Debug, PartialEq)]
+pub struct TextButton {
+	pub label: String,
+	pub emphasized: bool,
+	#[serde(skip)]
+	#[derivative(Debug = "ignore", PartialEq = "ignore")]
+	pub on_update: WidgetCallback<TextButton>,
+}
+
 #[derive(Clone, Serialize, Deserialize, Derivative, Default)]
 #[derivative(Debug, PartialEq)]
 pub struct OptionalInput {
@@ -138,3 +138,29 @@ macro_rules! advertise_actions {
 		}
 	}
 }
+
+/// Implements `ConsumesInput` for a tool, which consumes input whenever its `fsm_state` isn't the default `Ready` state.
+///
+/// # Example
+///
+/// ```ignore
+/// consumes_input_unless_ready!(Pen);
+/// ```
+///
+/// expands to:
+/// ```ignore
+/// impl ConsumesInput for Pen {
+///     fn consumes_input(&self) -> bool {
+///         self.fsm_state != PenToolFsmState::default()
+///     }
+/// }
+/// ```
+macro_rules! consumes_input_unless_ready {
+	($tool:ty) => {
+		impl $crate::viewport_tools::tool::ConsumesInput for $tool {
+			fn consumes_input(&self) -> bool {
+				self.fsm_state != ::std::default::Default::default()
+			}
+		}
+	};
+}
@@ -1,10 +1,14 @@
+use crate::document::DocumentMessageHandler;
 use crate::input::input_preprocessor::ModifierKeys;
+use crate::input::keyboard::Key;
 use crate::input::mouse::{EditorMouseState, MouseKeys, ScrollDelta, ViewportPosition};
 use crate::message_prelude::*;
 use crate::viewport_tools::tool::ToolType;
 use crate::Editor;
 
 use graphene::color::Color;
+use graphene::layers::layer_info::{Layer, LayerDataType};
+use graphene::layers::style::PathStyle;
 
 /// A set of utility functions to make the writing of editor test more declarative
 pub trait EditorTestUtils {
@@ -18,9 +22,42 @@ pub trait EditorTestUtils {
 	fn mousedown(&mut self, state: EditorMouseState);
 	fn mouseup(&mut self, state: EditorMouseState);
 	fn lmb_mousedown(&mut self, x: f64, y: f64);
+	/// Move the pointer to a position while holding the given mouse buttons and modifier keys
+	fn pointer_move(&mut self, position: impl Into<ViewportPosition>, mouse_keys: MouseKeys, modifier_keys: ModifierKeys);
+	/// Press the given mouse buttons at a position while holding the modifier keys
+	fn pointer_down(&mut self, position: impl Into<ViewportPosition>, mouse_keys: MouseKeys, modifier_keys: ModifierKeys);
+	/// Release the mouse buttons at a position, leaving the given ones held, while holding the modifier keys
+	fn pointer_up(&mut self, position: impl Into<ViewportPosition>, mouse_keys: MouseKeys, modifier_keys: ModifierKeys);
+	/// Press and release the left mouse button at a position
+	fn click(&mut self, x: f64, y: f64);
+	/// Click twice at a position, followed by the double click that the frontend sends after the second one
+	fn double_click(&mut self, x: f64, y: f64);
+	/// Press a key while holding the modifier keys
+	fn press_key(&mut self, key: Key, modifier_keys: ModifierKeys);
 	fn input(&mut self, message: InputPreprocessorMessage);
 	fn select_tool(&mut self, typ: ToolType);
 	fn select_primary_color(&mut self, color: Color);
+
+	fn active_document(&self) -> &DocumentMessageHandler;
+	/// The IDs of the layers in the root folder of the active document, from bottom to top
+	fn root_layer_ids(&self) -> Vec<LayerId>;
+	/// The ID of the topmost layer in the root folder of the active document, which is the one most recently drawn
+	fn last_layer_id(&self) -> LayerId;
+	/// The topmost layer in the root folder of the active document, which is the one most recently drawn
+	fn last_layer(&self) -> &Layer;
+	/// The style of the topmost layer in the root folder of the active document, which must be a shape
+	fn last_shape_style(&self) -> PathStyle;
+	/// The paths of the selected layers in the active document, from bottom to top
+	fn selected_layers(&self) -> Vec<Vec<LayerId>>;
+}
+
+fn mouse_state(position: impl Into<ViewportPosition>, mouse_keys: MouseKeys) -> EditorMouseState {
+	EditorMouseState {
+		editor_position: position.into(),
+		mouse_keys,
+		scroll_delta: ScrollDelta::default(),
+		pressure: None,
+	}
 }
 
 impl EditorTestUtils for Editor {
@@ -45,6 +82,7 @@ impl EditorTestUtils for Editor {
 			editor_position: (x2, y2).into(),
 			mouse_keys: MouseKeys::empty(),
 			scroll_delta: ScrollDelta::default(),
+			pressure: None,
 		});
 	}
 
@@ -70,9 +108,43 @@ impl EditorTestUtils for Editor {
 			editor_position: (x, y).into(),
 			mouse_keys: MouseKeys::LEFT,
 			scroll_delta: ScrollDelta::default(),
+			pressure: None,
 		});
 	}
 
+	fn pointer_move(&mut self, position: impl Into<ViewportPosition>, mouse_keys: MouseKeys, modifier_keys: ModifierKeys) {
+		let editor_mouse_state = mouse_state(position, mouse_keys);
+		self.input(InputPreprocessorMessage::PointerMove { editor_mouse_state, modifier_keys });
+	}
+
+	fn pointer_down(&mut self, position: impl Into<ViewportPosition>, mouse_keys: MouseKeys, modifier_keys: ModifierKeys) {
+		let editor_mouse_state = mouse_state(position, mouse_keys);
+		self.input(InputPreprocessorMessage::PointerDown { editor_mouse_state, modifier_keys });
+	}
+
+	fn pointer_up(&mut self, position: impl Into<ViewportPosition>, mouse_keys: MouseKeys, modifier_keys: ModifierKeys) {
+		let editor_mouse_state = mouse_state(position, mouse_keys);
+		self.input(InputPreprocessorMessage::PointerUp { editor_mouse_state, modifier_keys });
+	}
+
+	fn click(&mut self, x: f64, y: f64) {
+		self.move_mouse(x, y);
+		self.pointer_down((x, y), MouseKeys::LEFT, ModifierKeys::empty());
+		self.pointer_up((x, y), MouseKeys::empty(), ModifierKeys::empty());
+	}
+
+	fn double_click(&mut self, x: f64, y: f64) {
+		self.click(x, y);
+		self.click(x, y);
+		let editor_mouse_state = mouse_state((x, y), MouseKeys::empty());
+		let modifier_keys = ModifierKeys::empty();
+		self.input(InputPreprocessorMessage::DoubleClick { editor_mouse_state, modifier_keys });
+	}
+
+	fn press_key(&mut self, key: Key, modifier_keys: ModifierKeys) {
+		self.input(InputPreprocessorMessage::KeyDown { key, modifier_keys });
+	}
+
 	fn input(&mut self, message: InputPreprocessorMessage) {
 		self.handle_message(Message::InputPreprocessor(message));
 	}
@@ -84,4 +156,45 @@ impl EditorTestUtils for Editor {
 	fn select_primary_color(&mut self, color: Color) {
 		self.handle_message(Message::Tool(ToolMessage::SelectPrimaryColor { color }));
 	}
+
+	fn active_document(&self) -> &DocumentMessageHandler {
+		self.dispatcher.message_handlers.portfolio_message_handler.active_document()
+	}
+
+	fn root_layer_ids(&self) -> Vec<LayerId> {
+		self.active_document().graphene_document.root.as_folder().unwrap().layer_ids.clone()
+	}
+
+	fn last_layer_id(&self) -> LayerId {
+		*self
+			.active_document()
+			.graphene_document
+			.root
+			.as_folder()
+			.unwrap()
+			.layer_ids
+			.last()
+			.expect("The document should have a layer")
+	}
+
+	fn last_layer(&self) -> &Layer {
+		self.active_document()
+			.graphene_document
+			.root
+			.as_folder()
+			.unwrap()
+			.layers()
+			.last()
+			.expect("The document should have a layer")
+	}
+	fn last_shape_style(&self) -> PathStyle {
+		match &self.last_layer().data {
+			LayerDataType::Shape(shape) => shape.style,
+			_ => panic!("The last layer should be a shape"),
+		}
+	}
+
+	fn selected_layers(&self) -> Vec<Vec<LayerId>> {
+		self.active_document().selected_layers_sorted().iter().map(|path| path.to_vec()).collect()
+	}
 }
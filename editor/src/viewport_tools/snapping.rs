@@ -1,7 +1,13 @@
-use crate::consts::{COLOR_ACCENT, SNAP_OVERLAY_FADE_DISTANCE, SNAP_OVERLAY_UNSNAPPED_OPACITY, SNAP_TOLERANCE};
+use crate::consts::{
+	COLOR_ACCENT, DEFAULT_SNAP_REFERENCE_FRACTION, EQUAL_SPACING_EPSILON, SNAP_EXTENSION_GUIDE_OPACITY, SNAP_OVERLAY_FADE_DISTANCE, SNAP_OVERLAY_UNSNAPPED_OPACITY, SNAP_POINT_INDICATOR_SIZE,
+	SNAP_POINT_TOLERANCE, SNAP_REFERENCE_POINT_SIZE, SNAP_TARGET_MARKER_RADIUS, SNAP_TARGET_MARKER_SIZE, SNAP_TOLERANCE,
+};
 use crate::document::DocumentMessageHandler;
 use crate::message_prelude::*;
+use crate::viewport_tools::tools::shared::overlay_pool::{OverlayPool, OverlayRole};
 
+use graphene::document::Document;
+use graphene::layers::layer_info::LayerData;
 use graphene::layers::style::{self, Stroke};
 use graphene::{LayerId, Operation};
 
@@ -11,7 +17,20 @@ use std::f64::consts::PI;
 #[derive(Debug, Clone, Default)]
 pub struct SnapHandler {
 	snap_targets: Option<(Vec<f64>, Vec<f64>)>,
+	target_bounding_boxes: Vec<[DVec2; 2]>,
+	point_targets: Vec<DVec2>,
+	reference_targets: Vec<DVec2>,
+	/// The anchor points of other shapes in viewport coords, which a position snaps onto within `anchor_tolerance` of them
+	anchor_targets: Vec<DVec2>,
+	anchor_tolerance: f64,
+	/// The endless lines that existing line segments extend along, each as a point on it and its direction in viewport coords
+	extension_targets: Vec<(DVec2, DVec2)>,
 	overlay_paths: Vec<Vec<LayerId>>,
+	point_indicator_path: Option<Vec<LayerId>>,
+	extension_guide_path: Option<Vec<LayerId>>,
+	/// The markers drawn on the snap targets near the pointer when they are turned on for debugging, along with the pool they are reused from
+	target_markers: Vec<(OverlayRole, Vec<LayerId>)>,
+	marker_pool: OverlayPool,
 }
 
 impl SnapHandler {
@@ -86,6 +105,100 @@ impl SnapHandler {
 		Self::remove_unused_overlays(overlay_paths, responses, index);
 	}
 
+	/// Draws a ring around the point snap target that the position snapped onto, or removes the ring if there is none.
+	pub fn update_point_indicator(point_indicator_path: &mut Option<Vec<LayerId>>, responses: &mut VecDeque<Message>, snapped_point: Option<DVec2>) {
+		match snapped_point {
+			Some(point) => {
+				let scale = DVec2::splat(SNAP_POINT_INDICATOR_SIZE);
+				let transform = DAffine2::from_scale_angle_translation(scale, 0., point - scale / 2.).to_cols_array();
+
+				let operation = match point_indicator_path {
+					Some(path) => Operation::SetLayerTransform { path: path.clone(), transform },
+					None => {
+						let path = vec![generate_uuid()];
+						*point_indicator_path = Some(path.clone());
+						Operation::AddOverlayEllipse {
+							path,
+							transform,
+							style: style::PathStyle::new(Some(Stroke::new(COLOR_ACCENT, 2.0)), None),
+						}
+					}
+				};
+				responses.push_back(DocumentMessage::Overlays(operation.into()).into());
+			}
+			None => {
+				if let Some(path) = point_indicator_path.take() {
+					responses.push_back(DocumentMessage::Overlays(Operation::DeleteLayer { path }.into()).into());
+				}
+			}
+		}
+	}
+
+	/// Draws a faint guide across the viewport along the extension of a line that a position snapped onto, or removes the guide if there is none.
+	fn update_extension_guide(extension_guide_path: &mut Option<Vec<LayerId>>, responses: &mut VecDeque<Message>, viewport_bounds: DVec2, extension: Option<(DVec2, DVec2)>) {
+		match extension {
+			Some((origin, direction)) => {
+				// Starting a viewport diagonal before the point on the line nearest the viewport center, and going twice as far, crosses the whole viewport
+				let center = viewport_bounds / 2.;
+				let diagonal = viewport_bounds.length();
+				let start = origin + direction * ((center - origin).dot(direction) - diagonal);
+				let transform = DAffine2::from_scale_angle_translation(DVec2::new(diagonal * 2., 1.), direction.y.atan2(direction.x), start).to_cols_array();
+
+				match extension_guide_path {
+					Some(path) => responses.push_back(DocumentMessage::Overlays(Operation::SetLayerTransform { path: path.clone(), transform }.into()).into()),
+					None => {
+						let path = vec![generate_uuid()];
+						*extension_guide_path = Some(path.clone());
+						let style = style::PathStyle::new(Some(Stroke::new(COLOR_ACCENT, 1.0)), None);
+						responses.push_back(DocumentMessage::Overlays(Operation::AddOverlayLine { path: path.clone(), transform, style }.into()).into());
+						let opacity = SNAP_EXTENSION_GUIDE_OPACITY;
+						responses.push_back(DocumentMessage::Overlays(Operation::SetLayerOpacity { path, opacity }.into()).into());
+					}
+				}
+			}
+			None => {
+				if let Some(path) = extension_guide_path.take() {
+					responses.push_back(DocumentMessage::Overlays(Operation::DeleteLayer { path }.into()).into());
+				}
+			}
+		}
+	}
+
+	/// Marks every snap target within a radius of the given viewport position, if the snap target markers are turned on for debugging.
+	/// Point targets are marked with circles, and the crossings of the alignment lines (such as the corners, edge midpoints, and centers of the target bounds) with squares.
+	fn update_target_markers(&mut self, responses: &mut VecDeque<Message>, document_message_handler: &DocumentMessageHandler, position_viewport: DVec2) {
+		let mut buffer = Vec::new();
+		for (role, path) in self.target_markers.drain(..) {
+			self.marker_pool.release(role, path, &mut buffer);
+		}
+
+		if document_message_handler.snap_targets_visible {
+			let nearby = |point: &DVec2| point.distance(position_viewport) <= SNAP_TARGET_MARKER_RADIUS;
+			let points = self.point_targets.iter().chain(self.reference_targets.iter()).chain(self.anchor_targets.iter()).copied().filter(nearby);
+
+			// Only the alignment lines near the pointer are crossed with each other, to keep the number of intersections small
+			let (x_targets, y_targets) = self.snap_targets.as_ref().map(|(x, y)| (x.as_slice(), y.as_slice())).unwrap_or_default();
+			let x_targets: Vec<f64> = x_targets.iter().copied().filter(|x| (x - position_viewport.x).abs() <= SNAP_TARGET_MARKER_RADIUS).collect();
+			let y_targets = y_targets.iter().copied().filter(|y| (y - position_viewport.y).abs() <= SNAP_TARGET_MARKER_RADIUS);
+			let intersections = y_targets.flat_map(|y| x_targets.iter().map(move |&x| DVec2::new(x, y))).filter(nearby);
+
+			let markers = points
+				.map(|point| (OverlayRole::SnapPointMarker, point))
+				.chain(intersections.map(|point| (OverlayRole::SnapIntersectionMarker, point)));
+			for (role, point) in markers.collect::<Vec<_>>() {
+				let path = self.marker_pool.acquire(role, &mut buffer);
+
+				let scale = DVec2::splat(SNAP_TARGET_MARKER_SIZE);
+				let transform = DAffine2::from_scale_angle_translation(scale, 0., point - scale / 2.).to_cols_array();
+				buffer.push(DocumentMessage::Overlays(Operation::SetLayerTransform { path: path.clone(), transform }.into()).into());
+
+				self.target_markers.push((role, path));
+			}
+		}
+
+		responses.extend(buffer);
+	}
+
 	/// Remove overlays from the pool beyond a given index. Pool entries up through that index will be kept.
 	fn remove_unused_overlays(overlay_paths: &mut Vec<Vec<LayerId>>, responses: &mut VecDeque<Message>, remove_after_index: usize) {
 		while overlay_paths.len() > remove_after_index {
@@ -96,8 +209,20 @@ impl SnapHandler {
 	/// Gets a list of snap targets for the X and Y axes (if specified) in Viewport coords for the target layers (usually all layers or all non-selected layers.)
 	/// This should be called at the start of a drag.
 	pub fn start_snap(&mut self, document_message_handler: &DocumentMessageHandler, bounding_boxes: impl Iterator<Item = [DVec2; 2]>, snap_x: bool, snap_y: bool) {
+		// The bounding boxes are kept regardless of the snapping state so they can be used for measuring distances
+		self.target_bounding_boxes = bounding_boxes.collect();
+
+		// The reference points are placed in document space, so their targets are found in viewport coords at the start of each drag like the bounding boxes
+		self.reference_targets = document_message_handler.snap_reference_points.targets(document_message_handler.graphene_document.root.transform);
+
 		if document_message_handler.snapping_enabled {
-			let (x_targets, y_targets) = bounding_boxes.flat_map(|[bound1, bound2]| [bound1, bound2, ((bound1 + bound2) / 2.)]).map(|vec| vec.into()).unzip();
+			let (x_targets, y_targets) = self
+				.target_bounding_boxes
+				.iter()
+				.flat_map(|&[bound1, bound2]| [bound1, bound2, ((bound1 + bound2) / 2.)])
+				.chain(self.reference_targets.iter().copied())
+				.map(|vec| vec.into())
+				.unzip();
 
 			// Could be made into sorted Vec or a HashSet for more performant lookups.
 			self.snap_targets = Some((if snap_x { x_targets } else { Vec::new() }, if snap_y { y_targets } else { Vec::new() }));
@@ -117,6 +242,42 @@ impl SnapHandler {
 		}
 	}
 
+	/// Sets the points in viewport coords which a position snaps onto, on both axes at once, when it comes within the point snap tolerance of them.
+	/// Unlike the targets found by `start_snap`, these may be replaced in the middle of a drag, such as to follow the start of a path as the canvas moves.
+	pub fn set_point_targets(&mut self, point_targets: Vec<DVec2>) {
+		self.point_targets = point_targets;
+	}
+
+	/// Adds anchor points in viewport coords, such as the corners of other shapes, which a position snaps onto when it comes within `tolerance` viewport pixels of them.
+	/// This lets the end of a line land exactly on a vertex. It should be called after `start_snap`.
+	pub fn add_anchor_targets(&mut self, document_message_handler: &DocumentMessageHandler, anchors: impl Iterator<Item = DVec2>, tolerance: f64) {
+		if document_message_handler.snapping_enabled {
+			self.anchor_targets.extend(anchors);
+			self.anchor_tolerance = tolerance;
+		}
+	}
+
+	/// Adds the extensions of line segments, given by their endpoints in viewport coords, as endless lines which a position snaps onto when it comes within the snap tolerance of them.
+	/// This makes it easy to draw lines collinear with existing ones. It should be called after `start_snap`.
+	pub fn add_extension_targets(&mut self, document_message_handler: &DocumentMessageHandler, segments: impl Iterator<Item = [DVec2; 2]>) {
+		if document_message_handler.snapping_enabled {
+			let extensions = segments.filter(|[start, end]| start != end).map(|[start, end]| (start, (end - start).normalize()));
+			self.extension_targets.extend(extensions);
+		}
+	}
+
+	/// Moves the snap targets along with the canvas when it is panned in the middle of a drag, since they are stored in viewport coords.
+	pub fn translate_targets(&mut self, delta: DVec2) {
+		if let Some((x_targets, y_targets)) = &mut self.snap_targets {
+			x_targets.iter_mut().for_each(|x| *x += delta.x);
+			y_targets.iter_mut().for_each(|y| *y += delta.y);
+		}
+		self.target_bounding_boxes.iter_mut().flatten().for_each(|bound| *bound += delta);
+		self.reference_targets.iter_mut().for_each(|target| *target += delta);
+		self.anchor_targets.iter_mut().for_each(|target| *target += delta);
+		self.extension_targets.iter_mut().for_each(|(origin, _)| *origin += delta);
+	}
+
 	/// Finds the closest snap from an array of layers to the specified snap targets in viewport coords.
 	/// Returns 0 for each axis that there is no snap less than the snap tolerance.
 	pub fn snap_layers(
@@ -128,6 +289,13 @@ impl SnapHandler {
 		mouse_delta: DVec2,
 	) -> DVec2 {
 		if document_message_handler.snapping_enabled {
+			// The markers are centered on the layers being snapped, since the pointer position isn't known here
+			if !snap_x.is_empty() && !snap_y.is_empty() {
+				let average = |values: &[f64]| values.iter().sum::<f64>() / values.len() as f64;
+				let center = DVec2::new(average(&snap_x), average(&snap_y)) + mouse_delta;
+				self.update_target_markers(responses, document_message_handler, center);
+			}
+
 			if let Some((targets_x, targets_y)) = &self.snap_targets {
 				let positions = targets_x.iter().flat_map(|&target| snap_x.iter().map(move |&snap| (target, target - mouse_delta.x - snap)));
 				let distances = targets_y.iter().flat_map(|&target| snap_y.iter().map(move |&snap| (target, target - mouse_delta.y - snap)));
@@ -157,6 +325,33 @@ impl SnapHandler {
 	/// Handles snapping of a viewport position, returning another viewport position.
 	pub fn snap_position(&mut self, responses: &mut VecDeque<Message>, viewport_bounds: DVec2, document_message_handler: &DocumentMessageHandler, position_viewport: DVec2) -> DVec2 {
 		if document_message_handler.snapping_enabled {
+			self.update_target_markers(responses, document_message_handler, position_viewport);
+
+			let point_targets = self.point_targets.iter().chain(self.reference_targets.iter()).map(|&point| (point, SNAP_POINT_TOLERANCE));
+			let anchor_targets = self.anchor_targets.iter().map(|&point| (point, self.anchor_tolerance));
+			let closest_point_target = point_targets
+				.chain(anchor_targets)
+				.filter(|(point, tolerance)| point.distance(position_viewport) <= *tolerance)
+				.map(|(point, _)| point)
+				.min_by(|a, b| a.distance(position_viewport).partial_cmp(&b.distance(position_viewport)).expect("Could not compare distance."));
+			Self::update_point_indicator(&mut self.point_indicator_path, responses, closest_point_target);
+
+			// Snapping onto the extension of a line comes after points, and is found by projecting the position onto each extension
+			let closest_extension = self
+				.extension_targets
+				.iter()
+				.map(|&(origin, direction)| (origin + direction * (position_viewport - origin).dot(direction), (origin, direction)))
+				.filter(|(projected, _)| projected.distance(position_viewport) <= SNAP_TOLERANCE)
+				.min_by(|a, b| a.0.distance(position_viewport).partial_cmp(&b.0.distance(position_viewport)).expect("Could not compare distance."))
+				.filter(|_| closest_point_target.is_none());
+			Self::update_extension_guide(&mut self.extension_guide_path, responses, viewport_bounds, closest_extension.map(|(_, extension)| extension));
+
+			// Snapping onto a point or extension takes priority over the alignment lines, which are hidden so they don't distract from the indicator or guide
+			if let Some(point) = closest_point_target.or_else(|| closest_extension.map(|(projected, _)| projected)) {
+				Self::remove_unused_overlays(&mut self.overlay_paths, responses, 0);
+				return point;
+			}
+
 			if let Some((targets_x, targets_y)) = &self.snap_targets {
 				let positions = targets_x.iter().map(|&x| (x, x - position_viewport.x));
 				let distances = targets_y.iter().map(|&y| (y, y - position_viewport.y));
@@ -183,9 +378,305 @@ impl SnapHandler {
 		}
 	}
 
+	/// Finds the gaps between the given bounds and the closest snap target bounding box in each of the four directions.
+	/// Only targets which overlap the bounds on the perpendicular axis are considered. Returns the start and end of each gap in viewport coords.
+	pub fn distances_to_nearest_targets(&self, [min, max]: [DVec2; 2]) -> Vec<[DVec2; 2]> {
+		let overlaps_x = |[target_min, target_max]: &[DVec2; 2]| target_min.x < max.x && target_max.x > min.x;
+		let overlaps_y = |[target_min, target_max]: &[DVec2; 2]| target_min.y < max.y && target_max.y > min.y;
+		fn closest(gaps: impl Iterator<Item = f64>) -> Option<f64> {
+			gaps.filter(|gap| *gap > 0.).min_by(|a, b| a.partial_cmp(b).expect("Could not compare distance."))
+		}
+
+		let center = (min + max) / 2.;
+		let mut gaps = Vec::new();
+
+		if let Some(gap) = closest(self.target_bounding_boxes.iter().filter(|bounds| overlaps_y(bounds)).map(|[_, target_max]| min.x - target_max.x)) {
+			gaps.push([DVec2::new(min.x - gap, center.y), DVec2::new(min.x, center.y)]);
+		}
+		if let Some(gap) = closest(self.target_bounding_boxes.iter().filter(|bounds| overlaps_y(bounds)).map(|[target_min, _]| target_min.x - max.x)) {
+			gaps.push([DVec2::new(max.x, center.y), DVec2::new(max.x + gap, center.y)]);
+		}
+		if let Some(gap) = closest(self.target_bounding_boxes.iter().filter(|bounds| overlaps_x(bounds)).map(|[_, target_max]| min.y - target_max.y)) {
+			gaps.push([DVec2::new(center.x, min.y - gap), DVec2::new(center.x, min.y)]);
+		}
+		if let Some(gap) = closest(self.target_bounding_boxes.iter().filter(|bounds| overlaps_x(bounds)).map(|[target_min, _]| target_min.y - max.y)) {
+			gaps.push([DVec2::new(center.x, max.y), DVec2::new(center.x, max.y + gap)]);
+		}
+
+		gaps
+	}
+
+	/// Finds the closest snap target bounding boxes on either side of the given bounds along an axis (0 for X, 1 for Y), among the targets which overlap the bounds on the other axis.
+	/// Returns the far edge of the target before the bounds and the near edge of the target after them.
+	fn neighbors_along_axis(&self, [min, max]: [DVec2; 2], axis: usize) -> Option<(f64, f64)> {
+		let other_axis = 1 - axis;
+		let center = (min + max) / 2.;
+		let neighbors = self
+			.target_bounding_boxes
+			.iter()
+			.filter(|[target_min, target_max]| target_min[other_axis] < max[other_axis] && target_max[other_axis] > min[other_axis]);
+
+		let before = neighbors
+			.clone()
+			.filter(|[target_min, target_max]| (target_min[axis] + target_max[axis]) / 2. < center[axis])
+			.map(|[_, target_max]| target_max[axis])
+			.max_by(|a, b| a.partial_cmp(b).expect("Could not compare position."))?;
+		let after = neighbors
+			.filter(|[target_min, target_max]| (target_min[axis] + target_max[axis]) / 2. > center[axis])
+			.map(|[target_min, _]| target_min[axis])
+			.min_by(|a, b| a.partial_cmp(b).expect("Could not compare position."))?;
+
+		// The bounds must fit between the neighbors for there to be an equal gap on both sides
+		(after - before > max[axis] - min[axis]).then(|| (before, after))
+	}
+
+	/// Finds the offset in viewport coords which moves the given bounds to where the gaps to the closest snap targets on both sides are equal.
+	/// Returns 0 for each axis that there is no such position within the snap tolerance.
+	pub fn equal_spacing_snap(&self, document_message_handler: &DocumentMessageHandler, bounds: [DVec2; 2]) -> DVec2 {
+		if !document_message_handler.snapping_enabled {
+			return DVec2::ZERO;
+		}
+
+		let [min, max] = bounds;
+		let offset_along_axis = |axis: usize| {
+			let (before, after) = self.neighbors_along_axis(bounds, axis)?;
+			let equal_gap_min = (before + after - (max[axis] - min[axis])) / 2.;
+			let offset = equal_gap_min - min[axis];
+			(offset.abs() <= SNAP_TOLERANCE).then(|| offset)
+		};
+
+		DVec2::new(offset_along_axis(0).unwrap_or(0.), offset_along_axis(1).unwrap_or(0.))
+	}
+
+	/// Finds the gaps on both sides of the given bounds along each axis where they are equally spaced between the closest snap targets.
+	/// Returns the start and end of each gap in viewport coords, to be labelled with their equal lengths.
+	pub fn equal_spacing_gaps(&self, bounds: [DVec2; 2]) -> Vec<[DVec2; 2]> {
+		let [min, max] = bounds;
+		let center = (min + max) / 2.;
+		let mut gaps = Vec::new();
+
+		for axis in 0..2 {
+			if let Some((before, after)) = self.neighbors_along_axis(bounds, axis) {
+				if ((min[axis] - before) - (after - max[axis])).abs() < EQUAL_SPACING_EPSILON {
+					let mut points = [center; 4];
+					points[0][axis] = before;
+					points[1][axis] = min[axis];
+					points[2][axis] = max[axis];
+					points[3][axis] = after;
+					gaps.extend([[points[0], points[1]], [points[2], points[3]]]);
+				}
+			}
+		}
+
+		gaps
+	}
+
 	/// Removes snap target data and overlays. Call this when snapping is done.
 	pub fn cleanup(&mut self, responses: &mut VecDeque<Message>) {
 		Self::remove_unused_overlays(&mut self.overlay_paths, responses, 0);
+		Self::update_point_indicator(&mut self.point_indicator_path, responses, None);
+		Self::update_extension_guide(&mut self.extension_guide_path, responses, DVec2::ZERO, None);
+		for (role, path) in self.target_markers.drain(..) {
+			self.marker_pool.release(role, path, responses);
+		}
+		self.marker_pool.clear(responses);
 		self.snap_targets = None;
+		self.target_bounding_boxes.clear();
+		self.point_targets.clear();
+		self.reference_targets.clear();
+		self.anchor_targets.clear();
+		self.extension_targets.clear();
+	}
+}
+
+/// A pair of temporary points placed by the user to snap onto a fraction of the way along the line between them, such as to center something between two features.
+/// The points are kept in document space so they stay on the features they were placed on as the canvas moves.
+#[derive(Debug, Clone)]
+pub struct SnapReferencePoints {
+	points: Vec<DVec2>,
+	/// How far along the line from the first point to the second the snap target lies, mirrored to also give a target the same distance from the second point
+	pub fraction: f64,
+	ring_paths: Vec<Vec<LayerId>>,
+	line_path: Option<Vec<LayerId>>,
+}
+
+impl Default for SnapReferencePoints {
+	fn default() -> Self {
+		Self {
+			points: Vec::new(),
+			fraction: DEFAULT_SNAP_REFERENCE_FRACTION,
+			ring_paths: Vec::new(),
+			line_path: None,
+		}
+	}
+}
+
+impl SnapReferencePoints {
+	/// Places a reference point in document space, starting over with a new pair if both points were already placed.
+	pub fn place(&mut self, point: DVec2) {
+		if self.points.len() == 2 {
+			self.points.clear();
+		}
+		self.points.push(point);
+	}
+
+	pub fn clear(&mut self) {
+		self.points.clear();
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.points.is_empty()
+	}
+
+	/// Gets the snap targets in viewport coords once both points are placed, at the fraction along the line between them from either end.
+	pub fn targets(&self, document_to_viewport: DAffine2) -> Vec<DVec2> {
+		match self.points[..] {
+			[start, end] => {
+				let (start, end) = (document_to_viewport.transform_point2(start), document_to_viewport.transform_point2(end));
+				let mut targets = vec![start.lerp(end, self.fraction)];
+				if (self.fraction - 0.5).abs() > f64::EPSILON {
+					targets.push(end.lerp(start, self.fraction));
+				}
+				targets
+			}
+			_ => Vec::new(),
+		}
+	}
+
+	/// Draws a ring at each reference point and a line between the pair, reusing the overlays from the last time they were drawn and removing those no longer needed.
+	pub fn render_overlays(&mut self, document_to_viewport: DAffine2, responses: &mut VecDeque<Message>) {
+		let viewport_points: Vec<DVec2> = self.points.iter().map(|&point| document_to_viewport.transform_point2(point)).collect();
+		let style = style::PathStyle::new(Some(Stroke::new(COLOR_ACCENT, 1.0)), None);
+
+		for (index, &point) in viewport_points.iter().enumerate() {
+			let scale = DVec2::splat(SNAP_REFERENCE_POINT_SIZE);
+			let transform = DAffine2::from_scale_angle_translation(scale, 0., point - scale / 2.).to_cols_array();
+
+			let operation = match self.ring_paths.get(index) {
+				Some(path) => Operation::SetLayerTransform { path: path.clone(), transform },
+				None => {
+					let path = vec![generate_uuid()];
+					self.ring_paths.push(path.clone());
+					Operation::AddOverlayEllipse {
+						path,
+						transform,
+						style: style.clone(),
+					}
+				}
+			};
+			responses.push_back(DocumentMessage::Overlays(operation.into()).into());
+		}
+		SnapHandler::remove_unused_overlays(&mut self.ring_paths, responses, viewport_points.len());
+
+		match viewport_points[..] {
+			[start, end] => {
+				let line = end - start;
+				let transform = DAffine2::from_scale_angle_translation(DVec2::new(line.length(), 1.), line.y.atan2(line.x), start).to_cols_array();
+
+				let operation = match &self.line_path {
+					Some(path) => Operation::SetLayerTransform { path: path.clone(), transform },
+					None => {
+						let path = vec![generate_uuid()];
+						self.line_path = Some(path.clone());
+						Operation::AddOverlayLine { path, transform, style }
+					}
+				};
+				responses.push_back(DocumentMessage::Overlays(operation.into()).into());
+			}
+			_ => {
+				if let Some(path) = self.line_path.take() {
+					responses.push_back(DocumentMessage::Overlays(Operation::DeleteLayer { path }.into()).into());
+				}
+			}
+		}
+	}
+}
+
+/// Finds the offset in viewport coords that moves the top left corner of the layers' combined bounding box, once moved by `mouse_delta`, onto the nearest whole pixel in document space.
+/// Snapping in document space keeps the layers on the pixel grid for crisp raster rendering regardless of the canvas zoom.
+pub fn pixel_grid_snap(document: &Document, layers: &[Vec<LayerId>], mouse_delta: DVec2) -> DVec2 {
+	let viewport_to_document = document.root.transform.inverse();
+
+	let bounds = layers
+		.iter()
+		.filter_map(|path| {
+			let layer = document.layer(path).ok()?;
+			let transform = viewport_to_document * document.multiply_transforms(path).ok()?;
+			layer.data.bounding_box(transform)
+		})
+		.reduce(|a, b| [a[0].min(b[0]), a[1].max(b[1])]);
+
+	match bounds {
+		Some([top_left, _]) => {
+			let moved_top_left = top_left + viewport_to_document.transform_vector2(mouse_delta);
+			document.root.transform.transform_vector2(moved_top_left.round() - moved_top_left)
+		}
+		None => DVec2::ZERO,
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn count_markers(responses: &VecDeque<Message>, filter: impl Fn(&Operation) -> bool) -> usize {
+		responses
+			.iter()
+			.filter(|message| match message {
+				Message::Portfolio(PortfolioMessage::Document(DocumentMessage::Overlays(OverlaysMessage::DispatchOperation(operation)))) => filter(operation),
+				_ => false,
+			})
+			.count()
+	}
+
+	#[test]
+	fn snap_target_markers_are_limited_to_the_radius() {
+		let mut document = DocumentMessageHandler::default();
+		let mut snap_handler = SnapHandler::default();
+		let mut responses = VecDeque::new();
+
+		let far = DVec2::splat(SNAP_TARGET_MARKER_RADIUS * 2.);
+		snap_handler.set_point_targets(vec![DVec2::new(10., 10.), DVec2::new(20., 0.), far]);
+
+		// The markers are off by default
+		snap_handler.snap_position(&mut responses, DVec2::splat(1000.), &document, DVec2::ZERO);
+		assert_eq!(count_markers(&responses, |operation| matches!(operation, Operation::AddOverlayEllipse { .. })), 0);
+
+		// Only the targets within the radius of the pointer are marked
+		document.snap_targets_visible = true;
+		let mut responses = VecDeque::new();
+		snap_handler.snap_position(&mut responses, DVec2::splat(1000.), &document, DVec2::ZERO);
+		assert_eq!(count_markers(&responses, |operation| matches!(operation, Operation::AddOverlayEllipse { .. })), 2);
+
+		// Moving the pointer reuses the markers rather than creating new ones
+		let mut responses = VecDeque::new();
+		snap_handler.snap_position(&mut responses, DVec2::splat(1000.), &document, DVec2::new(0., 5.));
+		assert_eq!(count_markers(&responses, |operation| matches!(operation, Operation::AddOverlayEllipse { .. })), 0);
+
+		let mut responses = VecDeque::new();
+		snap_handler.cleanup(&mut responses);
+		assert_eq!(count_markers(&responses, |operation| matches!(operation, Operation::DeleteLayer { .. })), 2);
+	}
+
+	#[test]
+	fn positions_snap_onto_line_extensions() {
+		let document = DocumentMessageHandler::default();
+		let mut snap_handler = SnapHandler::default();
+		let viewport_bounds = DVec2::splat(1000.);
+
+		// A diagonal segment, whose extension passes through (200, 200)
+		snap_handler.add_extension_targets(&document, std::iter::once([DVec2::new(10., 10.), DVec2::new(50., 50.)]));
+
+		// A position just off the extension snaps onto it, and the guide is drawn along it
+		let mut responses = VecDeque::new();
+		let snapped = snap_handler.snap_position(&mut responses, viewport_bounds, &document, DVec2::new(202., 200.));
+		assert!(snapped.distance(DVec2::new(201., 201.)) < 1e-10);
+		assert_eq!(count_markers(&responses, |operation| matches!(operation, Operation::AddOverlayLine { .. })), 1);
+
+		// A position beyond the tolerance stays put, and the guide is removed
+		let mut responses = VecDeque::new();
+		let position = DVec2::new(210., 200.);
+		assert_eq!(snap_handler.snap_position(&mut responses, viewport_bounds, &document, position), position);
+		assert_eq!(count_markers(&responses, |operation| matches!(operation, Operation::DeleteLayer { .. })), 1);
 	}
 }
@@ -1,5 +1,6 @@
 use super::tools::*;
 use crate::communication::message_handler::MessageHandler;
+use crate::consts::{AUTO_PAN_MARGIN, AUTO_PAN_SPEED, COLOR_ACCENT, DEFAULT_ANCHOR_SNAP_TOLERANCE, DEFAULT_POINT_SELECTION_THRESHOLD, DEFAULT_SELECTION_TOLERANCE, SELECTION_OUTLINE_WIDTH};
 use crate::document::DocumentMessageHandler;
 use crate::input::InputPreprocessorMessageHandler;
 use crate::layout::widgets::PropertyHolder;
@@ -29,7 +30,7 @@ pub trait Fsm {
 		messages: &mut VecDeque<Message>,
 	) -> Self;
 
-	fn update_hints(&self, responses: &mut VecDeque<Message>);
+	fn update_hints(&self, tool_data: &DocumentToolData, input: &InputPreprocessorMessageHandler, responses: &mut VecDeque<Message>);
 	fn update_cursor(&self, responses: &mut VecDeque<Message>);
 }
 
@@ -37,10 +38,35 @@ pub trait Fsm {
 pub struct DocumentToolData {
 	pub primary_color: Color,
 	pub secondary_color: Color,
+	pub selection_outline_color: Color,
+	pub selection_outline_width: f64,
+	/// Distance in viewport pixels from the edge of the viewport within which dragging pans the canvas
+	pub auto_pan_margin: f64,
+	/// Speed in viewport pixels per second that the canvas pans at when the pointer reaches the edge of the viewport
+	pub auto_pan_speed: f64,
+	/// Whether the Path tool keeps the handles of a smooth point mirrored by default, with Alt splitting them, or leaves them independent with Alt mirroring them
+	pub mirror_handles: bool,
+	/// Whether moving layers with the Select tool snaps them to whole pixels in document space
+	pub pixel_snapping: bool,
+	/// Whether clicking on empty canvas with the Select tool deselects all layers, rather than leaving the selection in place
+	pub deselect_on_empty_click: bool,
+	/// How far in viewport pixels, on every side of the pointer, a click reaches to pick the layers under it
+	pub selection_tolerance: f64,
+	/// Distance in viewport pixels within which a click picks a point on a path
+	pub point_selection_threshold: f64,
+	/// Distance in viewport pixels within which the end of a line being drawn snaps onto an anchor point of another shape
+	pub anchor_snap_tolerance: f64,
 }
 
-pub trait ToolCommon: for<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> + PropertyHolder {}
-impl<T> ToolCommon for T where T: for<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> + PropertyHolder {}
+/// Lets a tool report whether it consumes the pointer input mapped to it, so input it doesn't need can fall through to canvas navigation.
+/// Tools whose state machine starts in a `Ready` state implement it with `consumes_input_unless_ready!`.
+pub trait ConsumesInput {
+	/// A tool consumes input while it's in the middle of an interaction (any state other than `Ready`), and lets it through while ready to start a new one.
+	fn consumes_input(&self) -> bool;
+}
+
+pub trait ToolCommon: for<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> + PropertyHolder + ConsumesInput {}
+impl<T> ToolCommon for T where T: for<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> + PropertyHolder + ConsumesInput {}
 
 type Tool = dyn ToolCommon;
 
@@ -103,6 +129,16 @@ impl Default for ToolFsmState {
 			document_tool_data: DocumentToolData {
 				primary_color: Color::BLACK,
 				secondary_color: Color::WHITE,
+				selection_outline_color: COLOR_ACCENT,
+				selection_outline_width: SELECTION_OUTLINE_WIDTH,
+				auto_pan_margin: AUTO_PAN_MARGIN,
+				auto_pan_speed: AUTO_PAN_SPEED,
+				mirror_handles: true,
+				pixel_snapping: false,
+				deselect_on_empty_click: true,
+				selection_tolerance: DEFAULT_SELECTION_TOLERANCE,
+				point_selection_threshold: DEFAULT_POINT_SELECTION_THRESHOLD,
+				anchor_snap_tolerance: DEFAULT_ANCHOR_SNAP_TOLERANCE,
 			},
 		}
 	}
@@ -200,13 +236,13 @@ pub fn standard_tool_message(tool: ToolType, message_type: StandardToolMessageTy
 			ToolType::BlurSharpen => None, // Some(BlurSharpenMessage::DocumentIsDirty.into()),
 			ToolType::Relight => None,     // Some(RelightMessage::DocumentIsDirty.into()),
 			ToolType::Path => Some(PathMessage::DocumentIsDirty.into()),
-			ToolType::Pen => None,       // Some(PenMessage::DocumentIsDirty.into()),
-			ToolType::Freehand => None,  // Some(FreehandMessage::DocumentIsDirty.into()),
-			ToolType::Spline => None,    // Some(SplineMessage::DocumentIsDirty.into()),
-			ToolType::Line => None,      // Some(LineMessage::DocumentIsDirty.into()),
-			ToolType::Rectangle => None, // Some(RectangleMessage::DocumentIsDirty.into()),
-			ToolType::Ellipse => None,   // Some(EllipseMessage::DocumentIsDirty.into()),
-			ToolType::Shape => None,     // Some(ShapeMessage::DocumentIsDirty.into()),
+			ToolType::Pen => Some(PenMessage::DocumentIsDirty.into()),
+			ToolType::Freehand => None, // Some(FreehandMessage::DocumentIsDirty.into()),
+			ToolType::Spline => None,   // Some(SplineMessage::DocumentIsDirty.into()),
+			ToolType::Line => None,     // Some(LineMessage::DocumentIsDirty.into()),
+			ToolType::Rectangle => Some(RectangleMessage::DocumentIsDirty.into()),
+			ToolType::Ellipse => Some(EllipseMessage::DocumentIsDirty.into()),
+			ToolType::Shape => Some(ShapeMessage::DocumentIsDirty.into()),
 		},
 		StandardToolMessageType::Abort => match tool {
 			ToolType::Select => Some(SelectMessage::Abort.into()),
@@ -233,7 +269,9 @@ pub fn standard_tool_message(tool: ToolType, message_type: StandardToolMessageTy
 			_ => None,
 		},
 		StandardToolMessageType::SelectionChanged => match tool {
+			ToolType::Select => Some(SelectMessage::SelectionChanged.into()),
 			ToolType::Path => Some(PathMessage::SelectionChanged.into()),
+			ToolType::Text => Some(TextMessage::SelectionChanged.into()),
 			_ => None,
 		},
 	}
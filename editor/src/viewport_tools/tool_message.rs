@@ -84,7 +84,16 @@ pub enum ToolMessage {
 	ActivateTool {
 		tool_type: ToolType,
 	},
+	ApplyColorAtPosition {
+		color: Color,
+		position: (f64, f64),
+		apply_to_stroke: bool,
+	},
 	DocumentIsDirty,
+	HandleInput {
+		message: Box<ToolMessage>,
+		fallback: MovementMessage,
+	},
 	ResetColors,
 	SelectionChanged,
 	SelectPrimaryColor {
@@ -93,6 +102,30 @@ pub enum ToolMessage {
 	SelectSecondaryColor {
 		color: Color,
 	},
+	SetAnchorSnapTolerance {
+		tolerance: f64,
+	},
+	SetAutoPan {
+		margin: f64,
+		speed: f64,
+	},
+	SetDeselectOnEmptyClick {
+		enabled: bool,
+	},
+	SetHandleMirroring {
+		enabled: bool,
+	},
+	SetPixelSnapping {
+		enabled: bool,
+	},
+	SetSelectionOutlineStyle {
+		color: Color,
+		width: f64,
+	},
+	SetSelectionTolerance {
+		tolerance: f64,
+		point_threshold: f64,
+	},
 	SwapColors,
 	UpdateCursor,
 	UpdateHints,
@@ -1,4 +1,4 @@
-use super::tool::{message_to_tool_type, standard_tool_message, update_working_colors, StandardToolMessageType, ToolFsmState};
+use super::tool::{message_to_tool_type, standard_tool_message, update_working_colors, ConsumesInput, StandardToolMessageType, ToolFsmState};
 use crate::document::DocumentMessageHandler;
 use crate::input::InputPreprocessorMessageHandler;
 use crate::layout::layout_message::LayoutTarget;
@@ -6,11 +6,21 @@ use crate::message_prelude::*;
 
 use graphene::color::Color;
 
+use glam::DVec2;
 use std::collections::VecDeque;
 
 #[derive(Debug, Default)]
 pub struct ToolMessageHandler {
 	tool_state: ToolFsmState,
+	/// The selection bounds and rotation last sent to the frontend, so they're only sent again once they change
+	selection_bounds: Option<(Option<[DVec2; 2]>, f64)>,
+}
+
+impl ToolMessageHandler {
+	/// Whether the active tool is in the middle of an interaction, rather than ready to start a new one
+	pub fn active_tool_is_busy(&self) -> bool {
+		self.tool_state.tool_data.active_tool().consumes_input()
+	}
 }
 
 impl MessageHandler<ToolMessage, (&DocumentMessageHandler, &InputPreprocessorMessageHandler)> for ToolMessageHandler {
@@ -32,8 +42,10 @@ impl MessageHandler<ToolMessage, (&DocumentMessageHandler, &InputPreprocessorMes
 				let document_data = &self.tool_state.document_tool_data;
 				let old_tool = tool_data.active_tool_type;
 
-				// Do nothing if switching to the same tool
+				// Do nothing if switching to the same tool, besides confirming which tool is active
 				if tool_type == old_tool {
+					let tool_name = tool_type.to_string();
+					responses.push_back(FrontendMessage::UpdateActiveTool { tool_name }.into());
 					return;
 				}
 
@@ -49,12 +61,13 @@ impl MessageHandler<ToolMessage, (&DocumentMessageHandler, &InputPreprocessorMes
 					}
 				};
 				// Send the old and new tools a transition to their FSM Abort states
-				if let Some(tool_message) = standard_tool_message(tool_type, StandardToolMessageType::Abort) {
-					send_abort_to_tool(tool_type, tool_message, true);
-				}
+				// The old tool goes first, since aborting an interaction in progress updates its hints and cursor, which the new tool's must replace
 				if let Some(tool_message) = standard_tool_message(old_tool, StandardToolMessageType::Abort) {
 					send_abort_to_tool(old_tool, tool_message, false);
 				}
+				if let Some(tool_message) = standard_tool_message(tool_type, StandardToolMessageType::Abort) {
+					send_abort_to_tool(tool_type, tool_message, true);
+				}
 
 				// Send the SelectionChanged message to the active tool, this will ensure the selection is updated
 				if let Some(message) = standard_tool_message(tool_type, StandardToolMessageType::SelectionChanged) {
@@ -76,12 +89,42 @@ impl MessageHandler<ToolMessage, (&DocumentMessageHandler, &InputPreprocessorMes
 				// Send Properties to the frontend
 				tool_data.tools.get(&tool_type).unwrap().register_properties(responses, LayoutTarget::ToolOptions);
 			}
+			ApplyColorAtPosition { color, position, apply_to_stroke } => {
+				// The shape under the dropped color is hit with the same tolerance as a click
+				let tolerance = self.tool_state.document_tool_data.selection_tolerance;
+				responses.push_back(
+					DocumentMessage::ApplyColorAtPosition {
+						color,
+						position,
+						apply_to_stroke,
+						tolerance,
+					}
+					.into(),
+				);
+			}
 			DocumentIsDirty => {
 				// Send the DocumentIsDirty message to the active tool's sub-tool message handler
 				let active_tool = self.tool_state.tool_data.active_tool_type;
 				if let Some(message) = standard_tool_message(active_tool, StandardToolMessageType::DocumentIsDirty) {
 					responses.push_back(message.into());
 				}
+
+				// Every transform, nudge, and selection change marks the document as dirty, so this keeps the frontend's selection bounds up to date
+				// The bounds are the same viewport-aligned box as the transformation cage, while the rotation is that of the topmost selected layer in the document
+				let bounds = document.selected_visible_layers_bounding_box();
+				let rotation = document.selected_layers_axis_angle().unwrap_or_default();
+				if self.selection_bounds != Some((bounds, rotation)) {
+					self.selection_bounds = Some((bounds, rotation));
+					responses.push_back(FrontendMessage::UpdateSelectionBounds { bounds, rotation }.into());
+				}
+			}
+			HandleInput { message, fallback } => {
+				// A tool that's ready to start a new interaction lets the input through to canvas navigation, while one in the middle of an interaction keeps it
+				if self.tool_state.tool_data.active_tool().consumes_input() {
+					responses.push_front((*message).into());
+				} else {
+					responses.push_front(fallback.into());
+				}
 			}
 			ResetColors => {
 				let document_data = &mut self.tool_state.document_tool_data;
@@ -109,6 +152,37 @@ impl MessageHandler<ToolMessage, (&DocumentMessageHandler, &InputPreprocessorMes
 
 				update_working_colors(document_data, responses);
 			}
+			SetAnchorSnapTolerance { tolerance } => {
+				self.tool_state.document_tool_data.anchor_snap_tolerance = tolerance;
+			}
+			SetAutoPan { margin, speed } => {
+				let document_data = &mut self.tool_state.document_tool_data;
+				document_data.auto_pan_margin = margin;
+				document_data.auto_pan_speed = speed;
+			}
+			SetDeselectOnEmptyClick { enabled } => {
+				self.tool_state.document_tool_data.deselect_on_empty_click = enabled;
+			}
+			SetHandleMirroring { enabled } => {
+				self.tool_state.document_tool_data.mirror_handles = enabled;
+
+				responses.push_back(ToolMessage::UpdateHints.into());
+			}
+			SetPixelSnapping { enabled } => {
+				self.tool_state.document_tool_data.pixel_snapping = enabled;
+			}
+			SetSelectionOutlineStyle { color, width } => {
+				let document_data = &mut self.tool_state.document_tool_data;
+				document_data.selection_outline_color = color;
+				document_data.selection_outline_width = width;
+
+				responses.push_back(ToolMessage::DocumentIsDirty.into());
+			}
+			SetSelectionTolerance { tolerance, point_threshold } => {
+				let document_data = &mut self.tool_state.document_tool_data;
+				document_data.selection_tolerance = tolerance;
+				document_data.point_selection_threshold = point_threshold;
+			}
 			SwapColors => {
 				let document_data = &mut self.tool_state.document_tool_data;
 
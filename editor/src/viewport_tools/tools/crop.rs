@@ -1,4 +1,3 @@
-use crate::consts::SELECTION_TOLERANCE;
 use crate::document::DocumentMessageHandler;
 use crate::frontend::utility_types::MouseCursorIcon;
 use crate::input::keyboard::{Key, MouseMotion};
@@ -11,6 +10,7 @@ use crate::viewport_tools::tool::{DocumentToolData, Fsm, ToolActionHandlerData};
 
 use graphene::intersection::Quad;
 
+use super::shared::overlay_pool::OverlayPool;
 use super::shared::transformation_cage::*;
 
 use glam::{DVec2, Vec2Swizzles};
@@ -41,10 +41,12 @@ pub enum CropMessage {
 	PointerUp,
 }
 
+consumes_input_unless_ready!(Crop);
+
 impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Crop {
 	fn process_action(&mut self, action: ToolMessage, data: ToolActionHandlerData<'a>, responses: &mut VecDeque<Message>) {
 		if action == ToolMessage::UpdateHints {
-			self.fsm_state.update_hints(responses);
+			self.fsm_state.update_hints(data.1, data.2, responses);
 			return;
 		}
 
@@ -57,7 +59,7 @@ impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Crop {
 
 		if self.fsm_state != new_state {
 			self.fsm_state = new_state;
-			self.fsm_state.update_hints(responses);
+			self.fsm_state.update_hints(data.1, data.2, responses);
 		}
 	}
 
@@ -83,6 +85,7 @@ impl Default for CropToolFsmState {
 #[derive(Clone, Debug, Default)]
 struct CropToolData {
 	bounding_box_overlays: Option<BoundingBoxOverlays>,
+	overlay_pool: OverlayPool,
 	selected_board: Option<LayerId>,
 	snap_handler: SnapHandler,
 	cursor: MouseCursorIcon,
@@ -98,7 +101,7 @@ impl Fsm for CropToolFsmState {
 		self,
 		event: ToolMessage,
 		document: &DocumentMessageHandler,
-		_tool_data: &DocumentToolData,
+		tool_data: &DocumentToolData,
 		data: &mut Self::ToolData,
 		_tool_options: &Self::ToolOptions,
 		input: &InputPreprocessorMessageHandler,
@@ -112,9 +115,9 @@ impl Fsm for CropToolFsmState {
 						data.selected_board.map(|path| document.artboard_bounding_box_and_transform(&[path])).unwrap_or(None),
 						data.bounding_box_overlays.take(),
 					) {
-						(None, Some(bounding_box_overlays)) => bounding_box_overlays.delete(&mut buffer),
+						(None, Some(bounding_box_overlays)) => bounding_box_overlays.delete(&mut data.overlay_pool, &mut buffer),
 						(Some((bounds, transform)), paths) => {
-							let mut bounding_box_overlays = paths.unwrap_or_else(|| BoundingBoxOverlays::new(&mut buffer));
+							let mut bounding_box_overlays = paths.unwrap_or_else(|| BoundingBoxOverlays::new(&mut data.overlay_pool, &mut buffer));
 
 							bounding_box_overlays.bounds = bounds;
 							bounding_box_overlays.transform = transform;
@@ -138,7 +141,7 @@ impl Fsm for CropToolFsmState {
 						let edges = bounding_box.check_selected_edges(input.mouse.position);
 
 						bounding_box.selected_edges = edges.map(|(top, bottom, left, right)| {
-							let edges = SelectedEdges::new(top, bottom, left, right, bounding_box.bounds);
+							let edges = SelectedEdges::new(top, bottom, left, right, bounding_box.bounds, false);
 							bounding_box.pivot = edges.calculate_pivot();
 							edges
 						});
@@ -157,7 +160,7 @@ impl Fsm for CropToolFsmState {
 
 						CropToolFsmState::ResizingBounds
 					} else {
-						let tolerance = DVec2::splat(SELECTION_TOLERANCE);
+						let tolerance = DVec2::splat(tool_data.selection_tolerance);
 						let quad = Quad::from_box([input.mouse.position - tolerance, input.mouse.position + tolerance]);
 						let intersection = document.artboard_message_handler.artboards_graphene_document.intersects_quad_root(quad);
 
@@ -190,14 +193,14 @@ impl Fsm for CropToolFsmState {
 				(CropToolFsmState::ResizingBounds, CropMessage::PointerMove { constrain_axis_or_aspect, center }) => {
 					if let Some(bounds) = &data.bounding_box_overlays {
 						if let Some(movement) = &bounds.selected_edges {
-							let from_center = input.keyboard.get(center as usize);
-							let constrain_square = input.keyboard.get(constrain_axis_or_aspect as usize);
+							let from_center = input.is_pressed(center);
+							let constrain_aspect = input.is_pressed(constrain_axis_or_aspect);
 
 							let mouse_position = input.mouse.position;
 							let snapped_mouse_position = data.snap_handler.snap_position(responses, input.viewport_bounds.size(), document, mouse_position);
 
-							let [position, size] = movement.new_size(snapped_mouse_position, bounds.transform, from_center, constrain_square);
-							let position = movement.center_position(position, size, from_center);
+							let [position, size] = movement.new_artboard_bounds(snapped_mouse_position, bounds.transform, from_center, constrain_aspect);
+							let (position, size) = normalize_bounds(position, size);
 
 							responses.push_back(
 								ArtboardMessage::ResizeArtboard {
@@ -215,7 +218,7 @@ impl Fsm for CropToolFsmState {
 				}
 				(CropToolFsmState::Dragging, CropMessage::PointerMove { constrain_axis_or_aspect, .. }) => {
 					if let Some(bounds) = &data.bounding_box_overlays {
-						let axis_align = input.keyboard.get(constrain_axis_or_aspect as usize);
+						let axis_align = input.is_pressed(constrain_axis_or_aspect);
 
 						let mouse_position = axis_align_drag(axis_align, input.mouse.position, data.drag_start);
 						let mouse_delta = mouse_position - data.drag_current;
@@ -246,22 +249,21 @@ impl Fsm for CropToolFsmState {
 					let mouse_position = input.mouse.position;
 					let snapped_mouse_position = data.snap_handler.snap_position(responses, input.viewport_bounds.size(), document, mouse_position);
 
-					let root_transform = document.graphene_document.root.transform.inverse();
-
 					let mut start = data.drag_start;
 					let mut size = snapped_mouse_position - start;
 					// Constrain axis
-					if input.keyboard.get(constrain_axis_or_aspect as usize) {
+					if input.is_pressed(constrain_axis_or_aspect) {
 						size = size.abs().max(size.abs().yx()) * size.signum();
 					}
 					// From center
-					if input.keyboard.get(center as usize) {
+					if input.is_pressed(center) {
 						start -= size;
 						size *= 2.;
 					}
 
-					let start = root_transform.transform_point2(start);
-					let size = root_transform.transform_vector2(size);
+					let start = document.viewport_to_document(start);
+					let size = document.viewport_to_document_vector(size);
+					let (start, size) = normalize_bounds(start, size);
 
 					responses.push_back(
 						ArtboardMessage::ResizeArtboard {
@@ -302,6 +304,16 @@ impl Fsm for CropToolFsmState {
 						bounds.original_transforms.clear();
 					}
 
+					// A click without a drag, or a drag along only one axis, would leave behind an invisible artboard that's hard to select, so it's removed instead
+					if let Some(id) = data.selected_board {
+						let artboard = document.artboard_message_handler.artboards_graphene_document.layer(&[id]);
+						// Artboard sizes are rounded to whole document pixels, so an empty one has a size of exactly zero
+						if artboard.map_or(false, |artboard| (artboard.transform.matrix2 * DVec2::ONE).abs().min_element() < 1.) {
+							responses.push_back(ArtboardMessage::DeleteArtboard { artboard: id }.into());
+							data.selected_board = None;
+						}
+					}
+
 					responses.push_back(ToolMessage::DocumentIsDirty.into());
 
 					CropToolFsmState::Ready
@@ -315,10 +327,18 @@ impl Fsm for CropToolFsmState {
 
 					CropToolFsmState::Ready
 				}
-				(_, CropMessage::Abort) => {
+				(state, CropMessage::Abort) => {
+					// An artboard still being drawn is discarded rather than left behind half-drawn
+					if state == CropToolFsmState::Drawing {
+						if let Some(id) = data.selected_board.take() {
+							responses.push_back(ArtboardMessage::DeleteArtboard { artboard: id }.into());
+						}
+					}
+
 					if let Some(bounding_box_overlays) = data.bounding_box_overlays.take() {
-						bounding_box_overlays.delete(responses);
+						bounding_box_overlays.delete(&mut data.overlay_pool, responses);
 					}
+					data.overlay_pool.clear(responses);
 
 					data.snap_handler.cleanup(responses);
 					CropToolFsmState::Ready
@@ -330,7 +350,8 @@ impl Fsm for CropToolFsmState {
 		}
 	}
 
-	fn update_hints(&self, responses: &mut VecDeque<Message>) {
+	fn update_hints(&self, _tool_data: &DocumentToolData, input: &InputPreprocessorMessageHandler, responses: &mut VecDeque<Message>) {
+		let bindings = input.modifier_bindings;
 		let hint_data = match self {
 			CropToolFsmState::Ready => HintData(vec![
 				HintGroup(vec![HintInfo {
@@ -347,20 +368,34 @@ impl Fsm for CropToolFsmState {
 				}]),
 			]),
 			CropToolFsmState::Dragging => HintData(vec![HintGroup(vec![HintInfo {
-				key_groups: vec![KeysGroup(vec![Key::KeyShift])],
+				key_groups: vec![KeysGroup(vec![bindings.constrain])],
 				mouse: None,
 				label: String::from("Constrain to Axis"),
 				plus: false,
 			}])]),
-			CropToolFsmState::Drawing | CropToolFsmState::ResizingBounds => HintData(vec![HintGroup(vec![
+			CropToolFsmState::Drawing => HintData(vec![HintGroup(vec![
 				HintInfo {
-					key_groups: vec![KeysGroup(vec![Key::KeyShift])],
+					key_groups: vec![KeysGroup(vec![bindings.constrain])],
 					mouse: None,
 					label: String::from("Constrain Square"),
 					plus: false,
 				},
 				HintInfo {
-					key_groups: vec![KeysGroup(vec![Key::KeyAlt])],
+					key_groups: vec![KeysGroup(vec![bindings.center])],
+					mouse: None,
+					label: String::from("From Center"),
+					plus: false,
+				},
+			])]),
+			CropToolFsmState::ResizingBounds => HintData(vec![HintGroup(vec![
+				HintInfo {
+					key_groups: vec![KeysGroup(vec![bindings.constrain])],
+					mouse: None,
+					label: String::from("Constrain Aspect Ratio"),
+					plus: false,
+				},
+				HintInfo {
+					key_groups: vec![KeysGroup(vec![bindings.center])],
 					mouse: None,
 					label: String::from("From Center"),
 					plus: false,
@@ -375,3 +410,9 @@ impl Fsm for CropToolFsmState {
 		responses.push_back(FrontendMessage::UpdateMouseCursor { cursor: MouseCursorIcon::Default }.into());
 	}
 }
+
+/// Moves the origin of a rectangle with a negative width or height to its opposite side, so its size is positive while it covers the same area.
+/// Artboards drawn or resized up and to the left would otherwise be given negative sizes.
+fn normalize_bounds(position: DVec2, size: DVec2) -> (DVec2, DVec2) {
+	(position + size.min(DVec2::ZERO), size.abs())
+}
@@ -1,49 +1,278 @@
-use super::shared::resize::Resize;
+use super::shared::resize::{handle_under, selected_shape, Resize};
 use crate::consts::DRAG_THRESHOLD;
 use crate::document::DocumentMessageHandler;
 use crate::frontend::utility_types::MouseCursorIcon;
 use crate::input::keyboard::{Key, MouseMotion};
+use crate::input::mouse::ViewportPosition;
 use crate::input::InputPreprocessorMessageHandler;
-use crate::layout::widgets::PropertyHolder;
+use crate::layout::layout_message::LayoutTarget;
+use crate::layout::widgets::{
+	DropdownEntryData, DropdownInput, LayoutRow, NumberInput, OptionalInput, PropertyHolder, Separator, SeparatorDirection, SeparatorType, Widget, WidgetCallback, WidgetHolder, WidgetLayout,
+};
 use crate::message_prelude::*;
 use crate::misc::{HintData, HintGroup, HintInfo, KeysGroup};
 use crate::viewport_tools::tool::{DocumentToolData, Fsm, ToolActionHandlerData};
 
+use graphene::layers::layer_info::LayerDataType;
+use graphene::layers::simple_shape::{ArcType, EllipseArc, Shape};
 use graphene::layers::style;
 use graphene::Operation;
 
-use glam::DAffine2;
+use glam::{DAffine2, DVec2};
 use serde::{Deserialize, Serialize};
+use std::f64::consts::TAU;
 
 #[derive(Default)]
 pub struct Ellipse {
 	fsm_state: EllipseToolFsmState,
 	data: EllipseToolData,
+	options: EllipseOptions,
+}
+
+pub struct EllipseOptions {
+	mode: EllipseMode,
+	/// In degrees, clockwise from pointing right
+	start_angle: f64,
+	/// In degrees, clockwise from pointing right
+	end_angle: f64,
+	line_weight: u32,
+	/// The width divided by the height in document space that drawn ellipses are held to, if their aspect ratio is locked
+	ratio_lock: Option<f64>,
+	/// The aspect ratio shown while it isn't locked, which is used again once it's locked
+	ratio: f64,
+}
+
+impl Default for EllipseOptions {
+	fn default() -> Self {
+		Self {
+			mode: EllipseMode::Full,
+			start_angle: 0.,
+			end_angle: 270.,
+			line_weight: 5,
+			ratio_lock: None,
+			ratio: 1.,
+		}
+	}
+}
+
+impl EllipseOptions {
+	/// The clockwise angle in radians from the start angle to the end angle. Zero means the ellipse is complete.
+	fn sweep_angle(&self) -> f64 {
+		(self.end_angle - self.start_angle).rem_euclid(360.).to_radians()
+	}
+}
+
+/// Whether the Ellipse tool draws a complete ellipse, or only part of one and how its ends are connected.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum EllipseMode {
+	Full,
+	Arc,
+	Pie,
+	Chord,
+}
+
+impl EllipseMode {
+	/// How the ends of the partial ellipse are connected, or `None` for a complete ellipse
+	fn arc_type(self) -> Option<ArcType> {
+		match self {
+			EllipseMode::Full => None,
+			EllipseMode::Arc => Some(ArcType::Open),
+			EllipseMode::Pie => Some(ArcType::PieSlice),
+			EllipseMode::Chord => Some(ArcType::Chord),
+		}
+	}
 }
 
 #[remain::sorted]
 #[impl_message(Message, ToolMessage, Ellipse)]
-#[derive(PartialEq, Clone, Debug, Hash, Serialize, Deserialize)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub enum EllipseMessage {
 	// Standard messages
 	#[remain::unsorted]
 	Abort,
+	#[remain::unsorted]
+	DocumentIsDirty,
 
 	// Tool-specific messages
+	AutoPan,
 	DragStart,
 	DragStop,
 	Resize {
+		adjust_end_angle: Key,
 		center: Key,
 		lock_ratio: Key,
+		rotate: Key,
+		snap_angle: Key,
 	},
+	/// Sets the width and height in document units of the selected ellipse, keeping the other if it's `None`
+	SetDimensions {
+		width: Option<f64>,
+		height: Option<f64>,
+	},
+	UpdateOptions(EllipseOptionsUpdate),
+}
+
+#[remain::sorted]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub enum EllipseOptionsUpdate {
+	EndAngle(f64),
+	LineWeight(u32),
+	LockRatio(bool),
+	Mode(EllipseMode),
+	Ratio(f64),
+	StartAngle(f64),
+}
+
+impl PropertyHolder for Ellipse {
+	fn properties(&self) -> WidgetLayout {
+		let mut widgets = vec![WidgetHolder::new(Widget::DropdownInput(DropdownInput {
+			entries: vec![vec![
+				DropdownEntryData {
+					value: "full".into(),
+					label: "Full".into(),
+					on_update: WidgetCallback::new(|_| EllipseMessage::UpdateOptions(EllipseOptionsUpdate::Mode(EllipseMode::Full)).into()),
+				},
+				DropdownEntryData {
+					value: "arc".into(),
+					label: "Arc".into(),
+					on_update: WidgetCallback::new(|_| EllipseMessage::UpdateOptions(EllipseOptionsUpdate::Mode(EllipseMode::Arc)).into()),
+				},
+				DropdownEntryData {
+					value: "pie".into(),
+					label: "Pie".into(),
+					on_update: WidgetCallback::new(|_| EllipseMessage::UpdateOptions(EllipseOptionsUpdate::Mode(EllipseMode::Pie)).into()),
+				},
+				DropdownEntryData {
+					value: "chord".into(),
+					label: "Chord".into(),
+					on_update: WidgetCallback::new(|_| EllipseMessage::UpdateOptions(EllipseOptionsUpdate::Mode(EllipseMode::Chord)).into()),
+				},
+			]],
+			selected_index: match self.options.mode {
+				EllipseMode::Full => 0,
+				EllipseMode::Arc => 1,
+				EllipseMode::Pie => 2,
+				EllipseMode::Chord => 3,
+			},
+		}))];
+
+		// A complete ellipse has no angles to set
+		if self.options.mode != EllipseMode::Full {
+			widgets.extend([
+				WidgetHolder::new(Widget::Separator(Separator {
+					separator_type: SeparatorType::Related,
+					direction: SeparatorDirection::Horizontal,
+				})),
+				WidgetHolder::new(Widget::NumberInput(NumberInput {
+					unit: "°".into(),
+					label: "Start".into(),
+					value: self.options.start_angle,
+					min: Some(0.),
+					max: Some(360.),
+					on_update: WidgetCallback::new(|number_input| EllipseMessage::UpdateOptions(EllipseOptionsUpdate::StartAngle(number_input.value)).into()),
+					..NumberInput::default()
+				})),
+				WidgetHolder::new(Widget::Separator(Separator {
+					separator_type: SeparatorType::Related,
+					direction: SeparatorDirection::Horizontal,
+				})),
+				WidgetHolder::new(Widget::NumberInput(NumberInput {
+					unit: "°".into(),
+					label: "End".into(),
+					value: self.options.end_angle,
+					min: Some(0.),
+					max: Some(360.),
+					on_update: WidgetCallback::new(|number_input| EllipseMessage::UpdateOptions(EllipseOptionsUpdate::EndAngle(number_input.value)).into()),
+					..NumberInput::default()
+				})),
+			]);
+
+			// Open arcs have no area to fill, so they are drawn with a stroke instead
+			if self.options.mode == EllipseMode::Arc {
+				widgets.push(WidgetHolder::new(Widget::Separator(Separator {
+					separator_type: SeparatorType::Unrelated,
+					direction: SeparatorDirection::Horizontal,
+				})));
+				widgets.push(WidgetHolder::new(Widget::NumberInput(NumberInput {
+					unit: " px".into(),
+					label: "Weight".into(),
+					value: self.options.line_weight as f64,
+					is_integer: true,
+					min: Some(0.),
+					on_update: WidgetCallback::new(|number_input| EllipseMessage::UpdateOptions(EllipseOptionsUpdate::LineWeight(number_input.value as u32)).into()),
+					..NumberInput::default()
+				})));
+			}
+		}
+
+		widgets.push(WidgetHolder::new(Widget::Separator(Separator {
+			separator_type: SeparatorType::Unrelated,
+			direction: SeparatorDirection::Horizontal,
+		})));
+		widgets.push(WidgetHolder::new(Widget::OptionalInput(OptionalInput {
+			checked: self.options.ratio_lock.is_some(),
+			icon: "Link".into(),
+			tooltip: "Lock Aspect Ratio".into(),
+			on_update: WidgetCallback::new(|optional_input| EllipseMessage::UpdateOptions(EllipseOptionsUpdate::LockRatio(optional_input.checked)).into()),
+		})));
+		widgets.push(WidgetHolder::new(Widget::NumberInput(NumberInput {
+			label: "W/H".into(),
+			value: self.options.ratio,
+			min: Some(0.),
+			on_update: WidgetCallback::new(|number_input| EllipseMessage::UpdateOptions(EllipseOptionsUpdate::Ratio(number_input.value)).into()),
+			..NumberInput::default()
+		})));
+
+		// The selected ellipse, including one being drawn, can be given an exact size, and the inputs follow along while dragging
+		if let Some(size) = self.data.dimensions {
+			widgets.push(WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Unrelated,
+				direction: SeparatorDirection::Horizontal,
+			})));
+			widgets.push(WidgetHolder::new(Widget::NumberInput(NumberInput {
+				unit: " px".into(),
+				label: "W".into(),
+				value: size.x,
+				min: Some(0.),
+				on_update: WidgetCallback::new(|number_input| {
+					EllipseMessage::SetDimensions {
+						width: Some(number_input.value),
+						height: None,
+					}
+					.into()
+				}),
+				..NumberInput::default()
+			})));
+			widgets.push(WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Related,
+				direction: SeparatorDirection::Horizontal,
+			})));
+			widgets.push(WidgetHolder::new(Widget::NumberInput(NumberInput {
+				unit: " px".into(),
+				label: "H".into(),
+				value: size.y,
+				min: Some(0.),
+				on_update: WidgetCallback::new(|number_input| {
+					EllipseMessage::SetDimensions {
+						width: None,
+						height: Some(number_input.value),
+					}
+					.into()
+				}),
+				..NumberInput::default()
+			})));
+		}
+
+		WidgetLayout::new(vec![LayoutRow::Row { name: "".into(), widgets }])
+	}
 }
 
-impl PropertyHolder for Ellipse {}
+consumes_input_unless_ready!(Ellipse);
 
 impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Ellipse {
 	fn process_action(&mut self, action: ToolMessage, data: ToolActionHandlerData<'a>, responses: &mut VecDeque<Message>) {
 		if action == ToolMessage::UpdateHints {
-			self.fsm_state.update_hints(responses);
+			self.fsm_state.update_hints(data.1, data.2, responses);
 			return;
 		}
 
@@ -52,11 +281,43 @@ impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Ellipse {
 			return;
 		}
 
-		let new_state = self.fsm_state.transition(action, data.0, data.1, &mut self.data, &(), data.2, responses);
+		if let ToolMessage::Ellipse(EllipseMessage::UpdateOptions(action)) = action {
+			match action {
+				EllipseOptionsUpdate::EndAngle(end_angle) => {
+					self.options.end_angle = end_angle;
+					update_selected_arcs(data.0, &self.options, responses);
+				}
+				EllipseOptionsUpdate::LineWeight(line_weight) => self.options.line_weight = line_weight,
+				EllipseOptionsUpdate::LockRatio(locked) => self.options.ratio_lock = locked.then(|| self.options.ratio),
+				EllipseOptionsUpdate::Mode(mode) => {
+					self.options.mode = mode;
+					self.register_properties(responses, LayoutTarget::ToolOptions);
+				}
+				EllipseOptionsUpdate::Ratio(ratio) => {
+					self.options.ratio = ratio;
+					if self.options.ratio_lock.is_some() {
+						self.options.ratio_lock = Some(ratio);
+					}
+				}
+				EllipseOptionsUpdate::StartAngle(start_angle) => {
+					self.options.start_angle = start_angle;
+					update_selected_arcs(data.0, &self.options, responses);
+				}
+			}
+			return;
+		}
+
+		let dimensions = self.data.dimensions;
+		let new_state = self.fsm_state.transition(action, data.0, data.1, &mut self.data, &self.options, data.2, responses);
+
+		// Keep the width and height inputs in step with the selected ellipse
+		if self.data.dimensions != dimensions {
+			self.register_properties(responses, LayoutTarget::ToolOptions);
+		}
 
 		if self.fsm_state != new_state {
 			self.fsm_state = new_state;
-			self.fsm_state.update_hints(responses);
+			self.fsm_state.update_hints(data.1, data.2, responses);
 			self.fsm_state.update_cursor(responses);
 		}
 	}
@@ -66,15 +327,87 @@ impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Ellipse {
 
 		match self.fsm_state {
 			Ready => actions!(EllipseMessageDiscriminant; DragStart),
-			Drawing => actions!(EllipseMessageDiscriminant; DragStop, Abort, Resize),
+			Drawing => actions!(EllipseMessageDiscriminant; AutoPan, DragStop, Abort, Resize),
+			DraggingAngle => actions!(EllipseMessageDiscriminant; DragStop, Abort, Resize),
 		}
 	}
 }
 
+/// Sets the angles of the selected arcs, so editing the tool options shows its effect on existing layers.
+fn update_selected_arcs(document: &DocumentMessageHandler, options: &EllipseOptions, responses: &mut VecDeque<Message>) {
+	let arcs: Vec<_> = document
+		.selected_layers()
+		.filter(|path| matches!(document.graphene_document.layer(path).map(|layer| &layer.data), Ok(LayerDataType::Shape(shape)) if shape.arc().is_some()))
+		.map(|path| path.to_vec())
+		.collect();
+	if arcs.is_empty() {
+		return;
+	}
+
+	let (start_angle, sweep_angle) = (options.start_angle.to_radians(), options.sweep_angle());
+	responses.push_back(DocumentMessage::StartTransaction.into());
+	for path in arcs {
+		responses.push_back(Operation::SetEllipseArcAngles { path, start_angle, sweep_angle }.into());
+	}
+	responses.push_back(DocumentMessage::CommitTransaction.into());
+}
+
+/// The path and document space transform of the single selected layer, if it's an ellipse or part of one.
+fn selected_ellipse(document: &DocumentMessageHandler) -> Option<(Vec<LayerId>, DAffine2)> {
+	let mut selected = document.selected_visible_layers();
+	let path = selected.next().filter(|_| selected.next().is_none())?;
+
+	match &document.graphene_document.layer(path).ok()?.data {
+		LayerDataType::Shape(shape) if shape.arc().is_some() || shape.path == Shape::ellipse_path() => {}
+		_ => return None,
+	}
+	let transform = document.graphene_document.root.transform.inverse() * document.graphene_document.multiply_transforms(path).ok()?;
+	Some((path.to_vec(), transform))
+}
+
+/// The arc shape of the single selected layer, along with its path and its transform to the viewport.
+fn selected_arc(document: &DocumentMessageHandler) -> Option<(Vec<LayerId>, EllipseArc, DAffine2)> {
+	let (path, shape, transform) = selected_shape(document)?;
+	shape.arc().map(|arc| (path.to_vec(), arc, transform))
+}
+
+fn layer_arc(document: &DocumentMessageHandler, path: &[LayerId]) -> Option<EllipseArc> {
+	match document.graphene_document.layer(path).map(|layer| &layer.data) {
+		Ok(LayerDataType::Shape(shape)) => shape.arc(),
+		_ => None,
+	}
+}
+
+/// The viewport positions of the start and end of an arc, which is drawn around the unit square its layer transform maps to the viewport.
+fn arc_end_positions(arc: EllipseArc, transform: DAffine2) -> [DVec2; 2] {
+	let on_ellipse = |angle: f64| transform.transform_point2(DVec2::splat(0.5) + 0.5 * DVec2::new(angle.cos(), angle.sin()));
+	[on_ellipse(arc.start_angle), on_ellipse(arc.start_angle + arc.sweep_angle)]
+}
+
+/// Finds the handle of the selected arc under the pointer, if any, preferring the end so an arc with both ends together can be opened up.
+fn arc_handle_under(document: &DocumentMessageHandler, position: ViewportPosition) -> Option<(Vec<LayerId>, ArcEnd)> {
+	let (path, arc, transform) = selected_arc(document)?;
+	let [start, end] = arc_end_positions(arc, transform);
+
+	match handle_under(&[end, start], position)? {
+		0 => Some((path, ArcEnd::End)),
+		_ => Some((path, ArcEnd::Start)),
+	}
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum EllipseToolFsmState {
 	Ready,
 	Drawing,
+	/// Dragging the handle on one end of the selected arc to change its angle
+	DraggingAngle,
+}
+
+/// The ends of an arc, which each have a handle while it's selected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ArcEnd {
+	Start,
+	End,
 }
 
 impl Default for EllipseToolFsmState {
@@ -86,11 +419,18 @@ impl Default for EllipseToolFsmState {
 #[derive(Clone, Debug, Default)]
 struct EllipseToolData {
 	data: Resize,
+	/// How the ends of the ellipse being drawn are connected, or `None` if it's complete
+	arc_type: Option<ArcType>,
+	start_angle: f64,
+	/// The arc whose angle is being changed, and which of its ends is being dragged
+	dragging: Option<(Vec<LayerId>, ArcEnd)>,
+	/// The width and height in document units of the selected ellipse, which can be given an exact size
+	dimensions: Option<DVec2>,
 }
 
 impl Fsm for EllipseToolFsmState {
 	type ToolData = EllipseToolData;
-	type ToolOptions = ();
+	type ToolOptions = EllipseOptions;
 
 	fn transition(
 		self,
@@ -98,7 +438,7 @@ impl Fsm for EllipseToolFsmState {
 		document: &DocumentMessageHandler,
 		tool_data: &DocumentToolData,
 		data: &mut Self::ToolData,
-		_tool_options: &Self::ToolOptions,
+		tool_options: &Self::ToolOptions,
 		input: &InputPreprocessorMessageHandler,
 		responses: &mut VecDeque<Message>,
 	) -> Self {
@@ -109,31 +449,172 @@ impl Fsm for EllipseToolFsmState {
 
 		if let ToolMessage::Ellipse(event) = event {
 			match (self, event) {
+				(_, DocumentIsDirty) => {
+					// The handles sit on the start and end of the selected arc
+					if self != Drawing {
+						let handle_positions = selected_arc(document).map_or(Vec::new(), |(_, arc, transform)| arc_end_positions(arc, transform).to_vec());
+						shape_data.update_handles(&handle_positions, responses);
+					}
+					data.dimensions = selected_ellipse(document).map(|(_, transform)| DVec2::new(transform.matrix2.x_axis.length(), transform.matrix2.y_axis.length()));
+
+					self
+				}
 				(Ready, DragStart) => {
+					// Grabbing a handle of the selected arc changes its angle instead of drawing a new ellipse
+					if let Some((path, end)) = arc_handle_under(document, input.mouse.position) {
+						responses.push_back(DocumentMessage::StartTransaction.into());
+						data.dragging = Some((path, end));
+
+						return DraggingAngle;
+					}
+					shape_data.hide_handles(responses);
+
 					shape_data.start(responses, input.viewport_bounds.size(), document, input.mouse.position);
+					shape_data.ratio_lock = tool_options.ratio_lock;
 					responses.push_back(DocumentMessage::StartTransaction.into());
 					shape_data.path = Some(document.get_path_for_new_layer());
 					responses.push_back(DocumentMessage::DeselectAllLayers.into());
+					data.arc_type = tool_options.mode.arc_type();
+					data.start_angle = tool_options.start_angle.to_radians();
 
-					responses.push_back(
-						Operation::AddEllipse {
-							path: shape_data.path.clone().unwrap(),
+					let path = shape_data.path.clone().unwrap();
+					let transform = DAffine2::ZERO.to_cols_array();
+					let operation = match data.arc_type {
+						None => Operation::AddEllipse {
+							path,
 							insert_index: -1,
-							transform: DAffine2::ZERO.to_cols_array(),
+							transform,
 							style: style::PathStyle::new(None, Some(style::Fill::new(tool_data.primary_color))),
-						}
-						.into(),
-					);
+						},
+						Some(arc_type) => Operation::AddArc {
+							path,
+							insert_index: -1,
+							transform,
+							arc_type,
+							start_angle: data.start_angle,
+							sweep_angle: tool_options.sweep_angle(),
+							style: match arc_type {
+								ArcType::Open => style::PathStyle::new(Some(style::Stroke::new(tool_data.primary_color, tool_options.line_weight as f32)), None),
+								_ => style::PathStyle::new(None, Some(style::Fill::new(tool_data.primary_color))),
+							},
+						},
+					};
+					responses.push_back(operation.into());
 
 					Drawing
 				}
-				(state, Resize { center, lock_ratio }) => {
-					if let Some(message) = shape_data.calculate_transform(responses, input.viewport_bounds.size(), document, center, lock_ratio, input) {
+				(DraggingAngle, Resize { snap_angle, .. }) => {
+					if let Some((path, end)) = &data.dragging {
+						let arc = layer_arc(document, path);
+						let transform = document
+							.graphene_document
+							.generate_transform_relative_to_viewport(path)
+							.ok()
+							.filter(|transform| transform.matrix2.determinant() != 0.);
+						if let (Some(arc), Some(transform)) = (arc, transform) {
+							// The angle the pointer makes with the center of the ellipse, in the unit square it's drawn in
+							let pointer = transform.inverse().transform_point2(input.mouse.position) - DVec2::splat(0.5);
+							let mut angle = pointer.y.atan2(pointer.x);
+							if input.is_pressed(snap_angle) {
+								let snap_resolution = input.rotate_snap_angle.to_radians();
+								angle = (angle / snap_resolution).round() * snap_resolution;
+							}
+
+							// The other end of the arc stays put
+							let (start_angle, sweep_angle) = match end {
+								ArcEnd::Start => (angle, (arc.start_angle + arc.sweep_angle - angle).rem_euclid(TAU)),
+								ArcEnd::End => (arc.start_angle, (angle - arc.start_angle).rem_euclid(TAU)),
+							};
+							responses.push_back(
+								Operation::SetEllipseArcAngles {
+									path: path.clone(),
+									start_angle,
+									sweep_angle,
+								}
+								.into(),
+							);
+						}
+					}
+
+					DraggingAngle
+				}
+				(DraggingAngle, DragStop) => {
+					responses.push_back(DocumentMessage::CommitTransaction.into());
+					data.dragging = None;
+
+					Ready
+				}
+				(DraggingAngle, Abort) => {
+					responses.push_back(DocumentMessage::AbortTransaction.into());
+					data.dragging = None;
+
+					Ready
+				}
+				(Ready, SetDimensions { width, height }) => {
+					if let Some((path, transform)) = selected_ellipse(document) {
+						let size = DVec2::new(transform.matrix2.x_axis.length(), transform.matrix2.y_axis.length());
+						let new_size = DVec2::new(width.unwrap_or(size.x), height.unwrap_or(size.y));
+
+						// A flattened ellipse has lost the direction of its zero-length side, so there's nothing to scale
+						if size.x != 0. && size.y != 0. {
+							// The ellipse grows away from the corner of its bounds that's at the top left when it isn't rotated or flipped
+							let flipped = |flipped: bool| if flipped { 1. } else { 0. };
+							let corner = DVec2::new(flipped(transform.matrix2.x_axis.x < 0.), flipped(transform.matrix2.y_axis.y < 0.));
+							let scale = DAffine2::from_translation(corner) * DAffine2::from_scale(new_size / size) * DAffine2::from_translation(-corner);
+							let transform = (document.graphene_document.root.transform * transform * scale).to_cols_array();
+
+							responses.push_back(DocumentMessage::StartTransaction.into());
+							responses.push_back(Operation::SetLayerTransformInViewport { path, transform }.into());
+							responses.push_back(DocumentMessage::CommitTransaction.into());
+						}
+					}
+
+					Ready
+				}
+				(Ready, Abort) => {
+					shape_data.clear_handles(responses);
+
+					Ready
+				}
+				(
+					state,
+					Resize {
+						adjust_end_angle,
+						center,
+						lock_ratio,
+						rotate,
+						snap_angle,
+					},
+				) => {
+					// The end angle key also snaps the rotation, which takes precedence while rotating
+					if data.arc_type.is_some() && input.is_pressed(adjust_end_angle) && !input.is_pressed(rotate) {
+						// Keep the size and instead end the arc at the angle the pointer makes with the center of the ellipse
+						let transform = shape_data.path.as_ref().and_then(|path| document.graphene_document.generate_transform_relative_to_viewport(path).ok());
+						if let (Some(path), Some(transform)) = (&shape_data.path, transform.filter(|transform| transform.matrix2.determinant() != 0.)) {
+							let pointer = transform.inverse().transform_point2(input.mouse.position) - DVec2::splat(0.5);
+							let sweep_angle = (pointer.y.atan2(pointer.x) - data.start_angle).rem_euclid(TAU);
+							let start_angle = data.start_angle;
+
+							responses.push_back(
+								Operation::SetEllipseArcAngles {
+									path: path.clone(),
+									start_angle,
+									sweep_angle,
+								}
+								.into(),
+							);
+						}
+					} else if let Some(message) = shape_data.calculate_transform(responses, input.viewport_bounds.size(), document, center, lock_ratio, rotate, snap_angle, input) {
 						responses.push_back(message);
 					}
 
 					state
 				}
+				(Drawing, AutoPan) => {
+					shape_data.auto_pan(tool_data, input, responses);
+
+					Drawing
+				}
 				(Drawing, DragStop) => {
 					match shape_data.drag_start.distance(input.mouse.position) <= DRAG_THRESHOLD {
 						true => responses.push_back(DocumentMessage::AbortTransaction.into()),
@@ -141,6 +622,8 @@ impl Fsm for EllipseToolFsmState {
 					}
 
 					shape_data.cleanup(responses);
+					// Shows the handles on the new arc once it's committed
+					responses.push_back(ToolMessage::DocumentIsDirty.into());
 					Ready
 				}
 				(Drawing, Abort) => {
@@ -156,7 +639,8 @@ impl Fsm for EllipseToolFsmState {
 		}
 	}
 
-	fn update_hints(&self, responses: &mut VecDeque<Message>) {
+	fn update_hints(&self, _tool_data: &DocumentToolData, input: &InputPreprocessorMessageHandler, responses: &mut VecDeque<Message>) {
+		let bindings = input.modifier_bindings;
 		let hint_data = match self {
 			EllipseToolFsmState::Ready => HintData(vec![HintGroup(vec![
 				HintInfo {
@@ -166,31 +650,55 @@ impl Fsm for EllipseToolFsmState {
 					plus: false,
 				},
 				HintInfo {
-					key_groups: vec![KeysGroup(vec![Key::KeyShift])],
+					key_groups: vec![KeysGroup(vec![bindings.constrain])],
 					mouse: None,
 					label: String::from("Constrain Circular"),
 					plus: true,
 				},
 				HintInfo {
-					key_groups: vec![KeysGroup(vec![Key::KeyAlt])],
+					key_groups: vec![KeysGroup(vec![bindings.center])],
 					mouse: None,
 					label: String::from("From Center"),
 					plus: true,
 				},
 			])]),
+			EllipseToolFsmState::DraggingAngle => HintData(vec![HintGroup(vec![HintInfo {
+				key_groups: vec![KeysGroup(vec![Key::KeyPrimaryModifier])],
+				mouse: None,
+				label: String::from("Snap Angle"),
+				plus: false,
+			}])]),
 			EllipseToolFsmState::Drawing => HintData(vec![HintGroup(vec![
 				HintInfo {
-					key_groups: vec![KeysGroup(vec![Key::KeyShift])],
+					key_groups: vec![KeysGroup(vec![bindings.constrain])],
 					mouse: None,
 					label: String::from("Constrain Circular"),
 					plus: false,
 				},
 				HintInfo {
-					key_groups: vec![KeysGroup(vec![Key::KeyAlt])],
+					key_groups: vec![KeysGroup(vec![bindings.center])],
 					mouse: None,
 					label: String::from("From Center"),
 					plus: false,
 				},
+				HintInfo {
+					key_groups: vec![KeysGroup(vec![Key::KeyR])],
+					mouse: None,
+					label: String::from("Rotate"),
+					plus: false,
+				},
+				HintInfo {
+					key_groups: vec![KeysGroup(vec![Key::KeyPrimaryModifier, Key::KeyR])],
+					mouse: None,
+					label: String::from("Rotate by Snap Angle"),
+					plus: false,
+				},
+				HintInfo {
+					key_groups: vec![KeysGroup(vec![Key::KeyPrimaryModifier])],
+					mouse: None,
+					label: String::from("Adjust End Angle"),
+					plus: false,
+				},
 			])]),
 		};
 
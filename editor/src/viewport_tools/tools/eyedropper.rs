@@ -1,4 +1,3 @@
-use crate::consts::SELECTION_TOLERANCE;
 use crate::document::DocumentMessageHandler;
 use crate::frontend::utility_types::MouseCursorIcon;
 use crate::input::keyboard::MouseMotion;
@@ -35,10 +34,12 @@ pub enum EyedropperMessage {
 
 impl PropertyHolder for Eyedropper {}
 
+consumes_input_unless_ready!(Eyedropper);
+
 impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Eyedropper {
 	fn process_action(&mut self, action: ToolMessage, data: ToolActionHandlerData<'a>, responses: &mut VecDeque<Message>) {
 		if action == ToolMessage::UpdateHints {
-			self.fsm_state.update_hints(responses);
+			self.fsm_state.update_hints(data.1, data.2, responses);
 			return;
 		}
 
@@ -51,7 +52,7 @@ impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Eyedropper {
 
 		if self.fsm_state != new_state {
 			self.fsm_state = new_state;
-			self.fsm_state.update_hints(responses);
+			self.fsm_state.update_hints(data.1, data.2, responses);
 			self.fsm_state.update_cursor(responses);
 		}
 	}
@@ -81,7 +82,7 @@ impl Fsm for EyedropperToolFsmState {
 		self,
 		event: ToolMessage,
 		document: &DocumentMessageHandler,
-		_tool_data: &DocumentToolData,
+		tool_data: &DocumentToolData,
 		_data: &mut Self::ToolData,
 		_tool_options: &Self::ToolOptions,
 		input: &InputPreprocessorMessageHandler,
@@ -94,7 +95,7 @@ impl Fsm for EyedropperToolFsmState {
 			match (self, event) {
 				(Ready, lmb_or_rmb) if lmb_or_rmb == LeftMouseDown || lmb_or_rmb == RightMouseDown => {
 					let mouse_pos = input.mouse.position;
-					let tolerance = DVec2::splat(SELECTION_TOLERANCE);
+					let tolerance = DVec2::splat(tool_data.selection_tolerance);
 					let quad = Quad::from_box([mouse_pos - tolerance, mouse_pos + tolerance]);
 
 					// TODO: Destroy this pyramid
@@ -121,7 +122,7 @@ impl Fsm for EyedropperToolFsmState {
 		}
 	}
 
-	fn update_hints(&self, responses: &mut VecDeque<Message>) {
+	fn update_hints(&self, _tool_data: &DocumentToolData, _input: &InputPreprocessorMessageHandler, responses: &mut VecDeque<Message>) {
 		let hint_data = match self {
 			EyedropperToolFsmState::Ready => HintData(vec![HintGroup(vec![
 				HintInfo {
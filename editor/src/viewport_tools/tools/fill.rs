@@ -1,44 +1,145 @@
-use crate::consts::SELECTION_TOLERANCE;
+use crate::consts::DRAG_THRESHOLD;
 use crate::document::DocumentMessageHandler;
 use crate::frontend::utility_types::MouseCursorIcon;
-use crate::input::keyboard::MouseMotion;
+use crate::input::keyboard::{Key, MouseMotion};
 use crate::input::InputPreprocessorMessageHandler;
-use crate::layout::widgets::PropertyHolder;
+use crate::layout::layout_message::LayoutTarget;
+use crate::layout::widgets::{LayoutRow, NumberInput, PropertyHolder, RadioEntryData, RadioInput, Separator, SeparatorDirection, SeparatorType, Widget, WidgetCallback, WidgetHolder, WidgetLayout};
 use crate::message_prelude::*;
-use crate::misc::{HintData, HintGroup, HintInfo};
+use crate::misc::{HintData, HintGroup, HintInfo, KeysGroup};
 use crate::viewport_tools::tool::{DocumentToolData, Fsm, ToolActionHandlerData};
 
+use graphene::color::Color;
 use graphene::intersection::Quad;
+use graphene::layers::style::{Gradient, GradientType};
 use graphene::Operation;
 
-use glam::DVec2;
+use glam::{DAffine2, DVec2};
 use serde::{Deserialize, Serialize};
 
 #[derive(Default)]
 pub struct Fill {
 	fsm_state: FillToolFsmState,
 	data: FillToolData,
+	options: FillOptions,
+}
+
+/// A gradient always blends from the clicked working color to the other one, so its only stops are placed by the options rather than picked
+pub struct FillOptions {
+	/// Fills with a solid color when there's no gradient type
+	gradient_type: Option<GradientType>,
+	/// Where the clicked and the other working color sit along a gradient, from 0 to 1
+	stops: [f64; 2],
+}
+
+impl Default for FillOptions {
+	fn default() -> Self {
+		Self { gradient_type: None, stops: [0., 1.] }
+	}
 }
 
 #[remain::sorted]
 #[impl_message(Message, ToolMessage, Fill)]
-#[derive(PartialEq, Clone, Debug, Hash, Serialize, Deserialize)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub enum FillMessage {
 	// Standard messages
 	#[remain::unsorted]
 	Abort,
 
 	// Tool-specific messages
+	DragStop,
 	LeftMouseDown,
+	PointerMove,
 	RightMouseDown,
+	UpdateOptions(FillOptionsUpdate),
 }
 
-impl PropertyHolder for Fill {}
+#[remain::sorted]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub enum FillOptionsUpdate {
+	/// Where the other working color sits along a gradient, from 0 to 1
+	EndStop(f64),
+	/// Fills with a solid color when there's no gradient type
+	GradientType(Option<GradientType>),
+	/// Where the clicked working color sits along a gradient, from 0 to 1
+	StartStop(f64),
+}
+
+impl PropertyHolder for Fill {
+	fn properties(&self) -> WidgetLayout {
+		let mut widgets = vec![WidgetHolder::new(Widget::RadioInput(RadioInput {
+			entries: vec![
+				RadioEntryData {
+					value: "solid".into(),
+					label: "Solid".into(),
+					tooltip: "Fill with a Solid Color".into(),
+					on_update: WidgetCallback::new(|_| FillMessage::UpdateOptions(FillOptionsUpdate::GradientType(None)).into()),
+					..RadioEntryData::default()
+				},
+				RadioEntryData {
+					value: "linear".into(),
+					label: "Linear".into(),
+					tooltip: "Fill with a Linear Gradient".into(),
+					on_update: WidgetCallback::new(|_| FillMessage::UpdateOptions(FillOptionsUpdate::GradientType(Some(GradientType::Linear))).into()),
+					..RadioEntryData::default()
+				},
+				RadioEntryData {
+					value: "radial".into(),
+					label: "Radial".into(),
+					tooltip: "Fill with a Radial Gradient".into(),
+					on_update: WidgetCallback::new(|_| FillMessage::UpdateOptions(FillOptionsUpdate::GradientType(Some(GradientType::Radial))).into()),
+					..RadioEntryData::default()
+				},
+			],
+			selected_index: match self.options.gradient_type {
+				None => 0,
+				Some(GradientType::Linear) => 1,
+				Some(GradientType::Radial) => 2,
+			},
+		}))];
+
+		// The working colors are only placed along a gradient
+		if self.options.gradient_type.is_some() {
+			widgets.extend([
+				WidgetHolder::new(Widget::Separator(Separator {
+					separator_type: SeparatorType::Unrelated,
+					direction: SeparatorDirection::Horizontal,
+				})),
+				WidgetHolder::new(Widget::NumberInput(NumberInput {
+					unit: "%".into(),
+					label: "Clicked Color".into(),
+					value: self.options.stops[0] * 100.,
+					min: Some(0.),
+					max: Some(100.),
+					on_update: WidgetCallback::new(|number_input| FillMessage::UpdateOptions(FillOptionsUpdate::StartStop(number_input.value / 100.)).into()),
+					..NumberInput::default()
+				})),
+				WidgetHolder::new(Widget::Separator(Separator {
+					separator_type: SeparatorType::Related,
+					direction: SeparatorDirection::Horizontal,
+				})),
+				WidgetHolder::new(Widget::NumberInput(NumberInput {
+					unit: "%".into(),
+					label: "Other Color".into(),
+					value: self.options.stops[1] * 100.,
+					min: Some(0.),
+					max: Some(100.),
+					on_update: WidgetCallback::new(|number_input| FillMessage::UpdateOptions(FillOptionsUpdate::EndStop(number_input.value / 100.)).into()),
+					..NumberInput::default()
+				})),
+			]);
+		}
+
+		WidgetLayout::new(vec![LayoutRow::Row { name: "".into(), widgets }])
+	}
+}
+
+consumes_input_unless_ready!(Fill);
 
 impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Fill {
 	fn process_action(&mut self, action: ToolMessage, data: ToolActionHandlerData<'a>, responses: &mut VecDeque<Message>) {
 		if action == ToolMessage::UpdateHints {
-			self.fsm_state.update_hints(responses);
+			self.fsm_state.update_hints(data.1, data.2, responses);
 			return;
 		}
 
@@ -47,21 +148,43 @@ impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Fill {
 			return;
 		}
 
-		let new_state = self.fsm_state.transition(action, data.0, data.1, &mut self.data, &(), data.2, responses);
+		if let ToolMessage::Fill(FillMessage::UpdateOptions(action)) = action {
+			match action {
+				FillOptionsUpdate::EndStop(offset) => self.options.stops[1] = offset,
+				FillOptionsUpdate::GradientType(gradient_type) => {
+					self.options.gradient_type = gradient_type;
+					// The stop inputs only appear for gradients
+					self.register_properties(responses, LayoutTarget::ToolOptions);
+				}
+				FillOptionsUpdate::StartStop(offset) => self.options.stops[0] = offset,
+			}
+			return;
+		}
+
+		let new_state = self.fsm_state.transition(action, data.0, data.1, &mut self.data, &self.options, data.2, responses);
 
 		if self.fsm_state != new_state {
 			self.fsm_state = new_state;
-			self.fsm_state.update_hints(responses);
+			self.fsm_state.update_hints(data.1, data.2, responses);
 			self.fsm_state.update_cursor(responses);
 		}
 	}
 
-	advertise_actions!(FillMessageDiscriminant; LeftMouseDown, RightMouseDown);
+	fn actions(&self) -> ActionList {
+		use FillToolFsmState::*;
+
+		match self.fsm_state {
+			Ready => actions!(FillMessageDiscriminant; LeftMouseDown, RightMouseDown),
+			Dragging => actions!(FillMessageDiscriminant; PointerMove, DragStop, Abort),
+		}
+	}
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum FillToolFsmState {
 	Ready,
+	/// Dragging out the axis of a gradient, or its radius from the center
+	Dragging,
 }
 
 impl Default for FillToolFsmState {
@@ -71,19 +194,60 @@ impl Default for FillToolFsmState {
 }
 
 #[derive(Clone, Debug, Default)]
-struct FillToolData {}
+struct FillToolData {
+	/// The layer being given a gradient
+	path: Vec<LayerId>,
+	/// Where the drag started in the viewport
+	drag_start: DVec2,
+	/// The clicked working color followed by the other one
+	colors: [Color; 2],
+}
+
+/// The gradient from where the drag started to `drag_end`, in the space of the layer.
+/// A click without a drag puts the gradient across the middle of the layer, from its left edge to its right edge, or from its center for a radial gradient.
+fn gradient(document: &DocumentMessageHandler, data: &FillToolData, gradient_type: GradientType, stops: [f64; 2], drag_end: DVec2) -> Option<Gradient> {
+	let layer = document.graphene_document.layer(&data.path).ok()?;
+	let transform = document.graphene_document.multiply_transforms(&data.path).ok()?;
+
+	let (start, end) = if data.drag_start.distance(drag_end) < DRAG_THRESHOLD {
+		let [min, max] = layer.current_bounding_box_with_transform(DAffine2::IDENTITY)?;
+		let center = (min + max) / 2.;
+		let left = DVec2::new(min.x, center.y);
+		let right = DVec2::new(max.x, center.y);
+		match gradient_type {
+			GradientType::Linear => (left, right),
+			GradientType::Radial => (center, right),
+		}
+	} else {
+		let inverse = transform.inverse();
+		(inverse.transform_point2(data.drag_start), inverse.transform_point2(drag_end))
+	};
+
+	let stops = [(stops[0], data.colors[0]), (stops[1], data.colors[1])];
+	Some(Gradient::new(gradient_type, start, end, &stops))
+}
+
+fn update_gradient(document: &DocumentMessageHandler, data: &FillToolData, tool_options: &FillOptions, drag_end: DVec2, responses: &mut VecDeque<Message>) {
+	let gradient_type = match tool_options.gradient_type {
+		Some(gradient_type) => gradient_type,
+		None => return,
+	};
+	if let Some(gradient) = gradient(document, data, gradient_type, tool_options.stops, drag_end) {
+		responses.push_back(Operation::SetLayerGradient { path: data.path.clone(), gradient }.into());
+	}
+}
 
 impl Fsm for FillToolFsmState {
 	type ToolData = FillToolData;
-	type ToolOptions = ();
+	type ToolOptions = FillOptions;
 
 	fn transition(
 		self,
 		event: ToolMessage,
 		document: &DocumentMessageHandler,
 		tool_data: &DocumentToolData,
-		_data: &mut Self::ToolData,
-		_tool_options: &Self::ToolOptions,
+		data: &mut Self::ToolData,
+		tool_options: &Self::ToolOptions,
 		input: &InputPreprocessorMessageHandler,
 		responses: &mut VecDeque<Message>,
 	) -> Self {
@@ -94,20 +258,47 @@ impl Fsm for FillToolFsmState {
 			match (self, event) {
 				(Ready, lmb_or_rmb) if lmb_or_rmb == LeftMouseDown || lmb_or_rmb == RightMouseDown => {
 					let mouse_pos = input.mouse.position;
-					let tolerance = DVec2::splat(SELECTION_TOLERANCE);
+					let tolerance = DVec2::splat(tool_data.selection_tolerance);
 					let quad = Quad::from_box([mouse_pos - tolerance, mouse_pos + tolerance]);
 
-					if let Some(path) = document.graphene_document.intersects_quad_root(quad).last() {
-						let color = match lmb_or_rmb {
-							LeftMouseDown => tool_data.primary_color,
-							RightMouseDown => tool_data.secondary_color,
-							Abort => unreachable!(),
-						};
-						responses.push_back(DocumentMessage::StartTransaction.into());
-						responses.push_back(Operation::SetLayerFill { path: path.to_vec(), color }.into());
+					let path = match document.graphene_document.intersects_quad_root(quad).last() {
+						Some(path) => path.clone(),
+						None => return Ready,
+					};
+					let colors = match lmb_or_rmb {
+						LeftMouseDown => [tool_data.primary_color, tool_data.secondary_color],
+						RightMouseDown => [tool_data.secondary_color, tool_data.primary_color],
+						_ => unreachable!(),
+					};
+
+					responses.push_back(DocumentMessage::StartTransaction.into());
+					if tool_options.gradient_type.is_none() {
+						responses.push_back(Operation::SetLayerFill { path, color: colors[0] }.into());
 						responses.push_back(DocumentMessage::CommitTransaction.into());
+
+						return Ready;
 					}
 
+					data.path = path;
+					data.drag_start = mouse_pos;
+					data.colors = colors;
+					update_gradient(document, data, tool_options, mouse_pos, responses);
+
+					Dragging
+				}
+				(Dragging, PointerMove) => {
+					update_gradient(document, data, tool_options, input.mouse.position, responses);
+
+					Dragging
+				}
+				(Dragging, DragStop) => {
+					responses.push_back(DocumentMessage::CommitTransaction.into());
+
+					Ready
+				}
+				(Dragging, Abort) => {
+					responses.push_back(DocumentMessage::AbortTransaction.into());
+
 					Ready
 				}
 				_ => self,
@@ -117,7 +308,7 @@ impl Fsm for FillToolFsmState {
 		}
 	}
 
-	fn update_hints(&self, responses: &mut VecDeque<Message>) {
+	fn update_hints(&self, _tool_data: &DocumentToolData, _input: &InputPreprocessorMessageHandler, responses: &mut VecDeque<Message>) {
 		let hint_data = match self {
 			FillToolFsmState::Ready => HintData(vec![HintGroup(vec![
 				HintInfo {
@@ -133,6 +324,20 @@ impl Fsm for FillToolFsmState {
 					plus: false,
 				},
 			])]),
+			FillToolFsmState::Dragging => HintData(vec![
+				HintGroup(vec![HintInfo {
+					key_groups: vec![],
+					mouse: Some(MouseMotion::LmbDrag),
+					label: String::from("Drag Gradient End"),
+					plus: false,
+				}]),
+				HintGroup(vec![HintInfo {
+					key_groups: vec![KeysGroup(vec![Key::KeyEscape])],
+					mouse: None,
+					label: String::from("Cancel"),
+					plus: false,
+				}]),
+			]),
 		};
 
 		responses.push_back(FrontendMessage::UpdateInputHints { hint_data }.into());
@@ -1,8 +1,9 @@
+use crate::consts::{DEFAULT_FREEHAND_CLOSE_DISTANCE, MAX_FREEHAND_SMOOTHING};
 use crate::document::DocumentMessageHandler;
 use crate::frontend::utility_types::MouseCursorIcon;
 use crate::input::keyboard::MouseMotion;
 use crate::input::InputPreprocessorMessageHandler;
-use crate::layout::widgets::{LayoutRow, NumberInput, PropertyHolder, Widget, WidgetCallback, WidgetHolder, WidgetLayout};
+use crate::layout::widgets::{LayoutRow, NumberInput, PropertyHolder, Separator, SeparatorDirection, SeparatorType, Widget, WidgetCallback, WidgetHolder, WidgetLayout};
 use crate::message_prelude::*;
 use crate::misc::{HintData, HintGroup, HintInfo};
 use crate::viewport_tools::tool::{DocumentToolData, Fsm, ToolActionHandlerData};
@@ -22,17 +23,34 @@ pub struct Freehand {
 
 pub struct FreehandOptions {
 	line_weight: u32,
+	/// How close, in viewport pixels, a stroke must end to where it started to close into a filled loop, or 0 to leave every stroke open
+	close_distance: f64,
+	/// The width of strokes drawn with a pen where it's pressed the lightest, while mice and other pointers without pressure use the line weight throughout
+	min_width: f64,
+	/// The width of strokes drawn with a pen where it's pressed the hardest
+	max_width: f64,
+	/// How far each point lags behind the pointer towards the previous one, from 0 for none, which evens out wobbles as the stroke is drawn
+	smoothing: f64,
+	/// How far the finished stroke may stray from the points drawn, in exchange for fewer of them, or 0 to keep every point
+	tolerance: f64,
 }
 
 impl Default for FreehandOptions {
 	fn default() -> Self {
-		Self { line_weight: 5 }
+		Self {
+			line_weight: 5,
+			close_distance: DEFAULT_FREEHAND_CLOSE_DISTANCE,
+			min_width: 1.,
+			max_width: 10.,
+			smoothing: 0.,
+			tolerance: 2.,
+		}
 	}
 }
 
 #[remain::sorted]
 #[impl_message(Message, ToolMessage, Freehand)]
-#[derive(PartialEq, Clone, Debug, Hash, Serialize, Deserialize)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub enum FreehandMessage {
 	// Standard messages
 	#[remain::unsorted]
@@ -46,9 +64,14 @@ pub enum FreehandMessage {
 }
 
 #[remain::sorted]
-#[derive(PartialEq, Clone, Debug, Hash, Serialize, Deserialize)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub enum FreehandMessageOptionsUpdate {
+	CloseDistance(f64),
 	LineWeight(u32),
+	MaxWidth(f64),
+	MinWidth(f64),
+	Smoothing(f64),
+	Tolerance(f64),
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -61,23 +84,88 @@ impl PropertyHolder for Freehand {
 	fn properties(&self) -> WidgetLayout {
 		WidgetLayout::new(vec![LayoutRow::Row {
 			name: "".into(),
-			widgets: vec![WidgetHolder::new(Widget::NumberInput(NumberInput {
-				unit: " px".into(),
-				label: "Weight".into(),
-				value: self.options.line_weight as f64,
-				is_integer: true,
-				min: Some(1.),
-				on_update: WidgetCallback::new(|number_input| FreehandMessage::UpdateOptions(FreehandMessageOptionsUpdate::LineWeight(number_input.value as u32)).into()),
-				..NumberInput::default()
-			}))],
+			widgets: vec![
+				WidgetHolder::new(Widget::NumberInput(NumberInput {
+					unit: " px".into(),
+					label: "Weight".into(),
+					value: self.options.line_weight as f64,
+					is_integer: true,
+					min: Some(1.),
+					on_update: WidgetCallback::new(|number_input| FreehandMessage::UpdateOptions(FreehandMessageOptionsUpdate::LineWeight(number_input.value as u32)).into()),
+					..NumberInput::default()
+				})),
+				WidgetHolder::new(Widget::Separator(Separator {
+					separator_type: SeparatorType::Unrelated,
+					direction: SeparatorDirection::Horizontal,
+				})),
+				WidgetHolder::new(Widget::NumberInput(NumberInput {
+					unit: " px".into(),
+					label: "Min Width".into(),
+					value: self.options.min_width,
+					min: Some(0.),
+					on_update: WidgetCallback::new(|number_input| FreehandMessage::UpdateOptions(FreehandMessageOptionsUpdate::MinWidth(number_input.value)).into()),
+					..NumberInput::default()
+				})),
+				WidgetHolder::new(Widget::Separator(Separator {
+					separator_type: SeparatorType::Related,
+					direction: SeparatorDirection::Horizontal,
+				})),
+				WidgetHolder::new(Widget::NumberInput(NumberInput {
+					unit: " px".into(),
+					label: "Max Width".into(),
+					value: self.options.max_width,
+					min: Some(0.),
+					on_update: WidgetCallback::new(|number_input| FreehandMessage::UpdateOptions(FreehandMessageOptionsUpdate::MaxWidth(number_input.value)).into()),
+					..NumberInput::default()
+				})),
+				WidgetHolder::new(Widget::Separator(Separator {
+					separator_type: SeparatorType::Unrelated,
+					direction: SeparatorDirection::Horizontal,
+				})),
+				WidgetHolder::new(Widget::NumberInput(NumberInput {
+					label: "Smoothing".into(),
+					value: self.options.smoothing,
+					min: Some(0.),
+					max: Some(MAX_FREEHAND_SMOOTHING),
+					increment_factor: 0.05,
+					on_update: WidgetCallback::new(|number_input| FreehandMessage::UpdateOptions(FreehandMessageOptionsUpdate::Smoothing(number_input.value)).into()),
+					..NumberInput::default()
+				})),
+				WidgetHolder::new(Widget::Separator(Separator {
+					separator_type: SeparatorType::Related,
+					direction: SeparatorDirection::Horizontal,
+				})),
+				WidgetHolder::new(Widget::NumberInput(NumberInput {
+					unit: " px".into(),
+					label: "Tolerance".into(),
+					value: self.options.tolerance,
+					min: Some(0.),
+					on_update: WidgetCallback::new(|number_input| FreehandMessage::UpdateOptions(FreehandMessageOptionsUpdate::Tolerance(number_input.value)).into()),
+					..NumberInput::default()
+				})),
+				WidgetHolder::new(Widget::Separator(Separator {
+					separator_type: SeparatorType::Unrelated,
+					direction: SeparatorDirection::Horizontal,
+				})),
+				WidgetHolder::new(Widget::NumberInput(NumberInput {
+					unit: " px".into(),
+					label: "Close Within".into(),
+					value: self.options.close_distance,
+					min: Some(0.),
+					on_update: WidgetCallback::new(|number_input| FreehandMessage::UpdateOptions(FreehandMessageOptionsUpdate::CloseDistance(number_input.value)).into()),
+					..NumberInput::default()
+				})),
+			],
 		}])
 	}
 }
 
+consumes_input_unless_ready!(Freehand);
+
 impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Freehand {
 	fn process_action(&mut self, action: ToolMessage, data: ToolActionHandlerData<'a>, responses: &mut VecDeque<Message>) {
 		if action == ToolMessage::UpdateHints {
-			self.fsm_state.update_hints(responses);
+			self.fsm_state.update_hints(data.1, data.2, responses);
 			return;
 		}
 
@@ -88,7 +176,12 @@ impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Freehand {
 
 		if let ToolMessage::Freehand(FreehandMessage::UpdateOptions(action)) = action {
 			match action {
+				FreehandMessageOptionsUpdate::CloseDistance(close_distance) => self.options.close_distance = close_distance.max(0.),
 				FreehandMessageOptionsUpdate::LineWeight(line_weight) => self.options.line_weight = line_weight,
+				FreehandMessageOptionsUpdate::MaxWidth(max_width) => self.options.max_width = max_width.max(0.),
+				FreehandMessageOptionsUpdate::MinWidth(min_width) => self.options.min_width = min_width.max(0.),
+				FreehandMessageOptionsUpdate::Smoothing(smoothing) => self.options.smoothing = smoothing.clamp(0., MAX_FREEHAND_SMOOTHING),
+				FreehandMessageOptionsUpdate::Tolerance(tolerance) => self.options.tolerance = tolerance.max(0.),
 			}
 			return;
 		}
@@ -97,7 +190,7 @@ impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Freehand {
 
 		if self.fsm_state != new_state {
 			self.fsm_state = new_state;
-			self.fsm_state.update_hints(responses);
+			self.fsm_state.update_hints(data.1, data.2, responses);
 			self.fsm_state.update_cursor(responses);
 		}
 	}
@@ -120,10 +213,49 @@ impl Default for FreehandToolFsmState {
 #[derive(Clone, Debug, Default)]
 struct FreehandToolData {
 	points: Vec<DVec2>,
+	/// The pen pressure at each point drawn, if the pointer senses it
+	pressures: Vec<Option<f64>>,
 	weight: u32,
+	min_width: f64,
+	max_width: f64,
+	smoothing: f64,
+	tolerance: f64,
+	close_distance: f64,
+	/// Whether the finished stroke loops back around to its first point
+	closed: bool,
 	path: Option<Vec<LayerId>>,
 }
 
+impl FreehandToolData {
+	/// Closes the stroke if the pointer ends within the close distance of where it started, measured in the viewport so it feels the same at any zoom.
+	/// The points drawn on the way back in over the start are dropped, and the outline of a pen stroke is instead snapped shut by ending it on the start.
+	fn close_if_ended_at_start(&mut self, document: &DocumentMessageHandler, pointer: DVec2) {
+		let (start, close_distance) = match self.points.first() {
+			Some(start) => (document.document_to_viewport(*start), self.close_distance),
+			None => return,
+		};
+		if close_distance <= 0. || start.distance(pointer) > close_distance {
+			return;
+		}
+
+		let near_start = |point: &DVec2| document.document_to_viewport(*point).distance(start) <= close_distance;
+		let end = self.points.iter().rposition(|point| !near_start(point)).map_or(0, |index| index + 1);
+		// A loop needs at least 3 points to enclose anything
+		if end < 3 {
+			return;
+		}
+		self.points.truncate(end);
+		self.pressures.truncate(end);
+
+		if pressure_widths(self).is_some() {
+			self.points.push(self.points[0]);
+			self.pressures.push(self.pressures[0]);
+		} else {
+			self.closed = true;
+		}
+	}
+}
+
 impl Fsm for FreehandToolFsmState {
 	type ToolData = FreehandToolData;
 	type ToolOptions = FreehandOptions;
@@ -141,8 +273,6 @@ impl Fsm for FreehandToolFsmState {
 		use FreehandMessage::*;
 		use FreehandToolFsmState::*;
 
-		let transform = document.graphene_document.root.transform;
-
 		if let ToolMessage::Freehand(event) = event {
 			match (self, event) {
 				(Ready, DragStart) => {
@@ -150,33 +280,48 @@ impl Fsm for FreehandToolFsmState {
 					responses.push_back(DocumentMessage::DeselectAllLayers.into());
 					data.path = Some(document.get_path_for_new_layer());
 
-					let pos = transform.inverse().transform_point2(input.mouse.position);
+					let pos = document.viewport_to_document(input.mouse.position);
 
 					data.points.push(pos);
+					data.pressures.push(input.mouse.pressure);
 
 					data.weight = tool_options.line_weight;
+					data.min_width = tool_options.min_width;
+					data.max_width = tool_options.max_width;
+					data.smoothing = tool_options.smoothing;
+					data.tolerance = tool_options.tolerance;
+					data.close_distance = tool_options.close_distance;
+					data.closed = false;
 
-					responses.push_back(add_polyline(data, tool_data));
+					responses.push_back(add_preview(data, tool_data));
 
 					Drawing
 				}
 				(Drawing, PointerMove) => {
-					let pos = transform.inverse().transform_point2(input.mouse.position);
+					let pos = document.viewport_to_document(input.mouse.position);
+					// Smoothing moves each point only part of the way from the last one towards the pointer, so the stroke trails behind it without its wobbles
+					let pos = match data.points.last() {
+						Some(last) if data.smoothing > 0. => last.lerp(pos, 1. - data.smoothing),
+						_ => pos,
+					};
 
 					if data.points.last() != Some(&pos) {
 						data.points.push(pos);
+						data.pressures.push(input.mouse.pressure);
 					}
 
 					responses.push_back(remove_preview(data));
-					responses.push_back(add_polyline(data, tool_data));
+					responses.push_back(add_preview(data, tool_data));
 
 					Drawing
 				}
-				(Drawing, DragStop) | (Drawing, Abort) => {
+				(Drawing, DragStop) => {
+					data.close_if_ended_at_start(document, input.mouse.position);
+
 					if data.points.len() >= 2 {
 						responses.push_back(DocumentMessage::DeselectAllLayers.into());
 						responses.push_back(remove_preview(data));
-						responses.push_back(add_polyline(data, tool_data));
+						responses.push_back(add_simplified_stroke(data, tool_data));
 						responses.push_back(DocumentMessage::CommitTransaction.into());
 					} else {
 						responses.push_back(DocumentMessage::AbortTransaction.into());
@@ -184,6 +329,16 @@ impl Fsm for FreehandToolFsmState {
 
 					data.path = None;
 					data.points.clear();
+					data.pressures.clear();
+
+					Ready
+				}
+				(Drawing, Abort) => {
+					responses.push_back(DocumentMessage::AbortTransaction.into());
+
+					data.path = None;
+					data.points.clear();
+					data.pressures.clear();
 
 					Ready
 				}
@@ -194,7 +349,7 @@ impl Fsm for FreehandToolFsmState {
 		}
 	}
 
-	fn update_hints(&self, responses: &mut VecDeque<Message>) {
+	fn update_hints(&self, _tool_data: &DocumentToolData, _input: &InputPreprocessorMessageHandler, responses: &mut VecDeque<Message>) {
 		let hint_data = match self {
 			FreehandToolFsmState::Ready => HintData(vec![HintGroup(vec![HintInfo {
 				key_groups: vec![],
@@ -225,7 +380,115 @@ fn add_polyline(data: &FreehandToolData, tool_data: &DocumentToolData) -> Messag
 		insert_index: -1,
 		transform: DAffine2::IDENTITY.to_cols_array(),
 		points,
-		style: style::PathStyle::new(Some(style::Stroke::new(tool_data.primary_color, data.weight as f32)), None),
+		closed: data.closed,
+		style: stroke_style(data, tool_data),
+	}
+	.into()
+}
+
+/// The stroke is drawn in the primary color, and a closed loop is filled in with the secondary color.
+fn stroke_style(data: &FreehandToolData, tool_data: &DocumentToolData) -> style::PathStyle {
+	let fill = if data.closed { Some(style::Fill::new(tool_data.secondary_color)) } else { None };
+	style::PathStyle::new(Some(style::Stroke::new(tool_data.primary_color, data.weight as f32)), fill)
+}
+
+/// The stroke drawn so far, without simplifying it.
+fn add_preview(data: &FreehandToolData, tool_data: &DocumentToolData) -> Message {
+	match pressure_widths(data) {
+		Some(widths) => add_variable_width_stroke(data, tool_data, &data.points, widths),
+		None => add_polyline(data, tool_data),
+	}
+}
+
+/// The finished stroke, reduced to the fewest points that stay within the tolerance of the points drawn and smoothly curved through them.
+/// With no tolerance, the stroke is kept as a polyline through every point drawn.
+/// Strokes drawn with a pen are outlined instead, growing wider where the pen was pressed harder.
+fn add_simplified_stroke(data: &FreehandToolData, tool_data: &DocumentToolData) -> Message {
+	let kept = if data.tolerance > 0. {
+		simplify(&data.points, data.tolerance)
+	} else {
+		(0..data.points.len()).collect()
+	};
+
+	if let Some(widths) = pressure_widths(data) {
+		let points: Vec<_> = kept.iter().map(|&index| data.points[index]).collect();
+		let widths = kept.iter().map(|&index| widths[index]).collect();
+		return add_variable_width_stroke(data, tool_data, &points, widths);
+	}
+
+	if data.tolerance <= 0. {
+		return add_polyline(data, tool_data);
+	}
+
+	Operation::AddSpline {
+		path: data.path.clone().unwrap(),
+		insert_index: -1,
+		transform: DAffine2::IDENTITY.to_cols_array(),
+		points: kept.iter().map(|&index| (data.points[index].x, data.points[index].y)).collect(),
+		closed: data.closed,
+		// The natural spline, the same as the Spline tool draws by default
+		tension: 0.5,
+		style: stroke_style(data, tool_data),
+	}
+	.into()
+}
+
+/// The width of the stroke at each point drawn, from the minimum to the maximum width as the pressure goes from 0 to 1, if every point came with the pressure of a pen.
+fn pressure_widths(data: &FreehandToolData) -> Option<Vec<f64>> {
+	data.pressures
+		.iter()
+		.map(|pressure| pressure.map(|pressure| data.min_width + pressure * (data.max_width - data.min_width)))
+		.collect()
+}
+
+fn add_variable_width_stroke(data: &FreehandToolData, tool_data: &DocumentToolData, points: &[DVec2], widths: Vec<f64>) -> Message {
+	Operation::AddVariableWidthStroke {
+		path: data.path.clone().unwrap(),
+		insert_index: -1,
+		transform: DAffine2::IDENTITY.to_cols_array(),
+		points: points.iter().map(|p| (p.x, p.y)).collect(),
+		widths,
+		style: style::PathStyle::new(None, Some(style::Fill::new(tool_data.primary_color))),
 	}
 	.into()
 }
+
+/// The indices of the points left after dropping those within `tolerance` of the line between the points kept around them, using the Ramer-Douglas-Peucker algorithm.
+/// See https://en.wikipedia.org/wiki/Ramer%E2%80%93Douglas%E2%80%93Peucker_algorithm
+fn simplify(points: &[DVec2], tolerance: f64) -> Vec<usize> {
+	if points.len() < 3 {
+		return (0..points.len()).collect();
+	}
+	let last_index = points.len() - 1;
+	let (first, last) = (points[0], points[last_index]);
+
+	// The distance to the segment rather than the whole line, so a stroke that comes back around to where it started keeps its shape
+	let distance_to_segment = |point: DVec2| {
+		let segment = last - first;
+		let along = if segment.length_squared() > 0. {
+			((point - first).dot(segment) / segment.length_squared()).clamp(0., 1.)
+		} else {
+			0.
+		};
+		point.distance(first + along * segment)
+	};
+
+	let (mut farthest_index, mut farthest_distance) = (0, 0.);
+	for (index, point) in points.iter().enumerate().take(last_index).skip(1) {
+		let distance = distance_to_segment(*point);
+		if distance > farthest_distance {
+			farthest_index = index;
+			farthest_distance = distance;
+		}
+	}
+
+	if farthest_distance > tolerance {
+		// Keep the farthest point, and simplify the stroke on either side of it
+		let mut kept = simplify(&points[..=farthest_index], tolerance);
+		kept.pop();
+		kept.extend(simplify(&points[farthest_index..], tolerance).into_iter().map(|index| index + farthest_index));
+		kept
+	} else {
+		vec![0, last_index]
+	}
+}
@@ -1,19 +1,26 @@
-use crate::consts::{DRAG_THRESHOLD, LINE_ROTATE_SNAP_ANGLE};
+use super::shared::auto_pan::auto_pan;
+use crate::consts::{COLOR_ACCENT, DRAG_THRESHOLD, MEASUREMENT_LABEL_FONT_SIZE, READOUT_CURSOR_OFFSET};
 use crate::document::DocumentMessageHandler;
 use crate::frontend::utility_types::MouseCursorIcon;
 use crate::input::keyboard::{Key, MouseMotion};
 use crate::input::mouse::ViewportPosition;
 use crate::input::InputPreprocessorMessageHandler;
-use crate::layout::widgets::{LayoutRow, NumberInput, PropertyHolder, Widget, WidgetCallback, WidgetHolder, WidgetLayout};
+use crate::layout::layout_message::LayoutTarget;
+use crate::layout::widgets::{
+	DropdownEntryData, DropdownInput, LayoutRow, NumberInput, PropertyHolder, RadioEntryData, RadioInput, Separator, SeparatorDirection, SeparatorType, Widget, WidgetCallback, WidgetHolder,
+	WidgetLayout,
+};
 use crate::message_prelude::*;
 use crate::misc::{HintData, HintGroup, HintInfo, KeysGroup};
 use crate::viewport_tools::snapping::SnapHandler;
 use crate::viewport_tools::tool::{DocumentToolData, Fsm, ToolActionHandlerData};
 
-use graphene::layers::style;
+use graphene::color::Color;
+use graphene::layers::style::{self, Fill};
 use graphene::Operation;
 
 use glam::{DAffine2, DVec2};
+use kurbo::{BezPath, Point};
 use serde::{Deserialize, Serialize};
 
 #[derive(Default)]
@@ -25,23 +32,151 @@ pub struct Line {
 
 pub struct LineOptions {
 	line_weight: u32,
+	/// Alternating lengths of dashes and gaps in multiples of the line weight, or empty for a solid line
+	dash_pattern: Vec<f32>,
+	start_cap: LineCap,
+	end_cap: LineCap,
+	/// Whether each line continues on from the end of the last one as a single path, until the chain is finished
+	polyline: bool,
 }
 
 impl Default for LineOptions {
 	fn default() -> Self {
-		Self { line_weight: 5 }
+		Self {
+			line_weight: 5,
+			dash_pattern: Vec::new(),
+			start_cap: LineCap::None,
+			end_cap: LineCap::None,
+			polyline: false,
+		}
+	}
+}
+
+/// The dash patterns offered in the tool options.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash, Serialize, Deserialize)]
+pub enum DashPreset {
+	Solid,
+	Dashed,
+	Dotted,
+}
+
+impl DashPreset {
+	/// The alternating lengths of dashes and gaps, in multiples of the line weight
+	fn pattern(self) -> Vec<f32> {
+		match self {
+			DashPreset::Solid => Vec::new(),
+			DashPreset::Dashed => vec![3., 2.],
+			DashPreset::Dotted => vec![1., 1.],
+		}
+	}
+}
+
+/// The shape drawn at an end of a line, sized relative to the line weight.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash, Serialize, Deserialize)]
+pub enum LineCap {
+	None,
+	Arrow,
+	Circle,
+	Square,
+}
+
+impl Default for LineCap {
+	fn default() -> Self {
+		LineCap::None
+	}
+}
+
+impl LineCap {
+	const ALL: [LineCap; 4] = [LineCap::None, LineCap::Arrow, LineCap::Circle, LineCap::Square];
+
+	fn value(self) -> &'static str {
+		match self {
+			LineCap::None => "none",
+			LineCap::Arrow => "arrow",
+			LineCap::Circle => "circle",
+			LineCap::Square => "square",
+		}
+	}
+
+	fn label(self) -> &'static str {
+		match self {
+			LineCap::None => "None",
+			LineCap::Arrow => "Arrow",
+			LineCap::Circle => "Circle",
+			LineCap::Square => "Square",
+		}
+	}
+
+	fn from_value(value: &str) -> Self {
+		LineCap::ALL.into_iter().find(|cap| cap.value() == value).unwrap_or(LineCap::None)
+	}
+
+	/// The length and width of the cap for a line of the given weight
+	fn size(self, weight: f64) -> f64 {
+		match self {
+			LineCap::None => 0.,
+			LineCap::Arrow => 3. * weight,
+			LineCap::Circle | LineCap::Square => 2. * weight,
+		}
+	}
+
+	/// How far the line stops short of its end, so it meets the base of an arrowhead instead of poking out past the tip
+	fn inset(self, weight: f64) -> f64 {
+		match self {
+			LineCap::Arrow => self.size(weight),
+			_ => 0.,
+		}
+	}
+
+	/// Adds the cap's shape, which is drawn in a unit square facing along the X axis with the end of the line at the origin
+	fn add_layer(self, path: Vec<LayerId>, color: Color) -> Option<Message> {
+		let (transform, style) = (DAffine2::ZERO.to_cols_array(), style::PathStyle::new(None, Some(Fill::new(color))));
+		let operation = match self {
+			LineCap::None => return None,
+			LineCap::Arrow => Operation::AddPolyline {
+				path,
+				transform,
+				insert_index: -1,
+				points: vec![(0., 0.), (-1., -0.5), (-1., 0.5), (0., 0.)],
+				closed: false,
+				style,
+			},
+			LineCap::Circle => Operation::AddEllipse {
+				path,
+				insert_index: -1,
+				transform,
+				style,
+			},
+			LineCap::Square => Operation::AddRect {
+				path,
+				insert_index: -1,
+				transform,
+				style,
+			},
+		};
+		Some(operation.into())
+	}
+
+	/// The ellipse and rectangle fill the unit square from the origin, so they're moved back to be centered on the end of the line
+	fn local_transform(self) -> DAffine2 {
+		match self {
+			LineCap::Circle | LineCap::Square => DAffine2::from_translation(DVec2::splat(-0.5)),
+			_ => DAffine2::IDENTITY,
+		}
 	}
 }
 
 #[remain::sorted]
 #[impl_message(Message, ToolMessage, Line)]
-#[derive(PartialEq, Clone, Debug, Hash, Serialize, Deserialize)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub enum LineMessage {
 	// Standard messages
 	#[remain::unsorted]
 	Abort,
 
 	// Tool-specific messages
+	AutoPan,
+	Confirm,
 	DragStart,
 	DragStop,
 	Redraw {
@@ -49,20 +184,69 @@ pub enum LineMessage {
 		lock_angle: Key,
 		snap_angle: Key,
 	},
+	/// Sets the length in document units and the counterclockwise angle in degrees of the last line drawn, keeping the other if it's `None`
+	SetDimensions {
+		length: Option<f64>,
+		angle: Option<f64>,
+	},
 	UpdateOptions(LineOptionsUpdate),
 }
 
 #[remain::sorted]
 #[derive(PartialEq, Clone, Debug, Hash, Serialize, Deserialize)]
 pub enum LineOptionsUpdate {
+	DashPreset(DashPreset),
+	EndCap(LineCap),
 	LineWeight(u32),
+	Polyline(bool),
+	StartCap(LineCap),
 }
 
 impl PropertyHolder for Line {
 	fn properties(&self) -> WidgetLayout {
-		WidgetLayout::new(vec![LayoutRow::Row {
-			name: "".into(),
-			widgets: vec![WidgetHolder::new(Widget::NumberInput(NumberInput {
+		// Widget callbacks can't capture variables, so each entry tells the callback which cap it is by its value
+		let cap_dropdown = |selected: LineCap, on_update: WidgetCallback<DropdownEntryData>| {
+			WidgetHolder::new(Widget::DropdownInput(DropdownInput {
+				entries: vec![LineCap::ALL
+					.into_iter()
+					.map(|cap| DropdownEntryData {
+						value: cap.value().into(),
+						label: cap.label().into(),
+						on_update: on_update.clone(),
+					})
+					.collect()],
+				selected_index: LineCap::ALL.iter().position(|&cap| cap == selected).unwrap_or_default() as u32,
+			}))
+		};
+		let separator = || {
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Related,
+				direction: SeparatorDirection::Horizontal,
+			}))
+		};
+
+		let mut widgets = vec![
+			WidgetHolder::new(Widget::RadioInput(RadioInput {
+				selected_index: if self.options.polyline { 1 } else { 0 },
+				entries: vec![
+					RadioEntryData {
+						value: "line".into(),
+						label: "Line".into(),
+						tooltip: "Draw a separate line with each drag".into(),
+						on_update: WidgetCallback::new(|_| LineMessage::UpdateOptions(LineOptionsUpdate::Polyline(false)).into()),
+						..RadioEntryData::default()
+					},
+					RadioEntryData {
+						value: "polyline".into(),
+						label: "Polyline".into(),
+						tooltip: "Chain lines end to end into one path, finished with Enter, Escape, or a double click".into(),
+						on_update: WidgetCallback::new(|_| LineMessage::UpdateOptions(LineOptionsUpdate::Polyline(true)).into()),
+						..RadioEntryData::default()
+					},
+				],
+			})),
+			separator(),
+			WidgetHolder::new(Widget::NumberInput(NumberInput {
 				unit: " px".into(),
 				label: "Weight".into(),
 				value: self.options.line_weight as f64,
@@ -70,15 +254,89 @@ impl PropertyHolder for Line {
 				min: Some(0.),
 				on_update: WidgetCallback::new(|number_input| LineMessage::UpdateOptions(LineOptionsUpdate::LineWeight(number_input.value as u32)).into()),
 				..NumberInput::default()
-			}))],
-		}])
+			})),
+			separator(),
+			WidgetHolder::new(Widget::DropdownInput(DropdownInput {
+				entries: vec![vec![
+					DropdownEntryData {
+						value: "solid".into(),
+						label: "Solid".into(),
+						on_update: WidgetCallback::new(|_| LineMessage::UpdateOptions(LineOptionsUpdate::DashPreset(DashPreset::Solid)).into()),
+					},
+					DropdownEntryData {
+						value: "dashed".into(),
+						label: "Dashed".into(),
+						on_update: WidgetCallback::new(|_| LineMessage::UpdateOptions(LineOptionsUpdate::DashPreset(DashPreset::Dashed)).into()),
+					},
+					DropdownEntryData {
+						value: "dotted".into(),
+						label: "Dotted".into(),
+						on_update: WidgetCallback::new(|_| LineMessage::UpdateOptions(LineOptionsUpdate::DashPreset(DashPreset::Dotted)).into()),
+					},
+				]],
+				selected_index: [DashPreset::Solid, DashPreset::Dashed, DashPreset::Dotted]
+					.iter()
+					.position(|preset| preset.pattern() == self.options.dash_pattern)
+					.unwrap_or_default() as u32,
+			})),
+			separator(),
+			cap_dropdown(
+				self.options.start_cap,
+				WidgetCallback::new(|entry| LineMessage::UpdateOptions(LineOptionsUpdate::StartCap(LineCap::from_value(&entry.value))).into()),
+			),
+			separator(),
+			cap_dropdown(
+				self.options.end_cap,
+				WidgetCallback::new(|entry| LineMessage::UpdateOptions(LineOptionsUpdate::EndCap(LineCap::from_value(&entry.value))).into()),
+			),
+		];
+
+		// The line being drawn, or the last one drawn, can be given an exact length and angle, and the inputs follow along while dragging
+		if let Some((length, angle)) = self.data.dimensions {
+			widgets.push(WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Unrelated,
+				direction: SeparatorDirection::Horizontal,
+			})));
+			widgets.push(WidgetHolder::new(Widget::NumberInput(NumberInput {
+				unit: " px".into(),
+				label: "Length".into(),
+				value: length,
+				min: Some(0.),
+				on_update: WidgetCallback::new(|number_input| {
+					LineMessage::SetDimensions {
+						length: Some(number_input.value),
+						angle: None,
+					}
+					.into()
+				}),
+				..NumberInput::default()
+			})));
+			widgets.push(separator());
+			widgets.push(WidgetHolder::new(Widget::NumberInput(NumberInput {
+				unit: "°".into(),
+				label: "Angle".into(),
+				value: angle,
+				on_update: WidgetCallback::new(|number_input| {
+					LineMessage::SetDimensions {
+						length: None,
+						angle: Some(number_input.value),
+					}
+					.into()
+				}),
+				..NumberInput::default()
+			})));
+		}
+
+		WidgetLayout::new(vec![LayoutRow::Row { name: "".into(), widgets }])
 	}
 }
 
+consumes_input_unless_ready!(Line);
+
 impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Line {
 	fn process_action(&mut self, action: ToolMessage, data: ToolActionHandlerData<'a>, responses: &mut VecDeque<Message>) {
 		if action == ToolMessage::UpdateHints {
-			self.fsm_state.update_hints(responses);
+			self.fsm_state.update_hints(data.1, data.2, responses);
 			return;
 		}
 
@@ -89,16 +347,30 @@ impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Line {
 
 		if let ToolMessage::Line(LineMessage::UpdateOptions(action)) = action {
 			match action {
+				LineOptionsUpdate::DashPreset(preset) => self.options.dash_pattern = preset.pattern(),
+				LineOptionsUpdate::EndCap(end_cap) => self.options.end_cap = end_cap,
 				LineOptionsUpdate::LineWeight(line_weight) => self.options.line_weight = line_weight,
+				LineOptionsUpdate::Polyline(polyline) => self.options.polyline = polyline,
+				LineOptionsUpdate::StartCap(start_cap) => self.options.start_cap = start_cap,
 			}
 			return;
 		}
 
+		// Keep the length and angle inputs in step with the line
+		let dimensions_changed = matches!(
+			action,
+			ToolMessage::Line(LineMessage::Redraw { .. } | LineMessage::SetDimensions { .. } | LineMessage::DragStop | LineMessage::Abort)
+		);
+
 		let new_state = self.fsm_state.transition(action, data.0, data.1, &mut self.data, &self.options, data.2, responses);
 
+		if dimensions_changed {
+			self.register_properties(responses, LayoutTarget::ToolOptions);
+		}
+
 		if self.fsm_state != new_state {
 			self.fsm_state = new_state;
-			self.fsm_state.update_hints(responses);
+			self.fsm_state.update_hints(data.1, data.2, responses);
 			self.fsm_state.update_cursor(responses);
 		}
 	}
@@ -108,7 +380,9 @@ impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Line {
 
 		match self.fsm_state {
 			Ready => actions!(LineMessageDiscriminant; DragStart),
-			Drawing => actions!(LineMessageDiscriminant; DragStop, Redraw, Abort),
+			Drawing if self.data.polyline => actions!(LineMessageDiscriminant; AutoPan, Confirm, DragStop, Redraw, Abort),
+			Drawing => actions!(LineMessageDiscriminant; AutoPan, DragStop, Redraw, Abort),
+			Chaining => actions!(LineMessageDiscriminant; Confirm, DragStart, Redraw, Abort),
 		}
 	}
 }
@@ -117,6 +391,8 @@ impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Line {
 enum LineToolFsmState {
 	Ready,
 	Drawing,
+	/// Between the lines of a polyline, with the next one following the pointer from the end of the last
+	Chaining,
 }
 
 impl Default for LineToolFsmState {
@@ -130,9 +406,24 @@ struct LineToolData {
 	drag_start: ViewportPosition,
 	drag_current: ViewportPosition,
 	angle: f64,
+	/// The signed length of the line along its angle, in viewport space
+	scale: f64,
 	weight: u32,
 	path: Option<Vec<LayerId>>,
+	/// The caps at the start and end of the line, drawn as their own layers in a folder with the line
+	caps: [(LineCap, Option<Vec<LayerId>>); 2],
 	snap_handler: SnapHandler,
+	readout_path: Option<Vec<LayerId>>,
+	/// Whether the line was drawn out both ways from `drag_start` as its center
+	from_center: bool,
+	/// The start of the drag in document space, which stays put when the canvas moves before the dimensions of the line are entered
+	document_anchor: DVec2,
+	/// The length in document units and counterclockwise angle in degrees of the line that can be given exact dimensions
+	dimensions: Option<(f64, f64)>,
+	/// Whether the lines are being chained into a polyline, which is drawn as a single path instead of a line with caps
+	polyline: bool,
+	/// The points of the polyline placed so far in document space, so they stay put when the canvas moves, with the line being drawn running from the last of them to the pointer
+	points: Vec<DVec2>,
 }
 
 impl Fsm for LineToolFsmState {
@@ -156,51 +447,187 @@ impl Fsm for LineToolFsmState {
 			match (self, event) {
 				(Ready, DragStart) => {
 					data.snap_handler.start_snap(document, document.bounding_boxes(None, None), true, true);
+					data.snap_handler.add_extension_targets(document, document.line_segments());
+					data.snap_handler.add_anchor_targets(document, document.anchor_points(), tool_data.anchor_snap_tolerance);
 					data.drag_start = data.snap_handler.snap_position(responses, input.viewport_bounds.size(), document, input.mouse.position);
 
 					responses.push_back(DocumentMessage::StartTransaction.into());
-					data.path = Some(document.get_path_for_new_layer());
+					let layer_path = document.get_path_for_new_layer();
 					responses.push_back(DocumentMessage::DeselectAllLayers.into());
 
 					data.weight = tool_options.line_weight;
+					// The dashes are set once here, since redrawing the line while dragging only changes its transform
+					let dash_lengths: Vec<f32> = tool_options.dash_pattern.iter().map(|&length| length * data.weight as f32).collect();
+					let stroke = style::Stroke::new(tool_data.primary_color, data.weight as f32).with_dash_pattern(&dash_lengths);
+
+					data.caps = Default::default();
+					data.dimensions = None;
+					data.polyline = tool_options.polyline;
+
+					// A polyline is a single path that grows by a point at the end of each line, until the chain is finished
+					if data.polyline {
+						data.path = Some(layer_path.clone());
+						data.points = vec![document.viewport_to_document(data.drag_start)];
+						data.scale = 0.;
+						responses.push_back(
+							Operation::AddPolyline {
+								path: layer_path,
+								insert_index: -1,
+								transform: DAffine2::IDENTITY.to_cols_array(),
+								points: Vec::new(),
+								closed: false,
+								style: style::PathStyle::new(Some(stroke), None),
+							}
+							.into(),
+						);
+
+						return Drawing;
+					}
+
+					// With caps, the line and its caps are grouped into a folder so they're moved and exported together
+					let caps = [tool_options.start_cap, tool_options.end_cap];
+					data.path = if caps.iter().all(|&cap| cap == LineCap::None) {
+						Some(layer_path)
+					} else {
+						responses.push_back(Operation::CreateFolder { path: layer_path.clone() }.into());
+						data.caps = caps.map(|cap| (cap, (cap != LineCap::None).then(|| [layer_path.as_slice(), &[generate_uuid()]].concat())));
+						Some([layer_path.as_slice(), &[generate_uuid()]].concat())
+					};
 
 					responses.push_back(
 						Operation::AddLine {
 							path: data.path.clone().unwrap(),
 							insert_index: -1,
 							transform: DAffine2::ZERO.to_cols_array(),
-							style: style::PathStyle::new(Some(style::Stroke::new(tool_data.primary_color, data.weight as f32)), None),
+							style: style::PathStyle::new(Some(stroke), None),
 						}
 						.into(),
 					);
+					for (cap, path) in &data.caps {
+						if let Some(path) = path {
+							responses.extend(cap.add_layer(path.clone(), tool_data.primary_color));
+						}
+					}
 
 					Drawing
 				}
-				(Drawing, Redraw { center, snap_angle, lock_angle }) => {
+				(Drawing | Chaining, Redraw { center, snap_angle, lock_angle }) => {
 					data.drag_current = data.snap_handler.snap_position(responses, input.viewport_bounds.size(), document, input.mouse.position);
 
-					let values: Vec<_> = [lock_angle, snap_angle, center].iter().map(|k| input.keyboard.get(*k as usize)).collect();
-					responses.push_back(generate_transform(data, values[0], values[1], values[2]));
+					let values: Vec<_> = [lock_angle, snap_angle, center].iter().map(|k| input.is_pressed(k)).collect();
+					let snap_angle = values[1].then(|| input.rotate_snap_angle);
+					if data.polyline {
+						// Each line of a polyline starts from the end of the last one, so it can't be drawn from its center
+						if let Some(&last) = data.points.last() {
+							data.drag_start = document.document_to_viewport(last);
+						}
+						constrain_line(data, values[0], snap_angle, false);
+						redraw_polyline(data, document, Some(line_end(data)), responses);
+					} else {
+						data.from_center = values[2];
+						generate_transform(data, values[0], snap_angle, values[2], responses);
+						data.dimensions = Some(line_dimensions(data, document));
+					}
+					update_readout(data, document, input.mouse.position, responses);
+
+					self
+				}
+				(Drawing, AutoPan) => {
+					if let Some(delta) = auto_pan(tool_data, input, responses) {
+						data.drag_start += delta;
+						data.snap_handler.translate_targets(delta);
+					}
 
 					Drawing
 				}
+				(Drawing, DragStop) if data.polyline => {
+					// A line too short to see, like one from a click, adds no point but keeps the chain going
+					let end = line_end(data);
+					if data.points.last().map_or(true, |&last| document.document_to_viewport(last).distance(end) > DRAG_THRESHOLD) {
+						data.points.push(document.viewport_to_document(end));
+						data.drag_start = end;
+					}
+					data.scale = 0.;
+					redraw_polyline(data, document, None, responses);
+
+					Chaining
+				}
+				(Chaining, DragStart) => Drawing,
+				(Drawing | Chaining, Confirm) => {
+					data.snap_handler.cleanup(responses);
+
+					// The line following the pointer is dropped, leaving the chain as it was at the last point placed
+					if data.points.len() >= 2 {
+						redraw_polyline(data, document, None, responses);
+						responses.push_back(DocumentMessage::CommitTransaction.into());
+					} else {
+						responses.push_back(DocumentMessage::AbortTransaction.into());
+					}
+
+					data.points.clear();
+					forget_line(data);
+					delete_readout(data, responses);
+
+					Ready
+				}
 				(Drawing, DragStop) => {
 					data.drag_current = data.snap_handler.snap_position(responses, input.viewport_bounds.size(), document, input.mouse.position);
 					data.snap_handler.cleanup(responses);
 
+					// The finished line stays around to be given exact dimensions until another one is drawn
 					match data.drag_start.distance(input.mouse.position) <= DRAG_THRESHOLD {
-						true => responses.push_back(DocumentMessage::AbortTransaction.into()),
-						false => responses.push_back(DocumentMessage::CommitTransaction.into()),
+						true => {
+							responses.push_back(DocumentMessage::AbortTransaction.into());
+							forget_line(data);
+						}
+						false => {
+							responses.push_back(DocumentMessage::CommitTransaction.into());
+							data.document_anchor = document.viewport_to_document(data.drag_start);
+						}
 					}
 
-					data.path = None;
+					delete_readout(data, responses);
 
 					Ready
 				}
-				(Drawing, Abort) => {
+				(Drawing | Chaining, Abort) => {
 					data.snap_handler.cleanup(responses);
 					responses.push_back(DocumentMessage::AbortTransaction.into());
-					data.path = None;
+					data.points.clear();
+					forget_line(data);
+					delete_readout(data, responses);
+					Ready
+				}
+				(Ready, Abort) => {
+					forget_line(data);
+					Ready
+				}
+				(Ready, SetDimensions { length, angle }) => {
+					let line_exists = data.path.as_ref().map_or(false, |path| document.graphene_document.layer(path).is_ok());
+					let (current_length, current_angle) = match data.dimensions {
+						Some(dimensions) if line_exists => dimensions,
+						_ => {
+							// The line was undone or deleted since it was drawn
+							forget_line(data);
+							return Ready;
+						}
+					};
+					let (length, angle) = (length.unwrap_or(current_length), angle.unwrap_or(current_angle).to_radians());
+
+					// The angle is counterclockwise, but the Y axis points down in the viewport
+					let direction = DVec2::new(angle.cos(), -angle.sin());
+					let viewport_length = length / document.viewport_to_document_vector(direction).length();
+
+					// The angle is locked to the entered one, which keeps it even when the length is zero
+					data.drag_start = document.document_to_viewport(data.document_anchor);
+					data.drag_current = data.drag_start + direction * if data.from_center { viewport_length / 2. } else { viewport_length };
+					data.angle = direction.y.atan2(direction.x);
+
+					responses.push_back(DocumentMessage::StartTransaction.into());
+					generate_transform(data, true, None, data.from_center, responses);
+					responses.push_back(DocumentMessage::CommitTransaction.into());
+					data.dimensions = Some(line_dimensions(data, document));
+
 					Ready
 				}
 				_ => self,
@@ -210,7 +637,8 @@ impl Fsm for LineToolFsmState {
 		}
 	}
 
-	fn update_hints(&self, responses: &mut VecDeque<Message>) {
+	fn update_hints(&self, _tool_data: &DocumentToolData, input: &InputPreprocessorMessageHandler, responses: &mut VecDeque<Message>) {
+		let bindings = input.modifier_bindings;
 		let hint_data = match self {
 			LineToolFsmState::Ready => HintData(vec![HintGroup(vec![
 				HintInfo {
@@ -220,19 +648,19 @@ impl Fsm for LineToolFsmState {
 					plus: false,
 				},
 				HintInfo {
-					key_groups: vec![KeysGroup(vec![Key::KeyShift])],
+					key_groups: vec![KeysGroup(vec![bindings.constrain])],
 					mouse: None,
-					label: String::from("Snap 15°"),
+					label: String::from("Snap Angle"),
 					plus: true,
 				},
 				HintInfo {
-					key_groups: vec![KeysGroup(vec![Key::KeyAlt])],
+					key_groups: vec![KeysGroup(vec![bindings.center])],
 					mouse: None,
 					label: String::from("From Center"),
 					plus: true,
 				},
 				HintInfo {
-					key_groups: vec![KeysGroup(vec![Key::KeyControl])],
+					key_groups: vec![KeysGroup(vec![Key::KeyPrimaryModifier])],
 					mouse: None,
 					label: String::from("Lock Angle"),
 					plus: true,
@@ -240,24 +668,44 @@ impl Fsm for LineToolFsmState {
 			])]),
 			LineToolFsmState::Drawing => HintData(vec![HintGroup(vec![
 				HintInfo {
-					key_groups: vec![KeysGroup(vec![Key::KeyShift])],
+					key_groups: vec![KeysGroup(vec![bindings.constrain])],
 					mouse: None,
-					label: String::from("Snap 15°"),
+					label: String::from("Snap Angle"),
 					plus: false,
 				},
 				HintInfo {
-					key_groups: vec![KeysGroup(vec![Key::KeyAlt])],
+					key_groups: vec![KeysGroup(vec![bindings.center])],
 					mouse: None,
 					label: String::from("From Center"),
 					plus: false,
 				},
 				HintInfo {
-					key_groups: vec![KeysGroup(vec![Key::KeyControl])],
+					key_groups: vec![KeysGroup(vec![Key::KeyPrimaryModifier])],
 					mouse: None,
 					label: String::from("Lock Angle"),
 					plus: false,
 				},
 			])]),
+			LineToolFsmState::Chaining => HintData(vec![
+				HintGroup(vec![HintInfo {
+					key_groups: vec![],
+					mouse: Some(MouseMotion::Lmb),
+					label: String::from("Add Point"),
+					plus: false,
+				}]),
+				HintGroup(vec![HintInfo {
+					key_groups: vec![KeysGroup(vec![Key::KeyEnter])],
+					mouse: None,
+					label: String::from("End Polyline"),
+					plus: false,
+				}]),
+				HintGroup(vec![HintInfo {
+					key_groups: vec![],
+					mouse: Some(MouseMotion::Rmb),
+					label: String::from("Cancel Polyline"),
+					plus: false,
+				}]),
+			]),
 		};
 
 		responses.push_back(FrontendMessage::UpdateInputHints { hint_data }.into());
@@ -268,7 +716,51 @@ impl Fsm for LineToolFsmState {
 	}
 }
 
-fn generate_transform(data: &mut LineToolData, lock: bool, snap: bool, center: bool) -> Message {
+/// With `snap_angle`, the angle of the line snaps to multiples of that many degrees.
+/// Any caps are placed on the ends of the line, facing outward along it.
+fn generate_transform(data: &mut LineToolData, lock: bool, snap_angle: Option<f64>, center: bool, responses: &mut VecDeque<Message>) {
+	let start = constrain_line(data, lock, snap_angle, center);
+	let (angle, scale) = (data.angle, data.scale);
+
+	// The line is drawn from `start` in the direction of `angle`, but a negative scale flips it to point the other way
+	let direction = DVec2::new(angle.cos(), angle.sin()) * scale.signum();
+	let end = start + direction * scale.abs();
+	let weight = data.weight as f64;
+	let [(start_cap, _), (end_cap, _)] = &data.caps;
+	let (start_inset, end_inset) = (start_cap.inset(weight), end_cap.inset(weight));
+
+	let transform = if start_inset + end_inset > 0. {
+		let length = (scale.abs() - start_inset - end_inset).max(0.);
+		DAffine2::from_scale_angle_translation(DVec2::new(length, 1.), direction.y.atan2(direction.x), start + direction * start_inset)
+	} else {
+		DAffine2::from_scale_angle_translation(DVec2::new(scale, 1.), angle, start)
+	};
+	responses.push_back(
+		Operation::SetLayerTransformInViewport {
+			path: data.path.clone().unwrap(),
+			transform: transform.to_cols_array(),
+		}
+		.into(),
+	);
+
+	for ((cap, path), (position, outward)) in data.caps.iter().zip([(start, -direction), (end, direction)]) {
+		if let Some(path) = path {
+			let size = cap.size(weight);
+			let transform = DAffine2::from_scale_angle_translation(DVec2::splat(size), outward.y.atan2(outward.x), position) * cap.local_transform();
+			responses.push_back(
+				Operation::SetLayerTransformInViewport {
+					path: path.clone(),
+					transform: transform.to_cols_array(),
+				}
+				.into(),
+			);
+		}
+	}
+}
+
+/// Works out the angle and signed length of the line from the drag, storing them in `data`, and returns where the line starts.
+/// With `lock`, the line keeps its last angle, and with `center`, it's drawn out both ways from the start of the drag.
+fn constrain_line(data: &mut LineToolData, lock: bool, snap_angle: Option<f64>, center: bool) -> ViewportPosition {
 	let mut start = data.drag_start;
 	let stop = data.drag_current;
 
@@ -280,8 +772,8 @@ fn generate_transform(data: &mut LineToolData, lock: bool, snap: bool, center: b
 		angle = data.angle
 	};
 
-	if snap {
-		let snap_resolution = LINE_ROTATE_SNAP_ANGLE.to_radians();
+	if let Some(snap_angle) = snap_angle {
+		let snap_resolution = snap_angle.to_radians();
 		angle = (angle / snap_resolution).round() * snap_resolution;
 	}
 
@@ -299,9 +791,99 @@ fn generate_transform(data: &mut LineToolData, lock: bool, snap: bool, center: b
 		scale *= 2.;
 	}
 
-	Operation::SetLayerTransformInViewport {
-		path: data.path.clone().unwrap(),
-		transform: glam::DAffine2::from_scale_angle_translation(DVec2::new(scale, 1.), angle, start).to_cols_array(),
+	data.scale = scale;
+
+	start
+}
+
+/// The end of the line from the start of the drag, as last worked out by `constrain_line` without `center`
+fn line_end(data: &LineToolData) -> ViewportPosition {
+	data.drag_start + data.scale * DVec2::new(data.angle.cos(), data.angle.sin())
+}
+
+/// Sets the path of the polyline to run through its points, then on to `end` in viewport space if the next line is being drawn
+fn redraw_polyline(data: &LineToolData, document: &DocumentMessageHandler, end: Option<ViewportPosition>, responses: &mut VecDeque<Message>) {
+	let mut bez_path = BezPath::new();
+	let points = data.points.iter().map(|&point| document.document_to_viewport(point));
+	for (index, point) in points.chain(end).enumerate() {
+		let point = Point::new(point.x, point.y);
+		if index == 0 {
+			bez_path.move_to(point);
+		} else {
+			bez_path.line_to(point);
+		}
+	}
+
+	// The path is built in viewport space, so the layer is put there too
+	responses.push_back(
+		Operation::SetShapePathInViewport {
+			path: data.path.clone().unwrap(),
+			bez_path,
+			transform: DAffine2::IDENTITY.to_cols_array(),
+		}
+		.into(),
+	);
+}
+
+/// Shows the angle and length of the line being drawn in a label next to the cursor.
+/// The angle is the one the line is drawn at, so it reflects any snapping, and is measured counterclockwise from the viewport's X axis.
+fn update_readout(data: &mut LineToolData, document: &DocumentMessageHandler, cursor: ViewportPosition, responses: &mut VecDeque<Message>) {
+	let (length, angle) = line_dimensions(data, document);
+
+	// Adding zero turns a negative zero into a positive one, so it isn't shown with a minus sign
+	let round = |value: f64| (value * 100.).round() / 100. + 0.;
+	let new_text = format!("{}° {}", round(angle), round(length));
+	let transform = DAffine2::from_translation(cursor + DVec2::splat(READOUT_CURSOR_OFFSET)).to_cols_array();
+
+	match &data.readout_path {
+		Some(path) => {
+			responses.push_back(DocumentMessage::Overlays(Operation::SetLayerTransform { path: path.clone(), transform }.into()).into());
+			responses.push_back(DocumentMessage::Overlays(Operation::SetTextContent { path: path.clone(), new_text }.into()).into());
+		}
+		None => {
+			let path = vec![generate_uuid()];
+			responses.push_back(
+				DocumentMessage::Overlays(
+					Operation::AddText {
+						path: path.clone(),
+						transform,
+						insert_index: -1,
+						text: new_text,
+						style: style::PathStyle::new(None, Some(Fill::new(COLOR_ACCENT))),
+						size: MEASUREMENT_LABEL_FONT_SIZE,
+					}
+					.into(),
+				)
+				.into(),
+			);
+			data.readout_path = Some(path);
+		}
+	}
+}
+
+/// The length of the line in document units, and its angle in degrees counterclockwise from the viewport's X axis
+fn line_dimensions(data: &LineToolData, document: &DocumentMessageHandler) -> (f64, f64) {
+	let line_vector = data.scale * DVec2::new(data.angle.cos(), data.angle.sin());
+
+	// The Y axis points down in the viewport, so it is flipped to measure the angle counterclockwise, then normalized to the range (-180°, 180°]
+	let mut angle = (-line_vector.y).atan2(line_vector.x).to_degrees();
+	if angle <= -180. {
+		angle += 360.;
+	}
+	let length = document.viewport_to_document_vector(line_vector).length();
+
+	(length, angle)
+}
+
+/// Stops tracking the last line drawn, so it can no longer be given exact dimensions
+fn forget_line(data: &mut LineToolData) {
+	data.path = None;
+	data.caps = Default::default();
+	data.dimensions = None;
+}
+
+fn delete_readout(data: &mut LineToolData, responses: &mut VecDeque<Message>) {
+	if let Some(path) = data.readout_path.take() {
+		responses.push_back(DocumentMessage::Overlays(Operation::DeleteLayer { path }.into()).into());
 	}
-	.into()
 }
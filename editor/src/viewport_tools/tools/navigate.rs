@@ -30,6 +30,7 @@ pub enum NavigateMessage {
 	},
 	PointerMove {
 		snap_angle: Key,
+		snap_angle_to_selection: Key,
 		snap_zoom: Key,
 	},
 	RotateCanvasBegin,
@@ -40,10 +41,12 @@ pub enum NavigateMessage {
 
 impl PropertyHolder for Navigate {}
 
+consumes_input_unless_ready!(Navigate);
+
 impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Navigate {
 	fn process_action(&mut self, action: ToolMessage, data: ToolActionHandlerData<'a>, responses: &mut VecDeque<Message>) {
 		if action == ToolMessage::UpdateHints {
-			self.fsm_state.update_hints(responses);
+			self.fsm_state.update_hints(data.1, data.2, responses);
 			return;
 		}
 
@@ -56,7 +59,7 @@ impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Navigate {
 
 		if self.fsm_state != new_state {
 			self.fsm_state = new_state;
-			self.fsm_state.update_hints(responses);
+			self.fsm_state.update_hints(data.1, data.2, responses);
 			self.fsm_state.update_cursor(responses);
 		}
 	}
@@ -122,10 +125,15 @@ impl Fsm for NavigateToolFsmState {
 
 					NavigateToolFsmState::Ready
 				}
-				PointerMove { snap_angle, snap_zoom } => {
+				PointerMove {
+					snap_angle,
+					snap_angle_to_selection,
+					snap_zoom,
+				} => {
 					messages.push_front(
 						MovementMessage::PointerMove {
 							snap_angle,
+							snap_angle_to_selection,
 							wait_for_snap_angle_release: false,
 							snap_zoom,
 							zoom_from_viewport: Some(data.drag_start),
@@ -163,7 +171,8 @@ impl Fsm for NavigateToolFsmState {
 		}
 	}
 
-	fn update_hints(&self, responses: &mut VecDeque<Message>) {
+	fn update_hints(&self, _tool_data: &DocumentToolData, input: &InputPreprocessorMessageHandler, responses: &mut VecDeque<Message>) {
+		let bindings = input.modifier_bindings;
 		let hint_data = match self {
 			NavigateToolFsmState::Ready => HintData(vec![
 				HintGroup(vec![
@@ -174,7 +183,7 @@ impl Fsm for NavigateToolFsmState {
 						plus: false,
 					},
 					HintInfo {
-						key_groups: vec![KeysGroup(vec![Key::KeyShift])],
+						key_groups: vec![KeysGroup(vec![bindings.constrain])],
 						mouse: None,
 						label: String::from("Zoom Out"),
 						plus: true,
@@ -188,7 +197,7 @@ impl Fsm for NavigateToolFsmState {
 						plus: false,
 					},
 					HintInfo {
-						key_groups: vec![KeysGroup(vec![Key::KeyControl])],
+						key_groups: vec![KeysGroup(vec![Key::KeyPrimaryModifier])],
 						mouse: None,
 						label: String::from("Snap Increments"),
 						plus: true,
@@ -208,21 +217,29 @@ impl Fsm for NavigateToolFsmState {
 						plus: false,
 					},
 					HintInfo {
-						key_groups: vec![KeysGroup(vec![Key::KeyControl])],
+						key_groups: vec![KeysGroup(vec![Key::KeyPrimaryModifier])],
 						mouse: None,
-						label: String::from("Snap 15°"),
+						label: String::from("Snap Angle"),
 						plus: true,
 					},
 				]),
 			]),
-			NavigateToolFsmState::Tilting => HintData(vec![HintGroup(vec![HintInfo {
-				key_groups: vec![KeysGroup(vec![Key::KeyControl])],
-				mouse: None,
-				label: String::from("Snap 15°"),
-				plus: false,
-			}])]),
+			NavigateToolFsmState::Tilting => HintData(vec![HintGroup(vec![
+				HintInfo {
+					key_groups: vec![KeysGroup(vec![Key::KeyPrimaryModifier])],
+					mouse: None,
+					label: String::from("Snap Angle"),
+					plus: false,
+				},
+				HintInfo {
+					key_groups: vec![KeysGroup(vec![bindings.constrain])],
+					mouse: None,
+					label: String::from("Snap to Selection"),
+					plus: false,
+				},
+			])]),
 			NavigateToolFsmState::Zooming => HintData(vec![HintGroup(vec![HintInfo {
-				key_groups: vec![KeysGroup(vec![Key::KeyControl])],
+				key_groups: vec![KeysGroup(vec![Key::KeyPrimaryModifier])],
 				mouse: None,
 				label: String::from("Snap Increments"),
 				plus: false,
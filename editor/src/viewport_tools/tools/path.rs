@@ -1,9 +1,9 @@
-use crate::consts::SELECTION_THRESHOLD;
 use crate::document::DocumentMessageHandler;
 use crate::frontend::utility_types::MouseCursorIcon;
 use crate::input::keyboard::{Key, MouseMotion};
 use crate::input::InputPreprocessorMessageHandler;
-use crate::layout::widgets::PropertyHolder;
+use crate::layout::layout_message::LayoutTarget;
+use crate::layout::widgets::{LayoutRow, PropertyHolder, RadioEntryData, RadioInput, Widget, WidgetCallback, WidgetHolder, WidgetLayout};
 use crate::message_prelude::*;
 use crate::misc::{HintData, HintGroup, HintInfo, KeysGroup};
 use crate::viewport_tools::snapping::SnapHandler;
@@ -19,6 +19,18 @@ use serde::{Deserialize, Serialize};
 pub struct Path {
 	fsm_state: PathToolFsmState,
 	data: PathToolData,
+	options: PathOptions,
+}
+
+pub struct PathOptions {
+	/// Whether every anchor shows its handles, rather than only the anchors with a selected point
+	show_all_handles: bool,
+}
+
+impl Default for PathOptions {
+	fn default() -> Self {
+		Self { show_all_handles: true }
+	}
 }
 
 #[remain::sorted]
@@ -42,14 +54,48 @@ pub enum PathMessage {
 		alt_mirror_angle: Key,
 		shift_mirror_distance: Key,
 	},
+	UpdateOptions(PathOptionsUpdate),
 }
 
-impl PropertyHolder for Path {}
+#[remain::sorted]
+#[derive(PartialEq, Clone, Debug, Hash, Serialize, Deserialize)]
+pub enum PathOptionsUpdate {
+	ShowAllHandles(bool),
+}
+
+impl PropertyHolder for Path {
+	fn properties(&self) -> WidgetLayout {
+		WidgetLayout::new(vec![LayoutRow::Row {
+			name: "".into(),
+			widgets: vec![WidgetHolder::new(Widget::RadioInput(RadioInput {
+				selected_index: if self.options.show_all_handles { 0 } else { 1 },
+				entries: vec![
+					RadioEntryData {
+						value: "all".into(),
+						label: "All Handles".into(),
+						tooltip: "Show the handles of every point".into(),
+						on_update: WidgetCallback::new(|_| PathMessage::UpdateOptions(PathOptionsUpdate::ShowAllHandles(true)).into()),
+						..RadioEntryData::default()
+					},
+					RadioEntryData {
+						value: "selected".into(),
+						label: "Selected Handles".into(),
+						tooltip: "Only show the handles of selected points".into(),
+						on_update: WidgetCallback::new(|_| PathMessage::UpdateOptions(PathOptionsUpdate::ShowAllHandles(false)).into()),
+						..RadioEntryData::default()
+					},
+				],
+			}))],
+		}])
+	}
+}
+
+consumes_input_unless_ready!(Path);
 
 impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Path {
 	fn process_action(&mut self, action: ToolMessage, data: ToolActionHandlerData<'a>, responses: &mut VecDeque<Message>) {
 		if action == ToolMessage::UpdateHints {
-			self.fsm_state.update_hints(responses);
+			self.fsm_state.update_hints(data.1, data.2, responses);
 			return;
 		}
 
@@ -58,11 +104,22 @@ impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Path {
 			return;
 		}
 
-		let new_state = self.fsm_state.transition(action, data.0, data.1, &mut self.data, &(), data.2, responses);
+		if let ToolMessage::Path(PathMessage::UpdateOptions(action)) = action {
+			match action {
+				PathOptionsUpdate::ShowAllHandles(show_all_handles) => {
+					self.options.show_all_handles = show_all_handles;
+					self.register_properties(responses, LayoutTarget::ToolOptions);
+					self.data.shape_editor.update_handle_visibility(show_all_handles, responses);
+				}
+			}
+			return;
+		}
+
+		let new_state = self.fsm_state.transition(action, data.0, data.1, &mut self.data, &self.options, data.2, responses);
 
 		if self.fsm_state != new_state {
 			self.fsm_state = new_state;
-			self.fsm_state.update_hints(responses);
+			self.fsm_state.update_hints(data.1, data.2, responses);
 			self.fsm_state.update_cursor(responses);
 		}
 	}
@@ -100,15 +157,15 @@ struct PathToolData {
 
 impl Fsm for PathToolFsmState {
 	type ToolData = PathToolData;
-	type ToolOptions = ();
+	type ToolOptions = PathOptions;
 
 	fn transition(
 		self,
 		event: ToolMessage,
 		document: &DocumentMessageHandler,
-		_tool_data: &DocumentToolData,
+		tool_data: &DocumentToolData,
 		data: &mut Self::ToolData,
-		_tool_options: &Self::ToolOptions,
+		tool_options: &Self::ToolOptions,
 		input: &InputPreprocessorMessageHandler,
 		responses: &mut VecDeque<Message>,
 	) -> Self {
@@ -125,6 +182,7 @@ impl Fsm for PathToolFsmState {
 					// This currently creates new VectorManipulatorShapes for every shape, which is not ideal
 					// At least it is only on selection change for now
 					data.shape_editor.set_shapes_to_modify(document.selected_visible_layers_vector_shapes(responses));
+					data.shape_editor.update_handle_visibility(tool_options.show_all_handles, responses);
 
 					self
 				}
@@ -137,10 +195,14 @@ impl Fsm for PathToolFsmState {
 				}
 				// Mouse down
 				(_, DragStart { add_to_selection }) => {
-					let add_to_selection = input.keyboard.get(add_to_selection as usize);
+					let add_to_selection = input.is_pressed(add_to_selection);
 
 					// Select the first point within the threshold (in pixels)
-					if data.shape_editor.select_point(input.mouse.position, SELECTION_THRESHOLD, add_to_selection, responses) {
+					let (threshold, mirror_handles) = (tool_data.point_selection_threshold, tool_data.mirror_handles);
+					if data.shape_editor.select_point(input.mouse.position, threshold, add_to_selection, mirror_handles, responses) {
+						// Reveal the handles of the newly selected point right away
+						data.shape_editor.update_handle_visibility(tool_options.show_all_handles, responses);
+
 						responses.push_back(DocumentMessage::StartTransaction.into());
 						data.snap_handler.start_snap(document, document.bounding_boxes(None, None), true, true);
 						let snap_points = data
@@ -173,7 +235,7 @@ impl Fsm for PathToolFsmState {
 							}
 						} else {
 							// Clear the previous selection if we didn't find anything
-							if !input.keyboard.get(add_to_selection as usize) {
+							if !input.is_pressed(add_to_selection) {
 								responses.push_back(DocumentMessage::DeselectAllLayers.into());
 							}
 						}
@@ -189,7 +251,7 @@ impl Fsm for PathToolFsmState {
 					},
 				) => {
 					// Determine when alt state changes
-					let alt_pressed = input.keyboard.get(alt_mirror_angle as usize);
+					let alt_pressed = input.is_pressed(alt_mirror_angle);
 					if alt_pressed != data.alt_debounce {
 						data.alt_debounce = alt_pressed;
 						// Only on alt down
@@ -199,7 +261,7 @@ impl Fsm for PathToolFsmState {
 					}
 
 					// Determine when shift state changes
-					let shift_pressed = input.keyboard.get(shift_mirror_distance as usize);
+					let shift_pressed = input.is_pressed(shift_mirror_distance);
 					if shift_pressed != data.shift_debounce {
 						data.shift_debounce = shift_pressed;
 						data.shape_editor.toggle_selected_mirror_distance();
@@ -215,8 +277,14 @@ impl Fsm for PathToolFsmState {
 					data.snap_handler.cleanup(responses);
 					Ready
 				}
-				(_, Abort) => {
+				(state, Abort) => {
+					// Put back any points that were partway through being dragged
+					if state == Dragging {
+						responses.push_back(DocumentMessage::AbortTransaction.into());
+					}
+
 					data.shape_editor.remove_overlays(responses);
+					data.snap_handler.cleanup(responses);
 					Ready
 				}
 				(
@@ -226,13 +294,18 @@ impl Fsm for PathToolFsmState {
 						shift_mirror_distance: _,
 					},
 				) => self,
+				// Options are updated before the state machine is reached
+				(_, UpdateOptions(_)) => self,
 			}
 		} else {
 			self
 		}
 	}
 
-	fn update_hints(&self, responses: &mut VecDeque<Message>) {
+	fn update_hints(&self, tool_data: &DocumentToolData, input: &InputPreprocessorMessageHandler, responses: &mut VecDeque<Message>) {
+		// The hints depend on whether handles are mirrored by default, which decides what holding the center modifier does while dragging
+		let mirror_handles = tool_data.mirror_handles;
+		let bindings = input.modifier_bindings;
 		let hint_data = match self {
 			PathToolFsmState::Ready => HintData(vec![
 				HintGroup(vec![
@@ -243,7 +316,7 @@ impl Fsm for PathToolFsmState {
 						plus: false,
 					},
 					HintInfo {
-						key_groups: vec![KeysGroup(vec![Key::KeyShift])],
+						key_groups: vec![KeysGroup(vec![bindings.constrain])],
 						mouse: None,
 						label: String::from("Grow/Shrink Selection"),
 						plus: true,
@@ -297,13 +370,13 @@ impl Fsm for PathToolFsmState {
 			]),
 			PathToolFsmState::Dragging => HintData(vec![HintGroup(vec![
 				HintInfo {
-					key_groups: vec![KeysGroup(vec![Key::KeyAlt])],
+					key_groups: vec![KeysGroup(vec![bindings.center])],
 					mouse: None,
-					label: String::from("Split/Align Handles (Toggle)"),
+					label: String::from(if mirror_handles { "Split/Align Handles (Toggle)" } else { "Mirror Handles (Toggle)" }),
 					plus: false,
 				},
 				HintInfo {
-					key_groups: vec![KeysGroup(vec![Key::KeyShift])],
+					key_groups: vec![KeysGroup(vec![bindings.constrain])],
 					mouse: None,
 					label: String::from("Share Lengths of Aligned Handles"),
 					plus: false,
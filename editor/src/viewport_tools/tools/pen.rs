@@ -1,3 +1,4 @@
+use super::shared::overlay_pool::{OverlayPool, OverlayRole};
 use crate::consts::DRAG_THRESHOLD;
 use crate::document::DocumentMessageHandler;
 use crate::frontend::utility_types::MouseCursorIcon;
@@ -9,10 +10,10 @@ use crate::misc::{HintData, HintGroup, HintInfo, KeysGroup};
 use crate::viewport_tools::snapping::SnapHandler;
 use crate::viewport_tools::tool::{DocumentToolData, Fsm, ToolActionHandlerData};
 
-use graphene::layers::style;
 use graphene::Operation;
 
 use glam::{DAffine2, DVec2};
+use kurbo::BezPath;
 use serde::{Deserialize, Serialize};
 
 #[derive(Default)]
@@ -39,12 +40,18 @@ pub enum PenMessage {
 	// Standard messages
 	#[remain::unsorted]
 	Abort,
+	#[remain::unsorted]
+	DocumentIsDirty,
 
 	// Tool-specific messages
 	Confirm,
 	DragStart,
 	DragStop,
-	PointerMove,
+	PointerMove {
+		break_handle: Key,
+	},
+	/// Takes back the most recently placed anchor, ending the path without keeping it once none are left
+	RemoveLastPoint,
 	Undo,
 	UpdateOptions(PenOptionsUpdate),
 }
@@ -53,6 +60,7 @@ pub enum PenMessage {
 enum PenToolFsmState {
 	Ready,
 	Drawing,
+	DraggingHandle,
 }
 
 #[remain::sorted]
@@ -78,10 +86,12 @@ impl PropertyHolder for Pen {
 	}
 }
 
+consumes_input_unless_ready!(Pen);
+
 impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Pen {
 	fn process_action(&mut self, action: ToolMessage, data: ToolActionHandlerData<'a>, responses: &mut VecDeque<Message>) {
 		if action == ToolMessage::UpdateHints {
-			self.fsm_state.update_hints(responses);
+			self.fsm_state.update_hints(data.1, data.2, responses);
 			return;
 		}
 
@@ -101,7 +111,7 @@ impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Pen {
 
 		if self.fsm_state != new_state {
 			self.fsm_state = new_state;
-			self.fsm_state.update_hints(responses);
+			self.fsm_state.update_hints(data.1, data.2, responses);
 			self.fsm_state.update_cursor(responses);
 		}
 	}
@@ -111,7 +121,8 @@ impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Pen {
 
 		match self.fsm_state {
 			Ready => actions!(PenMessageDiscriminant; Undo, DragStart, DragStop, Confirm, Abort),
-			Drawing => actions!(PenMessageDiscriminant; DragStop, PointerMove, Confirm, Abort),
+			Drawing => actions!(PenMessageDiscriminant; DragStart, DragStop, PointerMove, RemoveLastPoint, Confirm, Abort),
+			DraggingHandle => actions!(PenMessageDiscriminant; DragStop, PointerMove, Confirm, Abort),
 		}
 	}
 }
@@ -121,13 +132,67 @@ impl Default for PenToolFsmState {
 		PenToolFsmState::Ready
 	}
 }
+
+/// An anchor of the path being drawn, along with the handles pulling the curve on either side of it, which sit on the anchor itself where the path has a sharp corner
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct PenAnchor {
+	position: DVec2,
+	in_handle: DVec2,
+	out_handle: DVec2,
+}
+
+impl PenAnchor {
+	fn corner(position: DVec2) -> Self {
+		Self {
+			position,
+			in_handle: position,
+			out_handle: position,
+		}
+	}
+
+	fn document_to_viewport(&self, document: &DocumentMessageHandler) -> Self {
+		Self {
+			position: document.document_to_viewport(self.position),
+			in_handle: document.document_to_viewport(self.in_handle),
+			out_handle: document.document_to_viewport(self.out_handle),
+		}
+	}
+}
+
 #[derive(Clone, Debug, Default)]
 struct PenToolData {
-	points: Vec<DVec2>,
+	points: Vec<PenAnchor>,
 	next_point: DVec2,
+	/// Where the pointer went down to place the anchor whose handles are being dragged out, in viewport coords
+	drag_start: DVec2,
 	weight: u32,
+	/// Whether the path being finished loops back around to its first anchor
+	closed: bool,
 	path: Option<Vec<LayerId>>,
+	/// The overlay showing the segment that clicking would add, from the last anchor to the pointer
+	preview_segment: Option<Vec<LayerId>>,
+	overlay_pool: OverlayPool,
 	snap_handler: SnapHandler,
+	cursor: MouseCursorIcon,
+}
+
+impl PenToolData {
+	/// The start of the path in viewport coords, once there are enough points to close the path there, which is the only point of the path being drawn that is snapped onto.
+	/// The other points already placed are left out so the cursor doesn't stick to the anchors it just left.
+	fn closing_snap_targets(&self, document: &DocumentMessageHandler) -> Vec<DVec2> {
+		match self.points.as_slice() {
+			[start, _, ..] => vec![document.document_to_viewport(start.position)],
+			_ => Vec::new(),
+		}
+	}
+
+	/// Whether the pointer is on the start of the path, within the distance in viewport pixels that the Path tool selects anchors from, once there are enough anchors to close the path there.
+	fn pointer_over_start(&self, document: &DocumentMessageHandler, tool_data: &DocumentToolData, pointer: DVec2) -> bool {
+		match self.points.as_slice() {
+			[start, _, ..] => document.document_to_viewport(start.position).distance(pointer) <= tool_data.point_selection_threshold,
+			_ => false,
+		}
+	}
 }
 
 impl Fsm for PenToolFsmState {
@@ -147,8 +212,6 @@ impl Fsm for PenToolFsmState {
 		use PenMessage::*;
 		use PenToolFsmState::*;
 
-		let transform = document.graphene_document.root.transform;
-
 		if let ToolMessage::Pen(event) = event {
 			match (self, event) {
 				(Ready, DragStart) => {
@@ -159,56 +222,124 @@ impl Fsm for PenToolFsmState {
 					data.snap_handler.start_snap(document, document.bounding_boxes(None, None), true, true);
 					let snapped_position = data.snap_handler.snap_position(responses, input.viewport_bounds.size(), document, input.mouse.position);
 
-					let pos = transform.inverse().transform_point2(snapped_position);
+					let pos = document.viewport_to_document(snapped_position);
 
-					data.points.push(pos);
+					data.points.push(PenAnchor::corner(pos));
 					data.next_point = pos;
+					data.drag_start = input.mouse.position;
 
 					data.weight = tool_options.line_weight;
 
-					responses.push_back(add_polyline(data, tool_data, true));
+					responses.push_back(add_path(data, tool_data));
 
-					Drawing
+					DraggingHandle
 				}
-				(Drawing, DragStop) => {
+				(Drawing, DragStart) => {
+					// Clicking the start of the path closes it once the pointer is released there
+					if data.pointer_over_start(document, tool_data, input.mouse.position) {
+						return Drawing;
+					}
+
+					data.snap_handler.set_point_targets(data.closing_snap_targets(document));
 					let snapped_position = data.snap_handler.snap_position(responses, input.viewport_bounds.size(), document, input.mouse.position);
-					let pos = transform.inverse().transform_point2(snapped_position);
+					let pos = document.viewport_to_document(snapped_position);
 
-					if let Some(last_pos) = data.points.last() {
-						if last_pos.distance(pos) > DRAG_THRESHOLD {
-							data.points.push(pos);
-							data.next_point = pos;
+					// Pressing on the last anchor again drags its handles back out instead of stacking another anchor on top of it
+					if let Some(last) = data.points.last() {
+						if last.position.distance(pos) > DRAG_THRESHOLD {
+							data.points.push(PenAnchor::corner(pos));
 						}
 					}
+					data.drag_start = input.mouse.position;
 
+					remove_preview_segment(data, responses);
 					responses.push_back(remove_preview(data));
-					responses.push_back(add_polyline(data, tool_data, true));
+					responses.push_back(add_path(data, tool_data));
+
+					DraggingHandle
+				}
+				(Drawing, DragStop) => {
+					// Clicking the start of the path closes it there and finishes it
+					if data.pointer_over_start(document, tool_data, input.mouse.position) {
+						data.closed = true;
+						finish_path(data, tool_data, responses);
+
+						return Ready;
+					}
 
 					Drawing
 				}
-				(Drawing, PointerMove) => {
+				(DraggingHandle, DragStop) => {
+					data.next_point = document.viewport_to_document(input.mouse.position);
+					update_preview_segment(data, document, responses);
+
+					Drawing
+				}
+				(DraggingHandle, PointerMove { break_handle }) => {
+					let pointer = document.viewport_to_document(input.mouse.position);
+					let dragged = input.mouse.position.distance(data.drag_start) > DRAG_THRESHOLD;
+
+					if let Some(anchor) = data.points.last_mut() {
+						// The outgoing handle follows the pointer, and the incoming one mirrors it unless Alt breaks them apart into a corner
+						anchor.out_handle = if dragged { pointer } else { anchor.position };
+						if !input.is_pressed(break_handle) {
+							anchor.in_handle = anchor.position * 2. - anchor.out_handle;
+						}
+					}
+
+					responses.push_back(remove_preview(data));
+					responses.push_back(add_path(data, tool_data));
+
+					DraggingHandle
+				}
+				(Drawing, PointerMove { .. }) => {
+					data.snap_handler.set_point_targets(data.closing_snap_targets(document));
 					let snapped_position = data.snap_handler.snap_position(responses, input.viewport_bounds.size(), document, input.mouse.position);
-					let pos = transform.inverse().transform_point2(snapped_position);
+					let pos = document.viewport_to_document(snapped_position);
 					data.next_point = pos;
+					update_preview_segment(data, document, responses);
 
-					responses.push_back(remove_preview(data));
-					responses.push_back(add_polyline(data, tool_data, true));
+					// Show that clicking would close the path while the pointer is on its start
+					let cursor = if data.pointer_over_start(document, tool_data, input.mouse.position) {
+						MouseCursorIcon::Pointer
+					} else {
+						MouseCursorIcon::Default
+					};
+					if data.cursor != cursor {
+						data.cursor = cursor;
+						responses.push_back(FrontendMessage::UpdateMouseCursor { cursor }.into());
+					}
 
 					Drawing
 				}
-				(Drawing, Confirm) | (Drawing, Abort) => {
-					if data.points.len() >= 2 {
-						responses.push_back(DocumentMessage::DeselectAllLayers.into());
-						responses.push_back(remove_preview(data));
-						responses.push_back(add_polyline(data, tool_data, false));
-						responses.push_back(DocumentMessage::CommitTransaction.into());
+				(Drawing, DocumentIsDirty) => {
+					// Keeps the segment on the last anchor when the canvas is panned or zoomed
+					update_preview_segment(data, document, responses);
+
+					Drawing
+				}
+				(Drawing, RemoveLastPoint) => {
+					data.points.pop();
+
+					if data.points.is_empty() {
+						finish_path(data, tool_data, responses);
+
+						Ready
 					} else {
-						responses.push_back(DocumentMessage::AbortTransaction.into());
+						responses.push_back(remove_preview(data));
+						responses.push_back(add_path(data, tool_data));
+						update_preview_segment(data, document, responses);
+
+						Drawing
 					}
+				}
+				(Drawing, Confirm) | (Drawing, Abort) | (DraggingHandle, Confirm) | (DraggingHandle, Abort) => {
+					finish_path(data, tool_data, responses);
 
-					data.path = None;
-					data.points.clear();
-					data.snap_handler.cleanup(responses);
+					Ready
+				}
+				(Ready, Abort) => {
+					data.overlay_pool.clear(responses);
 
 					Ready
 				}
@@ -219,7 +350,8 @@ impl Fsm for PenToolFsmState {
 		}
 	}
 
-	fn update_hints(&self, responses: &mut VecDeque<Message>) {
+	fn update_hints(&self, _tool_data: &DocumentToolData, input: &InputPreprocessorMessageHandler, responses: &mut VecDeque<Message>) {
+		let bindings = input.modifier_bindings;
 		let hint_data = match self {
 			PenToolFsmState::Ready => HintData(vec![HintGroup(vec![HintInfo {
 				key_groups: vec![],
@@ -234,13 +366,37 @@ impl Fsm for PenToolFsmState {
 					label: String::from("Extend Path"),
 					plus: false,
 				}]),
+				HintGroup(vec![HintInfo {
+					key_groups: vec![],
+					mouse: Some(MouseMotion::LmbDrag),
+					label: String::from("Extend with Curve"),
+					plus: false,
+				}]),
+				HintGroup(vec![HintInfo {
+					key_groups: vec![KeysGroup(vec![Key::KeyBackspace])],
+					mouse: None,
+					label: String::from("Remove Last Point"),
+					plus: false,
+				}]),
 				HintGroup(vec![HintInfo {
 					key_groups: vec![KeysGroup(vec![Key::KeyEnter])],
 					mouse: None,
 					label: String::from("End Path"),
 					plus: false,
 				}]),
+				HintGroup(vec![HintInfo {
+					key_groups: vec![],
+					mouse: Some(MouseMotion::Lmb),
+					label: String::from("Click Start to Close"),
+					plus: false,
+				}]),
 			]),
+			PenToolFsmState::DraggingHandle => HintData(vec![HintGroup(vec![HintInfo {
+				key_groups: vec![KeysGroup(vec![bindings.center])],
+				mouse: None,
+				label: String::from("Break Handles"),
+				plus: false,
+			}])]),
 		};
 
 		responses.push_back(FrontendMessage::UpdateInputHints { hint_data }.into());
@@ -251,21 +407,90 @@ impl Fsm for PenToolFsmState {
 	}
 }
 
+/// Commits the path if it has enough anchors to be one, or otherwise discards it.
+fn finish_path(data: &mut PenToolData, tool_data: &DocumentToolData, responses: &mut VecDeque<Message>) {
+	if data.points.len() >= 2 {
+		responses.push_back(DocumentMessage::DeselectAllLayers.into());
+		responses.push_back(remove_preview(data));
+		responses.push_back(add_path(data, tool_data));
+		responses.push_back(DocumentMessage::CommitTransaction.into());
+	} else {
+		responses.push_back(DocumentMessage::AbortTransaction.into());
+	}
+
+	remove_preview_segment(data, responses);
+	data.path = None;
+	data.points.clear();
+	data.closed = false;
+	data.cursor = MouseCursorIcon::Default;
+	data.snap_handler.cleanup(responses);
+}
+
 fn remove_preview(data: &PenToolData) -> Message {
 	Operation::DeleteLayer { path: data.path.clone().unwrap() }.into()
 }
 
-fn add_polyline(data: &PenToolData, tool_data: &DocumentToolData, show_preview: bool) -> Message {
-	let mut points: Vec<(f64, f64)> = data.points.iter().map(|p| (p.x, p.y)).collect();
-	if show_preview {
-		points.push((data.next_point.x, data.next_point.y))
+/// Builds the path through the anchors, curving each segment where a handle has been pulled away from its anchor and looping back to the start if the path is closed.
+fn bez_path(anchors: &[PenAnchor], closed: bool) -> BezPath {
+	let point = |v: DVec2| kurbo::Point::new(v.x, v.y);
+	let segment = |path: &mut BezPath, start: &PenAnchor, end: &PenAnchor| {
+		if start.out_handle == start.position && end.in_handle == end.position {
+			path.line_to(point(end.position));
+		} else {
+			path.curve_to(point(start.out_handle), point(end.in_handle), point(end.position));
+		}
+	};
+
+	let mut path = BezPath::new();
+	if let Some(start) = anchors.first() {
+		path.move_to(point(start.position));
+	}
+	for pair in anchors.windows(2) {
+		segment(&mut path, &pair[0], &pair[1]);
 	}
+	if closed {
+		// A straight closing segment is left to the close itself
+		if let [start, .., end] = anchors {
+			if end.out_handle != end.position || start.in_handle != start.position {
+				segment(&mut path, end, start);
+			}
+		}
+		path.close_path();
+	}
+
+	path
+}
+
+/// Shows the segment from the last anchor to the pointer, curving away from the last anchor along its outgoing handle, in place of the one shown before.
+fn update_preview_segment(data: &mut PenToolData, document: &DocumentMessageHandler, responses: &mut VecDeque<Message>) {
+	let last = match data.points.last() {
+		Some(last) => last.document_to_viewport(document),
+		None => return,
+	};
+	let bez_path = bez_path(&[last, PenAnchor::corner(document.document_to_viewport(data.next_point))], false);
+
+	let mut buffer = Vec::new();
+	let path = data.preview_segment.get_or_insert_with(|| data.overlay_pool.acquire(OverlayRole::PenSegment, &mut buffer)).clone();
+	// A segment taken back from the pool was collapsed when it was released
+	let transform = DAffine2::IDENTITY.to_cols_array();
+	buffer.push(DocumentMessage::Overlays(Operation::SetShapePath { path: path.clone(), bez_path }.into()).into());
+	buffer.push(DocumentMessage::Overlays(Operation::SetLayerTransform { path, transform }.into()).into());
+	responses.extend(buffer);
+}
+
+fn remove_preview_segment(data: &mut PenToolData, responses: &mut VecDeque<Message>) {
+	if let Some(path) = data.preview_segment.take() {
+		data.overlay_pool.release(OverlayRole::PenSegment, path, responses);
+	}
+}
 
-	Operation::AddPolyline {
+fn add_path(data: &PenToolData, tool_data: &DocumentToolData) -> Message {
+	Operation::AddBezPath {
 		path: data.path.clone().unwrap(),
 		insert_index: -1,
 		transform: DAffine2::IDENTITY.to_cols_array(),
-		points,
+		bez_path: bez_path(&data.points, data.closed),
+		closed: data.closed,
 		style: style::PathStyle::new(Some(style::Stroke::new(tool_data.primary_color, data.weight as f32)), None),
 	}
 	.into()
@@ -1,49 +1,224 @@
-use super::shared::resize::Resize;
-use crate::consts::DRAG_THRESHOLD;
+use super::shared::resize::{handle_under, selected_shape, Resize};
+use crate::consts::{COLOR_ACCENT, CORNER_RADIUS_HANDLE_MIN_INSET, DRAG_THRESHOLD};
 use crate::document::DocumentMessageHandler;
 use crate::frontend::utility_types::MouseCursorIcon;
+use crate::input::input_mapper::ModifierBindings;
 use crate::input::keyboard::{Key, MouseMotion};
+use crate::input::mouse::ViewportPosition;
 use crate::input::InputPreprocessorMessageHandler;
-use crate::layout::widgets::PropertyHolder;
+use crate::layout::layout_message::LayoutTarget;
+use crate::layout::widgets::{LayoutRow, NumberInput, OptionalInput, PropertyHolder, Separator, SeparatorDirection, SeparatorType, Widget, WidgetCallback, WidgetHolder, WidgetLayout};
 use crate::message_prelude::*;
 use crate::misc::{HintData, HintGroup, HintInfo, KeysGroup};
 use crate::viewport_tools::tool::{DocumentToolData, Fsm, ToolActionHandlerData};
 
+use graphene::layers::layer_info::LayerDataType;
 use graphene::layers::style;
 use graphene::Operation;
 
-use glam::DAffine2;
+use glam::{DAffine2, DVec2};
 use serde::{Deserialize, Serialize};
 
 #[derive(Default)]
 pub struct Rectangle {
 	fsm_state: RectangleToolFsmState,
 	data: RectangleToolData,
+	options: RectangleOptions,
+}
+
+#[derive(Default)]
+pub struct RectangleOptions {
+	corner_radius: f64,
+	independent_corners: bool,
+	corner_radii: [f64; 4],
+}
+
+impl RectangleOptions {
+	/// The radius of each corner, clockwise from the top left.
+	fn radii(&self) -> [f64; 4] {
+		match self.independent_corners {
+			true => self.corner_radii,
+			false => [self.corner_radius; 4],
+		}
+	}
 }
 
 #[remain::sorted]
 #[impl_message(Message, ToolMessage, Rectangle)]
-#[derive(PartialEq, Clone, Debug, Hash, Serialize, Deserialize)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub enum RectangleMessage {
 	// Standard messages
 	#[remain::unsorted]
 	Abort,
+	#[remain::unsorted]
+	DocumentIsDirty,
 
 	// Tool-specific messages
+	AutoPan,
 	DragStart,
 	DragStop,
 	Resize {
 		center: Key,
 		lock_ratio: Key,
+		rotate: Key,
+		snap_angle: Key,
 	},
+	/// Moves the top left corner of the selected rectangles to `x`, `y` and resizes them to `width` × `height` in document units, keeping whatever is `None`
+	SetBounds {
+		x: Option<f64>,
+		y: Option<f64>,
+		width: Option<f64>,
+		height: Option<f64>,
+	},
+	UpdateOptions(RectangleOptionsUpdate),
+}
+
+#[remain::sorted]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub enum RectangleOptionsUpdate {
+	CornerRadius(f64),
+	IndependentCornerRadius { corner: usize, radius: f64 },
+	IndependentCorners(bool),
 }
 
-impl PropertyHolder for Rectangle {}
+impl PropertyHolder for Rectangle {
+	fn properties(&self) -> WidgetLayout {
+		let corner_radius_input = |label: &str, value: f64, on_update: WidgetCallback<NumberInput>| {
+			WidgetHolder::new(Widget::NumberInput(NumberInput {
+				unit: " px".into(),
+				label: label.into(),
+				value,
+				min: Some(0.),
+				on_update,
+				..NumberInput::default()
+			}))
+		};
+
+		let mut widgets = match self.options.independent_corners {
+			false => vec![corner_radius_input(
+				"Radius",
+				self.options.corner_radius,
+				WidgetCallback::new(|number_input| RectangleMessage::UpdateOptions(RectangleOptionsUpdate::CornerRadius(number_input.value)).into()),
+			)],
+			true => {
+				// Widget callbacks can't capture variables, so each corner needs its own
+				let corner_callbacks: [WidgetCallback<NumberInput>; 4] = [
+					WidgetCallback::new(|number_input| update_corner_radius(0, number_input)),
+					WidgetCallback::new(|number_input| update_corner_radius(1, number_input)),
+					WidgetCallback::new(|number_input| update_corner_radius(2, number_input)),
+					WidgetCallback::new(|number_input| update_corner_radius(3, number_input)),
+				];
+				["TL", "TR", "BR", "BL"]
+					.into_iter()
+					.zip(self.options.corner_radii)
+					.zip(corner_callbacks)
+					.map(|((label, value), on_update)| corner_radius_input(label, value, on_update))
+					.collect()
+			}
+		};
+		widgets.push(WidgetHolder::new(Widget::OptionalInput(OptionalInput {
+			checked: self.options.independent_corners,
+			icon: "Link".into(),
+			tooltip: "Independent Corners".into(),
+			on_update: WidgetCallback::new(|optional_input| RectangleMessage::UpdateOptions(RectangleOptionsUpdate::IndependentCorners(optional_input.checked)).into()),
+		})));
+
+		// The selected rectangles can be moved and resized exactly, with the inputs left blank when several rectangles would each show their own values
+		let bounds = match self.data.selected_bounds {
+			SelectedBounds::None => None,
+			SelectedBounds::Single(bounds) => Some(bounds),
+			SelectedBounds::Multiple => Some([f64::NAN; 4]),
+		};
+		if let Some([x, y, width, height]) = bounds {
+			let bounds_input = |label: &str, value: f64, min: Option<f64>, on_update: WidgetCallback<NumberInput>| {
+				WidgetHolder::new(Widget::NumberInput(NumberInput {
+					unit: " px".into(),
+					label: label.into(),
+					value,
+					min,
+					on_update,
+					..NumberInput::default()
+				}))
+			};
+			let separator = |separator_type| {
+				WidgetHolder::new(Widget::Separator(Separator {
+					separator_type,
+					direction: SeparatorDirection::Horizontal,
+				}))
+			};
+
+			widgets.extend([
+				separator(SeparatorType::Unrelated),
+				bounds_input("X", x, None, WidgetCallback::new(|number_input| set_bounds([Some(number_input.value), None, None, None]))),
+				separator(SeparatorType::Related),
+				bounds_input("Y", y, None, WidgetCallback::new(|number_input| set_bounds([None, Some(number_input.value), None, None]))),
+				separator(SeparatorType::Unrelated),
+				bounds_input("W", width, Some(0.), WidgetCallback::new(|number_input| set_bounds([None, None, Some(number_input.value), None]))),
+				separator(SeparatorType::Related),
+				bounds_input("H", height, Some(0.), WidgetCallback::new(|number_input| set_bounds([None, None, None, Some(number_input.value)]))),
+			]);
+		}
+
+		WidgetLayout::new(vec![LayoutRow::Row { name: "".into(), widgets }])
+	}
+}
+
+fn update_corner_radius(corner: usize, number_input: &NumberInput) -> Message {
+	RectangleMessage::UpdateOptions(RectangleOptionsUpdate::IndependentCornerRadius { corner, radius: number_input.value }).into()
+}
+
+fn set_bounds([x, y, width, height]: [Option<f64>; 4]) -> Message {
+	RectangleMessage::SetBounds { x, y, width, height }.into()
+}
+
+/// The selected layers that are rectangles, along with their corner radii.
+fn selected_rectangles(document: &DocumentMessageHandler) -> Vec<(Vec<LayerId>, [f64; 4])> {
+	document
+		.selected_layers()
+		.filter_map(|path| match document.graphene_document.layer(path).map(|layer| &layer.data) {
+			Ok(LayerDataType::Shape(shape)) => shape.corner_radii().map(|corner_radii| (path.to_vec(), corner_radii)),
+			_ => None,
+		})
+		.collect()
+}
+
+/// The transform from the unit square of a layer into document space, which leaves out the canvas zoom and rotation.
+fn document_transform(document: &DocumentMessageHandler, path: &[LayerId]) -> Option<DAffine2> {
+	Some(document.graphene_document.root.transform.inverse() * document.graphene_document.multiply_transforms(path).ok()?)
+}
+
+/// The corner of the unit square that's at the top left of the rectangle when it isn't rotated, and stays put when it's resized.
+fn top_left_corner(transform: DAffine2) -> DVec2 {
+	let flipped = |flipped: bool| if flipped { 1. } else { 0. };
+	DVec2::new(flipped(transform.matrix2.x_axis.x < 0.), flipped(transform.matrix2.y_axis.y < 0.))
+}
+
+/// The position of the top left corner, the width, and the height of a rectangle with the given document space transform.
+fn rectangle_bounds(transform: DAffine2) -> [f64; 4] {
+	let position = transform.transform_point2(top_left_corner(transform));
+	[position.x, position.y, transform.matrix2.x_axis.length(), transform.matrix2.y_axis.length()]
+}
+
+/// Rounds the corners of the selected rectangles, so editing the tool options shows its effect on existing layers.
+fn update_selected_rectangles(document: &DocumentMessageHandler, corner_radii: [f64; 4], responses: &mut VecDeque<Message>) {
+	let rectangles = selected_rectangles(document);
+	if rectangles.is_empty() {
+		return;
+	}
+
+	responses.push_back(DocumentMessage::StartTransaction.into());
+	for (path, _) in rectangles {
+		responses.push_back(Operation::SetRectangleCornerRadii { path, corner_radii }.into());
+	}
+	responses.push_back(DocumentMessage::CommitTransaction.into());
+}
+
+consumes_input_unless_ready!(Rectangle);
 
 impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Rectangle {
 	fn process_action(&mut self, action: ToolMessage, data: ToolActionHandlerData<'a>, responses: &mut VecDeque<Message>) {
 		if action == ToolMessage::UpdateHints {
-			self.fsm_state.update_hints(responses);
+			self.fsm_state.update_hints(data.1, data.2, responses);
 			return;
 		}
 
@@ -52,11 +227,34 @@ impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Rectangle {
 			return;
 		}
 
-		let new_state = self.fsm_state.transition(action, data.0, data.1, &mut self.data, &(), data.2, responses);
+		if let ToolMessage::Rectangle(RectangleMessage::UpdateOptions(action)) = action {
+			match action {
+				RectangleOptionsUpdate::CornerRadius(radius) => self.options.corner_radius = radius,
+				RectangleOptionsUpdate::IndependentCornerRadius { corner, radius } => self.options.corner_radii[corner] = radius,
+				RectangleOptionsUpdate::IndependentCorners(independent_corners) => {
+					// Start the independent corners off matching the shared radius, so toggling doesn't change the shape
+					if independent_corners && !self.options.independent_corners {
+						self.options.corner_radii = [self.options.corner_radius; 4];
+					}
+					self.options.independent_corners = independent_corners;
+					self.register_properties(responses, LayoutTarget::ToolOptions);
+				}
+			}
+			update_selected_rectangles(data.0, self.options.radii(), responses);
+			return;
+		}
+
+		let selected_bounds = self.data.selected_bounds;
+		let new_state = self.fsm_state.transition(action, data.0, data.1, &mut self.data, &self.options, data.2, responses);
+
+		// Keep the position and size inputs in step with the selected rectangles
+		if self.data.selected_bounds != selected_bounds {
+			self.register_properties(responses, LayoutTarget::ToolOptions);
+		}
 
 		if self.fsm_state != new_state {
 			self.fsm_state = new_state;
-			self.fsm_state.update_hints(responses);
+			self.fsm_state.update_hints(data.1, data.2, responses);
 			self.fsm_state.update_cursor(responses);
 		}
 	}
@@ -66,15 +264,96 @@ impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Rectangle {
 
 		match self.fsm_state {
 			Ready => actions!(RectangleMessageDiscriminant; DragStart),
-			Drawing => actions!(RectangleMessageDiscriminant; DragStop, Abort, Resize),
+			Drawing => actions!(RectangleMessageDiscriminant; AutoPan, DragStop, Abort, Resize),
+			DraggingRadius => actions!(RectangleMessageDiscriminant; DragStop, Abort, Resize),
 		}
 	}
 }
 
+/// The path, corner radii, and transform to the viewport of the single selected rectangle.
+fn selected_rectangle(document: &DocumentMessageHandler) -> Option<(Vec<LayerId>, [f64; 4], DAffine2)> {
+	let (path, shape, transform) = selected_shape(document)?;
+	shape.corner_radii().map(|corner_radii| (path.to_vec(), corner_radii, transform))
+}
+
+/// The viewport position of the rectangle's first corner, and the direction from there into the rectangle along which its radius handle moves.
+/// A step of one along the direction goes one viewport pixel along each of the two sides that meet at the corner.
+fn radius_handle_axis(transform: DAffine2) -> (DVec2, DVec2) {
+	let corner = transform.transform_point2(DVec2::ZERO);
+	(corner, transform.matrix2.x_axis.normalize() + transform.matrix2.y_axis.normalize())
+}
+
+/// The viewport position of the handle for the radius of the rectangle's first corner, which sits where the rounded corner is centered.
+fn radius_handle_position(document: &DocumentMessageHandler, corner_radius: f64, transform: DAffine2) -> ViewportPosition {
+	let (corner, direction) = radius_handle_axis(transform);
+	let viewport_radius = document.document_to_viewport_vector(DVec2::new(corner_radius, 0.)).length();
+	corner + direction * viewport_radius.max(CORNER_RADIUS_HANDLE_MIN_INSET)
+}
+
+/// The hints while drawing, where the constrain hint shows whether the rectangle is currently constrained to a square.
+fn drawing_hints(square_constrained: bool, bindings: ModifierBindings) -> HintData {
+	HintData(vec![HintGroup(vec![
+		HintInfo {
+			key_groups: vec![KeysGroup(vec![bindings.constrain])],
+			mouse: None,
+			label: String::from(if square_constrained { "Constrained to Square" } else { "Constrain Square" }),
+			plus: false,
+		},
+		HintInfo {
+			key_groups: vec![KeysGroup(vec![bindings.center])],
+			mouse: None,
+			label: String::from("From Center"),
+			plus: false,
+		},
+		HintInfo {
+			key_groups: vec![KeysGroup(vec![Key::KeyR])],
+			mouse: None,
+			label: String::from("Rotate"),
+			plus: false,
+		},
+		HintInfo {
+			key_groups: vec![KeysGroup(vec![Key::KeyPrimaryModifier, Key::KeyR])],
+			mouse: None,
+			label: String::from("Rotate by Snap Angle"),
+			plus: false,
+		},
+	])])
+}
+
+/// Shows a guide along the diagonal of the rectangle being drawn with the given viewport transform while it's constrained to a square, or removes the guide if it's `None`.
+fn update_square_guide(guide: &mut Option<Vec<LayerId>>, transform: Option<DAffine2>, responses: &mut VecDeque<Message>) {
+	let transform = match transform {
+		Some(transform) => transform,
+		None => {
+			if let Some(path) = guide.take() {
+				responses.push_back(DocumentMessage::Overlays(Operation::DeleteLayer { path }.into()).into());
+			}
+			return;
+		}
+	};
+
+	let (start, end) = (transform.transform_point2(DVec2::ZERO), transform.transform_point2(DVec2::ONE));
+	let line = end - start;
+	let transform = DAffine2::from_scale_angle_translation(DVec2::new(line.length(), 1.), line.y.atan2(line.x), start).to_cols_array();
+
+	let operation = match guide {
+		Some(path) => Operation::SetLayerTransform { path: path.clone(), transform },
+		None => {
+			let path = vec![generate_uuid()];
+			*guide = Some(path.clone());
+			let style = style::PathStyle::new(Some(style::Stroke::new(COLOR_ACCENT, 1.0)), None);
+			Operation::AddOverlayLine { path, transform, style }
+		}
+	};
+	responses.push_back(DocumentMessage::Overlays(operation.into()).into());
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum RectangleToolFsmState {
 	Ready,
 	Drawing,
+	/// Dragging the handle inside the corner of the selected rectangle to change how much its corners are rounded
+	DraggingRadius,
 }
 
 impl Default for RectangleToolFsmState {
@@ -82,14 +361,35 @@ impl Default for RectangleToolFsmState {
 		RectangleToolFsmState::Ready
 	}
 }
+/// What the position and size inputs show for the selected rectangles.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SelectedBounds {
+	None,
+	/// The position of the top left corner, the width, and the height of the only selected rectangle, in document units
+	Single([f64; 4]),
+	Multiple,
+}
+
+impl Default for SelectedBounds {
+	fn default() -> Self {
+		SelectedBounds::None
+	}
+}
+
 #[derive(Clone, Debug, Default)]
 struct RectangleToolData {
 	data: Resize,
+	/// The rectangle whose corner radius is being dragged
+	dragging: Option<Vec<LayerId>>,
+	selected_bounds: SelectedBounds,
+	/// Whether the rectangle being drawn is constrained to a square, which is shown in the hints and by a guide along its diagonal
+	square_constrained: bool,
+	square_guide: Option<Vec<LayerId>>,
 }
 
 impl Fsm for RectangleToolFsmState {
 	type ToolData = RectangleToolData;
-	type ToolOptions = ();
+	type ToolOptions = RectangleOptions;
 
 	fn transition(
 		self,
@@ -97,7 +397,7 @@ impl Fsm for RectangleToolFsmState {
 		document: &DocumentMessageHandler,
 		tool_data: &DocumentToolData,
 		data: &mut Self::ToolData,
-		_tool_options: &Self::ToolOptions,
+		tool_options: &Self::ToolOptions,
 		input: &InputPreprocessorMessageHandler,
 		responses: &mut VecDeque<Message>,
 	) -> Self {
@@ -108,7 +408,32 @@ impl Fsm for RectangleToolFsmState {
 
 		if let ToolMessage::Rectangle(event) = event {
 			match (self, event) {
+				(_, DocumentIsDirty) => {
+					// The handle for the corner radius sits inside the first corner of the selected rectangle
+					if self != Drawing {
+						let handle_positions = selected_rectangle(document).map_or(Vec::new(), |(_, corner_radii, transform)| vec![radius_handle_position(document, corner_radii[0], transform)]);
+						shape_data.update_handles(&handle_positions, responses);
+					}
+					data.selected_bounds = match selected_rectangles(document).as_slice() {
+						[] => SelectedBounds::None,
+						[(path, _)] => document_transform(document, path).map_or(SelectedBounds::None, |transform| SelectedBounds::Single(rectangle_bounds(transform))),
+						_ => SelectedBounds::Multiple,
+					};
+
+					self
+				}
 				(Ready, DragStart) => {
+					// Grabbing the handle of the selected rectangle rounds its corners instead of drawing a new rectangle
+					if let Some((path, corner_radii, transform)) = selected_rectangle(document) {
+						if handle_under(&[radius_handle_position(document, corner_radii[0], transform)], input.mouse.position).is_some() {
+							responses.push_back(DocumentMessage::StartTransaction.into());
+							data.dragging = Some(path);
+
+							return DraggingRadius;
+						}
+					}
+					shape_data.hide_handles(responses);
+
 					shape_data.start(responses, input.viewport_bounds.size(), document, input.mouse.position);
 					responses.push_back(DocumentMessage::StartTransaction.into());
 					shape_data.path = Some(document.get_path_for_new_layer());
@@ -126,13 +451,115 @@ impl Fsm for RectangleToolFsmState {
 
 					Drawing
 				}
-				(state, Resize { center, lock_ratio }) => {
-					if let Some(message) = shape_data.calculate_transform(responses, input.viewport_bounds.size(), document, center, lock_ratio, input) {
+				(DraggingRadius, Resize { .. }) => {
+					let selected = selected_rectangle(document).filter(|(path, _, _)| Some(path) == data.dragging.as_ref());
+					if let Some((path, corner_radii, transform)) = selected {
+						let (corner, direction) = radius_handle_axis(transform);
+						let viewport_radius = (input.mouse.position - corner).dot(direction) / direction.length_squared();
+
+						// Corners are rounded no more than half the shorter side, where they meet in the middle
+						let document_transform = document.graphene_document.root.transform.inverse() * transform;
+						let max_radius = document_transform.matrix2.x_axis.length().min(document_transform.matrix2.y_axis.length()) / 2.;
+						let radius = document.viewport_to_document_vector(DVec2::new(viewport_radius.max(0.), 0.)).length().min(max_radius);
+
+						// Matching corners are rounded together, while independent ones leave the others as they are
+						let corner_radii = match corner_radii.iter().all(|&other| other == corner_radii[0]) {
+							true => [radius; 4],
+							false => [radius, corner_radii[1], corner_radii[2], corner_radii[3]],
+						};
+						responses.push_back(Operation::SetRectangleCornerRadii { path, corner_radii }.into());
+					}
+
+					DraggingRadius
+				}
+				(DraggingRadius, DragStop) => {
+					responses.push_back(DocumentMessage::CommitTransaction.into());
+					data.dragging = None;
+
+					Ready
+				}
+				(DraggingRadius, Abort) => {
+					responses.push_back(DocumentMessage::AbortTransaction.into());
+					data.dragging = None;
+
+					Ready
+				}
+				(Ready, SetBounds { x, y, width, height }) => {
+					let rectangles = selected_rectangles(document);
+					if rectangles.is_empty() {
+						return Ready;
+					}
+
+					responses.push_back(DocumentMessage::StartTransaction.into());
+					for (path, corner_radii) in rectangles {
+						let transform = match document_transform(document, &path) {
+							Some(transform) => transform,
+							None => continue,
+						};
+						let corner = top_left_corner(transform);
+
+						// A side with no length has no direction to grow along, so it's left as it is
+						let size = DVec2::new(transform.matrix2.x_axis.length(), transform.matrix2.y_axis.length());
+						let scale_side = |new_length: Option<f64>, length: f64| if length == 0. { 1. } else { new_length.unwrap_or(length) / length };
+						let scale = DVec2::new(scale_side(width, size.x), scale_side(height, size.y));
+						let transform = transform * DAffine2::from_translation(corner) * DAffine2::from_scale(scale) * DAffine2::from_translation(-corner);
+
+						let position = transform.transform_point2(corner);
+						let new_position = DVec2::new(x.unwrap_or(position.x), y.unwrap_or(position.y));
+						let transform = DAffine2::from_translation(new_position - position) * transform;
+
+						let transform = (document.graphene_document.root.transform * transform).to_cols_array();
+						responses.push_back(Operation::SetLayerTransformInViewport { path: path.clone(), transform }.into());
+						// Round the corners again since their radii are relative to the new size
+						responses.push_back(Operation::SetRectangleCornerRadii { path, corner_radii }.into());
+					}
+					responses.push_back(DocumentMessage::CommitTransaction.into());
+
+					Ready
+				}
+				(Ready, Abort) => {
+					shape_data.clear_handles(responses);
+
+					Ready
+				}
+				(
+					state,
+					Resize {
+						center,
+						lock_ratio,
+						rotate,
+						snap_angle,
+					},
+				) => {
+					if let Some(message) = shape_data.calculate_transform(responses, input.viewport_bounds.size(), document, center, lock_ratio, rotate, snap_angle, input) {
 						responses.push_back(message);
+
+						let square_constrained = input.is_pressed(lock_ratio);
+						if square_constrained != data.square_constrained {
+							data.square_constrained = square_constrained;
+							responses.push_back(
+								FrontendMessage::UpdateInputHints {
+									hint_data: drawing_hints(square_constrained, input.modifier_bindings),
+								}
+								.into(),
+							);
+						}
+						update_square_guide(&mut data.square_guide, square_constrained.then(|| shape_data.viewport_transform()), responses);
+
+						// Round the corners again since their radii are relative to the new size
+						if let Some(path) = shape_data.path.clone() {
+							let corner_radii = tool_options.radii();
+							responses.push_back(Operation::SetRectangleCornerRadii { path, corner_radii }.into());
+						}
 					}
 
 					state
 				}
+				(Drawing, AutoPan) => {
+					shape_data.auto_pan(tool_data, input, responses);
+
+					Drawing
+				}
 				(Drawing, DragStop) => {
 					match shape_data.drag_start.distance(input.mouse.position) <= DRAG_THRESHOLD {
 						true => responses.push_back(DocumentMessage::AbortTransaction.into()),
@@ -140,6 +567,10 @@ impl Fsm for RectangleToolFsmState {
 					}
 
 					shape_data.cleanup(responses);
+					data.square_constrained = false;
+					update_square_guide(&mut data.square_guide, None, responses);
+					// Shows the corner radius handle on the new rectangle once it's committed
+					responses.push_back(ToolMessage::DocumentIsDirty.into());
 
 					Ready
 				}
@@ -147,6 +578,8 @@ impl Fsm for RectangleToolFsmState {
 					responses.push_back(DocumentMessage::AbortTransaction.into());
 
 					shape_data.cleanup(responses);
+					data.square_constrained = false;
+					update_square_guide(&mut data.square_guide, None, responses);
 
 					Ready
 				}
@@ -157,7 +590,8 @@ impl Fsm for RectangleToolFsmState {
 		}
 	}
 
-	fn update_hints(&self, responses: &mut VecDeque<Message>) {
+	fn update_hints(&self, _tool_data: &DocumentToolData, input: &InputPreprocessorMessageHandler, responses: &mut VecDeque<Message>) {
+		let bindings = input.modifier_bindings;
 		let hint_data = match self {
 			RectangleToolFsmState::Ready => HintData(vec![HintGroup(vec![
 				HintInfo {
@@ -167,32 +601,20 @@ impl Fsm for RectangleToolFsmState {
 					plus: false,
 				},
 				HintInfo {
-					key_groups: vec![KeysGroup(vec![Key::KeyShift])],
+					key_groups: vec![KeysGroup(vec![bindings.constrain])],
 					mouse: None,
 					label: String::from("Constrain Square"),
 					plus: true,
 				},
 				HintInfo {
-					key_groups: vec![KeysGroup(vec![Key::KeyAlt])],
+					key_groups: vec![KeysGroup(vec![bindings.center])],
 					mouse: None,
 					label: String::from("From Center"),
 					plus: true,
 				},
 			])]),
-			RectangleToolFsmState::Drawing => HintData(vec![HintGroup(vec![
-				HintInfo {
-					key_groups: vec![KeysGroup(vec![Key::KeyShift])],
-					mouse: None,
-					label: String::from("Constrain Square"),
-					plus: false,
-				},
-				HintInfo {
-					key_groups: vec![KeysGroup(vec![Key::KeyAlt])],
-					mouse: None,
-					label: String::from("From Center"),
-					plus: false,
-				},
-			])]),
+			RectangleToolFsmState::Drawing => drawing_hints(false, bindings),
+			RectangleToolFsmState::DraggingRadius => HintData(vec![]),
 		};
 
 		responses.push_back(FrontendMessage::UpdateInputHints { hint_data }.into());
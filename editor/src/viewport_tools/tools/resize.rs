@@ -30,10 +30,10 @@ impl Resize {
 			let stop = self.snap_handler.snap_position(document, ipp.mouse.position);
 
 			let mut size = stop - start;
-			if ipp.keyboard.get(lock_ratio as usize) {
+			if ipp.is_pressed(lock_ratio) {
 				size = size.abs().max(size.abs().yx()) * size.signum();
 			}
-			if ipp.keyboard.get(center as usize) {
+			if ipp.is_pressed(center) {
 				start -= size;
 				size *= 2.;
 			}
@@ -1,15 +1,18 @@
-use crate::consts::{ROTATE_SNAP_ANGLE, SELECTION_TOLERANCE};
 use crate::document::transformation::Selected;
-use crate::document::utility_types::{AlignAggregate, AlignAxis, FlipAxis};
+use crate::document::utility_types::{AlignAggregate, AlignAxis, FlipAxis, TransformInputMode};
 use crate::document::DocumentMessageHandler;
 use crate::frontend::utility_types::MouseCursorIcon;
 use crate::input::keyboard::{Key, MouseMotion};
 use crate::input::mouse::ViewportPosition;
 use crate::input::InputPreprocessorMessageHandler;
-use crate::layout::widgets::{IconButton, LayoutRow, PopoverButton, PropertyHolder, Separator, SeparatorDirection, SeparatorType, Widget, WidgetCallback, WidgetHolder, WidgetLayout};
+use crate::layout::layout_message::LayoutTarget;
+use crate::layout::widgets::{
+	IconButton, LayoutRow, NumberInput, PopoverButton, PropertyHolder, RadioEntryData, RadioInput, Separator, SeparatorDirection, SeparatorType, TextButton, Widget, WidgetCallback, WidgetHolder,
+	WidgetLayout,
+};
 use crate::message_prelude::*;
 use crate::misc::{HintData, HintGroup, HintInfo, KeysGroup};
-use crate::viewport_tools::snapping::SnapHandler;
+use crate::viewport_tools::snapping::{pixel_grid_snap, SnapHandler};
 use crate::viewport_tools::tool::{DocumentToolData, Fsm, ToolActionHandlerData, ToolType};
 
 use graphene::document::Document;
@@ -17,6 +20,11 @@ use graphene::intersection::Quad;
 use graphene::layers::layer_info::LayerDataType;
 use graphene::Operation;
 
+use super::shared::auto_pan::auto_pan;
+use super::shared::axis_lock::AxisLock;
+use super::shared::distance_overlays::DistanceOverlays;
+use super::shared::overlay_pool::{OverlayPool, OverlayRole};
+use super::shared::selection_outlines::SelectionOutlines;
 use super::shared::transformation_cage::*;
 
 use glam::{DAffine2, DVec2};
@@ -26,39 +34,90 @@ use serde::{Deserialize, Serialize};
 pub struct Select {
 	fsm_state: SelectToolFsmState,
 	data: SelectToolData,
+	options: SelectOptions,
+}
+
+pub struct SelectOptions {
+	position_jitter: f64,
+	rotation_jitter: f64,
+	scale_jitter: f64,
+	seed: u64,
+	transform_input_mode: TransformInputMode,
+	/// The last position entered in absolute mode, which is shown in the position inputs
+	absolute_position: DVec2,
+}
+
+impl Default for SelectOptions {
+	fn default() -> Self {
+		Self {
+			position_jitter: 10.,
+			rotation_jitter: 15.,
+			scale_jitter: 10.,
+			seed: 0,
+			transform_input_mode: TransformInputMode::default(),
+			absolute_position: DVec2::ZERO,
+		}
+	}
 }
 
 #[remain::sorted]
 #[impl_message(Message, ToolMessage, Select)]
-#[derive(PartialEq, Clone, Debug, Hash, Serialize, Deserialize)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub enum SelectMessage {
 	// Standard messages
 	#[remain::unsorted]
 	Abort,
 	#[remain::unsorted]
 	DocumentIsDirty,
+	#[remain::unsorted]
+	SelectionChanged,
 
 	// Tool-specific messages
 	Align {
 		axis: AlignAxis,
 		aggregate: AlignAggregate,
 	},
+	AutoPan,
 	DragStart {
 		add_to_selection: Key,
 	},
-	DragStop,
+	DragStop {
+		enclose: Key,
+	},
 	EditLayer,
 	FlipHorizontal,
 	FlipVertical,
 	PointerMove {
 		axis_align: Key,
 		snap_angle: Key,
+		free_rotate: Key,
 		center: Key,
+		measure: Key,
+		enclose: Key,
 	},
+	RandomizeTransforms,
+	UpdateOptions(SelectOptionsUpdate),
+}
+
+#[remain::sorted]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub enum SelectOptionsUpdate {
+	Position { axis: AlignAxis, value: f64 },
+	PositionJitter(f64),
+	RotationJitter(f64),
+	ScaleJitter(f64),
+	Seed(u64),
+	TransformInputMode(TransformInputMode),
 }
 
 impl PropertyHolder for Select {
 	fn properties(&self) -> WidgetLayout {
+		// Relative inputs start from zero each time, since their value is the distance to move by
+		let position_input_value = match self.options.transform_input_mode {
+			TransformInputMode::Absolute => self.options.absolute_position,
+			TransformInputMode::Relative => DVec2::ZERO,
+		};
+
 		WidgetLayout::new(vec![LayoutRow::Row {
 			name: "".into(),
 			widgets: vec![
@@ -225,15 +284,120 @@ impl PropertyHolder for Select {
 					title: "Boolean".into(),
 					text: "The contents of this popover menu are coming soon".into(),
 				})),
+				WidgetHolder::new(Widget::Separator(Separator {
+					direction: SeparatorDirection::Horizontal,
+					separator_type: SeparatorType::Section,
+				})),
+				WidgetHolder::new(Widget::NumberInput(NumberInput {
+					label: "Position ±".into(),
+					unit: " px".into(),
+					value: self.options.position_jitter,
+					min: Some(0.),
+					on_update: WidgetCallback::new(|number_input| SelectMessage::UpdateOptions(SelectOptionsUpdate::PositionJitter(number_input.value)).into()),
+					..NumberInput::default()
+				})),
+				WidgetHolder::new(Widget::NumberInput(NumberInput {
+					label: "Rotation ±".into(),
+					unit: "°".into(),
+					value: self.options.rotation_jitter,
+					min: Some(0.),
+					max: Some(180.),
+					on_update: WidgetCallback::new(|number_input| SelectMessage::UpdateOptions(SelectOptionsUpdate::RotationJitter(number_input.value)).into()),
+					..NumberInput::default()
+				})),
+				WidgetHolder::new(Widget::NumberInput(NumberInput {
+					label: "Scale ±".into(),
+					unit: "%".into(),
+					value: self.options.scale_jitter,
+					min: Some(0.),
+					max: Some(99.),
+					on_update: WidgetCallback::new(|number_input| SelectMessage::UpdateOptions(SelectOptionsUpdate::ScaleJitter(number_input.value)).into()),
+					..NumberInput::default()
+				})),
+				WidgetHolder::new(Widget::NumberInput(NumberInput {
+					label: "Seed".into(),
+					value: self.options.seed as f64,
+					min: Some(0.),
+					is_integer: true,
+					on_update: WidgetCallback::new(|number_input| SelectMessage::UpdateOptions(SelectOptionsUpdate::Seed(number_input.value as u64)).into()),
+					..NumberInput::default()
+				})),
+				WidgetHolder::new(Widget::Separator(Separator {
+					direction: SeparatorDirection::Horizontal,
+					separator_type: SeparatorType::Related,
+				})),
+				WidgetHolder::new(Widget::TextButton(TextButton {
+					label: "Randomize".into(),
+					on_update: WidgetCallback::new(|_| SelectMessage::RandomizeTransforms.into()),
+					..TextButton::default()
+				})),
+				WidgetHolder::new(Widget::Separator(Separator {
+					direction: SeparatorDirection::Horizontal,
+					separator_type: SeparatorType::Section,
+				})),
+				WidgetHolder::new(Widget::RadioInput(RadioInput {
+					selected_index: match self.options.transform_input_mode {
+						TransformInputMode::Absolute => 0,
+						TransformInputMode::Relative => 1,
+					},
+					entries: vec![
+						RadioEntryData {
+							value: "absolute".into(),
+							label: "Absolute".into(),
+							tooltip: "Set the position of the selection".into(),
+							on_update: WidgetCallback::new(|_| SelectMessage::UpdateOptions(SelectOptionsUpdate::TransformInputMode(TransformInputMode::Absolute)).into()),
+							..RadioEntryData::default()
+						},
+						RadioEntryData {
+							value: "relative".into(),
+							label: "Relative".into(),
+							tooltip: "Move each selected layer by the entered distance".into(),
+							on_update: WidgetCallback::new(|_| SelectMessage::UpdateOptions(SelectOptionsUpdate::TransformInputMode(TransformInputMode::Relative)).into()),
+							..RadioEntryData::default()
+						},
+					],
+				})),
+				WidgetHolder::new(Widget::Separator(Separator {
+					direction: SeparatorDirection::Horizontal,
+					separator_type: SeparatorType::Related,
+				})),
+				WidgetHolder::new(Widget::NumberInput(NumberInput {
+					label: "X".into(),
+					unit: " px".into(),
+					value: position_input_value.x,
+					on_update: WidgetCallback::new(|number_input| {
+						SelectMessage::UpdateOptions(SelectOptionsUpdate::Position {
+							axis: AlignAxis::X,
+							value: number_input.value,
+						})
+						.into()
+					}),
+					..NumberInput::default()
+				})),
+				WidgetHolder::new(Widget::NumberInput(NumberInput {
+					label: "Y".into(),
+					unit: " px".into(),
+					value: position_input_value.y,
+					on_update: WidgetCallback::new(|number_input| {
+						SelectMessage::UpdateOptions(SelectOptionsUpdate::Position {
+							axis: AlignAxis::Y,
+							value: number_input.value,
+						})
+						.into()
+					}),
+					..NumberInput::default()
+				})),
 			],
 		}])
 	}
 }
 
+consumes_input_unless_ready!(Select);
+
 impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Select {
 	fn process_action(&mut self, action: ToolMessage, data: ToolActionHandlerData<'a>, responses: &mut VecDeque<Message>) {
 		if action == ToolMessage::UpdateHints {
-			self.fsm_state.update_hints(responses);
+			self.fsm_state.update_hints(data.1, data.2, responses);
 			return;
 		}
 
@@ -242,11 +406,38 @@ impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Select {
 			return;
 		}
 
-		let new_state = self.fsm_state.transition(action, data.0, data.1, &mut self.data, &(), data.2, responses);
+		if let ToolMessage::Select(SelectMessage::UpdateOptions(action)) = action {
+			match action {
+				SelectOptionsUpdate::PositionJitter(position_jitter) => self.options.position_jitter = position_jitter,
+				SelectOptionsUpdate::RotationJitter(rotation_jitter) => self.options.rotation_jitter = rotation_jitter,
+				SelectOptionsUpdate::ScaleJitter(scale_jitter) => self.options.scale_jitter = scale_jitter,
+				SelectOptionsUpdate::Seed(seed) => self.options.seed = seed,
+				SelectOptionsUpdate::TransformInputMode(mode) => {
+					// Only the inputs are updated, so switching modes leaves the layers where they are
+					self.options.transform_input_mode = mode;
+					self.register_properties(responses, LayoutTarget::ToolOptions);
+				}
+				SelectOptionsUpdate::Position { axis, value } => {
+					let mode = self.options.transform_input_mode;
+					match mode {
+						TransformInputMode::Absolute => match axis {
+							AlignAxis::X => self.options.absolute_position.x = value,
+							AlignAxis::Y => self.options.absolute_position.y = value,
+						},
+						// Reset the inputs back to zero, ready for the next distance
+						TransformInputMode::Relative => self.register_properties(responses, LayoutTarget::ToolOptions),
+					}
+					responses.push_back(DocumentMessage::PositionSelectedLayers { axis, value, mode }.into());
+				}
+			}
+			return;
+		}
+
+		let new_state = self.fsm_state.transition(action, data.0, data.1, &mut self.data, &self.options, data.2, responses);
 
 		if self.fsm_state != new_state {
 			self.fsm_state = new_state;
-			self.fsm_state.update_hints(responses);
+			self.fsm_state.update_hints(data.1, data.2, responses);
 		}
 	}
 
@@ -255,7 +446,8 @@ impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Select {
 
 		match self.fsm_state {
 			Ready => actions!(SelectMessageDiscriminant; DragStart, PointerMove, EditLayer),
-			Dragging => actions!(SelectMessageDiscriminant; DragStop, PointerMove, EditLayer),
+			Dragging => actions!(SelectMessageDiscriminant; AutoPan, DragStop, PointerMove, Abort, EditLayer),
+			DrawingBox => actions!(SelectMessageDiscriminant; AutoPan, DragStop, PointerMove, Abort, EditLayer),
 			_ => actions!(SelectMessageDiscriminant; DragStop, PointerMove, Abort, EditLayer),
 		}
 	}
@@ -265,6 +457,7 @@ impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Select {
 enum SelectToolFsmState {
 	Ready,
 	Dragging,
+	DraggingPivot,
 	DrawingBox,
 	ResizingBounds,
 	RotatingBounds,
@@ -283,37 +476,65 @@ struct SelectToolData {
 	layers_dragging: Vec<Vec<LayerId>>, // Paths and offsets
 	drag_box_overlay_layer: Option<Vec<LayerId>>,
 	bounding_box_overlays: Option<BoundingBoxOverlays>,
+	selection_outlines: SelectionOutlines,
+	overlay_pool: OverlayPool,
 	snap_handler: SnapHandler,
+	distance_overlays: DistanceOverlays,
+	equal_spacing_overlays: DistanceOverlays,
+	axis_lock: AxisLock,
 	cursor: MouseCursorIcon,
+	/// Whether the marquee currently selects only the layers it fully encloses, rather than every layer it touches
+	enclose_marquee: bool,
+	/// Whether the marquee replaces the selection once it selects any layers, since the click on empty canvas that started it left the selection in place
+	marquee_replaces_selection: bool,
 }
 
 impl SelectToolData {
-	fn selection_quad(&self) -> Quad {
-		let bbox = self.selection_box();
+	/// `tolerance` is how far, in viewport pixels, a click without any drag reaches around the pointer.
+	fn selection_quad(&self, tolerance: f64) -> Quad {
+		let bbox = self.selection_box(tolerance);
 		Quad::from_box(bbox)
 	}
 
-	fn selection_box(&self) -> [DVec2; 2] {
+	fn selection_box(&self, tolerance: f64) -> [DVec2; 2] {
 		if self.drag_current == self.drag_start {
-			let tolerance = DVec2::splat(SELECTION_TOLERANCE);
+			let tolerance = DVec2::splat(tolerance);
 			[self.drag_start - tolerance, self.drag_start + tolerance]
 		} else {
 			[self.drag_start, self.drag_current]
 		}
 	}
+
+	/// The layers picked by the marquee, which are those it touches or, with `enclose`, only those whose bounding boxes lie entirely within it.
+	/// A click without any drag always picks what it touches, since nothing fits within the small area around the pointer.
+	fn marquee_layers(&self, document: &DocumentMessageHandler, tolerance: f64, enclose: bool) -> Vec<Vec<LayerId>> {
+		let touched = document.graphene_document.intersects_quad_root(self.selection_quad(tolerance));
+		if !enclose || self.drag_current == self.drag_start {
+			return touched;
+		}
+
+		let [min, max] = [self.drag_start.min(self.drag_current), self.drag_start.max(self.drag_current)];
+		touched
+			.into_iter()
+			.filter(|path| match document.layer_viewport_bounding_box(path, false) {
+				Some([layer_min, layer_max]) => layer_min.cmpge(min).all() && layer_max.cmple(max).all(),
+				None => false,
+			})
+			.collect()
+	}
 }
 
 impl Fsm for SelectToolFsmState {
 	type ToolData = SelectToolData;
-	type ToolOptions = ();
+	type ToolOptions = SelectOptions;
 
 	fn transition(
 		self,
 		event: ToolMessage,
 		document: &DocumentMessageHandler,
-		_tool_data: &DocumentToolData,
+		tool_data: &DocumentToolData,
 		data: &mut Self::ToolData,
-		_tool_options: &Self::ToolOptions,
+		tool_options: &Self::ToolOptions,
 		input: &InputPreprocessorMessageHandler,
 		responses: &mut VecDeque<Message>,
 	) -> Self {
@@ -322,12 +543,21 @@ impl Fsm for SelectToolFsmState {
 
 		if let ToolMessage::Select(event) = event {
 			match (self, event) {
-				(_, DocumentIsDirty) => {
+				// The selection is stored in the document, so it's still there after switching back from another tool and only needs its overlays redrawn
+				(_, event @ (DocumentIsDirty | SelectionChanged)) => {
+					// A pivot placed for one selection doesn't mean anything for another, so it goes back to the center
+					if event == SelectionChanged {
+						if let Some(bounding_box_overlays) = &mut data.bounding_box_overlays {
+							bounding_box_overlays.custom_pivot = None;
+						}
+					}
+
 					let mut buffer = Vec::new();
 					match (document.selected_visible_layers_bounding_box(), data.bounding_box_overlays.take()) {
-						(None, Some(bounding_box_overlays)) => bounding_box_overlays.delete(&mut buffer),
+						(None, Some(bounding_box_overlays)) => bounding_box_overlays.delete(&mut data.overlay_pool, &mut buffer),
 						(Some(bounds), paths) => {
-							let mut bounding_box_overlays = paths.unwrap_or_else(|| BoundingBoxOverlays::new(&mut buffer));
+							let mut bounding_box_overlays = paths.unwrap_or_else(|| BoundingBoxOverlays::new(&mut data.overlay_pool, &mut buffer));
+							bounding_box_overlays.add_pivot_marker(&mut data.overlay_pool, &mut buffer);
 
 							bounding_box_overlays.bounds = bounds;
 							bounding_box_overlays.transform = DAffine2::IDENTITY;
@@ -339,11 +569,13 @@ impl Fsm for SelectToolFsmState {
 						(_, _) => {}
 					};
 					buffer.into_iter().rev().for_each(|message| responses.push_front(message));
+
+					data.selection_outlines.update(document, tool_data, responses);
 					self
 				}
 				(_, EditLayer) => {
 					let mouse_pos = input.mouse.position;
-					let tolerance = DVec2::splat(SELECTION_TOLERANCE);
+					let tolerance = DVec2::splat(tool_data.selection_tolerance);
 					let quad = Quad::from_box([mouse_pos - tolerance, mouse_pos + tolerance]);
 
 					if let Some(Ok(intersect)) = document.graphene_document.intersects_quad_root(quad).last().map(|path| document.graphene_document.layer(path)) {
@@ -366,11 +598,18 @@ impl Fsm for SelectToolFsmState {
 					data.drag_current = input.mouse.position;
 					let mut buffer = Vec::new();
 
-					let dragging_bounds = if let Some(bounding_box) = &mut data.bounding_box_overlays {
+					let aspect_locked = document
+						.selected_visible_layers()
+						.any(|path| document.graphene_document.layer(path).map_or(false, |layer| layer.aspect_locked));
+
+					// The pivot marker sits on top of the cage, so it can be grabbed even where it overlaps a handle
+					let dragging_pivot = data.bounding_box_overlays.as_ref().map_or(false, |bounding_box| bounding_box.check_pivot(input.mouse.position));
+
+					let dragging_bounds = if let Some(bounding_box) = data.bounding_box_overlays.as_mut().filter(|_| !dragging_pivot) {
 						let edges = bounding_box.check_selected_edges(input.mouse.position);
 
 						bounding_box.selected_edges = edges.map(|(top, bottom, left, right)| {
-							let edges = SelectedEdges::new(top, bottom, left, right, bounding_box.bounds);
+							let edges = SelectedEdges::new(top, bottom, left, right, bounding_box.bounds, aspect_locked);
 							bounding_box.pivot = edges.calculate_pivot();
 							edges
 						});
@@ -387,14 +626,16 @@ impl Fsm for SelectToolFsmState {
 					};
 
 					let mut selected: Vec<_> = document.selected_visible_layers().map(|path| path.to_vec()).collect();
-					let quad = data.selection_quad();
+					let quad = data.selection_quad(tool_data.selection_tolerance);
 					let mut intersection = document.graphene_document.intersects_quad_root(quad);
 					// If the user is dragging the bounding box bounds, go into ResizingBounds mode.
 					// If the user is dragging the rotate trigger, go into RotatingBounds mode.
 					// If the user clicks on a layer that is in their current selection, go into the dragging mode.
 					// If the user clicks on new shape, make that layer their new selection.
 					// Otherwise enter the box select mode
-					let state = if let Some(selected_edges) = dragging_bounds {
+					let state = if dragging_pivot {
+						DraggingPivot
+					} else if let Some(selected_edges) = dragging_bounds {
 						let snap_x = selected_edges.2 || selected_edges.3;
 						let snap_y = selected_edges.0 || selected_edges.1;
 
@@ -405,10 +646,10 @@ impl Fsm for SelectToolFsmState {
 						ResizingBounds
 					} else if rotating_bounds {
 						if let Some(bounds) = &mut data.bounding_box_overlays {
+							bounds.pivot = bounds.pivot_position();
+							// Record the transforms the layers start from, which the rotation is applied to
 							let selected = selected.iter().collect::<Vec<_>>();
-							let mut selected = Selected::new(&mut bounds.original_transforms, &mut bounds.pivot, &selected, responses, &document.graphene_document);
-
-							*selected.pivot = selected.calculate_pivot();
+							Selected::new(&mut bounds.original_transforms, &mut bounds.pivot, &selected, responses, &document.graphene_document);
 						}
 
 						data.layers_dragging = selected;
@@ -422,7 +663,9 @@ impl Fsm for SelectToolFsmState {
 
 						Dragging
 					} else {
-						if !input.keyboard.get(add_to_selection as usize) {
+						// Clicking on empty canvas only deselects if the user's setting allows it, but clicking on another layer always replaces the selection
+						let replace_selection = !input.is_pressed(add_to_selection);
+						if replace_selection && (!intersection.is_empty() || tool_data.deselect_on_empty_click) {
 							buffer.push(DocumentMessage::DeselectAllLayers.into());
 							data.layers_dragging.clear();
 						}
@@ -436,7 +679,9 @@ impl Fsm for SelectToolFsmState {
 
 							Dragging
 						} else {
-							data.drag_box_overlay_layer = Some(add_bounding_box(&mut buffer));
+							data.drag_box_overlay_layer = Some(add_bounding_box(&mut data.overlay_pool, &mut buffer));
+							data.enclose_marquee = false;
+							data.marquee_replaces_selection = replace_selection && !tool_data.deselect_on_empty_click;
 							DrawingBox
 						}
 					};
@@ -444,23 +689,43 @@ impl Fsm for SelectToolFsmState {
 
 					state
 				}
-				(Dragging, PointerMove { axis_align, .. }) => {
+				(Dragging, PointerMove { axis_align, measure, .. }) => {
 					// TODO: This is a cheat. Break out the relevant functionality from the handler above and call it from there and here.
 					responses.push_front(SelectMessage::DocumentIsDirty.into());
 
-					let mouse_position = axis_align_drag(input.keyboard.get(axis_align as usize), input.mouse.position, data.drag_start);
+					let mouse_position = data.axis_lock.constrain(input.is_pressed(axis_align), input.mouse.position, data.drag_start);
+					data.axis_lock.update_guide(responses, data.drag_start, input.viewport_bounds.size());
 
 					let mouse_delta = mouse_position - data.drag_current;
 
 					let snap = data
 						.layers_dragging
 						.iter()
-						.filter_map(|path| document.graphene_document.viewport_bounding_box(path).ok()?)
+						.filter_map(|path| document.layer_viewport_bounding_box(path, true))
 						.flat_map(|[bound1, bound2]| [bound1, bound2, (bound1 + bound2) / 2.])
 						.map(|vec| vec.into())
 						.unzip();
 
-					let closest_move = data.snap_handler.snap_layers(responses, document, snap, input.viewport_bounds.size(), mouse_delta);
+					let dragged_bounds = data
+						.layers_dragging
+						.iter()
+						.filter_map(|path| document.layer_viewport_bounding_box(path, true))
+						.reduce(|a, b| [a[0].min(b[0]), a[1].max(b[1])]);
+
+					let mut closest_move = data.snap_handler.snap_layers(responses, document, snap, input.viewport_bounds.size(), mouse_delta);
+					// Snap to equal gaps between the neighboring layers, unless aligning with another layer is closer
+					if let Some([min, max]) = dragged_bounds {
+						let spacing_move = data.snap_handler.equal_spacing_snap(document, [min + mouse_delta, max + mouse_delta]);
+						let closer = |spacing: f64, object: f64| if spacing != 0. && (object == 0. || spacing.abs() < object.abs()) { spacing } else { object };
+						closest_move = DVec2::new(closer(spacing_move.x, closest_move.x), closer(spacing_move.y, closest_move.y));
+					}
+					// Snap to whole pixels in document space, except on an axis where the layers already snap to another layer which is at least as close
+					if tool_data.pixel_snapping {
+						let pixel_move = pixel_grid_snap(&document.graphene_document, &data.layers_dragging, mouse_delta);
+						let closer = |object: f64, pixel: f64| if object != 0. && object.abs() <= pixel.abs() { object } else { pixel };
+						closest_move = DVec2::new(closer(closest_move.x, pixel_move.x), closer(closest_move.y, pixel_move.y));
+					}
+					let closest_move = data.axis_lock.constrain_offset(closest_move);
 					// TODO: Cache the result of `shallowest_unique_layers` to avoid this heavy computation every frame of movement, see https://github.com/GraphiteEditor/Graphite/pull/481
 					for path in Document::shallowest_unique_layers(data.layers_dragging.iter()) {
 						responses.push_front(
@@ -472,12 +737,28 @@ impl Fsm for SelectToolFsmState {
 						);
 					}
 					data.drag_current = mouse_position + closest_move;
+
+					let moved_bounds = dragged_bounds.map(|[min, max]| [min + mouse_delta + closest_move, max + mouse_delta + closest_move]);
+					let viewport_to_document_scale = document.viewport_to_document_vector(DVec2::X).length();
+
+					// Label the equal gaps on both sides of the moved selection when it's evenly spaced between its neighbors
+					let equal_gaps = moved_bounds.map(|bounds| data.snap_handler.equal_spacing_gaps(bounds)).unwrap_or_default();
+					data.equal_spacing_overlays.update(responses, &equal_gaps, viewport_to_document_scale);
+
+					// Measure the distances from the moved selection to the closest other layers
+					if input.is_pressed(measure) {
+						let gaps = moved_bounds.map(|bounds| data.snap_handler.distances_to_nearest_targets(bounds)).unwrap_or_default();
+						data.distance_overlays.update(responses, &gaps, viewport_to_document_scale);
+					} else {
+						data.distance_overlays.delete(responses);
+					}
+
 					Dragging
 				}
 				(ResizingBounds, PointerMove { axis_align, center, .. }) => {
 					if let Some(bounds) = &mut data.bounding_box_overlays {
 						if let Some(movement) = &mut bounds.selected_edges {
-							let (center, axis_align) = (input.keyboard.get(center as usize), input.keyboard.get(axis_align as usize));
+							let (center, axis_align) = (input.is_pressed(center), input.is_pressed(axis_align));
 
 							let mouse_position = input.mouse.position;
 
@@ -494,7 +775,7 @@ impl Fsm for SelectToolFsmState {
 					}
 					ResizingBounds
 				}
-				(RotatingBounds, PointerMove { snap_angle, .. }) => {
+				(RotatingBounds, PointerMove { snap_angle, free_rotate, .. }) => {
 					if let Some(bounds) = &mut data.bounding_box_overlays {
 						let angle = {
 							let start_offset = data.drag_start - bounds.pivot;
@@ -503,26 +784,53 @@ impl Fsm for SelectToolFsmState {
 							start_offset.angle_between(end_offset)
 						};
 
-						let snapped_angle = if input.keyboard.get(snap_angle as usize) {
-							let snap_resolution = ROTATE_SNAP_ANGLE.to_radians();
+						let snapped_angle = if input.is_pressed(snap_angle) {
+							let snap_resolution = input.rotate_snap_angle.to_radians();
 							(angle / snap_resolution).round() * snap_resolution
 						} else {
 							angle
 						};
 
-						let delta = DAffine2::from_angle(snapped_angle);
-
 						let selected = data.layers_dragging.iter().collect::<Vec<_>>();
 						let mut selected = Selected::new(&mut bounds.original_transforms, &mut bounds.pivot, &selected, responses, &document.graphene_document);
 
+						let snapped_angle = if input.is_pressed(free_rotate) {
+							snapped_angle
+						} else {
+							selected.snap_rotation_to_axes(snapped_angle, input.axis_snap_tolerance)
+						};
+						let delta = DAffine2::from_angle(snapped_angle);
+
 						selected.update_transforms(delta);
 					}
 
 					RotatingBounds
 				}
-				(DrawingBox, PointerMove { .. }) => {
+				(DraggingPivot, PointerMove { .. }) => {
+					if let Some(bounds) = &mut data.bounding_box_overlays {
+						bounds.drag_pivot(input.mouse.position, responses);
+
+						let mut buffer = Vec::new();
+						bounds.transform(&mut buffer);
+						responses.extend(buffer);
+					}
+
+					DraggingPivot
+				}
+				(DrawingBox, PointerMove { enclose: enclose_key, .. }) => {
 					data.drag_current = input.mouse.position;
 
+					let enclose = input.is_pressed(enclose_key);
+					if enclose != data.enclose_marquee {
+						data.enclose_marquee = enclose;
+						responses.push_back(
+							FrontendMessage::UpdateInputHints {
+								hint_data: marquee_hints(enclose, enclose_key),
+							}
+							.into(),
+						);
+					}
+
 					responses.push_front(
 						DocumentMessage::Overlays(
 							Operation::SetLayerTransformInViewport {
@@ -545,16 +853,29 @@ impl Fsm for SelectToolFsmState {
 
 					Ready
 				}
-				(Dragging, DragStop) => {
+				(Dragging | DrawingBox, AutoPan) => {
+					// Keep the start of the drag in place on the canvas, and for dragged layers, keep them under the pointer once the drag catches up
+					if let Some(delta) = auto_pan(tool_data, input, responses) {
+						data.drag_start += delta;
+						data.drag_current += delta;
+						data.snap_handler.translate_targets(delta);
+					}
+
+					self
+				}
+				(Dragging, DragStop { .. }) => {
 					let response = match input.mouse.position.distance(data.drag_start) < 10. * f64::EPSILON {
 						true => DocumentMessage::Undo,
 						false => DocumentMessage::CommitTransaction,
 					};
 					data.snap_handler.cleanup(responses);
+					data.distance_overlays.delete(responses);
+					data.equal_spacing_overlays.delete(responses);
+					data.axis_lock.clear(responses);
 					responses.push_front(response.into());
 					Ready
 				}
-				(ResizingBounds, DragStop) => {
+				(ResizingBounds, DragStop { .. }) => {
 					data.snap_handler.cleanup(responses);
 
 					if let Some(bounds) = &mut data.bounding_box_overlays {
@@ -563,41 +884,66 @@ impl Fsm for SelectToolFsmState {
 
 					Ready
 				}
-				(RotatingBounds, DragStop) => {
+				(DraggingPivot, DragStop { .. }) => {
+					if let Some(bounds) = &mut data.bounding_box_overlays {
+						bounds.stop_dragging_pivot(responses);
+					}
+
+					Ready
+				}
+				(RotatingBounds, DragStop { .. }) => {
 					if let Some(bounds) = &mut data.bounding_box_overlays {
 						bounds.original_transforms.clear();
 					}
 
 					Ready
 				}
-				(DrawingBox, DragStop) => {
-					let quad = data.selection_quad();
-					responses.push_front(
-						DocumentMessage::AddSelectedLayers {
-							additional_layers: document.graphene_document.intersects_quad_root(quad),
-						}
-						.into(),
-					);
-					responses.push_front(
-						DocumentMessage::Overlays(
-							Operation::DeleteLayer {
-								path: data.drag_box_overlay_layer.take().unwrap(),
+				(DrawingBox, DragStop { enclose }) => {
+					let enclose = input.is_pressed(enclose);
+					let additional_layers = data.marquee_layers(document, tool_data.selection_tolerance, enclose);
+					if data.marquee_replaces_selection && !additional_layers.is_empty() {
+						responses.push_front(
+							DocumentMessage::SetSelectedLayers {
+								replacement_selected_layers: additional_layers,
 							}
 							.into(),
-						)
-						.into(),
-					);
+						);
+					} else {
+						responses.push_front(DocumentMessage::AddSelectedLayers { additional_layers }.into());
+					}
+					let mut buffer = Vec::new();
+					data.overlay_pool.release(OverlayRole::BoundingBox, data.drag_box_overlay_layer.take().unwrap(), &mut buffer);
+					buffer.into_iter().rev().for_each(|message| responses.push_front(message));
 					Ready
 				}
-				(_, Abort) => {
+				(state, Abort) => {
+					// Put the layers back where they were before the interaction that's being aborted
+					match state {
+						Dragging => responses.push_back(DocumentMessage::AbortTransaction.into()),
+						ResizingBounds | RotatingBounds => {
+							if let Some(bounds) = &mut data.bounding_box_overlays {
+								let selected = data.layers_dragging.iter().collect::<Vec<_>>();
+								let mut selected = Selected::new(&mut bounds.original_transforms, &mut bounds.pivot, &selected, responses, &document.graphene_document);
+
+								selected.revert_operation();
+							}
+						}
+						_ => {}
+					}
+
 					if let Some(path) = data.drag_box_overlay_layer.take() {
-						responses.push_front(DocumentMessage::Overlays(Operation::DeleteLayer { path }.into()).into())
+						data.overlay_pool.release(OverlayRole::BoundingBox, path, responses);
 					};
 					if let Some(bounding_box_overlays) = data.bounding_box_overlays.take() {
-						bounding_box_overlays.delete(responses);
+						bounding_box_overlays.delete(&mut data.overlay_pool, responses);
 					}
+					data.overlay_pool.clear(responses);
+					data.selection_outlines.delete(responses);
 
 					data.snap_handler.cleanup(responses);
+					data.distance_overlays.delete(responses);
+					data.equal_spacing_overlays.delete(responses);
+					data.axis_lock.clear(responses);
 					Ready
 				}
 				(_, Align { axis, aggregate }) => {
@@ -615,6 +961,19 @@ impl Fsm for SelectToolFsmState {
 
 					self
 				}
+				(Ready, RandomizeTransforms) => {
+					responses.push_back(
+						DocumentMessage::RandomizeTransforms {
+							position_jitter: tool_options.position_jitter,
+							rotation_jitter: tool_options.rotation_jitter,
+							scale_jitter: tool_options.scale_jitter,
+							seed: tool_options.seed,
+						}
+						.into(),
+					);
+
+					self
+				}
 				_ => self,
 			}
 		} else {
@@ -622,7 +981,8 @@ impl Fsm for SelectToolFsmState {
 		}
 	}
 
-	fn update_hints(&self, responses: &mut VecDeque<Message>) {
+	fn update_hints(&self, _tool_data: &DocumentToolData, input: &InputPreprocessorMessageHandler, responses: &mut VecDeque<Message>) {
+		let bindings = input.modifier_bindings;
 		let hint_data = match self {
 			SelectToolFsmState::Ready => HintData(vec![
 				HintGroup(vec![HintInfo {
@@ -650,6 +1010,12 @@ impl Fsm for SelectToolFsmState {
 						label: String::from("Scale Selected"),
 						plus: false,
 					},
+					HintInfo {
+						key_groups: vec![KeysGroup(vec![Key::KeyAlt])],
+						mouse: None,
+						label: String::from("Rotate/Scale Each"),
+						plus: true,
+					},
 				]),
 				HintGroup(vec![
 					HintInfo {
@@ -659,13 +1025,13 @@ impl Fsm for SelectToolFsmState {
 						plus: false,
 					},
 					HintInfo {
-						key_groups: vec![KeysGroup(vec![Key::KeyControl])],
+						key_groups: vec![KeysGroup(vec![Key::KeyPrimaryModifier])],
 						mouse: None,
 						label: String::from("Innermost"),
 						plus: true,
 					},
 					HintInfo {
-						key_groups: vec![KeysGroup(vec![Key::KeyShift])],
+						key_groups: vec![KeysGroup(vec![bindings.constrain])],
 						mouse: None,
 						label: String::from("Grow/Shrink Selection"),
 						plus: true,
@@ -679,7 +1045,7 @@ impl Fsm for SelectToolFsmState {
 						plus: false,
 					},
 					HintInfo {
-						key_groups: vec![KeysGroup(vec![Key::KeyShift])],
+						key_groups: vec![KeysGroup(vec![bindings.constrain])],
 						mouse: None,
 						label: String::from("Grow/Shrink Selection"),
 						plus: true,
@@ -706,13 +1072,13 @@ impl Fsm for SelectToolFsmState {
 				]),
 				HintGroup(vec![
 					HintInfo {
-						key_groups: vec![KeysGroup(vec![Key::KeyAlt])],
+						key_groups: vec![KeysGroup(vec![bindings.center])],
 						mouse: Some(MouseMotion::LmbDrag),
 						label: String::from("Move Duplicate"),
 						plus: false,
 					},
 					HintInfo {
-						key_groups: vec![KeysGroup(vec![Key::KeyControl, Key::KeyD])],
+						key_groups: vec![KeysGroup(vec![Key::KeyPrimaryModifier, Key::KeyD])],
 						mouse: None,
 						label: String::from("Duplicate"),
 						plus: false,
@@ -721,26 +1087,46 @@ impl Fsm for SelectToolFsmState {
 			]),
 			SelectToolFsmState::Dragging => HintData(vec![HintGroup(vec![
 				HintInfo {
-					key_groups: vec![KeysGroup(vec![Key::KeyShift])],
+					key_groups: vec![KeysGroup(vec![bindings.constrain])],
 					mouse: None,
 					label: String::from("Constrain to Axis"),
 					plus: false,
 				},
 				HintInfo {
-					key_groups: vec![KeysGroup(vec![Key::KeyControl])],
+					key_groups: vec![KeysGroup(vec![Key::KeyPrimaryModifier])],
 					mouse: None,
 					label: String::from("Snap to Points (coming soon)"),
 					plus: false,
 				},
+				HintInfo {
+					key_groups: vec![KeysGroup(vec![bindings.center])],
+					mouse: None,
+					label: String::from("Measure Distances"),
+					plus: false,
+				},
 			])]),
-			SelectToolFsmState::DrawingBox => HintData(vec![]),
-			SelectToolFsmState::ResizingBounds => HintData(vec![]),
-			SelectToolFsmState::RotatingBounds => HintData(vec![HintGroup(vec![HintInfo {
-				key_groups: vec![KeysGroup(vec![Key::KeyControl])],
-				mouse: None,
-				label: String::from("Snap 15°"),
+			SelectToolFsmState::DraggingPivot => HintData(vec![HintGroup(vec![HintInfo {
+				key_groups: vec![],
+				mouse: Some(MouseMotion::LmbDrag),
+				label: String::from("Move Pivot (Snaps to Handles and Center)"),
 				plus: false,
 			}])]),
+			SelectToolFsmState::DrawingBox => marquee_hints(false, bindings.center),
+			SelectToolFsmState::ResizingBounds => HintData(vec![]),
+			SelectToolFsmState::RotatingBounds => HintData(vec![HintGroup(vec![
+				HintInfo {
+					key_groups: vec![KeysGroup(vec![Key::KeyPrimaryModifier])],
+					mouse: None,
+					label: String::from("Snap Angle"),
+					plus: false,
+				},
+				HintInfo {
+					key_groups: vec![KeysGroup(vec![bindings.center])],
+					mouse: None,
+					label: String::from("Don't Straighten"),
+					plus: false,
+				},
+			])]),
 		};
 
 		responses.push_back(FrontendMessage::UpdateInputHints { hint_data }.into());
@@ -750,3 +1136,26 @@ impl Fsm for SelectToolFsmState {
 		responses.push_back(FrontendMessage::UpdateMouseCursor { cursor: MouseCursorIcon::Default }.into());
 	}
 }
+
+/// The hints shown while drawing the marquee, naming which layers it currently selects and the key held to only select enclosed ones.
+fn marquee_hints(enclose: bool, enclose_key: Key) -> HintData {
+	let mode = match enclose {
+		true => "Selecting Enclosed Layers",
+		false => "Selecting Touched Layers",
+	};
+
+	HintData(vec![
+		HintGroup(vec![HintInfo {
+			key_groups: vec![],
+			mouse: Some(MouseMotion::LmbDrag),
+			label: String::from(mode),
+			plus: false,
+		}]),
+		HintGroup(vec![HintInfo {
+			key_groups: vec![KeysGroup(vec![enclose_key])],
+			mouse: None,
+			label: String::from("Only Select Enclosed Layers"),
+			plus: false,
+		}]),
+	])
+}
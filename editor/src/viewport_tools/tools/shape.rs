@@ -1,19 +1,24 @@
-use super::shared::resize::Resize;
-use crate::consts::DRAG_THRESHOLD;
+use super::shared::resize::{handle_under, selected_shape, Resize};
+use crate::consts::{DRAG_THRESHOLD, MAX_POLYGON_SIDES};
 use crate::document::DocumentMessageHandler;
 use crate::frontend::utility_types::MouseCursorIcon;
 use crate::input::keyboard::{Key, MouseMotion};
+use crate::input::mouse::ViewportPosition;
 use crate::input::InputPreprocessorMessageHandler;
-use crate::layout::widgets::{LayoutRow, NumberInput, PropertyHolder, Widget, WidgetCallback, WidgetHolder, WidgetLayout};
+use crate::layout::layout_message::LayoutTarget;
+use crate::layout::widgets::{LayoutRow, NumberInput, PropertyHolder, RadioEntryData, RadioInput, Separator, SeparatorDirection, SeparatorType, Widget, WidgetCallback, WidgetHolder, WidgetLayout};
 use crate::message_prelude::*;
 use crate::misc::{HintData, HintGroup, HintInfo, KeysGroup};
 use crate::viewport_tools::tool::{DocumentToolData, Fsm, ToolActionHandlerData};
 
+use graphene::layers::layer_info::LayerDataType;
+use graphene::layers::simple_shape::ShapeParameters;
 use graphene::layers::style;
 use graphene::Operation;
 
-use glam::DAffine2;
+use glam::{DAffine2, DVec2};
 use serde::{Deserialize, Serialize};
+use std::f64::consts::{FRAC_PI_2, PI};
 
 #[derive(Default)]
 pub struct Shape {
@@ -23,60 +28,222 @@ pub struct Shape {
 }
 
 pub struct ShapeOptions {
-	vertices: u8,
+	mode: ShapeMode,
+	/// The number of sides of a polygon, or of points of a star
+	sides: u8,
+	/// The inner radius of a star as a fraction of its outer radius, where smaller values make deeper spikes
+	inner_radius: f64,
+	/// The radius in document units that each vertex is rounded by
+	corner_radius: f64,
+	/// In degrees, clockwise, how far each shape is turned about its center from where its first vertex usually points
+	rotation: f64,
 }
 
 impl Default for ShapeOptions {
 	fn default() -> Self {
-		Self { vertices: 6 }
+		Self {
+			mode: ShapeMode::Polygon,
+			sides: 6,
+			inner_radius: 0.5,
+			corner_radius: 0.,
+			rotation: 0.,
+		}
 	}
 }
 
+/// Whether the Shape tool draws a regular polygon or a star.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum ShapeMode {
+	Polygon,
+	Star,
+}
+
 #[remain::sorted]
 #[impl_message(Message, ToolMessage, Shape)]
-#[derive(PartialEq, Clone, Debug, Hash, Serialize, Deserialize)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub enum ShapeMessage {
 	// Standard messages
 	#[remain::unsorted]
 	Abort,
+	#[remain::unsorted]
+	DocumentIsDirty,
 
 	// Tool-specific messages
+	AutoPan,
 	DragStart,
 	DragStop,
 	Resize {
 		center: Key,
 		lock_ratio: Key,
+		rotate: Key,
+		snap_angle: Key,
 	},
 	UpdateOptions(ShapeOptionsUpdate),
 }
 
 #[remain::sorted]
-#[derive(PartialEq, Clone, Debug, Hash, Serialize, Deserialize)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub enum ShapeOptionsUpdate {
-	Vertices(u8),
+	CornerRadius(f64),
+	InnerRadius(f64),
+	Mode(ShapeMode),
+	Rotation(f64),
+	Sides(u8),
 }
 
 impl PropertyHolder for Shape {
 	fn properties(&self) -> WidgetLayout {
-		WidgetLayout::new(vec![LayoutRow::Row {
-			name: "".into(),
-			widgets: vec![WidgetHolder::new(Widget::NumberInput(NumberInput {
-				label: "Sides".into(),
-				value: self.options.vertices as f64,
+		let mut widgets = vec![
+			WidgetHolder::new(Widget::RadioInput(RadioInput {
+				selected_index: if self.options.mode == ShapeMode::Star { 1 } else { 0 },
+				entries: vec![
+					RadioEntryData {
+						value: "polygon".into(),
+						label: "Polygon".into(),
+						on_update: WidgetCallback::new(|_| ShapeMessage::UpdateOptions(ShapeOptionsUpdate::Mode(ShapeMode::Polygon)).into()),
+						..RadioEntryData::default()
+					},
+					RadioEntryData {
+						value: "star".into(),
+						label: "Star".into(),
+						on_update: WidgetCallback::new(|_| ShapeMessage::UpdateOptions(ShapeOptionsUpdate::Mode(ShapeMode::Star)).into()),
+						..RadioEntryData::default()
+					},
+				],
+			})),
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Unrelated,
+				direction: SeparatorDirection::Horizontal,
+			})),
+			WidgetHolder::new(Widget::NumberInput(NumberInput {
+				label: if self.options.mode == ShapeMode::Star { "Points" } else { "Sides" }.into(),
+				value: self.options.sides as f64,
 				is_integer: true,
 				min: Some(3.),
-				max: Some(256.),
-				on_update: WidgetCallback::new(|number_input| ShapeMessage::UpdateOptions(ShapeOptionsUpdate::Vertices(number_input.value as u8)).into()),
+				max: Some(MAX_POLYGON_SIDES as f64),
+				on_update: WidgetCallback::new(|number_input| ShapeMessage::UpdateOptions(ShapeOptionsUpdate::Sides(number_input.value as u8)).into()),
 				..NumberInput::default()
-			}))],
-		}])
+			})),
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Related,
+				direction: SeparatorDirection::Horizontal,
+			})),
+			WidgetHolder::new(Widget::NumberInput(NumberInput {
+				unit: "°".into(),
+				label: "Rotation".into(),
+				value: self.options.rotation,
+				on_update: WidgetCallback::new(|number_input| ShapeMessage::UpdateOptions(ShapeOptionsUpdate::Rotation(number_input.value)).into()),
+				..NumberInput::default()
+			})),
+		];
+
+		// Only a star has spikes whose depth can be set
+		if self.options.mode == ShapeMode::Star {
+			widgets.push(WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Related,
+				direction: SeparatorDirection::Horizontal,
+			})));
+			widgets.push(WidgetHolder::new(Widget::NumberInput(NumberInput {
+				label: "Inner Radius".into(),
+				value: self.options.inner_radius,
+				min: Some(0.),
+				max: Some(1.),
+				increment_factor: 0.05,
+				on_update: WidgetCallback::new(|number_input| ShapeMessage::UpdateOptions(ShapeOptionsUpdate::InnerRadius(number_input.value)).into()),
+				..NumberInput::default()
+			})));
+		}
+
+		widgets.push(WidgetHolder::new(Widget::Separator(Separator {
+			separator_type: SeparatorType::Unrelated,
+			direction: SeparatorDirection::Horizontal,
+		})));
+		widgets.push(WidgetHolder::new(Widget::NumberInput(NumberInput {
+			unit: " px".into(),
+			label: "Radius".into(),
+			value: self.options.corner_radius,
+			min: Some(0.),
+			on_update: WidgetCallback::new(|number_input| ShapeMessage::UpdateOptions(ShapeOptionsUpdate::CornerRadius(number_input.value)).into()),
+			..NumberInput::default()
+		})));
+
+		WidgetLayout::new(vec![LayoutRow::Row { name: "".into(), widgets }])
+	}
+}
+
+/// The single selected star, whose inner radius can be changed with a handle.
+struct SelectedStar {
+	path: Vec<LayerId>,
+	points: u8,
+	inner_radius: f64,
+	/// In radians, clockwise
+	rotation: f64,
+	/// The transform to the viewport from the unit square the star is drawn in
+	transform: DAffine2,
+}
+
+impl SelectedStar {
+	/// The direction from the center of the unit square towards the star's first inner vertex, which is where its inner radius handle sits.
+	fn inner_vertex_direction(&self) -> DVec2 {
+		let angle = PI / self.points as f64 - FRAC_PI_2 + self.rotation;
+		DVec2::new(angle.cos(), angle.sin())
+	}
+
+	/// The viewport position of the handle for the inner radius, on the star's first inner vertex.
+	fn handle_position(&self) -> ViewportPosition {
+		self.transform.transform_point2(DVec2::splat(0.5) + 0.5 * self.inner_radius * self.inner_vertex_direction())
+	}
+}
+
+fn selected_star(document: &DocumentMessageHandler) -> Option<SelectedStar> {
+	let (path, shape, transform) = selected_shape(document)?;
+	match shape.parametric {
+		Some(ShapeParameters::Polygon {
+			sides: points,
+			star_inner_radius: Some(inner_radius),
+			rotation,
+			..
+		}) => Some(SelectedStar {
+			path: path.to_vec(),
+			points,
+			inner_radius,
+			rotation,
+			transform,
+		}),
+		_ => None,
 	}
 }
 
+/// Finds the path of the selected star if its inner radius handle is under the pointer.
+fn inner_radius_handle_under(document: &DocumentMessageHandler, position: ViewportPosition) -> Option<Vec<LayerId>> {
+	selected_star(document).filter(|star| handle_under(&[star.handle_position()], position).is_some()).map(|star| star.path)
+}
+
+/// Applies the operation made by `operation` to each selected polygon and star, so editing the tool options shows its effect on existing layers.
+/// Each shape keeps its layer transform, so it's regenerated within the same bounding box.
+fn update_selected_polygons(document: &DocumentMessageHandler, operation: impl Fn(Vec<LayerId>) -> Operation, responses: &mut VecDeque<Message>) {
+	let polygons: Vec<_> = document
+		.selected_layers()
+		.filter(|path| matches!(document.graphene_document.layer(path).map(|layer| &layer.data), Ok(LayerDataType::Shape(shape)) if matches!(shape.parametric, Some(ShapeParameters::Polygon { .. }))))
+		.map(|path| path.to_vec())
+		.collect();
+	if polygons.is_empty() {
+		return;
+	}
+
+	responses.push_back(DocumentMessage::StartTransaction.into());
+	for path in polygons {
+		responses.push_back(operation(path).into());
+	}
+	responses.push_back(DocumentMessage::CommitTransaction.into());
+}
+
+consumes_input_unless_ready!(Shape);
+
 impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Shape {
 	fn process_action(&mut self, action: ToolMessage, data: ToolActionHandlerData<'a>, responses: &mut VecDeque<Message>) {
 		if action == ToolMessage::UpdateHints {
-			self.fsm_state.update_hints(responses);
+			self.fsm_state.update_hints(data.1, data.2, responses);
 			return;
 		}
 
@@ -87,7 +254,34 @@ impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Shape {
 
 		if let ToolMessage::Shape(ShapeMessage::UpdateOptions(action)) = action {
 			match action {
-				ShapeOptionsUpdate::Vertices(vertices) => self.options.vertices = vertices,
+				ShapeOptionsUpdate::CornerRadius(corner_radius) => {
+					let corner_radius = corner_radius.max(0.);
+					self.options.corner_radius = corner_radius;
+					update_selected_polygons(data.0, |path| Operation::SetPolygonCornerRadius { path, corner_radius }, responses);
+				}
+				ShapeOptionsUpdate::InnerRadius(inner_radius) => {
+					let inner_radius = inner_radius.clamp(0., 1.);
+					self.options.inner_radius = inner_radius;
+					update_selected_polygons(data.0, |path| Operation::SetStarInnerRadius { path, inner_radius }, responses);
+				}
+				ShapeOptionsUpdate::Mode(mode) => {
+					self.options.mode = mode;
+					self.register_properties(responses, LayoutTarget::ToolOptions);
+				}
+				ShapeOptionsUpdate::Rotation(rotation) => {
+					self.options.rotation = rotation;
+					let rotation = rotation.to_radians();
+					update_selected_polygons(data.0, |path| Operation::SetPolygonRotation { path, rotation }, responses);
+				}
+				ShapeOptionsUpdate::Sides(sides) => {
+					self.options.sides = sides.clamp(3, MAX_POLYGON_SIDES);
+					// Show the clamped value in place of one that was out of range
+					if self.options.sides != sides {
+						self.register_properties(responses, LayoutTarget::ToolOptions);
+					}
+					let sides = self.options.sides;
+					update_selected_polygons(data.0, |path| Operation::SetPolygonSides { path, sides }, responses);
+				}
 			}
 			return;
 		}
@@ -96,7 +290,7 @@ impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Shape {
 
 		if self.fsm_state != new_state {
 			self.fsm_state = new_state;
-			self.fsm_state.update_hints(responses);
+			self.fsm_state.update_hints(data.1, data.2, responses);
 			self.fsm_state.update_cursor(responses);
 		}
 	}
@@ -106,7 +300,8 @@ impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Shape {
 
 		match self.fsm_state {
 			Ready => actions!(ShapeMessageDiscriminant; DragStart),
-			Drawing => actions!(ShapeMessageDiscriminant; DragStop, Abort, Resize),
+			Drawing => actions!(ShapeMessageDiscriminant; AutoPan, DragStop, Abort, Resize),
+			DraggingInnerRadius => actions!(ShapeMessageDiscriminant; DragStop, Abort, Resize),
 		}
 	}
 }
@@ -115,6 +310,8 @@ impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Shape {
 enum ShapeToolFsmState {
 	Ready,
 	Drawing,
+	/// Dragging the handle on an inner vertex of the selected star to change how deep its spikes are
+	DraggingInnerRadius,
 }
 
 impl Default for ShapeToolFsmState {
@@ -126,6 +323,10 @@ impl Default for ShapeToolFsmState {
 struct ShapeToolData {
 	sides: u8,
 	data: Resize,
+	/// The star whose inner radius is being changed
+	dragging: Option<Vec<LayerId>>,
+	/// The rotation in radians given to the shape being drawn, which snaps to increments while Shift is held
+	rotation: f64,
 }
 
 impl Fsm for ShapeToolFsmState {
@@ -149,33 +350,121 @@ impl Fsm for ShapeToolFsmState {
 
 		if let ToolMessage::Shape(event) = event {
 			match (self, event) {
+				(_, DocumentIsDirty) => {
+					// The handle for the inner radius sits on an inner vertex of the selected star
+					if self != Drawing {
+						let handle_positions = selected_star(document).map_or(Vec::new(), |star| vec![star.handle_position()]);
+						shape_data.update_handles(&handle_positions, responses);
+					}
+
+					self
+				}
 				(Ready, DragStart) => {
+					// Grabbing the handle of the selected star changes its inner radius instead of drawing a new shape
+					if let Some(path) = inner_radius_handle_under(document, input.mouse.position) {
+						responses.push_back(DocumentMessage::StartTransaction.into());
+						data.dragging = Some(path);
+
+						return DraggingInnerRadius;
+					}
+					shape_data.hide_handles(responses);
+
 					shape_data.start(responses, input.viewport_bounds.size(), document, input.mouse.position);
 					responses.push_back(DocumentMessage::StartTransaction.into());
 					shape_data.path = Some(document.get_path_for_new_layer());
 					responses.push_back(DocumentMessage::DeselectAllLayers.into());
-					data.sides = tool_options.vertices;
-
-					responses.push_back(
-						Operation::AddNgon {
-							path: shape_data.path.clone().unwrap(),
+					data.sides = tool_options.sides;
+					data.rotation = 0.;
+
+					let path = shape_data.path.clone().unwrap();
+					let transform = DAffine2::ZERO.to_cols_array();
+					let style = style::PathStyle::new(None, Some(style::Fill::new(tool_data.primary_color)));
+					let operation = match tool_options.mode {
+						ShapeMode::Polygon => Operation::AddNgon {
+							path,
 							insert_index: -1,
-							transform: DAffine2::ZERO.to_cols_array(),
+							transform,
 							sides: data.sides,
-							style: style::PathStyle::new(None, Some(style::Fill::new(tool_data.primary_color))),
-						}
-						.into(),
-					);
+							style,
+						},
+						ShapeMode::Star => Operation::AddStar {
+							path,
+							insert_index: -1,
+							transform,
+							points: data.sides,
+							inner_radius: tool_options.inner_radius,
+							style,
+						},
+					};
+					responses.push_back(operation.into());
 
 					Drawing
 				}
-				(state, Resize { center, lock_ratio }) => {
-					if let Some(message) = shape_data.calculate_transform(responses, input.viewport_bounds.size(), document, center, lock_ratio, input) {
+				(DraggingInnerRadius, Resize { .. }) => {
+					if let Some(star) = selected_star(document).filter(|star| Some(&star.path) == data.dragging.as_ref()) {
+						// How far the pointer is from the center of the star towards the handle's inner vertex, in the unit square it's drawn in
+						let pointer = star.transform.inverse().transform_point2(input.mouse.position) - DVec2::splat(0.5);
+						let inner_radius = (pointer.dot(star.inner_vertex_direction()) / 0.5).clamp(0., 1.);
+						let path = star.path;
+
+						responses.push_back(Operation::SetStarInnerRadius { path, inner_radius }.into());
+					}
+
+					DraggingInnerRadius
+				}
+				(DraggingInnerRadius, DragStop) => {
+					responses.push_back(DocumentMessage::CommitTransaction.into());
+					data.dragging = None;
+
+					Ready
+				}
+				(DraggingInnerRadius, Abort) => {
+					responses.push_back(DocumentMessage::AbortTransaction.into());
+					data.dragging = None;
+
+					Ready
+				}
+				(Ready, Abort) => {
+					shape_data.clear_handles(responses);
+
+					Ready
+				}
+				(
+					state,
+					Resize {
+						center,
+						lock_ratio,
+						rotate,
+						snap_angle,
+					},
+				) => {
+					if let Some(message) = shape_data.calculate_transform(responses, input.viewport_bounds.size(), document, center, lock_ratio, rotate, snap_angle, input) {
 						responses.push_back(message);
+
+						// Round the corners again since their arcs are relative to the new size
+						if let Some(path) = shape_data.path.clone().filter(|_| tool_options.corner_radius > 0.) {
+							let corner_radius = tool_options.corner_radius;
+							responses.push_back(Operation::SetPolygonCornerRadius { path, corner_radius }.into());
+						}
+
+						let mut rotation = tool_options.rotation.to_radians();
+						if input.is_pressed(snap_angle) {
+							let snap_resolution = input.rotate_snap_angle.to_radians();
+							rotation = (rotation / snap_resolution).round() * snap_resolution;
+						}
+						if let Some(path) = shape_data.path.clone().filter(|_| rotation != data.rotation) {
+							data.rotation = rotation;
+							responses.push_back(Operation::SetPolygonRotation { path, rotation }.into());
+						}
 					}
 
 					state
 				}
+				(Drawing, AutoPan) => {
+					shape_data.auto_pan(tool_data, input, responses);
+
+					Drawing
+				}
 				(Drawing, DragStop) => {
 					match shape_data.drag_start.distance(input.mouse.position) <= DRAG_THRESHOLD {
 						true => responses.push_back(DocumentMessage::AbortTransaction.into()),
@@ -183,6 +472,8 @@ impl Fsm for ShapeToolFsmState {
 					}
 
 					shape_data.cleanup(responses);
+					// Shows the handle on the new star once it's committed
+					responses.push_back(ToolMessage::DocumentIsDirty.into());
 
 					Ready
 				}
@@ -200,7 +491,8 @@ impl Fsm for ShapeToolFsmState {
 		}
 	}
 
-	fn update_hints(&self, responses: &mut VecDeque<Message>) {
+	fn update_hints(&self, _tool_data: &DocumentToolData, input: &InputPreprocessorMessageHandler, responses: &mut VecDeque<Message>) {
+		let bindings = input.modifier_bindings;
 		let hint_data = match self {
 			ShapeToolFsmState::Ready => HintData(vec![HintGroup(vec![
 				HintInfo {
@@ -210,13 +502,13 @@ impl Fsm for ShapeToolFsmState {
 					plus: false,
 				},
 				HintInfo {
-					key_groups: vec![KeysGroup(vec![Key::KeyShift])],
+					key_groups: vec![KeysGroup(vec![bindings.constrain])],
 					mouse: None,
 					label: String::from("Constrain 1:1 Aspect"),
 					plus: true,
 				},
 				HintInfo {
-					key_groups: vec![KeysGroup(vec![Key::KeyAlt])],
+					key_groups: vec![KeysGroup(vec![bindings.center])],
 					mouse: None,
 					label: String::from("From Center"),
 					plus: true,
@@ -224,18 +516,31 @@ impl Fsm for ShapeToolFsmState {
 			])]),
 			ShapeToolFsmState::Drawing => HintData(vec![HintGroup(vec![
 				HintInfo {
-					key_groups: vec![KeysGroup(vec![Key::KeyShift])],
+					key_groups: vec![KeysGroup(vec![bindings.constrain])],
 					mouse: None,
 					label: String::from("Constrain 1:1 Aspect"),
 					plus: false,
 				},
 				HintInfo {
-					key_groups: vec![KeysGroup(vec![Key::KeyAlt])],
+					key_groups: vec![KeysGroup(vec![bindings.center])],
 					mouse: None,
 					label: String::from("From Center"),
 					plus: false,
 				},
+				HintInfo {
+					key_groups: vec![KeysGroup(vec![Key::KeyR])],
+					mouse: None,
+					label: String::from("Rotate"),
+					plus: false,
+				},
+				HintInfo {
+					key_groups: vec![KeysGroup(vec![Key::KeyPrimaryModifier, Key::KeyR])],
+					mouse: None,
+					label: String::from("Rotate by Snap Angle"),
+					plus: false,
+				},
 			])]),
+			ShapeToolFsmState::DraggingInnerRadius => HintData(vec![]),
 		};
 
 		responses.push_back(FrontendMessage::UpdateInputHints { hint_data }.into());
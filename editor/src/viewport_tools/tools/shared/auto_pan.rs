@@ -0,0 +1,64 @@
+use crate::input::InputPreprocessorMessageHandler;
+use crate::message_prelude::*;
+use crate::viewport_tools::tool::DocumentToolData;
+
+use glam::DVec2;
+
+/// Pans the canvas on a frame tick while the pointer is held within the auto-pan margin of the viewport edge, so a drag can continue past the visible area.
+/// The closer the pointer is to the edge (or the further past it), the faster the canvas moves, up to the auto-pan speed.
+///
+/// Returns how far the canvas was moved in viewport space, which the tool should add to any viewport positions it remembered from earlier in the drag.
+/// The pointer move is then replayed so the drag catches up with the new canvas position.
+pub fn auto_pan(tool_data: &DocumentToolData, input: &InputPreprocessorMessageHandler, responses: &mut VecDeque<Message>) -> Option<DVec2> {
+	let (margin, speed) = (tool_data.auto_pan_margin, tool_data.auto_pan_speed);
+	let delta = auto_pan_delta(margin, speed, input.mouse.position, input.viewport_bounds.size(), input.frame_delta_time);
+	if delta == DVec2::ZERO {
+		return None;
+	}
+
+	responses.push_back(MovementMessage::TranslateCanvas { delta }.into());
+	responses.push_back(InputMapperMessage::PointerMove.into());
+
+	Some(delta)
+}
+
+/// The distance to move the canvas by this frame, moving the content away from the edge the pointer is approaching to reveal what lies beyond it.
+fn auto_pan_delta(margin: f64, speed: f64, pointer: DVec2, viewport_size: DVec2, delta_time: f64) -> DVec2 {
+	if margin <= 0. || speed <= 0. {
+		return DVec2::ZERO;
+	}
+
+	let strength = |distance_to_edge: f64| ((margin - distance_to_edge) / margin).clamp(0., 1.);
+	let direction = DVec2::new(strength(pointer.x) - strength(viewport_size.x - pointer.x), strength(pointer.y) - strength(viewport_size.y - pointer.y));
+
+	// The speed is in viewport pixels per second and the frame delta time is in milliseconds
+	direction * speed * delta_time / 1000.
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	const MARGIN: f64 = 20.;
+	const SPEED: f64 = 1000.;
+	const VIEWPORT: DVec2 = DVec2::new(800., 600.);
+
+	#[test]
+	fn auto_pan_only_near_edges() {
+		assert_eq!(auto_pan_delta(MARGIN, SPEED, DVec2::new(400., 300.), VIEWPORT, 16.), DVec2::ZERO);
+		assert_eq!(auto_pan_delta(MARGIN, SPEED, DVec2::new(MARGIN, 300.), VIEWPORT, 16.), DVec2::ZERO);
+
+		// Halfway into the left margin pans right at half speed, right at the bottom edge pans up at full speed
+		assert_eq!(auto_pan_delta(MARGIN, SPEED, DVec2::new(10., 300.), VIEWPORT, 16.), DVec2::new(8., 0.));
+		assert_eq!(auto_pan_delta(MARGIN, SPEED, DVec2::new(400., 600.), VIEWPORT, 16.), DVec2::new(0., -16.));
+
+		// Beyond the edge the speed doesn't increase further
+		assert_eq!(auto_pan_delta(MARGIN, SPEED, DVec2::new(-50., -50.), VIEWPORT, 16.), DVec2::new(16., 16.));
+	}
+
+	#[test]
+	fn auto_pan_disabled() {
+		assert_eq!(auto_pan_delta(0., SPEED, DVec2::new(0., 0.), VIEWPORT, 16.), DVec2::ZERO);
+		assert_eq!(auto_pan_delta(MARGIN, 0., DVec2::new(0., 0.), VIEWPORT, 16.), DVec2::ZERO);
+	}
+}
@@ -0,0 +1,77 @@
+use crate::consts::{AXIS_LOCK_DRAG_THRESHOLD, COLOR_ACCENT};
+use crate::message_prelude::*;
+
+use graphene::layers::style::{self, Stroke};
+use graphene::Operation;
+
+use glam::{DAffine2, DVec2};
+
+/// Locks a drag to the horizontal or vertical axis, whichever the drag initially moved toward. The axis is kept until the lock is released, so it doesn't flip between axes.
+#[derive(Clone, Debug, Default)]
+pub struct AxisLock {
+	axis: Option<DVec2>,
+	guide_overlay: Option<Vec<LayerId>>,
+}
+
+impl AxisLock {
+	/// Projects the position onto the locked axis through the drag start, choosing the axis once the drag has moved far enough to tell its direction.
+	/// Releasing the lock unlocks the axis and returns the position unchanged.
+	pub fn constrain(&mut self, lock: bool, position: DVec2, start: DVec2) -> DVec2 {
+		if !lock {
+			self.axis = None;
+			return position;
+		}
+
+		let delta = position - start;
+		let axis = match self.axis {
+			Some(axis) => axis,
+			None if delta.length() < AXIS_LOCK_DRAG_THRESHOLD => return start,
+			None => *self.axis.insert(if delta.x.abs() >= delta.y.abs() { DVec2::X } else { DVec2::Y }),
+		};
+
+		start + axis * delta.dot(axis)
+	}
+
+	/// Removes the part of an offset (such as a snapping adjustment) that would move off the locked axis.
+	pub fn constrain_offset(&self, offset: DVec2) -> DVec2 {
+		self.axis.map_or(offset, |axis| axis * offset.dot(axis))
+	}
+
+	/// Shows a guide line across the viewport along the locked axis, or removes it when unlocked.
+	pub fn update_guide(&mut self, responses: &mut VecDeque<Message>, start: DVec2, viewport_size: DVec2) {
+		match (self.axis, &self.guide_overlay) {
+			(Some(axis), None) => {
+				let half_length = viewport_size.length();
+				let transform = DAffine2::from_scale_angle_translation(DVec2::new(half_length * 2., 1.), DVec2::X.angle_between(axis), (start - axis * half_length).round() - DVec2::splat(0.5));
+
+				let path = vec![generate_uuid()];
+				responses.push_back(
+					DocumentMessage::Overlays(
+						Operation::AddOverlayLine {
+							path: path.clone(),
+							transform: transform.to_cols_array(),
+							style: style::PathStyle::new(Some(Stroke::new(COLOR_ACCENT, 1.0)), None),
+						}
+						.into(),
+					)
+					.into(),
+				);
+				self.guide_overlay = Some(path);
+			}
+			(None, Some(_)) => self.delete_guide(responses),
+			_ => {}
+		}
+	}
+
+	/// Unlocks the axis and removes the guide line. Call this when the drag ends.
+	pub fn clear(&mut self, responses: &mut VecDeque<Message>) {
+		self.axis = None;
+		self.delete_guide(responses);
+	}
+
+	fn delete_guide(&mut self, responses: &mut VecDeque<Message>) {
+		if let Some(path) = self.guide_overlay.take() {
+			responses.push_back(DocumentMessage::Overlays(Operation::DeleteLayer { path }.into()).into());
+		}
+	}
+}
@@ -0,0 +1,97 @@
+use crate::consts::{COLOR_ACCENT, MEASUREMENT_LABEL_FONT_SIZE, MEASUREMENT_LABEL_OFFSET};
+use crate::message_prelude::*;
+
+use graphene::layers::style::{self, Fill, Stroke};
+use graphene::Operation;
+
+use glam::{DAffine2, DVec2};
+
+/// Overlay lines with a text label showing the length of each line, used to measure the gaps between layers.
+#[derive(Clone, Debug, Default)]
+pub struct DistanceOverlays {
+	overlay_paths: Vec<(Vec<LayerId>, Vec<LayerId>)>,
+}
+
+impl DistanceOverlays {
+	/// Draws a labelled line for each of the given gaps (in viewport coords), reusing the overlays from the pool if available.
+	/// `viewport_to_document_scale` converts the viewport lengths into the document lengths shown in the labels.
+	pub fn update(&mut self, responses: &mut VecDeque<Message>, gaps: &[[DVec2; 2]], viewport_to_document_scale: f64) {
+		for (index, &[start, end]) in gaps.iter().enumerate() {
+			let line_vector = end - start;
+			let line_transform = DAffine2::from_scale_angle_translation(DVec2::new(line_vector.length(), 1.), DVec2::X.angle_between(line_vector), start.round() - DVec2::splat(0.5)).to_cols_array();
+			let label_transform = DAffine2::from_translation((start + end) / 2. + DVec2::splat(MEASUREMENT_LABEL_OFFSET)).to_cols_array();
+			let label = format!("{}", (line_vector.length() * viewport_to_document_scale * 100.).round() / 100.);
+
+			if index < self.overlay_paths.len() {
+				let (line_path, label_path) = self.overlay_paths[index].clone();
+
+				responses.push_back(
+					DocumentMessage::Overlays(
+						Operation::SetLayerTransform {
+							path: line_path,
+							transform: line_transform,
+						}
+						.into(),
+					)
+					.into(),
+				);
+				responses.push_back(
+					DocumentMessage::Overlays(
+						Operation::SetLayerTransform {
+							path: label_path.clone(),
+							transform: label_transform,
+						}
+						.into(),
+					)
+					.into(),
+				);
+				responses.push_back(DocumentMessage::Overlays(Operation::SetTextContent { path: label_path, new_text: label }.into()).into());
+			} else {
+				let line_path = vec![generate_uuid()];
+				let label_path = vec![generate_uuid()];
+
+				responses.push_back(
+					DocumentMessage::Overlays(
+						Operation::AddOverlayLine {
+							path: line_path.clone(),
+							transform: line_transform,
+							style: style::PathStyle::new(Some(Stroke::new(COLOR_ACCENT, 1.0)), None),
+						}
+						.into(),
+					)
+					.into(),
+				);
+				responses.push_back(
+					DocumentMessage::Overlays(
+						Operation::AddText {
+							path: label_path.clone(),
+							transform: label_transform,
+							insert_index: -1,
+							text: label,
+							style: style::PathStyle::new(None, Some(Fill::new(COLOR_ACCENT))),
+							size: MEASUREMENT_LABEL_FONT_SIZE,
+						}
+						.into(),
+					)
+					.into(),
+				);
+				self.overlay_paths.push((line_path, label_path));
+			}
+		}
+
+		self.remove_unused_overlays(responses, gaps.len());
+	}
+
+	/// Removes all of the measurement overlays. Call this when the measuring is done.
+	pub fn delete(&mut self, responses: &mut VecDeque<Message>) {
+		self.remove_unused_overlays(responses, 0);
+	}
+
+	fn remove_unused_overlays(&mut self, responses: &mut VecDeque<Message>, remove_after_index: usize) {
+		while self.overlay_paths.len() > remove_after_index {
+			let (line_path, label_path) = self.overlay_paths.pop().unwrap();
+			responses.push_back(DocumentMessage::Overlays(Operation::DeleteLayer { path: line_path }.into()).into());
+			responses.push_back(DocumentMessage::Overlays(Operation::DeleteLayer { path: label_path }.into()).into());
+		}
+	}
+}
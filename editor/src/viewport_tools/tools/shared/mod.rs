@@ -1,2 +1,7 @@
+pub mod auto_pan;
+pub mod axis_lock;
+pub mod distance_overlays;
+pub mod overlay_pool;
 pub mod resize;
+pub mod selection_outlines;
 pub mod transformation_cage;
@@ -0,0 +1,146 @@
+use crate::consts::COLOR_ACCENT;
+use crate::message_prelude::*;
+
+use graphene::color::Color;
+use graphene::layers::style::{self, Fill, Stroke};
+use graphene::Operation;
+
+use glam::DAffine2;
+use kurbo::BezPath;
+use std::collections::HashMap;
+
+/// What an overlay is used for, which determines the shape and style it's created with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OverlayRole {
+	/// The outline of a bounding box, also used for the box drawn while drag selecting
+	BoundingBox,
+	/// One of the square handles on the sides and corners of a transformation cage
+	TransformHandle,
+	/// The round marker for the point a transformation cage rotates about
+	PivotMarker,
+	/// One of the round handles dragged to edit a selected shape, like those on the ends of an arc or the corner of a rectangle
+	ShapeHandle,
+	/// The segment the Pen tool would add to the path being drawn, which is given its shape each time it's shown
+	PenSegment,
+	/// A small square marking where two alignment lines that positions snap onto cross, shown while debugging snapping
+	SnapIntersectionMarker,
+	/// A small circle marking a point that positions snap onto, shown while debugging snapping
+	SnapPointMarker,
+}
+
+impl OverlayRole {
+	fn create(self, buffer: &mut Vec<Message>) -> Vec<LayerId> {
+		let path = vec![generate_uuid()];
+
+		let transform = DAffine2::ZERO.to_cols_array();
+		let operation = match self {
+			OverlayRole::BoundingBox => Operation::AddOverlayRect {
+				path: path.clone(),
+				transform,
+				style: style::PathStyle::new(Some(Stroke::new(COLOR_ACCENT, 1.0)), None),
+			},
+			OverlayRole::TransformHandle => Operation::AddOverlayRect {
+				path: path.clone(),
+				transform,
+				style: style::PathStyle::new(Some(Stroke::new(COLOR_ACCENT, 2.0)), Some(Fill::new(Color::WHITE))),
+			},
+			OverlayRole::PivotMarker | OverlayRole::ShapeHandle => Operation::AddOverlayEllipse {
+				path: path.clone(),
+				transform,
+				style: style::PathStyle::new(Some(Stroke::new(COLOR_ACCENT, 2.0)), Some(Fill::new(Color::WHITE))),
+			},
+			// The segment starts out with an empty path, which draws nothing just like a collapsed overlay
+			OverlayRole::PenSegment => Operation::AddOverlayShape {
+				path: path.clone(),
+				bez_path: BezPath::new(),
+				style: style::PathStyle::new(Some(Stroke::new(COLOR_ACCENT, 1.0)), None),
+				closed: false,
+			},
+			OverlayRole::SnapIntersectionMarker => Operation::AddOverlayRect {
+				path: path.clone(),
+				transform,
+				style: style::PathStyle::new(Some(Stroke::new(COLOR_ACCENT, 1.0)), None),
+			},
+			OverlayRole::SnapPointMarker => Operation::AddOverlayEllipse {
+				path: path.clone(),
+				transform,
+				style: style::PathStyle::new(Some(Stroke::new(COLOR_ACCENT, 1.0)), None),
+			},
+		};
+		buffer.push(DocumentMessage::Overlays(operation.into()).into());
+
+		path
+	}
+}
+
+/// Overlays that are no longer in use, kept around (collapsed to a zero transform so they aren't drawn) to be repositioned
+/// the next time an overlay with the same role is needed, rather than being deleted and recreated with a new ID.
+#[derive(Clone, Debug, Default)]
+pub struct OverlayPool {
+	unused_overlays: HashMap<OverlayRole, Vec<Vec<LayerId>>>,
+}
+
+impl OverlayPool {
+	/// Takes an overlay with the given role from the pool, or creates a new one if none are available.
+	/// The overlay starts out collapsed, so it isn't drawn until it's given a transform.
+	#[must_use]
+	pub fn acquire(&mut self, role: OverlayRole, buffer: &mut Vec<Message>) -> Vec<LayerId> {
+		match self.unused_overlays.get_mut(&role).and_then(|paths| paths.pop()) {
+			Some(path) => path,
+			None => role.create(buffer),
+		}
+	}
+
+	/// Collapses the overlay and returns it to the pool to be reused.
+	pub fn release(&mut self, role: OverlayRole, path: Vec<LayerId>, buffer: &mut impl Extend<Message>) {
+		let transform = DAffine2::ZERO.to_cols_array();
+		buffer.extend([DocumentMessage::Overlays(Operation::SetLayerTransform { path: path.clone(), transform }.into()).into()]);
+
+		self.unused_overlays.entry(role).or_default().push(path);
+	}
+
+	/// Deletes every overlay in the pool. Call this when the tool no longer needs its overlays, such as when it is aborted.
+	pub fn clear(&mut self, buffer: &mut impl Extend<Message>) {
+		let paths = self.unused_overlays.drain().flat_map(|(_, paths)| paths);
+		buffer.extend(paths.map(|path| DocumentMessage::Overlays(Operation::DeleteLayer { path }.into()).into()));
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn count_operations(messages: &[Message], filter: impl Fn(&Operation) -> bool) -> usize {
+		messages
+			.iter()
+			.filter(|message| match message {
+				Message::Portfolio(PortfolioMessage::Document(DocumentMessage::Overlays(OverlaysMessage::DispatchOperation(operation)))) => filter(operation),
+				_ => false,
+			})
+			.count()
+	}
+
+	#[test]
+	fn rapid_selection_changes_reuse_overlays() {
+		let mut pool = OverlayPool::default();
+		let mut buffer = Vec::new();
+
+		// Show and hide a transformation cage, as when repeatedly selecting and deselecting a layer
+		for _ in 0..10 {
+			let bounding_box = pool.acquire(OverlayRole::BoundingBox, &mut buffer);
+			let handles: Vec<_> = (0..8).map(|_| pool.acquire(OverlayRole::TransformHandle, &mut buffer)).collect();
+
+			pool.release(OverlayRole::BoundingBox, bounding_box, &mut buffer);
+			handles.into_iter().for_each(|path| pool.release(OverlayRole::TransformHandle, path, &mut buffer));
+		}
+
+		// Only the first cage creates its overlays, where previously every cage created and deleted all nine
+		assert_eq!(count_operations(&buffer, |operation| matches!(operation, Operation::AddOverlayRect { .. })), 9);
+		assert_eq!(count_operations(&buffer, |operation| matches!(operation, Operation::DeleteLayer { .. })), 0);
+		assert_eq!(buffer.len(), 9 + 10 * 9);
+
+		let mut buffer = Vec::new();
+		pool.clear(&mut buffer);
+		assert_eq!(count_operations(&buffer, |operation| matches!(operation, Operation::DeleteLayer { .. })), 9);
+	}
+}
@@ -1,10 +1,17 @@
+use super::auto_pan::auto_pan;
+use super::overlay_pool::{OverlayPool, OverlayRole};
+use crate::consts::{COLOR_ACCENT, MEASUREMENT_LABEL_FONT_SIZE, READOUT_CURSOR_OFFSET, SHAPE_HANDLE_GRAB_RADIUS, SHAPE_HANDLE_SIZE};
 use crate::document::DocumentMessageHandler;
 use crate::input::keyboard::Key;
 use crate::input::mouse::ViewportPosition;
 use crate::input::InputPreprocessorMessageHandler;
 use crate::message_prelude::*;
 use crate::viewport_tools::snapping::SnapHandler;
+use crate::viewport_tools::tool::DocumentToolData;
 
+use graphene::layers::layer_info::LayerDataType;
+use graphene::layers::simple_shape::Shape;
+use graphene::layers::style::{self, Fill};
 use graphene::Operation;
 
 use glam::{DAffine2, DVec2, Vec2Swizzles};
@@ -14,6 +21,37 @@ pub struct Resize {
 	pub drag_start: ViewportPosition,
 	pub path: Option<Vec<LayerId>>,
 	snap_handler: SnapHandler,
+	/// The start and size of the shape the last time it was resized, which stay fixed while it's rotated
+	bounds: (DVec2, DVec2),
+	/// The rotation of the shape in radians, turned about its center
+	rotation: f64,
+	/// While rotating, the angle of the pointer around the shape's center at which the shape would have no rotation
+	rotation_origin: Option<f64>,
+	/// The overlay label next to the cursor showing the width and height of the shape being drawn
+	readout_path: Option<Vec<LayerId>>,
+	/// The width divided by the height in document space that the shape is held to, taking precedence over the `lock_ratio` key
+	pub ratio_lock: Option<f64>,
+	/// The round handles shown on the selected shape, which are dragged to edit it
+	handles: Vec<Vec<LayerId>>,
+	overlay_pool: OverlayPool,
+}
+
+/// The single selected layer if it's a shape, along with its path and its transform to the viewport, as long as that doesn't flatten it.
+pub fn selected_shape(document: &DocumentMessageHandler) -> Option<(&[LayerId], &Shape, DAffine2)> {
+	let mut selected = document.selected_visible_layers();
+	let path = selected.next().filter(|_| selected.next().is_none())?;
+
+	let shape = match &document.graphene_document.layer(path).ok()?.data {
+		LayerDataType::Shape(shape) => shape,
+		_ => return None,
+	};
+	let transform = document.graphene_document.generate_transform_relative_to_viewport(path).ok()?;
+	(transform.matrix2.determinant() != 0.).then(|| (path, shape, transform))
+}
+
+/// The index of the first of the handle positions within grabbing distance of the pointer, if any.
+pub fn handle_under(handle_positions: &[ViewportPosition], pointer: ViewportPosition) -> Option<usize> {
+	handle_positions.iter().position(|position| position.distance(pointer) <= SHAPE_HANDLE_GRAB_RADIUS)
 }
 
 impl Resize {
@@ -23,6 +61,8 @@ impl Resize {
 		self.drag_start = self.snap_handler.snap_position(responses, viewport_bounds, document, mouse_position);
 	}
 
+	/// Resizes the shape to the pointer, or while the `rotate` key is held, turns the shape about its center to follow the pointer instead.
+	#[allow(clippy::too_many_arguments)]
 	pub fn calculate_transform(
 		&mut self,
 		responses: &mut VecDeque<Message>,
@@ -30,36 +70,147 @@ impl Resize {
 		document: &DocumentMessageHandler,
 		center: Key,
 		lock_ratio: Key,
+		rotate: Key,
+		snap_angle: Key,
 		ipp: &InputPreprocessorMessageHandler,
 	) -> Option<Message> {
-		if let Some(path) = &self.path {
+		let path = self.path.as_ref()?;
+
+		if ipp.is_pressed(rotate) {
+			let (start, size) = self.bounds;
+			let pointer_offset = ipp.mouse.position - (start + size / 2.);
+			let pointer_angle = pointer_offset.y.atan2(pointer_offset.x);
+
+			let rotation_origin = *self.rotation_origin.get_or_insert(pointer_angle - self.rotation);
+			self.rotation = pointer_angle - rotation_origin;
+			if ipp.is_pressed(snap_angle) {
+				let snap_resolution = ipp.rotate_snap_angle.to_radians();
+				self.rotation = (self.rotation / snap_resolution).round() * snap_resolution;
+			}
+		} else {
+			self.rotation_origin = None;
+
 			let mut start = self.drag_start;
 
 			let stop = self.snap_handler.snap_position(responses, viewport_bounds, document, ipp.mouse.position);
 
 			let mut size = stop - start;
-			if ipp.keyboard.get(lock_ratio as usize) {
+			if let Some(ratio) = self.ratio_lock.filter(|&ratio| ratio > 0.) {
+				// Measured in document space, so the shape keeps its ratio however the canvas is zoomed or rotated
+				let scale = DVec2::new(document.viewport_to_document_vector(DVec2::X).length(), document.viewport_to_document_vector(DVec2::Y).length());
+				let document_size = size.abs() * scale;
+				let width = document_size.x.max(document_size.y * ratio);
+				size = DVec2::new(width, width / ratio) / scale * size.signum();
+			} else if ipp.is_pressed(lock_ratio) {
 				size = size.abs().max(size.abs().yx()) * size.signum();
 			}
-			if ipp.keyboard.get(center as usize) {
+			if ipp.is_pressed(center) {
 				start -= size;
 				size *= 2.;
 			}
 
-			Some(
-				Operation::SetLayerTransformInViewport {
-					path: path.to_vec(),
-					transform: DAffine2::from_scale_angle_translation(size, 0., start).to_cols_array(),
-				}
-				.into(),
-			)
-		} else {
-			None
+			self.bounds = (start, size);
+		}
+
+		self.update_readout(document, ipp.mouse.position, responses);
+
+		Some(
+			Operation::SetLayerTransformInViewport {
+				path: path.to_vec(),
+				transform: self.viewport_transform().to_cols_array(),
+			}
+			.into(),
+		)
+	}
+
+	/// The transform from the unit square to the shape in the viewport, as of the last time it was resized or rotated.
+	pub fn viewport_transform(&self) -> DAffine2 {
+		let (start, size) = self.bounds;
+		DAffine2::from_translation(start + size / 2.) * DAffine2::from_angle(self.rotation) * DAffine2::from_scale_angle_translation(size, 0., -size / 2.)
+	}
+
+	/// Pans the canvas if the pointer is near the edge of the viewport, keeping the start of the drag in place on the canvas.
+	pub fn auto_pan(&mut self, tool_data: &DocumentToolData, ipp: &InputPreprocessorMessageHandler, responses: &mut VecDeque<Message>) {
+		if let Some(delta) = auto_pan(tool_data, ipp, responses) {
+			self.drag_start += delta;
+			self.bounds.0 += delta;
+			self.snap_handler.translate_targets(delta);
 		}
 	}
 
+	/// Shows the width and height of the shape in document units in a label next to the cursor.
+	/// The size is the one the shape is drawn at, so it's doubled when drawing from the center and squared up when the aspect ratio is locked.
+	fn update_readout(&mut self, document: &DocumentMessageHandler, cursor: ViewportPosition, responses: &mut VecDeque<Message>) {
+		let (_, size) = self.bounds;
+		let width = document.viewport_to_document_vector(DVec2::new(size.x, 0.)).length();
+		let height = document.viewport_to_document_vector(DVec2::new(0., size.y)).length();
+
+		let round = |value: f64| (value * 100.).round() / 100.;
+		let new_text = format!("{} × {}", round(width), round(height));
+		let transform = DAffine2::from_translation(cursor + DVec2::splat(READOUT_CURSOR_OFFSET)).to_cols_array();
+
+		match &self.readout_path {
+			Some(path) => {
+				responses.push_back(DocumentMessage::Overlays(Operation::SetLayerTransform { path: path.clone(), transform }.into()).into());
+				responses.push_back(DocumentMessage::Overlays(Operation::SetTextContent { path: path.clone(), new_text }.into()).into());
+			}
+			None => {
+				let path = vec![generate_uuid()];
+				responses.push_back(
+					DocumentMessage::Overlays(
+						Operation::AddText {
+							path: path.clone(),
+							transform,
+							insert_index: -1,
+							text: new_text,
+							style: style::PathStyle::new(None, Some(Fill::new(COLOR_ACCENT))),
+							size: MEASUREMENT_LABEL_FONT_SIZE,
+						}
+						.into(),
+					)
+					.into(),
+				);
+				self.readout_path = Some(path);
+			}
+		}
+	}
+
+	/// Shows a handle at each of the viewport positions, reusing the handles already shown and hiding any left over.
+	pub fn update_handles(&mut self, handle_positions: &[ViewportPosition], responses: &mut VecDeque<Message>) {
+		let unused = self.handles.split_off(handle_positions.len().min(self.handles.len()));
+		for path in unused {
+			self.overlay_pool.release(OverlayRole::ShapeHandle, path, responses);
+		}
+
+		let mut buffer = Vec::new();
+		while self.handles.len() < handle_positions.len() {
+			self.handles.push(self.overlay_pool.acquire(OverlayRole::ShapeHandle, &mut buffer));
+		}
+		for (path, &position) in self.handles.iter().zip(handle_positions) {
+			let size = DVec2::splat(SHAPE_HANDLE_SIZE);
+			let transform = DAffine2::from_scale_angle_translation(size, 0., position - size / 2.).to_cols_array();
+			buffer.push(DocumentMessage::Overlays(Operation::SetLayerTransform { path: path.clone(), transform }.into()).into());
+		}
+		responses.extend(buffer);
+	}
+
+	pub fn hide_handles(&mut self, responses: &mut VecDeque<Message>) {
+		self.update_handles(&[], responses);
+	}
+
+	/// Hides the handles and deletes the overlays kept around for them, for when the tool is put away.
+	pub fn clear_handles(&mut self, responses: &mut VecDeque<Message>) {
+		self.hide_handles(responses);
+		self.overlay_pool.clear(responses);
+	}
+
 	pub fn cleanup(&mut self, responses: &mut VecDeque<Message>) {
 		self.snap_handler.cleanup(responses);
+		if let Some(path) = self.readout_path.take() {
+			responses.push_back(DocumentMessage::Overlays(Operation::DeleteLayer { path }.into()).into());
+		}
 		self.path = None;
+		self.rotation = 0.;
+		self.rotation_origin = None;
 	}
 }
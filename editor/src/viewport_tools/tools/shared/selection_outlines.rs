@@ -0,0 +1,98 @@
+use crate::document::DocumentMessageHandler;
+use crate::message_prelude::*;
+use crate::viewport_tools::tool::DocumentToolData;
+
+use graphene::layers::layer_info::LayerDataType;
+use graphene::layers::style::{self, Stroke};
+use graphene::Operation;
+
+use glam::DAffine2;
+use kurbo::BezPath;
+
+/// Outlines following the geometry of each selected layer, which distinguish the selection from the transformation cage around it.
+#[derive(Clone, Debug, Default)]
+pub struct SelectionOutlines {
+	overlay_paths: Vec<Vec<LayerId>>,
+}
+
+impl SelectionOutlines {
+	/// Draws an outline along each selected visible layer, reusing the overlays from the pool if available.
+	pub fn update(&mut self, document: &DocumentMessageHandler, tool_data: &DocumentToolData, responses: &mut VecDeque<Message>) {
+		let outlines: Vec<(BezPath, DAffine2)> = document.selected_visible_layers().filter_map(|path| layer_outline(document, path)).collect();
+		let style = style::PathStyle::new(Some(Stroke::new(tool_data.selection_outline_color, tool_data.selection_outline_width as f32)), None);
+
+		for (index, (bez_path, transform)) in outlines.iter().enumerate() {
+			let overlay_path = if index < self.overlay_paths.len() {
+				let overlay_path = self.overlay_paths[index].clone();
+
+				responses.push_back(
+					DocumentMessage::Overlays(
+						Operation::SetShapePath {
+							path: overlay_path.clone(),
+							bez_path: bez_path.clone(),
+						}
+						.into(),
+					)
+					.into(),
+				);
+				responses.push_back(DocumentMessage::Overlays(Operation::SetLayerStyle { path: overlay_path.clone(), style }.into()).into());
+				overlay_path
+			} else {
+				let overlay_path = vec![generate_uuid()];
+
+				responses.push_back(
+					DocumentMessage::Overlays(
+						Operation::AddOverlayShape {
+							path: overlay_path.clone(),
+							bez_path: bez_path.clone(),
+							style,
+							closed: false,
+						}
+						.into(),
+					)
+					.into(),
+				);
+				self.overlay_paths.push(overlay_path.clone());
+				overlay_path
+			};
+
+			responses.push_back(
+				DocumentMessage::Overlays(
+					Operation::SetLayerTransform {
+						path: overlay_path,
+						transform: transform.to_cols_array(),
+					}
+					.into(),
+				)
+				.into(),
+			);
+		}
+
+		self.remove_unused_overlays(responses, outlines.len());
+	}
+
+	/// Removes all of the selection outlines. Call this when the tool stops showing the selection.
+	pub fn delete(&mut self, responses: &mut VecDeque<Message>) {
+		self.remove_unused_overlays(responses, 0);
+	}
+
+	fn remove_unused_overlays(&mut self, responses: &mut VecDeque<Message>, remove_after_index: usize) {
+		while self.overlay_paths.len() > remove_after_index {
+			let path = self.overlay_paths.pop().unwrap();
+			responses.push_back(DocumentMessage::Overlays(Operation::DeleteLayer { path }.into()).into());
+		}
+	}
+}
+
+/// The geometry of a layer along with its transform into viewport space, or `None` for layers without an outline of their own such as folders.
+fn layer_outline(document: &DocumentMessageHandler, path: &[LayerId]) -> Option<(BezPath, DAffine2)> {
+	let layer = document.graphene_document.layer(path).ok()?;
+	let bez_path = match &layer.data {
+		LayerDataType::Shape(shape) => shape.path.clone(),
+		LayerDataType::Text(text) => text.to_bez_path_nonmut(),
+		LayerDataType::Folder(_) => return None,
+	};
+	let transform = document.graphene_document.generate_transform_relative_to_viewport(path).ok()?;
+
+	Some((bez_path, transform))
+}
@@ -1,11 +1,14 @@
-use crate::consts::{BOUNDS_ROTATE_THRESHOLD, BOUNDS_SELECT_THRESHOLD, COLOR_ACCENT, SELECTION_DRAG_ANGLE, VECTOR_MANIPULATOR_ANCHOR_MARKER_SIZE};
+use super::overlay_pool::{OverlayPool, OverlayRole};
+use crate::consts::{
+	BOUNDS_CORNER_GRAB_RADIUS, BOUNDS_ROTATE_THRESHOLD, BOUNDS_SELECT_THRESHOLD, PIVOT_GRAB_RADIUS, PIVOT_MARKER_SIZE, SELECTION_DRAG_ANGLE, SNAP_POINT_TOLERANCE,
+	VECTOR_MANIPULATOR_ANCHOR_MARKER_SIZE,
+};
 use crate::document::transformation::OriginalTransforms;
 use crate::frontend::utility_types::MouseCursorIcon;
 use crate::input::InputPreprocessorMessageHandler;
 use crate::message_prelude::*;
+use crate::viewport_tools::snapping::SnapHandler;
 
-use graphene::color::Color;
-use graphene::layers::style::{self, Fill, Stroke};
 use graphene::Operation;
 
 use glam::{DAffine2, DVec2, Vec2Swizzles};
@@ -18,11 +21,19 @@ pub struct SelectedEdges {
 	bottom: bool,
 	left: bool,
 	right: bool,
+	aspect_locked: bool,
 }
 
 impl SelectedEdges {
-	pub fn new(top: bool, bottom: bool, left: bool, right: bool, bounds: [DVec2; 2]) -> Self {
-		Self { top, bottom, left, right, bounds }
+	pub fn new(top: bool, bottom: bool, left: bool, right: bool, bounds: [DVec2; 2], aspect_locked: bool) -> Self {
+		Self {
+			top,
+			bottom,
+			left,
+			right,
+			bounds,
+			aspect_locked,
+		}
 	}
 
 	/// Calculate the pivot for the operation (the opposite point to the edge dragged)
@@ -49,7 +60,8 @@ impl SelectedEdges {
 		DVec2::new(x, y)
 	}
 
-	/// Computes the new bounds with the given mouse move and modifier keys
+	/// Computes the new bounds with the given mouse move and modifier keys.
+	/// If the edges are aspect locked, the constrain key unlocks the aspect ratio instead of constraining to a square.
 	pub fn new_size(&self, mouse: DVec2, transform: DAffine2, center: bool, constrain: bool) -> [DVec2; 2] {
 		let mouse = transform.inverse().transform_point2(mouse);
 
@@ -67,22 +79,63 @@ impl SelectedEdges {
 		}
 
 		let mut size = max - min;
-		if constrain && ((self.top || self.bottom) && (self.left || self.right)) {
-			size = size.abs().max(size.abs().yx()) * size.signum();
-		}
+		let dragging_x = self.left || self.right;
+		let dragging_y = self.top || self.bottom;
 		if center {
-			if self.left || self.right {
+			if dragging_x {
 				size.x *= 2.;
 			}
 
-			if self.bottom || self.top {
+			if dragging_y {
 				size.y *= 2.;
 			}
 		}
+		if self.aspect_locked && !constrain {
+			let original_size = self.bounds[1] - self.bounds[0];
+			let scale = (size / original_size).abs();
+
+			// Corners use the axis that was scaled the most, edges scale the other axis along with the dragged one
+			let uniform_scale = match (dragging_x, dragging_y) {
+				(true, true) => scale.x.max(scale.y),
+				(true, false) => scale.x,
+				_ => scale.y,
+			};
+			let sign = DVec2::new(if dragging_x { size.x.signum() } else { 1. }, if dragging_y { size.y.signum() } else { 1. });
+			if uniform_scale.is_finite() {
+				size = original_size.abs() * uniform_scale * sign;
+			}
+		} else if constrain && dragging_x && dragging_y {
+			size = size.abs().max(size.abs().yx()) * size.signum();
+		}
 
 		[min, size]
 	}
 
+	/// Computes the new position and size of bounds which are set directly rather than scaled with a transform, such as artboards.
+	/// The constrain key keeps the original aspect ratio, and the position follows the size so the pivot stays in place.
+	pub fn new_artboard_bounds(&self, mouse: DVec2, transform: DAffine2, center: bool, constrain: bool) -> [DVec2; 2] {
+		let edges = Self {
+			aspect_locked: constrain,
+			..self.clone()
+		};
+		let [_, size] = edges.new_size(mouse, transform, center, false);
+
+		// Keeping the aspect ratio can change the size along an axis that isn't dragged, or away from the pointer, so the position is found from the pivot instead
+		let pivot = self.calculate_pivot();
+		let position_along_axis = |start_dragged: bool, end_dragged: bool, pivot: f64, size: f64| {
+			if center || !(start_dragged || end_dragged) {
+				pivot - size / 2.
+			} else if start_dragged {
+				pivot - size
+			} else {
+				pivot
+			}
+		};
+		let position = DVec2::new(position_along_axis(self.left, self.right, pivot.x, size.x), position_along_axis(self.top, self.bottom, pivot.y, size.y));
+
+		[position, size]
+	}
+
 	/// Offsets the transformation pivot in order to scale from the center
 	fn offset_pivot(&self, center: bool, size: DVec2) -> DVec2 {
 		let mut offset = DVec2::ZERO;
@@ -102,54 +155,24 @@ impl SelectedEdges {
 		offset
 	}
 
-	/// Moves the position to account for centring (only necessary with absolute transforms - e.g. with artboards)
-	pub fn center_position(&self, mut position: DVec2, size: DVec2, center: bool) -> DVec2 {
-		if center && self.right {
-			position.x -= size.x / 2.;
-		}
-		if center && self.bottom {
-			position.y -= size.y / 2.;
-		}
-
-		position
-	}
-
 	/// Calculates the required scaling to resize the bounding box
 	pub fn bounds_to_scale_transform(&self, center: bool, size: DVec2) -> DAffine2 {
 		DAffine2::from_translation(self.offset_pivot(center, size)) * DAffine2::from_scale(size / (self.bounds[1] - self.bounds[0]))
 	}
 }
 
-/// Create a viewport relative bounding box overlay with no transform handles
-pub fn add_bounding_box(responses: &mut Vec<Message>) -> Vec<LayerId> {
-	let path = vec![generate_uuid()];
-
-	let operation = Operation::AddOverlayRect {
-		path: path.clone(),
-		transform: DAffine2::ZERO.to_cols_array(),
-		style: style::PathStyle::new(Some(Stroke::new(COLOR_ACCENT, 1.0)), None),
-	};
-	responses.push(DocumentMessage::Overlays(operation.into()).into());
-
-	path
+/// Take a viewport relative bounding box overlay with no transform handles from the pool
+pub fn add_bounding_box(pool: &mut OverlayPool, responses: &mut Vec<Message>) -> Vec<LayerId> {
+	pool.acquire(OverlayRole::BoundingBox, responses)
 }
 
-/// Add the transform handle overlay
-fn add_transform_handles(responses: &mut Vec<Message>) -> [Vec<LayerId>; 8] {
+/// Take the transform handle overlays from the pool
+fn add_transform_handles(pool: &mut OverlayPool, responses: &mut Vec<Message>) -> [Vec<LayerId>; 8] {
 	const EMPTY_VEC: Vec<LayerId> = Vec::new();
 	let mut transform_handle_paths = [EMPTY_VEC; 8];
 
 	for item in &mut transform_handle_paths {
-		let current_path = vec![generate_uuid()];
-
-		let operation = Operation::AddOverlayRect {
-			path: current_path.clone(),
-			transform: DAffine2::ZERO.to_cols_array(),
-			style: style::PathStyle::new(Some(Stroke::new(COLOR_ACCENT, 2.0)), Some(Fill::new(Color::WHITE))),
-		};
-		responses.push(DocumentMessage::Overlays(operation.into()).into());
-
-		*item = current_path;
+		*item = pool.acquire(OverlayRole::TransformHandle, responses);
 	}
 
 	transform_handle_paths
@@ -189,18 +212,31 @@ pub struct BoundingBoxOverlays {
 	pub selected_edges: Option<SelectedEdges>,
 	pub original_transforms: OriginalTransforms,
 	pub pivot: DVec2,
+	/// Only shown on cages that can be rotated
+	pub pivot_marker: Option<Vec<LayerId>>,
+	/// Where the user has placed the rotation pivot, as a fraction of the bounds so it stays put on the selection as it's transformed, or `None` for the center
+	pub custom_pivot: Option<DVec2>,
+	/// Marks the handle position or center that the pivot is snapped to while it's being dragged
+	pivot_snap_indicator: Option<Vec<LayerId>>,
 }
 
 impl BoundingBoxOverlays {
 	#[must_use]
-	pub fn new(buffer: &mut Vec<Message>) -> Self {
+	pub fn new(pool: &mut OverlayPool, buffer: &mut Vec<Message>) -> Self {
 		Self {
-			bounding_box: add_bounding_box(buffer),
-			transform_handles: add_transform_handles(buffer),
+			bounding_box: add_bounding_box(pool, buffer),
+			transform_handles: add_transform_handles(pool, buffer),
 			..Default::default()
 		}
 	}
 
+	/// Shows a marker for the point the cage rotates about, which can be dragged to move it
+	pub fn add_pivot_marker(&mut self, pool: &mut OverlayPool, buffer: &mut Vec<Message>) {
+		if self.pivot_marker.is_none() {
+			self.pivot_marker = Some(pool.acquire(OverlayRole::PivotMarker, buffer));
+		}
+	}
+
 	/// Calculats the transformed handle positions based on the bounding box and the transform
 	pub fn evaluate_transform_handle_positions(&self) -> [DVec2; 8] {
 		let (left, top): (f64, f64) = self.bounds[0].into();
@@ -217,7 +253,45 @@ impl BoundingBoxOverlays {
 		]
 	}
 
-	/// Update the position of the bounding box and transform handles
+	/// The point in the viewport that rotating the cage turns about, which is the center of the bounds unless the user has moved the pivot.
+	/// With several layers selected, that's the center of their combined bounds rather than the average of their individual centers.
+	pub fn pivot_position(&self) -> DVec2 {
+		let fraction = self.custom_pivot.unwrap_or_else(|| DVec2::splat(0.5));
+		self.transform.transform_point2(self.bounds[0] + (self.bounds[1] - self.bounds[0]) * fraction)
+	}
+
+	/// Check if the user has grabbed the pivot marker
+	pub fn check_pivot(&self, cursor: DVec2) -> bool {
+		self.pivot_marker.is_some() && self.pivot_position().distance(cursor) < PIVOT_GRAB_RADIUS
+	}
+
+	/// Moves the pivot to the cursor, snapping it to the closest of the handle positions and the center within the snapping tolerance, and marks the one it snapped to.
+	pub fn drag_pivot(&mut self, cursor: DVec2, responses: &mut VecDeque<Message>) {
+		let center = self.transform.transform_point2((self.bounds[0] + self.bounds[1]) / 2.);
+		let snapped = self
+			.evaluate_transform_handle_positions()
+			.into_iter()
+			.chain([center])
+			.map(|target| (target.distance(cursor), target))
+			.filter(|&(distance, _)| distance < SNAP_POINT_TOLERANCE)
+			.min_by(|(a, _), (b, _)| a.total_cmp(b))
+			.map(|(_, target)| target);
+
+		// A cage with no width or height keeps the pivot in its middle along that axis
+		let size = self.bounds[1] - self.bounds[0];
+		let fraction = (self.transform.inverse().transform_point2(snapped.unwrap_or(cursor)) - self.bounds[0]) / size;
+		let fraction_along = |fraction: f64, size: f64| if size == 0. { 0.5 } else { fraction };
+		self.custom_pivot = Some(DVec2::new(fraction_along(fraction.x, size.x), fraction_along(fraction.y, size.y)));
+
+		SnapHandler::update_point_indicator(&mut self.pivot_snap_indicator, responses, snapped);
+	}
+
+	/// Removes the mark from the position the pivot was snapped to, once it's no longer being dragged
+	pub fn stop_dragging_pivot(&mut self, responses: &mut VecDeque<Message>) {
+		SnapHandler::update_point_indicator(&mut self.pivot_snap_indicator, responses, None);
+	}
+
+	/// Update the position of the bounding box, transform handles, and pivot marker
 	pub fn transform(&mut self, buffer: &mut Vec<Message>) {
 		let transform = transform_from_box(self.bounds[0], self.bounds[1], self.transform).to_cols_array();
 		let path = self.bounding_box.clone();
@@ -233,10 +307,43 @@ impl BoundingBoxOverlays {
 			let path = path.clone();
 			buffer.push(DocumentMessage::Overlays(Operation::SetLayerTransformInViewport { path, transform }.into()).into());
 		}
+
+		if let Some(path) = self.pivot_marker.clone() {
+			let scale = DVec2::splat(PIVOT_MARKER_SIZE);
+			let transform = DAffine2::from_scale_angle_translation(scale, 0., self.pivot_position() - scale / 2.).to_cols_array();
+			buffer.push(DocumentMessage::Overlays(Operation::SetLayerTransformInViewport { path, transform }.into()).into());
+		}
+	}
+
+	/// Check if the user has grabbed a corner handle, which can be grabbed from further away than its drawn size (returns which edges in order top, bottom, left, right)
+	fn check_selected_corner(&self, cursor: DVec2) -> Option<(bool, bool, bool, bool)> {
+		let [top_left, _, bottom_left, _, _, top_right, _, bottom_right] = self.evaluate_transform_handle_positions();
+
+		// Keep the grab areas from covering the whole cage when it's small, so the edges and the inside can still be grabbed
+		let shortest_side = top_left.distance(bottom_left).min(top_left.distance(top_right));
+		let grab_radius = BOUNDS_CORNER_GRAB_RADIUS.min(shortest_side / 3.);
+
+		let corners = [
+			(top_left, (true, false, true, false)),
+			(top_right, (true, false, false, true)),
+			(bottom_left, (false, true, true, false)),
+			(bottom_right, (false, true, false, true)),
+		];
+		corners
+			.into_iter()
+			.map(|(position, edges)| (position.distance(cursor), edges))
+			.filter(|&(distance, _)| distance < grab_radius)
+			.min_by(|(a, _), (b, _)| a.total_cmp(b))
+			.map(|(_, edges)| edges)
 	}
 
 	/// Check if the user has selected the edge for dragging (returns which edge in order top, bottom, left, right)
 	pub fn check_selected_edges(&self, cursor: DVec2) -> Option<(bool, bool, bool, bool)> {
+		// Corners take priority over the edges meeting at them
+		if let Some(edges) = self.check_selected_corner(cursor) {
+			return Some(edges);
+		}
+
 		let cursor = self.transform.inverse().transform_point2(cursor);
 		let select_threshold = self.transform.inverse().transform_vector2(DVec2::new(0., BOUNDS_SELECT_THRESHOLD)).length();
 
@@ -278,9 +385,11 @@ impl BoundingBoxOverlays {
 		outside_bounds & inside_extended_bounds
 	}
 
-	/// Gets the required mouse cursor to show resizing bounds or optionally rotation
+	/// Gets the required mouse cursor to show moving the pivot, resizing bounds, or optionally rotation
 	pub fn get_cursor(&self, input: &InputPreprocessorMessageHandler, rotate: bool) -> MouseCursorIcon {
-		if let Some(directions) = self.check_selected_edges(input.mouse.position) {
+		if self.check_pivot(input.mouse.position) {
+			MouseCursorIcon::Crosshair
+		} else if let Some(directions) = self.check_selected_edges(input.mouse.position) {
 			match directions {
 				(true, false, false, false) | (false, true, false, false) => MouseCursorIcon::NSResize,
 				(false, false, true, false) | (false, false, false, true) => MouseCursorIcon::EWResize,
@@ -295,13 +404,69 @@ impl BoundingBoxOverlays {
 		}
 	}
 
-	/// Removes the overlays
-	pub fn delete(self, buffer: &mut impl Extend<Message>) {
-		buffer.extend([DocumentMessage::Overlays(Operation::DeleteLayer { path: self.bounding_box }.into()).into()]);
-		buffer.extend(
-			self.transform_handles
-				.iter()
-				.map(|path| DocumentMessage::Overlays(Operation::DeleteLayer { path: path.clone() }.into()).into()),
-		);
+	/// Hides the overlays and returns them to the pool
+	pub fn delete(self, pool: &mut OverlayPool, buffer: &mut impl Extend<Message>) {
+		pool.release(OverlayRole::BoundingBox, self.bounding_box, buffer);
+		for path in self.transform_handles {
+			pool.release(OverlayRole::TransformHandle, path, buffer);
+		}
+		if let Some(path) = self.pivot_marker {
+			pool.release(OverlayRole::PivotMarker, path, buffer);
+		}
+		if let Some(path) = self.pivot_snap_indicator {
+			buffer.extend([DocumentMessage::Overlays(Operation::DeleteLayer { path }.into()).into()]);
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn bounding_box(bounds: [DVec2; 2]) -> BoundingBoxOverlays {
+		BoundingBoxOverlays {
+			bounds,
+			transform: DAffine2::IDENTITY,
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn corner_grab_takes_priority_over_edges() {
+		let bounding_box = bounding_box([DVec2::ZERO, DVec2::new(200., 100.)]);
+
+		// Outside of the edge threshold, but within the corner's grab radius
+		let near_corner = DVec2::new(200. + BOUNDS_SELECT_THRESHOLD + 1., 100.);
+		assert!(near_corner.distance(DVec2::new(200., 100.)) < BOUNDS_CORNER_GRAB_RADIUS);
+		assert_eq!(bounding_box.check_selected_edges(near_corner), Some((false, true, false, true)));
+
+		// Along the top edge, but close enough to the corner to grab it instead
+		let along_edge = DVec2::new(BOUNDS_CORNER_GRAB_RADIUS - 2., 0.);
+		assert_eq!(bounding_box.check_selected_edges(along_edge), Some((true, false, true, false)));
+
+		// Further along the top edge only grabs the edge
+		let middle_of_edge = DVec2::new(100., 0.);
+		assert_eq!(bounding_box.check_selected_edges(middle_of_edge), Some((true, false, false, false)));
+	}
+
+	#[test]
+	fn pivot_snaps_to_handles_and_center() {
+		let mut bounding_box = bounding_box([DVec2::ZERO, DVec2::new(200., 100.)]);
+		let mut responses = VecDeque::new();
+		assert_eq!(bounding_box.pivot_position(), DVec2::new(100., 50.));
+
+		// Close to the top right corner, so the pivot snaps onto it and marks it
+		bounding_box.drag_pivot(DVec2::new(198., 2.), &mut responses);
+		assert_eq!(bounding_box.pivot_position(), DVec2::new(200., 0.));
+		assert!(bounding_box.pivot_snap_indicator.is_some());
+
+		// Away from every handle, so the pivot follows the cursor
+		bounding_box.drag_pivot(DVec2::new(50., 30.), &mut responses);
+		assert_eq!(bounding_box.custom_pivot, Some(DVec2::new(0.25, 0.3)));
+		assert!(bounding_box.pivot_snap_indicator.is_none());
+
+		// Back near the center
+		bounding_box.drag_pivot(DVec2::new(101., 49.), &mut responses);
+		assert_eq!(bounding_box.pivot_position(), DVec2::new(100., 50.));
 	}
 }
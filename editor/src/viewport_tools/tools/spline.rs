@@ -1,18 +1,22 @@
-use crate::consts::DRAG_THRESHOLD;
+use crate::consts::{DRAG_THRESHOLD, SNAP_POINT_TOLERANCE};
 use crate::document::DocumentMessageHandler;
 use crate::frontend::utility_types::MouseCursorIcon;
 use crate::input::keyboard::{Key, MouseMotion};
 use crate::input::InputPreprocessorMessageHandler;
-use crate::layout::widgets::{LayoutRow, NumberInput, PropertyHolder, Widget, WidgetCallback, WidgetHolder, WidgetLayout};
+use crate::layout::widgets::{
+	LayoutRow, NumberInput, PropertyHolder, RadioEntryData, RadioInput, Separator, SeparatorDirection, SeparatorType, TextButton, Widget, WidgetCallback, WidgetHolder, WidgetLayout,
+};
 use crate::message_prelude::*;
 use crate::misc::{HintData, HintGroup, HintInfo, KeysGroup};
 use crate::viewport_tools::snapping::SnapHandler;
 use crate::viewport_tools::tool::{DocumentToolData, Fsm, ToolActionHandlerData};
 
+use graphene::layers::layer_info::LayerDataType;
 use graphene::layers::style;
 use graphene::Operation;
 
 use glam::{DAffine2, DVec2};
+use kurbo::BezPath;
 use serde::{Deserialize, Serialize};
 
 #[derive(Default)]
@@ -24,27 +28,41 @@ pub struct Spline {
 
 pub struct SplineOptions {
 	line_weight: u32,
+	/// Whether splines loop back around to their first point when they're ended
+	closed: bool,
+	/// How far the handles reach out from the points, from 0 for straight segments to 1 for very round curves
+	tension: f64,
 }
 
 impl Default for SplineOptions {
 	fn default() -> Self {
-		Self { line_weight: 5 }
+		Self {
+			line_weight: 5,
+			closed: false,
+			tension: 0.5,
+		}
 	}
 }
 
 #[remain::sorted]
 #[impl_message(Message, ToolMessage, Spline)]
-#[derive(PartialEq, Clone, Debug, Hash, Serialize, Deserialize)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub enum SplineMessage {
 	// Standard messages
 	#[remain::unsorted]
 	Abort,
 
 	// Tool-specific messages
+	/// Ends the spline by closing it, if the pointer is on its first point
+	Close,
 	Confirm,
+	/// Turns the selected splines into plain paths, so their points and handles can be edited with the Path tool
+	ConvertToPath,
 	DragStart,
 	DragStop,
 	PointerMove,
+	/// Takes back the most recently placed point, ending the spline without keeping it once none are left
+	RemoveLastPoint,
 	Undo,
 	UpdateOptions(SplineOptionsUpdate),
 }
@@ -56,32 +74,108 @@ enum SplineToolFsmState {
 }
 
 #[remain::sorted]
-#[derive(PartialEq, Clone, Debug, Hash, Serialize, Deserialize)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub enum SplineOptionsUpdate {
+	Closed(bool),
 	LineWeight(u32),
+	Tension(f64),
 }
 
 impl PropertyHolder for Spline {
 	fn properties(&self) -> WidgetLayout {
 		WidgetLayout::new(vec![LayoutRow::Row {
 			name: "".into(),
-			widgets: vec![WidgetHolder::new(Widget::NumberInput(NumberInput {
-				unit: " px".into(),
-				label: "Weight".into(),
-				value: self.options.line_weight as f64,
-				is_integer: true,
-				min: Some(0.),
-				on_update: WidgetCallback::new(|number_input| SplineMessage::UpdateOptions(SplineOptionsUpdate::LineWeight(number_input.value as u32)).into()),
-				..NumberInput::default()
-			}))],
+			widgets: vec![
+				WidgetHolder::new(Widget::RadioInput(RadioInput {
+					selected_index: if self.options.closed { 1 } else { 0 },
+					entries: vec![
+						RadioEntryData {
+							value: "open".into(),
+							label: "Open".into(),
+							tooltip: "Leave the ends of the spline apart".into(),
+							on_update: WidgetCallback::new(|_| SplineMessage::UpdateOptions(SplineOptionsUpdate::Closed(false)).into()),
+							..RadioEntryData::default()
+						},
+						RadioEntryData {
+							value: "closed".into(),
+							label: "Closed".into(),
+							tooltip: "Loop the spline back around to its first point".into(),
+							on_update: WidgetCallback::new(|_| SplineMessage::UpdateOptions(SplineOptionsUpdate::Closed(true)).into()),
+							..RadioEntryData::default()
+						},
+					],
+				})),
+				WidgetHolder::new(Widget::Separator(Separator {
+					separator_type: SeparatorType::Unrelated,
+					direction: SeparatorDirection::Horizontal,
+				})),
+				WidgetHolder::new(Widget::NumberInput(NumberInput {
+					label: "Tension".into(),
+					value: self.options.tension,
+					min: Some(0.),
+					max: Some(1.),
+					increment_factor: 0.05,
+					on_update: WidgetCallback::new(|number_input| SplineMessage::UpdateOptions(SplineOptionsUpdate::Tension(number_input.value)).into()),
+					..NumberInput::default()
+				})),
+				WidgetHolder::new(Widget::Separator(Separator {
+					separator_type: SeparatorType::Unrelated,
+					direction: SeparatorDirection::Horizontal,
+				})),
+				WidgetHolder::new(Widget::NumberInput(NumberInput {
+					unit: " px".into(),
+					label: "Weight".into(),
+					value: self.options.line_weight as f64,
+					is_integer: true,
+					min: Some(0.),
+					on_update: WidgetCallback::new(|number_input| SplineMessage::UpdateOptions(SplineOptionsUpdate::LineWeight(number_input.value as u32)).into()),
+					..NumberInput::default()
+				})),
+				WidgetHolder::new(Widget::Separator(Separator {
+					separator_type: SeparatorType::Section,
+					direction: SeparatorDirection::Horizontal,
+				})),
+				WidgetHolder::new(Widget::TextButton(TextButton {
+					label: "Convert to Path".into(),
+					on_update: WidgetCallback::new(|_| SplineMessage::ConvertToPath.into()),
+					..TextButton::default()
+				})),
+			],
 		}])
 	}
 }
 
+/// The selected layers that are still splines, along with their current paths.
+fn selected_splines(document: &DocumentMessageHandler) -> Vec<(Vec<LayerId>, &BezPath)> {
+	document
+		.selected_layers()
+		.filter_map(|path| match document.graphene_document.layer(path).map(|layer| &layer.data) {
+			Ok(LayerDataType::Shape(shape)) if shape.spline_tension().is_some() => Some((path.to_vec(), &shape.path)),
+			_ => None,
+		})
+		.collect()
+}
+
+/// Sets the tension of each selected spline, so editing the tool option shows its effect on existing layers without moving the points they pass through.
+fn update_selected_splines(document: &DocumentMessageHandler, tension: f64, responses: &mut VecDeque<Message>) {
+	let splines = selected_splines(document);
+	if splines.is_empty() {
+		return;
+	}
+
+	responses.push_back(DocumentMessage::StartTransaction.into());
+	for (path, _) in splines {
+		responses.push_back(Operation::SetSplineTension { path, tension }.into());
+	}
+	responses.push_back(DocumentMessage::CommitTransaction.into());
+}
+
+consumes_input_unless_ready!(Spline);
+
 impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Spline {
 	fn process_action(&mut self, action: ToolMessage, data: ToolActionHandlerData<'a>, responses: &mut VecDeque<Message>) {
 		if action == ToolMessage::UpdateHints {
-			self.fsm_state.update_hints(responses);
+			self.fsm_state.update_hints(data.1, data.2, responses);
 			return;
 		}
 
@@ -92,7 +186,13 @@ impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Spline {
 
 		if let ToolMessage::Spline(SplineMessage::UpdateOptions(action)) = action {
 			match action {
+				SplineOptionsUpdate::Closed(closed) => self.options.closed = closed,
 				SplineOptionsUpdate::LineWeight(line_weight) => self.options.line_weight = line_weight,
+				SplineOptionsUpdate::Tension(tension) => {
+					let tension = tension.clamp(0., 1.);
+					self.options.tension = tension;
+					update_selected_splines(data.0, tension, responses);
+				}
 			}
 			return;
 		}
@@ -101,7 +201,7 @@ impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Spline {
 
 		if self.fsm_state != new_state {
 			self.fsm_state = new_state;
-			self.fsm_state.update_hints(responses);
+			self.fsm_state.update_hints(data.1, data.2, responses);
 			self.fsm_state.update_cursor(responses);
 		}
 	}
@@ -111,7 +211,7 @@ impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Spline {
 
 		match self.fsm_state {
 			Ready => actions!(SplineMessageDiscriminant; Undo, DragStart, DragStop, Confirm, Abort),
-			Drawing => actions!(SplineMessageDiscriminant; DragStop, PointerMove, Confirm, Abort),
+			Drawing => actions!(SplineMessageDiscriminant; DragStop, PointerMove, RemoveLastPoint, Close, Confirm, Abort),
 		}
 	}
 }
@@ -126,10 +226,24 @@ struct SplineToolData {
 	points: Vec<DVec2>,
 	next_point: DVec2,
 	weight: u32,
+	/// Whether the spline being drawn loops back around to its first point
+	closed: bool,
+	tension: f64,
 	path: Option<Vec<LayerId>>,
 	snap_handler: SnapHandler,
 }
 
+impl SplineToolData {
+	/// The start of the spline in viewport coords, once there are enough points to close the spline there, which is the only point of the spline being drawn that is snapped onto.
+	/// The other points already placed are left out so the cursor doesn't stick to the points it just left.
+	fn closing_snap_targets(&self, document: &DocumentMessageHandler) -> Vec<DVec2> {
+		match self.points.as_slice() {
+			[start, _, ..] => vec![document.document_to_viewport(*start)],
+			_ => Vec::new(),
+		}
+	}
+}
+
 impl Fsm for SplineToolFsmState {
 	type ToolData = SplineToolData;
 	type ToolOptions = SplineOptions;
@@ -147,8 +261,6 @@ impl Fsm for SplineToolFsmState {
 		use SplineMessage::*;
 		use SplineToolFsmState::*;
 
-		let transform = document.graphene_document.root.transform;
-
 		if let ToolMessage::Spline(event) = event {
 			match (self, event) {
 				(Ready, DragStart) => {
@@ -159,20 +271,23 @@ impl Fsm for SplineToolFsmState {
 					data.snap_handler.start_snap(document, document.bounding_boxes(None, None), true, true);
 					let snapped_position = data.snap_handler.snap_position(responses, input.viewport_bounds.size(), document, input.mouse.position);
 
-					let pos = transform.inverse().transform_point2(snapped_position);
+					let pos = document.viewport_to_document(snapped_position);
 
 					data.points.push(pos);
 					data.next_point = pos;
 
 					data.weight = tool_options.line_weight;
+					data.closed = tool_options.closed;
+					data.tension = tool_options.tension;
 
 					responses.push_back(add_spline(data, tool_data, true));
 
 					Drawing
 				}
 				(Drawing, DragStop) => {
+					data.snap_handler.set_point_targets(data.closing_snap_targets(document));
 					let snapped_position = data.snap_handler.snap_position(responses, input.viewport_bounds.size(), document, input.mouse.position);
-					let pos = transform.inverse().transform_point2(snapped_position);
+					let pos = document.viewport_to_document(snapped_position);
 
 					if let Some(last_pos) = data.points.last() {
 						if last_pos.distance(pos) > DRAG_THRESHOLD {
@@ -187,8 +302,9 @@ impl Fsm for SplineToolFsmState {
 					Drawing
 				}
 				(Drawing, PointerMove) => {
+					data.snap_handler.set_point_targets(data.closing_snap_targets(document));
 					let snapped_position = data.snap_handler.snap_position(responses, input.viewport_bounds.size(), document, input.mouse.position);
-					let pos = transform.inverse().transform_point2(snapped_position);
+					let pos = document.viewport_to_document(snapped_position);
 					data.next_point = pos;
 
 					responses.push_back(remove_preview(data));
@@ -196,19 +312,50 @@ impl Fsm for SplineToolFsmState {
 
 					Drawing
 				}
-				(Drawing, Confirm) | (Drawing, Abort) => {
-					if data.points.len() >= 2 {
-						responses.push_back(DocumentMessage::DeselectAllLayers.into());
+				(Drawing, Close) => {
+					// Double-clicking the first point closes the spline there, after dropping the points the clicks placed on top of it.
+					// Elsewhere, the clicks have already placed their point like any others, and the spline carries on.
+					let under_pointer = |point: &DVec2| document.document_to_viewport(*point).distance(input.mouse.position) <= SNAP_POINT_TOLERANCE;
+					if !data.points.first().map_or(false, under_pointer) {
+						return Drawing;
+					}
+					let end = data.points.iter().rposition(|point| !under_pointer(point)).unwrap_or(0) + 1;
+					data.points.truncate(end);
+					data.closed = true;
+
+					finish_spline(data, tool_data, responses);
+
+					Ready
+				}
+				(Drawing, RemoveLastPoint) => {
+					data.points.pop();
+
+					if data.points.is_empty() {
+						finish_spline(data, tool_data, responses);
+
+						Ready
+					} else {
 						responses.push_back(remove_preview(data));
-						responses.push_back(add_spline(data, tool_data, false));
+						responses.push_back(add_spline(data, tool_data, true));
+
+						Drawing
+					}
+				}
+				(Ready, ConvertToPath) => {
+					// Setting a spline's path to the curves it already has bakes them in, leaving a plain path that no longer regenerates from its points
+					let splines = selected_splines(document);
+					if !splines.is_empty() {
+						responses.push_back(DocumentMessage::StartTransaction.into());
+						for (path, bez_path) in splines {
+							responses.push_back(Operation::SetShapePath { path, bez_path: bez_path.clone() }.into());
+						}
 						responses.push_back(DocumentMessage::CommitTransaction.into());
-					} else {
-						responses.push_back(DocumentMessage::AbortTransaction.into());
 					}
 
-					data.path = None;
-					data.points.clear();
-					data.snap_handler.cleanup(responses);
+					Ready
+				}
+				(Drawing, Confirm) | (Drawing, Abort) => {
+					finish_spline(data, tool_data, responses);
 
 					Ready
 				}
@@ -219,7 +366,7 @@ impl Fsm for SplineToolFsmState {
 		}
 	}
 
-	fn update_hints(&self, responses: &mut VecDeque<Message>) {
+	fn update_hints(&self, _tool_data: &DocumentToolData, _input: &InputPreprocessorMessageHandler, responses: &mut VecDeque<Message>) {
 		let hint_data = match self {
 			SplineToolFsmState::Ready => HintData(vec![HintGroup(vec![HintInfo {
 				key_groups: vec![],
@@ -234,12 +381,24 @@ impl Fsm for SplineToolFsmState {
 					label: String::from("Extend Spline"),
 					plus: false,
 				}]),
+				HintGroup(vec![HintInfo {
+					key_groups: vec![KeysGroup(vec![Key::KeyBackspace])],
+					mouse: None,
+					label: String::from("Remove Last Point"),
+					plus: false,
+				}]),
 				HintGroup(vec![HintInfo {
 					key_groups: vec![KeysGroup(vec![Key::KeyEnter])],
 					mouse: None,
 					label: String::from("End Spline"),
 					plus: false,
 				}]),
+				HintGroup(vec![HintInfo {
+					key_groups: vec![],
+					mouse: Some(MouseMotion::Lmb),
+					label: String::from("Double-Click Start to Close"),
+					plus: false,
+				}]),
 			]),
 		};
 
@@ -251,6 +410,22 @@ impl Fsm for SplineToolFsmState {
 	}
 }
 
+/// Commits the spline if it has enough points to be one, or otherwise discards it.
+fn finish_spline(data: &mut SplineToolData, tool_data: &DocumentToolData, responses: &mut VecDeque<Message>) {
+	if data.points.len() >= 2 {
+		responses.push_back(DocumentMessage::DeselectAllLayers.into());
+		responses.push_back(remove_preview(data));
+		responses.push_back(add_spline(data, tool_data, false));
+		responses.push_back(DocumentMessage::CommitTransaction.into());
+	} else {
+		responses.push_back(DocumentMessage::AbortTransaction.into());
+	}
+
+	data.path = None;
+	data.points.clear();
+	data.snap_handler.cleanup(responses);
+}
+
 fn remove_preview(data: &SplineToolData) -> Message {
 	Operation::DeleteLayer { path: data.path.clone().unwrap() }.into()
 }
@@ -266,6 +441,8 @@ fn add_spline(data: &SplineToolData, tool_data: &DocumentToolData, show_preview:
 		insert_index: -1,
 		transform: DAffine2::IDENTITY.to_cols_array(),
 		points,
+		closed: data.closed,
+		tension: data.tension,
 		style: style::PathStyle::new(Some(style::Stroke::new(tool_data.primary_color, data.weight as f32)), None),
 	}
 	.into()
@@ -1,9 +1,12 @@
-use crate::consts::{COLOR_ACCENT, SELECTION_TOLERANCE};
+use crate::consts::COLOR_ACCENT;
 use crate::document::DocumentMessageHandler;
 use crate::frontend::utility_types::MouseCursorIcon;
 use crate::input::keyboard::{Key, MouseMotion};
 use crate::input::InputPreprocessorMessageHandler;
-use crate::layout::widgets::{LayoutRow, NumberInput, PropertyHolder, Widget, WidgetCallback, WidgetHolder, WidgetLayout};
+use crate::layout::layout_message::LayoutTarget;
+use crate::layout::widgets::{
+	DropdownEntryData, DropdownInput, LayoutRow, NumberInput, OptionalInput, PropertyHolder, Separator, SeparatorDirection, SeparatorType, Widget, WidgetCallback, WidgetHolder, WidgetLayout,
+};
 use crate::message_prelude::*;
 use crate::misc::{HintData, HintGroup, HintInfo, KeysGroup};
 use crate::viewport_tools::tool::{DocumentToolData, Fsm, ToolActionHandlerData};
@@ -11,6 +14,7 @@ use crate::viewport_tools::tool::{DocumentToolData, Fsm, ToolActionHandlerData};
 use glam::{DAffine2, DVec2};
 use graphene::intersection::Quad;
 use graphene::layers::style::{self, Fill, Stroke};
+use graphene::layers::text::{TextAlignment, DEFAULT_FONT_FAMILY};
 use graphene::Operation;
 use kurbo::Shape;
 use serde::{Deserialize, Serialize};
@@ -24,17 +28,63 @@ pub struct Text {
 
 pub struct TextOptions {
 	font_size: u32,
+	font_family: String,
+	/// The width that text wraps at, when wrapping is turned on
+	wrap_width: f64,
+	line_width: Option<f64>,
+	alignment: TextAlignment,
+	bold: bool,
+	italic: bool,
 }
 
 impl Default for TextOptions {
 	fn default() -> Self {
-		Self { font_size: 14 }
+		Self {
+			font_size: 14,
+			font_family: DEFAULT_FONT_FAMILY.into(),
+			wrap_width: 200.,
+			line_width: None,
+			alignment: TextAlignment::default(),
+			bold: false,
+			italic: false,
+		}
+	}
+}
+
+impl TextOptions {
+	/// Picks up the font, size, style, alignment, and wrapping of a text layer, so editing it carries on in them
+	fn load(&mut self, text: &graphene::layers::text::Text) {
+		self.font_size = text.size.round() as u32;
+		self.font_family = text.font_family.clone();
+		self.alignment = text.alignment;
+		self.load_style(text);
+		self.line_width = text.line_width;
+		if let Some(line_width) = text.line_width {
+			self.wrap_width = line_width;
+		}
+	}
+
+	/// Picks up whether a text layer is bold and italic, so the toggles show how it looks
+	fn load_style(&mut self, text: &graphene::layers::text::Text) {
+		self.bold = text.bold;
+		self.italic = text.italic;
 	}
 }
 
+/// The font families offered for text, as their labels and the names they're referenced by
+const FONT_FAMILIES: [(&str, &str); 3] = [("Sans-Serif", DEFAULT_FONT_FAMILY), ("Serif", "serif"), ("Monospace", "monospace")];
+
+/// The ways text can be aligned, as their labels and values
+const ALIGNMENTS: [(&str, TextAlignment); 4] = [
+	("Left", TextAlignment::Left),
+	("Center", TextAlignment::Center),
+	("Right", TextAlignment::Right),
+	("Justify", TextAlignment::Justify),
+];
+
 #[remain::sorted]
 #[impl_message(Message, ToolMessage, Text)]
-#[derive(PartialEq, Clone, Debug, Hash, Serialize, Deserialize)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub enum TextMessage {
 	// Standard messages
 	#[remain::unsorted]
@@ -42,10 +92,25 @@ pub enum TextMessage {
 
 	#[remain::unsorted]
 	DocumentIsDirty,
+	#[remain::unsorted]
+	SelectionChanged,
 
 	// Tool-specific messages
 	CommitText,
 	Interact,
+	/// Picks the alignment of new text, and changes it on the selected text layers
+	SetAlignment {
+		alignment: TextAlignment,
+	},
+	/// Picks the font family of new text, and changes it on the selected text layers
+	SetFont {
+		font_family: String,
+	},
+	/// Turns bold and italic on or off for new text and the selected text layers, leaving either as it is when it's `None`
+	SetStyle {
+		bold: Option<bool>,
+		italic: Option<bool>,
+	},
 	TextChange {
 		new_text: String,
 	},
@@ -56,32 +121,121 @@ pub enum TextMessage {
 }
 
 #[remain::sorted]
-#[derive(PartialEq, Clone, Debug, Hash, Serialize, Deserialize)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub enum TextOptionsUpdate {
 	FontSize(u32),
+	Wrap(bool),
+	WrapWidth(f64),
 }
 
 impl PropertyHolder for Text {
 	fn properties(&self) -> WidgetLayout {
+		let entries = FONT_FAMILIES
+			.iter()
+			.map(|&(label, font_family)| DropdownEntryData {
+				value: font_family.into(),
+				label: label.into(),
+				on_update: WidgetCallback::new(|entry: &DropdownEntryData| TextMessage::SetFont { font_family: entry.value.clone() }.into()),
+			})
+			.collect();
+
+		let alignment_entries = ALIGNMENTS
+			.iter()
+			.map(|&(label, alignment)| DropdownEntryData {
+				value: alignment.css_value().into(),
+				label: label.into(),
+				on_update: WidgetCallback::new(|entry: &DropdownEntryData| match ALIGNMENTS.iter().find(|(_, alignment)| alignment.css_value() == entry.value) {
+					Some(&(_, alignment)) => TextMessage::SetAlignment { alignment }.into(),
+					None => Message::NoOp,
+				}),
+			})
+			.collect();
+
 		WidgetLayout::new(vec![LayoutRow::Row {
 			name: "".into(),
-			widgets: vec![WidgetHolder::new(Widget::NumberInput(NumberInput {
-				unit: " px".into(),
-				label: "Font Size".into(),
-				value: self.options.font_size as f64,
-				is_integer: true,
-				min: Some(1.),
-				on_update: WidgetCallback::new(|number_input| TextMessage::UpdateOptions(TextOptionsUpdate::FontSize(number_input.value as u32)).into()),
-				..NumberInput::default()
-			}))],
+			widgets: vec![
+				WidgetHolder::new(Widget::DropdownInput(DropdownInput {
+					entries: vec![entries],
+					selected_index: FONT_FAMILIES.iter().position(|&(_, font_family)| font_family == self.options.font_family).unwrap_or_default() as u32,
+				})),
+				WidgetHolder::new(Widget::Separator(Separator {
+					separator_type: SeparatorType::Related,
+					direction: SeparatorDirection::Horizontal,
+				})),
+				WidgetHolder::new(Widget::NumberInput(NumberInput {
+					unit: " px".into(),
+					label: "Font Size".into(),
+					value: self.options.font_size as f64,
+					is_integer: true,
+					min: Some(1.),
+					on_update: WidgetCallback::new(|number_input| TextMessage::UpdateOptions(TextOptionsUpdate::FontSize(number_input.value as u32)).into()),
+					..NumberInput::default()
+				})),
+				WidgetHolder::new(Widget::Separator(Separator {
+					separator_type: SeparatorType::Related,
+					direction: SeparatorDirection::Horizontal,
+				})),
+				WidgetHolder::new(Widget::OptionalInput(OptionalInput {
+					checked: self.options.bold,
+					icon: "TextBold".into(),
+					tooltip: "Bold".into(),
+					on_update: WidgetCallback::new(|optional_input| {
+						TextMessage::SetStyle {
+							bold: Some(optional_input.checked),
+							italic: None,
+						}
+						.into()
+					}),
+				})),
+				WidgetHolder::new(Widget::OptionalInput(OptionalInput {
+					checked: self.options.italic,
+					icon: "TextItalic".into(),
+					tooltip: "Italic".into(),
+					on_update: WidgetCallback::new(|optional_input| {
+						TextMessage::SetStyle {
+							bold: None,
+							italic: Some(optional_input.checked),
+						}
+						.into()
+					}),
+				})),
+				WidgetHolder::new(Widget::Separator(Separator {
+					separator_type: SeparatorType::Unrelated,
+					direction: SeparatorDirection::Horizontal,
+				})),
+				WidgetHolder::new(Widget::OptionalInput(OptionalInput {
+					checked: self.options.line_width.is_some(),
+					icon: "KeyboardEnter".into(),
+					tooltip: "Wrap Text".into(),
+					on_update: WidgetCallback::new(|optional_input| TextMessage::UpdateOptions(TextOptionsUpdate::Wrap(optional_input.checked)).into()),
+				})),
+				WidgetHolder::new(Widget::NumberInput(NumberInput {
+					unit: " px".into(),
+					label: "Width".into(),
+					value: self.options.wrap_width,
+					min: Some(1.),
+					on_update: WidgetCallback::new(|number_input| TextMessage::UpdateOptions(TextOptionsUpdate::WrapWidth(number_input.value)).into()),
+					..NumberInput::default()
+				})),
+				WidgetHolder::new(Widget::Separator(Separator {
+					separator_type: SeparatorType::Unrelated,
+					direction: SeparatorDirection::Horizontal,
+				})),
+				WidgetHolder::new(Widget::DropdownInput(DropdownInput {
+					entries: vec![alignment_entries],
+					selected_index: ALIGNMENTS.iter().position(|&(_, alignment)| alignment == self.options.alignment).unwrap_or_default() as u32,
+				})),
+			],
 		}])
 	}
 }
 
+consumes_input_unless_ready!(Text);
+
 impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Text {
 	fn process_action(&mut self, action: ToolMessage, data: ToolActionHandlerData<'a>, responses: &mut VecDeque<Message>) {
 		if action == ToolMessage::UpdateHints {
-			self.fsm_state.update_hints(responses);
+			self.fsm_state.update_hints(data.1, data.2, responses);
 			return;
 		}
 
@@ -90,18 +244,77 @@ impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for Text {
 			return;
 		}
 
+		// While text is being edited, changes to its options join the edit's transaction rather than making their own, so aborting the edit takes them back too
+		let editing = self.fsm_state == TextToolFsmState::Editing;
+
 		if let ToolMessage::Text(TextMessage::UpdateOptions(action)) = action {
 			match action {
-				TextOptionsUpdate::FontSize(font_size) => self.options.font_size = font_size,
+				TextOptionsUpdate::FontSize(font_size) => {
+					self.options.font_size = font_size;
+					update_selected_font_sizes(data.0, font_size as f64, editing, responses);
+				}
+				TextOptionsUpdate::Wrap(wrap) => {
+					self.options.line_width = wrap.then(|| self.options.wrap_width);
+					update_selected_line_widths(data.0, self.options.line_width, editing, responses);
+				}
+				TextOptionsUpdate::WrapWidth(wrap_width) => {
+					self.options.wrap_width = wrap_width;
+					if self.options.line_width.is_some() {
+						self.options.line_width = Some(wrap_width);
+						update_selected_line_widths(data.0, self.options.line_width, editing, responses);
+					}
+				}
 			}
 			return;
 		}
 
+		if let ToolMessage::Text(TextMessage::SetFont { font_family }) = action {
+			update_selected_fonts(data.0, &font_family, editing, responses);
+			self.options.font_family = font_family;
+			self.register_properties(responses, LayoutTarget::ToolOptions);
+			return;
+		}
+
+		if let ToolMessage::Text(TextMessage::SetAlignment { alignment }) = action {
+			self.options.alignment = alignment;
+			update_selected_alignments(data.0, alignment, editing, responses);
+			self.register_properties(responses, LayoutTarget::ToolOptions);
+			return;
+		}
+
+		if let ToolMessage::Text(TextMessage::SetStyle { bold, italic }) = action {
+			self.options.bold = bold.unwrap_or(self.options.bold);
+			self.options.italic = italic.unwrap_or(self.options.italic);
+			update_selected_styles(data.0, bold, italic, editing, responses);
+			self.register_properties(responses, LayoutTarget::ToolOptions);
+			return;
+		}
+
+		// The bold and italic toggles show the state of the selected text
+		if action == ToolMessage::Text(TextMessage::SelectionChanged) {
+			let document = data.0;
+			let selected_text = document.selected_layers().find_map(|path| document.graphene_document.layer(path).ok()?.as_text().ok());
+			if let Some(text) = selected_text {
+				self.options.load_style(text);
+				self.register_properties(responses, LayoutTarget::ToolOptions);
+			}
+			return;
+		}
+
+		let edited_path = self.data.path.clone();
 		let new_state = self.fsm_state.transition(action, data.0, data.1, &mut self.data, &self.options, data.2, responses);
 
+		// Starting to edit an existing text layer picks up its options, while a new one has yet to be added to the document and takes on the tool's
+		if new_state == TextToolFsmState::Editing && (self.fsm_state != TextToolFsmState::Editing || self.data.path != edited_path) {
+			if let Ok(Ok(text)) = data.0.graphene_document.layer(&self.data.path).map(|layer| layer.as_text()) {
+				self.options.load(text);
+				self.register_properties(responses, LayoutTarget::ToolOptions);
+			}
+		}
+
 		if self.fsm_state != new_state {
 			self.fsm_state = new_state;
-			self.fsm_state.update_hints(responses);
+			self.fsm_state.update_hints(data.1, data.2, responses);
 			self.fsm_state.update_cursor(responses);
 		}
 	}
@@ -134,6 +347,90 @@ struct TextToolData {
 	overlays: Vec<Vec<LayerId>>,
 }
 
+/// Changes the font family of each selected text layer that isn't already in it, as a single undo step.
+fn update_selected_fonts(document: &DocumentMessageHandler, font_family: &str, editing: bool, responses: &mut VecDeque<Message>) {
+	let operation = |path| Operation::SetTextFont {
+		path,
+		font_family: font_family.to_string(),
+	};
+	update_selected_text(document, editing, |text| text.font_family != font_family, operation, responses);
+}
+
+/// Changes the font size of each selected text layer, including one being edited, as a single undo step.
+fn update_selected_font_sizes(document: &DocumentMessageHandler, font_size: f64, editing: bool, responses: &mut VecDeque<Message>) {
+	update_selected_text(document, editing, |text| text.size != font_size, |path| Operation::SetTextFontSize { path, font_size }, responses);
+}
+
+/// Sets the width that each selected text layer wraps at, or stops it from wrapping, as a single undo step.
+fn update_selected_line_widths(document: &DocumentMessageHandler, line_width: Option<f64>, editing: bool, responses: &mut VecDeque<Message>) {
+	update_selected_text(
+		document,
+		editing,
+		|text| text.line_width != line_width,
+		|path| Operation::SetTextLineWidth { path, line_width },
+		responses,
+	);
+}
+
+/// Changes how the lines of each selected text layer are aligned, as a single undo step.
+fn update_selected_alignments(document: &DocumentMessageHandler, alignment: TextAlignment, editing: bool, responses: &mut VecDeque<Message>) {
+	update_selected_text(document, editing, |text| text.alignment != alignment, |path| Operation::SetTextAlignment { path, alignment }, responses);
+}
+
+/// Turns bold and italic on or off for each selected text layer that differs, as a single undo step.
+fn update_selected_styles(document: &DocumentMessageHandler, bold: Option<bool>, italic: Option<bool>, editing: bool, responses: &mut VecDeque<Message>) {
+	let differs = |current: bool, new: Option<bool>| new.map_or(false, |new| new != current);
+	update_selected_text(
+		document,
+		editing,
+		|text| differs(text.bold, bold) || differs(text.italic, italic),
+		|path| Operation::SetTextStyle { path, bold, italic },
+		responses,
+	);
+}
+
+/// Pushes the operation for each selected text layer that needs it, as an undo step of its own unless it's part of an edit already in progress.
+fn update_selected_text(
+	document: &DocumentMessageHandler,
+	editing: bool,
+	needs_update: impl Fn(&graphene::layers::text::Text) -> bool,
+	operation: impl Fn(Vec<LayerId>) -> Operation,
+	responses: &mut VecDeque<Message>,
+) {
+	let paths: Vec<_> = document
+		.selected_layers()
+		.filter(|path| match document.graphene_document.layer(path).map(|layer| layer.as_text()) {
+			Ok(Ok(text)) => needs_update(text),
+			_ => false,
+		})
+		.collect();
+	if paths.is_empty() {
+		return;
+	}
+
+	if !editing {
+		responses.push_back(DocumentMessage::StartTransaction.into());
+	}
+	for path in paths {
+		responses.push_back(operation(path.to_vec()).into());
+	}
+	if !editing {
+		responses.push_back(DocumentMessage::CommitTransaction.into());
+	}
+}
+
+/// Stops editing the text without keeping what was typed, and takes back the edit's changes, removing the text if it was new.
+fn abandon_edit(data: &TextToolData, responses: &mut VecDeque<Message>) {
+	responses.push_back(
+		DocumentMessage::SetTexboxEditability {
+			path: data.path.clone(),
+			editable: false,
+		}
+		.into(),
+	);
+	responses.push_back(DocumentMessage::AbortTransaction.into());
+}
+
 fn transform_from_box(pos1: DVec2, pos2: DVec2) -> [f64; 6] {
 	DAffine2::from_scale_angle_translation((pos2 - pos1).round(), 0., pos1.round() - DVec2::splat(0.5)).to_cols_array()
 }
@@ -203,7 +500,7 @@ impl Fsm for TextToolFsmState {
 				}
 				(state, Interact) => {
 					let mouse_pos = input.mouse.position;
-					let tolerance = DVec2::splat(SELECTION_TOLERANCE);
+					let tolerance = DVec2::splat(tool_data.selection_tolerance);
 					let quad = Quad::from_box([mouse_pos - tolerance, mouse_pos + tolerance]);
 
 					let new_state = if let Some(l) = document
@@ -213,18 +510,17 @@ impl Fsm for TextToolFsmState {
 						.filter(|l| document.graphene_document.layer(l).map(|l| l.as_text().is_ok()).unwrap_or(false))
 					// Editing existing text
 					{
+						// Clicking the text already being edited carries on editing it
+						if state == TextToolFsmState::Editing && *l == data.path {
+							return Editing;
+						}
 						if state == TextToolFsmState::Editing {
-							responses.push_back(
-								DocumentMessage::SetTexboxEditability {
-									path: data.path.clone(),
-									editable: false,
-								}
-								.into(),
-							);
+							abandon_edit(data, responses);
 						}
 
 						data.path = l.clone();
 
+						responses.push_back(DocumentMessage::StartTransaction.into());
 						responses.push_back(
 							DocumentMessage::SetTexboxEditability {
 								path: data.path.clone(),
@@ -247,6 +543,7 @@ impl Fsm for TextToolFsmState {
 						let font_size = tool_options.font_size;
 						data.path = document.get_path_for_new_layer();
 
+						responses.push_back(DocumentMessage::StartTransaction.into());
 						responses.push_back(
 							Operation::AddText {
 								path: data.path.clone(),
@@ -259,6 +556,35 @@ impl Fsm for TextToolFsmState {
 							.into(),
 						);
 						responses.push_back(Operation::SetLayerTransformInViewport { path: data.path.clone(), transform }.into());
+						responses.push_back(
+							Operation::SetTextFont {
+								path: data.path.clone(),
+								font_family: tool_options.font_family.clone(),
+							}
+							.into(),
+						);
+						responses.push_back(
+							Operation::SetTextLineWidth {
+								path: data.path.clone(),
+								line_width: tool_options.line_width,
+							}
+							.into(),
+						);
+						responses.push_back(
+							Operation::SetTextAlignment {
+								path: data.path.clone(),
+								alignment: tool_options.alignment,
+							}
+							.into(),
+						);
+						responses.push_back(
+							Operation::SetTextStyle {
+								path: data.path.clone(),
+								bold: Some(tool_options.bold),
+								italic: Some(tool_options.italic),
+							}
+							.into(),
+						);
 
 						responses.push_back(
 							DocumentMessage::SetTexboxEditability {
@@ -277,14 +603,8 @@ impl Fsm for TextToolFsmState {
 
 						Editing
 					} else {
-						// Removing old text as editable
-						responses.push_back(
-							DocumentMessage::SetTexboxEditability {
-								path: data.path.clone(),
-								editable: false,
-							}
-							.into(),
-						);
+						// Clicking away from the text abandons the edit
+						abandon_edit(data, responses);
 
 						resize_overlays(&mut data.overlays, responses, 0);
 
@@ -295,13 +615,7 @@ impl Fsm for TextToolFsmState {
 				}
 				(state, Abort) => {
 					if state == TextToolFsmState::Editing {
-						responses.push_back(
-							DocumentMessage::SetTexboxEditability {
-								path: data.path.clone(),
-								editable: false,
-							}
-							.into(),
-						);
+						abandon_edit(data, responses);
 					}
 
 					resize_overlays(&mut data.overlays, responses, 0);
@@ -323,6 +637,7 @@ impl Fsm for TextToolFsmState {
 						}
 						.into(),
 					);
+					responses.push_back(DocumentMessage::CommitTransaction.into());
 
 					resize_overlays(&mut data.overlays, responses, 0);
 
@@ -355,7 +670,7 @@ impl Fsm for TextToolFsmState {
 		}
 	}
 
-	fn update_hints(&self, responses: &mut VecDeque<Message>) {
+	fn update_hints(&self, _tool_data: &DocumentToolData, _input: &InputPreprocessorMessageHandler, responses: &mut VecDeque<Message>) {
 		let hint_data = match self {
 			TextToolFsmState::Ready => HintData(vec![HintGroup(vec![
 				HintInfo {
@@ -371,20 +686,20 @@ impl Fsm for TextToolFsmState {
 					plus: false,
 				},
 			])]),
-			TextToolFsmState::Editing => HintData(vec![HintGroup(vec![
-				HintInfo {
-					key_groups: vec![KeysGroup(vec![Key::KeyControl, Key::KeyEnter])],
+			TextToolFsmState::Editing => HintData(vec![
+				HintGroup(vec![HintInfo {
+					key_groups: vec![KeysGroup(vec![Key::KeyEnter])],
 					mouse: None,
-					label: String::from("Commit Edit"),
+					label: String::from("New Line"),
 					plus: false,
-				},
-				HintInfo {
-					key_groups: vec![KeysGroup(vec![Key::KeyEscape])],
+				}]),
+				HintGroup(vec![HintInfo {
+					key_groups: vec![KeysGroup(vec![Key::KeyEscape]), KeysGroup(vec![Key::KeyPrimaryModifier, Key::KeyEnter])],
 					mouse: None,
-					label: String::from("Discard Edit"),
+					label: String::from("Commit Edit"),
 					plus: false,
-				},
-			])]),
+				}]),
+			]),
 		};
 
 		responses.push_back(FrontendMessage::UpdateInputHints { hint_data }.into());
@@ -37,7 +37,8 @@ pub struct ShapeEditor {
 impl ShapeEditor {
 	/// Select the first point within the selection threshold
 	/// Returns true if we've found a point, false otherwise
-	pub fn select_point(&mut self, mouse_position: DVec2, select_threshold: f64, add_to_selection: bool, responses: &mut VecDeque<Message>) -> bool {
+	/// The handles of a smooth point start out mirrored only if `mirror_handles` is set
+	pub fn select_point(&mut self, mouse_position: DVec2, select_threshold: f64, add_to_selection: bool, mirror_handles: bool, responses: &mut VecDeque<Message>) -> bool {
 		if self.shapes_to_modify.is_empty() {
 			return false;
 		}
@@ -73,7 +74,8 @@ impl ShapeEditor {
 			}
 
 			// Due to the shape data structure not persisting across shape selection changes we need to rely on the kurbo path to know if we should mirror
-			selected_anchor.set_mirroring((selected_anchor.angle_between_handles().abs() - std::f64::consts::PI).abs() < MINIMUM_MIRROR_THRESHOLD);
+			let is_smooth = (selected_anchor.angle_between_handles().abs() - std::f64::consts::PI).abs() < MINIMUM_MIRROR_THRESHOLD;
+			selected_anchor.set_mirroring(mirror_handles && is_smooth);
 			return true;
 		}
 		false
@@ -186,6 +188,13 @@ impl ShapeEditor {
 		}
 	}
 
+	/// Show the handles of every anchor, or with `show_all_handles` unset, only those of the anchors with a selected point
+	pub fn update_handle_visibility(&self, show_all_handles: bool, responses: &mut VecDeque<Message>) {
+		for anchor in self.shapes_to_modify.iter().flat_map(|shape| shape.anchors.iter()) {
+			anchor.set_handle_visiblity(show_all_handles || anchor.is_selected(), responses);
+		}
+	}
+
 	/// Remove all of the overlays for the VectorManipulators / shape
 	pub fn remove_overlays(&mut self, responses: &mut VecDeque<Message>) {
 		for shape in self.shapes_to_modify.iter_mut() {
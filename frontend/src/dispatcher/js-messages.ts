@@ -44,6 +44,17 @@ export class UpdateOpenDocumentsList extends JsMessage {
 	readonly open_documents!: FrontendDocumentDetails[];
 }
 
+export class FrontendRecentDocument {
+	readonly name!: string;
+
+	readonly id!: BigInt;
+}
+
+export class UpdateRecentDocumentsList extends JsMessage {
+	@Type(() => FrontendRecentDocument)
+	readonly recent_documents!: FrontendRecentDocument[];
+}
+
 export class UpdateInputHints extends JsMessage {
 	@Type(() => HintInfo)
 	readonly hint_data!: HintData;
@@ -199,10 +210,18 @@ export class UpdateDocumentRulers extends JsMessage {
 	readonly interval!: number;
 }
 
-export type MouseCursorIcon = "default" | "zoom-in" | "zoom-out" | "grabbing" | "crosshair" | "text" | "ns-resize" | "ew-resize" | "nesw-resize" | "nwse-resize";
+export class UpdateSelectionBounds extends JsMessage {
+	@Transform(({ value }) => value && value.map(([x, y]: [number, number]) => ({ x, y })))
+	readonly bounds!: undefined | [{ x: number; y: number }, { x: number; y: number }];
+
+	readonly rotation!: number;
+}
+
+export type MouseCursorIcon = "default" | "pointer" | "zoom-in" | "zoom-out" | "grabbing" | "crosshair" | "text" | "ns-resize" | "ew-resize" | "nesw-resize" | "nwse-resize";
 
 const ToCssCursorProperty = Transform(({ value }) => {
 	const cssNames: Record<string, MouseCursorIcon> = {
+		Pointer: "pointer",
 		ZoomIn: "zoom-in",
 		ZoomOut: "zoom-out",
 		Grabbing: "grabbing",
@@ -222,6 +241,20 @@ export class UpdateMouseCursor extends JsMessage {
 	readonly cursor!: MouseCursorIcon;
 }
 
+export class UpdateFrameTicksRequested extends JsMessage {
+	readonly requested!: boolean;
+}
+
+export class UpdateUndoRedoState extends JsMessage {
+	readonly can_undo!: boolean;
+
+	readonly can_redo!: boolean;
+
+	readonly undo_label!: undefined | string;
+
+	readonly redo_label!: undefined | string;
+}
+
 export class TriggerFileDownload extends JsMessage {
 	readonly document!: string;
 
@@ -301,8 +334,6 @@ export class DisplayEditableTextbox extends JsMessage {
 	readonly text!: string;
 
 	readonly line_width!: undefined | number;
-
-	readonly font_size!: number;
 }
 
 export class DisplayRemoveEditableTextbox extends JsMessage {}
@@ -412,7 +443,7 @@ export function isWidgetSection(layoutRow: WidgetRow | WidgetSection): layoutRow
 	return Boolean((layoutRow as WidgetSection).layout);
 }
 
-export type WidgetKind = "NumberInput" | "Separator" | "IconButton" | "PopoverButton" | "OptionalInput" | "RadioInput" | "TextInput";
+export type WidgetKind = "DropdownInput" | "NumberInput" | "Separator" | "IconButton" | "PopoverButton" | "OptionalInput" | "RadioInput" | "TextButton" | "TextInput";
 
 export interface Widget {
 	kind: WidgetKind;
@@ -435,6 +466,13 @@ export class UpdateDocumentBarLayout extends JsMessage {
 	layout!: LayoutRow[];
 }
 
+export class UpdateSwatchLibraryLayout extends JsMessage {
+	layout_target!: unknown;
+
+	@Transform(({ value }) => createWidgetLayout(value))
+	layout!: LayoutRow[];
+}
+
 // Unpacking rust types to more usable type in the frontend
 // eslint-disable-next-line @typescript-eslint/no-explicit-any
 function createWidgetLayout(widgetLayout: any[]): LayoutRow[] {
@@ -471,6 +509,14 @@ export class DisplayDialogComingSoon extends JsMessage {
 
 export class TriggerTextCommit extends JsMessage {}
 
+export class TriggerTextCopy extends JsMessage {
+	readonly copy_text!: string;
+}
+
+export class TriggerSaveSwatchLibrary extends JsMessage {
+	readonly swatch_library!: string;
+}
+
 // Any is used since the type of the object should be known from the rust side
 // eslint-disable-next-line @typescript-eslint/no-explicit-any
 type JSMessageFactory = (data: any, wasm: WasmInstance, instance: RustEditorInstance) => JsMessage;
@@ -490,11 +536,15 @@ export const messageConstructors: Record<string, MessageMaker> = {
 	UpdateActiveTool,
 	UpdateActiveDocument,
 	UpdateOpenDocumentsList,
+	UpdateRecentDocumentsList,
+	UpdateSelectionBounds,
 	UpdateInputHints,
 	UpdateWorkingColors,
 	UpdateCanvasZoom,
 	UpdateCanvasRotation,
 	UpdateMouseCursor,
+	UpdateFrameTicksRequested,
+	UpdateUndoRedoState,
 	DisplayDialogError,
 	DisplayDialogPanic,
 	DisplayConfirmationToCloseDocument,
@@ -503,9 +553,12 @@ export const messageConstructors: Record<string, MessageMaker> = {
 	TriggerIndexedDbWriteDocument,
 	TriggerIndexedDbRemoveDocument,
 	TriggerTextCommit,
+	TriggerTextCopy,
 	UpdateDocumentArtboards,
 	UpdateToolOptionsLayout,
 	DisplayDialogComingSoon,
 	UpdateDocumentBarLayout,
+	UpdateSwatchLibraryLayout,
+	TriggerSaveSwatchLibrary,
 } as const;
 export type JsMessageType = keyof typeof messageConstructors;
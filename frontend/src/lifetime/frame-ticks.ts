@@ -0,0 +1,28 @@
+import { UpdateFrameTicksRequested } from "@/dispatcher/js-messages";
+import { EditorState } from "@/state/wasm-loader";
+
+// eslint-disable-next-line @typescript-eslint/explicit-function-return-type
+export function createFrameTickManager(editor: EditorState) {
+	let animationFrameRequest: number | undefined;
+	let previousTimestamp: number | undefined;
+
+	const onAnimationFrame = (timestamp: number): void => {
+		const deltaTime = previousTimestamp === undefined ? 0 : timestamp - previousTimestamp;
+		previousTimestamp = timestamp;
+
+		// Request the next frame before dispatching the tick, since handling the tick may ask us to stop
+		animationFrameRequest = window.requestAnimationFrame(onAnimationFrame);
+		editor.instance.on_frame_tick(deltaTime);
+	};
+
+	// The editor tells us when something is animating, so we only send ticks while they're actually needed
+	editor.dispatcher.subscribeJsMessage(UpdateFrameTicksRequested, (updateFrameTicksRequested) => {
+		if (updateFrameTicksRequested.requested && animationFrameRequest === undefined) {
+			previousTimestamp = undefined;
+			animationFrameRequest = window.requestAnimationFrame(onAnimationFrame);
+		} else if (!updateFrameTicksRequested.requested && animationFrameRequest !== undefined) {
+			window.cancelAnimationFrame(animationFrameRequest);
+			animationFrameRequest = undefined;
+		}
+	});
+}
@@ -104,7 +104,7 @@ export function createInputManager(editor: EditorState, container: HTMLElement,
 		if (!e.buttons) viewportPointerInteractionOngoing = false;
 
 		const modifiers = makeModifiersBitfield(e);
-		editor.instance.on_mouse_move(e.clientX, e.clientY, e.buttons, modifiers);
+		editor.instance.on_mouse_move(e.clientX, e.clientY, e.buttons, penPressure(e), modifiers);
 	};
 
 	const onPointerDown = (e: PointerEvent): void => {
@@ -125,7 +125,7 @@ export function createInputManager(editor: EditorState, container: HTMLElement,
 
 		if (viewportPointerInteractionOngoing) {
 			const modifiers = makeModifiersBitfield(e);
-			editor.instance.on_mouse_down(e.clientX, e.clientY, e.buttons, modifiers);
+			editor.instance.on_mouse_down(e.clientX, e.clientY, e.buttons, penPressure(e), modifiers);
 		}
 	};
 
@@ -134,7 +134,7 @@ export function createInputManager(editor: EditorState, container: HTMLElement,
 
 		if (!textInput) {
 			const modifiers = makeModifiersBitfield(e);
-			editor.instance.on_mouse_up(e.clientX, e.clientY, e.buttons, modifiers);
+			editor.instance.on_mouse_up(e.clientX, e.clientY, e.buttons, penPressure(e), modifiers);
 		}
 	};
 
@@ -229,7 +229,12 @@ export function createInputManager(editor: EditorState, container: HTMLElement,
 export type InputManager = ReturnType<typeof createInputManager>;
 
 export function makeModifiersBitfield(e: WheelEvent | PointerEvent | KeyboardEvent): number {
-	return Number(e.ctrlKey) | (Number(e.shiftKey) << 1) | (Number(e.altKey) << 2);
+	return Number(e.ctrlKey) | (Number(e.shiftKey) << 1) | (Number(e.altKey) << 2) | (Number(e.metaKey) << 3);
+}
+
+// Only pens sense pressure, since browsers report a made-up pressure of 0.5 for mice and most touchscreens while a button is held
+export function penPressure(e: PointerEvent): number | undefined {
+	return e.pointerType === "pen" ? e.pressure : undefined;
 }
 
 // Necessary because innerText puts an extra newline character at the end when the text is more than one line.
@@ -0,0 +1,15 @@
+import { TriggerSaveSwatchLibrary } from "@/dispatcher/js-messages";
+import { EditorState } from "@/state/wasm-loader";
+
+const GRAPHITE_SWATCH_LIBRARY_KEY = "swatch-library";
+
+// eslint-disable-next-line @typescript-eslint/explicit-function-return-type
+export function createSwatchLibraryManager(editor: EditorState) {
+	// The editor sends the whole library, already serialized, whenever a swatch is added, removed, or renamed
+	editor.dispatcher.subscribeJsMessage(TriggerSaveSwatchLibrary, (triggerSaveSwatchLibrary) => {
+		window.localStorage.setItem(GRAPHITE_SWATCH_LIBRARY_KEY, triggerSaveSwatchLibrary.swatch_library);
+	});
+
+	const savedSwatchLibrary = window.localStorage.getItem(GRAPHITE_SWATCH_LIBRARY_KEY);
+	if (savedSwatchLibrary) editor.instance.load_swatch_library(savedSwatchLibrary);
+}
@@ -6,9 +6,12 @@ import {
 	DisplayConfirmationToCloseDocument,
 	TriggerFileDownload,
 	FrontendDocumentDetails,
+	FrontendRecentDocument,
 	TriggerFileUpload,
+	TriggerTextCopy,
 	UpdateActiveDocument,
 	UpdateOpenDocumentsList,
+	UpdateRecentDocumentsList,
 } from "@/dispatcher/js-messages";
 import { DialogState } from "@/state/dialog";
 import { EditorState } from "@/state/wasm-loader";
@@ -19,6 +22,7 @@ export function createDocumentsState(editor: EditorState, dialogState: DialogSta
 	const state = reactive({
 		unsaved: false,
 		documents: [] as FrontendDocumentDetails[],
+		recentDocuments: [] as FrontendRecentDocument[],
 		activeDocumentIndex: 0,
 	});
 
@@ -80,6 +84,10 @@ export function createDocumentsState(editor: EditorState, dialogState: DialogSta
 		state.documents = updateOpenDocumentList.open_documents;
 	});
 
+	editor.dispatcher.subscribeJsMessage(UpdateRecentDocumentsList, (updateRecentDocumentsList) => {
+		state.recentDocuments = updateRecentDocumentsList.recent_documents;
+	});
+
 	editor.dispatcher.subscribeJsMessage(UpdateActiveDocument, (updateActiveDocument) => {
 		// Assume we receive a correct document id
 		const activeId = state.documents.findIndex((doc) => doc.id === updateActiveDocument.document_id);
@@ -104,6 +112,10 @@ export function createDocumentsState(editor: EditorState, dialogState: DialogSta
 		download(triggerFileDownload.name, triggerFileDownload.document);
 	});
 
+	editor.dispatcher.subscribeJsMessage(TriggerTextCopy, async (triggerTextCopy) => {
+		await navigator.clipboard.writeText(triggerTextCopy.copy_text);
+	});
+
 	// Get the initial documents
 	editor.instance.get_open_documents_list();
 
@@ -21,6 +21,8 @@ import Overlays from "@/../assets/12px-solid/overlays.svg";
 import ResetColors from "@/../assets/12px-solid/reset-colors.svg";
 import Snapping from "@/../assets/12px-solid/snapping.svg";
 import Swap from "@/../assets/12px-solid/swap.svg";
+import TextBold from "@/../assets/12px-solid/text-bold.svg";
+import TextItalic from "@/../assets/12px-solid/text-italic.svg";
 import VerticalEllipsis from "@/../assets/12px-solid/vertical-ellipsis.svg";
 import Warning from "@/../assets/12px-solid/warning.svg";
 import WindowButtonWinClose from "@/../assets/12px-solid/window-button-win-close.svg";
@@ -125,6 +127,8 @@ export const ICON_LIST = {
 	ResetColors: { component: ResetColors, size: size12 },
 	Snapping: { component: Snapping, size: size12 },
 	Swap: { component: Swap, size: size12 },
+	TextBold: { component: TextBold, size: size12 },
+	TextItalic: { component: TextItalic, size: size12 },
 	VerticalEllipsis: { component: VerticalEllipsis, size: size12 },
 	Warning: { component: Warning, size: size12 },
 	WindowButtonWinClose: { component: WindowButtonWinClose, size: size12 },
@@ -0,0 +1,15 @@
+export type OperatingSystemPlatform = "Windows" | "Mac" | "Linux" | "Unknown";
+
+export function operatingSystemPlatform(): OperatingSystemPlatform {
+	const agent = window.navigator.userAgent;
+
+	if (agent.includes("Windows")) return "Windows";
+	if (agent.includes("Mac")) return "Mac";
+	if (agent.includes("Linux")) return "Linux";
+	return "Unknown";
+}
+
+// The key name (as used by the hints) of the physical key that acts as the primary modifier for shortcuts
+export function primaryModifierKeyName(): string {
+	return operatingSystemPlatform() === "Mac" ? "Command" : "Control";
+}
@@ -3,11 +3,14 @@
 // on the dispatcher messaging system and more complex Rust data types.
 
 use crate::helpers::Error;
-use crate::type_translators::{translate_blend_mode, translate_key, translate_tool_type};
+use crate::type_translators::{translate_artboard_preset, translate_blend_mode, translate_key, translate_platform, translate_tool_type, translate_wheel_behavior};
 use crate::{EDITOR_HAS_CRASHED, EDITOR_INSTANCES};
 
 use editor::consts::{FILE_SAVE_SUFFIX, GRAPHITE_DOCUMENT_VERSION};
-use editor::input::input_preprocessor::ModifierKeys;
+use editor::document::utility_types::{AlignAxis, ExportScope, NewDocumentArtboard};
+use editor::global::swatches::SwatchTarget;
+use editor::input::input_mapper::ModifierBindings;
+use editor::input::input_preprocessor::{ModifierKeys, Platform};
 use editor::input::mouse::{EditorMouseState, ScrollDelta, ViewportBounds};
 use editor::message_prelude::*;
 use editor::misc::EditorError;
@@ -51,12 +54,14 @@ impl JsEditorHandle {
 		}
 
 		let responses = EDITOR_INSTANCES.with(|instances| {
-			instances
-				.borrow_mut()
-				.get_mut(&self.editor_id)
-				.expect("EDITOR_INSTANCES does not contain the current editor_id")
-				.0
-				.handle_message(message.into())
+			let mut instances = instances.borrow_mut();
+			let editor = &mut instances.get_mut(&self.editor_id).expect("EDITOR_INSTANCES does not contain the current editor_id").0;
+
+			// Let the editor know when this message was sent, so it can tell a rapid series of edits apart from separate ones
+			let timestamp = js_sys::Date::now() as u64;
+			let mut responses = editor.handle_message(InputPreprocessorMessage::SetTime { timestamp });
+			responses.extend(editor.handle_message(message.into()));
+			responses
 		});
 		for response in responses.into_iter() {
 			// Send each FrontendMessage to the JavaScript frontend
@@ -91,7 +96,7 @@ impl JsEditorHandle {
 		EDITOR_HAS_CRASHED.load(Ordering::SeqCst)
 	}
 
-	/// Modify the currently selected tool in the document state store
+	/// Activate the given tool, aborting what the previous tool was doing. The frontend is sent the active tool in response, even if it was already active.
 	pub fn select_tool(&self, tool: String) -> Result<(), JsValue> {
 		match translate_tool_type(&tool) {
 			Some(tool_type) => {
@@ -150,10 +155,46 @@ impl JsEditorHandle {
 	}
 
 	pub fn new_document(&self) {
-		let message = PortfolioMessage::NewDocument;
+		let message = PortfolioMessage::NewDocument { artboard: None };
 		self.dispatch(message);
 	}
 
+	/// Create a new document starting with an artboard of one of the common sizes ("A4", "FullHd", or "Square") and a white background, zoomed to fit the viewport
+	pub fn new_document_from_preset(&self, preset: String) -> Result<(), JsValue> {
+		match translate_artboard_preset(&preset) {
+			Some(preset) => {
+				let artboard = Some(NewDocumentArtboard::from_preset(preset));
+				let message = PortfolioMessage::NewDocument { artboard };
+				self.dispatch(message);
+				let message = DocumentMessage::ZoomCanvasToFitAll;
+				self.dispatch(message);
+
+				Ok(())
+			}
+			None => Err(Error::new(&format!("Couldn't create a new document because the artboard preset {} was not recognized", preset)).into()),
+		}
+	}
+
+	/// Create a new document starting with a named artboard of the given size in pixels and background color, zoomed to fit the viewport
+	pub fn new_document_with_artboard(&self, name: String, width: f64, height: f64, red: f32, green: f32, blue: f32, alpha: f32) -> Result<(), JsValue> {
+		let background = match Color::from_rgbaf32(red, green, blue, alpha) {
+			Some(color) => color,
+			None => return Err(Error::new("Invalid color").into()),
+		};
+
+		let artboard = Some(NewDocumentArtboard {
+			name,
+			size: (width, height),
+			background,
+		});
+		let message = PortfolioMessage::NewDocument { artboard };
+		self.dispatch(message);
+		let message = DocumentMessage::ZoomCanvasToFitAll;
+		self.dispatch(message);
+
+		Ok(())
+	}
+
 	pub fn open_document(&self) {
 		let message = PortfolioMessage::OpenDocument;
 		self.dispatch(message);
@@ -177,6 +218,105 @@ impl JsEditorHandle {
 		self.dispatch(message);
 	}
 
+	pub fn open_recent_document(&self, document_id: u64) {
+		let message = PortfolioMessage::OpenRecentDocument { document_id };
+		self.dispatch(message);
+	}
+
+	pub fn set_max_recent_documents(&self, max_recent_documents: usize) {
+		let message = PortfolioMessage::SetMaxRecentDocuments { max_recent_documents };
+		self.dispatch(message);
+	}
+
+	/// Set the most undo steps each document keeps, and how many milliseconds apart edits of the same kind can be to be merged into one undo step
+	pub fn set_undo_history_limits(&self, max_undo_steps: usize, coalesce_window: u64) {
+		let message = PortfolioMessage::SetUndoHistoryLimits { max_undo_steps, coalesce_window };
+		self.dispatch(message);
+	}
+
+	/// Set how close to the edge of the viewport, in pixels, a drag needs to be for the canvas to pan, and how fast it pans at the edge in pixels per second
+	pub fn set_auto_pan(&self, margin: f64, speed: f64) {
+		let message = GlobalMessage::SetAutoPan { margin, speed };
+		self.dispatch(message);
+	}
+
+	/// Choose whether clicking on empty canvas with the Select tool deselects all layers, or leaves the selection to be cleared explicitly
+	pub fn set_deselect_on_empty_click(&self, enabled: bool) {
+		let message = GlobalMessage::SetDeselectOnEmptyClick { enabled };
+		self.dispatch(message);
+	}
+
+	/// Choose whether the Path tool keeps the handles of smooth points mirrored by default, or leaves them independent unless Alt is held
+	pub fn set_handle_mirroring(&self, enabled: bool) {
+		let message = GlobalMessage::SetHandleMirroring { enabled };
+		self.dispatch(message);
+	}
+
+	/// Reassign the keys held to draw from the center and to constrain proportions or angles, given as key names like "alt" or "shift"
+	pub fn set_modifier_bindings(&self, center: String, constrain: String) {
+		let bindings = ModifierBindings {
+			center: translate_key(&center),
+			constrain: translate_key(&constrain),
+		};
+		let message = GlobalMessage::SetModifierBindings { bindings };
+		self.dispatch(message);
+	}
+
+	/// Show or hide the overlays of every document, leaving the artwork and selection untouched
+	pub fn set_overlays_visible(&self, visible: bool) {
+		let message = GlobalMessage::SetOverlaysVisible { visible };
+		self.dispatch(message);
+	}
+
+	/// Snap layers moved with the Select tool to whole pixels in document space, for crisp raster rendering
+	pub fn set_pixel_snapping(&self, enabled: bool) {
+		let message = GlobalMessage::SetPixelSnapping { enabled };
+		self.dispatch(message);
+	}
+
+	/// Set the color and stroke width of the outlines drawn along selected layers
+	pub fn set_selection_outline_style(&self, red: f32, green: f32, blue: f32, alpha: f32, width: f64) -> Result<(), JsValue> {
+		let color = match Color::from_rgbaf32(red, green, blue, alpha) {
+			Some(color) => color,
+			None => return Err(Error::new("Invalid color").into()),
+		};
+
+		let message = GlobalMessage::SetSelectionOutlineStyle { color, width };
+		self.dispatch(message);
+
+		Ok(())
+	}
+
+	/// Set how far in viewport pixels a click reaches around the pointer to pick layers, and to pick points on a path
+	pub fn set_selection_tolerance(&self, tolerance: f64, point_threshold: f64) {
+		let message = GlobalMessage::SetSelectionTolerance { tolerance, point_threshold };
+		self.dispatch(message);
+	}
+
+	/// Set how far in viewport pixels the end of a line being drawn reaches to snap onto an anchor point of another shape
+	pub fn set_anchor_snap_tolerance(&self, tolerance: f64) {
+		let message = GlobalMessage::SetAnchorSnapTolerance { tolerance };
+		self.dispatch(message);
+	}
+
+	/// Mark the snap targets near the pointer while snapping, as a developer aid for debugging snapping
+	pub fn set_snap_targets_visible(&self, visible: bool) {
+		let message = GlobalMessage::SetSnapTargetsVisible { visible };
+		self.dispatch(message);
+	}
+
+	/// Set the increment in degrees that rotating the canvas or layers, and drawing lines, snaps to while the snapping key is held
+	pub fn set_rotate_snap_angle(&self, angle: f64) {
+		let message = GlobalMessage::SetRotateSnapAngle { angle };
+		self.dispatch(message);
+	}
+
+	/// Set how close in degrees a rotated layer must come to being axis-aligned before it's straightened, or 0 to never straighten
+	pub fn set_axis_snap_tolerance(&self, tolerance: f64) {
+		let message = GlobalMessage::SetAxisSnapTolerance { tolerance };
+		self.dispatch(message);
+	}
+
 	pub fn save_document(&self) {
 		let message = DocumentMessage::SaveDocument;
 		self.dispatch(message);
@@ -220,6 +360,25 @@ impl JsEditorHandle {
 		self.dispatch(message);
 	}
 
+	/// Set the platform so the shortcuts use its primary modifier key (Command on Mac and Control elsewhere)
+	pub fn set_platform(&self, platform: String) {
+		let platform = translate_platform(&platform).unwrap_or(Platform::Unknown);
+		let message = GlobalMessage::SetPlatform { platform };
+		self.dispatch(message);
+	}
+
+	/// Choose whether the plain mouse wheel scrolls ("Scroll") or zooms ("Zoom") the canvas, with the primary modifier key doing the other
+	pub fn set_wheel_behavior(&self, wheel_behavior: String) -> Result<(), JsValue> {
+		match translate_wheel_behavior(&wheel_behavior) {
+			Some(wheel_behavior) => {
+				let message = GlobalMessage::SetWheelBehavior { wheel_behavior };
+				self.dispatch(message);
+				Ok(())
+			}
+			None => Err(Error::new(&format!("Couldn't set the wheel behavior to {} because it was not recognized", wheel_behavior)).into()),
+		}
+	}
+
 	pub fn log_level_info(&self) {
 		let message = GlobalMessage::LogInfo;
 		self.dispatch(message);
@@ -245,8 +404,9 @@ impl JsEditorHandle {
 	}
 
 	/// Mouse movement within the screenspace bounds of the viewport
-	pub fn on_mouse_move(&self, x: f64, y: f64, mouse_keys: u8, modifiers: u8) {
-		let editor_mouse_state = EditorMouseState::from_keys_and_editor_position(mouse_keys, (x, y).into());
+	pub fn on_mouse_move(&self, x: f64, y: f64, mouse_keys: u8, pressure: Option<f64>, modifiers: u8) {
+		let mut editor_mouse_state = EditorMouseState::from_keys_and_editor_position(mouse_keys, (x, y).into());
+		editor_mouse_state.pressure = pressure;
 
 		let modifier_keys = ModifierKeys::from_bits(modifiers).expect("Invalid modifier keys");
 
@@ -266,8 +426,9 @@ impl JsEditorHandle {
 	}
 
 	/// A mouse button depressed within screenspace the bounds of the viewport
-	pub fn on_mouse_down(&self, x: f64, y: f64, mouse_keys: u8, modifiers: u8) {
-		let editor_mouse_state = EditorMouseState::from_keys_and_editor_position(mouse_keys, (x, y).into());
+	pub fn on_mouse_down(&self, x: f64, y: f64, mouse_keys: u8, pressure: Option<f64>, modifiers: u8) {
+		let mut editor_mouse_state = EditorMouseState::from_keys_and_editor_position(mouse_keys, (x, y).into());
+		editor_mouse_state.pressure = pressure;
 
 		let modifier_keys = ModifierKeys::from_bits(modifiers).expect("Invalid modifier keys");
 
@@ -276,8 +437,9 @@ impl JsEditorHandle {
 	}
 
 	/// A mouse button released
-	pub fn on_mouse_up(&self, x: f64, y: f64, mouse_keys: u8, modifiers: u8) {
-		let editor_mouse_state = EditorMouseState::from_keys_and_editor_position(mouse_keys, (x, y).into());
+	pub fn on_mouse_up(&self, x: f64, y: f64, mouse_keys: u8, pressure: Option<f64>, modifiers: u8) {
+		let mut editor_mouse_state = EditorMouseState::from_keys_and_editor_position(mouse_keys, (x, y).into());
+		editor_mouse_state.pressure = pressure;
 
 		let modifier_keys = ModifierKeys::from_bits(modifiers).expect("Invalid modifier keys");
 
@@ -294,6 +456,12 @@ impl JsEditorHandle {
 		self.dispatch(message);
 	}
 
+	/// A new animation frame is about to be drawn, sent only while the editor has requested frame ticks
+	pub fn on_frame_tick(&self, delta_time: f64) {
+		let message = InputPreprocessorMessage::FrameTick { delta_time };
+		self.dispatch(message);
+	}
+
 	/// A keyboard button depressed within screenspace the bounds of the viewport
 	pub fn on_key_down(&self, name: String, modifiers: u8) {
 		let key = translate_key(&name);
@@ -358,6 +526,67 @@ impl JsEditorHandle {
 		Ok(())
 	}
 
+	/// Restore the swatch library saved by the frontend from a previous session
+	pub fn load_swatch_library(&self, swatch_library: String) {
+		let message = GlobalMessage::LoadSwatchLibrary { swatch_library };
+		self.dispatch(message);
+	}
+
+	/// Add the swatches from a palette with one hex color and name per line, like `#E52E1B Signal Red`, to the swatch library
+	pub fn import_palette(&self, palette: String) {
+		let message = GlobalMessage::ImportPalette { palette };
+		self.dispatch(message);
+	}
+
+	/// Add a named color to the end of the swatch library
+	pub fn add_swatch(&self, name: String, red: f32, green: f32, blue: f32, alpha: f32) -> Result<(), JsValue> {
+		let color = match Color::from_rgbaf32(red, green, blue, alpha) {
+			Some(color) => color,
+			None => return Err(Error::new("Invalid color").into()),
+		};
+
+		let message = GlobalMessage::AddSwatch { name, color };
+		self.dispatch(message);
+
+		Ok(())
+	}
+
+	/// Set the primary color, or fill the selected layers, with the color of a swatch from the library
+	pub fn apply_swatch(&self, index: usize, to_selected_layers: bool) {
+		let target = if to_selected_layers { SwatchTarget::SelectedLayers } else { SwatchTarget::PrimaryColor };
+		let message = GlobalMessage::ApplySwatch { index, target };
+		self.dispatch(message);
+	}
+
+	/// Apply a color dropped onto the canvas at a position in the viewport, to the fill (or the stroke) of the topmost shape there, or otherwise to the background
+	pub fn apply_color_at_position(&self, x: f64, y: f64, red: f32, green: f32, blue: f32, alpha: f32, apply_to_stroke: bool) -> Result<(), JsValue> {
+		let color = match Color::from_rgbaf32(red, green, blue, alpha) {
+			Some(color) => color,
+			None => return Err(Error::new("Invalid color").into()),
+		};
+
+		let message = ToolMessage::ApplyColorAtPosition {
+			color,
+			position: (x, y),
+			apply_to_stroke,
+		};
+		self.dispatch(message);
+
+		Ok(())
+	}
+
+	/// Remove a swatch from the library
+	pub fn remove_swatch(&self, index: usize) {
+		let message = GlobalMessage::RemoveSwatch { index };
+		self.dispatch(message);
+	}
+
+	/// Rename a swatch in the library
+	pub fn rename_swatch(&self, index: usize, name: String) {
+		let message = GlobalMessage::RenameSwatch { index, name };
+		self.dispatch(message);
+	}
+
 	/// Swap primary and secondary color
 	pub fn swap_colors(&self) {
 		let message = ToolMessage::SwapColors;
@@ -400,6 +629,12 @@ impl JsEditorHandle {
 		self.dispatch(message);
 	}
 
+	/// Paste layers in place of the selected layers, centered where they were
+	pub fn paste_replace(&self) {
+		let message = PortfolioMessage::PasteReplace { clipboard: Clipboard::User };
+		self.dispatch(message);
+	}
+
 	/// Modify the layer selection based on the layer which is clicked while holding down the <kbd>Ctrl</kbd> and/or <kbd>Shift</kbd> modifier keys used for range selection behavior
 	pub fn select_layer(&self, layer_path: Vec<LayerId>, ctrl: bool, shift: bool) {
 		let message = DocumentMessage::SelectLayer { layer_path, ctrl, shift };
@@ -418,6 +653,18 @@ impl JsEditorHandle {
 		self.dispatch(message);
 	}
 
+	/// Enter a folder so new and pasted layers are inserted into it
+	pub fn enter_folder(&self, folder_path: Vec<LayerId>) {
+		let message = DocumentMessage::EnterFolder { folder_path };
+		self.dispatch(message);
+	}
+
+	/// Leave the entered folder, returning to its parent folder
+	pub fn exit_folder(&self) {
+		let message = DocumentMessage::ExitFolder;
+		self.dispatch(message);
+	}
+
 	/// Reorder selected layer
 	pub fn reorder_selected_layers(&self, relative_index_offset: isize) {
 		let message = DocumentMessage::ReorderSelectedLayers { relative_index_offset };
@@ -459,9 +706,47 @@ impl JsEditorHandle {
 		self.dispatch(message);
 	}
 
+	/// Center the selected layers on the artboard they're over, or the nearest one
+	pub fn center_on_artboard(&self) {
+		let message = DocumentMessage::CenterOnArtboard { axis: None };
+		self.dispatch(message);
+	}
+
+	/// Center the selected layers horizontally on the artboard they're over, or the nearest one
+	pub fn center_on_artboard_horizontally(&self) {
+		let message = DocumentMessage::CenterOnArtboard { axis: Some(AlignAxis::X) };
+		self.dispatch(message);
+	}
+
+	/// Center the selected layers vertically on the artboard they're over, or the nearest one
+	pub fn center_on_artboard_vertically(&self) {
+		let message = DocumentMessage::CenterOnArtboard { axis: Some(AlignAxis::Y) };
+		self.dispatch(message);
+	}
+
 	/// Export the document
 	pub fn export_document(&self) {
-		let message = DocumentMessage::ExportDocument;
+		let message = DocumentMessage::ExportDocument { scope: ExportScope::Document };
+		self.dispatch(message);
+	}
+
+	/// Export only the selected layers, fit tightly to their bounds
+	pub fn export_selection(&self) {
+		let message = DocumentMessage::ExportDocument { scope: ExportScope::Selection };
+		self.dispatch(message);
+	}
+
+	/// Export the artwork within the bounds of an artboard
+	pub fn export_artboard(&self, artboard: LayerId) {
+		let message = DocumentMessage::ExportDocument {
+			scope: ExportScope::Artboard { artboard },
+		};
+		self.dispatch(message);
+	}
+
+	/// Copy the selected layers to the system clipboard as SVG markup, fit tightly to their bounds
+	pub fn copy_selection_as_svg(&self) {
+		let message = DocumentMessage::CopyAsSvg { scope: ExportScope::Selection };
 		self.dispatch(message);
 	}
 
@@ -1,5 +1,7 @@
 use crate::helpers::match_string_to_enum;
 
+use editor::document::utility_types::ArtboardPreset;
+use editor::input::input_preprocessor::{Platform, WheelBehavior};
 use editor::input::keyboard::Key;
 use editor::viewport_tools::tool::ToolType;
 use graphene::layers::blend_mode::BlendMode;
@@ -32,6 +34,35 @@ pub fn translate_tool_type(name: &str) -> Option<ToolType> {
 	})
 }
 
+pub fn translate_platform(name: &str) -> Option<Platform> {
+	use Platform::*;
+
+	match_string_to_enum!(match (name) {
+		Windows,
+		Mac,
+		Linux,
+	})
+}
+
+pub fn translate_artboard_preset(name: &str) -> Option<ArtboardPreset> {
+	use ArtboardPreset::*;
+
+	match_string_to_enum!(match (name) {
+		A4,
+		FullHd,
+		Square,
+	})
+}
+
+pub fn translate_wheel_behavior(name: &str) -> Option<WheelBehavior> {
+	use WheelBehavior::*;
+
+	match_string_to_enum!(match (name) {
+		Scroll,
+		Zoom,
+	})
+}
+
 pub fn translate_blend_mode(blend_mode_svg_style_name: &str) -> Option<BlendMode> {
 	use BlendMode::*;
 
@@ -112,6 +143,7 @@ pub fn translate_key(name: &str) -> Key {
 		"delete" => KeyDelete,
 		"backspace" => KeyBackspace,
 		"alt" => KeyAlt,
+		"meta" => KeyCommand,
 		"escape" => KeyEscape,
 		"tab" => KeyTab,
 		"arrowup" => KeyArrowUp,
@@ -75,6 +75,31 @@ impl Color {
 		}
 	}
 
+	/// Return a `Color` from a 6-character RGB or 8-character RGBA hex string, with or without a # prefix.
+	/// Returns `None` if the string is any other length or contains characters that aren't hex digits.
+	///
+	/// # Examples
+	/// ```
+	/// use graphite_graphene::color::Color;
+	/// let color = Color::from_hex_str("#7C67FA").unwrap();
+	/// assert!(color == Color::from_rgb8(0x7C, 0x67, 0xFA));
+	///
+	/// let color = Color::from_hex_str("7C67FA61").unwrap();
+	/// assert!("7C67FA61" == color.rgba_hex());
+	///
+	/// assert!(Color::from_hex_str("7C67F") == None);
+	/// ```
+	pub fn from_hex_str(hex: &str) -> Option<Color> {
+		let hex = hex.strip_prefix('#').unwrap_or(hex);
+		if !(hex.len() == 6 || hex.len() == 8) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+			return None;
+		}
+
+		let channel = |index: usize| u8::from_str_radix(&hex[index * 2..index * 2 + 2], 16).ok();
+		let alpha = if hex.len() == 8 { channel(3)? } else { 0xFF };
+		Some(Color::from_rgba8(channel(0)?, channel(1)?, channel(2)?, alpha))
+	}
+
 	/// Return the `red` component.
 	///
 	/// # Examples
@@ -1,8 +1,8 @@
 use crate::intersection::Quad;
 use crate::layers;
 use crate::layers::folder::Folder;
-use crate::layers::layer_info::{Layer, LayerData, LayerDataType};
-use crate::layers::simple_shape::Shape;
+use crate::layers::layer_info::{Layer, LayerData, LayerDataType, RenderCacheStats};
+use crate::layers::simple_shape::{Shape, ShapeParameters};
 use crate::layers::style::ViewMode;
 use crate::layers::text::Text;
 use crate::{DocumentError, DocumentResponse, Operation};
@@ -22,6 +22,9 @@ pub struct Document {
 	/// This identifier is not a hash and is not guaranteed to be equal for equivalent documents.
 	#[serde(skip)]
 	pub state_identifier: DefaultHasher,
+	/// How often rendering has reused the cached output of unchanged layers, accumulated over every render of this document.
+	#[serde(skip)]
+	pub render_cache_stats: RenderCacheStats,
 }
 
 impl Default for Document {
@@ -29,6 +32,7 @@ impl Default for Document {
 		Self {
 			root: Layer::new(LayerDataType::Folder(Folder::default()), DAffine2::IDENTITY.to_cols_array()),
 			state_identifier: DefaultHasher::new(),
+			render_cache_stats: RenderCacheStats::default(),
 		}
 	}
 }
@@ -36,10 +40,38 @@ impl Default for Document {
 impl Document {
 	/// Wrapper around render, that returns the whole document as a Response.
 	pub fn render_root(&mut self, mode: ViewMode) -> String {
-		self.root.render(&mut vec![], mode);
+		self.root.render(&mut vec![], mode, &mut self.render_cache_stats);
 		self.root.cache.clone()
 	}
 
+	/// Renders only the layers at `paths`, along with everything inside them, leaving out every other layer even where it overlaps them.
+	/// The layers keep their place in the document, so the output lines up with that of [`Self::render_root`].
+	pub fn render_layers(&self, paths: &[Vec<LayerId>], mode: ViewMode) -> String {
+		fn hide_excluded(layer: &mut Layer, path: &mut Vec<LayerId>, paths: &[Vec<LayerId>]) {
+			if paths.iter().any(|included| included == path) {
+				return;
+			}
+			// The cached output of a folder holding excluded layers includes them, so it has to be rendered again
+			layer.cache_dirty = true;
+			if let LayerDataType::Folder(folder) = &mut layer.data {
+				let layer_ids = folder.layer_ids.clone();
+				for (id, child) in layer_ids.into_iter().zip(folder.layers_mut()) {
+					path.push(id);
+					if paths.iter().any(|included| included.starts_with(path)) {
+						hide_excluded(child, path, paths);
+					} else {
+						child.visible = false;
+					}
+					path.pop();
+				}
+			}
+		}
+
+		let mut root = self.root.clone();
+		hide_excluded(&mut root, &mut vec![], paths);
+		root.render(&mut vec![], mode, &mut RenderCacheStats::default()).to_string()
+	}
+
 	pub fn current_state_identifier(&self) -> u64 {
 		self.state_identifier.finish()
 	}
@@ -294,9 +326,14 @@ impl Document {
 	}
 
 	pub fn viewport_bounding_box(&self, path: &[LayerId]) -> Result<Option<[DVec2; 2]>, DocumentError> {
+		self.viewport_bounding_box_with_visibility(path, false)
+	}
+
+	/// The same as [`Self::viewport_bounding_box`], except that with `skip_hidden` the box of a folder only wraps the visible layers within it.
+	pub fn viewport_bounding_box_with_visibility(&self, path: &[LayerId], skip_hidden: bool) -> Result<Option<[DVec2; 2]>, DocumentError> {
 		let layer = self.layer(path)?;
 		let transform = self.multiply_transforms(path)?;
-		Ok(layer.data.bounding_box(transform))
+		Ok(layer.data.bounding_box_with_visibility(transform, skip_hidden))
 	}
 
 	pub fn bounding_box_and_transform(&self, path: &[LayerId]) -> Result<Option<([DVec2; 2], DAffine2)>, DocumentError> {
@@ -308,11 +345,12 @@ impl Document {
 	pub fn visible_layers_bounding_box(&self) -> Option<[DVec2; 2]> {
 		let mut paths = vec![];
 		self.visible_layers(&mut vec![], &mut paths).ok()?;
-		self.combined_viewport_bounding_box(paths.iter().map(|x| x.as_slice()))
+		self.combined_viewport_bounding_box(paths.iter().map(|x| x.as_slice()), true)
 	}
 
-	pub fn combined_viewport_bounding_box<'a>(&self, paths: impl Iterator<Item = &'a [LayerId]>) -> Option<[DVec2; 2]> {
-		let boxes = paths.filter_map(|path| self.viewport_bounding_box(path).ok()?);
+	/// The combined viewport bounding box of the layers at `paths`, where `skip_hidden` leaves out the hidden layers within folders.
+	pub fn combined_viewport_bounding_box<'a>(&self, paths: impl Iterator<Item = &'a [LayerId]>, skip_hidden: bool) -> Option<[DVec2; 2]> {
+		let boxes = paths.filter_map(|path| self.viewport_bounding_box_with_visibility(path, skip_hidden).ok()?);
 		boxes.reduce(|a, b| [a[0].min(b[0]), a[1].max(b[1])])
 	}
 
@@ -372,6 +410,12 @@ impl Document {
 		Ok(trans)
 	}
 
+	/// The width and height in document space of the unit square a layer is drawn in, unaffected by the canvas zoom.
+	pub fn layer_size_in_document(&self, path: &[LayerId]) -> Result<DVec2, DocumentError> {
+		let transform = self.root.transform.inverse() * self.multiply_transforms(path)?;
+		Ok(DVec2::new(transform.matrix2.x_axis.length(), transform.matrix2.y_axis.length()))
+	}
+
 	pub fn generate_transform_across_scope(&self, from: &[LayerId], to: Option<DAffine2>) -> Result<DAffine2, DocumentError> {
 		let from_rev = self.multiply_transforms(from)?;
 		let scope = to.unwrap_or(DAffine2::IDENTITY);
@@ -419,6 +463,21 @@ impl Document {
 
 				Some([vec![DocumentChanged, CreatedLayer { path: path.clone() }], update_thumbnails_upstream(path)].concat())
 			}
+			Operation::AddArc {
+				path,
+				insert_index,
+				transform,
+				arc_type,
+				start_angle,
+				sweep_angle,
+				style,
+			} => {
+				let layer = Layer::new(LayerDataType::Shape(Shape::arc(*arc_type, *start_angle, *sweep_angle, *style)), *transform);
+
+				self.set_layer(path, layer, *insert_index)?;
+
+				Some([vec![DocumentChanged, CreatedLayer { path: path.clone() }], update_thumbnails_upstream(path)].concat())
+			}
 			Operation::AddOverlayEllipse { path, transform, style } => {
 				let mut ellipse = Shape::ellipse(*style);
 				ellipse.render_index = -1;
@@ -486,6 +545,36 @@ impl Document {
 
 				Some([vec![DocumentChanged], update_thumbnails_upstream(path)].concat())
 			}
+			Operation::SetTextAlignment { path, alignment } => {
+				self.layer_mut(path)?.as_text_mut()?.update_alignment(*alignment);
+				self.mark_as_dirty(path)?;
+
+				Some([vec![DocumentChanged], update_thumbnails_upstream(path)].concat())
+			}
+			Operation::SetTextFont { path, font_family } => {
+				self.layer_mut(path)?.as_text_mut()?.font_family = font_family.clone();
+				self.mark_as_dirty(path)?;
+
+				Some([vec![DocumentChanged], update_thumbnails_upstream(path)].concat())
+			}
+			Operation::SetTextFontSize { path, font_size } => {
+				self.layer_mut(path)?.as_text_mut()?.update_size(*font_size);
+				self.mark_as_dirty(path)?;
+
+				Some([vec![DocumentChanged], update_thumbnails_upstream(path)].concat())
+			}
+			Operation::SetTextLineWidth { path, line_width } => {
+				self.layer_mut(path)?.as_text_mut()?.update_line_width(*line_width);
+				self.mark_as_dirty(path)?;
+
+				Some([vec![DocumentChanged], update_thumbnails_upstream(path)].concat())
+			}
+			Operation::SetTextStyle { path, bold, italic } => {
+				self.layer_mut(path)?.as_text_mut()?.update_style(*bold, *italic);
+				self.mark_as_dirty(path)?;
+
+				Some([vec![DocumentChanged], update_thumbnails_upstream(path)].concat())
+			}
 			Operation::AddNgon {
 				path,
 				insert_index,
@@ -499,6 +588,20 @@ impl Document {
 
 				Some([vec![DocumentChanged, CreatedLayer { path: path.clone() }], update_thumbnails_upstream(path)].concat())
 			}
+			Operation::AddStar {
+				path,
+				insert_index,
+				transform,
+				points,
+				inner_radius,
+				style,
+			} => {
+				let layer = Layer::new(LayerDataType::Shape(Shape::star(*points, *inner_radius, *style)), *transform);
+
+				self.set_layer(path, layer, *insert_index)?;
+
+				Some([vec![DocumentChanged, CreatedLayer { path: path.clone() }], update_thumbnails_upstream(path)].concat())
+			}
 			Operation::AddOverlayShape { path, style, bez_path, closed } => {
 				let mut shape = Shape::from_bez_path(bez_path.clone(), *style, *closed);
 				shape.render_index = -1;
@@ -512,22 +615,54 @@ impl Document {
 				path,
 				insert_index,
 				points,
+				closed,
 				transform,
 				style,
 			} => {
 				let points: Vec<glam::DVec2> = points.iter().map(|&it| it.into()).collect();
-				self.set_layer(path, Layer::new(LayerDataType::Shape(Shape::poly_line(points, *style)), *transform), *insert_index)?;
+				self.set_layer(path, Layer::new(LayerDataType::Shape(Shape::poly_line(points, *closed, *style)), *transform), *insert_index)?;
 				Some([vec![DocumentChanged, CreatedLayer { path: path.clone() }], update_thumbnails_upstream(path)].concat())
 			}
 			Operation::AddSpline {
 				path,
 				insert_index,
 				points,
+				closed,
+				tension,
+				transform,
+				style,
+			} => {
+				let points: Vec<glam::DVec2> = points.iter().map(|&it| it.into()).collect();
+				let shape = match closed {
+					true => Shape::closed_spline(points, *tension, *style),
+					false => Shape::spline(points, *tension, *style),
+				};
+				self.set_layer(path, Layer::new(LayerDataType::Shape(shape), *transform), *insert_index)?;
+				Some([vec![DocumentChanged, CreatedLayer { path: path.clone() }], update_thumbnails_upstream(path)].concat())
+			}
+			Operation::AddVariableWidthStroke {
+				path,
+				insert_index,
+				points,
+				widths,
 				transform,
 				style,
 			} => {
 				let points: Vec<glam::DVec2> = points.iter().map(|&it| it.into()).collect();
-				self.set_layer(path, Layer::new(LayerDataType::Shape(Shape::spline(points, *style)), *transform), *insert_index)?;
+				let shape = Shape::variable_width_stroke(points, widths.clone(), *style);
+				self.set_layer(path, Layer::new(LayerDataType::Shape(shape), *transform), *insert_index)?;
+				Some([vec![DocumentChanged, CreatedLayer { path: path.clone() }], update_thumbnails_upstream(path)].concat())
+			}
+			Operation::AddBezPath {
+				path,
+				insert_index,
+				bez_path,
+				closed,
+				transform,
+				style,
+			} => {
+				let shape = Shape::from_bez_path(bez_path.clone(), *style, *closed);
+				self.set_layer(path, Layer::new(LayerDataType::Shape(shape), *transform), *insert_index)?;
 				Some([vec![DocumentChanged, CreatedLayer { path: path.clone() }], update_thumbnails_upstream(path)].concat())
 			}
 			Operation::DeleteLayer { path } => {
@@ -629,11 +764,74 @@ impl Document {
 				self.mark_as_dirty(path)?;
 				Some([vec![DocumentChanged], update_thumbnails_upstream(path)].concat())
 			}
+			Operation::SetEllipseArcAngles { path, start_angle, sweep_angle } => {
+				self.mark_as_dirty(path)?;
+
+				if let LayerDataType::Shape(shape) = &mut self.layer_mut(path)?.data {
+					if let Some(ShapeParameters::Arc(arc)) = &mut shape.parametric {
+						arc.start_angle = *start_angle;
+						arc.sweep_angle = *sweep_angle;
+						shape.path = Shape::arc_path(arc.arc_type, arc.start_angle, arc.sweep_angle);
+					}
+				}
+				Some([vec![DocumentChanged, LayerChanged { path: path.clone() }], update_thumbnails_upstream(path)].concat())
+			}
+			Operation::SetPolygonCornerRadius { path, corner_radius } => {
+				// The corners are rounded according to the size of the polygon in document space, unaffected by the canvas zoom
+				let size = self.layer_size_in_document(path)?;
+				self.mark_as_dirty(path)?;
+
+				if let LayerDataType::Shape(shape) = &mut self.layer_mut(path)?.data {
+					if let Some(ShapeParameters::Polygon { corner_radius: current, .. }) = &mut shape.parametric {
+						*current = *corner_radius;
+						shape.update_polygon_path(size);
+					}
+				}
+				Some([vec![DocumentChanged, LayerChanged { path: path.clone() }], update_thumbnails_upstream(path)].concat())
+			}
+			Operation::SetPolygonRotation { path, rotation } => {
+				let size = self.layer_size_in_document(path)?;
+				self.mark_as_dirty(path)?;
+
+				if let LayerDataType::Shape(shape) = &mut self.layer_mut(path)?.data {
+					if let Some(ShapeParameters::Polygon { rotation: current, .. }) = &mut shape.parametric {
+						*current = *rotation;
+						shape.update_polygon_path(size);
+					}
+				}
+				Some([vec![DocumentChanged, LayerChanged { path: path.clone() }], update_thumbnails_upstream(path)].concat())
+			}
+			Operation::SetPolygonSides { path, sides } => {
+				let size = self.layer_size_in_document(path)?;
+				self.mark_as_dirty(path)?;
+
+				if let LayerDataType::Shape(shape) = &mut self.layer_mut(path)?.data {
+					if let Some(ShapeParameters::Polygon { sides: current, .. }) = &mut shape.parametric {
+						*current = *sides;
+						shape.update_polygon_path(size);
+					}
+				}
+				Some([vec![DocumentChanged, LayerChanged { path: path.clone() }], update_thumbnails_upstream(path)].concat())
+			}
+			Operation::SetRectangleCornerRadii { path, corner_radii } => {
+				// The corners are rounded according to the size of the rectangle in document space, unaffected by the canvas zoom
+				let size = self.layer_size_in_document(path)?;
+				self.mark_as_dirty(path)?;
+
+				if let LayerDataType::Shape(shape) = &mut self.layer_mut(path)?.data {
+					if let Some(ShapeParameters::Rectangle { corner_radii: current }) = &mut shape.parametric {
+						*current = *corner_radii;
+						shape.path = Shape::rounded_rectangle_path(*corner_radii, size);
+					}
+				}
+				Some(vec![DocumentChanged, LayerChanged { path: path.clone() }])
+			}
 			Operation::SetShapePath { path, bez_path } => {
 				self.mark_as_dirty(path)?;
 
 				if let LayerDataType::Shape(shape) = &mut self.layer_mut(path)?.data {
 					shape.path = bez_path.clone();
+					shape.parametric = None;
 				}
 				Some(vec![DocumentChanged, LayerChanged { path: path.clone() }])
 			}
@@ -649,6 +847,30 @@ impl Document {
 
 				if let LayerDataType::Shape(shape) = &mut self.layer_mut(path)?.data {
 					shape.path = bez_path.clone();
+					shape.parametric = None;
+				}
+				Some([vec![DocumentChanged, LayerChanged { path: path.clone() }], update_thumbnails_upstream(path)].concat())
+			}
+			Operation::SetSplineTension { path, tension } => {
+				self.mark_as_dirty(path)?;
+
+				if let LayerDataType::Shape(shape) = &mut self.layer_mut(path)?.data {
+					if let Some(ShapeParameters::Spline { tension: current }) = &mut shape.parametric {
+						*current = *tension;
+						shape.update_spline_path();
+					}
+				}
+				Some([vec![DocumentChanged, LayerChanged { path: path.clone() }], update_thumbnails_upstream(path)].concat())
+			}
+			Operation::SetStarInnerRadius { path, inner_radius } => {
+				let size = self.layer_size_in_document(path)?;
+				self.mark_as_dirty(path)?;
+
+				if let LayerDataType::Shape(shape) = &mut self.layer_mut(path)?.data {
+					if let Some(ShapeParameters::Polygon { star_inner_radius: Some(current), .. }) = &mut shape.parametric {
+						*current = *inner_radius;
+						shape.update_polygon_path(size);
+					}
 				}
 				Some([vec![DocumentChanged, LayerChanged { path: path.clone() }], update_thumbnails_upstream(path)].concat())
 			}
@@ -692,6 +914,11 @@ impl Document {
 
 				Some(vec![LayerChanged { path: path.clone() }])
 			}
+			Operation::SetLayerAspectLock { path, aspect_locked } => {
+				self.layer_mut(path)?.aspect_locked = *aspect_locked;
+
+				Some(vec![LayerChanged { path: path.clone() }])
+			}
 			Operation::SetLayerBlendMode { path, blend_mode } => {
 				self.mark_as_dirty(path)?;
 				self.layer_mut(path)?.blend_mode = *blend_mode;
@@ -722,6 +949,16 @@ impl Document {
 				self.mark_as_dirty(path)?;
 				Some([vec![DocumentChanged], update_thumbnails_upstream(path)].concat())
 			}
+			Operation::SetLayerGradient { path, gradient } => {
+				let layer = self.layer_mut(path)?;
+				match &mut layer.data {
+					LayerDataType::Shape(s) => s.style.set_fill(layers::style::Fill::new_gradient(*gradient)),
+					LayerDataType::Text(text) => text.style.set_fill(layers::style::Fill::new_gradient(*gradient)),
+					_ => return Err(DocumentError::NotAShape),
+				}
+				self.mark_as_dirty(path)?;
+				Some([vec![DocumentChanged], update_thumbnails_upstream(path)].concat())
+			}
 		};
 		Ok(responses)
 	}
@@ -1,4 +1,4 @@
-use super::layer_info::{Layer, LayerData, LayerDataType};
+use super::layer_info::{Layer, LayerData, LayerDataType, RenderCacheStats};
 use super::style::ViewMode;
 use crate::intersection::Quad;
 use crate::{DocumentError, LayerId};
@@ -16,9 +16,7 @@ pub struct Folder {
 
 impl LayerData for Folder {
 	fn render(&mut self, svg: &mut String, transforms: &mut Vec<glam::DAffine2>, view_mode: ViewMode) {
-		for layer in &mut self.layers {
-			let _ = writeln!(svg, "{}", layer.render(transforms, view_mode));
-		}
+		self.render_layers(svg, transforms, view_mode, &mut RenderCacheStats::default());
 	}
 
 	fn intersects_quad(&self, quad: Quad, path: &mut Vec<LayerId>, intersections: &mut Vec<Vec<LayerId>>) {
@@ -30,14 +28,27 @@ impl LayerData for Folder {
 	}
 
 	fn bounding_box(&self, transform: glam::DAffine2) -> Option<[DVec2; 2]> {
+		self.layers_bounding_box(transform, false)
+	}
+}
+
+impl Folder {
+	/// The combined bounding box of the layers in the folder, which with `skip_hidden` leaves out hidden layers and any hidden layers nested in the folders within.
+	pub fn layers_bounding_box(&self, transform: glam::DAffine2, skip_hidden: bool) -> Option<[DVec2; 2]> {
 		self.layers
 			.iter()
-			.filter_map(|layer| layer.data.bounding_box(transform * layer.transform))
+			.filter(|layer| layer.visible || !skip_hidden)
+			.filter_map(|layer| layer.data.bounding_box_with_visibility(transform * layer.transform, skip_hidden))
 			.reduce(|a, b| [a[0].min(b[0]), a[1].max(b[1])])
 	}
-}
 
-impl Folder {
+	/// Renders each layer in the folder, reusing the cached output of those which haven't changed.
+	pub fn render_layers(&mut self, svg: &mut String, transforms: &mut Vec<glam::DAffine2>, view_mode: ViewMode, stats: &mut RenderCacheStats) {
+		for layer in &mut self.layers {
+			let _ = writeln!(svg, "{}", layer.render(transforms, view_mode, stats));
+		}
+	}
+
 	/// When a insertion id is provided, try to insert the layer with the given id.
 	/// If that id is already used, return None.
 	/// When no insertion id is provided, search for the next free id and insert it with that.
@@ -19,6 +19,14 @@ pub enum LayerDataType {
 }
 
 impl LayerDataType {
+	/// The same as [`LayerData::bounding_box`], except that with `skip_hidden` a folder's box leaves out its hidden layers so it only wraps the visible content.
+	pub fn bounding_box_with_visibility(&self, transform: glam::DAffine2, skip_hidden: bool) -> Option<[DVec2; 2]> {
+		match self {
+			LayerDataType::Folder(folder) => folder.layers_bounding_box(transform, skip_hidden),
+			_ => self.bounding_box(transform),
+		}
+	}
+
 	pub fn inner(&self) -> &dyn LayerData {
 		match self {
 			LayerDataType::Shape(s) => s,
@@ -63,6 +71,21 @@ struct DAffine2Ref {
 	pub translation: DVec2,
 }
 
+/// Counts how often rendering reused the cached output of a layer and everything within it, rather than rendering the layer again.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RenderCacheStats {
+	pub hits: u64,
+	pub misses: u64,
+}
+
+impl RenderCacheStats {
+	/// The fraction of layer renders which were served from the cache, or `None` if nothing has been rendered.
+	pub fn hit_rate(&self) -> Option<f64> {
+		let total = self.hits + self.misses;
+		(total > 0).then(|| self.hits as f64 / total as f64)
+	}
+}
+
 fn return_true() -> bool {
 	true
 }
@@ -80,8 +103,17 @@ pub struct Layer {
 	pub thumbnail_cache: String,
 	#[serde(skip, default = "return_true")]
 	pub cache_dirty: bool,
+	/// The view mode the cache was rendered in
+	#[serde(skip)]
+	cache_view_mode: Option<ViewMode>,
+	/// The combined transform of the layer's parents when the cache was rendered, kept only if the cached output depends on it
+	#[serde(skip)]
+	cache_parent_transform: Option<DAffine2>,
 	pub blend_mode: BlendMode,
 	pub opacity: f64,
+	/// Whether resizing the layer with the transform cage should keep its aspect ratio
+	#[serde(default)]
+	pub aspect_locked: bool,
 }
 
 impl Layer {
@@ -94,8 +126,11 @@ impl Layer {
 			cache: String::new(),
 			thumbnail_cache: String::new(),
 			cache_dirty: true,
+			cache_view_mode: None,
+			cache_parent_transform: None,
 			blend_mode: BlendMode::Normal,
 			opacity: 1.,
+			aspect_locked: false,
 		}
 	}
 
@@ -103,15 +138,26 @@ impl Layer {
 		LayerIter { stack: vec![self] }
 	}
 
-	pub fn render(&mut self, transforms: &mut Vec<DAffine2>, view_mode: ViewMode) -> &str {
+	/// Renders the layer, reusing its cached output (and that of each unchanged layer within a folder) unless the layer was edited,
+	/// the view mode changed, or the transforms of its parents changed in a way that affects the output.
+	/// Every reuse and every fresh render is counted in `stats`.
+	pub fn render(&mut self, transforms: &mut Vec<DAffine2>, view_mode: ViewMode, stats: &mut RenderCacheStats) -> &str {
 		if !self.visible {
 			return "";
 		}
 
-		if self.cache_dirty {
+		let parent_transform = transforms.iter().cloned().reduce(|a, b| a * b).unwrap_or(DAffine2::IDENTITY);
+		let parents_changed = self.cache_parent_transform.map_or(false, |cached| cached != parent_transform);
+
+		if self.cache_dirty || parents_changed || self.cache_view_mode != Some(view_mode) {
+			stats.misses += 1;
+
 			transforms.push(self.transform);
 			self.thumbnail_cache.clear();
-			self.data.render(&mut self.thumbnail_cache, transforms, view_mode);
+			match &mut self.data {
+				LayerDataType::Folder(folder) => folder.render_layers(&mut self.thumbnail_cache, transforms, view_mode, stats),
+				data => data.render(&mut self.thumbnail_cache, transforms, view_mode),
+			}
 
 			self.cache.clear();
 			let _ = writeln!(self.cache, r#"<g transform="matrix("#);
@@ -127,11 +173,25 @@ impl Layer {
 			);
 			transforms.pop();
 			self.cache_dirty = false;
+			self.cache_view_mode = Some(view_mode);
+			self.cache_parent_transform = self.depends_on_parent_transform(view_mode).then(|| parent_transform);
+		} else {
+			stats.hits += 1;
 		}
 
 		self.cache.as_str()
 	}
 
+	/// Whether the rendered output of this layer changes with the transforms of its parents, rather than only with its own.
+	/// Folders depend on them if any of their visible layers do, so this must be called after the layers within have been rendered.
+	fn depends_on_parent_transform(&self, view_mode: ViewMode) -> bool {
+		match &self.data {
+			LayerDataType::Shape(shape) => view_mode == ViewMode::Outline || !(0..=1).contains(&shape.render_index),
+			LayerDataType::Text(_) => view_mode == ViewMode::Outline,
+			LayerDataType::Folder(folder) => folder.layers().iter().any(|layer| layer.visible && layer.cache_parent_transform.is_some()),
+		}
+	}
+
 	pub fn intersects_quad(&self, quad: Quad, path: &mut Vec<LayerId>, intersections: &mut Vec<Vec<LayerId>>) {
 		if !self.visible {
 			return;
@@ -188,8 +248,11 @@ impl Clone for Layer {
 			cache: String::new(),
 			thumbnail_cache: String::new(),
 			cache_dirty: true,
+			cache_view_mode: None,
+			cache_parent_transform: None,
 			blend_mode: self.blend_mode,
 			opacity: self.opacity,
+			aspect_locked: self.aspect_locked,
 		}
 	}
 }
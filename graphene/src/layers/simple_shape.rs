@@ -4,7 +4,7 @@ use crate::intersection::{intersect_quad_bez_path, Quad};
 use crate::LayerId;
 
 use glam::{DAffine2, DMat2, DVec2};
-use kurbo::{Affine, BezPath, Shape as KurboShape};
+use kurbo::{Affine, BezPath, PathEl, Shape as KurboShape};
 use serde::{Deserialize, Serialize};
 use std::fmt::Write;
 
@@ -12,12 +12,59 @@ fn glam_to_kurbo(transform: DAffine2) -> Affine {
 	Affine::new(transform.to_cols_array())
 }
 
+/// How the ends of a partial ellipse are connected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ArcType {
+	/// Only the curved outline, with the ends left unconnected
+	Open,
+	/// Both ends connected to the center, like a slice of a pie chart
+	PieSlice,
+	/// The ends connected to each other by a straight line
+	Chord,
+}
+
+/// The angles of part of an ellipse, kept with its shape so they can be changed after it's drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct EllipseArc {
+	pub arc_type: ArcType,
+	/// In radians, clockwise from pointing right
+	pub start_angle: f64,
+	/// In radians, clockwise from the start angle. Zero means the ellipse is complete.
+	pub sweep_angle: f64,
+}
+
+/// What a shape was drawn as, kept with it so it can still be changed that way after it's drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub enum ShapeParameters {
+	/// A rectangle whose corners can be rounded, with the radius of each corner clockwise from the top left
+	Rectangle { corner_radii: [f64; 4] },
+	/// Part of an ellipse whose angles can be changed
+	Arc(EllipseArc),
+	/// A regular polygon whose side count can be changed, or a star if it has an inner radius
+	Polygon {
+		/// The number of sides, or of points if it's a star
+		sides: u8,
+		/// The inner radius as a fraction of the outer radius, if it's a star whose spikes can be made deeper or shallower
+		star_inner_radius: Option<f64>,
+		/// The radius in document units that each vertex is rounded by
+		corner_radius: f64,
+		/// The angle in radians, clockwise, that the shape is turned about its center from where its first vertex usually points
+		rotation: f64,
+	},
+	/// A spline whose tension can be changed, which is how far the handles reach out from the points the curve passes through.
+	/// At 0.5 the curve is the natural smooth spline, towards 0 it tightens into straight segments, and towards 1 it loosens into rounder curves.
+	Spline { tension: f64 },
+}
+
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Shape {
 	pub path: BezPath,
 	pub style: style::PathStyle,
 	pub render_index: i32,
 	pub closed: bool,
+	/// What the shape was drawn as, if it can still be changed that way rather than only by editing its path
+	#[serde(default)]
+	pub parametric: Option<ShapeParameters>,
 }
 
 impl LayerData for Shape {
@@ -36,7 +83,13 @@ impl LayerData for Shape {
 			let _ = svg.write_str(&(entry.to_string() + if i == 5 { "" } else { "," }));
 		});
 		let _ = svg.write_str(r#")">"#);
-		let _ = write!(svg, r#"<path d="{}" {} />"#, path.to_svg(), self.style.render(view_mode));
+		let _ = write!(
+			svg,
+			r#"{}<path d="{}" {} />"#,
+			self.style.render_definitions(view_mode, transform),
+			path.to_svg(),
+			self.style.render(view_mode, transform)
+		);
 		let _ = svg.write_str("</g>");
 	}
 
@@ -70,45 +123,178 @@ impl Shape {
 		transforms.iter().skip(start).cloned().reduce(|a, b| a * b).unwrap_or(DAffine2::IDENTITY)
 	}
 
+	/// The radius of each corner, clockwise from the top left, if this shape is a rectangle whose corners can be rounded.
+	pub fn corner_radii(&self) -> Option<[f64; 4]> {
+		match self.parametric {
+			Some(ShapeParameters::Rectangle { corner_radii }) => Some(corner_radii),
+			_ => None,
+		}
+	}
+
+	/// The arc type and angles, if this shape is part of an ellipse whose angles can be changed.
+	pub fn arc(&self) -> Option<EllipseArc> {
+		match self.parametric {
+			Some(ShapeParameters::Arc(arc)) => Some(arc),
+			_ => None,
+		}
+	}
+
+	/// The tension, if this shape is a spline whose tension can be changed.
+	pub fn spline_tension(&self) -> Option<f64> {
+		match self.parametric {
+			Some(ShapeParameters::Spline { tension }) => Some(tension),
+			_ => None,
+		}
+	}
+
 	pub fn from_bez_path(bez_path: BezPath, style: PathStyle, closed: bool) -> Self {
 		Self {
 			path: bez_path,
 			style,
 			render_index: 1,
 			closed,
+			parametric: None,
 		}
 	}
 
 	pub fn ngon(sides: u8, style: PathStyle) -> Self {
+		Self {
+			path: Self::ngon_path(sides),
+			style,
+			render_index: 1,
+			closed: true,
+			parametric: Some(ShapeParameters::Polygon {
+				sides,
+				star_inner_radius: None,
+				corner_radius: 0.,
+				rotation: 0.,
+			}),
+		}
+	}
+
+	/// Creates the path of a regular polygon with the given number of sides, fitted into the unit square, which every polygon shape starts out with.
+	pub fn ngon_path(sides: u8) -> BezPath {
+		Self::rounded_polygon_path(&Self::ngon_vertices(sides, 0.), 0., DVec2::ONE)
+	}
+
+	fn ngon_vertices(sides: u8, rotation: f64) -> Vec<DVec2> {
 		use std::f64::consts::{FRAC_PI_2, TAU};
 
 		fn unit_rotation(theta: f64) -> DVec2 {
 			DVec2::new(theta.sin(), theta.cos())
 		}
 
-		let mut path = kurbo::BezPath::new();
-
 		let apothem_offset_angle = TAU / (sides as f64);
 		// Rotate odd sided shapes by 90 degrees
-		let offset = ((sides + 1) % 2) as f64 * FRAC_PI_2;
+		let offset = if sides % 2 == 0 { FRAC_PI_2 } else { 0. };
 
 		let relative_points = (0..sides).map(|i| apothem_offset_angle * i as f64 + offset).map(unit_rotation);
 
+		// The unrotated polygon is fitted into the unit square, and any rotation turns it about its center from there
 		let min = relative_points.clone().reduce(|a, b| a.min(b)).unwrap_or_default();
-		let transform = DAffine2::from_scale_angle_translation(DVec2::ONE / 2., 0., -min / 2.);
-		let point = |vec: DVec2| kurbo::Point::new(vec.x, vec.y);
-
-		let mut relative_points = relative_points.map(|p| point(transform.transform_point2(p)));
-		path.move_to(relative_points.next().expect("Tried to create an ngon with 0 sides"));
-		relative_points.for_each(|p| path.line_to(p));
+		let transform = DAffine2::from_scale_angle_translation(DVec2::ONE / 2., rotation, -min / 2.);
 
-		path.close_path();
+		relative_points.map(|p| transform.transform_point2(p)).collect()
+	}
 
+	pub fn star(points: u8, inner_radius: f64, style: PathStyle) -> Self {
 		Self {
-			path,
+			path: Self::star_path(points, inner_radius),
 			style,
 			render_index: 1,
 			closed: true,
+			parametric: Some(ShapeParameters::Polygon {
+				sides: points,
+				star_inner_radius: Some(inner_radius),
+				corner_radius: 0.,
+				rotation: 0.,
+			}),
+		}
+	}
+
+	/// Creates the path of a star with the given number of points, centered in the unit square with its first point at the top.
+	/// The vertices alternate between the outer radius of half the square and the inner radius, which is the given fraction of that.
+	pub fn star_path(points: u8, inner_radius: f64) -> BezPath {
+		Self::rounded_polygon_path(&Self::star_vertices(points, inner_radius, 0.), 0., DVec2::ONE)
+	}
+
+	fn star_vertices(points: u8, inner_radius: f64, rotation: f64) -> Vec<DVec2> {
+		use std::f64::consts::{FRAC_PI_2, PI};
+
+		(0..points.saturating_mul(2))
+			.map(|index| {
+				let radius = if index % 2 == 0 { 0.5 } else { 0.5 * inner_radius.clamp(0., 1.) };
+				let angle = PI / points as f64 * index as f64 - FRAC_PI_2 + rotation;
+				DVec2::splat(0.5) + radius * DVec2::new(angle.cos(), angle.sin())
+			})
+			.collect()
+	}
+
+	/// Creates the closed path through the given vertices of the unit square, with each vertex rounded into a circular arc of `corner_radius` once the square is stretched to `size`.
+	/// Both convex and concave vertices are rounded, and each arc is shrunk as needed so it takes up no more than half of either edge it touches, keeping neighboring arcs from overlapping.
+	pub fn rounded_polygon_path(vertices: &[DVec2], corner_radius: f64, size: DVec2) -> BezPath {
+		let size = size.abs();
+		// The vertices are rounded where the polygon has its real proportions, so the arcs aren't stretched along with it
+		let scale = if size.min_element() > 0. { size } else { DVec2::ONE };
+		let point = |vec: DVec2| kurbo::Point::new(vec.x / scale.x, vec.y / scale.y);
+
+		let mut path = kurbo::BezPath::new();
+		let count = vertices.len();
+		if count == 0 {
+			return path;
+		}
+		for (index, &vertex) in vertices.iter().enumerate() {
+			let vertex = vertex * scale;
+			let previous = vertices[(index + count - 1) % count] * scale - vertex;
+			let next = vertices[(index + 1) % count] * scale - vertex;
+
+			// Half the angle between the two edges meeting at the vertex, which is the same whether the vertex is convex or concave
+			let half_angle = previous.angle_between(next).abs() / 2.;
+			let distance = corner_radius.max(0.) / half_angle.tan();
+			let distance = distance.min(previous.length() / 2.).min(next.length() / 2.);
+
+			// A vertex on a straight line, or with an edge of no length, has no corner to round
+			let on_straight_line = (std::f64::consts::FRAC_PI_2 - half_angle).abs() < 1e-9;
+			if on_straight_line || !(distance.is_finite() && distance > 0. && half_angle > 0.) {
+				if index == 0 {
+					path.move_to(point(vertex));
+				} else {
+					path.line_to(point(vertex));
+				}
+				continue;
+			}
+
+			let (start, end) = (vertex + previous.normalize() * distance, vertex + next.normalize() * distance);
+			// The control points approximate the arc of the fillet, which turns by the supplement of the angle between the edges
+			let radius = distance * half_angle.tan();
+			let handle = 4. / 3. * ((std::f64::consts::PI - 2. * half_angle) / 4.).tan() * radius;
+
+			if index == 0 {
+				path.move_to(point(start));
+			} else {
+				path.line_to(point(start));
+			}
+			path.curve_to(point(start - previous.normalize() * handle), point(end - next.normalize() * handle), point(end));
+		}
+
+		path.close_path();
+		path
+	}
+
+	/// Regenerates the path of a regular polygon or star from its number of sides, inner radius, corner radius, and rotation, with its layer stretched to `size` in document space.
+	pub fn update_polygon_path(&mut self, size: DVec2) {
+		if let Some(ShapeParameters::Polygon {
+			sides,
+			star_inner_radius,
+			corner_radius,
+			rotation,
+		}) = self.parametric
+		{
+			let vertices = match star_inner_radius {
+				Some(inner_radius) => Self::star_vertices(sides, inner_radius, rotation),
+				None => Self::ngon_vertices(sides, rotation),
+			};
+			self.path = Self::rounded_polygon_path(&vertices, corner_radius, size);
 		}
 	}
 
@@ -118,28 +304,118 @@ impl Shape {
 			style,
 			render_index: 1,
 			closed: true,
+			parametric: Some(ShapeParameters::Rectangle { corner_radii: [0.; 4] }),
 		}
 	}
 
+	/// Creates the path of a unit square with rounded corners, which become circular arcs of the given radii once the square is stretched to `size`.
+	/// The radii are listed clockwise from the top left and each is clamped to half of the shorter side.
+	pub fn rounded_rectangle_path(corner_radii: [f64; 4], size: DVec2) -> BezPath {
+		// Length of the control point handles for a cubic bezier approximating a quarter circle of radius 1
+		const KAPPA: f64 = 0.552_284_75;
+
+		let size = size.abs();
+		let max_radius = size.min_element() / 2.;
+		let point = |vec: DVec2| kurbo::Point::new(vec.x, vec.y);
+
+		let corners = [DVec2::new(0., 0.), DVec2::new(1., 0.), DVec2::new(1., 1.), DVec2::new(0., 1.)];
+		let mut path = kurbo::BezPath::new();
+
+		for (index, (corner, radius)) in corners.into_iter().zip(corner_radii).enumerate() {
+			let radius = radius.clamp(0., max_radius.max(0.));
+			let radius = if radius > 0. { DVec2::splat(radius) / size } else { DVec2::ZERO };
+			let inwards = DVec2::ONE - 2. * corner;
+
+			// Going clockwise, the arc at the top left and bottom right corners starts on the vertical edge, and on the horizontal edge at the other two
+			let (start, end) = match index % 2 {
+				0 => (corner + DVec2::new(0., radius.y * inwards.y), corner + DVec2::new(radius.x * inwards.x, 0.)),
+				_ => (corner + DVec2::new(radius.x * inwards.x, 0.), corner + DVec2::new(0., radius.y * inwards.y)),
+			};
+
+			if index == 0 {
+				path.move_to(point(start));
+			} else {
+				path.line_to(point(start));
+			}
+			if start != end {
+				path.curve_to(point(start + (corner - start) * KAPPA), point(end + (corner - end) * KAPPA), point(end));
+			}
+		}
+
+		path.close_path();
+		path
+	}
+
 	pub fn ellipse(style: PathStyle) -> Self {
 		Self {
-			path: kurbo::Ellipse::from_rect(kurbo::Rect::new(0., 0., 1., 1.)).to_path(0.01),
+			path: Self::ellipse_path(),
 			style,
 			render_index: 1,
 			closed: true,
+			parametric: None,
 		}
 	}
 
+	pub fn arc(arc_type: ArcType, start_angle: f64, sweep_angle: f64, style: PathStyle) -> Self {
+		Self {
+			path: Self::arc_path(arc_type, start_angle, sweep_angle),
+			style,
+			render_index: 1,
+			closed: arc_type != ArcType::Open,
+			parametric: Some(ShapeParameters::Arc(EllipseArc { arc_type, start_angle, sweep_angle })),
+		}
+	}
+
+	/// Creates the path of the ellipse inscribed in the unit square, which every ellipse shape starts out with.
+	pub fn ellipse_path() -> BezPath {
+		kurbo::Ellipse::from_rect(kurbo::Rect::new(0., 0., 1., 1.)).to_path(0.01)
+	}
+
+	/// Creates the path of part of the ellipse inscribed in the unit square, beginning at `start_angle` and extending clockwise by `sweep_angle`.
+	/// Angles are in radians, with zero pointing to the right. A sweep of zero or of at least a full turn gives the complete ellipse, identical to [Shape::ellipse].
+	pub fn arc_path(arc_type: ArcType, start_angle: f64, sweep_angle: f64) -> BezPath {
+		if sweep_angle == 0. || sweep_angle.abs() >= std::f64::consts::TAU {
+			return Self::ellipse_path();
+		}
+
+		let center = kurbo::Point::new(0.5, 0.5);
+		let radii = kurbo::Vec2::new(0.5, 0.5);
+		let arc = kurbo::Arc {
+			center,
+			radii,
+			start_angle,
+			sweep_angle,
+			x_rotation: 0.,
+		};
+		let start = center + radii.x * kurbo::Vec2::from_angle(start_angle);
+
+		let mut path = kurbo::BezPath::new();
+		if arc_type == ArcType::PieSlice {
+			path.move_to(center);
+			path.line_to(start);
+		} else {
+			path.move_to(start);
+		}
+		path.extend(arc.append_iter(0.01));
+		if arc_type != ArcType::Open {
+			path.close_path();
+		}
+
+		path
+	}
+
 	pub fn line(style: PathStyle) -> Self {
 		Self {
 			path: kurbo::Line::new((0., 0.), (1., 0.)).to_path(0.01),
 			style,
 			render_index: 1,
 			closed: false,
+			parametric: None,
 		}
 	}
 
-	pub fn poly_line(points: Vec<impl Into<glam::DVec2>>, style: PathStyle) -> Self {
+	/// Creates straight segments through all given points, which loop back around to the first one if `closed`.
+	pub fn poly_line(points: Vec<impl Into<glam::DVec2>>, closed: bool, style: PathStyle) -> Self {
 		let mut path = kurbo::BezPath::new();
 		points
 			.into_iter()
@@ -147,24 +423,84 @@ impl Shape {
 			.map(|v: DVec2| kurbo::Point { x: v.x, y: v.y })
 			.enumerate()
 			.for_each(|(i, p)| if i == 0 { path.move_to(p) } else { path.line_to(p) });
+		if closed {
+			path.close_path();
+		}
 
 		Self {
 			path,
 			style,
 			render_index: 0,
+			closed,
+			parametric: None,
+		}
+	}
+
+	/// Creates the outline of a stroke through all given points, as wide at each point as the corresponding entry in `widths`, to be filled in rather than stroked.
+	pub fn variable_width_stroke(points: Vec<impl Into<glam::DVec2>>, widths: Vec<f64>, style: PathStyle) -> Self {
+		let points: Vec<DVec2> = points.into_iter().map(|v| v.into()).collect();
+		let point = |v: DVec2| kurbo::Point { x: v.x, y: v.y };
+		let n = points.len();
+
+		// The offset from each point to the stroke's edge on its left, perpendicular to the direction the stroke passes through the point
+		let offsets: Vec<_> = (0..n)
+			.map(|i| {
+				let direction = (points[(i + 1).min(n - 1)] - points[i.saturating_sub(1)]).normalize_or_zero();
+				DVec2::new(-direction.y, direction.x) * widths[i] / 2.
+			})
+			.collect();
+
+		// Go along the left edge, then back along the right edge to where the stroke started
+		let mut path = kurbo::BezPath::new();
+		let left = points.iter().zip(&offsets).map(|(point, offset)| *point + *offset);
+		let right = points.iter().zip(&offsets).rev().map(|(point, offset)| *point - *offset);
+		left.chain(right).enumerate().for_each(|(i, p)| if i == 0 { path.move_to(point(p)) } else { path.line_to(point(p)) });
+		path.close_path();
+
+		Self {
+			path,
+			style,
+			render_index: 0,
+			closed: true,
+			parametric: None,
+		}
+	}
+
+	/// Creates a smooth bezier spline that passes through all given points, with its handles reaching out according to `tension` (see [ShapeParameters::Spline]).
+	pub fn spline(points: Vec<impl Into<glam::DVec2>>, tension: f64, style: PathStyle) -> Self {
+		let points: Vec<DVec2> = points.into_iter().map(|v| v.into()).collect();
+
+		Self {
+			path: Self::spline_path(&points, tension),
+			style,
+			render_index: 0,
 			closed: false,
+			parametric: Some(ShapeParameters::Spline { tension }),
 		}
 	}
 
-	/// Creates a smooth bezier spline that passes through all given points.
+	/// Creates a smooth bezier spline that passes through all given points and loops back around to the first one, with no corner where it closes.
+	pub fn closed_spline(points: Vec<impl Into<glam::DVec2>>, tension: f64, style: PathStyle) -> Self {
+		let points: Vec<DVec2> = points.into_iter().map(|v| v.into()).collect();
+
+		Self {
+			path: Self::closed_spline_path(&points, tension),
+			style,
+			render_index: 0,
+			closed: true,
+			parametric: Some(ShapeParameters::Spline { tension }),
+		}
+	}
+
+	/// The path of a spline through the given points.
 	/// The algorithm used in this implementation is described here: https://www.particleincell.com/2012/bezier-splines/
-	pub fn spline(points: Vec<impl Into<glam::DVec2>>, style: PathStyle) -> Self {
+	fn spline_path(points: &[DVec2], tension: f64) -> BezPath {
 		let mut path = kurbo::BezPath::new();
 
 		// Creating a bezier spline is only necessary for 3 or more points.
 		// For 2 given points a line segment is created instead.
 		if points.len() > 2 {
-			let points: Vec<_> = points.into_iter().map(|v| v.into()).map(|v: DVec2| kurbo::Vec2 { x: v.x, y: v.y }).collect();
+			let points: Vec<_> = points.iter().map(|v| kurbo::Vec2 { x: v.x, y: v.y }).collect();
 
 			// Number of bezier segments
 			let n = points.len() - 1;
@@ -209,6 +545,13 @@ impl Shape {
 			}
 			p2[n - 1] = 0.5 * (points[n] + p1[n - 1]);
 
+			// Scale each handle's reach from the point it belongs to, which keeps the handles on either side of a point in line so the curve stays smooth
+			let reach = 2. * tension;
+			for i in 0..n {
+				p1[i] = points[i] + reach * (p1[i] - points[i]);
+				p2[i] = points[i + 1] + reach * (p2[i] - points[i + 1]);
+			}
+
 			// Create bezier path from given points and computed control points
 			points.into_iter().enumerate().for_each(|(i, p)| {
 				if i == 0 {
@@ -219,18 +562,281 @@ impl Shape {
 			});
 		} else {
 			points
-				.into_iter()
-				.map(|v| v.into())
-				.map(|v: DVec2| kurbo::Point { x: v.x, y: v.y })
+				.iter()
+				.map(|v| kurbo::Point { x: v.x, y: v.y })
 				.enumerate()
 				.for_each(|(i, p)| if i == 0 { path.move_to(p) } else { path.line_to(p) });
 		}
 
-		Self {
-			path,
-			style,
-			render_index: 0,
-			closed: false,
+		path
+	}
+
+	/// The path of a spline through the given points that loops back around to the first one.
+	/// Like [Shape::spline_path], the curve is continuous in its first and second derivatives, but at every point since the loop has no ends.
+	fn closed_spline_path(points: &[DVec2], tension: f64) -> BezPath {
+		let point = |v: DVec2| kurbo::Point { x: v.x, y: v.y };
+		let mut path = kurbo::BezPath::new();
+
+		// A loop through fewer than 3 points has nothing to curve around, so straight segments are used instead
+		let n = points.len();
+		if n > 2 {
+			// The first control point of each segment satisfies p1[i - 1] + 4 * p1[i] + p1[i + 1] = 4 * points[i] + 2 * points[i + 1], wrapping around the loop
+			let r: Vec<_> = (0..n).map(|i| 4. * points[i] + 2. * points[(i + 1) % n]).collect();
+			let p1 = solve_cyclic_tridiagonal(&r);
+			let p2: Vec<_> = (0..n).map(|i| 2. * points[(i + 1) % n] - p1[(i + 1) % n]).collect();
+
+			let reach = 2. * tension;
+			path.move_to(point(points[0]));
+			for i in 0..n {
+				let (start, end) = (points[i], points[(i + 1) % n]);
+				path.curve_to(point(start + reach * (p1[i] - start)), point(end + reach * (p2[i] - end)), point(end));
+			}
+		} else {
+			points.iter().enumerate().for_each(|(i, p)| if i == 0 { path.move_to(point(*p)) } else { path.line_to(point(*p)) });
+		}
+		path.close_path();
+
+		path
+	}
+
+	/// Regenerates the path of a spline from the points it passes through, after its tension has changed.
+	pub fn update_spline_path(&mut self) {
+		let tension = match self.spline_tension() {
+			Some(tension) => tension,
+			None => return,
+		};
+
+		let mut points: Vec<DVec2> = self
+			.path
+			.elements()
+			.iter()
+			.filter_map(|element| match element {
+				PathEl::MoveTo(point) | PathEl::LineTo(point) | PathEl::CurveTo(_, _, point) => Some(DVec2::new(point.x, point.y)),
+				_ => None,
+			})
+			.collect();
+
+		self.path = if self.closed {
+			// A curved loop ends back on its first point, which isn't repeated when the loop is regenerated
+			if points.len() > 2 {
+				points.pop();
+			}
+			Self::closed_spline_path(&points, tension)
+		} else {
+			Self::spline_path(&points, tension)
+		};
+	}
+}
+
+/// Solves the cyclic tridiagonal system with 4 on the diagonal and 1 beside it, including in the corners where it wraps around, for at least 3 unknowns.
+/// The Sherman-Morrison formula turns it into two plain tridiagonal systems, see https://en.wikipedia.org/wiki/Tridiagonal_matrix_algorithm#Variants
+fn solve_cyclic_tridiagonal(r: &[DVec2]) -> Vec<DVec2> {
+	let n = r.len();
+
+	// Solves the tridiagonal system with the given diagonal and 1 beside it with the Thomas algorithm
+	let solve = |b: &[f64], r: &[DVec2]| {
+		let mut c = vec![0.; n];
+		let mut d = vec![DVec2::ZERO; n];
+		c[0] = 1. / b[0];
+		d[0] = r[0] / b[0];
+		for i in 1..n {
+			let m = b[i] - c[i - 1];
+			c[i] = 1. / m;
+			d[i] = (r[i] - d[i - 1]) / m;
+		}
+		for i in (0..n - 1).rev() {
+			let next = c[i] * d[i + 1];
+			d[i] -= next;
 		}
+		d
+	};
+
+	// The corners are removed from the matrix by adjusting the first and last diagonal entries, then added back with a correction along `u`
+	let gamma = -4.;
+	let mut b = vec![4.; n];
+	b[0] -= gamma;
+	b[n - 1] -= 1. / gamma;
+	let mut u = vec![DVec2::ZERO; n];
+	u[0] = DVec2::splat(gamma);
+	u[n - 1] = DVec2::ONE;
+
+	let x = solve(&b, r);
+	let z = solve(&b, &u);
+	let factor = (x[0] + x[n - 1] / gamma) / (1. + z[0].x + z[n - 1].x / gamma);
+	x.iter().zip(z).map(|(x, z)| *x - factor * z).collect()
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	use std::f64::consts::{FRAC_PI_2, PI, TAU};
+
+	const SQUARE: [DVec2; 4] = [DVec2::new(0., 0.), DVec2::new(1., 0.), DVec2::new(1., 1.), DVec2::new(0., 1.)];
+
+	/// The points that the path's segments end on, leaving out where it closes
+	fn end_points(path: &BezPath) -> Vec<DVec2> {
+		path.elements()
+			.iter()
+			.filter_map(|element| match element {
+				PathEl::MoveTo(point) | PathEl::LineTo(point) | PathEl::QuadTo(_, point) | PathEl::CurveTo(_, _, point) => Some(DVec2::new(point.x, point.y)),
+				PathEl::ClosePath => None,
+			})
+			.collect()
+	}
+
+	fn curve_count(path: &BezPath) -> usize {
+		path.elements().iter().filter(|element| matches!(element, PathEl::CurveTo(..))).count()
+	}
+
+	fn is_finite(path: &BezPath) -> bool {
+		path.elements().iter().all(|element| {
+			let points = match *element {
+				PathEl::MoveTo(point) | PathEl::LineTo(point) => vec![point],
+				PathEl::QuadTo(a, b) => vec![a, b],
+				PathEl::CurveTo(a, b, c) => vec![a, b, c],
+				PathEl::ClosePath => vec![],
+			};
+			points.iter().all(|point| point.x.is_finite() && point.y.is_finite())
+		})
+	}
+
+	fn assert_near(actual: DVec2, expected: DVec2) {
+		assert!(actual.abs_diff_eq(expected, 1e-9), "Expected {} but got {}", expected, actual);
+	}
+
+	#[test]
+	fn polygon_without_corner_radius_is_straight() {
+		let path = Shape::rounded_polygon_path(&SQUARE, 0., DVec2::splat(100.));
+		assert_eq!(curve_count(&path), 0);
+		assert_eq!(end_points(&path), SQUARE.to_vec());
+		assert_eq!(path.elements().last(), Some(&PathEl::ClosePath));
+	}
+
+	#[test]
+	fn polygon_corner_radius_is_clamped_to_half_of_each_edge() {
+		// Far too large a radius for the 100 by 100 square, so each arc reaches from the middle of one edge to the middle of the next
+		let path = Shape::rounded_polygon_path(&SQUARE, 1000., DVec2::splat(100.));
+		assert_eq!(curve_count(&path), 4);
+		let points = end_points(&path);
+		assert_near(points[0], DVec2::new(0., 0.5));
+		assert_near(points[1], DVec2::new(0.5, 0.));
+
+		// The arcs are round where the polygon has its real proportions, so they reach twice as far across the unit square along its shorter side
+		let points = end_points(&Shape::rounded_polygon_path(&SQUARE, 10., DVec2::new(100., 50.)));
+		assert_near(points[0], DVec2::new(0., 0.2));
+		assert_near(points[1], DVec2::new(0.1, 0.));
+	}
+
+	#[test]
+	fn polygon_leaves_collinear_vertices_and_empty_edges_sharp() {
+		let vertices = [DVec2::new(0., 0.), DVec2::new(0.5, 0.), DVec2::new(1., 0.), DVec2::new(1., 1.), DVec2::new(1., 1.), DVec2::new(0., 1.)];
+		let path = Shape::rounded_polygon_path(&vertices, 10., DVec2::splat(100.));
+		assert!(is_finite(&path));
+
+		// Only the top left, top right, and bottom left corners are rounded, with the vertex along the top edge and the doubled bottom right one left as they are
+		assert_eq!(curve_count(&path), 3);
+		let points = end_points(&path);
+		assert!(points.contains(&DVec2::new(0.5, 0.)));
+		assert!(points.contains(&DVec2::new(1., 1.)));
+	}
+
+	#[test]
+	fn polygon_rounds_concave_vertices() {
+		let path = Shape::rounded_polygon_path(&Shape::star_vertices(5, 0.4, 0.), 5., DVec2::splat(100.));
+		assert!(is_finite(&path));
+		assert_eq!(curve_count(&path), 10);
+	}
+
+	#[test]
+	fn polygon_without_size_or_vertices() {
+		assert!(is_finite(&Shape::rounded_polygon_path(&SQUARE, 10., DVec2::ZERO)));
+
+		assert!(Shape::rounded_polygon_path(&[], 10., DVec2::ONE).elements().is_empty());
+		assert!(Shape::ngon_path(0).elements().is_empty());
+		assert!(Shape::star_path(0, 0.5).elements().is_empty());
+	}
+
+	#[test]
+	fn ngon_fits_unit_square() {
+		for sides in [3, 4, 5, 6, u8::MAX] {
+			let points = end_points(&Shape::ngon_path(sides));
+			assert_eq!(points.len(), sides as usize);
+
+			let min = points.iter().fold(DVec2::splat(f64::INFINITY), |min, point| min.min(*point));
+			let max = points.iter().fold(DVec2::splat(f64::NEG_INFINITY), |max, point| max.max(*point));
+			assert_near(min, DVec2::ZERO);
+			assert!(max.x <= 1. + 1e-9 && max.y <= 1. + 1e-9, "The polygon with {} sides reaches {}", sides, max);
+		}
+	}
+
+	#[test]
+	fn rectangle_without_corner_radii_is_straight() {
+		let path = Shape::rounded_rectangle_path([0.; 4], DVec2::splat(100.));
+		assert_eq!(curve_count(&path), 0);
+		assert_eq!(end_points(&path), SQUARE.to_vec());
+
+		// Negative radii are as good as none
+		assert_eq!(curve_count(&Shape::rounded_rectangle_path([-10.; 4], DVec2::splat(100.))), 0);
+	}
+
+	#[test]
+	fn rectangle_corner_radii_are_clamped_to_half_of_shorter_side() {
+		// At 100 by 50 no radius can be larger than 25
+		let path = Shape::rounded_rectangle_path([1000.; 4], DVec2::new(100., 50.));
+		assert_eq!(curve_count(&path), 4);
+		let points = end_points(&path);
+		assert_near(points[0], DVec2::new(0., 0.5));
+		assert_near(points[1], DVec2::new(0.25, 0.));
+	}
+
+	#[test]
+	fn rectangle_rounds_each_corner_independently() {
+		let path = Shape::rounded_rectangle_path([10., 0., 20., 0.], DVec2::splat(100.));
+		assert_eq!(curve_count(&path), 2);
+		let points = end_points(&path);
+		assert!(points.contains(&DVec2::new(1., 0.)));
+		assert!(points.contains(&DVec2::new(0., 1.)));
+	}
+
+	#[test]
+	fn rectangle_without_size() {
+		let path = Shape::rounded_rectangle_path([10.; 4], DVec2::ZERO);
+		assert!(is_finite(&path));
+		assert_eq!(curve_count(&path), 0);
+	}
+
+	#[test]
+	fn arc_without_sweep_or_with_full_turn_is_whole_ellipse() {
+		for sweep_angle in [0., TAU, -TAU, 10.] {
+			let path = Shape::arc_path(ArcType::PieSlice, 1., sweep_angle);
+			assert_eq!(path, Shape::ellipse_path(), "A sweep of {} should be the whole ellipse", sweep_angle);
+		}
+	}
+
+	#[test]
+	fn arc_types_connect_their_ends() {
+		let open = Shape::arc_path(ArcType::Open, 0., FRAC_PI_2);
+		let points = end_points(&open);
+		assert_near(points[0], DVec2::new(1., 0.5));
+		assert_near(*points.last().unwrap(), DVec2::new(0.5, 1.));
+		assert_ne!(open.elements().last(), Some(&PathEl::ClosePath));
+
+		let pie_slice = Shape::arc_path(ArcType::PieSlice, 0., FRAC_PI_2);
+		let points = end_points(&pie_slice);
+		assert_near(points[0], DVec2::splat(0.5));
+		assert_near(points[1], DVec2::new(1., 0.5));
+		assert_eq!(pie_slice.elements().last(), Some(&PathEl::ClosePath));
+
+		let chord = Shape::arc_path(ArcType::Chord, 0., FRAC_PI_2);
+		assert_near(end_points(&chord)[0], DVec2::new(1., 0.5));
+		assert_eq!(chord.elements().last(), Some(&PathEl::ClosePath));
+	}
+
+	#[test]
+	fn arc_with_negative_sweep_turns_counterclockwise() {
+		let points = end_points(&Shape::arc_path(ArcType::Open, PI, -FRAC_PI_2));
+		assert_near(points[0], DVec2::new(0., 0.5));
+		assert_near(*points.last().unwrap(), DVec2::new(0.5, 1.));
 	}
 }
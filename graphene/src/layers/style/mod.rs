@@ -1,9 +1,16 @@
 use crate::color::Color;
 use crate::consts::{LAYER_OUTLINE_STROKE_COLOR, LAYER_OUTLINE_STROKE_WIDTH};
 
+use glam::{DAffine2, DVec2};
 use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 
 const OPACITY_PRECISION: usize = 3;
+/// The most dash and gap lengths a stroke's dash pattern holds, so the stroke can stay `Copy`
+pub const MAX_DASH_LENGTHS: usize = 4;
+/// The most color stops a gradient holds, so the fill can stay `Copy`
+pub const MAX_GRADIENT_STOPS: usize = 4;
 
 fn format_opacity(name: &str, opacity: f32) -> String {
 	if (opacity - 1.).abs() > 10_f32.powi(-(OPACITY_PRECISION as i32)) {
@@ -26,27 +33,147 @@ impl Default for ViewMode {
 	}
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum GradientType {
+	Linear,
+	Radial,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Gradient {
+	pub gradient_type: GradientType,
+	/// Where a linear gradient starts, or the center of a radial one, in the space of the layer it fills
+	pub start: DVec2,
+	/// Where a linear gradient ends, or a point on the outer edge of a radial one
+	pub end: DVec2,
+	/// Offsets from 0 at the start to 1 at the end, each with its color, of which only the first `stop_count` are used
+	stops: [(f64, Color); MAX_GRADIENT_STOPS],
+	stop_count: u8,
+}
+
+impl Gradient {
+	/// Only the first `MAX_GRADIENT_STOPS` stops are kept.
+	pub fn new(gradient_type: GradientType, start: DVec2, end: DVec2, stops: &[(f64, Color)]) -> Self {
+		let count = stops.len().min(MAX_GRADIENT_STOPS);
+		let mut gradient = Self {
+			gradient_type,
+			start,
+			end,
+			stops: [(0., Color::BLACK); MAX_GRADIENT_STOPS],
+			stop_count: count as u8,
+		};
+		gradient.stops[..count].copy_from_slice(&stops[..count]);
+		gradient
+	}
+
+	/// The offsets of the colors along the gradient, each with its color
+	pub fn stops(&self) -> &[(f64, Color)] {
+		// A deserialized count isn't checked, so it could be out of bounds
+		&self.stops[..(self.stop_count as usize).min(MAX_GRADIENT_STOPS)]
+	}
+
+	/// The gradient with its start and end moved by `transform`, such as into the space a layer is rendered in
+	#[must_use]
+	pub fn transformed(mut self, transform: DAffine2) -> Self {
+		self.start = transform.transform_point2(self.start);
+		self.end = transform.transform_point2(self.end);
+		self
+	}
+
+	/// An ID for the SVG definition of the gradient, which is the same for gradients that look the same so their definitions can be shared
+	fn id(&self) -> String {
+		let mut hasher = DefaultHasher::new();
+		self.gradient_type.hash(&mut hasher);
+		for value in [self.start.x, self.start.y, self.end.x, self.end.y] {
+			value.to_bits().hash(&mut hasher);
+		}
+		for (offset, color) in self.stops() {
+			offset.to_bits().hash(&mut hasher);
+			color.rgba_hex().hash(&mut hasher);
+		}
+		format!("gradient-{:016x}", hasher.finish())
+	}
+
+	fn render_definition(&self) -> String {
+		let stops: String = self
+			.stops()
+			.iter()
+			.map(|(offset, color)| format!(r##"<stop offset="{}" stop-color="#{}"{} />"##, offset, color.rgb_hex(), format_opacity("stop", color.a())))
+			.collect();
+		match self.gradient_type {
+			GradientType::Linear => format!(
+				r#"<linearGradient id="{}" gradientUnits="userSpaceOnUse" x1="{}" y1="{}" x2="{}" y2="{}">{}</linearGradient>"#,
+				self.id(),
+				self.start.x,
+				self.start.y,
+				self.end.x,
+				self.end.y,
+				stops
+			),
+			GradientType::Radial => format!(
+				r#"<radialGradient id="{}" gradientUnits="userSpaceOnUse" cx="{}" cy="{}" r="{}">{}</radialGradient>"#,
+				self.id(),
+				self.start.x,
+				self.start.y,
+				self.start.distance(self.end),
+				stops
+			),
+		}
+	}
+}
+
 #[repr(C)]
 #[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
 pub struct Fill {
 	color: Color,
+	/// Fills with the gradient instead of the color when there is one
+	#[serde(default)]
+	gradient: Option<Gradient>,
 }
 
 impl Fill {
 	pub fn new(color: Color) -> Self {
-		Self { color }
+		Self { color, gradient: None }
+	}
+
+	/// A fill with a gradient, which counts as the color of its first stop wherever only a single color is used
+	pub fn new_gradient(gradient: Gradient) -> Self {
+		Self {
+			color: gradient.stops().first().map_or(Color::BLACK, |&(_, color)| color),
+			gradient: Some(gradient),
+		}
 	}
 
 	pub fn color(&self) -> Color {
 		self.color
 	}
 
+	pub fn gradient(&self) -> Option<Gradient> {
+		self.gradient
+	}
+
 	pub fn render(fill: Option<Fill>) -> String {
+		Self::render_transformed(fill, DAffine2::IDENTITY)
+	}
+
+	/// Renders the fill of a layer rendered in the space given by `transform`, which a gradient is moved into
+	fn render_transformed(fill: Option<Fill>, transform: DAffine2) -> String {
 		match fill {
+			Some(Fill { gradient: Some(gradient), .. }) => format!(r#" fill="url(#{})""#, gradient.transformed(transform).id()),
 			Some(c) => format!(r##" fill="#{}"{}"##, c.color.rgb_hex(), format_opacity("fill", c.color.a())),
 			None => r#" fill="none""#.to_string(),
 		}
 	}
+
+	/// Renders the fill as the paint of a stroke of the given width instead, which thickens the shape it's drawn around, for a layer rendered in the space given by `transform`
+	pub fn render_as_stroke(&self, width: f64, transform: DAffine2) -> String {
+		let paint = match self.gradient {
+			Some(gradient) => format!(r#" stroke="url(#{})""#, gradient.transformed(transform).id()),
+			None => format!(r##" stroke="#{}"{}"##, self.color.rgb_hex(), format_opacity("stroke", self.color.a())),
+		};
+		format!(r#" fill="none"{} stroke-width="{}" stroke-linejoin="round""#, paint, width)
+	}
 }
 
 #[repr(C)]
@@ -54,11 +181,38 @@ impl Fill {
 pub struct Stroke {
 	color: Color,
 	width: f32,
+	/// Alternating lengths of dashes and gaps, of which only the first `dash_count` are used
+	#[serde(default)]
+	dash_lengths: [f32; MAX_DASH_LENGTHS],
+	#[serde(default)]
+	dash_count: u8,
 }
 
 impl Stroke {
 	pub const fn new(color: Color, width: f32) -> Self {
-		Self { color, width }
+		Self {
+			color,
+			width,
+			dash_lengths: [0.; MAX_DASH_LENGTHS],
+			dash_count: 0,
+		}
+	}
+
+	/// Dashes the stroke with alternating lengths of dashes and gaps, or makes it solid if `lengths` is empty.
+	/// Only the first `MAX_DASH_LENGTHS` lengths are kept.
+	#[must_use]
+	pub fn with_dash_pattern(mut self, lengths: &[f32]) -> Self {
+		let count = lengths.len().min(MAX_DASH_LENGTHS);
+		self.dash_lengths = [0.; MAX_DASH_LENGTHS];
+		self.dash_lengths[..count].copy_from_slice(&lengths[..count]);
+		self.dash_count = count as u8;
+		self
+	}
+
+	/// The alternating lengths of dashes and gaps, which is empty for a solid stroke
+	pub fn dash_pattern(&self) -> &[f32] {
+		// A deserialized count may be out of range, so it's clamped rather than trusted
+		&self.dash_lengths[..(self.dash_count as usize).min(MAX_DASH_LENGTHS)]
 	}
 
 	pub fn color(&self) -> Color {
@@ -70,7 +224,12 @@ impl Stroke {
 	}
 
 	pub fn render(&self) -> String {
-		format!(r##" stroke="#{}"{} stroke-width="{}""##, self.color.rgb_hex(), format_opacity("stroke", self.color.a()), self.width)
+		let dash_array = match self.dash_pattern() {
+			[] => String::new(),
+			lengths => format!(r#" stroke-dasharray="{}""#, lengths.iter().map(|length| length.to_string()).collect::<Vec<_>>().join(" ")),
+		};
+		let opacity = format_opacity("stroke", self.color.a());
+		format!(r##" stroke="#{}"{} stroke-width="{}"{}"##, self.color.rgb_hex(), opacity, self.width, dash_array)
 	}
 }
 
@@ -110,10 +269,19 @@ impl PathStyle {
 		self.stroke = None;
 	}
 
-	pub fn render(&self, view_mode: ViewMode) -> String {
+	/// The definitions that the style's attributes refer to, such as a gradient, for a layer rendered in the space given by `transform`
+	pub fn render_definitions(&self, view_mode: ViewMode, transform: DAffine2) -> String {
+		match (view_mode, self.fill.and_then(|fill| fill.gradient)) {
+			(ViewMode::Outline, _) | (_, None) => String::new(),
+			(_, Some(gradient)) => format!("<defs>{}</defs>", gradient.transformed(transform).render_definition()),
+		}
+	}
+
+	/// The attributes of the style for a layer rendered in the space given by `transform`, which must match that of `render_definitions`
+	pub fn render(&self, view_mode: ViewMode, transform: DAffine2) -> String {
 		let fill_attribute = match (view_mode, self.fill) {
 			(ViewMode::Outline, _) => Fill::render(None),
-			(_, fill) => Fill::render(fill),
+			(_, fill) => Fill::render_transformed(fill, transform),
 		};
 		let stroke_attribute = match (view_mode, self.stroke) {
 			(ViewMode::Outline, _) => Stroke::new(LAYER_OUTLINE_STROKE_COLOR, LAYER_OUTLINE_STROKE_WIDTH).render(),
@@ -10,6 +10,59 @@ use std::fmt::Write;
 
 mod to_kurbo;
 
+/// The font family of text that hasn't been given another, which is drawn with the embedded Source Sans Pro
+pub const DEFAULT_FONT_FAMILY: &str = "sans-serif";
+
+/// The CSS font stack of the embedded font, matching the one the frontend loads it under, so text edited in place looks like the outlines it's drawn as
+const EMBEDDED_FONT_STACK: &str = "'Source Sans Pro', Arial, sans-serif";
+
+/// How far italic text in the embedded font leans over for each unit up, since the font has no italic outlines
+const SYNTHETIC_ITALIC_SLANT: f64 = 0.2;
+
+/// How much bold text in the embedded font is thickened by, as a fraction of its size, since the font has no bold outlines
+const SYNTHETIC_BOLD_WEIGHT: f64 = 0.04;
+
+fn default_font_family() -> String {
+	DEFAULT_FONT_FAMILY.to_string()
+}
+
+/// How each line of text is positioned within the width of the text box
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum TextAlignment {
+	Left,
+	Center,
+	Right,
+	/// Widens the spaces of each wrapped line to fill the box, leaving the last line of each paragraph aligned left
+	Justify,
+}
+
+impl Default for TextAlignment {
+	fn default() -> Self {
+		Self::Left
+	}
+}
+
+impl TextAlignment {
+	/// The equivalent value of the CSS `text-align` property
+	pub fn css_value(&self) -> &'static str {
+		match self {
+			Self::Left => "left",
+			Self::Center => "center",
+			Self::Right => "right",
+			Self::Justify => "justify",
+		}
+	}
+
+	/// The equivalent value of the SVG `text-anchor` attribute, which justified lines share with left-aligned ones
+	pub fn text_anchor(&self) -> &'static str {
+		match self {
+			Self::Left | Self::Justify => "start",
+			Self::Center => "middle",
+			Self::Right => "end",
+		}
+	}
+}
+
 fn glam_to_kurbo(transform: DAffine2) -> Affine {
 	Affine::new(transform.to_cols_array())
 }
@@ -20,6 +73,17 @@ pub struct Text {
 	pub style: style::PathStyle,
 	pub size: f64,
 	pub line_width: Option<f64>,
+	/// Any family other than the default is referenced by name in the SVG, for the viewer to supply.
+	/// Only the embedded font's metrics are available, so text in another family is wrapped and measured as if it were in the embedded font, and its bounds are approximate.
+	#[serde(default = "default_font_family")]
+	pub font_family: String,
+	#[serde(default)]
+	pub alignment: TextAlignment,
+	/// The embedded font is neither bold nor italic, so text in the default family is thickened and slanted instead, while other families are styled by the viewer
+	#[serde(default)]
+	pub bold: bool,
+	#[serde(default)]
+	pub italic: bool,
 	#[serde(skip)]
 	pub editable: bool,
 	#[serde(skip)]
@@ -43,7 +107,7 @@ impl LayerData for Text {
 		if self.editable {
 			let _ = write!(
 				svg,
-				r#"<foreignObject transform="matrix({})" style="color: {}"></foreignObject>"#,
+				r#"<foreignObject transform="matrix({})" style="color: {}; font-family: {}; font-size: {}px; font-weight: {}; font-style: {}; text-align: {}"></foreignObject>"#,
 				transform
 					.to_cols_array()
 					.iter()
@@ -53,19 +117,43 @@ impl LayerData for Text {
 				match self.style.fill() {
 					Some(fill) => format!("#{}", fill.color().rgba_hex()),
 					None => "gray".to_string(),
-				}
+				},
+				self.css_font_family(),
+				self.size,
+				self.font_weight(),
+				self.font_style(),
+				self.alignment.css_value()
 			);
+		} else if self.font_family != DEFAULT_FONT_FAMILY {
+			self.render_referenced_font(svg, transform, view_mode);
 		} else {
 			let mut path = self.to_bez_path();
 
 			path.apply_affine(glam_to_kurbo(transform));
 
-			let _ = write!(svg, r#"<path d="{}" {} />"#, path.to_svg(), self.style.render(view_mode));
+			// Bold text is thickened by stroking its outlines in its fill underneath them
+			let emboldening = match self.style.fill() {
+				Some(fill) if self.bold && view_mode != ViewMode::Outline => {
+					let width = self.size * SYNTHETIC_BOLD_WEIGHT * transform.matrix2.determinant().abs().sqrt();
+					format!(r#"<path d="{}"{} />"#, path.to_svg(), fill.render_as_stroke(width, transform))
+				}
+				_ => String::new(),
+			};
+
+			let _ = write!(
+				svg,
+				r#"{}{}<path d="{}" {} />"#,
+				self.style.render_definitions(view_mode, transform),
+				emboldening,
+				path.to_svg(),
+				self.style.render(view_mode, transform)
+			);
 		}
 		let _ = svg.write_str("</g>");
 	}
 
 	fn bounding_box(&self, transform: glam::DAffine2) -> Option<[DVec2; 2]> {
+		// Measured with the embedded font, which only approximates the bounds of text in a referenced font family
 		let mut path = self.bounding_box(&self.text).to_path(0.1);
 
 		if transform.matrix2 == DMat2::ZERO {
@@ -99,6 +187,10 @@ impl Text {
 			style,
 			size,
 			line_width: None,
+			font_family: default_font_family(),
+			alignment: TextAlignment::default(),
+			bold: false,
+			italic: false,
 			editable: false,
 			cached_path: None,
 		};
@@ -130,15 +222,85 @@ impl Text {
 
 	#[inline]
 	fn generate_path(&self) -> BezPath {
-		to_kurbo::to_kurbo(&self.text, Self::font_face(), self.size, self.line_width)
+		let slant = if self.italic { SYNTHETIC_ITALIC_SLANT } else { 0. };
+		to_kurbo::to_kurbo(&self.text, Self::font_face(), self.size, self.line_width, self.alignment, slant)
 	}
 
 	#[inline]
 	pub fn bounding_box(&self, text: &str) -> Rect {
-		let far = to_kurbo::bounding_box(text, Self::font_face(), self.size, self.line_width);
+		let far = to_kurbo::bounding_box(text, Self::font_face(), self.size, self.line_width, self.alignment);
 		Rect::new(0., 0., far.x, far.y)
 	}
 
+	/// Writes the text as SVG text in its font family, wrapped, spaced, and aligned line by line like the embedded font lays it out, since only the embedded font's outlines are available.
+	fn render_referenced_font(&self, svg: &mut String, transform: DAffine2, view_mode: ViewMode) {
+		let face = Self::font_face();
+		let ascender = face.ascender() as f64 / face.height() as f64 * self.size;
+
+		// The text is placed by its transform attribute, so a gradient is given in the text's own space
+		let _ = svg.write_str(&self.style.render_definitions(view_mode, DAffine2::IDENTITY));
+		let _ = write!(
+			svg,
+			r#"<text transform="matrix({})" font-family="{}" font-size="{}" font-weight="{}" font-style="{}"{}>"#,
+			transform.to_cols_array().iter().map(|entry| entry.to_string()).collect::<Vec<_>>().join(","),
+			escape_xml(&self.font_family),
+			self.size,
+			self.font_weight(),
+			self.font_style(),
+			self.style.render(view_mode, DAffine2::IDENTITY)
+		);
+		let (lines, box_width) = to_kurbo::wrapped_lines(&self.text, Self::font_face(), self.size, self.line_width, self.alignment);
+		let x = match self.alignment {
+			TextAlignment::Left | TextAlignment::Justify => 0.,
+			TextAlignment::Center => box_width / 2.,
+			TextAlignment::Right => box_width,
+		};
+		for (index, (line, stretched)) in lines.iter().enumerate() {
+			// Justified lines are spread across the box by the viewer, since the referenced font's glyphs aren't the same widths as the embedded font's
+			let length = if *stretched {
+				format!(r#" textLength="{}" lengthAdjust="spacing""#, box_width)
+			} else {
+				String::new()
+			};
+			let _ = write!(
+				svg,
+				r#"<tspan x="{}" y="{}" text-anchor="{}"{} xml:space="preserve">{}</tspan>"#,
+				x,
+				ascender + index as f64 * self.size,
+				self.alignment.text_anchor(),
+				length,
+				escape_xml(line.trim_end_matches(' '))
+			);
+		}
+		let _ = svg.write_str("</text>");
+	}
+
+	/// The value of the CSS `font-family` property, which names the embedded font for the default family instead of letting the viewer pick any sans-serif font
+	fn css_font_family(&self) -> String {
+		match self.font_family.as_str() {
+			DEFAULT_FONT_FAMILY => EMBEDDED_FONT_STACK.to_string(),
+			family => escape_xml(family),
+		}
+	}
+
+	/// The value of the CSS `font-weight` property and SVG attribute
+	fn font_weight(&self) -> &'static str {
+		if self.bold {
+			"bold"
+		} else {
+			"normal"
+		}
+	}
+
+	/// The value of the CSS `font-style` property and SVG attribute
+	fn font_style(&self) -> &'static str {
+		if self.italic {
+			"italic"
+		} else {
+			"normal"
+		}
+	}
+
 	pub fn regenerate_path(&mut self) {
 		self.cached_path = Some(self.generate_path());
 	}
@@ -147,4 +309,30 @@ impl Text {
 		self.text = text;
 		self.regenerate_path();
 	}
+
+	pub fn update_size(&mut self, size: f64) {
+		self.size = size;
+		self.regenerate_path();
+	}
+
+	pub fn update_alignment(&mut self, alignment: TextAlignment) {
+		self.alignment = alignment;
+		self.regenerate_path();
+	}
+
+	/// Sets whether the text is bold and italic, leaving either one as it is if it's `None`
+	pub fn update_style(&mut self, bold: Option<bool>, italic: Option<bool>) {
+		self.bold = bold.unwrap_or(self.bold);
+		self.italic = italic.unwrap_or(self.italic);
+		self.regenerate_path();
+	}
+
+	pub fn update_line_width(&mut self, line_width: Option<f64>) {
+		self.line_width = line_width;
+		self.regenerate_path();
+	}
+}
+
+fn escape_xml(text: &str) -> String {
+	text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
 }
@@ -3,36 +3,41 @@ use kurbo::{BezPath, Point, Vec2};
 use rustybuzz::{GlyphBuffer, UnicodeBuffer};
 use ttf_parser::{GlyphId, OutlineBuilder};
 
+use super::TextAlignment;
+
 struct Builder {
 	path: BezPath,
 	pos: Point,
 	offset: Vec2,
 	ascender: f64,
 	scale: f64,
+	/// How far each point of the glyphs leans over for every unit it's above the baseline
+	slant: f64,
+}
+
+impl Builder {
+	/// Places a point of a glyph's outline, which is in font units with the Y axis pointing up from the baseline
+	fn point(&self, x: f32, y: f32) -> Point {
+		let (x, y) = (x as f64, y as f64);
+		self.pos + self.offset + Vec2::new(x + y * self.slant, self.ascender - y) * self.scale
+	}
 }
 
 impl OutlineBuilder for Builder {
 	fn move_to(&mut self, x: f32, y: f32) {
-		self.path.move_to(self.pos + self.offset + Vec2::new(x as f64, self.ascender - y as f64) * self.scale);
+		self.path.move_to(self.point(x, y));
 	}
 
 	fn line_to(&mut self, x: f32, y: f32) {
-		self.path.line_to(self.pos + self.offset + Vec2::new(x as f64, self.ascender - y as f64) * self.scale);
+		self.path.line_to(self.point(x, y));
 	}
 
 	fn quad_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32) {
-		self.path.quad_to(
-			self.pos + self.offset + Vec2::new(x1 as f64, self.ascender - y1 as f64) * self.scale,
-			self.pos + self.offset + Vec2::new(x2 as f64, self.ascender - y2 as f64) * self.scale,
-		);
+		self.path.quad_to(self.point(x1, y1), self.point(x2, y2));
 	}
 
 	fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x3: f32, y3: f32) {
-		self.path.curve_to(
-			self.pos + self.offset + Vec2::new(x1 as f64, self.ascender - y1 as f64) * self.scale,
-			self.pos + self.offset + Vec2::new(x2 as f64, self.ascender - y2 as f64) * self.scale,
-			self.pos + self.offset + Vec2::new(x3 as f64, self.ascender - y3 as f64) * self.scale,
-		);
+		self.path.curve_to(self.point(x1, y1), self.point(x2, y2), self.point(x3, y3));
 	}
 
 	fn close(&mut self) {
@@ -67,77 +72,181 @@ fn wrap_word(line_width: Option<f64>, glyph_buffer: &GlyphBuffer, scale: f64, x_
 	false
 }
 
-pub fn to_kurbo(str: &str, buzz_face: rustybuzz::Face, font_size: f64, line_width: Option<f64>) -> BezPath {
-	let (scale, line_height, mut buffer) = font_properties(&buzz_face, font_size);
+/// A glyph placed on a line of text
+struct PlacedGlyph {
+	id: GlyphId,
+	/// How far along the line the glyph starts
+	x: f64,
+	offset: Vec2,
+	advance: f64,
+	/// Whether the glyph is a space, which a justified line widens to fill the box
+	space: bool,
+}
 
-	let mut builder = Builder {
-		path: BezPath::new(),
-		pos: Point::ZERO,
-		offset: Vec2::ZERO,
-		ascender: (buzz_face.ascender() as f64 / buzz_face.height() as f64) * font_size / scale,
-		scale,
-	};
+/// A line of text as it's laid out, before it's aligned within the box
+#[derive(Default)]
+struct Line {
+	glyphs: Vec<PlacedGlyph>,
+	/// The characters on the line, including any spaces it ends with
+	text: String,
+	/// How far the glyphs reach, including any spaces the line ends with
+	width: f64,
+	/// Whether the line ends its paragraph, which justified text leaves unstretched
+	ends_paragraph: bool,
+}
+
+impl Line {
+	/// How far the glyphs reach, leaving out any spaces the line ends with
+	fn trimmed_width(&self) -> f64 {
+		self.glyphs.iter().rev().find(|glyph| !glyph.space).map_or(0., |glyph| glyph.x + glyph.advance)
+	}
 
-	for line in str.split('\n') {
-		let length = line.split(' ').count();
-		for (index, word) in line.split(' ').enumerate() {
-			push_str(&mut buffer, word, index != length - 1);
+	/// The number of spaces between the glyphs of the line, leaving out any spaces it ends with
+	fn inner_spaces(&self) -> usize {
+		let trailing = self.glyphs.iter().rev().take_while(|glyph| glyph.space).count();
+		self.glyphs.iter().filter(|glyph| glyph.space).count() - trailing
+	}
 
-			let glyph_buffer = rustybuzz::shape(&buzz_face, &[], buffer);
+	/// Where the line starts within the box, and how much wider each of its spaces becomes
+	fn alignment(&self, box_width: f64, alignment: TextAlignment) -> (f64, f64) {
+		let room = box_width - self.trimmed_width();
+		match alignment {
+			TextAlignment::Left => (0., 0.),
+			TextAlignment::Center => (room / 2., 0.),
+			TextAlignment::Right => (room, 0.),
+			TextAlignment::Justify if self.ends_paragraph || self.inner_spaces() == 0 => (0., 0.),
+			TextAlignment::Justify => (0., room / self.inner_spaces() as f64),
+		}
+	}
+}
 
-			if wrap_word(line_width, &glyph_buffer, scale, builder.pos.x) {
-				builder.pos = Point::new(0., builder.pos.y + line_height);
+/// Breaks the text into lines at each newline and, given a line width, wherever a word or glyph would overflow it.
+fn layout(str: &str, buzz_face: &rustybuzz::Face, font_size: f64, line_width: Option<f64>) -> Vec<Line> {
+	let (scale, _, mut buffer) = font_properties(buzz_face, font_size);
+
+	let mut lines = Vec::new();
+	for paragraph in str.split('\n') {
+		let mut line = Line::default();
+
+		let length = paragraph.split(' ').count();
+		for (index, word) in paragraph.split(' ').enumerate() {
+			let trailing_space = index != length - 1;
+			push_str(&mut buffer, word, trailing_space);
+			let word = if trailing_space { format!("{} ", word) } else { word.to_string() };
+
+			let glyph_buffer = rustybuzz::shape(buzz_face, &[], buffer);
+
+			if wrap_word(line_width, &glyph_buffer, scale, line.width) {
+				lines.push(std::mem::take(&mut line));
 			}
 
-			for (glyph_position, glyph_info) in glyph_buffer.glyph_positions().iter().zip(glyph_buffer.glyph_infos()) {
+			// Each glyph stands for the characters from the start of its cluster up to the start of the following cluster
+			let clusters: Vec<usize> = glyph_buffer.glyph_infos().iter().map(|info| info.cluster as usize).collect();
+			let mut previous_cluster = None;
+			for ((glyph_position, glyph_info), &start) in glyph_buffer.glyph_positions().iter().zip(glyph_buffer.glyph_infos()).zip(&clusters) {
+				let advance = glyph_position.x_advance as f64 * scale;
 				if let Some(line_width) = line_width {
-					if builder.pos.x + (glyph_position.x_advance as f64 * builder.scale) >= line_width {
-						builder.pos = Point::new(0., builder.pos.y + line_height);
+					if line.width + advance >= line_width {
+						lines.push(std::mem::take(&mut line));
 					}
 				}
-				builder.offset = Vec2::new(glyph_position.x_offset as f64, glyph_position.y_offset as f64) * builder.scale;
-				buzz_face.outline_glyph(GlyphId(glyph_info.glyph_id as u16), &mut builder);
-				builder.pos += Vec2::new(glyph_position.x_advance as f64, glyph_position.y_advance as f64) * builder.scale;
+
+				// Several glyphs can make up one cluster, whose characters are only added once
+				let mut characters = "";
+				if previous_cluster != Some(start) {
+					let end = clusters.iter().copied().filter(|&cluster| cluster > start).min().unwrap_or(word.len());
+					characters = &word[start..end];
+					line.text.push_str(characters);
+					previous_cluster = Some(start);
+				}
+
+				line.glyphs.push(PlacedGlyph {
+					id: GlyphId(glyph_info.glyph_id as u16),
+					x: line.width,
+					offset: Vec2::new(glyph_position.x_offset as f64, glyph_position.y_offset as f64) * scale,
+					advance,
+					space: characters == " ",
+				});
+				line.width += advance;
 			}
 
 			buffer = glyph_buffer.clear();
 		}
-		builder.pos = Point::new(0., builder.pos.y + line_height);
+
+		line.ends_paragraph = true;
+		lines.push(line);
 	}
-	builder.path
+
+	lines
 }
 
-pub fn bounding_box(str: &str, buzz_face: rustybuzz::Face, font_size: f64, line_width: Option<f64>) -> DVec2 {
-	let (scale, line_height, mut buffer) = font_properties(&buzz_face, font_size);
+/// The width that lines are aligned within, which is the line width when wrapping or otherwise the width of the longest line.
+fn box_width(lines: &[Line], line_width: Option<f64>) -> f64 {
+	line_width.unwrap_or_else(|| lines.iter().map(Line::trimmed_width).fold(0., f64::max))
+}
 
-	let mut pos = DVec2::ZERO;
-	let mut bounds = DVec2::ZERO;
+/// Lays out the text and builds the outlines of its glyphs, leaning them over by `slant` to the side for each unit up.
+pub fn to_kurbo(str: &str, buzz_face: rustybuzz::Face, font_size: f64, line_width: Option<f64>, alignment: TextAlignment, slant: f64) -> BezPath {
+	let (scale, line_height, _) = font_properties(&buzz_face, font_size);
+	let lines = layout(str, &buzz_face, font_size, line_width);
+	let box_width = box_width(&lines, line_width);
 
-	for line in str.split('\n') {
-		let length = line.split(' ').count();
-		for (index, word) in line.split(' ').enumerate() {
-			push_str(&mut buffer, word, index != length - 1);
+	let mut builder = Builder {
+		path: BezPath::new(),
+		pos: Point::ZERO,
+		offset: Vec2::ZERO,
+		ascender: (buzz_face.ascender() as f64 / buzz_face.height() as f64) * font_size / scale,
+		scale,
+		slant,
+	};
 
-			let glyph_buffer = rustybuzz::shape(&buzz_face, &[], buffer);
+	for (index, line) in lines.iter().enumerate() {
+		let (start, extra_space) = line.alignment(box_width, alignment);
 
-			if wrap_word(line_width, &glyph_buffer, scale, pos.x) {
-				pos = DVec2::new(0., pos.y + line_height);
-			}
+		let mut spaces = 0;
+		for glyph in &line.glyphs {
+			builder.pos = Point::new(start + glyph.x + extra_space * spaces as f64, index as f64 * line_height);
+			builder.offset = glyph.offset;
+			buzz_face.outline_glyph(glyph.id, &mut builder);
 
-			for glyph_position in glyph_buffer.glyph_positions() {
-				if let Some(line_width) = line_width {
-					if pos.x + (glyph_position.x_advance as f64 * scale) >= line_width {
-						pos = DVec2::new(0., pos.y + line_height);
-					}
-				}
-				pos += DVec2::new(glyph_position.x_advance as f64, glyph_position.y_advance as f64) * scale;
+			if glyph.space {
+				spaces += 1;
 			}
-			bounds = bounds.max(pos + DVec2::new(0., line_height));
-
-			buffer = glyph_buffer.clear();
 		}
-		pos = DVec2::new(0., pos.y + line_height);
 	}
 
-	bounds
+	builder.path
+}
+
+pub fn bounding_box(str: &str, buzz_face: rustybuzz::Face, font_size: f64, line_width: Option<f64>, alignment: TextAlignment) -> DVec2 {
+	let (_, line_height, _) = font_properties(&buzz_face, font_size);
+	let lines = layout(str, &buzz_face, font_size, line_width);
+	let box_width = box_width(&lines, line_width);
+
+	let width = lines
+		.iter()
+		.map(|line| {
+			let (start, extra_space) = line.alignment(box_width, alignment);
+			start + line.width + extra_space * line.inner_spaces() as f64
+		})
+		.fold(0., f64::max);
+
+	DVec2::new(width, lines.len() as f64 * line_height)
+}
+
+/// Splits the text into the lines it's laid out on, breaking where it wraps as well as at each newline, so empty lines are kept.
+/// Each line comes with whether justifying stretches it across the box, and the lines come with the width of that box.
+pub fn wrapped_lines(str: &str, buzz_face: rustybuzz::Face, font_size: f64, line_width: Option<f64>, alignment: TextAlignment) -> (Vec<(String, bool)>, f64) {
+	let lines = layout(str, &buzz_face, font_size, line_width);
+	let box_width = box_width(&lines, line_width);
+
+	let lines = lines
+		.into_iter()
+		.map(|line| {
+			let stretched = line.alignment(box_width, alignment).1 != 0.;
+			(line.text, stretched)
+		})
+		.collect();
+
+	(lines, box_width)
 }
@@ -1,7 +1,9 @@
 use crate::color::Color;
 use crate::layers::blend_mode::BlendMode;
 use crate::layers::layer_info::Layer;
+use crate::layers::simple_shape::ArcType;
 use crate::layers::style;
+use crate::layers::text::TextAlignment;
 use crate::LayerId;
 
 use serde::{Deserialize, Serialize};
@@ -18,6 +20,15 @@ pub enum Operation {
 		transform: [f64; 6],
 		style: style::PathStyle,
 	},
+	AddArc {
+		path: Vec<LayerId>,
+		insert_index: isize,
+		transform: [f64; 6],
+		arc_type: ArcType,
+		start_angle: f64,
+		sweep_angle: f64,
+		style: style::PathStyle,
+	},
 	AddOverlayEllipse {
 		path: Vec<LayerId>,
 		transform: [f64; 6],
@@ -61,11 +72,33 @@ pub enum Operation {
 		path: Vec<LayerId>,
 		new_text: String,
 	},
+	SetTextAlignment {
+		path: Vec<LayerId>,
+		alignment: TextAlignment,
+	},
+	SetTextFont {
+		path: Vec<LayerId>,
+		font_family: String,
+	},
+	SetTextFontSize {
+		path: Vec<LayerId>,
+		font_size: f64,
+	},
+	SetTextLineWidth {
+		path: Vec<LayerId>,
+		line_width: Option<f64>,
+	},
+	SetTextStyle {
+		path: Vec<LayerId>,
+		bold: Option<bool>,
+		italic: Option<bool>,
+	},
 	AddPolyline {
 		path: Vec<LayerId>,
 		transform: [f64; 6],
 		insert_index: isize,
 		points: Vec<(f64, f64)>,
+		closed: bool,
 		style: style::PathStyle,
 	},
 	AddSpline {
@@ -73,6 +106,24 @@ pub enum Operation {
 		transform: [f64; 6],
 		insert_index: isize,
 		points: Vec<(f64, f64)>,
+		closed: bool,
+		tension: f64,
+		style: style::PathStyle,
+	},
+	AddVariableWidthStroke {
+		path: Vec<LayerId>,
+		transform: [f64; 6],
+		insert_index: isize,
+		points: Vec<(f64, f64)>,
+		widths: Vec<f64>,
+		style: style::PathStyle,
+	},
+	AddBezPath {
+		path: Vec<LayerId>,
+		transform: [f64; 6],
+		insert_index: isize,
+		bez_path: kurbo::BezPath,
+		closed: bool,
 		style: style::PathStyle,
 	},
 	AddNgon {
@@ -82,6 +133,14 @@ pub enum Operation {
 		sides: u8,
 		style: style::PathStyle,
 	},
+	AddStar {
+		path: Vec<LayerId>,
+		insert_index: isize,
+		transform: [f64; 6],
+		points: u8,
+		inner_radius: f64,
+		style: style::PathStyle,
+	},
 	AddOverlayShape {
 		path: Vec<LayerId>,
 		bez_path: kurbo::BezPath,
@@ -118,6 +177,27 @@ pub enum Operation {
 		path: Vec<LayerId>,
 		transform: [f64; 6],
 	},
+	SetEllipseArcAngles {
+		path: Vec<LayerId>,
+		start_angle: f64,
+		sweep_angle: f64,
+	},
+	SetPolygonCornerRadius {
+		path: Vec<LayerId>,
+		corner_radius: f64,
+	},
+	SetPolygonRotation {
+		path: Vec<LayerId>,
+		rotation: f64,
+	},
+	SetPolygonSides {
+		path: Vec<LayerId>,
+		sides: u8,
+	},
+	SetRectangleCornerRadii {
+		path: Vec<LayerId>,
+		corner_radii: [f64; 4],
+	},
 	SetShapePath {
 		path: Vec<LayerId>,
 		bez_path: kurbo::BezPath,
@@ -127,6 +207,14 @@ pub enum Operation {
 		bez_path: kurbo::BezPath,
 		transform: [f64; 6],
 	},
+	SetSplineTension {
+		path: Vec<LayerId>,
+		tension: f64,
+	},
+	SetStarInnerRadius {
+		path: Vec<LayerId>,
+		inner_radius: f64,
+	},
 	TransformLayerInScope {
 		path: Vec<LayerId>,
 		transform: [f64; 6],
@@ -152,6 +240,10 @@ pub enum Operation {
 		path: Vec<LayerId>,
 		name: String,
 	},
+	SetLayerAspectLock {
+		path: Vec<LayerId>,
+		aspect_locked: bool,
+	},
 	SetLayerBlendMode {
 		path: Vec<LayerId>,
 		blend_mode: BlendMode,
@@ -168,6 +260,10 @@ pub enum Operation {
 		path: Vec<LayerId>,
 		color: Color,
 	},
+	SetLayerGradient {
+		path: Vec<LayerId>,
+		gradient: style::Gradient,
+	},
 }
 
 impl Operation {